@@ -1,16 +1,23 @@
 use std::path::PathBuf;
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
 
 /// A text editor
 #[derive(Parser, Debug)]
 #[command(name = "ferrite", version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Args {
-    /// Path to files that will be opened
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Path to files that will be opened, `file:line:column` and `+line file` open a file at a position
     pub files: Vec<PathBuf>,
-    /// Line to open file on
+    /// Line to open files without a position on
     #[arg(long, short, default_value = "0")]
     pub line: u32,
+    /// Open the files read-only
+    #[arg(long)]
+    pub read_only: bool,
     /// Language
     #[arg(long = "lang")]
     pub language: Option<String>,
@@ -23,6 +30,9 @@ pub struct Args {
     /// Type UI to use
     #[arg(long, default_value = "auto")]
     pub ui: Option<Ui>,
+    /// Terminal backend used by the tui, overrides `term_backend` in the config
+    #[arg(long)]
+    pub term_backend: Option<TermBackend>,
     /// Tail log file
     #[arg(long)]
     pub log: bool,
@@ -32,12 +42,97 @@ pub struct Args {
     /// Overwrite existing config
     #[arg(long)]
     pub overwrite: bool,
+    /// Write the text read from stdin to stdout on exit so the editor can be used in a pipeline,
+    /// only used when stdout is not a terminal
+    #[arg(long)]
+    pub pipe: bool,
     /// Wait for editor to close
     #[arg(short, long)]
     pub wait: bool,
+    /// Open the files in the running instance, `file:line:column` opens a file at a position.
+    /// Starts a new instance if none is running. Only supported on unix.
+    #[arg(long)]
+    pub remote: bool,
     /// Profile
     #[arg(long)]
     pub profile: bool,
+    /// Write every input to a file so bugs can be reproduced with `--replay`
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record_input: Option<PathBuf>,
+    /// Replay the inputs written by `--record-input`, should be given the same files as the recording
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+    /// Replay without a ui as fast as possible and exit once the inputs have run out
+    #[arg(long, requires = "replay")]
+    pub headless: bool,
+}
+
+impl Args {
+    /// Returns the files with their positions
+    pub fn file_args(&self) -> Vec<FileArg> {
+        let mut file_args = Vec::new();
+        let mut pending_line = None;
+        for file in &self.files {
+            let text = file.to_string_lossy();
+            if let Some(line) = text.strip_prefix('+').and_then(|line| line.parse().ok()) {
+                pending_line = Some(line);
+                continue;
+            }
+            // Files whose name looks like a position are opened as they are
+            let (path, line, column) = match file.exists() {
+                true => (file.clone(), None, None),
+                false => split_position(&text),
+            };
+            file_args.push(FileArg {
+                path,
+                line: line
+                    .or(pending_line.take())
+                    .or((self.line > 0).then_some(self.line as usize)),
+                column,
+            });
+        }
+        file_args
+    }
+}
+
+/// A file given on the command line, `line` and `column` are one based
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileArg {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Splits `path:line:column` and `path:line` into their parts
+fn split_position(text: &str) -> (PathBuf, Option<usize>, Option<usize>) {
+    let Some((rest, last)) = text.rsplit_once(':') else {
+        return (text.into(), None, None);
+    };
+    let Ok(last) = last.parse() else {
+        return (text.into(), None, None);
+    };
+    match rest.rsplit_once(':') {
+        Some((path, line)) => match line.parse() {
+            Ok(line) => (path.into(), Some(line), Some(last)),
+            Err(_) => (rest.into(), Some(last), None),
+        },
+        None => (rest.into(), Some(last), None),
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run palette commands on files without a ui and exit
+    Exec {
+        /// Palette command to run on every file, can be given multiple times
+        #[arg(long = "cmd", value_name = "COMMAND")]
+        commands: Vec<String>,
+        /// File with one palette command per line, run after the `--cmd` commands
+        #[arg(long)]
+        script: Option<PathBuf>,
+        /// Files the commands are run on
+        files: Vec<PathBuf>,
+    },
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -47,6 +142,18 @@ pub enum Ui {
     Auto,
 }
 
+/// How the tui takes over the terminal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TermBackend {
+    /// Alternate screen with mouse capture, bracketed paste and keyboard enhancements
+    #[default]
+    Crossterm,
+    /// Draws on the main screen and only relies on raw mode,
+    /// for serial consoles and terminals where the crossterm defaults break
+    Plain,
+}
+
 pub fn parse() -> Args {
     Args::parse()
 }
@@ -0,0 +1,226 @@
+use std::{mem, ops::Range};
+
+use ropey::Rope;
+
+/// Incomplete sequences longer than this are dropped instead of waiting for more input
+const MAX_SEQUENCE_LEN: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiColor {
+    /// A color of the xterm 256 color palette, the first 16 colors are taken from the theme
+    Indexed(u8),
+    Rgb([u8; 3]),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnsiStyle {
+    pub fg: Option<AnsiColor>,
+    pub bg: Option<AnsiColor>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl AnsiStyle {
+    /// Applies the parameters of a SGR sequence such as `1;31`
+    fn apply(&mut self, params: &str) {
+        let mut params = params
+            .split([';', ':'])
+            .map(|param| param.parse::<u16>().unwrap_or(0));
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => self.bold = false,
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.fg = Some(AnsiColor::Indexed((param - 30) as u8)),
+                38 => self.fg = extended_color(&mut params),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(AnsiColor::Indexed((param - 40) as u8)),
+                48 => self.bg = extended_color(&mut params),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some(AnsiColor::Indexed((param - 90 + 8) as u8)),
+                100..=107 => self.bg = Some(AnsiColor::Indexed((param - 100 + 8) as u8)),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Parses the rest of `38;5;n` and `38;2;r;g;b`
+fn extended_color(params: &mut impl Iterator<Item = u16>) -> Option<AnsiColor> {
+    match params.next()? {
+        5 => Some(AnsiColor::Indexed(params.next()?.min(255) as u8)),
+        2 => {
+            let mut channel = || params.next().map(|value| value.min(255) as u8);
+            Some(AnsiColor::Rgb([channel()?, channel()?, channel()?]))
+        }
+        _ => None,
+    }
+}
+
+/// Text styled by escape sequences, spans never cross line endings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsiSpan {
+    pub range: Range<usize>,
+    pub style: AnsiStyle,
+}
+
+/// Strips escape sequences from text and keeps the styles set by SGR sequences as spans.
+/// The state is kept between calls so streamed output can be parsed in chunks.
+#[derive(Debug, Default)]
+pub struct AnsiParser {
+    style: AnsiStyle,
+    /// Start of an escape sequence that was cut off at the end of the last chunk
+    pending: String,
+}
+
+impl AnsiParser {
+    /// Returns `text` without escape sequences and its styled spans offset by `offset`
+    pub fn parse(&mut self, text: &str, offset: usize) -> (String, Vec<AnsiSpan>) {
+        let input = mem::take(&mut self.pending) + text;
+        let mut out = String::with_capacity(input.len());
+        let mut spans = Vec::new();
+
+        let mut rest = input.as_str();
+        while let Some(esc) = rest.find('\x1b') {
+            self.push_text(&rest[..esc], offset, &mut out, &mut spans);
+            rest = &rest[esc..];
+            match sequence_len(rest) {
+                Some(len) => {
+                    if let Some(params) = rest[..len]
+                        .strip_prefix("\x1b[")
+                        .and_then(|sequence| sequence.strip_suffix('m'))
+                    {
+                        self.style.apply(params);
+                    }
+                    rest = &rest[len..];
+                }
+                None if rest.len() > MAX_SEQUENCE_LEN => rest = &rest[1..],
+                None => {
+                    self.pending = rest.to_string();
+                    rest = "";
+                }
+            }
+        }
+        self.push_text(rest, offset, &mut out, &mut spans);
+
+        (out, spans)
+    }
+
+    fn push_text(&self, text: &str, offset: usize, out: &mut String, spans: &mut Vec<AnsiSpan>) {
+        if self.style != AnsiStyle::default() {
+            let mut start = offset + out.len();
+            for line in text.split_inclusive('\n') {
+                let len = line.trim_end_matches(['\n', '\r']).len();
+                match spans.last_mut() {
+                    _ if len == 0 => (),
+                    // Sequences that do not change the style split the text
+                    Some(last) if last.range.end == start && last.style == self.style => {
+                        last.range.end += len;
+                    }
+                    _ => spans.push(AnsiSpan {
+                        range: start..start + len,
+                        style: self.style,
+                    }),
+                }
+                start += line.len();
+            }
+        }
+        out.push_str(text);
+    }
+}
+
+/// A buffer shown with its escape sequences rendered, the raw text is restored when turned off
+#[derive(Debug, Clone)]
+pub struct AnsiRender {
+    pub raw: Rope,
+    pub spans: Vec<AnsiSpan>,
+    /// If the buffer was read-only before, rendered buffers cannot be edited
+    pub read_only: bool,
+}
+
+/// Strips all escape sequences from `text`
+pub fn parse(text: &str) -> (String, Vec<AnsiSpan>) {
+    AnsiParser::default().parse(text, 0)
+}
+
+/// Length of the escape sequence at the start of `text`, `None` if it is incomplete
+fn sequence_len(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    match bytes.get(1)? {
+        // Control sequences end with a byte in the range `@` to `~`, any other byte that is not
+        // a parameter such as a newline aborts the sequence so the text after it is kept
+        b'[' => bytes[2..]
+            .iter()
+            .position(|b| !(0x20..=0x3f).contains(b))
+            .map(|i| match bytes[i + 2] {
+                0x40..=0x7e => i + 3,
+                _ => i + 2,
+            }),
+        // Operating system commands such as hyperlinks end with BEL or ESC \
+        b']' => bytes[2..].iter().enumerate().find_map(|(i, b)| match b {
+            0x07 => Some(i + 3),
+            0x1b if bytes.get(i + 3) == Some(&b'\\') => Some(i + 4),
+            _ => None,
+        }),
+        _ => Some(1 + text[1..].chars().next()?.len_utf8()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sgr() {
+        let (text, spans) = parse("\x1b[1;31merror\x1b[0m: \x1b[38;5;208mx\ny\x1b[m\x1b[K done");
+        assert_eq!(text, "error: x\ny done");
+        let red = AnsiStyle {
+            fg: Some(AnsiColor::Indexed(1)),
+            bold: true,
+            ..Default::default()
+        };
+        let orange = AnsiStyle {
+            fg: Some(AnsiColor::Indexed(208)),
+            ..Default::default()
+        };
+        assert_eq!(
+            spans,
+            vec![
+                AnsiSpan {
+                    range: 0..5,
+                    style: red
+                },
+                AnsiSpan {
+                    range: 7..8,
+                    style: orange
+                },
+                AnsiSpan {
+                    range: 9..10,
+                    style: orange
+                },
+            ]
+        );
+
+        let mut parser = AnsiParser::default();
+        assert_eq!(parser.parse("a\x1b[3", 0), ("a".to_string(), Vec::new()));
+        let (text, spans) = parser.parse("2mb\x1b]8;;https://example.com\x1b\\c", 1);
+        assert_eq!(text, "bc");
+        assert_eq!(spans[0].range, 1..3);
+        assert_eq!(spans[0].style.fg, Some(AnsiColor::Indexed(2)));
+    }
+
+    #[test]
+    fn unterminated_csi() {
+        assert_eq!(parse("a\x1b[31\nb").0, "a\nb");
+
+        let mut parser = AnsiParser::default();
+        assert_eq!(parser.parse("a\x1b[3", 0).0, "a");
+        assert_eq!(parser.parse("\nb\x1b[1\x1b[32mc", 1).0, "\nbc");
+        assert_eq!(parser.style.fg, Some(AnsiColor::Indexed(2)));
+    }
+}
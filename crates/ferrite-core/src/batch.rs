@@ -0,0 +1,145 @@
+use std::{path::PathBuf, sync::mpsc, thread, time::Duration};
+
+use anyhow::Result;
+use ferrite_cli::Args;
+
+use crate::{
+    engine::Engine,
+    event_loop_proxy::{EventLoopControlFlow, EventLoopProxy, UserEvent},
+    logger::LogMessage,
+    palette::PaletteState,
+    tr,
+};
+
+/// What running a command on a file resulted in
+#[derive(Debug)]
+pub struct Outcome {
+    pub path: PathBuf,
+    /// `None` if the file could not be opened
+    pub command: Option<String>,
+    /// The message the command showed in the palette or the error it showed
+    pub result: Result<Option<String>, String>,
+}
+
+/// Sends the events of a headless engine to a channel
+#[derive(Clone)]
+pub(crate) struct BatchProxy(pub(crate) mpsc::Sender<UserEvent>);
+
+impl EventLoopProxy for BatchProxy {
+    fn send(&self, event: UserEvent) {
+        let _ = self.0.send(event);
+    }
+
+    fn request_render(&self) {}
+
+    fn dup(&self) -> Box<dyn EventLoopProxy> {
+        Box::new(self.clone())
+    }
+}
+
+/// Parses a script for `ferrite exec --script`, one palette command per line.
+/// Empty lines and lines starting with `#` are skipped.
+pub fn parse_script(script: &str) -> Vec<String> {
+    script
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Opens the files one at a time in a headless engine and runs the commands on each of them.
+/// Changes that are not saved by one of the commands are discarded.
+pub fn run(
+    args: &Args,
+    files: &[PathBuf],
+    commands: &[String],
+    recv: mpsc::Receiver<LogMessage>,
+) -> Result<Vec<Outcome>> {
+    let (tx, rx) = mpsc::channel();
+    let mut engine = Engine::new_headless(args, Box::new(BatchProxy(tx)), recv)?;
+    let mut control_flow = EventLoopControlFlow::Poll;
+
+    // Running the commands with a broken config could do something the user did not ask for
+    if let PaletteState::Error(err) = engine.palette.state() {
+        anyhow::bail!(err.clone());
+    }
+
+    let mut outcomes = Vec::new();
+    for path in files {
+        if !engine.open_file(path) {
+            outcomes.push(Outcome {
+                path: path.clone(),
+                command: None,
+                result: Err(take_result(&mut engine, &path.to_string_lossy())
+                    .err()
+                    .unwrap_or_default()),
+            });
+            continue;
+        }
+        wait_for_jobs(&mut engine, &rx, &mut control_flow);
+        engine.palette.reset();
+
+        for command in commands {
+            engine.handle_app_event(
+                UserEvent::PaletteEvent {
+                    mode: String::from("command"),
+                    content: command.clone(),
+                },
+                &mut control_flow,
+            );
+            wait_for_jobs(&mut engine, &rx, &mut control_flow);
+            outcomes.push(Outcome {
+                path: path.clone(),
+                command: Some(command.clone()),
+                result: take_result(&mut engine, command),
+            });
+        }
+        engine.force_close_current_buffer();
+    }
+
+    Ok(outcomes)
+}
+
+fn wait_for_jobs(
+    engine: &mut Engine,
+    rx: &mpsc::Receiver<UserEvent>,
+    control_flow: &mut EventLoopControlFlow,
+) {
+    loop {
+        while let Ok(event) = rx.try_recv() {
+            engine.handle_app_event(event, control_flow);
+        }
+        engine.do_polling(control_flow);
+        if !engine.has_running_jobs() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Returns what the last command left in the palette and clears it
+fn take_result(engine: &mut Engine, command: &str) -> Result<Option<String>, String> {
+    let result = match engine.palette.state() {
+        PaletteState::Error(err) => Err(err.trim_end().to_string()),
+        PaletteState::Prompt { prompt, .. } => Err(prompt.clone()),
+        PaletteState::Input { focused: true, .. } => {
+            Err(tr!("misc.needs_input", command = command))
+        }
+        PaletteState::Message(msg) => Ok(Some(msg.clone())),
+        _ => Ok(None),
+    };
+    engine.palette.reset();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script() {
+        let script = "# format everything\nformat\n\n  save  \n";
+        assert_eq!(parse_script(script), ["format", "save"]);
+    }
+}
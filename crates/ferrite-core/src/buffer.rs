@@ -3,101 +3,69 @@ use std::{
     cmp, fs, io,
     ops::Range,
     path::{Path, PathBuf},
-    sync::OnceLock,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
 use encoding_rs::Encoding;
+use ferrite_text::history::History;
 use ferrite_utility::{
     graphemes::RopeGraphemeExt,
-    line_ending::{rope_end_without_line_ending, LineEnding, DEFAULT_LINE_ENDING},
+    line_ending::{LineEnding, DEFAULT_LINE_ENDING},
     point::Point,
     vec1::Vec1,
 };
 use ropey::{Rope, RopeSlice};
-use search::search_rope;
-use serde::{Deserialize, Serialize};
+use search::{search_rope, SearchMatch, SearchOptions};
 use slotmap::{Key, SecondaryMap, SlotMap};
 
-use self::{error::BufferError, history::History, search::BufferSearcher};
+use self::{
+    change::BufferChange, conflict::Conflict, error::BufferError, search::BufferSearcher,
+    tail::Tail, write::DiskState,
+};
 use super::{
     indent::Indentation,
-    language::{get_language_from_path, syntax::Syntax},
+    language::{
+        auto_indent::{self, NewlineIndent},
+        get_language_from_path,
+        syntax::Syntax,
+    },
 };
 use crate::{
-    clipboard, cmd::LineMoveDir, event_loop_proxy::EventLoopProxy,
-    language::detect::detect_language, workspace::BufferData,
+    clipboard,
+    diff::{diff_word_changes, WordChange},
+    encryption::Encryption,
+    event_loop_proxy::{EventLoopProxy, NoopProxy},
+    job_manager::JobManager,
+    language::detect::detect_language,
+    scratch,
+    workspace::BufferData,
 };
 
+pub use ferrite_text::{change, Cursor, ViewId};
+
+pub mod abbreviation;
+pub mod bookmark;
 pub mod case;
+pub mod conflict;
 pub mod encoding;
 pub mod error;
 mod format;
-mod history;
 pub mod input;
+pub mod line_endings;
+pub mod lines;
+pub mod number;
+pub mod outline;
 pub mod read;
 pub mod search;
+pub mod tail;
+pub mod unicode;
 pub mod write;
 
 #[cfg(test)]
 pub mod buffer_tests;
-
-static PROXY: OnceLock<Box<dyn EventLoopProxy>> = OnceLock::new();
-
-pub fn set_buffer_proxy(proxy: Box<dyn EventLoopProxy>) {
-    if PROXY.set(proxy).is_err() {
-        tracing::error!("Error attempted to set buffer proxy twice");
-    }
-}
-
-fn get_buffer_proxy() -> Box<dyn EventLoopProxy> {
-    PROXY.get().unwrap().dup()
-}
-
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-pub struct Cursor {
-    pub position: usize,
-    pub anchor: usize,
-    pub affinity: usize,
-}
-
-impl Cursor {
-    pub fn has_selection(&self) -> bool {
-        self.position != self.anchor
-    }
-
-    pub fn intersects(&self, other: Cursor) -> bool {
-        let start1 = self.start();
-        let end1 = self.end();
-        let start2 = other.start();
-        let end2 = other.end();
-        !(start1 > end2 || end1 < start2)
-    }
-
-    pub fn coalesce(self, other: Cursor) -> Self {
-        if self.position >= self.anchor {
-            Self {
-                position: self.position.max(other.position),
-                anchor: self.anchor.min(other.anchor),
-                affinity: self.affinity,
-            }
-        } else {
-            Self {
-                position: self.position.min(other.position),
-                anchor: self.anchor.max(other.anchor),
-                affinity: self.affinity,
-            }
-        }
-    }
-
-    pub fn start(&self) -> usize {
-        self.position.min(self.anchor)
-    }
-
-    pub fn end(&self) -> usize {
-        self.position.max(self.anchor)
-    }
-}
+#[cfg(test)]
+pub mod test_support;
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Selection {
@@ -186,54 +154,79 @@ impl View {
     }
 }
 
-slotmap::new_key_type! {
-    pub struct ViewId;
-}
-
 pub struct Buffer {
     rope: Rope,
     pub views: SlotMap<ViewId, View>,
     file: Option<PathBuf>,
+    /// The symlink the file was opened through
+    link: Option<PathBuf>,
     name: String,
     dirty: bool,
     pub read_only: bool,
     pub read_only_file: bool,
     last_edit: Instant,
+    /// State of the file on disk when it was last loaded or saved
+    disk_state: Option<DiskState>,
+    encryption: Option<Encryption>,
     pub line_ending: LineEnding,
     pub encoding: &'static Encoding,
+    /// The file starts with a byte order mark, it is written again when saving
+    pub bom: bool,
     pub indent: Indentation,
     last_interact: Instant,
     last_used_view: ViewId,
     // syntax highlight
     syntax: Option<Syntax>,
+    /// Woken when the syntax has been highlighted, a no-op until one is set with `set_proxy`
+    proxy: Box<dyn EventLoopProxy>,
     history: History,
+    revision: u64,
+    change_listeners: Vec<cb::Sender<BufferChange>>,
+    /// Byte offsets of bookmarked lines, kept sorted
+    bookmarks: Vec<usize>,
+    tail: Option<Tail>,
+    /// Conflicts found at a revision
+    conflicts: Option<(u64, Arc<[Conflict]>)>,
+    /// Word changes of the lines of a diff at a revision
+    word_changes: Option<(u64, Range<usize>, Arc<[WordChange]>)>,
 }
 
 impl Clone for Buffer {
     fn clone(&self) -> Self {
         let rope = self.rope.clone();
-        let mut syntax = Syntax::new(get_buffer_proxy());
+        let mut syntax = Syntax::new(self.proxy.dup());
         if let Err(err) = syntax.set_language(self.language_name()) {
             tracing::error!("Error setting language: {err}");
         }
-        syntax.update_text(rope.clone());
+        syntax.update_text(rope.clone(), self.revision);
 
         Self {
             rope,
             file: self.file.clone(),
+            link: self.link.clone(),
             name: self.name.clone(),
             dirty: self.dirty,
             read_only: self.read_only,
             read_only_file: self.read_only_file,
             last_edit: self.last_edit,
+            disk_state: self.disk_state,
+            encryption: self.encryption.clone(),
             line_ending: self.line_ending,
             encoding: self.encoding,
+            bom: self.bom,
             indent: self.indent,
             syntax: Some(syntax),
+            proxy: self.proxy.dup(),
             history: self.history.clone(),
             last_interact: self.last_interact,
             last_used_view: self.last_used_view,
             views: self.views.clone(),
+            revision: self.revision,
+            change_listeners: Vec::new(),
+            bookmarks: self.bookmarks.clone(),
+            tail: self.tail,
+            conflicts: self.conflicts.clone(),
+            word_changes: self.word_changes.clone(),
         }
     }
 }
@@ -243,19 +236,30 @@ impl Default for Buffer {
         Self {
             rope: Rope::new(),
             file: None,
+            link: None,
             name: String::from("[scratch]"),
             encoding: encoding_rs::UTF_8,
+            bom: false,
             indent: Indentation::default(),
             dirty: false,
             last_edit: Instant::now(),
+            disk_state: None,
+            encryption: None,
             read_only: false,
             read_only_file: false,
             line_ending: DEFAULT_LINE_ENDING,
             syntax: None,
+            proxy: Box::new(NoopProxy),
             history: History::default(),
             last_interact: Instant::now(),
             last_used_view: ViewId::null(),
             views: SlotMap::with_key(),
+            revision: 0,
+            change_listeners: Vec::new(),
+            bookmarks: Vec::new(),
+            tail: None,
+            conflicts: None,
+            word_changes: None,
         }
     }
 }
@@ -290,12 +294,12 @@ impl Buffer {
             cwd.join(path)
         };
 
-        let mut syntax = Syntax::new(get_buffer_proxy());
+        let mut syntax = Syntax::new(Box::new(NoopProxy));
         if let Some(language) = get_language_from_path(&path) {
             if let Err(err) = syntax.set_language(language) {
                 tracing::error!("Error setting language: {err}");
             }
-            syntax.update_text(Rope::new());
+            syntax.update_text(Rope::new(), 0);
         }
 
         let Some(name) = path.file_name() else {
@@ -314,12 +318,12 @@ impl Buffer {
     pub fn with_name(name: impl Into<String>) -> Self {
         let name = name.into();
         let path = Path::new(&name);
-        let mut syntax = Syntax::new(get_buffer_proxy());
+        let mut syntax = Syntax::new(Box::new(NoopProxy));
         if let Some(language) = get_language_from_path(path) {
             if let Err(err) = syntax.set_language(language) {
                 tracing::error!("Error setting language: {err}");
             }
-            syntax.update_text(Rope::new());
+            syntax.update_text(Rope::new(), 0);
         }
 
         Self {
@@ -331,6 +335,28 @@ impl Buffer {
 
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self, io::Error> {
         let path = path.as_ref();
+        let (encoding, bom, rope) = read::read_with_bom(fs::File::open(path)?)?;
+        Self::from_rope_and_path(path, encoding, bom, rope)
+    }
+
+    /// Opens a file that is decrypted with `encryption`, saving the buffer encrypts it again
+    pub fn from_encrypted_file(
+        path: impl AsRef<Path>,
+        encryption: Encryption,
+    ) -> Result<Self, io::Error> {
+        let path = path.as_ref();
+        let (encoding, bom, rope) = read::read_with_bom(&encryption.read_file(path)?[..])?;
+        let mut buffer = Self::from_rope_and_path(path, encoding, bom, rope)?;
+        buffer.encryption = Some(encryption);
+        Ok(buffer)
+    }
+
+    fn from_rope_and_path(
+        path: &Path,
+        encoding: &'static Encoding,
+        bom: bool,
+        rope: Rope,
+    ) -> Result<Self, io::Error> {
         #[cfg(not(unix))]
         let read_only_file = {
             let metadata = std::fs::metadata(path)?;
@@ -338,24 +364,28 @@ impl Buffer {
         };
         #[cfg(unix)]
         let read_only_file = rustix::fs::access(path, rustix::fs::Access::WRITE_OK).is_err();
-        let (encoding, rope) = read::read_from_file(path)?;
 
-        let mut syntax = Syntax::new(get_buffer_proxy());
+        let mut syntax = Syntax::new(Box::new(NoopProxy));
         if let Some(language) = get_language_from_path(path) {
             if let Err(err) = syntax.set_language(language) {
                 tracing::error!("Error setting language: {err}");
             }
-            syntax.update_text(rope.clone());
+            syntax.update_text(rope.clone(), 0);
         }
 
         if let Some(language) = detect_language(syntax.get_language_name(), rope.clone()) {
             if let Err(err) = syntax.set_language(language) {
                 tracing::error!("Error setting language: {err}");
             }
-            syntax.update_text(rope.clone());
+            syntax.update_text(rope.clone(), 0);
         }
 
         let name = path.file_name().unwrap().to_string_lossy().into();
+        let link = match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_symlink() => Some(std::env::current_dir()?.join(path)),
+            _ => None,
+        };
+        let line_ending = line_endings::detect_line_ending(&rope).unwrap_or(DEFAULT_LINE_ENDING);
 
         Ok(Self {
             indent: Indentation::detect_indent_rope(rope.slice(..)),
@@ -363,28 +393,35 @@ impl Buffer {
             read_only_file,
             name,
             file: Some(dunce::canonicalize(path)?),
+            link,
+            disk_state: DiskState::read(path).ok(),
+            line_ending,
             encoding,
+            bom,
             syntax: Some(syntax),
             ..Default::default()
         })
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
-        let (encoding, rope) = read::read(bytes)?;
-        let mut syntax = Syntax::new(get_buffer_proxy());
+        let (encoding, bom, rope) = read::read_with_bom(bytes)?;
+        let mut syntax = Syntax::new(Box::new(NoopProxy));
 
         if let Some(language) = detect_language(None, rope.clone()) {
             if let Err(err) = syntax.set_language(language) {
                 tracing::error!("Error setting language: {err}");
             }
-            syntax.update_text(rope.clone());
+            syntax.update_text(rope.clone(), 0);
         }
+        let line_ending = line_endings::detect_line_ending(&rope).unwrap_or(DEFAULT_LINE_ENDING);
 
         Ok(Self {
             indent: Indentation::detect_indent_rope(rope.slice(..)),
             rope,
             file: None,
+            line_ending,
             encoding,
+            bom,
             syntax: Some(syntax),
             ..Default::default()
         })
@@ -394,7 +431,7 @@ impl Buffer {
         let syntax = match self.syntax.as_mut() {
             Some(syntax) => syntax,
             None => {
-                self.syntax = Some(Syntax::new(get_buffer_proxy()));
+                self.syntax = Some(Syntax::new(self.proxy.dup()));
                 self.syntax.as_mut().unwrap()
             }
         };
@@ -402,15 +439,23 @@ impl Buffer {
             if let Err(err) = syntax.set_language(language) {
                 tracing::error!("Error setting language: {err}");
             }
-            syntax.update_text(self.rope.clone());
+            syntax.update_text(self.rope.clone(), self.revision);
         }
     }
 
     pub fn set_text(&mut self, text: &str) {
-        self.rope = Rope::from(text);
+        self.set_rope(Rope::from(text));
+    }
+
+    /// Replaces the text without adding it to the history, cursors past the end are moved back
+    pub fn set_rope(&mut self, rope: Rope) {
+        let old_len = self.rope.len_bytes();
+        self.rope = rope;
+        self.emit_change(0..old_len, self.rope.len_bytes());
         if let Some(ref mut syntax) = self.syntax {
-            syntax.update_text(self.rope.clone());
+            syntax.update_text(self.rope.clone(), self.revision);
         }
+        self.ensure_every_cursor_is_valid();
     }
 
     /// Replaces ropye, moves all cursors to end of file and autoscrolls
@@ -427,9 +472,11 @@ impl Buffer {
             }
         }
 
+        let old_len = self.rope.len_bytes();
         self.rope = rope;
+        self.emit_change(0..old_len, self.rope.len_bytes());
         if let Some(ref mut syntax) = self.syntax {
-            syntax.update_text(self.rope.clone());
+            syntax.update_text(self.rope.clone(), self.revision);
         }
         for view_id in self.views.keys().collect::<Vec<_>>().into_iter() {
             if let Some(scroll) = map.get(view_id) {
@@ -443,6 +490,12 @@ impl Buffer {
         }
     }
 
+    /// Scrolls so the last line is at the bottom of the view
+    pub fn scroll_to_end(&mut self, view_id: ViewId) {
+        let view = &mut self.views[view_id];
+        view.line_pos = self.rope.len_lines().saturating_sub(view.view_lines) as f64;
+    }
+
     pub fn cursor(&self, view_id: ViewId, cursor_index: usize) -> Cursor {
         self.views[view_id].cursors[cursor_index]
     }
@@ -451,6 +504,15 @@ impl Buffer {
         self.dirty
     }
 
+    /// Moves the cursor to `point` and centers the view on it
+    pub fn jump_to(&mut self, view_id: ViewId, point: Point<usize>) {
+        self.select_area(view_id, point, point, false);
+        // A buffers default amount of lines when newly opened is too large
+        // and the view will not jump to it.
+        self.set_view_lines(view_id, 10);
+        self.center_on_cursor(view_id);
+    }
+
     pub fn set_view_lines(&mut self, view_id: ViewId, lines: usize) {
         self.views[view_id].view_lines = lines;
     }
@@ -475,6 +537,14 @@ impl Buffer {
         self.name = name;
     }
 
+    /// Sets the proxy that is woken to render the buffer once its syntax has been highlighted
+    pub fn set_proxy(&mut self, proxy: Box<dyn EventLoopProxy>) {
+        if let Some(syntax) = &self.syntax {
+            syntax.set_proxy(proxy.dup());
+        }
+        self.proxy = proxy;
+    }
+
     pub fn language_name(&self) -> &str {
         match &self.syntax {
             Some(syntax) => syntax.get_language_name().unwrap_or("text"),
@@ -482,20 +552,16 @@ impl Buffer {
         }
     }
 
-    pub fn set_langauge(
-        &mut self,
-        language: &str,
-        proxy: Box<dyn EventLoopProxy>,
-    ) -> anyhow::Result<()> {
+    pub fn set_langauge(&mut self, language: &str) -> anyhow::Result<()> {
         let syntax = match self.syntax.as_mut() {
             Some(syntax) => syntax,
             None => {
-                self.syntax = Some(Syntax::new(proxy));
+                self.syntax = Some(Syntax::new(self.proxy.dup()));
                 self.syntax.as_mut().unwrap()
             }
         };
         syntax.set_language(language)?;
-        syntax.update_text(self.rope.clone());
+        syntax.update_text(self.rope.clone(), self.revision);
         Ok(())
     }
 
@@ -561,10 +627,46 @@ impl Buffer {
             let cwd = std::env::current_dir()?;
             cwd.join(path)
         };
+        if self.file.as_ref() != Some(&path) {
+            self.disk_state = None;
+            self.link = None;
+        }
         self.file = Some(path);
         Ok(())
     }
 
+    pub fn link(&self) -> Option<&Path> {
+        self.link.as_deref()
+    }
+
+    /// Makes the symlink the file was opened through the file of the buffer,
+    /// returns false if it was not opened through a symlink
+    pub fn detach_link(&mut self) -> bool {
+        let Some(link) = self.link.take() else {
+            return false;
+        };
+        self.file = Some(link);
+        self.disk_state = None;
+        true
+    }
+
+    pub fn disk_state(&self) -> Option<DiskState> {
+        self.disk_state
+    }
+
+    pub fn set_disk_state(&mut self, disk_state: Option<DiskState>) {
+        self.disk_state = disk_state;
+    }
+
+    /// Encryption used when the file is read and written, encrypted buffers are never written to disk as plaintext
+    pub fn encryption(&self) -> Option<&Encryption> {
+        self.encryption.as_ref()
+    }
+
+    pub fn set_encryption(&mut self, encryption: Option<Encryption>) {
+        self.encryption = encryption;
+    }
+
     pub fn line_pos(&self, view_id: ViewId) -> usize {
         self.views[view_id].line_pos_floored()
     }
@@ -845,7 +947,14 @@ impl Buffer {
     }
 
     fn select_word_raw(&mut self, view_id: ViewId, cursor_idx: usize) {
-        let mut start_byte_idx = self.views[view_id].cursors[cursor_idx].position;
+        let range = self.word_range(self.views[view_id].cursors[cursor_idx].position);
+        self.views[view_id].cursors[cursor_idx].position = range.end;
+        self.views[view_id].cursors[cursor_idx].anchor = range.start;
+    }
+
+    /// Byte range of the word around `byte_idx`, empty if there is no word there
+    fn word_range(&self, byte_idx: usize) -> Range<usize> {
+        let mut start_byte_idx = byte_idx;
         loop {
             let new_idx = self.rope.prev_grapheme_boundary_byte(start_byte_idx);
             let grapheme = self.rope.byte_slice(new_idx..start_byte_idx);
@@ -855,7 +964,7 @@ impl Buffer {
             start_byte_idx = new_idx;
         }
 
-        let mut end_byte_idx = self.views[view_id].cursors[cursor_idx].position;
+        let mut end_byte_idx = byte_idx;
         loop {
             let new_idx = self.rope.next_grapheme_boundary_byte(end_byte_idx);
             let grapheme = self.rope.byte_slice(end_byte_idx..new_idx);
@@ -865,8 +974,13 @@ impl Buffer {
             end_byte_idx = new_idx;
         }
 
-        self.views[view_id].cursors[cursor_idx].position = end_byte_idx;
-        self.views[view_id].cursors[cursor_idx].anchor = start_byte_idx;
+        start_byte_idx..end_byte_idx
+    }
+
+    /// The word under the primary cursor of the view
+    pub fn word_under_cursor(&self, view_id: ViewId) -> Option<String> {
+        let range = self.word_range(self.views[view_id].cursors.first().position);
+        (!range.is_empty()).then(|| self.rope.byte_slice(range).to_string())
     }
 
     pub fn select_word(&mut self, view_id: ViewId) {
@@ -894,7 +1008,7 @@ impl Buffer {
             let m = search_rope(
                 self.rope.byte_slice(search_start..),
                 self.get_selection(view_id, 0).to_string(),
-                false,
+                SearchOptions::default(),
                 true,
             )
             .pop();
@@ -921,6 +1035,19 @@ impl Buffer {
     }
 
     pub fn select_all_matching(&mut self, view_id: ViewId) {
+        let term = self.matching_term(view_id);
+        let matches = search_rope(
+            self.rope.byte_slice(..),
+            term,
+            SearchOptions::default(),
+            false,
+        );
+        self.add_match_cursors(view_id, &matches);
+    }
+
+    /// Selects the word under the first cursor if nothing is selected and returns the selection
+    /// that every match of is selected by `select_all_matching`
+    pub fn matching_term(&mut self, view_id: ViewId) -> String {
         self.views[view_id].coalesce_cursors();
         let cursors = self.get_cursors_sorted(view_id);
         self.views[view_id]
@@ -931,9 +1058,12 @@ impl Buffer {
             self.select_word_raw(view_id, 0);
         }
 
-        let term = self.get_selection(view_id, 0).to_string();
+        self.get_selection(view_id, 0).to_string()
+    }
 
-        for m in search_rope(self.rope.byte_slice(..), term, false, false) {
+    /// Adds a cursor selecting each of the matches
+    pub fn add_match_cursors(&mut self, view_id: ViewId, matches: &[SearchMatch]) {
+        for m in matches {
             self.views[view_id].cursors.push(Cursor {
                 anchor: m.start_byte,
                 position: m.end_byte,
@@ -1243,6 +1373,40 @@ impl Buffer {
                     self.views[view_id].cursors[cursor_index].position;
             }
             (text.len(), false)
+        } else if auto_indent && text == "\n" {
+            let position = self.views[view_id].cursors[cursor_index].position;
+            let indent = self.get_newline_indent(position);
+
+            let line_end = self.rope.end_of_line_byte(self.rope.byte_to_line(position));
+            let trailing_whitespace: usize = self
+                .rope
+                .byte_slice(position..line_end)
+                .chars()
+                .take_while(|ch| *ch != '\n' && *ch != '\r' && ch.is_whitespace())
+                .map(char::len_utf8)
+                .sum();
+
+            let mut input = String::from("\n");
+            input.push_str(&self.indent.from_width(indent.width));
+            let inserted_bytes = input.len();
+            if let Some(closing_width) = indent.closing_width {
+                input.push('\n');
+                input.push_str(&self.indent.from_width(closing_width));
+            }
+
+            self.history.replace(
+                &mut self.rope,
+                position..(position + trailing_whitespace),
+                &input,
+            );
+            (inserted_bytes, true)
+        } else if let Some((range, input)) = auto_indent
+            .then(|| self.get_dedent(self.views[view_id].cursors[cursor_index].position, text))
+            .flatten()
+        {
+            self.views[view_id].cursors[cursor_index].position = range.start;
+            self.history.replace(&mut self.rope, range, &input);
+            (input.len(), false)
         } else if auto_indent {
             let indent = self.guess_indent(self.views[view_id].cursors[cursor_index].position);
             let min_indent_width = Rope::from_str(&indent).width(0);
@@ -1668,98 +1832,6 @@ impl Buffer {
         self.history.finish();
     }
 
-    // TODO make multicursor aware
-    pub fn move_line(&mut self, view_id: ViewId, dir: LineMoveDir) {
-        self.views[view_id].cursors.clear();
-        self.history.begin(self.get_all_cursors(), self.dirty);
-        let len_lines = self.rope.len_lines();
-        let (cursor_col, cursor_line_idx) = self.cursor_byte_pos(view_id, 0);
-        let (anchor_col, anchor_line_idx) = self.anchor_byte_pos(view_id, 0);
-
-        let cursor_byte_idx_in_line =
-            self.views[view_id].cursors.first().position - self.rope.line_to_byte(cursor_line_idx);
-        let anchor_byte_idx_in_line =
-            self.views[view_id].cursors.first().anchor - self.rope.line_to_byte(anchor_line_idx);
-
-        let start_line_idx = cursor_line_idx.min(anchor_line_idx);
-        let mut end_line_idx = cursor_line_idx.max(anchor_line_idx);
-
-        let end_col = if self.views[view_id].cursors.first().position
-            > self.views[view_id].cursors.first().anchor
-        {
-            cursor_col
-        } else {
-            anchor_col
-        };
-        if end_col == 0 && start_line_idx < end_line_idx {
-            end_line_idx -= 1;
-        }
-
-        if (end_line_idx + 1 >= self.len_lines() && dir == LineMoveDir::Down)
-            || (start_line_idx == 0 && dir == LineMoveDir::Up)
-        {
-            return;
-        }
-
-        let old_line_idx = self
-            .rope
-            .byte_to_line(self.views[view_id].cursors.first().start());
-        let offset = match dir {
-            LineMoveDir::Up => -1,
-            LineMoveDir::Down => 1,
-        };
-        let new_line_idx = (old_line_idx as i64 + offset) as usize;
-
-        let start_byte_idx = self.rope.line_to_byte(start_line_idx);
-        let end_byte_idx = self.rope.end_of_line_byte(end_line_idx);
-
-        let mut removed = self
-            .rope
-            .byte_slice(start_byte_idx..end_byte_idx)
-            .to_string();
-
-        if RopeSlice::from(removed.as_str())
-            .get_line_ending()
-            .is_none()
-        {
-            removed.push('\n');
-        }
-
-        self.history
-            .remove(&mut self.rope, start_byte_idx..end_byte_idx);
-        let end_idx = self.rope.len_bytes();
-        self.history.insert(&mut self.rope, end_idx, "\n");
-
-        let new_line_start_byte_idx = self.rope.line_to_byte(new_line_idx);
-        self.history
-            .insert(&mut self.rope, new_line_start_byte_idx, &removed);
-
-        while len_lines < self.rope.len_lines() && self.rope.get_line_ending().is_some() {
-            let start = self
-                .rope
-                .char_to_byte(rope_end_without_line_ending(&self.rope.slice(..)));
-            let end = self.rope.len_bytes();
-            self.history.remove(&mut self.rope, start..end);
-        }
-
-        let new_cursor_line_idx = (cursor_line_idx as i64 + offset) as usize;
-        let new_anchor_line_idx = (anchor_line_idx as i64 + offset) as usize;
-
-        self.views[view_id].cursors.first_mut().position =
-            self.rope.line_to_byte(new_cursor_line_idx) + cursor_byte_idx_in_line;
-        self.views[view_id].cursors.first_mut().anchor =
-            self.rope.line_to_byte(new_anchor_line_idx) + anchor_byte_idx_in_line;
-
-        self.update_affinity(view_id);
-        self.mark_dirty();
-        self.ensure_every_cursor_is_valid();
-
-        if self.views[view_id].clamp_cursor {
-            self.center_on_cursor(view_id);
-        }
-        self.history.finish();
-    }
-
     // TODO make multicursor aware
     pub fn tab_or_indent(&mut self, view_id: ViewId, back: bool) {
         self.views[view_id].cursors.clear();
@@ -2013,6 +2085,14 @@ impl Buffer {
     }
 
     pub fn copy(&mut self, view_id: ViewId) {
+        let text = self.copy_text(view_id);
+        #[cfg(target_os = "linux")]
+        clipboard::set_primary(text.clone());
+        clipboard::set_contents(text);
+    }
+
+    /// Returns the text that `copy` would put in the clipboard
+    pub fn copy_text(&mut self, view_id: ViewId) -> String {
         self.views[view_id].coalesce_cursors();
         let multiple_cursors = self.views[view_id].cursors.len() > 1;
         let mut text = String::new();
@@ -2034,13 +2114,19 @@ impl Buffer {
                 text.push('\n');
             }
         }
+        text
+    }
+
+    pub fn cut(&mut self, view_id: ViewId) {
+        let text = self.cut_text(view_id);
         #[cfg(target_os = "linux")]
         clipboard::set_primary(text.clone());
         clipboard::set_contents(text);
     }
 
-    pub fn cut(&mut self, view_id: ViewId) {
-        self.copy(view_id);
+    /// Removes the selections and returns the removed text instead of putting it in the clipboard
+    pub fn cut_text(&mut self, view_id: ViewId) -> String {
+        let text = self.copy_text(view_id);
         self.history.begin(self.get_all_cursors(), self.dirty);
 
         for i in 0..self.views[view_id].cursors.len() {
@@ -2085,11 +2171,16 @@ impl Buffer {
             self.center_on_cursor(view_id);
         }
         self.history.finish();
+        text
     }
 
     pub fn paste(&mut self, view_id: ViewId) {
-        let text = clipboard::get_contents();
-        let rope = Rope::from_str(&text);
+        self.paste_text(view_id, &clipboard::get_contents());
+    }
+
+    /// Pastes `text`, if it has one line per cursor each cursor gets its own line
+    pub fn paste_text(&mut self, view_id: ViewId, text: &str) {
+        let rope = Rope::from_str(text);
 
         let lines = rope
             .lines()
@@ -2097,7 +2188,7 @@ impl Buffer {
             .count();
 
         if self.views[view_id].cursors.len() != lines {
-            self.insert_text(view_id, &text, true);
+            self.insert_text(view_id, text, true);
             self.history.finish();
             return;
         }
@@ -2171,11 +2262,19 @@ impl Buffer {
         let Some(path) = &self.file else {
             return Err(BufferError::NoPathSet);
         };
+        if self.tail.is_some() {
+            return self.load_tail();
+        }
         self.history.finish();
         self.history.begin(self.get_all_cursors(), self.dirty);
 
-        let (encoding, rope) = read::read_from_file(path)?;
+        let (encoding, bom, rope) = match &self.encryption {
+            Some(encryption) => read::read_with_bom(&encryption.read_file(path)?[..])?,
+            None => read::read_with_bom(fs::File::open(path)?)?,
+        };
+        self.disk_state = DiskState::read(path).ok();
         self.encoding = encoding;
+        self.bom = bom;
         let len_bytes = self.rope.len_bytes();
         self.history.replace(&mut self.rope, 0..len_bytes, rope);
 
@@ -2400,9 +2499,70 @@ impl Buffer {
     }
 
     pub fn queue_syntax_update(&mut self) {
+        for (range, new_len) in self.history.take_changes() {
+            self.emit_change(range, new_len);
+        }
         if let Some(syntax) = &mut self.syntax {
-            syntax.update_text(self.rope.clone());
+            syntax.update_text(self.rope.clone(), self.revision);
+        }
+        for view in self.views.values_mut() {
+            if let Some(searcher) = &mut view.searcher {
+                searcher.update_buffer(self.rope.clone(), self.revision, None);
+            }
+        }
+    }
+
+    /// Returns a receiver that gets a [`BufferChange`] for every edit made to the buffer
+    pub fn subscribe_changes(&mut self) -> cb::Receiver<BufferChange> {
+        let (tx, rx) = cb::unbounded();
+        self.change_listeners.push(tx);
+        rx
+    }
+
+    /// Revision of the text, incremented for every change made to the buffer
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Changes whenever the text, its highlights or its outline change.
+    /// Frontends only render panes showing the buffer again when it or the view has changed.
+    pub fn generation(&self) -> u64 {
+        let syntax = self.syntax.as_ref().map_or(0, |syntax| syntax.generation());
+        self.revision.wrapping_add(syntax)
+    }
+
+    /// Word level changes of the diff hunks intersecting `lines`,
+    /// they are only computed again when the text or the lines have changed
+    pub fn word_changes(&mut self, lines: Range<usize>) -> Arc<[WordChange]> {
+        match &self.word_changes {
+            Some((revision, cached, changes)) if *revision == self.revision && *cached == lines => {
+                changes.clone()
+            }
+            _ => {
+                let changes: Arc<[WordChange]> =
+                    diff_word_changes(&self.rope, lines.clone()).into();
+                self.word_changes = Some((self.revision, lines, changes.clone()));
+                changes
+            }
+        }
+    }
+
+    fn emit_change(&mut self, range: Range<usize>, new_len: usize) {
+        self.revision += 1;
+        self.shift_bookmarks(&range, new_len);
+        for view in self.views.values_mut() {
+            if let Some(searcher) = &mut view.searcher {
+                searcher.shift_matches(&range, new_len);
+            }
         }
+        let change = BufferChange {
+            revision: self.revision,
+            old_len: range.len(),
+            range,
+            new_len,
+        };
+        self.change_listeners
+            .retain(|listener| listener.send(change.clone()).is_ok());
     }
 
     pub fn get_syntax(&mut self) -> Option<&mut Syntax> {
@@ -2422,46 +2582,57 @@ impl Buffer {
         start..end
     }
 
-    pub fn start_search(
-        &mut self,
-        view_id: ViewId,
-        proxy: Box<dyn EventLoopProxy>,
-        query: String,
-        case_insensitive: bool,
-    ) {
+    pub fn start_search(&mut self, view_id: ViewId, query: String, options: SearchOptions) {
         let cursor_pos = self.views[view_id].cursors.first().position;
         if let Some(searcher) = &mut self.views[view_id].searcher {
-            searcher.update_query(query, case_insensitive, cursor_pos);
+            searcher.update_query(query, options, cursor_pos);
         } else {
             let searcher = BufferSearcher::new(
-                proxy,
                 query,
                 self.rope.clone(),
-                case_insensitive,
+                self.revision,
+                options,
                 self.views[view_id].cursors.first().position,
             );
             self.views[view_id].searcher = Some(searcher);
         }
     }
 
+    /// Starts the searches of large buffers as jobs and receives the matches they have found
+    pub fn poll_searches(&mut self, job_manager: &mut JobManager) {
+        for view in self.views.values_mut() {
+            if let Some(searcher) = &mut view.searcher {
+                searcher.poll(job_manager);
+            }
+        }
+    }
+
     pub fn get_searcher(&self, view_id: ViewId) -> Option<&BufferSearcher> {
         self.views[view_id].searcher.as_ref()
     }
 
-    pub fn next_match(&mut self, view_id: ViewId) {
+    /// Selects the next match, returns true if the search wrapped around to the top of the buffer
+    pub fn next_match(&mut self, view_id: ViewId) -> bool {
         if let Some(searcher) = &mut self.views[view_id].searcher {
             if let Some(search_match) = searcher.get_next_match() {
+                let wrapped = searcher.wrapped();
                 self.select_area(view_id, search_match.end, search_match.start, false);
+                return wrapped;
             }
         }
+        false
     }
 
-    pub fn prev_match(&mut self, view_id: ViewId) {
+    /// Selects the previous match, returns true if the search wrapped around to the bottom of the buffer
+    pub fn prev_match(&mut self, view_id: ViewId) -> bool {
         if let Some(searcher) = &mut self.views[view_id].searcher {
             if let Some(search_match) = searcher.get_prev_match() {
+                let wrapped = searcher.wrapped();
                 self.select_area(view_id, search_match.end, search_match.start, false);
+                return wrapped;
             }
         }
+        false
     }
 
     pub fn cursor_is_eof(&self, view_id: ViewId, cursor_index: usize) -> bool {
@@ -2496,6 +2667,15 @@ impl Buffer {
         slice.to_string()
     }
 
+    /// Edits made until `end_undo_group` are undone as a single step
+    pub fn begin_undo_group(&mut self) {
+        self.history.begin_group();
+    }
+
+    pub fn end_undo_group(&mut self) {
+        self.history.end_group();
+    }
+
     pub fn mark_history_dirty(&mut self) {
         self.history.mark_all_dirty();
     }
@@ -2559,69 +2739,39 @@ impl Buffer {
         self.indent.from_width(Rope::from_str(&indent).width(0))
     }
 
-    pub fn sort_lines(&mut self, view_id: ViewId, asc: bool) {
-        if self.views[view_id].cursors.len() > 1 {
-            return;
-        }
-
-        self.history.begin(self.get_all_cursors(), self.dirty);
-        let start = self
-            .rope
-            .byte_to_line(self.views[view_id].cursors.first().start());
-        let end = self
-            .rope
-            .byte_to_line(self.views[view_id].cursors.first().end());
-
-        let last_line_at_start = self.views[view_id]
-            .cursors
-            .first()
-            .position
-            .max(self.views[view_id].cursors.first().anchor)
-            == self.rope.line_to_byte(end);
-
-        let end = if last_line_at_start {
-            end.saturating_sub(1).max(start)
-        } else {
-            end
+    /// Computes the indentation of a newline inserted at `byte_index`
+    pub fn get_newline_indent(&self, byte_index: usize) -> NewlineIndent {
+        let config = self.syntax.as_ref().and_then(|syntax| syntax.get_config());
+        auto_indent::newline_indent(
+            config,
+            &self.rope,
+            byte_index,
+            self.indent.width(),
+            matches!(self.language_name(), "python" | "yaml"),
+        )
+    }
+
+    /// If typing `text` at `byte_index` should dedent the current line this returns
+    /// the range to replace and the text to replace it with
+    fn get_dedent(&self, byte_index: usize, text: &str) -> Option<(Range<usize>, String)> {
+        let mut chars = text.chars();
+        let (Some(ch), None) = (chars.next(), chars.next()) else {
+            return None;
         };
-
-        if end == start {
-            return;
+        if !auto_indent::is_dedent_char(ch) {
+            return None;
         }
 
-        let start_byte = self.rope.line_to_byte(start);
-        let end_byte = self.rope.end_of_line_byte(end);
-
-        let cloned_rope = self.rope.clone();
-        let mut lines: Vec<RopeSlice> = cloned_rope
-            .byte_slice(start_byte..end_byte)
-            .lines()
-            .collect();
-        lines.sort_by(|lhs, rhs| {
-            let lhs = lhs.trim_start_whitespace();
-            let rhs = rhs.trim_start_whitespace();
-            for (lhs, rhs) in lhs.chunks().zip(rhs.chunks()) {
-                match lexical_sort::natural_lexical_cmp(lhs, rhs) {
-                    cmp::Ordering::Equal => continue,
-                    ordering if asc => return ordering.reverse(),
-                    ordering => return ordering,
-                }
-            }
-            cmp::Ordering::Equal
-        });
-
-        self.history.remove(&mut self.rope, start_byte..end_byte);
-        let inserted_bytes = 0;
-        for line in lines {
-            self.history
-                .insert(&mut self.rope, start_byte + inserted_bytes, line);
+        let line_start = self.rope.line_to_byte(self.rope.byte_to_line(byte_index));
+        if line_start == byte_index || !self.rope.byte_slice(line_start..byte_index).is_whitespace()
+        {
+            return None;
         }
 
-        self.ensure_cursors_are_valid(view_id);
-        self.mark_dirty();
-        self.ensure_every_cursor_is_valid();
-
-        self.history.finish();
+        let width = auto_indent::dedent_width(&self.rope, byte_index, ch, self.indent.width());
+        let mut input = self.indent.from_width(width);
+        input.push(ch);
+        Some((line_start..byte_index, input))
     }
 
     pub fn replace_all(&mut self, view_id: ViewId, replacement: String) {
@@ -2630,8 +2780,6 @@ impl Buffer {
         if let Some(searcher) = &mut view.searcher {
             self.history.begin(cursors, self.dirty);
             let matches = searcher.get_matches();
-            let guard = matches.lock().unwrap();
-            let (matches, _) = &*guard;
 
             let mut diff: i64 = 0;
             for m in matches {
@@ -2654,8 +2802,6 @@ impl Buffer {
                 }
             }
 
-            searcher.update_buffer(self.rope.clone(), None);
-
             self.ensure_cursors_are_valid(view_id);
             self.mark_dirty();
             self.ensure_every_cursor_is_valid();
@@ -2668,6 +2814,11 @@ impl Buffer {
         }
     }
 
+    /// Scratch buffers are saved automatically and never prompt about unsaved changes
+    pub fn is_scratch(&self) -> bool {
+        self.file.as_ref().is_some_and(scratch::is_scratch_path)
+    }
+
     pub fn is_disposable(&self) -> bool {
         !self.is_dirty()
             && self.rope().len_bytes() == 0
@@ -2754,10 +2905,11 @@ impl Buffer {
     }
 
     pub fn load_buffer_data(&mut self, buffer_data: &BufferData) {
-        if let Err(err) = self.set_langauge(&buffer_data.language, get_buffer_proxy()) {
+        if let Err(err) = self.set_langauge(&buffer_data.language) {
             tracing::error!("Error loading buffer data: {err}");
         }
         self.indent = buffer_data.indent;
+        self.set_bookmarks(&buffer_data.bookmarks);
     }
 
     pub fn create_view(&mut self) -> ViewId {
@@ -2788,56 +2940,6 @@ impl Buffer {
         }
     }
 
-    pub fn number(&mut self, view_id: ViewId, number: Option<i64>) {
-        self.history.begin(self.get_all_cursors(), self.dirty);
-
-        self.views[view_id].coalesce_cursors();
-        let cursors = self.get_cursors_sorted(view_id);
-        let start = number.unwrap_or(0);
-        for (cursor_loop_index, (_, i)) in cursors.iter().copied().enumerate() {
-            let before_len_bytes = self.rope.len_bytes();
-
-            let text = (start + cursor_loop_index as i64).to_string();
-            let inserted_bytes = text.len();
-            if self.views[view_id].cursors[i].has_selection() {
-                let start_byte_idx = self.views[view_id].cursors[i].start();
-                let end_byte_idx = self.views[view_id].cursors[i].end();
-
-                self.history
-                    .replace(&mut self.rope, start_byte_idx..end_byte_idx, text);
-                self.views[view_id].cursors[i].position = self.views[view_id].cursors[i].start();
-                self.views[view_id].cursors[i].anchor = self.views[view_id].cursors[i].position;
-            } else {
-                self.history.insert(
-                    &mut self.rope,
-                    self.views[view_id].cursors[i].position,
-                    text,
-                );
-            };
-
-            self.views[view_id].cursors[i].position += inserted_bytes;
-            self.views[view_id].cursors[i].anchor = self.views[view_id].cursors[i].position;
-
-            let after_len_bytes = self.rope.len_bytes();
-            let diff_len_bytes = after_len_bytes as i64 - before_len_bytes as i64;
-            for (_, i) in cursors.iter().copied().skip(cursor_loop_index + 1) {
-                let cursor = &mut self.views[view_id].cursors[i];
-                cursor.position = (cursor.position as i64 + diff_len_bytes) as usize;
-                cursor.anchor = (cursor.anchor as i64 + diff_len_bytes) as usize;
-            }
-        }
-
-        if self.views[view_id].clamp_cursor {
-            self.center_on_cursor(view_id);
-        }
-
-        self.update_affinity(view_id);
-        self.mark_dirty();
-        self.ensure_every_cursor_is_valid();
-
-        self.history.finish();
-    }
-
     pub fn trim_trailing_whitespace(&mut self) {
         self.history.begin(self.get_all_cursors(), self.dirty);
 
@@ -0,0 +1,143 @@
+use std::ops::Range;
+
+use ferrite_utility::graphemes::{is_word_char, RopeGraphemeExt};
+
+use super::{Buffer, ViewId};
+
+/// Finds the abbreviation `before` ends with. Abbreviations have to start at the start of a word
+/// or where a run of punctuation starts so `steh` is not expanded by `teh`, longer abbreviations win.
+/// Returns the byte length of the abbreviation and its expansion.
+pub fn find_abbreviation<'a>(
+    before: &str,
+    lookup: impl Fn(&str) -> Option<&'a str>,
+) -> Option<(usize, &'a str)> {
+    let token_start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, ch)| !ch.is_whitespace())
+        .last()
+        .map(|(i, _)| i)?;
+    let token = &before[token_start..];
+
+    let mut prev = None;
+    for (i, ch) in token.char_indices() {
+        let starts_run = prev.is_none_or(|prev| is_word_char(prev) != is_word_char(ch));
+        prev = Some(ch);
+        if !starts_run {
+            continue;
+        }
+        if let Some(expansion) = lookup(&token[i..]) {
+            return Some((token.len() - i, expansion));
+        }
+    }
+    None
+}
+
+impl Buffer {
+    /// Expands the abbreviation before `ch` at every cursor after `ch` was typed.
+    /// The expansion is its own history entry so undo reverts just the expansion.
+    pub fn expand_abbreviations<'a>(
+        &mut self,
+        view_id: ViewId,
+        ch: char,
+        lookup: impl Fn(&str) -> Option<&'a str>,
+    ) {
+        if is_word_char(ch) {
+            return;
+        }
+
+        let mut edits: Vec<(Range<usize>, &str)> = Vec::new();
+        for (cursor, _) in self.get_cursors_sorted(view_id) {
+            if cursor.has_selection() {
+                continue;
+            }
+            let line_idx = self.rope.byte_to_line(cursor.position);
+            // Auto indent moves the cursor past the indentation of the new line
+            let end = if ch == '\n' {
+                if line_idx == 0 {
+                    continue;
+                }
+                let prev_line = line_idx - 1;
+                self.rope.line_to_byte(prev_line)
+                    + self.rope.line_without_line_ending(prev_line).len_bytes()
+            } else {
+                let Some(end) = cursor.position.checked_sub(ch.len_utf8()) else {
+                    continue;
+                };
+                if self.rope.byte_slice(end..cursor.position) != ch.to_string().as_str() {
+                    continue;
+                }
+                end
+            };
+            let line_start = self.rope.line_to_byte(self.rope.byte_to_line(end));
+            let before = self.rope.byte_slice(line_start..end).to_string();
+            if let Some((len, expansion)) = find_abbreviation(&before, &lookup) {
+                edits.push((end - len..end, expansion));
+            }
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        // Ends the history entry of the typed character
+        self.history.finish();
+        self.history.begin(self.get_all_cursors(), self.dirty);
+        for (range, expansion) in edits.iter().rev() {
+            self.history
+                .replace(&mut self.rope, range.clone(), *expansion);
+        }
+        let shift = |byte_idx: usize| {
+            let mut diff = 0;
+            for (range, expansion) in &edits {
+                if byte_idx < range.end {
+                    break;
+                }
+                diff += expansion.len() as isize - range.len() as isize;
+            }
+            (byte_idx as isize + diff) as usize
+        };
+        for cursor in self.views[view_id].cursors.iter_mut() {
+            cursor.position = shift(cursor.position);
+            cursor.anchor = shift(cursor.anchor);
+        }
+
+        self.update_affinity(view_id);
+        if self.views[view_id].clamp_cursor {
+            self.center_on_cursor(view_id);
+        }
+        self.mark_dirty();
+        self.ensure_every_cursor_is_valid();
+        self.history.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abbreviations() {
+        let lookup = |abbreviation: &str| match abbreviation {
+            "teh" => Some("the"),
+            ":shrug:" => Some("¯\\_(ツ)_/¯"),
+            _ => None,
+        };
+        assert_eq!(find_abbreviation("(teh", lookup), Some((3, "the")));
+        assert_eq!(find_abbreviation("steh", lookup), None);
+        assert_eq!(
+            find_abbreviation("ok:shrug:", lookup),
+            Some((7, "¯\\_(ツ)_/¯"))
+        );
+        assert_eq!(find_abbreviation("teh ", lookup), None);
+
+        let mut buffer = Buffer::with_text("");
+        let view_id = buffer.get_first_view_or_create();
+        buffer.insert_text(view_id, "teh", false);
+        buffer.insert_text(view_id, " ", false);
+        buffer.expand_abbreviations(view_id, ' ', lookup);
+        assert_eq!(buffer.rope().to_string(), "the ");
+        assert_eq!(buffer.views[view_id].cursors.first().position, 4);
+        buffer.undo(view_id);
+        assert_eq!(buffer.rope().to_string(), "teh ");
+    }
+}
@@ -0,0 +1,114 @@
+use std::ops::Range;
+
+use ferrite_utility::point::Point;
+
+use super::{Buffer, ViewId};
+
+impl Buffer {
+    /// Lines that have a bookmark, sorted
+    pub fn bookmarks(&self) -> Vec<usize> {
+        let len = self.rope.len_bytes();
+        let mut lines: Vec<_> = self
+            .bookmarks
+            .iter()
+            .map(|byte| self.rope.byte_to_line((*byte).min(len)))
+            .collect();
+        lines.dedup();
+        lines
+    }
+
+    pub fn set_bookmarks(&mut self, lines: &[usize]) {
+        let len_lines = self.rope.len_lines();
+        self.bookmarks = lines
+            .iter()
+            .filter(|line_idx| **line_idx < len_lines)
+            .map(|line_idx| self.rope.line_to_byte(*line_idx))
+            .collect();
+        self.bookmarks.sort_unstable();
+        self.bookmarks.dedup();
+    }
+
+    /// Adds or removes the bookmark on the line of the cursor, returns true if it was added
+    pub fn toggle_bookmark(&mut self, view_id: ViewId) -> bool {
+        let line_idx = self.cursor_line_idx(view_id, 0);
+        let len = self.bookmarks.len();
+        let rope = &self.rope;
+        self.bookmarks
+            .retain(|byte| rope.byte_to_line((*byte).min(rope.len_bytes())) != line_idx);
+        if self.bookmarks.len() != len {
+            return false;
+        }
+        let byte = self.rope.line_to_byte(line_idx);
+        let index = self.bookmarks.partition_point(|b| *b < byte);
+        self.bookmarks.insert(index, byte);
+        true
+    }
+
+    /// Moves the cursor to the next bookmark, wrapping around to the first one
+    pub fn next_bookmark(&mut self, view_id: ViewId) -> bool {
+        let line_idx = self.cursor_line_idx(view_id, 0);
+        let bookmarks = self.bookmarks();
+        let Some(line) = bookmarks
+            .iter()
+            .find(|line| **line > line_idx)
+            .or(bookmarks.first())
+        else {
+            return false;
+        };
+        self.jump_to(view_id, Point::new(0, *line));
+        true
+    }
+
+    /// Moves the cursor to the previous bookmark, wrapping around to the last one
+    pub fn prev_bookmark(&mut self, view_id: ViewId) -> bool {
+        let line_idx = self.cursor_line_idx(view_id, 0);
+        let bookmarks = self.bookmarks();
+        let Some(line) = bookmarks
+            .iter()
+            .rev()
+            .find(|line| **line < line_idx)
+            .or(bookmarks.last())
+        else {
+            return false;
+        };
+        self.jump_to(view_id, Point::new(0, *line));
+        true
+    }
+
+    /// Keeps the bookmarks on their lines when text before them is changed
+    pub(super) fn shift_bookmarks(&mut self, range: &Range<usize>, new_len: usize) {
+        for byte in &mut self.bookmarks {
+            if *byte >= range.end {
+                *byte = *byte - range.len() + new_len;
+            } else if *byte > range.start {
+                *byte = (*byte).min(range.start + new_len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bookmarks_follow_edits() {
+        let mut buffer = Buffer::with_text("a\nb\nc\n");
+        let view_id = buffer.create_view();
+        buffer.set_bookmarks(&[1, 2, 9]);
+        assert_eq!(buffer.bookmarks(), vec![1, 2]);
+
+        buffer.replace(view_id, 0..0, "x\ny\n");
+        buffer.queue_syntax_update();
+        assert_eq!(buffer.bookmarks(), vec![3, 4]);
+
+        // Joining the bookmarked lines keeps a single bookmark
+        buffer.replace(view_id, 7..8, "");
+        buffer.queue_syntax_update();
+        assert_eq!(buffer.bookmarks(), vec![3]);
+
+        buffer.goto(view_id, 4);
+        assert!(!buffer.toggle_bookmark(view_id));
+        assert!(buffer.bookmarks().is_empty());
+    }
+}
@@ -1,10 +1,19 @@
 use std::fs;
 
 use ferrite_utility::{line_ending::DEFAULT_LINE_ENDING, vec1::Vec1};
+use proptest::{collection::vec, prelude::*};
 use tempdir::TempDir;
 
-use super::{read, write};
-use crate::buffer::{Buffer, Cursor, View};
+use super::{
+    error::BufferError,
+    read,
+    test_support::{self, check_invariants, run_commands},
+    write,
+};
+use crate::{
+    buffer::{Buffer, Cursor, View},
+    cmd::Cmd,
+};
 
 #[test]
 fn read_utf8() {
@@ -23,7 +32,14 @@ fn read_write_utf8() {
     let (encoding, rope) = read::read_from_file(TEST_FILE).unwrap();
     let tmp_dir = TempDir::new("test").unwrap();
     let output_path = tmp_dir.path().join("output.json");
-    write::write(encoding, DEFAULT_LINE_ENDING, rope.clone(), &output_path).unwrap();
+    write::write(
+        encoding,
+        false,
+        DEFAULT_LINE_ENDING,
+        rope.clone(),
+        &output_path,
+    )
+    .unwrap();
 
     let written = fs::read_to_string(&output_path).unwrap();
     assert_eq!(written, rope.to_string());
@@ -182,3 +198,272 @@ fn colaese_single_edge() {
         }
     }
 }
+
+#[test]
+fn change_events() {
+    let mut buffer = Buffer::with_text("hello world");
+    let view_id = buffer.get_first_view_or_create();
+    let changes = buffer.subscribe_changes();
+
+    buffer.insert_text(view_id, "abc", false);
+    let change = changes.try_recv().unwrap();
+    assert_eq!(change.range, 0..0);
+    assert_eq!(change.old_len, 0);
+    assert_eq!(change.new_len, 3);
+    assert_eq!(change.revision, buffer.revision());
+
+    buffer.undo(view_id);
+    let change = changes.try_recv().unwrap();
+    assert_eq!(change.range, 0..3);
+    assert_eq!(change.new_len, 0);
+    assert_eq!(change.revision, buffer.revision());
+    assert!(changes.try_recv().is_err());
+}
+
+#[test]
+fn generation_changes_with_edits() {
+    let mut buffer = Buffer::with_text("hello world");
+    let view_id = buffer.get_first_view_or_create();
+    let generation = buffer.generation();
+    buffer.insert_text(view_id, "abc", false);
+    assert_ne!(buffer.generation(), generation);
+}
+
+#[test]
+fn generation_changes_on_reload() {
+    let tmp_dir = TempDir::new("test").unwrap();
+    let path = tmp_dir.path().join("file.txt");
+    fs::write(&path, "one\n").unwrap();
+    let mut buffer = Buffer::from_file(&path).unwrap();
+    buffer.get_first_view_or_create();
+
+    fs::write(&path, "two\n").unwrap();
+    let generation = buffer.generation();
+    buffer.reload().unwrap();
+    assert_eq!(buffer.rope().to_string(), "two\n");
+    assert_ne!(buffer.generation(), generation);
+}
+
+#[test]
+fn word_changes_cached_by_revision() {
+    let mut buffer = Buffer::with_text("-let a = 1;\n+let a = 2;\n");
+    let view_id = buffer.get_first_view_or_create();
+
+    let changes = buffer.word_changes(0..2);
+    assert!(!changes.is_empty());
+    assert!(std::sync::Arc::ptr_eq(&changes, &buffer.word_changes(0..2)));
+
+    buffer.insert_text(view_id, " ", false);
+    assert!(!std::sync::Arc::ptr_eq(
+        &changes,
+        &buffer.word_changes(0..2)
+    ));
+}
+
+#[test]
+fn undo_group() {
+    let mut buffer = Buffer::with_text("a\nb\nc\n");
+    let view_id = buffer.get_first_view_or_create();
+
+    buffer.begin_undo_group();
+    buffer.remove_line(view_id);
+    buffer.remove_line(view_id);
+    buffer.end_undo_group();
+    assert_eq!(buffer.rope().to_string(), "c\n");
+
+    buffer.remove_line(view_id);
+    buffer.undo(view_id);
+    assert_eq!(buffer.rope().to_string(), "c\n");
+    buffer.undo(view_id);
+    assert_eq!(buffer.rope().to_string(), "a\nb\nc\n");
+}
+
+#[test]
+fn read_only_blocks_edits() {
+    let mut buffer = Buffer::with_text("abc");
+    let view_id = buffer.get_first_view_or_create();
+    buffer.read_only = true;
+
+    let result = buffer.handle_input(view_id, Cmd::Char { ch: 'x' });
+    assert!(matches!(result, Err(BufferError::ReadOnly)));
+    assert_eq!(buffer.rope().to_string(), "abc");
+    buffer
+        .handle_input(
+            view_id,
+            Cmd::End {
+                expand_selection: false,
+            },
+        )
+        .unwrap();
+    buffer
+        .handle_input(view_id, Cmd::SelectAllMatching)
+        .unwrap();
+
+    buffer.read_only = false;
+    buffer.handle_input(view_id, Cmd::Char { ch: 'x' }).unwrap();
+    assert_eq!(buffer.rope().to_string(), "abcx");
+}
+
+#[test]
+fn disk_state_changed() {
+    let tmp_dir = TempDir::new("test").unwrap();
+    let path = tmp_dir.path().join("file.txt");
+    fs::write(&path, "foo").unwrap();
+    let disk_state = write::DiskState::read(&path).unwrap();
+    assert!(!disk_state.changed(&path));
+
+    fs::write(&path, "foo bar").unwrap();
+    assert!(disk_state.changed(&path));
+
+    fs::remove_file(&path).unwrap();
+    assert!(!disk_state.changed(&path));
+}
+
+#[cfg(unix)]
+#[test]
+fn save_keeps_permissions_and_links() {
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let path = tmp_dir.path().join("script.sh");
+    let link = tmp_dir.path().join("link.sh");
+    let save = |path: &std::path::Path, options| {
+        let rope = ropey::Rope::from_str("echo\n");
+        write::save(
+            None,
+            encoding_rs::UTF_8,
+            false,
+            DEFAULT_LINE_ENDING,
+            rope,
+            path,
+            options,
+        )
+        .unwrap();
+    };
+
+    fs::write(&path, "").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    let disk_state = write::DiskState::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let options = write::SaveOptions {
+        replace_link: false,
+        previous: Some(disk_state),
+    };
+    save(&path, options);
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o755);
+
+    symlink(&path, &link).unwrap();
+    save(&link, write::SaveOptions::default());
+    assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+    let options = write::SaveOptions {
+        replace_link: true,
+        previous: Some(disk_state),
+    };
+    save(&link, options);
+    assert!(!fs::symlink_metadata(&link).unwrap().is_symlink());
+    assert_eq!(fs::read_to_string(&link).unwrap(), "echo\n");
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn line_commands() {
+    use crate::cmd::LineMoveDir;
+
+    let mut buffer = Buffer::with_text("a\nb\nc\n");
+    let view_id = buffer.get_first_view_or_create();
+    let cursor = |position| Cursor {
+        position,
+        anchor: position,
+        affinity: 0,
+    };
+
+    buffer.views[view_id].cursors = Vec1::from_vec(vec![cursor(0), cursor(4)]).unwrap();
+    buffer.duplicate_lines(view_id);
+    assert_eq!(buffer.rope().to_string(), "a\na\nb\nc\nc\n");
+    assert_eq!(buffer.cursor_line_idx(view_id, 0), 1);
+    assert_eq!(buffer.cursor_line_idx(view_id, 1), 4);
+    buffer.undo(view_id);
+    assert_eq!(buffer.rope().to_string(), "a\nb\nc\n");
+
+    buffer.views[view_id].cursors = Vec1::from_vec(vec![cursor(0), cursor(2)]).unwrap();
+    buffer.move_line(view_id, LineMoveDir::Down);
+    assert_eq!(buffer.rope().to_string(), "c\na\nb\n");
+    assert_eq!(buffer.cursor_line_idx(view_id, 0), 1);
+    assert_eq!(buffer.cursor_line_idx(view_id, 1), 2);
+    buffer.move_line(view_id, LineMoveDir::Down);
+    assert_eq!(buffer.rope().to_string(), "c\na\nb\n");
+
+    buffer.select_all(view_id);
+    buffer.sort_lines(view_id, true, false);
+    assert_eq!(buffer.rope().to_string(), "a\nb\nc\n");
+    buffer.reverse_lines(view_id);
+    assert_eq!(buffer.rope().to_string(), "c\nb\na\n");
+
+    let mut buffer = Buffer::with_text("b\na\nb\nfoo\n    bar\n");
+    let view_id = buffer.get_first_view_or_create();
+    buffer.views[view_id].cursors = Vec1::new(Cursor {
+        position: 0,
+        anchor: 6,
+        affinity: 0,
+    });
+    buffer.sort_lines(view_id, true, true);
+    assert_eq!(buffer.rope().to_string(), "a\nb\nfoo\n    bar\n");
+    buffer.views[view_id].cursors = Vec1::new(cursor(4));
+    buffer.join_lines(view_id);
+    assert_eq!(buffer.rope().to_string(), "a\nb\nfoo bar\n");
+}
+
+#[test]
+fn align() {
+    let mut buffer = Buffer::with_text("a = 1\nlong_name = 2\n\tb = 3\nno match\n");
+    let view_id = buffer.get_first_view_or_create();
+    buffer.select_all(view_id);
+    buffer.align(view_id, "=");
+    assert_eq!(
+        buffer.rope().to_string(),
+        "a         = 1\nlong_name = 2\n\tb     = 3\nno match\n"
+    );
+    buffer.undo(view_id);
+    assert_eq!(
+        buffer.rope().to_string(),
+        "a = 1\nlong_name = 2\n\tb = 3\nno match\n"
+    );
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn commands_keep_buffer_valid(
+        text in test_support::text(20),
+        cmds in vec(test_support::cmd(), 0..200),
+    ) {
+        run_commands(&text, &cmds)?;
+    }
+
+    #[test]
+    fn history_round_trips(
+        text in test_support::text(20),
+        cmds in vec(test_support::cmd(), 0..200),
+    ) {
+        let (mut buffer, view_id) = run_commands(&text, &cmds)?;
+        // Undos among the commands can leave edits to redo, those are part of the history as well
+        for _ in 0..=cmds.len() {
+            buffer.redo(view_id);
+        }
+        let edited = buffer.rope().to_string();
+
+        for _ in 0..=cmds.len() {
+            buffer.undo(view_id);
+        }
+        prop_assert_eq!(buffer.rope().to_string(), text, "undo");
+        check_invariants(&buffer)?;
+
+        for _ in 0..=cmds.len() {
+            buffer.redo(view_id);
+        }
+        prop_assert_eq!(buffer.rope().to_string(), edited, "redo");
+        check_invariants(&buffer)?;
+    }
+}
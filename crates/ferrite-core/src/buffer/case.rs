@@ -18,6 +18,7 @@ pub enum Case {
     Camel,
     Pascal,
     Title,
+    Sentence,
     Train,
     ScreamingSnake,
     ScreamingKebab,
@@ -35,6 +36,7 @@ impl FromStr for Case {
             "camel" => Case::Camel,
             "pascal" => Case::Pascal,
             "title" => Case::Title,
+            "sentence" => Case::Sentence,
             "train" => Case::Train,
             "screaming-snake" => Case::ScreamingSnake,
             "screaming-kebab" => Case::ScreamingKebab,
@@ -44,63 +46,197 @@ impl FromStr for Case {
 }
 
 impl Case {
+    pub const NAMES: &'static [&'static str] = &[
+        "lower",
+        "upper",
+        "snake",
+        "kebab",
+        "camel",
+        "pascal",
+        "title",
+        "sentence",
+        "train",
+        "screaming-snake",
+        "screaming-kebab",
+    ];
+
+    /// Converts every identifier in `s` on its own, the text between identifiers is kept
     pub fn transform(&self, s: &str) -> String {
         match self {
             Case::Lower => s.to_lowercase(),
             Case::Upper => s.to_uppercase(),
-            Case::Snake => s.to_snake_case(),
-            Case::Kebab => s.to_kebab_case(),
-            Case::Camel => s.to_lower_camel_case(),
-            Case::Pascal => s.to_pascal_case(),
-            Case::Title => s.to_title_case(),
-            Case::Train => s.to_train_case(),
-            Case::ScreamingSnake => s.to_shouty_snake_case(),
-            Case::ScreamingKebab => s.to_shouty_kebab_case(),
+            Case::Sentence => sentence_case(s),
+            _ => identifiers(s)
+                .map(|(is_identifier, text)| {
+                    if is_identifier {
+                        self.transform_identifier(text)
+                    } else {
+                        text.to_string()
+                    }
+                })
+                .collect(),
         }
     }
+
+    fn transform_identifier(&self, identifier: &str) -> String {
+        // Leading and trailing underscores often carry meaning like `_unused` or `__init__`
+        let core = identifier.trim_matches('_');
+        let Some(core_start) = identifier.find(core).filter(|_| !core.is_empty()) else {
+            return identifier.to_string();
+        };
+        let converted = match self {
+            Case::Snake => core.to_snake_case(),
+            Case::Kebab => core.to_kebab_case(),
+            Case::Camel => core.to_lower_camel_case(),
+            Case::Pascal => core.to_pascal_case(),
+            Case::Title => core.to_title_case(),
+            Case::Train => core.to_train_case(),
+            Case::ScreamingSnake => core.to_shouty_snake_case(),
+            Case::ScreamingKebab => core.to_shouty_kebab_case(),
+            Case::Lower | Case::Upper | Case::Sentence => unreachable!(),
+        };
+        format!(
+            "{}{converted}{}",
+            &identifier[..core_start],
+            &identifier[core_start + core.len()..]
+        )
+    }
+}
+
+/// Splits `s` into identifiers and the text between them.
+/// A hyphen is part of an identifier when it is surrounded by letters or digits.
+fn identifiers(s: &str) -> Vec<(bool, &str)> {
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    let is_identifier = |i: usize| {
+        let ch = chars[i].1;
+        ch.is_alphanumeric()
+            || ch == '_'
+            || (ch == '-'
+                && i > 0
+                && chars[i - 1].1.is_alphanumeric()
+                && chars
+                    .get(i + 1)
+                    .is_some_and(|(_, next)| next.is_alphanumeric()))
+    };
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for i in 1..=chars.len() {
+        if i == chars.len() || is_identifier(i) != is_identifier(start) {
+            let end = chars.get(i).map_or(s.len(), |(byte_idx, _)| *byte_idx);
+            parts.push((is_identifier(start), &s[chars[start].0..end]));
+            start = i;
+        }
+    }
+    parts
+}
+
+/// Lowercases everything and capitalizes the first word of every sentence.
+/// Identifiers are split into space separated words.
+fn sentence_case(s: &str) -> String {
+    let mut output = String::new();
+    let mut capitalize = true;
+    for (is_identifier, text) in identifiers(s) {
+        if !is_identifier {
+            output.push_str(text);
+            if text.trim_end().ends_with(['.', '!', '?']) {
+                capitalize = true;
+            }
+            continue;
+        }
+
+        let words = text.to_snake_case().replace('_', " ");
+        if words.is_empty() {
+            output.push_str(text);
+            continue;
+        }
+
+        let mut chars = words.chars();
+        if capitalize {
+            capitalize = false;
+            if let Some(first) = chars.next() {
+                output.extend(first.to_uppercase());
+            }
+        }
+        output.push_str(chars.as_str());
+    }
+    output
 }
 
 impl Buffer {
-    // TODO make multicursor aware
+    /// Transforms the selection of every cursor, cursors without a selection are left alone
     pub fn transform_case(&mut self, view_id: ViewId, case: Case) {
-        self.views[view_id].cursors.clear();
-        if !self.views[view_id].cursors.first().has_selection() {
+        self.views[view_id].coalesce_cursors();
+
+        let edits: Vec<_> = self
+            .get_cursors_sorted(view_id)
+            .into_iter()
+            .filter(|(cursor, _)| cursor.has_selection())
+            .filter_map(|(cursor, i)| {
+                let range = cursor.start()..cursor.end();
+                let text = self.rope.byte_slice(range.clone()).to_string();
+                let output = case.transform(&text);
+                (output != text).then_some((i, range, output))
+            })
+            .collect();
+        if edits.is_empty() {
             return;
         }
 
         self.history.begin(self.get_all_cursors(), self.dirty);
-        let start_byte_idx = self.views[view_id]
-            .cursors
-            .first()
-            .position
-            .min(self.views[view_id].cursors.first().anchor);
-        let end_byte_idx = self.views[view_id]
-            .cursors
-            .first()
-            .position
-            .max(self.views[view_id].cursors.first().anchor);
-        let string = self.rope.slice(start_byte_idx..end_byte_idx).to_string();
-        let output = case.transform(&string);
-
-        self.history
-            .replace(&mut self.rope, start_byte_idx..end_byte_idx, &output);
-
-        if self.views[view_id].cursors.first().position < self.views[view_id].cursors.first().anchor
-        {
-            self.views[view_id].cursors.first_mut().position = start_byte_idx;
-            self.views[view_id].cursors.first_mut().anchor = start_byte_idx + output.len();
-        } else {
-            self.views[view_id].cursors.first_mut().anchor = start_byte_idx;
-            self.views[view_id].cursors.first_mut().position = start_byte_idx + output.len();
+        for (_, range, output) in edits.iter().rev() {
+            self.history
+                .replace(&mut self.rope, range.clone(), output.as_str());
         }
 
-        self.update_affinity(view_id);
+        let mut diff: isize = 0;
+        let mut edits = edits.into_iter().peekable();
+        for (cursor, i) in self.get_cursors_sorted(view_id) {
+            let offset = diff;
+            let shift = |byte_idx: usize| (byte_idx as isize + offset) as usize;
+            let (position, anchor) = match edits.next_if(|(edit_i, _, _)| *edit_i == i) {
+                Some((_, range, output)) => {
+                    let start = shift(range.start);
+                    let end = start + output.len();
+                    diff += output.len() as isize - range.len() as isize;
+                    if cursor.position < cursor.anchor {
+                        (start, end)
+                    } else {
+                        (end, start)
+                    }
+                }
+                None => (shift(cursor.position), shift(cursor.anchor)),
+            };
+            self.views[view_id].cursors[i].position = position;
+            self.views[view_id].cursors[i].anchor = anchor;
+        }
 
+        self.update_affinity(view_id);
         if self.views[view_id].clamp_cursor {
             self.center_on_cursor(view_id);
         }
-
         self.mark_dirty();
+        self.ensure_every_cursor_is_valid();
         self.history.finish();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transform_identifiers() {
+        assert_eq!(
+            Case::Snake.transform("fooBar(bazQux, _private)"),
+            "foo_bar(baz_qux, _private)"
+        );
+        assert_eq!(Case::Camel.transform("foo-bar - 1"), "fooBar - 1");
+        assert_eq!(Case::ScreamingSnake.transform("maxValue"), "MAX_VALUE");
+        assert_eq!(Case::Title.transform("hello world"), "Hello World");
+        assert_eq!(
+            Case::Sentence.transform("the QUICK brown_fox. jumps"),
+            "The quick brown fox. Jumps"
+        );
+    }
+}
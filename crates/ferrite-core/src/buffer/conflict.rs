@@ -0,0 +1,216 @@
+use std::{ops::Range, sync::Arc};
+
+use ferrite_utility::point::Point;
+use memchr::memmem;
+use ropey::{Rope, RopeSlice};
+
+use super::{Buffer, ViewId};
+
+/// Length of a conflict marker without the label after it
+const MARKER_LEN: usize = 7;
+
+/// A merge conflict region, every field is the line index of a marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    /// `<<<<<<<`
+    pub start: usize,
+    /// `|||||||`, only present in the diff3 conflict style
+    pub base: Option<usize>,
+    /// `=======`
+    pub separator: usize,
+    /// `>>>>>>>`
+    pub end: usize,
+}
+
+impl Conflict {
+    pub fn ours(&self) -> Range<usize> {
+        (self.start + 1)..self.base.unwrap_or(self.separator)
+    }
+
+    pub fn theirs(&self) -> Range<usize> {
+        (self.separator + 1)..self.end
+    }
+
+    /// All lines of the region including the markers
+    pub fn lines(&self) -> Range<usize> {
+        self.start..(self.end + 1)
+    }
+
+    pub fn is_marker(&self, line_idx: usize) -> bool {
+        line_idx == self.start
+            || Some(line_idx) == self.base
+            || line_idx == self.separator
+            || line_idx == self.end
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Start,
+    Base,
+    Separator,
+    End,
+}
+
+fn marker(line: RopeSlice) -> Option<Marker> {
+    let mut bytes = line.bytes();
+    let first = bytes.next()?;
+    let marker = match first {
+        b'<' => Marker::Start,
+        b'|' => Marker::Base,
+        b'=' => Marker::Separator,
+        b'>' => Marker::End,
+        _ => return None,
+    };
+    for _ in 1..MARKER_LEN {
+        if bytes.next()? != first {
+            return None;
+        }
+    }
+    match (marker, bytes.next()) {
+        (_, None | Some(b'\n' | b'\r')) => Some(marker),
+        (Marker::Separator, _) => None,
+        (_, Some(b' ')) => Some(marker),
+        _ => None,
+    }
+}
+
+/// Checks for a start marker before looking at every line, most buffers have none
+fn has_start_marker(rope: &Rope) -> bool {
+    let finder = memmem::Finder::new(&[b'<'; MARKER_LEN]);
+    let mut tail: Vec<u8> = Vec::new();
+    for chunk in rope.chunks() {
+        let bytes = chunk.as_bytes();
+        tail.extend_from_slice(&bytes[..bytes.len().min(MARKER_LEN - 1)]);
+        if finder.find(&tail).is_some() || finder.find(bytes).is_some() {
+            return true;
+        }
+        tail.clear();
+        tail.extend_from_slice(&bytes[bytes.len().saturating_sub(MARKER_LEN - 1)..]);
+    }
+    false
+}
+
+pub fn find_conflicts(rope: &Rope) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    if !has_start_marker(rope) {
+        return conflicts;
+    }
+
+    let (mut start, mut base, mut separator) = (None, None, None);
+    for (line_idx, line) in rope.lines().enumerate() {
+        match marker(line) {
+            Some(Marker::Start) => (start, base, separator) = (Some(line_idx), None, None),
+            Some(Marker::Base) if separator.is_none() => base = Some(line_idx),
+            Some(Marker::Separator) if separator.is_none() => separator = Some(line_idx),
+            Some(Marker::End) => {
+                if let (Some(start), Some(separator)) = (start.take(), separator.take()) {
+                    conflicts.push(Conflict {
+                        start,
+                        base: base.take(),
+                        separator,
+                        end: line_idx,
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+    conflicts
+}
+
+impl Buffer {
+    /// The conflicts in the text, they are only searched for again when the text has changed
+    pub fn conflicts(&mut self) -> Arc<[Conflict]> {
+        match &self.conflicts {
+            Some((revision, conflicts)) if *revision == self.revision => conflicts.clone(),
+            _ => {
+                let conflicts: Arc<[Conflict]> = find_conflicts(&self.rope).into();
+                self.conflicts = Some((self.revision, conflicts.clone()));
+                conflicts
+            }
+        }
+    }
+
+    /// Replaces the conflict under the cursor with one or both of its sides
+    pub fn resolve_conflict(&mut self, view_id: ViewId, resolution: Resolution) -> bool {
+        let line_idx = self.cursor_line_idx(view_id, 0);
+        let Some(conflict) = self
+            .conflicts()
+            .iter()
+            .find(|conflict| conflict.lines().contains(&line_idx))
+            .copied()
+        else {
+            return false;
+        };
+
+        let text = |lines: Range<usize>| {
+            self.rope
+                .byte_slice(self.rope.line_to_byte(lines.start)..self.rope.line_to_byte(lines.end))
+                .to_string()
+        };
+        let mut replacement = match resolution {
+            Resolution::Ours => text(conflict.ours()),
+            Resolution::Theirs => text(conflict.theirs()),
+            Resolution::Both => text(conflict.ours()) + &text(conflict.theirs()),
+        };
+        let start = self.rope.line_to_byte(conflict.start);
+        let end = self.rope.line_to_byte(conflict.end + 1);
+        // Keep a file that ends with the end marker without a line ending
+        if end == self.rope.len_bytes() && self.rope.line(conflict.end).chars().last() != Some('\n')
+        {
+            while replacement.ends_with(['\n', '\r']) {
+                replacement.pop();
+            }
+        }
+        self.replace(view_id, start..end, &replacement);
+        self.mark_dirty();
+        self.goto(view_id, conflict.start as i64 + 1);
+        true
+    }
+
+    /// Moves the cursor to the start of the next conflict, wrapping around to the first one
+    pub fn next_conflict(&mut self, view_id: ViewId) -> bool {
+        let line_idx = self.cursor_line_idx(view_id, 0);
+        let conflicts = self.conflicts();
+        let Some(conflict) = conflicts
+            .iter()
+            .find(|conflict| conflict.start > line_idx)
+            .or(conflicts.first())
+        else {
+            return false;
+        };
+        self.jump_to(view_id, Point::new(0, conflict.start));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicts() {
+        let rope = Rope::from_str(
+            "a\n<<<<<<< HEAD\nours\n||||||| base\nbase\n=======\ntheirs\n>>>>>>> branch\nb\n<<<<<<<< not a marker\n",
+        );
+        assert_eq!(
+            find_conflicts(&rope),
+            vec![Conflict {
+                start: 1,
+                base: Some(3),
+                separator: 5,
+                end: 7,
+            }]
+        );
+        assert_eq!(find_conflicts(&rope)[0].ours(), 2..3);
+        assert!(find_conflicts(&Rope::from_str("a\n=======\nb\n")).is_empty());
+    }
+}
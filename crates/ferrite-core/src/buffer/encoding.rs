@@ -1,5 +1,9 @@
+use std::fs;
+
 use encoding_rs::Encoding;
 
+use super::{error::BufferError, read, write, write::DiskState, Buffer};
+
 static ENCODINGS: &[&Encoding] = &[
     encoding_rs::BIG5,
     encoding_rs::EUC_JP,
@@ -53,3 +57,88 @@ pub fn get_encoding(name: &str) -> Option<&'static Encoding> {
 pub fn get_encoding_names() -> Vec<&'static str> {
     ENCODINGS.iter().map(|encoding| encoding.name()).collect()
 }
+
+impl Buffer {
+    /// Reads the file again decoded with `encoding` instead of the detected encoding
+    pub fn reopen_with_encoding(&mut self, encoding: &'static Encoding) -> Result<(), BufferError> {
+        let Some(path) = &self.file else {
+            return Err(BufferError::NoPathSet);
+        };
+        let bytes = match &self.encryption {
+            Some(encryption) => encryption.read_file(path)?,
+            None => fs::read(path)?,
+        };
+        let (bom, rope) = read::decode_with(encoding, &bytes);
+        self.disk_state = DiskState::read(path).ok();
+        self.encoding = encoding;
+        self.bom = bom;
+
+        self.history.finish();
+        self.history.begin(self.get_all_cursors(), self.dirty);
+        let len_bytes = self.rope.len_bytes();
+        self.history.replace(&mut self.rope, 0..len_bytes, rope);
+        for view in self.views.values_mut() {
+            view.coalesce_cursors();
+        }
+        self.dirty = false;
+        self.history.save();
+        self.queue_syntax_update();
+        self.history.finish();
+        self.ensure_every_cursor_is_valid();
+        Ok(())
+    }
+
+    /// Saves the buffer with `encoding` from now on. Returns the number of characters
+    /// that cannot be represented in `encoding`.
+    pub fn convert_encoding(&mut self, encoding: &'static Encoding) -> usize {
+        let unmappable = write::count_unmappable(encoding, &self.rope);
+        if encoding != self.encoding {
+            self.encoding = encoding;
+            self.bom &= write::bom_bytes(encoding).is_some();
+            self.mark_dirty();
+        }
+        unmappable
+    }
+
+    /// Adds or removes the byte order mark, returns false if the encoding has none
+    pub fn set_bom(&mut self, bom: bool) -> bool {
+        if bom && write::bom_bytes(self.encoding).is_none() {
+            return false;
+        }
+        if bom != self.bom {
+            self.bom = bom;
+            self.mark_dirty();
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrite_utility::line_ending::LineEnding;
+    use ropey::Rope;
+
+    use super::*;
+
+    #[test]
+    fn bom_round_trip() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("hé\n".encode_utf16().flat_map(u16::to_le_bytes));
+        let (encoding, bom, rope) = read::read_with_bom(&bytes[..]).unwrap();
+        assert_eq!(encoding, encoding_rs::UTF_16LE);
+        assert!(bom);
+        assert_eq!(rope.to_string(), "hé\n");
+
+        let mut out = Vec::new();
+        write::write_to(encoding, bom, LineEnding::LF, rope, &mut out).unwrap();
+        assert_eq!(out, bytes);
+
+        let (bom, rope) = read::decode_with(encoding_rs::WINDOWS_1252, b"caf\xe9");
+        assert!(!bom);
+        assert_eq!(rope.to_string(), "café");
+        assert_eq!(
+            write::count_unmappable(encoding_rs::WINDOWS_1252, &Rope::from_str("é€✓")),
+            1
+        );
+    }
+}
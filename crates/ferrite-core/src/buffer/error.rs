@@ -3,6 +3,8 @@ use std::{error::Error, fmt, io};
 #[derive(Debug)]
 pub enum BufferError {
     NoPathSet,
+    /// An edit was blocked because the buffer is read-only
+    ReadOnly,
     Io(io::Error),
 }
 
@@ -10,6 +12,7 @@ impl fmt::Display for BufferError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::NoPathSet => writeln!(f, "Error no path set"),
+            Self::ReadOnly => write!(f, "The buffer is read-only"),
             Self::Io(err) => err.fmt(f),
         }
     }
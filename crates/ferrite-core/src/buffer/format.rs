@@ -105,20 +105,25 @@ impl Buffer {
             return Ok(());
         }
 
-        self.history.begin(self.get_all_cursors(), self.dirty);
         let new_rope = format(formatter, self.rope.clone())?;
+        self.replace_transformed(&new_rope);
+        Ok(())
+    }
+
+    /// Replaces the whole text with the output of an external command, keeping the cursors where they were
+    pub fn replace_transformed(&mut self, text: &str) {
+        self.history.begin(self.get_all_cursors(), self.dirty);
 
         let cursor_positions = self.get_cursor_positions();
 
         let len = self.rope.len_bytes();
-        self.history.replace(&mut self.rope, 0..len, &new_rope);
+        self.history.replace(&mut self.rope, 0..len, text);
 
         self.restore_cursor_positions(cursor_positions);
 
         self.mark_dirty();
 
         self.history.finish();
-        Ok(())
     }
 
     pub fn format_selection(&mut self, view_id: ViewId, formatter: &str) -> Result<(), PopenError> {
@@ -1,9 +1,49 @@
 use super::{error::BufferError, Buffer, ViewId};
 use crate::cmd::Cmd;
 
+/// Commands that edit the text, they are blocked in read-only buffers
+fn is_edit(cmd: &Cmd) -> bool {
+    use Cmd::*;
+    matches!(
+        cmd,
+        MoveLine { .. }
+            | Insert { .. }
+            | Char { .. }
+            | Backspace
+            | BackspaceWord
+            | Delete
+            | DeleteWord
+            | RemoveLine
+            | DuplicateLines
+            | JoinLines
+            | SortLines { .. }
+            | ReverseLines
+            | Align { .. }
+            | Cut
+            | Paste
+            | PastePrimary { .. }
+            | TabOrIndent { .. }
+            | ReplaceCurrentMatch
+            | Undo
+            | Redo
+            | RevertBuffer
+            | InsertSequence { .. }
+            | IncrementNumber { .. }
+            | DecrementNumber { .. }
+            | TrimTrailingWhitespace
+            | NewLineWithoutBreaking
+            | NewLineAboveWithoutBreaking
+            | DeleteToEndOfLine
+            | BackspaceToStartOfLine
+    )
+}
+
 impl Buffer {
     pub fn handle_input(&mut self, view_id: ViewId, input: Cmd) -> Result<(), BufferError> {
         use Cmd::*;
+        if self.read_only && is_edit(&input) {
+            return Err(BufferError::ReadOnly);
+        }
         match input {
             MoveRight { expand_selection } => self.move_right_char(view_id, expand_selection),
             MoveLeft { expand_selection } => self.move_left_char(view_id, expand_selection),
@@ -19,13 +59,13 @@ impl Buffer {
             } => self.move_down(view_id, expand_selection, create_cursor, distance),
             MoveRightWord { expand_selection } => self.move_right_word(view_id, expand_selection),
             MoveLeftWord { expand_selection } => self.move_left_word(view_id, expand_selection),
-            MoveLine { direction } if !self.read_only => self.move_line(view_id, direction),
-            Insert { text } if !self.read_only => self.insert_text(view_id, &text, true),
-            Char { ch } if !self.read_only => self.insert_text(view_id, &String::from(ch), true),
-            Backspace if !self.read_only => self.backspace(view_id),
-            BackspaceWord if !self.read_only => self.backspace_word(view_id),
-            Delete if !self.read_only => self.delete(view_id),
-            DeleteWord if !self.read_only => self.delete_word(view_id),
+            MoveLine { direction } => self.move_line(view_id, direction),
+            Insert { text } => self.insert_text(view_id, &text, true),
+            Char { ch } => self.insert_text(view_id, &String::from(ch), true),
+            Backspace => self.backspace(view_id),
+            BackspaceWord => self.backspace_word(view_id),
+            Delete => self.delete(view_id),
+            DeleteWord => self.delete_word(view_id),
             Home { expand_selection } => self.home(view_id, expand_selection),
             End { expand_selection } => self.end(view_id, expand_selection),
             Eof { expand_selection } => self.eof(view_id, expand_selection),
@@ -33,15 +73,19 @@ impl Buffer {
             SelectAll => self.select_all(view_id),
             SelectWord => self.select_word(view_id),
             SelectLine => self.select_line(view_id),
-            RemoveLine if !self.read_only => self.remove_line(view_id),
+            RemoveLine => self.remove_line(view_id),
+            DuplicateLines => self.duplicate_lines(view_id),
+            JoinLines => self.join_lines(view_id),
+            SortLines { ascending, unique } => self.sort_lines(view_id, ascending, unique),
+            ReverseLines => self.reverse_lines(view_id),
+            Align { pattern } => self.align(view_id, &pattern),
             Copy => self.copy(view_id),
-            Cut if !self.read_only => self.cut(view_id),
-            Paste if !self.read_only => self.paste(view_id),
-            PastePrimary { column, line } if !self.read_only => {
-                self.paste_primary(view_id, column, line)
-            }
-            TabOrIndent { back } if !self.read_only => self.tab_or_indent(view_id, back),
+            Cut => self.cut(view_id),
+            Paste => self.paste(view_id),
+            PastePrimary { column, line } => self.paste_primary(view_id, column, line),
+            TabOrIndent { back } => self.tab_or_indent(view_id, back),
             VerticalScroll { distance } => self.vertical_scroll(view_id, distance),
+            HorizontalScroll { distance } => self.horizontal_scroll(view_id, distance),
             Escape => self.escape(view_id),
             ClickCell {
                 spawn_cursor,
@@ -49,29 +93,29 @@ impl Buffer {
                 line,
             } => self.handle_click(view_id, spawn_cursor, column, line),
             SelectArea { cursor, anchor } => self.select_area(view_id, cursor, anchor, true),
-            NextMatch => self.next_match(view_id),
-            PrevMatch => self.prev_match(view_id),
-            ReplaceCurrentMatch if !self.read_only => self.replace_current_match(view_id),
-            Undo if !self.read_only => self.undo(view_id),
-            Redo if !self.read_only => self.redo(view_id),
-            RevertBuffer if !self.read_only => self.revert_buffer(view_id),
-            Number { start } if !self.read_only => self.number(view_id, start),
-            TrimTrailingWhitespace if !self.read_only => self.trim_trailing_whitespace(),
-            NewLineWithoutBreaking if !self.read_only => self.new_line_without_breaking(view_id),
-            NewLineAboveWithoutBreaking if !self.read_only => {
-                self.new_line_above_without_breaking(view_id)
+            NextMatch => {
+                self.next_match(view_id);
             }
-            SelectAllMatching if !self.read_only => self.select_all_matching(view_id),
-            DeleteToEndOfLine if !self.read_only => self.delete_to_end_of_line(view_id),
-            BackspaceToStartOfLine if !self.read_only => self.backspace_to_start_of_line(view_id),
+            PrevMatch => {
+                self.prev_match(view_id);
+            }
+            ReplaceCurrentMatch => self.replace_current_match(view_id),
+            Undo => self.undo(view_id),
+            Redo => self.redo(view_id),
+            RevertBuffer => self.revert_buffer(view_id),
+            InsertSequence { start, step } => self.insert_sequence(view_id, start, step),
+            IncrementNumber { count } => self.increment_number(view_id, count),
+            DecrementNumber { count } => self.increment_number(view_id, count.saturating_neg()),
+            TrimTrailingWhitespace => self.trim_trailing_whitespace(),
+            NewLineWithoutBreaking => self.new_line_without_breaking(view_id),
+            NewLineAboveWithoutBreaking => self.new_line_above_without_breaking(view_id),
+            SelectAllMatching => self.select_all_matching(view_id),
+            DeleteToEndOfLine => self.delete_to_end_of_line(view_id),
+            BackspaceToStartOfLine => self.backspace_to_start_of_line(view_id),
             Nop => self.update_interact(Some(view_id)),
             _ => return Ok(()),
         }
 
-        if let Some(searcher) = &mut self.views[view_id].searcher {
-            searcher.update_buffer(self.rope.clone(), None);
-        }
-
         self.update_interact(Some(view_id));
 
         Ok(())
@@ -0,0 +1,114 @@
+use ferrite_utility::line_ending::{get_line_ending, LineEnding};
+use ropey::Rope;
+
+use super::Buffer;
+
+/// Line endings that are normalized, other unicode line breaks such as form feeds are kept
+fn is_normalized(line_ending: &LineEnding) -> bool {
+    matches!(
+        line_ending,
+        LineEnding::Crlf | LineEnding::LF | LineEnding::CR
+    )
+}
+
+/// Amount of lines ending with each line ending, the most common first
+pub fn count_line_endings(rope: &Rope) -> Vec<(LineEnding, usize)> {
+    let mut counts: Vec<(LineEnding, usize)> = Vec::new();
+    for line in rope.lines() {
+        let Some(line_ending) = get_line_ending(&line).filter(is_normalized) else {
+            continue;
+        };
+        match counts
+            .iter_mut()
+            .find(|(counted, _)| *counted == line_ending)
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((line_ending, 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1));
+    counts
+}
+
+/// The most common of `lf` and `crlf` in `rope`
+pub fn detect_line_ending(rope: &Rope) -> Option<LineEnding> {
+    count_line_endings(rope)
+        .into_iter()
+        .map(|(line_ending, _)| line_ending)
+        .find(|line_ending| matches!(line_ending, LineEnding::Crlf | LineEnding::LF))
+}
+
+impl Buffer {
+    /// Returns true if the lines of the buffer do not all end with the same line ending
+    pub fn has_mixed_line_endings(&self) -> bool {
+        count_line_endings(&self.rope).len() > 1
+    }
+
+    /// Replaces every line ending with the line ending of the buffer.
+    /// Returns the amount of lines that were changed.
+    pub fn normalize_line_endings(&mut self) -> usize {
+        let mut ranges = Vec::new();
+        for (line_idx, line) in self.rope.lines().enumerate() {
+            let Some(line_ending) = get_line_ending(&line).filter(is_normalized) else {
+                continue;
+            };
+            if line_ending != self.line_ending {
+                let end = self.rope.line_to_byte(line_idx) + line.len_bytes();
+                ranges.push(end - line_ending.as_str().len()..end);
+            }
+        }
+        if ranges.is_empty() {
+            return 0;
+        }
+
+        self.history.finish();
+        self.history.begin(self.get_all_cursors(), self.dirty);
+        // Only line endings change so the cursors keep their lines and columns
+        let cursor_positions = self.get_cursor_positions();
+        for range in ranges.iter().rev() {
+            self.history
+                .replace(&mut self.rope, range.clone(), self.line_ending.as_str());
+        }
+        self.restore_cursor_positions(cursor_positions);
+        self.mark_dirty();
+        self.history.finish();
+        ranges.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrite_utility::point::Point;
+
+    use super::*;
+
+    #[test]
+    fn normalize_line_endings() {
+        let mut buffer = Buffer::with_text("a\r\nb\nc\r\nd\x0c\r\n");
+        let view_id = buffer.create_view();
+        assert_eq!(detect_line_ending(buffer.rope()), Some(LineEnding::Crlf));
+        assert!(buffer.has_mixed_line_endings());
+
+        buffer.line_ending = LineEnding::Crlf;
+        buffer.select_area(view_id, Point::new(1, 2), Point::new(1, 2), false);
+        assert_eq!(buffer.normalize_line_endings(), 1);
+        assert_eq!(buffer.rope().to_string(), "a\r\nb\r\nc\r\nd\x0c\r\n");
+        assert!(!buffer.has_mixed_line_endings());
+        assert_eq!(buffer.views[view_id].cursors.first().position, 7);
+
+        buffer.line_ending = LineEnding::LF;
+        assert_eq!(buffer.normalize_line_endings(), 3);
+        assert_eq!(buffer.rope().to_string(), "a\nb\nc\nd\x0c\n");
+    }
+
+    #[test]
+    fn normalize_line_endings_is_own_undo_step() {
+        let mut buffer = Buffer::with_text("a\r\nb\n");
+        let view_id = buffer.create_view();
+        buffer.line_ending = LineEnding::LF;
+        buffer.insert_text(view_id, "x", false);
+        assert_eq!(buffer.normalize_line_endings(), 1);
+        buffer.undo(view_id);
+        assert_eq!(buffer.rope().to_string(), "xa\r\nb\n");
+    }
+}
@@ -0,0 +1,359 @@
+use std::ops::RangeInclusive;
+
+use ferrite_utility::{graphemes::RopeGraphemeExt, point::Point};
+use regex::Regex;
+
+use super::{Buffer, ViewId};
+use crate::cmd::LineMoveDir;
+
+/// Lines replaced by a line command
+struct LineEdit {
+    /// Lines that are replaced
+    lines: RangeInclusive<usize>,
+    /// The new lines without line endings
+    text: Vec<String>,
+    /// Lines covered by cursors
+    block: RangeInclusive<usize>,
+    /// Where the block starts in `text`
+    block_start: usize,
+    /// Length of the block in `text`
+    block_len: usize,
+}
+
+impl LineEdit {
+    /// An edit where the cursor lines are replaced by `text`
+    fn block(lines: RangeInclusive<usize>, text: Vec<String>) -> Self {
+        Self {
+            block: lines.clone(),
+            lines,
+            block_start: 0,
+            block_len: text.len(),
+            text,
+        }
+    }
+
+    fn line_diff(&self) -> isize {
+        self.text.len() as isize - self.lines.clone().count() as isize
+    }
+}
+
+/// Where a point ends up after `edits` have been applied
+fn map_point(edits: &[LineEdit], point: Point<usize>) -> Point<usize> {
+    let mut line_diff = 0;
+    for edit in edits {
+        let new_start = (*edit.lines.start() as isize + line_diff) as usize + edit.block_start;
+        if edit.block.contains(&point.line) {
+            let line = (point.line - edit.block.start()).min(edit.block_len.saturating_sub(1));
+            return Point::new(point.column, new_start + line);
+        }
+        // A selection ending at the start of the line after the block
+        if point.line == edit.block.end() + 1 && point.column == 0 {
+            return Point::new(0, new_start + edit.block_len);
+        }
+        if point.line < *edit.lines.start() {
+            break;
+        }
+        if edit.lines.contains(&point.line) {
+            return Point::new(point.column, new_start);
+        }
+        line_diff += edit.line_diff();
+    }
+    Point::new(point.column, (point.line as isize + line_diff) as usize)
+}
+
+/// Joins lines into one, separated by a single space
+fn join(lines: &[String]) -> String {
+    let mut joined = lines[0].clone();
+    for line in &lines[1..] {
+        let line = line.trim_start();
+        if line.is_empty() {
+            continue;
+        }
+        if !joined.is_empty() && !joined.ends_with(char::is_whitespace) {
+            joined.push(' ');
+        }
+        joined.push_str(line);
+    }
+    joined
+}
+
+impl Buffer {
+    /// The last line with content, the empty line after a trailing line ending is left out
+    fn last_content_line(&self) -> usize {
+        let last = self.rope.len_lines().saturating_sub(1);
+        if last > 0 && self.rope.line(last).len_bytes() == 0 {
+            last - 1
+        } else {
+            last
+        }
+    }
+
+    /// Lines covered by the cursors of a view in order, touching blocks are merged.
+    /// A selection ending at the start of a line does not include that line.
+    fn cursor_line_blocks(&self, view_id: ViewId) -> Vec<RangeInclusive<usize>> {
+        let last_line = self.last_content_line();
+        let mut blocks: Vec<_> = self.views[view_id]
+            .cursors
+            .iter()
+            .map(|cursor| {
+                let start = self.rope.byte_to_line(cursor.start());
+                let mut end = self.rope.byte_to_line(cursor.end());
+                if end > start && self.rope.line_to_byte(end) == cursor.end() {
+                    end -= 1;
+                }
+                start.min(last_line)..=end.min(last_line)
+            })
+            .collect();
+        blocks.sort_by_key(|block| *block.start());
+
+        let mut merged: Vec<RangeInclusive<usize>> = Vec::new();
+        for block in blocks {
+            match merged.last_mut() {
+                Some(last) if *block.start() <= last.end() + 1 => {
+                    *last = *last.start()..=*last.end().max(block.end());
+                }
+                _ => merged.push(block),
+            }
+        }
+        merged
+    }
+
+    fn lines_text(&self, lines: RangeInclusive<usize>) -> Vec<String> {
+        lines
+            .map(|line_idx| self.rope.line_without_line_ending(line_idx).to_string())
+            .collect()
+    }
+
+    /// Applies `edits` as a single history entry, `edits` must be in order and not overlap
+    fn apply_line_edits(&mut self, view_id: ViewId, edits: Vec<LineEdit>) {
+        let edits: Vec<_> = edits
+            .into_iter()
+            .filter(|edit| edit.text != self.lines_text(edit.lines.clone()))
+            .collect();
+        if edits.is_empty() {
+            return;
+        }
+
+        let mut cursor_positions = self.get_cursor_positions();
+        if let Some(positions) = cursor_positions.get_mut(view_id) {
+            for (pos, anchor) in positions {
+                *pos = map_point(&edits, *pos);
+                *anchor = map_point(&edits, *anchor);
+            }
+        }
+
+        self.history.begin(self.get_all_cursors(), self.dirty);
+        // Replacing from the end keeps the line indices of the earlier edits valid
+        for edit in edits.iter().rev() {
+            let start_byte_idx = self.rope.line_to_byte(*edit.lines.start());
+            let end_byte_idx = self.rope.line_to_byte(*edit.lines.end())
+                + self
+                    .rope
+                    .line_without_line_ending(*edit.lines.end())
+                    .len_bytes();
+            self.history.replace(
+                &mut self.rope,
+                start_byte_idx..end_byte_idx,
+                edit.text.join(self.line_ending.as_str()),
+            );
+        }
+
+        self.restore_cursor_positions(cursor_positions);
+        self.update_affinity(view_id);
+        if self.views[view_id].clamp_cursor {
+            self.center_on_cursor(view_id);
+        }
+        self.mark_dirty();
+        self.history.finish();
+    }
+
+    /// Swaps the lines of every cursor with the line above or below them
+    pub fn move_line(&mut self, view_id: ViewId, dir: LineMoveDir) {
+        let blocks = self.cursor_line_blocks(view_id);
+        let last_line = self.last_content_line();
+        let at_edge = match dir {
+            LineMoveDir::Up => blocks.first().is_some_and(|block| *block.start() == 0),
+            LineMoveDir::Down => blocks.last().is_some_and(|block| *block.end() >= last_line),
+        };
+        if at_edge {
+            return;
+        }
+
+        let edits = blocks
+            .into_iter()
+            .map(|block| {
+                let mut text = self.lines_text(block.clone());
+                let block_len = text.len();
+                match dir {
+                    LineMoveDir::Up => {
+                        let above = block.start() - 1;
+                        text.push(self.rope.line_without_line_ending(above).to_string());
+                        LineEdit {
+                            lines: above..=*block.end(),
+                            text,
+                            block,
+                            block_start: 0,
+                            block_len,
+                        }
+                    }
+                    LineMoveDir::Down => {
+                        let below = block.end() + 1;
+                        text.insert(0, self.rope.line_without_line_ending(below).to_string());
+                        LineEdit {
+                            lines: *block.start()..=below,
+                            text,
+                            block,
+                            block_start: 1,
+                            block_len,
+                        }
+                    }
+                }
+            })
+            .collect();
+        self.apply_line_edits(view_id, edits);
+    }
+
+    /// Inserts a copy of the lines of every cursor below them and moves the cursors to the copy
+    pub fn duplicate_lines(&mut self, view_id: ViewId) {
+        let edits = self
+            .cursor_line_blocks(view_id)
+            .into_iter()
+            .map(|block| {
+                let lines = self.lines_text(block.clone());
+                let block_len = lines.len();
+                LineEdit {
+                    lines: block.clone(),
+                    text: [lines.clone(), lines].concat(),
+                    block,
+                    block_start: block_len,
+                    block_len,
+                }
+            })
+            .collect();
+        self.apply_line_edits(view_id, edits);
+    }
+
+    /// Joins the selected lines, a cursor without a selection joins its line with the next one
+    pub fn join_lines(&mut self, view_id: ViewId) {
+        let last_line = self.last_content_line();
+        let edits = self
+            .cursor_line_blocks(view_id)
+            .into_iter()
+            .filter_map(|block| {
+                let lines = if block.start() == block.end() {
+                    if *block.end() >= last_line {
+                        return None;
+                    }
+                    *block.start()..=block.end() + 1
+                } else {
+                    block.clone()
+                };
+                let text = vec![join(&self.lines_text(lines.clone()))];
+                Some(LineEdit {
+                    lines,
+                    text,
+                    block,
+                    block_start: 0,
+                    block_len: 1,
+                })
+            })
+            .collect();
+        self.apply_line_edits(view_id, edits);
+    }
+
+    /// Sorts the selected lines of every cursor, ignoring leading whitespace
+    pub fn sort_lines(&mut self, view_id: ViewId, asc: bool, unique: bool) {
+        let edits = self
+            .cursor_line_blocks(view_id)
+            .into_iter()
+            .filter(|block| block.start() != block.end())
+            .map(|block| {
+                let mut lines = self.lines_text(block.clone());
+                lines.sort_by(|lhs, rhs| {
+                    let ordering =
+                        lexical_sort::natural_lexical_cmp(lhs.trim_start(), rhs.trim_start());
+                    if asc {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                });
+                if unique {
+                    lines.dedup();
+                }
+                LineEdit::block(block, lines)
+            })
+            .collect();
+        self.apply_line_edits(view_id, edits);
+    }
+
+    /// Reverses the order of the selected lines of every cursor
+    pub fn reverse_lines(&mut self, view_id: ViewId) {
+        let edits = self
+            .cursor_line_blocks(view_id)
+            .into_iter()
+            .filter(|block| block.start() != block.end())
+            .map(|block| {
+                let mut lines = self.lines_text(block.clone());
+                lines.reverse();
+                LineEdit::block(block, lines)
+            })
+            .collect();
+        self.apply_line_edits(view_id, edits);
+    }
+
+    /// Pads the first match of `pattern` on every cursor line with spaces so the matches
+    /// start on the same column. `pattern` is matched literally if it is not a valid regex.
+    pub fn align(&mut self, view_id: ViewId, pattern: &str) {
+        let Ok(regex) = Regex::new(pattern).or_else(|_| Regex::new(&regex::escape(pattern))) else {
+            return;
+        };
+
+        let mut matches = Vec::new();
+        for line_idx in self.cursor_line_blocks(view_id).into_iter().flatten() {
+            let line = self.rope.line_without_line_ending(line_idx);
+            let Some(found) = regex.find(&line.to_string()).map(|found| found.start()) else {
+                continue;
+            };
+            let column = line.byte_slice(..found).width(0);
+            matches.push((self.rope.line_to_byte(line_idx) + found, column));
+        }
+        let Some(target) = matches.iter().map(|(_, column)| *column).max() else {
+            return;
+        };
+        let padding: Vec<_> = matches
+            .into_iter()
+            .filter(|(_, column)| *column < target)
+            .map(|(byte_idx, column)| (byte_idx, target - column))
+            .collect();
+        if padding.is_empty() {
+            return;
+        }
+
+        self.history.begin(self.get_all_cursors(), self.dirty);
+        for (byte_idx, width) in padding.iter().rev() {
+            self.history
+                .insert(&mut self.rope, *byte_idx, " ".repeat(*width));
+        }
+        let shift = |byte_idx: usize| {
+            byte_idx
+                + padding
+                    .iter()
+                    .take_while(|(padded_idx, _)| *padded_idx < byte_idx)
+                    .map(|(_, width)| width)
+                    .sum::<usize>()
+        };
+        for cursor in self.views[view_id].cursors.iter_mut() {
+            cursor.position = shift(cursor.position);
+            cursor.anchor = shift(cursor.anchor);
+        }
+
+        self.update_affinity(view_id);
+        if self.views[view_id].clamp_cursor {
+            self.center_on_cursor(view_id);
+        }
+        self.mark_dirty();
+        self.ensure_every_cursor_is_valid();
+        self.history.finish();
+    }
+}
@@ -0,0 +1,237 @@
+use std::ops::Range;
+
+use ferrite_utility::graphemes::RopeGraphemeExt;
+
+use super::{Buffer, ViewId};
+
+/// Finds the integer literal under `byte_idx` in `line` or the first one after it.
+/// Hex (`0x`) and binary (`0b`) literals are supported, decimal literals can be negative.
+pub fn find_number(line: &str, byte_idx: usize) -> Option<Range<usize>> {
+    let bytes = line.as_bytes();
+    let digits_from = |start: usize, is_digit: fn(&u8) -> bool| {
+        start + bytes[start..].iter().take_while(|b| is_digit(b)).count()
+    };
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+
+        let prefixed = |prefix: &[u8], is_digit: fn(&u8) -> bool| {
+            bytes[i] == b'0'
+                && bytes
+                    .get(i + 1)
+                    .is_some_and(|b| prefix.contains(&b.to_ascii_lowercase()))
+                && bytes.get(i + 2).is_some_and(is_digit)
+        };
+        let (start, end) = if prefixed(b"x", u8::is_ascii_hexdigit) {
+            (i, digits_from(i + 2, u8::is_ascii_hexdigit))
+        } else if prefixed(b"b", |b| matches!(*b, b'0' | b'1')) {
+            (i, digits_from(i + 2, |b| matches!(*b, b'0' | b'1')))
+        } else {
+            let end = digits_from(i, u8::is_ascii_digit);
+            // A minus directly after a word is a subtraction and not a sign
+            let negative = i > 0
+                && bytes[i - 1] == b'-'
+                && !(i > 1 && (bytes[i - 2].is_ascii_alphanumeric() || bytes[i - 2] == b'_'));
+            (if negative { i - 1 } else { i }, end)
+        };
+
+        if end > byte_idx {
+            return Some(start..end);
+        }
+        i = end;
+    }
+    None
+}
+
+/// Adds `amount` to an integer literal found by [`find_number`].
+/// The radix, the case of hex digits and zero padding are kept.
+pub fn increment(literal: &str, amount: i64) -> Option<String> {
+    let (prefix, digits, radix) = match literal.get(..2) {
+        Some("0x" | "0X") => (&literal[..2], &literal[2..], 16),
+        Some("0b" | "0B") => (&literal[..2], &literal[2..], 2),
+        _ => ("", literal, 10),
+    };
+
+    if radix == 10 {
+        let value: i64 = literal.parse().ok()?;
+        let new = value.checked_add(amount)?;
+        let digits = digits.trim_start_matches('-');
+        let width = if digits.len() > 1 && digits.starts_with('0') {
+            digits.len()
+        } else {
+            0
+        };
+        let sign = if new < 0 { "-" } else { "" };
+        return Some(format!("{sign}{:0width$}", new.unsigned_abs()));
+    }
+
+    let value = u64::from_str_radix(digits, radix).ok()?;
+    let new = value.wrapping_add_signed(amount);
+    let width = digits.len();
+    let digits = match radix {
+        16 if digits.bytes().any(|b| b.is_ascii_uppercase()) => format!("{new:0width$X}"),
+        16 => format!("{new:0width$x}"),
+        _ => format!("{new:0width$b}"),
+    };
+    Some(format!("{prefix}{digits}"))
+}
+
+impl Buffer {
+    /// Adds `amount` to the integer under or after every cursor on its line
+    /// and moves the cursors to the start of the numbers
+    pub fn increment_number(&mut self, view_id: ViewId, amount: i64) {
+        self.views[view_id].coalesce_cursors();
+
+        let mut edits: Vec<(usize, Range<usize>, String)> = Vec::new();
+        for (cursor, i) in self.get_cursors_sorted(view_id) {
+            let line_idx = self.rope.byte_to_line(cursor.position);
+            let line_start = self.rope.line_to_byte(line_idx);
+            let line = self.rope.line_without_line_ending(line_idx).to_string();
+            let Some(range) = find_number(&line, cursor.position - line_start) else {
+                continue;
+            };
+            let Some(text) = increment(&line[range.clone()], amount) else {
+                continue;
+            };
+            edits.push((i, line_start + range.start..line_start + range.end, text));
+        }
+        // Several cursors can find the same number
+        edits.dedup_by(|a, b| a.1 == b.1);
+        if edits.is_empty() {
+            return;
+        }
+
+        self.history.begin(self.get_all_cursors(), self.dirty);
+        for (_, range, text) in edits.iter().rev() {
+            self.history
+                .replace(&mut self.rope, range.clone(), text.as_str());
+        }
+
+        let shift = |byte_idx: usize| {
+            let mut diff = 0;
+            for (_, range, text) in &edits {
+                if byte_idx < range.start {
+                    break;
+                }
+                if byte_idx < range.end {
+                    return (range.start as isize + diff) as usize;
+                }
+                diff += text.len() as isize - range.len() as isize;
+            }
+            (byte_idx as isize + diff) as usize
+        };
+        let view = &mut self.views[view_id];
+        for cursor in view.cursors.iter_mut() {
+            cursor.position = shift(cursor.position);
+            cursor.anchor = shift(cursor.anchor);
+        }
+        for (i, range, _) in &edits {
+            let cursor = &mut view.cursors[*i];
+            cursor.position = shift(range.start);
+            cursor.anchor = cursor.position;
+        }
+
+        self.update_affinity(view_id);
+        if self.views[view_id].clamp_cursor {
+            self.center_on_cursor(view_id);
+        }
+        self.mark_dirty();
+        self.ensure_every_cursor_is_valid();
+        self.history.finish();
+    }
+
+    /// Inserts `start`, `start + step`, `start + 2 * step`... at the cursors in order,
+    /// replacing the selections
+    pub fn insert_sequence(&mut self, view_id: ViewId, start: Option<i64>, step: Option<i64>) {
+        self.history.begin(self.get_all_cursors(), self.dirty);
+
+        self.views[view_id].coalesce_cursors();
+        let cursors = self.get_cursors_sorted(view_id);
+        let start = start.unwrap_or(0);
+        let step = step.unwrap_or(1);
+        for (cursor_loop_index, (_, i)) in cursors.iter().copied().enumerate() {
+            let before_len_bytes = self.rope.len_bytes();
+
+            let text = (start + cursor_loop_index as i64 * step).to_string();
+            let inserted_bytes = text.len();
+            if self.views[view_id].cursors[i].has_selection() {
+                let start_byte_idx = self.views[view_id].cursors[i].start();
+                let end_byte_idx = self.views[view_id].cursors[i].end();
+
+                self.history
+                    .replace(&mut self.rope, start_byte_idx..end_byte_idx, text);
+                self.views[view_id].cursors[i].position = self.views[view_id].cursors[i].start();
+                self.views[view_id].cursors[i].anchor = self.views[view_id].cursors[i].position;
+            } else {
+                self.history.insert(
+                    &mut self.rope,
+                    self.views[view_id].cursors[i].position,
+                    text,
+                );
+            };
+
+            self.views[view_id].cursors[i].position += inserted_bytes;
+            self.views[view_id].cursors[i].anchor = self.views[view_id].cursors[i].position;
+
+            let after_len_bytes = self.rope.len_bytes();
+            let diff_len_bytes = after_len_bytes as i64 - before_len_bytes as i64;
+            for (_, i) in cursors.iter().copied().skip(cursor_loop_index + 1) {
+                let cursor = &mut self.views[view_id].cursors[i];
+                cursor.position = (cursor.position as i64 + diff_len_bytes) as usize;
+                cursor.anchor = (cursor.anchor as i64 + diff_len_bytes) as usize;
+            }
+        }
+
+        if self.views[view_id].clamp_cursor {
+            self.center_on_cursor(view_id);
+        }
+
+        self.update_affinity(view_id);
+        self.mark_dirty();
+        self.ensure_every_cursor_is_valid();
+
+        self.history.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrite_utility::vec1::Vec1;
+
+    use super::*;
+    use crate::buffer::Cursor;
+
+    #[test]
+    fn increment_numbers() {
+        assert_eq!(find_number("let x = 41;", 0), Some(8..10));
+        assert_eq!(find_number("x = -5", 0), Some(4..6));
+        assert_eq!(find_number("a-5", 0), Some(2..3));
+        assert_eq!(find_number("0xff 12", 2), Some(0..4));
+        assert_eq!(find_number("0xff 12", 4), Some(5..7));
+        assert_eq!(find_number("none", 0), None);
+
+        assert_eq!(increment("41", 1).as_deref(), Some("42"));
+        assert_eq!(increment("-1", 3).as_deref(), Some("2"));
+        assert_eq!(increment("007", 1).as_deref(), Some("008"));
+        assert_eq!(increment("0x0F", 1).as_deref(), Some("0x10"));
+        assert_eq!(increment("0xff", 1).as_deref(), Some("0x100"));
+        assert_eq!(increment("0b0110", -1).as_deref(), Some("0b0101"));
+
+        let mut buffer = Buffer::with_text("a 9\nb 0x0e\n");
+        let view_id = buffer.get_first_view_or_create();
+        let cursor = |position| Cursor {
+            position,
+            anchor: position,
+            affinity: 0,
+        };
+        buffer.views[view_id].cursors = Vec1::from_vec(vec![cursor(0), cursor(4)]).unwrap();
+        buffer.increment_number(view_id, 1);
+        assert_eq!(buffer.rope().to_string(), "a 10\nb 0x0f\n");
+        assert_eq!(buffer.views[view_id].cursors[0].position, 2);
+        assert_eq!(buffer.views[view_id].cursors[1].position, 7);
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use ferrite_utility::point::Point;
+
+use super::{Buffer, ViewId};
+use crate::language::outline::{self, Symbol};
+
+impl Buffer {
+    /// Symbols of the buffer, updated in the background after the text changes
+    pub fn outline(&self) -> Arc<Vec<Symbol>> {
+        match &self.syntax {
+            Some(syntax) => syntax.outline(),
+            None => Arc::default(),
+        }
+    }
+
+    /// True if the language of the buffer has an outline query
+    pub fn has_outline(&self) -> bool {
+        self.syntax
+            .as_ref()
+            .and_then(|syntax| syntax.get_config())
+            .is_some_and(|config| config.outline_query.is_some())
+    }
+
+    /// Symbols containing the primary cursor, outermost first
+    pub fn breadcrumbs(&self, view_id: ViewId) -> Vec<Symbol> {
+        let byte_idx = self.views[view_id].cursors.first().position;
+        outline::symbols_at(&self.outline(), byte_idx)
+    }
+
+    /// Moves the cursor to the start of the symbol containing it,
+    /// or to the start of its parent if the cursor is already on the first line of the symbol
+    pub fn goto_parent_symbol(&mut self, view_id: ViewId) -> bool {
+        let line_idx = self.cursor_line_idx(view_id, 0);
+        let Some(line) = self
+            .breadcrumbs(view_id)
+            .iter()
+            .rev()
+            .find(|symbol| symbol.line < line_idx)
+            .map(|symbol| symbol.line)
+        else {
+            return false;
+        };
+        self.jump_to(view_id, Point::new(0, line));
+        true
+    }
+}
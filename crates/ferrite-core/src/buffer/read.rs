@@ -7,7 +7,15 @@ use std::{
 use encoding_rs::{CoderResult, Encoding};
 use ropey::{Rope, RopeBuilder};
 
-pub fn read(mut reader: impl io::Read) -> Result<(&'static Encoding, Rope), io::Error> {
+pub fn read(reader: impl io::Read) -> Result<(&'static Encoding, Rope), io::Error> {
+    read_with_bom(reader).map(|(encoding, _, rope)| (encoding, rope))
+}
+
+/// Reads text with a detected encoding, a byte order mark decides the encoding
+/// and is removed. Returns if the text started with a byte order mark.
+pub fn read_with_bom(
+    mut reader: impl io::Read,
+) -> Result<(&'static Encoding, bool, Rope), io::Error> {
     const BUFFER_SIZE: usize = 8192;
     let mut encoding_detector = chardetng::EncodingDetector::new();
     let mut content = Vec::new();
@@ -24,7 +32,15 @@ pub fn read(mut reader: impl io::Read) -> Result<(&'static Encoding, Rope), io::
         }
     };
 
-    let mut decoder = encoding.new_decoder();
+    let (encoding, bom) = match Encoding::for_bom(&content) {
+        Some((encoding, bom_len)) => {
+            content.drain(..bom_len);
+            (encoding, true)
+        }
+        None => (encoding, false),
+    };
+
+    let mut decoder = encoding.new_decoder_without_bom_handling();
     let mut rope_builder = RopeBuilder::new();
     let mut output = String::with_capacity(BUFFER_SIZE);
 
@@ -57,7 +73,18 @@ pub fn read(mut reader: impl io::Read) -> Result<(&'static Encoding, Rope), io::
 
     let rope = rope_builder.finish();
 
-    Ok((encoding, rope))
+    Ok((encoding, bom, rope))
+}
+
+/// Decodes `bytes` with `encoding` instead of detecting it, a byte order mark of `encoding` is removed.
+/// Returns if the text started with a byte order mark.
+pub fn decode_with(encoding: &'static Encoding, bytes: &[u8]) -> (bool, Rope) {
+    let (bom, bytes) = match Encoding::for_bom(bytes) {
+        Some((bom_encoding, bom_len)) if bom_encoding == encoding => (true, &bytes[bom_len..]),
+        _ => (false, bytes),
+    };
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    (bom, Rope::from_str(&text))
 }
 
 pub fn read_from_file(path: impl AsRef<Path>) -> Result<(&'static Encoding, Rope), io::Error> {
@@ -1,206 +1,338 @@
-use std::{
-    sync::{mpsc, Arc, Mutex},
-    thread,
-};
+use std::{mem, ops::Range};
 
-use ferrite_utility::{graphemes::RopeGraphemeExt as _, point::Point};
+pub use ferrite_text::search::{
+    compare_char, find_matches, search_rope, SearchMatch, SearchOptions, SearchStep,
+};
+use ferrite_utility::graphemes::RopeGraphemeExt as _;
 use ropey::{Rope, RopeSlice};
 
-use crate::event_loop_proxy::EventLoopProxy;
+use crate::{
+    job_manager::{CancellationToken, JobHandle, JobInfo, JobManager, Progress, Progressor},
+    tr,
+};
 
-#[derive(Debug, Clone, Copy)]
-pub struct SearchMatch {
-    pub start: Point<usize>,
-    pub end: Point<usize>,
-    pub start_byte: usize,
-    pub end_byte: usize,
-}
+/// Buffers larger than this are searched in jobs instead of on the main thread,
+/// searching anything smaller takes well below a frame
+pub const ASYNC_SEARCH_BYTES: usize = 32 * 1024;
 
-enum QueryUpdate {
-    Rope(Rope, Option<bool>),
-    Query(String, bool, usize),
-}
+/// Max amount of matches sent back by a search job at once
+const CHUNK_MATCHES: usize = 1024;
+
+/// Matches found by a search job along with the generation of the search they belong to
+pub type MatchChunk = (u64, Vec<SearchMatch>);
+
+/// Match index before a match has been selected, the next match after it is the first one
+const NO_MATCH: usize = usize::MAX - 1;
 
 pub struct BufferSearcher {
-    matches: Arc<Mutex<(Vec<SearchMatch>, Option<usize>)>>,
-    last_rope: Rope,
+    query: String,
+    options: SearchOptions,
+    rope: Rope,
+    revision: u64,
+    matches: Vec<SearchMatch>,
+    /// Index of the first match after the cursor the query was entered at, used by the next jump
+    first_match: Option<usize>,
+    /// Cursor position of the last query until the first match after it has been found
+    cursor_pos: Option<usize>,
+    /// Bumped for every new query or text, matches of older generations are discarded
+    generation: u64,
+    /// Generation of the search `matches` came from
+    matches_generation: u64,
+    /// The current generation still has to be searched by a job
+    pending: bool,
+    job: Option<JobHandle<Option<u64>, MatchChunk>>,
     match_index: usize,
-    tx: mpsc::Sender<QueryUpdate>,
+    wrapped: bool,
 }
 
 impl BufferSearcher {
     pub fn new(
-        proxy: Box<dyn EventLoopProxy>,
         query: String,
         rope: Rope,
-        case_insensitive: bool,
+        revision: u64,
+        options: SearchOptions,
         cursor_pos: usize,
     ) -> Self {
-        let matches = Arc::new(Mutex::new((Vec::new(), None)));
-        let (tx, rx) = mpsc::channel();
-        let _ = tx.send(QueryUpdate::Rope(rope.clone(), Some(case_insensitive)));
-        let thread_rope = rope.clone();
-
-        let thread_matches = matches.clone();
-        thread::spawn(move || {
-            tracing::info!("search thread spawned");
-            let matches = thread_matches;
-            let mut query = query;
-            let mut rope = thread_rope;
-            let mut case_insensitive = case_insensitive;
-            let mut cursor_pos = Some(cursor_pos);
-
-            // TODO don't block on every update do batch reciving
-            while let Ok(update) = rx.recv() {
-                match update {
-                    QueryUpdate::Rope(r, case) => {
-                        if let Some(case) = case {
-                            case_insensitive = case;
-                        }
-                        rope = r;
-                    }
-                    QueryUpdate::Query(q, case, cursor) => {
-                        case_insensitive = case;
-                        query = q;
-                        cursor_pos = Some(cursor);
-                    }
-                }
-
-                let match_buffer =
-                    search_rope(rope.slice(..), query.clone(), case_insensitive, false);
-
-                let mut index = match cursor_pos.take() {
-                    Some(cursor_pos) => {
-                        let mut index = 0;
-                        for (i, m) in match_buffer.iter().enumerate() {
-                            if m.end_byte > cursor_pos {
-                                index = i;
-                                break;
-                            }
-                        }
-                        Some(index)
-                    }
-                    None => None,
-                };
-
-                {
-                    let mut guard = matches.lock().unwrap();
-                    guard.0.clear();
-                    guard.0.extend_from_slice(&match_buffer);
-                    if index.is_some() {
-                        guard.1 = index.take();
-                    }
-                }
-
-                proxy.request_render();
-            }
-            tracing::info!("search thread exit");
-        });
-
-        Self {
-            matches,
-            tx,
-            last_rope: rope,
-            match_index: usize::MAX - 1,
-        }
+        let mut searcher = Self {
+            query,
+            options,
+            rope,
+            revision,
+            matches: Vec::new(),
+            first_match: None,
+            cursor_pos: Some(cursor_pos),
+            generation: 0,
+            matches_generation: 0,
+            pending: false,
+            job: None,
+            match_index: NO_MATCH,
+            wrapped: false,
+        };
+        searcher.restart();
+        searcher
     }
 
     pub fn get_next_match(&mut self) -> Option<SearchMatch> {
-        let mut guard = self.matches.lock().unwrap();
-        if let Some(index) = guard.1.take() {
-            self.match_index = index.min(guard.0.len().saturating_sub(1));
+        self.wrapped = false;
+        if let Some(index) = self.first_match.take() {
+            self.match_index = index.min(self.matches.len().saturating_sub(1));
         } else {
-            self.match_index += 1;
-            if self.match_index >= guard.0.len() {
+            self.wrapped = self.match_index + 1 == self.matches.len();
+            self.match_index = self.match_index.saturating_add(1);
+            if self.match_index >= self.matches.len() {
                 self.match_index = 0;
             }
         }
-        guard.0.get(self.match_index).copied()
+        self.matches.get(self.match_index).copied()
     }
 
     pub fn get_prev_match(&mut self) -> Option<SearchMatch> {
-        let mut guard = self.matches.lock().unwrap();
-        if let Some(index) = guard.1.take() {
-            self.match_index = index.min(guard.0.len().saturating_sub(1));
+        self.wrapped = false;
+        if let Some(index) = self.first_match.take() {
+            self.match_index = index.min(self.matches.len().saturating_sub(1));
         } else if self.match_index == 0 {
-            self.match_index = guard.0.len().saturating_sub(1);
+            self.wrapped = !self.matches.is_empty();
+            self.match_index = self.matches.len().saturating_sub(1);
         } else {
             self.match_index = self.match_index.saturating_sub(1);
         }
-        guard.0.get(self.match_index).copied()
+        self.matches.get(self.match_index).copied()
+    }
+
+    /// True if the last call to `get_next_match` or `get_prev_match` wrapped around the buffer
+    pub fn wrapped(&self) -> bool {
+        self.wrapped
+    }
+
+    /// Index of the current match, if one has been selected, and the total amount of matches
+    pub fn match_position(&self) -> (Option<usize>, usize) {
+        let len = self.matches.len();
+        ((self.match_index < len).then_some(self.match_index), len)
     }
 
     pub fn get_current_match(&mut self) -> Option<SearchMatch> {
-        self.matches
-            .lock()
-            .unwrap()
-            .0
-            .get(self.match_index)
-            .copied()
+        self.matches.get(self.match_index).copied()
     }
 
-    pub fn update_query(&mut self, query: String, case_insensitive: bool, cursor_pos: usize) {
-        let _ = self
-            .tx
-            .send(QueryUpdate::Query(query, case_insensitive, cursor_pos));
+    pub fn update_query(&mut self, query: String, options: SearchOptions, cursor_pos: usize) {
+        self.query = query;
+        self.options = options;
+        self.cursor_pos = Some(cursor_pos);
+        self.match_index = NO_MATCH;
+        self.restart();
     }
 
-    pub fn update_buffer(&mut self, rope: Rope, case_insensitive: Option<bool>) {
-        if !self.last_rope.is_instance(&rope) {
-            let _ = self.tx.send(QueryUpdate::Rope(rope, case_insensitive));
+    /// Searches the new text unless it is the same revision that was searched last
+    pub fn update_buffer(&mut self, rope: Rope, revision: u64, options: Option<SearchOptions>) {
+        if self.revision != revision {
+            self.revision = revision;
+            let len = rope.len_bytes();
+            for m in &mut self.matches {
+                m.start = rope.byte_to_point(m.start_byte.min(len));
+                m.end = rope.byte_to_point(m.end_byte.min(len));
+            }
+            if let Some(options) = options {
+                self.options = options;
+            }
+            self.rope = rope;
+            self.restart();
         }
     }
 
-    pub fn get_matches(&self) -> Arc<Mutex<(Vec<SearchMatch>, Option<usize>)>> {
-        self.matches.clone()
+    /// Starts a new generation of the search. Small buffers are searched right away,
+    /// large buffers are searched by a job started on the next `poll`.
+    fn restart(&mut self) {
+        self.generation += 1;
+        if let Some(job) = self.job.take() {
+            job.cancel();
+        }
+        if self.rope.len_bytes() > ASYNC_SEARCH_BYTES {
+            self.pending = true;
+        } else {
+            self.pending = false;
+            let matches = search_rope(self.rope.slice(..), self.query.clone(), self.options, false);
+            self.receive(self.generation, matches);
+            self.finish(self.generation);
+        }
     }
-}
 
-pub fn search_rope(
-    rope: RopeSlice,
-    query: String,
-    case_insensitive: bool,
-    stop_at_first: bool,
-) -> Vec<SearchMatch> {
-    let mut matches = Vec::new();
-    let chars: Vec<_> = query.chars().collect();
-    let mut query_idx = 0;
-    let mut current_char = 1;
-
-    for ch in rope.chars() {
-        if compare_char(&ch, &chars[query_idx], case_insensitive) {
-            query_idx += 1;
-        } else {
-            query_idx = 0;
-            if compare_char(&ch, &chars[query_idx], case_insensitive) {
-                query_idx += 1;
+    /// Starts the pending search and receives the matches found by the running job
+    pub fn poll(&mut self, job_manager: &mut JobManager) {
+        if self.pending {
+            self.pending = false;
+            let generation = self.generation;
+            self.job = Some(job_manager.spawn_foreground_job(
+                JobInfo::new(tr!("job.search_buffer", query = &self.query)).cancellable(),
+                move |token, progressor, (rope, query, options): (Rope, String, SearchOptions)| {
+                    stream_search(
+                        rope.slice(..),
+                        &query,
+                        options,
+                        generation,
+                        &token,
+                        progressor,
+                    )
+                    .then_some(generation)
+                },
+                (self.rope.clone(), self.query.clone(), self.options),
+            ));
+        }
+
+        let Some(job) = &mut self.job else {
+            return;
+        };
+        let mut chunks = Vec::new();
+        let mut finished = None;
+        while let Ok(progress) = job.poll_progress() {
+            match progress {
+                Progress::Progress(chunk) => chunks.push(chunk),
+                Progress::End(generation) => finished = Some(generation),
+            }
+        }
+        for (generation, matches) in chunks {
+            self.receive(generation, matches);
+        }
+        if let Some(generation) = finished {
+            self.job = None;
+            if let Some(generation) = generation {
+                self.finish(generation);
             }
         }
+    }
 
-        if query_idx >= chars.len() {
-            let start_byte = rope.char_to_byte(current_char - chars.len());
-            let end_byte = rope.char_to_byte(current_char);
-            matches.push(SearchMatch {
-                start: rope.byte_to_point(start_byte),
-                end: rope.byte_to_point(end_byte),
-                start_byte,
-                end_byte,
-            });
-            if stop_at_first {
-                break;
+    /// Adds matches found by the search of `generation`. The first matches of a new generation
+    /// replace the matches of the previous one, so those are shown until the new ones arrive.
+    fn receive(&mut self, generation: u64, matches: Vec<SearchMatch>) {
+        if generation != self.generation {
+            return;
+        }
+        if self.matches_generation != generation {
+            self.matches_generation = generation;
+            self.matches.clear();
+        }
+        if let Some(cursor_pos) = self.cursor_pos {
+            if let Some(i) = matches.iter().position(|m| m.end_byte > cursor_pos) {
+                self.first_match = Some(self.matches.len() + i);
+                self.cursor_pos = None;
             }
-            query_idx = 0;
         }
-        current_char += 1;
+        self.matches.extend(matches);
+    }
+
+    /// Called when the search of `generation` has gone through the whole text
+    fn finish(&mut self, generation: u64) {
+        // Removes the matches of the previous generation if nothing was found
+        self.receive(generation, Vec::new());
+        if generation == self.generation && self.cursor_pos.take().is_some() {
+            self.first_match = Some(0);
+        }
+    }
+
+    /// True while the text is being searched by a job
+    pub fn is_searching(&self) -> bool {
+        self.pending || self.job.is_some()
+    }
+
+    /// Moves the matches after a change so they stay in place until the buffer has been searched again.
+    /// Matches touching the changed range are removed as they might not match anymore.
+    pub(crate) fn shift_matches(&mut self, range: &Range<usize>, new_len: usize) {
+        self.matches
+            .retain(|m| m.end_byte < range.start || m.start_byte > range.end);
+        for m in &mut self.matches {
+            if m.start_byte > range.end {
+                m.start_byte = m.start_byte - range.len() + new_len;
+                m.end_byte = m.end_byte - range.len() + new_len;
+            }
+        }
+    }
+
+    pub fn get_matches(&self) -> &[SearchMatch] {
+        &self.matches
     }
-    matches
 }
 
-#[inline(always)]
-pub fn compare_char(lhs: &char, rhs: &char, case_insensitive: bool) -> bool {
-    if case_insensitive {
-        lhs.eq_ignore_ascii_case(rhs)
-    } else {
-        lhs == rhs
+impl Drop for BufferSearcher {
+    fn drop(&mut self) {
+        if let Some(job) = &self.job {
+            job.cancel();
+        }
+    }
+}
+
+/// Searches `rope` from a job and sends the matches back in chunks tagged with `generation`
+/// as they are found. Returns false if the job was cancelled before the search finished.
+pub fn stream_search(
+    rope: RopeSlice,
+    query: &str,
+    options: SearchOptions,
+    generation: u64,
+    token: &CancellationToken,
+    progressor: &mut Progressor<MatchChunk>,
+) -> bool {
+    let total = rope.len_chars() as u64;
+    let mut chunk = Vec::new();
+    let finished = find_matches(rope, query, options, |step| {
+        match step {
+            SearchStep::Match(m) => {
+                chunk.push(m);
+                if chunk.len() >= CHUNK_MATCHES {
+                    progressor.make_progress((generation, mem::take(&mut chunk)));
+                }
+            }
+            SearchStep::Progress(done) => {
+                if !chunk.is_empty() {
+                    progressor.make_progress((generation, mem::take(&mut chunk)));
+                }
+                progressor.report(done as u64, total, "");
+            }
+        }
+        !token.is_cancelled()
+    });
+    if finished && !chunk.is_empty() {
+        progressor.make_progress((generation, chunk));
+    }
+    finished
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_matches_are_discarded() {
+        let rope = Rope::from_str("foo bar foo bar");
+        let mut searcher =
+            BufferSearcher::new("foo".into(), rope.clone(), 0, SearchOptions::default(), 2);
+        assert_eq!(searcher.get_matches().len(), 2);
+        let stale = searcher.generation;
+
+        searcher.update_query("bar".into(), SearchOptions::default(), 9);
+        searcher.receive(
+            stale,
+            search_rope(
+                rope.slice(..),
+                "foo".into(),
+                SearchOptions::default(),
+                false,
+            ),
+        );
+        let starts: Vec<_> = searcher
+            .get_matches()
+            .iter()
+            .map(|m| m.start_byte)
+            .collect();
+        assert_eq!(starts, vec![4, 12]);
+        assert_eq!(searcher.get_next_match().unwrap().start_byte, 12);
+    }
+
+    #[test]
+    fn no_match_selected_after_new_query() {
+        let rope = Rope::from_str("foo foo");
+        let mut searcher = BufferSearcher::new("foo".into(), rope, 0, SearchOptions::default(), 0);
+        assert_eq!(searcher.match_position(), (None, 2));
+        searcher.get_next_match();
+        assert_eq!(searcher.match_position(), (Some(0), 2));
+        searcher.update_query("fo".into(), SearchOptions::default(), 0);
+        assert_eq!(searcher.match_position(), (None, 2));
     }
 }
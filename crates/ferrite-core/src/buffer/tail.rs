@@ -0,0 +1,214 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io::{self, Read, Seek, SeekFrom},
+};
+
+use super::{error::BufferError, read, write::DiskState, Buffer};
+
+/// A buffer following a file that is appended to such as a log
+#[derive(Debug, Clone, Copy)]
+pub struct Tail {
+    /// Bytes of the file that are shown in the buffer
+    offset: u64,
+    /// If the buffer was read-only before tailing, tailed buffers cannot be edited
+    read_only: bool,
+    /// Hash of the first `HEAD_BYTES` of the file, a different start means the file was rotated
+    head: u64,
+}
+
+/// Amount of bytes at the start of the file that are compared to notice rotated files
+const HEAD_BYTES: u64 = 256;
+
+/// Words of log lines that are highlighted and the diagnostic level they are shown as
+const LEVELS: [(&str, &str); 6] = [
+    ("ERROR", "error"),
+    ("FATAL", "error"),
+    ("PANIC", "error"),
+    ("CRITICAL", "error"),
+    ("WARN", "warning"),
+    ("WARNING", "warning"),
+];
+
+/// Level of the first word anywhere in `line` that names one, such as `ERROR` or `WARN`
+pub fn line_level(line: &str) -> Option<&'static str> {
+    line.split(|ch: char| !ch.is_ascii_alphabetic())
+        .find_map(|word| {
+            LEVELS
+                .iter()
+                .find(|(name, _)| *name == word)
+                .map(|(_, level)| *level)
+        })
+}
+
+/// The complete lines of `bytes`, a line that is still being written is left for the next read
+fn complete_lines(bytes: &[u8]) -> &[u8] {
+    match bytes.iter().rposition(|byte| *byte == b'\n') {
+        Some(end) => &bytes[..=end],
+        None => &[],
+    }
+}
+
+fn hash_head(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Buffer {
+    pub fn is_tailing(&self) -> bool {
+        self.tail.is_some()
+    }
+
+    /// Follows the file of the buffer, the buffer is reloaded and becomes read-only
+    pub fn start_tail(&mut self) -> Result<(), BufferError> {
+        if self.encryption.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "encrypted files cannot be tailed",
+            )
+            .into());
+        }
+        self.tail = Some(Tail {
+            offset: 0,
+            read_only: self.read_only,
+            head: 0,
+        });
+        self.read_only = true;
+        if let Err(err) = self.load_tail() {
+            self.stop_tail();
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    pub fn stop_tail(&mut self) {
+        if let Some(tail) = self.tail.take() {
+            self.read_only = tail.read_only;
+        }
+    }
+
+    /// Reads the complete lines of the whole file
+    pub(super) fn load_tail(&mut self) -> Result<(), BufferError> {
+        let Some(path) = &self.file else {
+            return Err(BufferError::NoPathSet);
+        };
+        let bytes = fs::read(path)?;
+        let bytes = complete_lines(&bytes);
+        let (encoding, bom, rope) = read::read_with_bom(bytes)?;
+        self.disk_state = DiskState::read(path).ok();
+        self.encoding = encoding;
+        self.bom = bom;
+
+        self.history.finish();
+        self.history.begin(self.get_all_cursors(), self.dirty);
+        let len_bytes = self.rope.len_bytes();
+        self.history.replace(&mut self.rope, 0..len_bytes, rope);
+        self.dirty = false;
+        self.history.save();
+        self.queue_syntax_update();
+        self.history.finish();
+        self.ensure_every_cursor_is_valid();
+
+        if let Some(tail) = &mut self.tail {
+            tail.offset = bytes.len() as u64;
+            tail.head = hash_head(&bytes[..bytes.len().min(HEAD_BYTES as usize)]);
+        }
+        Ok(())
+    }
+
+    /// Appends the lines written to the file since the last read instead of reading it again.
+    /// The appended lines are not added to the history so it does not grow with the file.
+    /// Truncated or rotated files are read again. Views scrolled to the end stay at the end.
+    pub fn read_tail(&mut self) -> Result<(), BufferError> {
+        let Some(tail) = self.tail else {
+            return Ok(());
+        };
+        let Some(path) = &self.file else {
+            return Err(BufferError::NoPathSet);
+        };
+
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut head = Vec::new();
+        (&mut file)
+            .take(tail.offset.min(HEAD_BYTES))
+            .read_to_end(&mut head)?;
+        let rotated = hash_head(&head) != tail.head
+            || self
+                .disk_state
+                .as_ref()
+                .is_some_and(|state| state.replaced(path));
+        // Line endings can only be found byte by byte in ascii compatible encodings
+        if len < tail.offset || rotated || !self.encoding.is_ascii_compatible() {
+            return self.load_tail();
+        }
+        file.seek(SeekFrom::Start(tail.offset))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let bytes = complete_lines(&bytes);
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        self.disk_state = DiskState::read(path).ok();
+        let (text, _) = self.encoding.decode_without_bom_handling(bytes);
+
+        let at_end: Vec<_> = self
+            .views
+            .iter()
+            .filter(|(_, view)| view.line_pos_floored() + view.view_lines >= self.rope.len_lines())
+            .map(|(view_id, _)| view_id)
+            .collect();
+
+        let len_bytes = self.rope.len_bytes();
+        let len_chars = self.rope.len_chars();
+        self.rope.insert(len_chars, &text);
+        self.emit_change(len_bytes..len_bytes, text.len());
+        self.queue_syntax_update();
+
+        for view_id in at_end {
+            self.scroll_to_end(view_id);
+        }
+        if let Some(tail) = &mut self.tail {
+            tail.offset += bytes.len() as u64;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail() {
+        assert_eq!(complete_lines(b"a\nb\nc"), b"a\nb\n");
+        assert_eq!(complete_lines(b"partial"), b"");
+        assert_eq!(line_level("2024-01-01 [ERROR] failed"), Some("error"));
+        assert_eq!(line_level("WARN: disk almost full"), Some("warning"));
+        assert_eq!(line_level("no ERRORS here"), None);
+
+        let dir = tempdir::TempDir::new("ferrite-tail").unwrap();
+        let path = dir.path().join("tail.log");
+        fs::write(&path, "one\ntw").unwrap();
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        buffer.start_tail().unwrap();
+        assert_eq!(buffer.rope().to_string(), "one\n");
+        fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        let revision = buffer.revision();
+        buffer.read_tail().unwrap();
+        assert_eq!(buffer.rope().to_string(), "one\ntwo\nthree\n");
+        assert_ne!(buffer.revision(), revision);
+        fs::write(&path, "new\n").unwrap();
+        buffer.read_tail().unwrap();
+        assert_eq!(buffer.rope().to_string(), "new\n");
+        // Rotated to a file longer than the one that was read
+        fs::write(&path, "rotated\nlog\n").unwrap();
+        buffer.read_tail().unwrap();
+        assert_eq!(buffer.rope().to_string(), "rotated\nlog\n");
+        assert!(buffer.read_only);
+        buffer.stop_tail();
+        assert!(!buffer.read_only);
+    }
+}
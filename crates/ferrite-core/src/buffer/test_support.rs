@@ -0,0 +1,147 @@
+//! Proptest strategies for buffer commands and the invariants that have to hold after each of them.
+//! `PROPTEST_CASES` sets how many cases are run, failing cases are shrunk to the fewest commands.
+
+use ferrite_utility::point::Point;
+use proptest::{collection::vec, prelude::*, sample::select, test_runner::TestCaseError};
+
+use super::{Buffer, ViewId};
+use crate::cmd::{Cmd, LineMoveDir};
+
+/// Pieces of text that are inserted, the multi byte chars catch cursors that end up inside of a char
+const PIECES: &[&str] = &["a", "b", "_", " ", "\t", "\n", "é", "ß", "😀", "ab\ncd"];
+
+/// Text made of up to `max_pieces` pieces
+pub fn text(max_pieces: usize) -> impl Strategy<Value = String> {
+    vec(select(PIECES), 0..=max_pieces).prop_map(|pieces| pieces.concat())
+}
+
+/// A point that can be past the end of the text, it is clamped to the lines of the buffer when run
+fn point() -> impl Strategy<Value = Point<usize>> {
+    (0..40usize, 0..40usize).prop_map(|(column, line)| Point::new(column, line))
+}
+
+/// A command that edits the text, moves the cursors or changes the selections
+pub fn cmd() -> impl Strategy<Value = Cmd> {
+    prop_oneof![
+        any::<bool>().prop_map(|expand_selection| Cmd::MoveRight { expand_selection }),
+        any::<bool>().prop_map(|expand_selection| Cmd::MoveLeft { expand_selection }),
+        (any::<bool>(), any::<bool>(), 1..4usize).prop_map(
+            |(expand_selection, create_cursor, distance)| Cmd::MoveUp {
+                expand_selection,
+                create_cursor,
+                distance,
+            }
+        ),
+        (any::<bool>(), any::<bool>(), 1..4usize).prop_map(
+            |(expand_selection, create_cursor, distance)| Cmd::MoveDown {
+                expand_selection,
+                create_cursor,
+                distance,
+            }
+        ),
+        any::<bool>().prop_map(|expand_selection| Cmd::MoveRightWord { expand_selection }),
+        any::<bool>().prop_map(|expand_selection| Cmd::MoveLeftWord { expand_selection }),
+        any::<bool>().prop_map(|expand_selection| Cmd::Home { expand_selection }),
+        any::<bool>().prop_map(|expand_selection| Cmd::End { expand_selection }),
+        any::<bool>().prop_map(|expand_selection| Cmd::Start { expand_selection }),
+        any::<bool>().prop_map(|expand_selection| Cmd::Eof { expand_selection }),
+        text(4).prop_map(|text| Cmd::Insert { text }),
+        select(PIECES).prop_map(|piece| Cmd::Char {
+            ch: piece.chars().next().unwrap(),
+        }),
+        Just(Cmd::Backspace),
+        Just(Cmd::BackspaceWord),
+        Just(Cmd::Delete),
+        Just(Cmd::DeleteWord),
+        Just(Cmd::SelectWord),
+        Just(Cmd::SelectLine),
+        Just(Cmd::RemoveLine),
+        Just(Cmd::DuplicateLines),
+        prop_oneof![Just(LineMoveDir::Up), Just(LineMoveDir::Down)]
+            .prop_map(|direction| Cmd::MoveLine { direction }),
+        (point(), point()).prop_map(|(cursor, anchor)| Cmd::SelectArea { cursor, anchor }),
+        Just(Cmd::Escape),
+        Just(Cmd::Undo),
+        Just(Cmd::Redo),
+    ]
+}
+
+/// Moves the points of a command onto the lines of the buffer
+fn clamp_to_buffer(cmd: Cmd, buffer: &Buffer) -> Cmd {
+    let last_line = buffer.rope().len_lines() - 1;
+    let clamp = |point: Point<usize>| Point::new(point.column, point.line.min(last_line));
+    match cmd {
+        Cmd::SelectArea { cursor, anchor } => Cmd::SelectArea {
+            cursor: clamp(cursor),
+            anchor: clamp(anchor),
+        },
+        cmd => cmd,
+    }
+}
+
+/// Checks what has to hold for every buffer after every command
+pub fn check_invariants(buffer: &Buffer) -> Result<(), TestCaseError> {
+    let rope = buffer.rope();
+    for (view_id, view) in &buffer.views {
+        for cursor in view.cursors.iter() {
+            for (name, byte_idx) in [("position", cursor.position), ("anchor", cursor.anchor)] {
+                prop_assert!(
+                    byte_idx <= rope.len_bytes(),
+                    "{view_id:?} cursor {name} {byte_idx} is past the end of the text {}",
+                    rope.len_bytes()
+                );
+                prop_assert!(
+                    rope.char_to_byte(rope.byte_to_char(byte_idx)) == byte_idx,
+                    "{view_id:?} cursor {name} {byte_idx} is inside of a char"
+                );
+            }
+        }
+
+        let mut coalesced = view.clone();
+        coalesced.coalesce_cursors();
+        let mut twice = coalesced.clone();
+        twice.coalesce_cursors();
+        prop_assert_eq!(
+            &*coalesced.cursors,
+            &*twice.cursors,
+            "coalescing the cursors twice changed them"
+        );
+    }
+
+    let mut byte_idx = 0;
+    for (line_idx, line) in rope.lines().enumerate() {
+        prop_assert_eq!(
+            rope.line_to_byte(line_idx),
+            byte_idx,
+            "line {} does not start at byte {}",
+            line_idx,
+            byte_idx
+        );
+        prop_assert_eq!(
+            rope.byte_to_line(byte_idx),
+            line_idx,
+            "byte {} is not on line {}",
+            byte_idx,
+            line_idx
+        );
+        byte_idx += line.len_bytes();
+    }
+    prop_assert_eq!(
+        byte_idx,
+        rope.len_bytes(),
+        "the lengths of the lines do not add up to the length of the text"
+    );
+    Ok(())
+}
+
+/// Runs `cmds` on a buffer with `text` and checks the invariants after each of them
+pub fn run_commands(text: &str, cmds: &[Cmd]) -> Result<(Buffer, ViewId), TestCaseError> {
+    let mut buffer = Buffer::with_text(text);
+    let view_id = buffer.create_view();
+    for cmd in cmds {
+        let cmd = clamp_to_buffer(cmd.clone(), &buffer);
+        buffer.handle_input(view_id, cmd).unwrap();
+        check_invariants(&buffer)?;
+    }
+    Ok((buffer, view_id))
+}
@@ -0,0 +1,237 @@
+use std::ops::Range;
+
+use ropey::RopeSlice;
+
+use super::Buffer;
+
+/// Why a character was flagged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuspiciousKind {
+    /// Bidirectional control characters can reorder how code is displayed, see trojan source attacks
+    Bidi,
+    /// Characters that take up no space
+    ZeroWidth,
+    /// Characters that look like an ascii character
+    Confusable(char),
+}
+
+/// A character that could make the text look different from how it is interpreted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuspiciousChar {
+    /// Byte range of the character in the buffer
+    pub range: Range<usize>,
+    pub kind: SuspiciousKind,
+}
+
+/// Homoglyphs of ascii characters that are commonly mixed up with them
+const CONFUSABLES: &[(char, char)] = &[
+    // Cyrillic
+    ('а', 'a'),
+    ('е', 'e'),
+    ('о', 'o'),
+    ('р', 'p'),
+    ('с', 'c'),
+    ('у', 'y'),
+    ('х', 'x'),
+    ('і', 'i'),
+    ('ј', 'j'),
+    ('ѕ', 's'),
+    ('ԁ', 'd'),
+    ('А', 'A'),
+    ('В', 'B'),
+    ('Е', 'E'),
+    ('К', 'K'),
+    ('М', 'M'),
+    ('Н', 'H'),
+    ('О', 'O'),
+    ('Р', 'P'),
+    ('С', 'C'),
+    ('Т', 'T'),
+    ('Х', 'X'),
+    ('І', 'I'),
+    ('Ј', 'J'),
+    ('Ѕ', 'S'),
+    // Greek
+    ('α', 'a'),
+    ('ο', 'o'),
+    ('ν', 'v'),
+    ('ρ', 'p'),
+    ('Α', 'A'),
+    ('Β', 'B'),
+    ('Ε', 'E'),
+    ('Ζ', 'Z'),
+    ('Η', 'H'),
+    ('Ι', 'I'),
+    ('Κ', 'K'),
+    ('Μ', 'M'),
+    ('Ν', 'N'),
+    ('Ο', 'O'),
+    ('Ρ', 'P'),
+    ('Τ', 'T'),
+    ('Υ', 'Y'),
+    ('Χ', 'X'),
+    ('\u{037E}', ';'),
+    // Punctuation
+    ('\u{00A0}', ' '),
+    ('\u{2007}', ' '),
+    ('\u{202F}', ' '),
+    ('‐', '-'),
+    ('‑', '-'),
+    ('‒', '-'),
+    ('–', '-'),
+    ('—', '-'),
+    ('−', '-'),
+    ('‘', '\''),
+    ('’', '\''),
+    ('‚', ','),
+    ('“', '"'),
+    ('”', '"'),
+    ('ǃ', '!'),
+    ('∕', '/'),
+    ('⁄', '/'),
+];
+
+pub fn classify(ch: char) -> Option<SuspiciousKind> {
+    match ch {
+        '\u{061C}'
+        | '\u{200E}'
+        | '\u{200F}'
+        | '\u{202A}'..='\u{202E}'
+        | '\u{2066}'..='\u{2069}' => Some(SuspiciousKind::Bidi),
+        // U+200D is left alone, it joins emoji sequences
+        '\u{00AD}' | '\u{200B}' | '\u{200C}' | '\u{2060}' | '\u{FEFF}' => {
+            Some(SuspiciousKind::ZeroWidth)
+        }
+        // Fullwidth forms of ascii
+        '\u{FF01}'..='\u{FF5E}' => Some(SuspiciousKind::Confusable(
+            char::from_u32(ch as u32 - 0xFEE0).unwrap_or(ch),
+        )),
+        _ => CONFUSABLES
+            .iter()
+            .find(|(confusable, _)| *confusable == ch)
+            .map(|(_, ascii)| SuspiciousKind::Confusable(*ascii)),
+    }
+}
+
+/// Finds the suspicious characters of `text`, the ranges are offset by `start_byte`.
+/// Confusables are only flagged in tokens that also contain ascii letters or digits,
+/// text written entirely in another script uses them for good reasons.
+pub fn find_suspicious(text: RopeSlice, start_byte: usize) -> Vec<SuspiciousChar> {
+    let mut found = Vec::new();
+    if text.chunks().all(str::is_ascii) {
+        return found;
+    }
+    let mut token = Vec::new();
+    let mut token_has_ascii = false;
+    let mut byte_idx = start_byte;
+    for ch in text.chars() {
+        let kind = classify(ch);
+        if ch.is_whitespace() && kind.is_none() {
+            flush_token(&mut found, &mut token, token_has_ascii);
+            token_has_ascii = false;
+        } else {
+            token_has_ascii |= ch.is_ascii_alphanumeric();
+            if let Some(kind) = kind {
+                token.push(SuspiciousChar {
+                    range: byte_idx..byte_idx + ch.len_utf8(),
+                    kind,
+                });
+            }
+        }
+        byte_idx += ch.len_utf8();
+    }
+    flush_token(&mut found, &mut token, token_has_ascii);
+    found
+}
+
+fn flush_token(found: &mut Vec<SuspiciousChar>, token: &mut Vec<SuspiciousChar>, mixed: bool) {
+    found.extend(
+        token
+            .drain(..)
+            .filter(|ch| mixed || !matches!(ch.kind, SuspiciousKind::Confusable(_))),
+    );
+}
+
+impl Buffer {
+    /// Plain text and prose can contain these characters for good reasons
+    fn lints_unicode(&self) -> bool {
+        !matches!(self.language_name(), "text" | "markdown")
+    }
+
+    /// Suspicious characters on `lines`, only source code buffers are checked
+    pub fn suspicious_chars(&self, lines: Range<usize>) -> Vec<SuspiciousChar> {
+        if !self.lints_unicode() {
+            return Vec::new();
+        }
+        let end = lines.end.min(self.rope.len_lines());
+        let start = lines.start.min(end);
+        let start_byte = self.rope.line_to_byte(start);
+        let end_byte = self.rope.line_to_byte(end);
+        find_suspicious(self.rope.byte_slice(start_byte..end_byte), start_byte)
+    }
+
+    /// Removes bidi control and zero width characters and replaces confusables with the ascii
+    /// character they look like. Returns the amount of characters that were changed.
+    pub fn normalize_unicode(&mut self) -> usize {
+        let found = find_suspicious(self.rope.slice(..), 0);
+        if found.is_empty() {
+            return 0;
+        }
+
+        self.history.begin(self.get_all_cursors(), self.dirty);
+        let cursor_positions = self.get_cursor_positions();
+        // Replacing from the end keeps the byte ranges of the earlier characters valid
+        for SuspiciousChar { range, kind } in found.iter().rev() {
+            match kind {
+                SuspiciousKind::Confusable(ascii) => {
+                    self.history
+                        .replace(&mut self.rope, range.clone(), ascii.to_string())
+                }
+                _ => self.history.remove(&mut self.rope, range.clone()),
+            }
+        }
+        self.restore_cursor_positions(cursor_positions);
+        self.mark_dirty();
+        self.history.finish();
+        found.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use super::*;
+
+    #[test]
+    fn suspicious_chars() {
+        let rope = Rope::from_str(
+            "let a\u{202E} = \"\u{0445}\u{200B}y\"; // \u{201C}ok\u{201D}\nlet привет = \"— 👩\u{200D}💻\";\n",
+        );
+        let kinds: Vec<_> = find_suspicious(rope.slice(..), 0)
+            .into_iter()
+            .map(|found| found.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SuspiciousKind::Bidi,
+                SuspiciousKind::Confusable('x'),
+                SuspiciousKind::ZeroWidth,
+                SuspiciousKind::Confusable('"'),
+                SuspiciousKind::Confusable('"'),
+            ]
+        );
+        assert_eq!(classify('Ａ'), Some(SuspiciousKind::Confusable('A')));
+        assert_eq!(classify('ä'), None);
+        assert_eq!(classify('\u{200D}'), None);
+
+        let mut buffer = Buffer::with_text(&rope.to_string());
+        buffer.create_view();
+        assert_eq!(buffer.normalize_unicode(), 5);
+        assert_eq!(
+            buffer.rope().to_string(),
+            "let a = \"xy\"; // \"ok\"\nlet привет = \"— 👩\u{200D}💻\";\n"
+        );
+    }
+}
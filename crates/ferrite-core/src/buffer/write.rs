@@ -1,24 +1,130 @@
 use std::{
-    fs::OpenOptions,
-    io::{BufWriter, Write},
+    fs::{self, OpenOptions},
+    io::{self, BufWriter, Write},
     path::Path,
+    time::SystemTime,
 };
 
-use encoding_rs::{CoderResult, Encoding};
+use encoding_rs::{CoderResult, EncoderResult, Encoding};
 use ferrite_utility::{graphemes::RopeGraphemeExt, line_ending::LineEnding};
 use ropey::{Rope, RopeBuilder};
 
 use super::error::BufferError;
+use crate::encryption::Encryption;
+
+/// Modification time and size of a file, used to detect changes made by other programs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskState {
+    modified: Option<SystemTime>,
+    len: u64,
+    /// Device and inode of the file, a new inode means the file was replaced
+    inode: Option<(u64, u64)>,
+    /// Permission bits, owner and group of the file
+    permissions: Option<(u32, u32, u32)>,
+}
+
+impl DiskState {
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        #[cfg(unix)]
+        let (inode, permissions) = {
+            use std::os::unix::fs::MetadataExt;
+            (
+                Some((metadata.dev(), metadata.ino())),
+                Some((metadata.mode(), metadata.uid(), metadata.gid())),
+            )
+        };
+        #[cfg(not(unix))]
+        let (inode, permissions) = (None, None);
+        Ok(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            inode,
+            permissions,
+        })
+    }
+
+    /// Returns true if the file at `path` no longer matches this state.
+    /// A file that has been removed does not count as changed.
+    pub fn changed(&self, path: impl AsRef<Path>) -> bool {
+        match Self::read(path) {
+            Ok(current) => {
+                current.modified != self.modified
+                    || current.len != self.len
+                    || current.inode != self.inode
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Returns true if the file at `path` is a different file than the one this state was read from,
+    /// such as a file that was moved over it
+    pub fn replaced(&self, path: impl AsRef<Path>) -> bool {
+        Self::read(path).is_ok_and(|current| current.inode != self.inode)
+    }
+
+    /// Gives the file at `path` the permissions of this state, the owner and group are only
+    /// changed where permitted
+    pub fn restore_permissions(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        #[cfg(unix)]
+        if let Some((mode, uid, gid)) = self.permissions {
+            use std::os::unix::fs::PermissionsExt;
+            let path = path.as_ref();
+            fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+            if let Err(err) = std::os::unix::fs::chown(path, Some(uid), Some(gid)) {
+                tracing::warn!("Could not restore the owner of {path:?}: {err}");
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = path;
+        Ok(())
+    }
+}
+
+/// How a buffer is saved over the file at its path
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SaveOptions {
+    /// Replace a symlink at the path by a file instead of writing to the file it points to
+    pub replace_link: bool,
+    /// State of the file when it was loaded, a file that was removed is created with its permissions
+    pub previous: Option<DiskState>,
+}
+
+/// Writes the text of a buffer over the file at `path` in place, so hard links and the permissions
+/// and ownership of the file are kept and symlinks are written through
+pub fn save(
+    encryption: Option<&Encryption>,
+    encoding: &'static Encoding,
+    bom: bool,
+    line_ending: LineEnding,
+    rope: Rope,
+    path: &Path,
+    options: SaveOptions,
+) -> Result<usize, BufferError> {
+    if options.replace_link
+        && fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_symlink())
+    {
+        fs::remove_file(path)?;
+    }
+    let created = !path.exists();
+    let written = match encryption {
+        Some(encryption) => write_encrypted(encryption, encoding, bom, line_ending, rope, path)?,
+        None => write(encoding, bom, line_ending, rope, path)?,
+    };
+    if let Some(previous) = options.previous.filter(|_| created) {
+        previous.restore_permissions(path)?;
+    }
+    Ok(written)
+}
 
 pub fn write(
     encoding: &'static Encoding,
+    bom: bool,
     line_ending: LineEnding,
     rope: Rope,
     path: impl AsRef<Path>,
 ) -> Result<usize, BufferError> {
     let path = path.as_ref().to_path_buf();
-    const BUFFER_SIZE: usize = 8192;
-
     let mut file = BufWriter::new(
         OpenOptions::new()
             .create(true)
@@ -26,10 +132,105 @@ pub fn write(
             .write(true)
             .open(path)?,
     );
+    let written = encode(encoding, bom, line_ending, rope, &mut file)?;
+    file.flush()?;
+    file.get_mut().sync_all()?;
+    Ok(written)
+}
+
+/// Encodes the text in memory and pipes it through `encryption` so only the ciphertext is written to disk
+pub fn write_encrypted(
+    encryption: &Encryption,
+    encoding: &'static Encoding,
+    bom: bool,
+    line_ending: LineEnding,
+    rope: Rope,
+    path: impl AsRef<Path>,
+) -> Result<usize, BufferError> {
+    let mut plaintext = Vec::new();
+    encode(encoding, bom, line_ending, rope, &mut plaintext)?;
+    let ciphertext = encryption.encrypt(&plaintext)?;
+    write_bytes(path, &ciphertext)?;
+    Ok(ciphertext.len())
+}
+
+/// Overwrites the file at `path` in place with `content`, so hard links and the permissions
+/// and ownership of the file are kept and symlinks are written through
+pub fn write_bytes(path: impl AsRef<Path>, content: &[u8]) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(path)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
+
+/// Encodes the text to `out`, used to write a buffer to stdout
+pub fn write_to(
+    encoding: &'static Encoding,
+    bom: bool,
+    line_ending: LineEnding,
+    rope: Rope,
+    mut out: impl Write,
+) -> Result<usize, BufferError> {
+    let written = encode(encoding, bom, line_ending, rope, &mut out)?;
+    out.flush()?;
+    Ok(written)
+}
+
+/// The byte order mark of `encoding`, encodings other than the unicode ones have none
+pub fn bom_bytes(encoding: &'static Encoding) -> Option<&'static [u8]> {
+    if encoding == encoding_rs::UTF_8 {
+        Some(&[0xEF, 0xBB, 0xBF])
+    } else if encoding == encoding_rs::UTF_16LE {
+        Some(&[0xFF, 0xFE])
+    } else if encoding == encoding_rs::UTF_16BE {
+        Some(&[0xFE, 0xFF])
+    } else {
+        None
+    }
+}
+
+/// Number of characters of `rope` that cannot be represented in `encoding`,
+/// they are written as html numeric character references
+pub fn count_unmappable(encoding: &'static Encoding, rope: &Rope) -> usize {
+    // Encoders of the utf-16 encodings output utf-8, every character can be represented in both
+    if encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE {
+        return 0;
+    }
+    let mut encoder = encoding.new_encoder();
+    let mut buffer = [0u8; 8192];
+    let mut count = 0;
+    for chunk in rope.chunks() {
+        let mut remainder = chunk;
+        loop {
+            let (result, read, _) =
+                encoder.encode_from_utf8_without_replacement(remainder, &mut buffer, false);
+            remainder = &remainder[read..];
+            match result {
+                EncoderResult::InputEmpty => break,
+                EncoderResult::OutputFull => (),
+                EncoderResult::Unmappable(_) => count += 1,
+            }
+        }
+    }
+    count
+}
+
+fn encode(
+    encoding: &'static Encoding,
+    bom: bool,
+    line_ending: LineEnding,
+    rope: Rope,
+    mut file: impl Write,
+) -> Result<usize, BufferError> {
+    const BUFFER_SIZE: usize = 8192;
 
     let mut output_rope = RopeBuilder::new();
+    // The editor inserts new lines as `\n`, the other line endings read from the file are kept
     for line in rope.lines() {
-        if line.get_line_ending().is_some() {
+        if line.get_line_ending() == Some(LineEnding::LF) {
             for chunk in line.line_without_line_ending(0).chunks() {
                 output_rope.append(chunk);
             }
@@ -38,11 +239,36 @@ pub fn write(
             for chunk in line.chunks() {
                 output_rope.append(chunk);
             }
-            break;
         }
     }
     let rope = output_rope.finish();
 
+    let mut bom_len = 0;
+    if let Some(bom) = bom.then(|| bom_bytes(encoding)).flatten() {
+        file.write_all(bom)?;
+        bom_len = bom.len();
+    }
+
+    // The encoders of encoding_rs only output utf-16 as utf-8
+    if encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE {
+        let mut written = bom_len;
+        for chunk in rope.chunks() {
+            let bytes: Vec<u8> = chunk
+                .encode_utf16()
+                .flat_map(|unit| {
+                    if encoding == encoding_rs::UTF_16LE {
+                        unit.to_le_bytes()
+                    } else {
+                        unit.to_be_bytes()
+                    }
+                })
+                .collect();
+            file.write_all(&bytes)?;
+            written += bytes.len();
+        }
+        return Ok(written);
+    }
+
     let mut encoder = encoding.new_encoder();
     let mut buffer = [0u8; BUFFER_SIZE];
 
@@ -77,8 +303,5 @@ pub fn write(
 
     write("", true)?;
 
-    file.flush()?;
-    file.get_mut().sync_all()?;
-
     Ok(total_written)
 }
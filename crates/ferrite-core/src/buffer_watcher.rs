@@ -52,11 +52,39 @@ impl BufferWatcher {
         })
     }
 
+    /// Reloads unmodified buffers whose files changed without the watcher being notified and
+    /// watches files that were replaced again, as the watch on the old file is lost
+    pub fn refresh(&mut self, buffers: &mut SlotMap<BufferId, Buffer>) {
+        for buffer in buffers.values_mut() {
+            let (Some(file), Some(disk_state)) = (buffer.file(), buffer.disk_state()) else {
+                continue;
+            };
+            if disk_state.replaced(file) {
+                let _ = self.watcher.unwatch(file);
+                self.buffers.remove(file);
+            }
+            if buffer.is_dirty() || buffer.is_tailing() || !disk_state.changed(file) {
+                continue;
+            }
+            tracing::info!("Reloading changed file: {file:?}");
+            if let Err(err) = buffer.reload() {
+                tracing::error!("Error reloading buffer: {err}");
+            }
+        }
+    }
+
     pub fn update(&mut self, buffers: &mut SlotMap<BufferId, Buffer>) {
         while let Ok(path) = self.update_rx.try_recv() {
             for buffer in buffers.values_mut() {
                 if let Some(file) = buffer.file() {
-                    if file == path && !buffer.is_dirty() {
+                    if file != path {
+                        continue;
+                    }
+                    if buffer.is_tailing() {
+                        if let Err(err) = buffer.read_tail() {
+                            tracing::error!("Error reading tail of {path:?}: {err}");
+                        }
+                    } else if !buffer.is_dirty() {
                         let _ = buffer.reload();
                     }
                 }
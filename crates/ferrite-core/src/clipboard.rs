@@ -1,6 +1,11 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Mutex,
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{self, IsTerminal, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
 };
 
 use arboard::Clipboard;
@@ -8,6 +13,19 @@ use arboard::Clipboard;
 static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
 static LOCAL_CLIPBOARD: Mutex<String> = Mutex::new(String::new());
 static IS_USING_LOCAL_CLIPBOARD: AtomicBool = AtomicBool::new(false);
+static IS_USING_OSC52: AtomicBool = AtomicBool::new(false);
+
+/// Where copied text ends up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardKind {
+    /// The clipboard of the display server
+    System,
+    /// A clipboard that only exists inside the editor
+    Local,
+    /// The clipboard of the terminal, set with the OSC 52 escape sequence.
+    /// The terminal can't be read from so pastes use the last copied text.
+    Osc52,
+}
 
 pub fn init(local_clipboard: bool) {
     IS_USING_LOCAL_CLIPBOARD.store(local_clipboard, Ordering::SeqCst);
@@ -22,11 +40,31 @@ pub fn uninit() {
     *CLIPBOARD.lock().unwrap() = None;
 }
 
+pub fn clipboard_kind() -> ClipboardKind {
+    if IS_USING_OSC52.load(Ordering::SeqCst) {
+        ClipboardKind::Osc52
+    } else if IS_USING_LOCAL_CLIPBOARD.load(Ordering::SeqCst) {
+        ClipboardKind::Local
+    } else {
+        ClipboardKind::System
+    }
+}
+
 pub fn set_contents(text: impl Into<String>) {
     let text: String = text.into();
-    if IS_USING_LOCAL_CLIPBOARD.load(Ordering::SeqCst) {
-        *LOCAL_CLIPBOARD.lock().unwrap() = text;
-        return;
+    match clipboard_kind() {
+        ClipboardKind::Local => {
+            *LOCAL_CLIPBOARD.lock().unwrap() = text;
+            return;
+        }
+        ClipboardKind::Osc52 => {
+            if let Err(err) = open_terminal().and_then(|mut out| write_osc52(&mut out, &text)) {
+                tracing::error!("{err}");
+            }
+            *LOCAL_CLIPBOARD.lock().unwrap() = text;
+            return;
+        }
+        ClipboardKind::System => (),
     }
 
     let mut clipboard = CLIPBOARD.lock().unwrap();
@@ -41,7 +79,7 @@ pub fn set_contents(text: impl Into<String>) {
 }
 
 pub fn get_contents() -> String {
-    if IS_USING_LOCAL_CLIPBOARD.load(Ordering::SeqCst) {
+    if clipboard_kind() != ClipboardKind::System {
         return LOCAL_CLIPBOARD.lock().unwrap().clone();
     }
 
@@ -84,6 +122,79 @@ pub fn get_primary() -> String {
     String::new()
 }
 
+pub fn is_using_local_clipboard() -> bool {
+    IS_USING_LOCAL_CLIPBOARD.load(Ordering::SeqCst)
+}
+
+/// Returns true if a system clipboard was found when the clipboard was initialized
+pub fn has_system_clipboard() -> bool {
+    CLIPBOARD.lock().unwrap().is_some()
+}
+
 pub fn set_local_clipboard(local_clipboard: bool) {
     IS_USING_LOCAL_CLIPBOARD.store(local_clipboard, Ordering::SeqCst);
 }
+
+/// Only the terminal frontend can use OSC 52 as it writes to stdout
+pub fn set_osc52(osc52: bool) {
+    IS_USING_OSC52.store(osc52, Ordering::SeqCst);
+}
+
+/// Returns true if the editor runs over ssh where the system clipboard is the one of the remote machine
+pub fn is_remote_session() -> bool {
+    env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some()
+}
+
+/// The terminal the editor is drawn on, stdout is not the terminal when the output is piped
+fn open_terminal() -> io::Result<Box<dyn Write>> {
+    if io::stdout().is_terminal() {
+        return Ok(Box::new(io::stdout()));
+    }
+    #[cfg(unix)]
+    let path = "/dev/tty";
+    #[cfg(not(unix))]
+    let path = "CONOUT$";
+    Ok(Box::new(OpenOptions::new().write(true).open(path)?))
+}
+
+fn write_osc52(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))?;
+    out.flush()
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | u32::from(*byte) << (16 - i * 8));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode("ferrite ✓".as_bytes()), "ZmVycml0ZSDinJM=");
+
+        let mut out = Vec::new();
+        write_osc52(&mut out, "hi").unwrap();
+        assert_eq!(out, b"\x1b]52;c;aGk=\x07");
+    }
+}
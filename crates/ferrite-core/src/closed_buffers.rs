@@ -0,0 +1,118 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use ferrite_utility::vec1::Vec1;
+use ropey::Rope;
+
+use crate::buffer::{Buffer, Cursor};
+
+/// Max number of closed buffers that can be reopened
+const MAX_CLOSED_BUFFERS: usize = 20;
+
+/// How long the text of closed buffers that cannot be reloaded from disk is kept
+pub const KEEP_CONTENT_FOR: Duration = Duration::from_secs(10 * 60);
+
+/// A buffer that was closed and how it looked at the time
+#[derive(Debug, Clone)]
+pub struct ClosedBuffer {
+    pub path: Option<PathBuf>,
+    pub name: String,
+    pub language: String,
+    /// Text of buffers without a file or with unsaved changes
+    pub content: Option<Rope>,
+    pub cursors: Vec1<Cursor>,
+    pub line_pos: usize,
+    pub col_pos: usize,
+    pub closed_at: Instant,
+}
+
+impl ClosedBuffer {
+    pub fn new(buffer: &Buffer, closed_at: Instant) -> Self {
+        let view_id = buffer.get_last_used_view();
+        let path = buffer.file().map(|path| path.to_path_buf());
+        Self {
+            content: (path.is_none() || buffer.is_dirty()).then(|| buffer.rope().clone()),
+            path,
+            name: buffer.name().to_string(),
+            language: buffer.language_name().to_string(),
+            cursors: view_id
+                .map(|view_id| buffer.views[view_id].cursors.clone())
+                .unwrap_or_default(),
+            line_pos: view_id.map_or(0, |view_id| buffer.line_pos(view_id)),
+            col_pos: view_id.map_or(0, |view_id| buffer.col_pos(view_id)),
+            closed_at,
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.closed_at) >= KEEP_CONTENT_FOR
+    }
+}
+
+/// Stack of recently closed buffers, the most recently closed is reopened first
+#[derive(Debug, Default)]
+pub struct ClosedBuffers(Vec<ClosedBuffer>);
+
+impl ClosedBuffers {
+    pub fn push(&mut self, closed: ClosedBuffer) {
+        let now = closed.closed_at;
+        if closed.path.is_some() {
+            self.0.retain(|old| old.path != closed.path);
+        }
+        self.0.push(closed);
+        for old in &mut self.0 {
+            if old.is_expired(now) {
+                old.content = None;
+            }
+        }
+        self.0
+            .retain(|old| old.path.is_some() || old.content.is_some());
+        if self.0.len() > MAX_CLOSED_BUFFERS {
+            self.0.drain(..self.0.len() - MAX_CLOSED_BUFFERS);
+        }
+    }
+
+    /// Removes the most recently closed buffer that can still be reopened
+    pub fn pop(&mut self, now: Instant) -> Option<ClosedBuffer> {
+        while let Some(mut closed) = self.0.pop() {
+            if closed.is_expired(now) {
+                closed.content = None;
+            }
+            if closed.path.is_some() || closed.content.is_some() {
+                return Some(closed);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reopen_order() {
+        let now = Instant::now();
+        let mut file = Buffer::with_text("file");
+        file.set_file("/tmp/file.txt").unwrap();
+        file.mark_clean();
+        let scratch = Buffer::with_text("scratch");
+
+        let mut closed = ClosedBuffers::default();
+        closed.push(ClosedBuffer::new(&file, now));
+        closed.push(ClosedBuffer::new(&scratch, now));
+        closed.push(ClosedBuffer::new(&file, now));
+
+        let reopened = closed.pop(now).unwrap();
+        assert_eq!(reopened.path.as_deref(), Some("/tmp/file.txt".as_ref()));
+        assert!(reopened.content.is_none());
+        let reopened = closed.pop(now).unwrap();
+        assert_eq!(reopened.content.unwrap().to_string(), "scratch");
+        assert!(closed.pop(now).is_none());
+
+        closed.push(ClosedBuffer::new(&scratch, now));
+        assert!(closed.pop(now + KEEP_CONTENT_FOR).is_none());
+    }
+}
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 use ferrite_utility::{line_ending::LineEnding, point::Point};
 use serde::{Deserialize, Serialize};
 
-use crate::{buffer::case::Case, layout::panes::Direction};
+use crate::{buffer::case::Case, config::editor::RenderWhitespace, layout::panes::Direction};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LineMoveDir {
@@ -19,6 +19,13 @@ pub enum Cmd {
     OpenFile {
         path: PathBuf,
     },
+    OpenFileSplit {
+        path: PathBuf,
+        direction: Direction,
+    },
+    MoveBufferToPane {
+        direction: Direction,
+    },
     Cd {
         path: PathBuf,
     },
@@ -31,9 +38,21 @@ pub enum Cmd {
     Encoding {
         encoding: Option<String>,
     },
+    ReopenWithEncoding {
+        encoding: String,
+    },
+    ConvertEncoding {
+        encoding: String,
+    },
+    Bom {
+        add: Option<bool>,
+    },
     LineEnding {
         line_ending: Option<LineEnding>,
     },
+    ShowWhitespace {
+        mode: Option<RenderWhitespace>,
+    },
     RunShellCmd {
         args: Vec<PathBuf>,
         pipe: bool,
@@ -59,9 +78,13 @@ pub enum Cmd {
     Reload,
     ReloadAll,
     Logger,
+    LoggerClear,
+    Notifications,
+    NotificationsClear,
     ForceQuit,
     Quit,
     UrlOpen,
+    GotoFileUnderCursor,
     Goto {
         line: i64,
     },
@@ -73,11 +96,28 @@ pub enum Cmd {
     },
     SortLines {
         ascending: bool,
+        unique: bool,
+    },
+    ReverseLines,
+    Align {
+        pattern: String,
     },
     BufferPickerOpen,
     FilePickerOpen,
     FilePickerReload,
+    IndexStatus,
+    RecentFilePickerOpen,
+    RecentProjectPickerOpen,
+    Scratch {
+        name: Option<String>,
+    },
+    CustomPicker {
+        name: String,
+    },
+    TrustWorkspace,
+    DiffSelectionClipboard,
     OpenConfig,
+    EditTheme,
     DefaultConfig,
     OpenLanguages,
     DefaultLanguages,
@@ -88,12 +128,38 @@ pub enum Cmd {
     ClosePane,
     Paste,
     Copy,
+    CopyTo {
+        register: char,
+    },
+    PasteFrom {
+        register: char,
+    },
+    SelectRegister {
+        register: char,
+    },
+    RegisterPicker,
+    LocalHistory,
+    PluginManager,
+    ReloadScripts,
+    FollowPane,
     Format,
     FormatSelection,
     GitReload,
+    GitStatus,
+    GitCommit,
+    GitBranches,
+    GitLog,
+    NextHunk,
+    PrevHunk,
+    RevertHunk,
+    StageHunk,
     RevertBuffer,
     Trash,
     Repeat,
+    RepeatCommand {
+        count: u16,
+        command: Box<Cmd>,
+    },
     MoveRight {
         expand_selection: bool,
     },
@@ -140,6 +206,10 @@ pub enum Cmd {
         cursor: Point<usize>,
         anchor: Point<usize>,
     },
+    /// A click on an item of the info line, such as `encoding` or `branch`
+    InfoLineClick {
+        item: String,
+    },
     PromptGoto,
     Home {
         expand_selection: bool,
@@ -157,6 +227,8 @@ pub enum Cmd {
     SelectLine,
     SelectWord,
     RemoveLine,
+    DuplicateLines,
+    JoinLines,
     Cut,
     PastePrimary {
         column: usize,
@@ -170,38 +242,108 @@ pub enum Cmd {
     VerticalScroll {
         distance: f64,
     },
+    HorizontalScroll {
+        distance: f64,
+    },
     ReplaceCurrentMatch,
     GlobalSearch,
+    SearchIn {
+        glob: String,
+    },
+    GlobalReplace {
+        replacement: String,
+    },
+    UndoLastRefactor,
+    IdentifierStats,
+    LocationList,
+    NextLocation,
+    PrevLocation,
     CaseInsensitive,
+    EditSearchResults,
+    SearchEditDiff,
+    SmartCase,
+    WholeWord,
     NextMatch,
     PrevMatch,
     FocusPalette,
+    HistorySearch,
+    Doctor,
+    ShowLastError,
     OpenFilePicker,
     OpenBufferPicker,
     Escape,
     SaveAll,
     GrowPane,
     ShrinkPane,
+    PaneResize {
+        amount: i64,
+    },
+    PaneSetSize {
+        percent: i64,
+    },
+    RotatePanes {
+        reverse: bool,
+    },
+    SwapPane {
+        direction: Direction,
+    },
+    EqualizePanes,
+    TogglePaneZoom,
+    SaveLayout {
+        name: String,
+    },
+    LoadLayout {
+        name: String,
+    },
     InputMode {
         name: String,
     },
     ReopenBuffer,
+    Recover,
     RotateFile,
     ForceRedraw,
+    Suspend,
     SwitchPane {
         direction: Direction,
     },
-    Number {
+    InsertSequence {
         start: Option<i64>,
+        step: Option<i64>,
+    },
+    IncrementNumber {
+        count: i64,
+    },
+    DecrementNumber {
+        count: i64,
     },
     OpenFileExplorer {
         path: Option<PathBuf>,
     },
     TrimTrailingWhitespace,
+    AcceptOurs,
+    AcceptTheirs,
+    AcceptBoth,
+    NormalizeUnicode,
+    NormalizeLineEndings,
+    ToggleReadOnly,
+    Complete,
+    ToggleAbbreviations,
+    Rerun,
+    AnsiRender,
+    ToggleFollowOutput,
+    Tail,
+    NextConflict,
+    ToggleBookmark,
+    NextBookmark,
+    PrevBookmark,
+    BookmarkPicker,
+    Outline,
+    ParentSymbol,
     ZoomIn,
     ZoomOut,
     ResetZoom,
     KillJob,
+    CancelJob,
     RunAction {
         name: String,
     },
@@ -216,6 +358,7 @@ impl Cmd {
         match self {
             Nop => "Nop",
             Repeat { .. } => "Repeat",
+            RepeatCommand { .. } => "Repeat command",
             MoveRight { .. } => "Move right",
             MoveLeft { .. } => "Move left",
             MoveUp { .. } => "Move up",
@@ -237,6 +380,7 @@ impl Cmd {
             DeleteWord => "Delete word",
             DeleteToEndOfLine => "Delete to end of line",
             ClickCell { .. } => "Set cursor pos",
+            InfoLineClick { .. } => "Info line click",
             SelectArea { .. } => "Select area",
             PromptGoto => "Goto",
             Home { .. } => "Home",
@@ -246,24 +390,49 @@ impl Cmd {
             SelectAll => "Select all",
             SelectLine => "Select line",
             RemoveLine => "Remove line",
+            DuplicateLines => "Duplicate lines",
+            JoinLines => "Join lines",
             SelectWord => "Select word",
             Copy => "Cpy",
             Cut => "Cut",
             Paste => "Paste",
+            CopyTo { .. } => "Copy to register",
+            PasteFrom { .. } => "Paste from register",
+            SelectRegister { .. } => "Select register",
+            RegisterPicker => "Register picker",
+            LocalHistory => "Local history",
+            PluginManager => "Plugin manager",
+            ReloadScripts => "Reload scripts",
+            FollowPane => "Follow pane",
             PastePrimary { .. } => "Paste primary",
             TabOrIndent { .. } => "TabOrIndent",
             Undo => "Undo",
             Redo => "Redo",
             RevertBuffer => "Revert buffer",
             VerticalScroll { .. } => "Vertical scroll",
+            HorizontalScroll { .. } => "Horizontal scroll",
             Search => "Search file",
             Replace => "Replace",
             ReplaceCurrentMatch => "Replace current match",
             GlobalSearch => "Global workspace search",
+            SearchIn { .. } => "Global search in files matching glob",
+            GlobalReplace { .. } => "Replace global search matches",
+            UndoLastRefactor => "Undo last refactor",
+            IdentifierStats => "Identifier statistics",
+            LocationList => "Location list",
+            NextLocation => "Next location",
+            PrevLocation => "Prev location",
             CaseInsensitive => "Case insensitive",
+            EditSearchResults => "Edit search results",
+            SearchEditDiff => "Search edit diff",
+            SmartCase => "Smart case",
+            WholeWord => "Whole word",
             NextMatch => "Next match",
             PrevMatch => "Prev match",
             FocusPalette => "Open palette",
+            HistorySearch => "Search palette history",
+            Doctor => "Doctor",
+            ShowLastError => "Show last error",
             OpenFilePicker => "Open file picker",
             OpenBufferPicker => "Open buffer picker",
             Escape => "Escape",
@@ -273,9 +442,18 @@ impl Cmd {
             ClosePane => "Close pane",
             GrowPane => "Grow pane",
             ShrinkPane => "Shrink pane",
+            PaneResize { .. } => "Resize pane",
+            PaneSetSize { .. } => "Set pane size",
+            RotatePanes { .. } => "Rotate panes",
+            SwapPane { .. } => "Swap pane",
+            EqualizePanes => "Equalize panes",
+            TogglePaneZoom => "Toggle pane zoom",
+            SaveLayout { .. } => "Save layout",
+            LoadLayout { .. } => "Load layout",
             InputMode { name } => name,
             Format => "Format",
             UrlOpen => "Open urls in selection",
+            GotoFileUnderCursor => "Go to file under cursor",
             Split {
                 direction: Direction::Right,
             } => "Split right",
@@ -289,14 +467,21 @@ impl Cmd {
                 direction: Direction::Down,
             } => "Split down",
             ReopenBuffer => "Reopen buffer",
+            Recover => "Recover unsaved buffers",
             New { .. } => "New",
             RotateFile => "Rotate file",
             OpenFile { .. } => "Open file",
+            OpenFileSplit { .. } => "Open file in split",
+            MoveBufferToPane { .. } => "Move buffer to pane",
             Cd { .. } => "Change project directory",
             Save { .. } => "Save buffer",
             Language { .. } => "Language",
             Encoding { .. } => "Encoding",
+            ReopenWithEncoding { .. } => "Reopen with encoding",
+            ConvertEncoding { .. } => "Convert encoding",
+            Bom { .. } => "Byte order mark",
             LineEnding { .. } => "Line ending",
+            ShowWhitespace { .. } => "Show whitespace",
             RunShellCmd { .. } => "Run shell command",
             OpenShellPalette { .. } => "Open shell command palette",
             Case { .. } => "Case",
@@ -307,15 +492,28 @@ impl Cmd {
             Reload => "Reload",
             ReloadAll => "Reload all buffers",
             Logger => "Logger",
+            LoggerClear => "Clear logger",
+            Notifications => "Notifications",
+            NotificationsClear => "Clear notifications",
             ForceQuit => "Force quit",
             Goto { .. } => "Goto",
             Indent { .. } => "Indent",
             Theme { .. } => "Theme",
             SortLines { .. } => "Sort lines",
+            ReverseLines => "Reverse lines",
+            Align { .. } => "Align",
             BufferPickerOpen => "Open buffer picker",
             FilePickerOpen => "Open file picker",
             FilePickerReload => "Reload file picker",
+            IndexStatus => "Show file index status",
+            RecentFilePickerOpen => "Open recent file picker",
+            RecentProjectPickerOpen => "Open recent project picker",
+            Scratch { .. } => "Open scratch buffer",
+            CustomPicker { .. } => "Open custom picker",
+            TrustWorkspace => "Trust workspace",
+            DiffSelectionClipboard => "Diff selection against clipboard",
             OpenConfig => "Open editor config file",
+            EditTheme => "Edit theme",
             DefaultConfig => "Open default editor config",
             OpenLanguages => "Open languages config file",
             DefaultLanguages => "Open default languages config",
@@ -324,21 +522,52 @@ impl Cmd {
             ForceClose => "Force close buffer",
             FormatSelection => "Format selection",
             GitReload => "Git reload",
+            GitStatus => "Git status",
+            GitCommit => "Git commit",
+            GitBranches => "Git branches",
+            GitLog => "Git log",
+            NextHunk => "Next hunk",
+            PrevHunk => "Prev hunk",
+            RevertHunk => "Revert hunk",
+            StageHunk => "Stage hunk",
             Trash => "Move to trash",
             ForceRedraw => "Force redraw",
+            Suspend => "Suspend",
             SwitchPane { direction } => match direction {
                 Direction::Up => "Up pane",
                 Direction::Down => "Down pane",
                 Direction::Right => "Right pane",
                 Direction::Left => "Left pane",
             },
-            Number { .. } => "Number",
+            InsertSequence { .. } => "Insert sequence",
+            IncrementNumber { .. } => "Increment number",
+            DecrementNumber { .. } => "Decrement number",
             OpenFileExplorer { .. } => "Open file explorer",
             TrimTrailingWhitespace => "Trim trailing whitespace",
+            AcceptOurs => "Accept ours",
+            AcceptTheirs => "Accept theirs",
+            AcceptBoth => "Accept both",
+            NormalizeUnicode => "Normalize unicode",
+            NormalizeLineEndings => "Normalize line endings",
+            ToggleReadOnly => "Toggle read-only",
+            Complete => "Complete",
+            ToggleAbbreviations => "Toggle abbreviations",
+            Rerun => "Rerun",
+            AnsiRender => "Toggle ansi render",
+            ToggleFollowOutput => "Toggle follow output",
+            Tail => "Toggle tail",
+            NextConflict => "Next conflict",
+            ToggleBookmark => "Toggle bookmark",
+            NextBookmark => "Next bookmark",
+            PrevBookmark => "Previous bookmark",
+            BookmarkPicker => "Bookmarks",
+            Outline => "Outline",
+            ParentSymbol => "Parent symbol",
             ZoomIn => "Zoom in",
             ZoomOut => "Zoom out",
             ResetZoom => "Reset zoom",
             KillJob => "Kill job",
+            CancelJob => "Cancel job",
             RunAction { .. } => "Run",
             NewLineWithoutBreaking => "Insert new line without breaking",
             NewLineAboveWithoutBreaking => "Insert new line above without breaking",
@@ -351,6 +580,7 @@ impl Cmd {
         match self {
             Nop => false,
             Repeat => false,
+            RepeatCommand { .. } => false,
             MoveRight { .. } => true,
             MoveLeft { .. } => true,
             MoveUp { .. } => true,
@@ -367,6 +597,7 @@ impl Cmd {
             DeleteWord => true,
             DeleteToEndOfLine => true,
             ClickCell { .. } => false,
+            InfoLineClick { .. } => false,
             SelectArea { .. } => false,
             PromptGoto => false,
             Home { .. } => true,
@@ -377,23 +608,48 @@ impl Cmd {
             SelectLine => true,
             SelectWord => true,
             RemoveLine => true,
+            DuplicateLines => true,
+            JoinLines => true,
             Copy => false,
             Cut => false,
             Paste => true,
+            CopyTo { .. } => false,
+            PasteFrom { .. } => true,
+            SelectRegister { .. } => false,
+            RegisterPicker => false,
+            LocalHistory => false,
+            PluginManager => false,
+            ReloadScripts => false,
+            FollowPane => false,
             PastePrimary { .. } => true,
             TabOrIndent { .. } => true,
             Undo => true,
             Redo => true,
             RevertBuffer => false,
             VerticalScroll { .. } => true,
+            HorizontalScroll { .. } => true,
             Search => false,
             Replace => false,
             ReplaceCurrentMatch => true,
             GlobalSearch => false,
+            SearchIn { .. } => false,
+            GlobalReplace { .. } => false,
+            UndoLastRefactor => false,
+            IdentifierStats => false,
+            LocationList => false,
+            NextLocation => false,
+            PrevLocation => false,
             CaseInsensitive => false,
+            EditSearchResults => false,
+            SearchEditDiff => false,
+            SmartCase => false,
+            WholeWord => false,
             NextMatch => true,
             PrevMatch => true,
             FocusPalette => false,
+            HistorySearch => false,
+            Doctor => false,
+            ShowLastError => false,
             OpenFilePicker => false,
             OpenBufferPicker => false,
             Escape => false,
@@ -403,19 +659,34 @@ impl Cmd {
             ClosePane => false,
             GrowPane => true,
             ShrinkPane => true,
+            PaneResize { .. } => true,
+            PaneSetSize { .. } => false,
+            RotatePanes { .. } => true,
+            SwapPane { .. } => false,
+            EqualizePanes => false,
+            TogglePaneZoom => false,
+            SaveLayout { .. } => false,
+            LoadLayout { .. } => false,
             InputMode { .. } => false,
             Format => false,
             RunShellCmd { .. } => false,
             OpenShellPalette { .. } => false,
             Split { .. } => false,
             ReopenBuffer => false,
+            Recover => false,
             RotateFile => false,
             OpenFile { .. } => false,
+            OpenFileSplit { .. } => false,
+            MoveBufferToPane { .. } => false,
             Cd { .. } => false,
             Save { .. } => false,
             Language { .. } => false,
             Encoding { .. } => false,
+            ReopenWithEncoding { .. } => false,
+            ConvertEncoding { .. } => false,
+            Bom { .. } => false,
             LineEnding { .. } => false,
+            ShowWhitespace { .. } => false,
             Case { .. } => false,
             ReplaceAll { .. } => false,
             About => false,
@@ -425,16 +696,30 @@ impl Cmd {
             Reload => false,
             ReloadAll => false,
             Logger => false,
+            LoggerClear => false,
+            Notifications => false,
+            NotificationsClear => false,
             ForceQuit => false,
             UrlOpen => false,
+            GotoFileUnderCursor => false,
             Goto { .. } => false,
             Indent { .. } => false,
             Theme { .. } => false,
             SortLines { .. } => false,
+            ReverseLines => false,
+            Align { .. } => false,
             BufferPickerOpen => false,
             FilePickerOpen => false,
             FilePickerReload => false,
+            IndexStatus => false,
+            RecentFilePickerOpen => false,
+            RecentProjectPickerOpen => false,
+            Scratch { .. } => false,
+            CustomPicker { .. } => false,
+            TrustWorkspace => false,
+            DiffSelectionClipboard => false,
             OpenConfig => false,
+            EditTheme => false,
             DefaultConfig => false,
             OpenLanguages => false,
             DefaultLanguages => false,
@@ -443,22 +728,92 @@ impl Cmd {
             ForceClose => false,
             FormatSelection => false,
             GitReload => false,
+            GitStatus => false,
+            GitCommit => false,
+            GitBranches => false,
+            GitLog => false,
+            NextHunk => false,
+            PrevHunk => false,
+            RevertHunk => false,
+            StageHunk => false,
             Trash => false,
             ForceRedraw => false,
+            Suspend => false,
             SwitchPane { .. } => false,
-            Number { .. } => false,
+            InsertSequence { .. } => false,
+            IncrementNumber { .. } => true,
+            DecrementNumber { .. } => true,
             OpenFileExplorer { .. } => false,
             TrimTrailingWhitespace => false,
+            AcceptOurs => false,
+            AcceptTheirs => false,
+            AcceptBoth => false,
+            NormalizeUnicode => false,
+            NormalizeLineEndings => false,
+            ToggleReadOnly => false,
+            Complete => false,
+            ToggleAbbreviations => false,
+            Rerun => false,
+            AnsiRender => false,
+            ToggleFollowOutput => false,
+            Tail => false,
+            NextConflict => false,
+            ToggleBookmark => false,
+            NextBookmark => false,
+            PrevBookmark => false,
+            BookmarkPicker => false,
+            Outline => false,
+            ParentSymbol => false,
             ZoomIn => false,
             ZoomOut => false,
             ResetZoom => false,
             KillJob => false,
+            CancelJob => false,
             RunAction { .. } => true,
             NewLineWithoutBreaking => true,
             NewLineAboveWithoutBreaking => true,
             SelectAllMatching => false,
         }
     }
+
+    /// Commands that only change the text or view of the current buffer,
+    /// panes showing other buffers do not have to be rendered again after them
+    pub fn is_local(&self) -> bool {
+        use Cmd::*;
+        matches!(
+            self,
+            MoveRight { .. }
+                | MoveLeft { .. }
+                | MoveUp { .. }
+                | MoveDown { .. }
+                | MoveRightWord { .. }
+                | MoveLeftWord { .. }
+                | Insert { .. }
+                | Char { .. }
+                | MoveLine { .. }
+                | Backspace
+                | BackspaceWord
+                | BackspaceToStartOfLine
+                | Delete
+                | DeleteWord
+                | DeleteToEndOfLine
+                | Home { .. }
+                | End { .. }
+                | Eof { .. }
+                | Start { .. }
+                | SelectAll
+                | SelectLine
+                | SelectWord
+                | RemoveLine
+                | DuplicateLines
+                | JoinLines
+                | TabOrIndent { .. }
+                | Undo
+                | Redo
+                | VerticalScroll { .. }
+                | HorizontalScroll { .. }
+        )
+    }
 }
 
 impl fmt::Display for Cmd {
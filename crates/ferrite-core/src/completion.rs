@@ -0,0 +1,217 @@
+use std::{collections::HashSet, ops::Range};
+
+use ferrite_utility::graphemes::is_word_char;
+use indexmap::IndexMap;
+use ropey::Rope;
+use slotmap::{SecondaryMap, SlotMap};
+
+use crate::{
+    buffer::{Buffer, ViewId},
+    workspace::BufferId,
+};
+
+/// Max number of suggestions shown at once
+pub const MAX_OPTIONS: usize = 10;
+/// Buffers larger than this are not searched for words
+const MAX_SCAN_SIZE: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Word,
+    Path,
+    Snippet,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionItem {
+    pub label: String,
+    /// Text that replaces the completed range, snippets mark the cursor with `$0`
+    pub text: String,
+    pub kind: CompletionKind,
+}
+
+/// Popup completing the text before the cursor
+pub struct Completion {
+    pub buffer_id: BufferId,
+    pub view_id: ViewId,
+    /// Byte range of the partial text that is replaced when accepting
+    pub range: Range<usize>,
+    pub options: Vec<CompletionItem>,
+    pub selected: usize,
+}
+
+impl Completion {
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.options.len();
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self
+            .selected
+            .checked_sub(1)
+            .unwrap_or(self.options.len() - 1);
+    }
+
+    pub fn current(&self) -> &CompletionItem {
+        &self.options[self.selected]
+    }
+}
+
+/// Finds the byte range of the partial word ending at `byte_idx`
+pub fn find_partial_word(rope: &Rope, byte_idx: usize) -> Option<Range<usize>> {
+    let line_start = rope.line_to_byte(rope.byte_to_line(byte_idx));
+    let before = rope.byte_slice(line_start..byte_idx).to_string();
+    let start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, ch)| is_word_char(*ch))
+        .last()
+        .map(|(i, _)| i)?;
+    // Numbers are not completed
+    if before[start..].starts_with(|ch: char| ch.is_numeric()) {
+        return None;
+    }
+    Some(line_start + start..byte_idx)
+}
+
+/// Matches case insensitively unless `partial` contains an uppercase letter
+fn matches_partial(word: &str, partial: &str) -> bool {
+    if partial.chars().any(char::is_uppercase) {
+        return word.starts_with(partial);
+    }
+    let mut chars = word.chars().flat_map(char::to_lowercase);
+    partial.chars().all(|ch| chars.next() == Some(ch))
+}
+
+/// Unique words of a rope
+fn index_words(rope: &Rope) -> HashSet<String> {
+    let mut words = HashSet::new();
+    if rope.len_bytes() > MAX_SCAN_SIZE {
+        return words;
+    }
+    let mut word = String::new();
+    for chunk in rope.chunks() {
+        for ch in chunk.chars() {
+            if is_word_char(ch) {
+                word.push(ch);
+            } else if !word.is_empty() {
+                if !words.contains(&word) {
+                    words.insert(word.clone());
+                }
+                word.clear();
+            }
+        }
+    }
+    if !word.is_empty() {
+        words.insert(word);
+    }
+    words
+}
+
+/// Words of every buffer, a buffer is only scanned again when its revision has changed
+#[derive(Default)]
+pub struct WordIndex {
+    buffers: SecondaryMap<BufferId, (u64, HashSet<String>)>,
+}
+
+impl WordIndex {
+    /// Words in `buffers` that complete `partial`, words with the exact same prefix and shorter words first
+    pub fn complete(&mut self, buffers: &SlotMap<BufferId, Buffer>, partial: &str) -> Vec<String> {
+        self.buffers
+            .retain(|buffer_id, _| buffers.contains_key(buffer_id));
+        for (buffer_id, buffer) in buffers {
+            let indexed = self
+                .buffers
+                .get(buffer_id)
+                .is_some_and(|(revision, _)| *revision == buffer.revision());
+            if !indexed {
+                self.buffers
+                    .insert(buffer_id, (buffer.revision(), index_words(buffer.rope())));
+            }
+        }
+
+        let mut words: Vec<_> = self
+            .buffers
+            .values()
+            .flat_map(|(_, words)| words)
+            .filter(|word| word.len() > partial.len() && matches_partial(word, partial))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        words.sort_by(|a, b| {
+            (!a.starts_with(partial))
+                .cmp(&!b.starts_with(partial))
+                .then(a.len().cmp(&b.len()))
+                .then(a.cmp(b))
+        });
+        words.truncate(MAX_OPTIONS);
+        words.into_iter().cloned().collect()
+    }
+
+    /// Forgets every buffer, the ids of a new workspace can be the same as the old ones
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+    }
+}
+
+/// Snippets with a prefix that starts with `partial`
+pub fn complete_snippets(
+    snippets: &IndexMap<String, String>,
+    partial: &str,
+) -> Vec<CompletionItem> {
+    snippets
+        .iter()
+        .filter(|(prefix, _)| prefix.starts_with(partial))
+        .map(|(prefix, body)| CompletionItem {
+            label: prefix.clone(),
+            text: body.clone(),
+            kind: CompletionKind::Snippet,
+        })
+        .collect()
+}
+
+/// Indents every line after the first of `body` with `indent` and removes the `$0` cursor marker.
+/// Returns the text and the byte offset of the cursor in it.
+pub fn expand_snippet(body: &str, indent: &str) -> (String, Option<usize>) {
+    let text = body.replace('\n', &format!("\n{indent}"));
+    match text.find("$0") {
+        Some(cursor) => (text.replacen("$0", "", 1), Some(cursor)),
+        None => (text, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete() {
+        let rope = Rope::from_str("let foo_bar = 1;\nlet FooBaz = foo");
+        let range = find_partial_word(&rope, rope.len_bytes()).unwrap();
+        assert_eq!(rope.byte_slice(range).to_string(), "foo");
+        assert_eq!(find_partial_word(&rope, 14), None);
+
+        let mut buffers = SlotMap::with_key();
+        let buffer_id = buffers.insert(Buffer::with_text(&rope.to_string()));
+        let mut index = WordIndex::default();
+        assert_eq!(index.complete(&buffers, "foo"), vec!["foo_bar", "FooBaz"]);
+        assert_eq!(index.complete(&buffers, "Foo"), vec!["FooBaz"]);
+
+        // Only buffers with a new revision are scanned again
+        let buffer = &mut buffers[buffer_id];
+        let view_id = buffer.create_view();
+        buffer.eof(view_id, false);
+        buffer.insert_text(view_id, " food", false);
+        assert_eq!(
+            index.complete(&buffers, "foo"),
+            vec!["food", "foo_bar", "FooBaz"]
+        );
+        buffers.remove(buffer_id);
+        assert!(index.complete(&buffers, "foo").is_empty());
+
+        assert_eq!(
+            expand_snippet("fn $0() {\n}", "    "),
+            ("fn () {\n    }".to_string(), Some(3))
+        );
+    }
+}
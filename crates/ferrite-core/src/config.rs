@@ -1,15 +1,25 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use editor::Editor;
+use editor::{Editor, Task};
+use indexmap::IndexMap;
 use keymap::Keymap;
 use languages::Languages;
 
-use crate::watcher::{FileWatcher, TomlConfig};
+use crate::{
+    event_loop_proxy::EventLoopProxy,
+    tasks::get_tasks_path,
+    watcher::{FileWatcher, TomlConfig},
+};
 
 pub mod editor;
 pub mod keymap;
 pub mod languages;
 
+/// Directory at the root of a workspace containing overrides for the global config
+pub const WORKSPACE_CONFIG_DIR: &str = ".ferrite";
+
+/// The effective config is built from the defaults, then the global config
+/// in the users config dir and last the overrides in the workspace `.ferrite` dir.
 pub struct Config {
     pub editor: Editor,
     pub editor_path: Option<PathBuf>,
@@ -18,4 +28,275 @@ pub struct Config {
     pub languages_path: Option<PathBuf>,
     pub languages_watcher: Option<FileWatcher<Languages, TomlConfig>>,
     pub keymap: Keymap,
+    /// Global editor config without the workspace overrides applied
+    pub global_editor: Editor,
+    /// Global languages without the workspace overrides applied
+    pub global_languages: Languages,
+    pub workspace: Option<WorkspaceOverrides>,
+}
+
+/// Config files found in the workspace `.ferrite` dir
+pub struct WorkspaceOverrides {
+    pub editor_path: PathBuf,
+    /// Partial editor config that is merged on top of the global one
+    pub editor: Option<toml::Table>,
+    pub editor_watcher: Option<FileWatcher<toml::Table, TomlConfig>>,
+    pub languages_path: PathBuf,
+    pub languages: Option<Languages>,
+    pub languages_watcher: Option<FileWatcher<Languages, TomlConfig>>,
+    /// Tasks that are added to the ones in the editor config
+    pub tasks: Option<IndexMap<String, Task>>,
+    pub tasks_watcher: Option<FileWatcher<IndexMap<String, Task>, TomlConfig>>,
+}
+
+impl WorkspaceOverrides {
+    pub fn load(workspace_dir: impl AsRef<Path>, proxy: &dyn EventLoopProxy) -> Self {
+        let dir = workspace_dir.as_ref().join(WORKSPACE_CONFIG_DIR);
+        let editor_path = dir.join("config.toml");
+        let languages_path = dir.join("languages.toml");
+
+        let mut editor_watcher = None;
+        let mut editor = None;
+        if editor_path.exists() {
+            editor = load_toml(&editor_path);
+            match FileWatcher::new(&editor_path, proxy.dup()) {
+                Ok(watcher) => editor_watcher = Some(watcher),
+                Err(err) => tracing::error!("Error starting workspace config watcher: {err}"),
+            }
+        }
+
+        let mut languages_watcher = None;
+        let mut languages = None;
+        if languages_path.exists() {
+            languages = load_toml(&languages_path);
+            match FileWatcher::new(&languages_path, proxy.dup()) {
+                Ok(watcher) => languages_watcher = Some(watcher),
+                Err(err) => tracing::error!("Error starting workspace languages watcher: {err}"),
+            }
+        }
+
+        let tasks_path = get_tasks_path(workspace_dir);
+        let mut tasks_watcher = None;
+        let mut tasks = None;
+        if tasks_path.exists() {
+            tasks = load_toml(&tasks_path);
+            match FileWatcher::new(&tasks_path, proxy.dup()) {
+                Ok(watcher) => tasks_watcher = Some(watcher),
+                Err(err) => tracing::error!("Error starting workspace tasks watcher: {err}"),
+            }
+        }
+
+        Self {
+            editor_path,
+            editor,
+            editor_watcher,
+            languages_path,
+            languages,
+            languages_watcher,
+            tasks,
+            tasks_watcher,
+        }
+    }
+}
+
+fn load_toml<T: for<'a> serde::Deserialize<'a>>(path: &Path) -> Option<T> {
+    let result = std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|text| Ok(toml::from_str(&text)?));
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            tracing::error!("Error loading `{}`: {err}", path.display());
+            None
+        }
+    }
+}
+
+impl Config {
+    pub fn new(
+        editor: Editor,
+        editor_path: Option<PathBuf>,
+        editor_watcher: Option<FileWatcher<Editor, TomlConfig>>,
+        languages: Languages,
+        languages_path: Option<PathBuf>,
+        languages_watcher: Option<FileWatcher<Languages, TomlConfig>>,
+    ) -> Self {
+        Self {
+            keymap: Keymap::from_editor(&editor),
+            editor: editor.clone(),
+            editor_path,
+            editor_watcher,
+            languages: languages.clone(),
+            languages_path,
+            languages_watcher,
+            global_editor: editor,
+            global_languages: languages,
+            workspace: None,
+        }
+    }
+
+    pub fn set_global_editor(&mut self, editor: Editor) {
+        self.global_editor = editor;
+        self.apply_overrides();
+    }
+
+    pub fn set_global_languages(&mut self, languages: Languages) {
+        self.global_languages = languages;
+        self.apply_overrides();
+    }
+
+    pub fn load_workspace_overrides(
+        &mut self,
+        workspace_dir: impl AsRef<Path>,
+        proxy: &dyn EventLoopProxy,
+    ) {
+        self.workspace = Some(WorkspaceOverrides::load(workspace_dir, proxy));
+        self.apply_overrides();
+    }
+
+    pub fn clear_workspace_overrides(&mut self) {
+        if self.workspace.take().is_some() {
+            self.apply_overrides();
+        }
+    }
+
+    /// Polls the workspace config watchers and reapplies the overrides if any of them changed.
+    /// Returns `None` if nothing changed.
+    pub fn poll_workspace_overrides(&mut self) -> Option<anyhow::Result<()>> {
+        let workspace = self.workspace.as_mut()?;
+        let mut result = None;
+        if let Some(update) = workspace
+            .editor_watcher
+            .as_mut()
+            .and_then(|watcher| watcher.poll_update())
+        {
+            result = Some(update.map(|editor| workspace.editor = Some(editor)));
+        }
+        if let Some(update) = workspace
+            .languages_watcher
+            .as_mut()
+            .and_then(|watcher| watcher.poll_update())
+        {
+            result = Some(update.map(|languages| workspace.languages = Some(languages)));
+        }
+        if let Some(update) = workspace
+            .tasks_watcher
+            .as_mut()
+            .and_then(|watcher| watcher.poll_update())
+        {
+            result = Some(update.map(|tasks| workspace.tasks = Some(tasks)));
+        }
+        if matches!(result, Some(Ok(()))) {
+            self.apply_overrides();
+        }
+        result
+    }
+
+    /// Rebuilds the effective config from the global config and the workspace overrides
+    pub fn apply_overrides(&mut self) {
+        let workspace = self.workspace.as_ref();
+
+        self.editor = match workspace.and_then(|workspace| workspace.editor.as_ref()) {
+            Some(overrides) => match merge_editor(&self.global_editor, overrides) {
+                Ok(editor) => editor,
+                Err(err) => {
+                    tracing::error!("Error applying workspace config: {err}");
+                    self.global_editor.clone()
+                }
+            },
+            None => self.global_editor.clone(),
+        };
+
+        self.languages = match workspace.and_then(|workspace| workspace.languages.as_ref()) {
+            Some(overrides) => merge_languages(&self.global_languages, overrides),
+            None => self.global_languages.clone(),
+        };
+
+        self.keymap = Keymap::from_editor(&self.editor);
+    }
+}
+
+fn merge_tables(base: &mut toml::Table, overlay: &toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn merge_editor(global: &Editor, overrides: &toml::Table) -> anyhow::Result<Editor> {
+    let mut table = toml::Table::try_from(global)?;
+    merge_tables(&mut table, overrides);
+    Ok(table.try_into()?)
+}
+
+/// Languages are matched by name and only the fields set in the override are replaced
+fn merge_languages(global: &Languages, overrides: &Languages) -> Languages {
+    let mut languages = global.clone();
+    for language in &overrides.languages {
+        match languages
+            .languages
+            .iter_mut()
+            .find(|global| global.name == language.name)
+        {
+            Some(global) => {
+                global.format = language.format.clone().or(global.format.take());
+                global.format_selection = language
+                    .format_selection
+                    .clone()
+                    .or(global.format_selection.take());
+                global.auto_trim_whitespace = language
+                    .auto_trim_whitespace
+                    .or(global.auto_trim_whitespace);
+                global.auto_format = language.auto_format.or(global.auto_format);
+            }
+            None => languages.languages.push(language.clone()),
+        }
+    }
+    languages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_overrides() {
+        let global = Editor::default();
+        let overrides: toml::Table = toml::from_str(
+            r#"
+            theme = "workspace"
+            rulers = [100, 120]
+            [picker]
+            show_hidden = true
+            "#,
+        )
+        .unwrap();
+        let editor = merge_editor(&global, &overrides).unwrap();
+        assert_eq!(editor.theme, "workspace");
+        assert_eq!(editor.rulers, vec![100, 120]);
+        assert!(editor.picker.show_hidden);
+        assert_eq!(
+            editor.picker.follow_gitignore,
+            global.picker.follow_gitignore
+        );
+        assert_eq!(editor.keymap.len(), global.keymap.len());
+
+        let overrides: Languages = toml::from_str(
+            r#"
+            [[language]]
+            name = "rust"
+            format = "cargo fmt"
+            "#,
+        )
+        .unwrap();
+        let languages = merge_languages(&Languages::default(), &overrides);
+        let rust = languages.from_name("rust").unwrap();
+        assert_eq!(rust.format.as_deref(), Some("cargo fmt"));
+    }
 }
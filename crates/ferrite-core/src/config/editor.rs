@@ -1,6 +1,7 @@
-use std::{fs, path::PathBuf};
+use std::{env, fs, path::PathBuf};
 
 use anyhow::Result;
+use ferrite_cli::TermBackend;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
@@ -18,10 +19,26 @@ pub fn default_font() -> String {
     "Noto Mono".into()
 }
 
+pub fn default_scratch_language() -> String {
+    "text".into()
+}
+
+pub fn default_locale() -> String {
+    crate::i18n::DEFAULT_LOCALE.into()
+}
+
 pub fn default_rulers() -> Vec<u16> {
     vec![80]
 }
 
+pub fn default_read_only_files() -> Vec<String> {
+    vec![
+        "target/**".into(),
+        "node_modules/**".into(),
+        "*.lock".into(),
+    ]
+}
+
 pub fn get_false() -> bool {
     false
 }
@@ -49,13 +66,51 @@ pub struct Editor {
     #[serde(default = "get_true")]
     pub case_insensitive_search: bool,
     #[serde(default = "get_true")]
+    pub smart_case_search: bool,
+    #[serde(default = "get_false")]
+    pub whole_word_search: bool,
+    #[serde(default = "get_true")]
     pub pipe_shell_palette: bool,
     #[serde(default = "get_true")]
     pub auto_trim_whitespace: bool,
+    /// Replace every line ending with the line ending of the buffer when saving,
+    /// otherwise only new lines are written with it
+    #[serde(default = "get_true")]
+    pub normalize_line_endings: bool,
+    /// Save files opened through a symlink to the file it points to, otherwise the link is replaced
+    #[serde(default = "get_true")]
+    pub save_through_symlinks: bool,
     #[serde(default = "get_false")]
     pub auto_format: bool,
+    /// Save every modified buffer that has a file when the window or terminal loses focus
+    #[serde(default = "get_false")]
+    pub save_on_focus_lost: bool,
     #[serde(default = "get_true")]
     pub highlight_cursor_line: bool,
+    /// Show the symbols containing the cursor above the text of a buffer
+    #[serde(default = "get_true")]
+    pub breadcrumbs: bool,
+    #[serde(default = "get_true")]
+    pub word_diff: bool,
+    #[serde(default = "get_true")]
+    pub path_completion: bool,
+    /// Show completions from buffer words and snippets while typing, they can always be requested with `complete`
+    #[serde(default = "get_false")]
+    pub auto_complete: bool,
+    /// Render the ansi escape sequences of text piped into the editor, toggled with `ansi-render`
+    #[serde(default = "get_true")]
+    pub ansi_render: bool,
+    /// Highlight lines with log levels such as `ERROR` and `WARN` in buffers followed with `tail`
+    #[serde(default = "get_true")]
+    pub tail_highlight: bool,
+    #[serde(default = "default_scratch_language")]
+    pub scratch_language: String,
+    /// Language of the editor UI, loaded from `locales/<locale>.toml` in the config dir
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// Indentation used for opened files instead of detecting it, a number of spaces or `tabs`
+    #[serde(default)]
+    pub indent: Option<String>,
     #[serde(default)]
     pub line_number: LineNumber,
     #[serde(default)]
@@ -63,11 +118,43 @@ pub struct Editor {
     #[serde(default)]
     pub picker: PickerConfig,
     #[serde(default)]
+    pub files: FilesConfig,
+    #[serde(default)]
     pub info_line: InfoLineConfig,
     #[serde(default)]
+    pub logger: LoggerConfig,
+    #[serde(default)]
+    pub mouse: MouseConfig,
+    #[serde(default)]
+    pub overlay: OverlayConfig,
+    #[serde(default)]
     pub gui: Gui,
     #[serde(default)]
+    pub terminal: TerminalConfig,
+    /// Terminal backend used by the tui, can be overridden with `--term-backend`
+    #[serde(default)]
+    pub term_backend: TermBackend,
+    #[serde(default)]
     pub keymap: IndexMap<Key, KeymapAndMetadata>,
+    #[serde(default)]
+    pub pickers: IndexMap<String, CustomPicker>,
+    /// Expanded when a word boundary is typed after them, languages can add their own
+    #[serde(default)]
+    pub abbreviations: IndexMap<String, String>,
+    /// Filters used to edit encrypted files, keyed by file extension
+    #[serde(default)]
+    pub encryption: IndexMap<String, EncryptionFilter>,
+    #[serde(default)]
+    pub hooks: Vec<Hook>,
+    /// Files matching these globs, relative to the workspace root, are opened read-only
+    #[serde(default = "default_read_only_files")]
+    pub read_only_files: Vec<String>,
+    #[serde(default)]
+    pub plugins: IndexMap<String, PluginConfig>,
+    #[serde(default)]
+    pub tools: IndexMap<String, Tool>,
+    #[serde(default)]
+    pub tasks: IndexMap<String, Task>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -80,13 +167,108 @@ pub struct KeymapAndMetadata {
     pub mode: String,
 }
 
+/// Commands that transform the text of files matching `glob`, they read from stdin and write to stdout
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Hook {
+    pub glob: String,
+    /// Run when the file is opened
+    #[serde(default)]
+    pub on_open: Option<String>,
+    /// Run on the buffer before it is saved
+    #[serde(default)]
+    pub on_save: Option<String>,
+    #[serde(default = "default_hook_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+pub fn default_hook_timeout_ms() -> u64 {
+    3000
+}
+
+/// A plugin process started by the editor, see the `ferrite-plugin` crate for the protocol
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PluginConfig {
+    /// Shell command that starts the plugin
+    pub cmd: String,
+    #[serde(default = "default_plugin_enabled")]
+    pub enabled: bool,
+}
+
+pub fn default_plugin_enabled() -> bool {
+    true
+}
+
+/// A picker filled with the lines printed by a shell command
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CustomPicker {
+    pub cmd: String,
+    #[serde(default)]
+    pub action: CustomPickerAction,
+}
+
+/// Commands that decrypt and encrypt a file, both read from stdin and write to stdout
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EncryptionFilter {
+    pub decrypt: String,
+    pub encrypt: String,
+    /// Prompt for a passphrase which is written to the first line of stdin
+    #[serde(default)]
+    pub passphrase: bool,
+}
+
+/// An external command run with `run <name>`.
+/// `$FILE`, `$SELECTION` and `$LINE` in the command are replaced with shell quoted values from the current buffer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Tool {
+    pub cmd: String,
+    #[serde(default)]
+    pub output: ToolOutput,
+}
+
+/// Where the output of a tool ends up
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolOutput {
+    ReplaceSelection,
+    InsertAtCursor,
+    NewBuffer,
+    /// A read-only pane below the current one that is reused by later runs
+    #[default]
+    Panel,
+}
+
+/// A command run in the task pane with `run <name>`, its output is parsed for problems.
+/// Tasks can also be declared in `.ferrite/tasks.toml` in the workspace.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Task {
+    pub cmd: String,
+    /// Regex with the named groups `file`, `line`, `column` and optionally `message`
+    #[serde(default)]
+    pub problem_matcher: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomPickerAction {
+    /// Open the choice as a file, `path:line` jumps to the line
+    #[default]
+    Open,
+    /// Insert the choice into the current buffer
+    Insert,
+    /// Run a shell command where `{}` is replaced with the shell quoted choice
+    Run(String),
+}
+
 #[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum RenderWhitespace {
     All,
     #[default]
+    #[serde(alias = "off")]
     None,
     Trailing,
+    /// Only whitespace inside of selections
+    Selection,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
@@ -118,7 +300,9 @@ pub enum FontWeight {
 pub enum CursorType {
     Block,
     #[default]
+    #[serde(alias = "beam")]
     Line,
+    Underline,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -129,6 +313,107 @@ pub struct Gui {
     pub font_weight: FontWeight,
     #[serde(default)]
     pub cursor_type: CursorType,
+    /// Shape of the secondary cursors, defaults to `cursor_type`
+    #[serde(default)]
+    pub secondary_cursor_type: Option<CursorType>,
+    #[serde(default)]
+    pub cursor_blink: bool,
+    /// Time in milliseconds the cursor stays visible or hidden while blinking
+    #[serde(default = "default_cursor_blink_interval")]
+    pub cursor_blink_interval: u64,
+    /// Slide the cursor to its new position instead of jumping
+    #[serde(default = "default_cursor_animation")]
+    pub cursor_animation: bool,
+    /// Length of the cursor slide in milliseconds
+    #[serde(default = "default_cursor_animation_duration")]
+    pub cursor_animation_duration: u64,
+    /// Fonts tried in order for characters that `font_family` does not have
+    #[serde(default)]
+    pub fallback_fonts: Vec<String>,
+    /// Fonts tried before `fallback_fonts` for a script,
+    /// keyed by `han`, `kana`, `hangul`, `emoji`, `arabic`, `hebrew`, `cyrillic`, `greek`, `devanagari` or `thai`
+    #[serde(default)]
+    pub script_fonts: IndexMap<String, String>,
+    /// Ligatures and contextual alternates
+    #[serde(default = "default_ligatures")]
+    pub ligatures: bool,
+    #[serde(default)]
+    pub touchpad: Touchpad,
+    /// Wait for the display to refresh before showing a frame
+    #[serde(default = "get_true")]
+    pub vsync: bool,
+    /// Most frames drawn per second, 0 draws a frame whenever something changes
+    #[serde(default)]
+    pub max_fps: u32,
+    /// Show the frames drawn in the last second and how long the last frame took
+    #[serde(default)]
+    pub debug_overlay: bool,
+    /// Only shape the lines of text that changed since the last frame
+    #[serde(default = "get_true")]
+    pub reuse_text_lines: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Touchpad {
+    #[serde(default = "default_pinch_zoom")]
+    pub pinch_zoom: bool,
+    /// Keep scrolling after the fingers are lifted
+    #[serde(default = "default_kinetic_scrolling")]
+    pub kinetic_scrolling: bool,
+    /// How quickly kinetic scrolling slows down, clamped between 1 and 20
+    #[serde(default = "default_kinetic_friction")]
+    pub kinetic_friction: f64,
+    #[serde(default = "default_horizontal_scroll")]
+    pub horizontal_scroll: bool,
+}
+
+pub fn default_pinch_zoom() -> bool {
+    true
+}
+
+pub fn default_kinetic_scrolling() -> bool {
+    true
+}
+
+pub fn default_kinetic_friction() -> f64 {
+    5.0
+}
+
+pub fn default_horizontal_scroll() -> bool {
+    true
+}
+
+impl Default for Touchpad {
+    fn default() -> Self {
+        Self {
+            pinch_zoom: default_pinch_zoom(),
+            kinetic_scrolling: default_kinetic_scrolling(),
+            kinetic_friction: default_kinetic_friction(),
+            horizontal_scroll: default_horizontal_scroll(),
+        }
+    }
+}
+
+pub fn default_ligatures() -> bool {
+    true
+}
+
+pub fn default_cursor_blink_interval() -> u64 {
+    500
+}
+
+pub fn default_cursor_animation() -> bool {
+    true
+}
+
+pub fn default_cursor_animation_duration() -> u64 {
+    80
+}
+
+impl Gui {
+    pub fn secondary_cursor_type(&self) -> CursorType {
+        self.secondary_cursor_type.unwrap_or(self.cursor_type)
+    }
 }
 
 impl Default for Gui {
@@ -137,11 +422,77 @@ impl Default for Gui {
             font_family: default_font(),
             font_weight: FontWeight::default(),
             cursor_type: CursorType::default(),
+            secondary_cursor_type: None,
+            cursor_blink: false,
+            cursor_blink_interval: default_cursor_blink_interval(),
+            cursor_animation: default_cursor_animation(),
+            cursor_animation_duration: default_cursor_animation_duration(),
+            fallback_fonts: Vec::new(),
+            script_fonts: IndexMap::new(),
+            ligatures: default_ligatures(),
+            touchpad: Touchpad::default(),
+            vsync: true,
+            max_fps: 0,
+            debug_overlay: false,
+            reuse_text_lines: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    /// Detected from the `COLORTERM` and `TERM` environment variables
+    #[default]
+    Auto,
+    Truecolor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16,
+}
+
+impl ColorMode {
+    /// Guesses the colors the terminal supports, terminals that don't set `TERM` are assumed to support true color
+    pub fn detect() -> Self {
+        match (env::var("COLORTERM"), env::var("TERM")) {
+            (Ok(colorterm), _) if colorterm == "truecolor" || colorterm == "24bit" => {
+                Self::Truecolor
+            }
+            (_, Ok(term)) if term.contains("256color") => Self::Ansi256,
+            (_, Ok(_)) => Self::Ansi16,
+            (_, Err(_)) => Self::Truecolor,
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerminalConfig {
+    /// Don't paint the theme background so the background of the terminal shows through
+    #[serde(default)]
+    pub transparent_background: bool,
+    /// Colors are reduced to fit in 256 or 16 colors if the terminal has no true color support
+    #[serde(default)]
+    pub colors: ColorMode,
+    /// Copy to the clipboard of the terminal with the OSC 52 escape sequence
+    #[serde(default)]
+    pub osc52_clipboard: Osc52Clipboard,
+    /// Show the current file and workspace in the title of the terminal
+    #[serde(default)]
+    pub set_title: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Osc52Clipboard {
+    /// Used over ssh and when there is no display server to reach
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PickerConfig {
     #[serde(default = "get_true")]
     pub show_hidden: bool,
@@ -159,6 +510,17 @@ pub struct PickerConfig {
     pub file_picker_auto_reload: bool,
 }
 
+/// Globs matched against paths relative to the workspace root,
+/// used by the file scanner and global search
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilesConfig {
+    /// Only files matching one of these globs are included, all files are included if empty
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InfoLineConfig {
     pub left: Vec<String>,
@@ -167,15 +529,125 @@ pub struct InfoLineConfig {
     pub padding: usize,
 }
 
+pub fn default_max_log_messages() -> usize {
+    5000
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoggerConfig {
+    /// Max number of messages kept in the logger pane, older messages are dropped
+    #[serde(default = "default_max_log_messages")]
+    pub max_messages: usize,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self {
+            max_messages: default_max_log_messages(),
+        }
+    }
+}
+
+pub fn default_focus_delay_ms() -> u64 {
+    150
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MouseConfig {
+    /// Focus the pane under the mouse without clicking it
+    #[serde(default = "get_false")]
+    pub focus_follows_mouse: bool,
+    /// How long the mouse has to stay over a pane before it is focused
+    #[serde(default = "default_focus_delay_ms")]
+    pub focus_delay_ms: u64,
+}
+
+impl Default for MouseConfig {
+    fn default() -> Self {
+        Self {
+            focus_follows_mouse: false,
+            focus_delay_ms: default_focus_delay_ms(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BorderStyle {
+    #[default]
+    Plain,
+    Rounded,
+    Double,
+    Thick,
+}
+
+/// Placement of an overlay drawn on top of the panes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverlayLayout {
+    /// Draw the overlay as a centered window instead of docking it
+    pub floating: bool,
+    #[serde(default)]
+    pub max_width: Option<usize>,
+    #[serde(default)]
+    pub max_height: Option<usize>,
+    #[serde(default)]
+    pub border: BorderStyle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverlayConfig {
+    /// When docked pickers cover the whole editor
+    #[serde(default = "default_picker_layout")]
+    pub picker: OverlayLayout,
+    /// When docked the palette is the bottom line of the editor
+    #[serde(default = "default_palette_layout")]
+    pub palette: OverlayLayout,
+}
+
+pub fn default_picker_layout() -> OverlayLayout {
+    OverlayLayout {
+        floating: true,
+        max_width: None,
+        max_height: None,
+        border: BorderStyle::Plain,
+    }
+}
+
+pub fn default_palette_layout() -> OverlayLayout {
+    OverlayLayout {
+        floating: false,
+        max_width: Some(80),
+        max_height: None,
+        border: BorderStyle::Rounded,
+    }
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            picker: default_picker_layout(),
+            palette: default_palette_layout(),
+        }
+    }
+}
+
 impl Default for InfoLineConfig {
     fn default() -> Self {
         Self {
             left: ["size"].iter().map(|s| s.to_string()).collect(),
             center: ["file"].iter().map(|s| s.to_string()).collect(),
-            right: ["branch", "position", "encoding", "language", "spinner"]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
+            right: [
+                "search",
+                "conflicts",
+                "branch",
+                "position",
+                "encoding",
+                "language",
+                "spinner",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
             padding: 1,
         }
     }
@@ -1,11 +1,12 @@
 use std::{fs, path::PathBuf};
 
 use anyhow::Result;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Languages {
-    #[serde(rename = "language")]
+    #[serde(rename = "language", default)]
     pub languages: Vec<Language>,
 }
 
@@ -16,6 +17,12 @@ pub struct Language {
     pub format_selection: Option<String>,
     pub auto_trim_whitespace: Option<bool>,
     pub auto_format: Option<bool>,
+    /// Snippets offered by completion, the prefix maps to the body and `$0` marks the cursor
+    #[serde(default)]
+    pub snippets: IndexMap<String, String>,
+    /// Abbreviations only expanded in this language, they take priority over the global ones
+    #[serde(default)]
+    pub abbreviations: IndexMap<String, String>,
 }
 
 impl Languages {
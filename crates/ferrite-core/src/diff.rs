@@ -0,0 +1,219 @@
+use std::ops::Range;
+
+use ferrite_utility::graphemes::is_word_char;
+use ropey::{Rope, RopeSlice};
+use similar::{capture_diff_slices, Algorithm, DiffOp, TextDiff};
+
+/// Max amount of lines scanned outside of the visible range to find the edges of a hunk
+const MAX_HUNK_SCAN: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordChange {
+    /// Byte range in the rope of the changed text
+    pub range: Range<usize>,
+    /// If the change is part of an added line or a removed line
+    pub added: bool,
+}
+
+/// Splits a line into words, runs of whitespace and single punctuation characters
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, ch)) = chars.next() {
+        let same_class = |next: char| {
+            (is_word_char(ch) && is_word_char(next)) || (ch.is_whitespace() && next.is_whitespace())
+        };
+        if chars.peek().is_some_and(|(_, next)| same_class(*next)) {
+            continue;
+        }
+        let end = i + ch.len_utf8();
+        tokens.push(&text[start..end]);
+        start = end;
+    }
+    tokens
+}
+
+fn token_ranges(tokens: &[&str]) -> Vec<Range<usize>> {
+    let mut offset = 0;
+    tokens
+        .iter()
+        .map(|token| {
+            let range = offset..(offset + token.len());
+            offset = range.end;
+            range
+        })
+        .collect()
+}
+
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+/// Computes the byte ranges of the words that differ between two lines.
+/// Returns the changed ranges in `old` and in `new`.
+pub fn word_diff(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let old_ranges = token_ranges(&old_tokens);
+    let new_ranges = token_ranges(&new_tokens);
+
+    let span = |ranges: &[Range<usize>], tokens: Range<usize>| {
+        if tokens.is_empty() {
+            return 0..0;
+        }
+        ranges[tokens.start].start..ranges[tokens.end - 1].end
+    };
+
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, &old_tokens, &new_tokens) {
+        match op {
+            DiffOp::Equal { .. } => (),
+            DiffOp::Delete { .. } | DiffOp::Insert { .. } | DiffOp::Replace { .. } => {
+                push_range(&mut removed, span(&old_ranges, op.old_range()));
+                push_range(&mut added, span(&new_ranges, op.new_range()));
+            }
+        }
+    }
+
+    (removed, added)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffLine {
+    Added,
+    Removed,
+    Other,
+}
+
+fn classify_line(line: RopeSlice) -> DiffLine {
+    let mut chars = line.chars();
+    let first = chars.next();
+    // File headers look like changed lines but are not
+    let header = first.is_some() && chars.next() == first && chars.next() == first;
+    match first {
+        Some('+') if !header => DiffLine::Added,
+        Some('-') if !header => DiffLine::Removed,
+        _ => DiffLine::Other,
+    }
+}
+
+fn line_content(rope: &Rope, line_idx: usize) -> (usize, String) {
+    let line = rope.line(line_idx);
+    let mut text = line.to_string();
+    while text.ends_with(['\n', '\r']) {
+        text.pop();
+    }
+    // Skip the `+` or `-` marker
+    text.remove(0);
+    (rope.line_to_byte(line_idx) + 1, text)
+}
+
+/// Computes the word level changes of the modified lines in a unified diff.
+/// Only hunks that intersect `lines` are diffed.
+pub fn diff_word_changes(rope: &Rope, lines: Range<usize>) -> Vec<WordChange> {
+    let len_lines = rope.len_lines();
+    let lines = lines.start.min(len_lines)..lines.end.min(len_lines);
+
+    let mut start = lines.start;
+    while start > 0
+        && lines.start - start < MAX_HUNK_SCAN
+        && classify_line(rope.line(start - 1)) != DiffLine::Other
+    {
+        start -= 1;
+    }
+
+    let mut changes = Vec::new();
+    let mut line_idx = start;
+    while line_idx < len_lines && line_idx < lines.end {
+        let removed_start = line_idx;
+        while line_idx < len_lines
+            && line_idx - removed_start < MAX_HUNK_SCAN
+            && classify_line(rope.line(line_idx)) == DiffLine::Removed
+        {
+            line_idx += 1;
+        }
+        let added_start = line_idx;
+        while line_idx < len_lines
+            && line_idx - added_start < MAX_HUNK_SCAN
+            && classify_line(rope.line(line_idx)) == DiffLine::Added
+        {
+            line_idx += 1;
+        }
+
+        let removed = removed_start..added_start;
+        let added = added_start..line_idx;
+        if removed.is_empty() && added.is_empty() {
+            line_idx += 1;
+            continue;
+        }
+
+        // Removed lines are paired up with the added lines that replaced them
+        for (old_idx, new_idx) in removed.zip(added) {
+            let visible = lines.contains(&old_idx) || lines.contains(&new_idx);
+            if !visible {
+                continue;
+            }
+
+            let (old_start, old) = line_content(rope, old_idx);
+            let (new_start, new) = line_content(rope, new_idx);
+            let (old_ranges, new_ranges) = word_diff(&old, &new);
+            changes.extend(old_ranges.into_iter().map(|range| WordChange {
+                range: (old_start + range.start)..(old_start + range.end),
+                added: false,
+            }));
+            changes.extend(new_ranges.into_iter().map(|range| WordChange {
+                range: (new_start + range.start)..(new_start + range.end),
+                added: true,
+            }));
+        }
+    }
+
+    changes
+}
+
+/// Creates a unified diff of two texts
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(old_name, new_name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words() {
+        let (old, new) = word_diff("let x = foo(a, b);", "let y = foo(a, c);");
+        assert_eq!(old, vec![4..5, 15..16]);
+        assert_eq!(new, vec![4..5, 15..16]);
+    }
+
+    #[test]
+    fn hunks() {
+        let rope = Rope::from_str("--- a\n+++ b\n context\n-hello world\n+hello there\n");
+        let changes = diff_word_changes(&rope, 0..rope.len_lines());
+        assert_eq!(
+            changes,
+            vec![
+                WordChange {
+                    range: 28..33,
+                    added: false
+                },
+                WordChange {
+                    range: 41..46,
+                    added: true
+                }
+            ]
+        );
+    }
+}
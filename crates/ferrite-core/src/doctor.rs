@@ -0,0 +1,260 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{self, Write as _},
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    clipboard,
+    config::{editor::Editor, languages::Languages, Config},
+    i18n,
+    theme::EditorTheme,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "[ok]   "),
+            Status::Warn => write!(f, "[warn] "),
+            Status::Error => write!(f, "[error]"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Report {
+    text: String,
+    warnings: usize,
+    errors: usize,
+}
+
+impl Report {
+    fn section(&mut self, name: &str) {
+        if !self.text.is_empty() {
+            self.text.push('\n');
+        }
+        let _ = writeln!(self.text, "{name}");
+    }
+
+    fn check(&mut self, status: Status, msg: impl fmt::Display) {
+        match status {
+            Status::Ok => (),
+            Status::Warn => self.warnings += 1,
+            Status::Error => self.errors += 1,
+        }
+        let _ = writeln!(self.text, "  {status} {msg}");
+    }
+}
+
+/// Checks the environment the editor is running in and returns a plain text report
+/// that can be attached to bug reports.
+pub fn run(config: &Config, themes: &HashMap<String, EditorTheme>) -> String {
+    let mut report = Report::default();
+
+    report.section("Config");
+    check_config_file::<Editor>(&mut report, "editor", Editor::get_default_location());
+    check_config_file::<Languages>(&mut report, "languages", Languages::get_default_location());
+    if let Some(workspace) = &config.workspace {
+        check_config_file::<toml::Table>(
+            &mut report,
+            "workspace editor",
+            Ok(workspace.editor_path.clone()),
+        );
+        check_config_file::<Languages>(
+            &mut report,
+            "workspace languages",
+            Ok(workspace.languages_path.clone()),
+        );
+    }
+    let theme = &config.editor.theme;
+    if themes.contains_key(theme) {
+        report.check(
+            Status::Ok,
+            format!("theme `{theme}` ({} themes available)", themes.len()),
+        );
+    } else {
+        report.check(Status::Error, format!("theme `{theme}` not found"));
+    }
+    let locale = &config.editor.locale;
+    if locale == i18n::DEFAULT_LOCALE {
+        report.check(Status::Ok, format!("locale `{locale}`"));
+    } else {
+        match i18n::get_locale_dir() {
+            Ok(dir) if dir.join(format!("{locale}.toml")).exists() => {
+                report.check(Status::Ok, format!("locale `{locale}`"))
+            }
+            _ => report.check(
+                Status::Warn,
+                format!("locale `{locale}` not found, falling back to english"),
+            ),
+        }
+    }
+
+    report.section("Executables");
+    let executables: HashMap<String, PathBuf> = executable_finder::unique_executables()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|exe| (exe.name, exe.path))
+        .collect();
+    match find_executable(&executables, "git") {
+        Some(path) => report.check(Status::Ok, format!("git: {}", path.display())),
+        None => report.check(Status::Warn, "git not found in PATH"),
+    }
+    let formatters: BTreeSet<_> = config
+        .languages
+        .languages
+        .iter()
+        .flat_map(|language| [&language.format, &language.format_selection])
+        .flatten()
+        .filter_map(|cmd| cmd.split_whitespace().next())
+        .collect();
+    for formatter in formatters {
+        match find_executable(&executables, formatter) {
+            Some(path) => report.check(
+                Status::Ok,
+                format!("formatter `{formatter}`: {}", path.display()),
+            ),
+            None => report.check(
+                Status::Warn,
+                format!("formatter `{formatter}` not found in PATH"),
+            ),
+        }
+    }
+
+    report.section("Clipboard");
+    if clipboard::clipboard_kind() == clipboard::ClipboardKind::Osc52 {
+        report.check(Status::Ok, "using the terminal clipboard through OSC 52");
+    } else if !clipboard::has_system_clipboard() {
+        report.check(
+            Status::Warn,
+            "system clipboard unavailable, using local clipboard",
+        );
+    } else if clipboard::is_using_local_clipboard() {
+        report.check(Status::Ok, "using local clipboard");
+    } else {
+        report.check(Status::Ok, "using system clipboard");
+    }
+
+    report.section("Terminal");
+    for var in ["TERM", "COLORTERM", "TERM_PROGRAM"] {
+        let value = std::env::var(var).unwrap_or_default();
+        report.check(Status::Ok, format!("{var}={value}"));
+    }
+    let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        report.check(Status::Ok, "true color supported");
+    } else {
+        report.check(
+            Status::Warn,
+            "true color not detected, themes may render incorrectly",
+        );
+    }
+    report.check(
+        Status::Ok,
+        format!("stdin is a tty: {}", io::stdin().is_terminal()),
+    );
+    report.check(
+        Status::Ok,
+        format!("stdout is a tty: {}", io::stdout().is_terminal()),
+    );
+    report.check(
+        Status::Ok,
+        format!("term backend: {:?}", config.editor.term_backend),
+    );
+
+    report.section("Directories");
+    match directories::ProjectDirs::from("", "", "ferrite") {
+        Some(directories) => {
+            for (name, dir) in [
+                ("config dir", directories.config_dir()),
+                ("data dir", directories.data_dir()),
+            ] {
+                match check_dir_writable(dir) {
+                    Ok(()) => report.check(Status::Ok, format!("{name}: {}", dir.display())),
+                    Err(err) => report.check(
+                        Status::Error,
+                        format!("{name} {} is not writable: {err}", dir.display()),
+                    ),
+                }
+            }
+        }
+        None => report.check(Status::Error, "unable to find project directory"),
+    }
+
+    format!(
+        "ferrite doctor: {} error(s), {} warning(s)\n\n{}",
+        report.errors, report.warnings, report.text
+    )
+}
+
+fn check_config_file<T: DeserializeOwned>(report: &mut Report, name: &str, path: Result<PathBuf>) {
+    let path = match path {
+        Ok(path) => path,
+        Err(err) => {
+            report.check(Status::Error, format!("{name} config: {err}"));
+            return;
+        }
+    };
+    if !path.exists() {
+        report.check(
+            Status::Ok,
+            format!("{name} config {} not found, using defaults", path.display()),
+        );
+        return;
+    }
+    let result = fs::read_to_string(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|text| Ok(toml::from_str::<T>(&text)?));
+    match result {
+        Ok(_) => report.check(Status::Ok, format!("{name} config: {}", path.display())),
+        Err(err) => report.check(
+            Status::Error,
+            format!("{name} config {}: {err}", path.display()),
+        ),
+    }
+}
+
+fn find_executable(executables: &HashMap<String, PathBuf>, name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if path.is_absolute() {
+        return path.exists().then(|| path.to_path_buf());
+    }
+    executables
+        .get(name)
+        .or_else(|| executables.get(&format!("{name}.exe")))
+        .cloned()
+}
+
+/// Creates the directory if needed and checks that a file can be written to it
+fn check_dir_writable(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(".ferrite-doctor");
+    fs::write(&probe, "")?;
+    fs::remove_file(&probe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_writable() {
+        let dir = tempdir::TempDir::new("ferrite-doctor").unwrap();
+        let nested = dir.path().join("a").join("b");
+        check_dir_writable(&nested).unwrap();
+        assert!(nested.exists());
+        assert_eq!(fs::read_dir(&nested).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use subprocess::{Exec, Redirection};
+
+use crate::{config::editor::EncryptionFilter, workspace::BufferId};
+
+/// How the content of an encrypted file is decrypted when read and encrypted when written.
+/// The plaintext only ever exists in memory and in the pipes to the filter commands.
+#[derive(Clone)]
+pub struct Encryption {
+    filter: EncryptionFilter,
+    passphrase: Option<String>,
+}
+
+impl fmt::Debug for Encryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encryption")
+            .field("filter", &self.filter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Encryption {
+    pub fn new(filter: EncryptionFilter, passphrase: Option<String>) -> Self {
+        Self { filter, passphrase }
+    }
+
+    pub fn filter(&self) -> &EncryptionFilter {
+        &self.filter
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> io::Result<Vec<u8>> {
+        run_filter(&self.filter.decrypt, self.passphrase.as_deref(), ciphertext)
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+        run_filter(&self.filter.encrypt, self.passphrase.as_deref(), plaintext)
+    }
+
+    pub fn read_file(&self, path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+        self.decrypt(&fs::read(path)?)
+    }
+}
+
+/// Something waiting for the user to enter a passphrase
+#[derive(Debug, Clone)]
+pub enum PassphraseRequest {
+    Open(PathBuf),
+    Save(BufferId),
+}
+
+/// Returns the filter configured for the extension of `path`
+pub fn filter_for<'a>(
+    filters: &'a IndexMap<String, EncryptionFilter>,
+    path: &Path,
+) -> Option<&'a EncryptionFilter> {
+    let extension = path.extension()?.to_str()?;
+    filters.get(extension)
+}
+
+fn run_filter(cmd: &str, passphrase: Option<&str>, input: &[u8]) -> io::Result<Vec<u8>> {
+    let home_dir = directories::UserDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    let mut parts = cmd
+        .split_whitespace()
+        .map(|part| match (part.strip_prefix('~'), &home_dir) {
+            (Some(rest), Some(home_dir)) => format!("{}{rest}", home_dir.to_string_lossy()),
+            _ => part.to_string(),
+        });
+    let Some(program) = parts.next() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid encryption command",
+        ));
+    };
+
+    let mut stdin = Vec::with_capacity(input.len());
+    if let Some(passphrase) = passphrase {
+        writeln!(stdin, "{passphrase}")?;
+    }
+    stdin.extend_from_slice(input);
+
+    let mut child = Exec::cmd(&program)
+        .args(&parts.collect::<Vec<_>>())
+        .stdin(Redirection::Pipe)
+        .stdout(Redirection::Pipe)
+        .stderr(Redirection::Pipe)
+        .popen()
+        .map_err(io::Error::other)?;
+    let (stdout, stderr) = child
+        .communicate_bytes(Some(&stdin))
+        .map_err(io::Error::other)?;
+    let exit_status = child.wait().map_err(io::Error::other)?;
+
+    if exit_status.success() {
+        Ok(stdout.unwrap_or_default())
+    } else {
+        let stderr = String::from_utf8_lossy(stderr.as_deref().unwrap_or_default());
+        Err(io::Error::other(format!(
+            "`{program}` failed: {}",
+            stderr.trim()
+        )))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_roundtrip() {
+        let encryption = Encryption::new(
+            EncryptionFilter {
+                decrypt: "tail -n +2".into(),
+                encrypt: "rev".into(),
+                passphrase: true,
+            },
+            Some("hunter2".into()),
+        );
+        assert_eq!(encryption.encrypt(b"abc\n").unwrap(), b"2retnuh\ncba\n");
+        assert_eq!(encryption.decrypt(b"abc\n").unwrap(), b"abc\n");
+
+        let filters = IndexMap::from([("gpg".to_string(), encryption.filter.clone())]);
+        assert!(filter_for(&filters, Path::new("secret.txt.gpg")).is_some());
+        assert!(filter_for(&filters, Path::new("secret.txt")).is_none());
+    }
+}
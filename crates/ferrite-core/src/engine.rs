@@ -1,85 +1,257 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     env,
+    fmt::Display,
+    fs,
     io::{self, Read},
-    num::NonZeroUsize,
+    mem,
     path::{Path, PathBuf},
     process::{Command, Stdio},
-    sync::{atomic::Ordering, mpsc, Arc},
+    sync::{mpsc, Arc},
     time::{Duration, Instant},
 };
 
 use anyhow::Result;
 use ferrite_cli::Args;
+use ferrite_plugin::{BufferEventKind, Message, Request};
 use ferrite_utility::{line_ending, point::Point, trim::trim_path};
+use indexmap::IndexMap;
 use linkify::{LinkFinder, LinkKind};
+use regex::Regex;
 use ropey::Rope;
-use slotmap::{Key as _, SlotMap};
+use slotmap::{Key as _, SecondaryMap, SlotMap};
 
 use crate::{
-    buffer::{self, encoding::get_encoding, Buffer, ViewId},
+    ansi::{self, AnsiParser, AnsiRender},
+    buffer::{
+        self,
+        change::BufferChange,
+        conflict::Resolution,
+        encoding::get_encoding,
+        error::BufferError,
+        search::{stream_search, MatchChunk, SearchOptions, ASYNC_SEARCH_BYTES},
+        write::{DiskState, SaveOptions},
+        Buffer, ViewId,
+    },
     buffer_watcher::BufferWatcher,
     byte_size::format_byte_size,
     clipboard,
+    closed_buffers::{ClosedBuffer, ClosedBuffers},
     cmd::Cmd,
+    completion::{self, Completion, CompletionItem, CompletionKind, WordIndex},
     config::{
-        editor::Editor,
+        editor::{CustomPickerAction, Editor, FilesConfig, RenderWhitespace, Task, ToolOutput},
         keymap::{Keymap, Keymapping},
         languages::Languages,
         Config,
     },
+    diff, doctor,
+    encryption::{self, Encryption, PassphraseRequest},
     event_loop_proxy::{EventLoopControlFlow, EventLoopProxy, UserEvent},
     file_explorer::FileExplorer,
-    git::branch::BranchWatcher,
+    follow::{FollowPane, FollowUpdate},
+    git::{
+        self,
+        branch::BranchWatcher,
+        hunks::{self, HunkAction, HunkInput, HunkOutput},
+        status::{GitAction, GitInput, GitStatus},
+    },
+    goto_file::{self, FileTarget},
+    hooks::{self, HookInput, HookKind, HookOutput},
+    i18n,
+    identifier_stats::{self, IdentifierStatsInput, VariantStats},
     indent::Indentation,
-    job_manager::{JobHandle, JobManager, Progress, Progressor},
-    jobs::{SaveBufferJob, ShellJobHandle},
-    layout::panes::{PaneKind, Panes, Rect},
+    ipc,
+    job_manager::{JobHandle, JobInfo, JobManager, Progress, Progressor},
+    jobs::{SaveBufferInput, SaveBufferJob, SaveConflict, ShellJobHandle},
+    language::get_language_from_path,
+    layout::panes::{layout::Layout, Direction, PaneKind, Panes, Rect},
+    local_history,
+    location_list::{Location, LocationList},
     logger::{LogMessage, LoggerState},
+    notifications::{Notifications, Severity},
+    output::{Output, OutputSource},
     palette::{
         cmd_parser::{self, generic_cmd::CmdTemplateArg},
         completer::CompleterContext,
         CommandPalette, PalettePromptEvent,
     },
+    path_completion,
     picker::{
+        bookmark_picker::{BookmarkItem, BookmarkPreviewer, BookmarkProvider},
         buffer_picker::{BufferFindProvider, BufferItem},
+        custom_picker::{parse_path_line, CustomPickerPreviewer, CustomPickerProvider},
         file_picker::FileFindProvider,
         file_previewer::{is_text_file, FilePreviewer},
-        file_scanner::FileScanner,
+        file_scanner::{build_overrides, FileScanner},
+        git_picker::{GitItem, GitPicker, GitPickerOutput, GitPreviewer, GitProvider},
         global_search_picker::{GlobalSearchMatch, GlobalSearchPreviewer, GlobalSearchProvider},
-        Picker,
+        identifier_stats_picker::{IdentifierStatsPreviewer, IdentifierStatsProvider},
+        job_picker::{JobItem, JobProvider, JobTarget},
+        local_history_picker::{LocalHistoryPreviewer, LocalHistoryProvider, SnapshotItem},
+        outline_picker::{OutlineItem, OutlinePreviewer, OutlineProvider},
+        plugin_picker::{PluginItem, PluginProvider},
+        recent_picker::RecentProvider,
+        register_picker::{RegisterItem, RegisterPreviewer, RegisterProvider},
+        Picker, Previewer,
     },
+    plugins::{self, PluginManager},
+    recent::{self, Recent},
+    recovery::{self, EmergencyBuffer, RecoveredBuffer},
+    refactor::{self, AppliedRefactor, ReplaceInput, Transaction},
+    registers::Registers,
+    replay::{InputRecorder, InputReplay, RecordedEvent},
+    scratch::{self, ScratchPreviewer},
+    scripts::Scripts,
+    search_edit::SearchEdit,
     spinner::Spinner,
-    theme::EditorTheme,
+    tags::Tags,
+    tasks,
+    theme::{EditorTheme, ThemeWatcher},
+    tools::{self, ToolInput, ToolResult},
+    tr,
+    trust::{self, Trust},
     watcher::FileWatcher,
-    workspace::{BufferData, BufferId, Workspace},
+    workspace::{self, BufferData, BufferId, Workspace},
 };
 
+/// Bounds of the font scale of the gui
+pub const MIN_SCALE: f32 = 0.5;
+pub const MAX_SCALE: f32 = 3.0;
+
 pub struct Engine {
     pub workspace: Workspace,
     pub themes: HashMap<String, EditorTheme>,
+    pub theme_watcher: ThemeWatcher,
     pub config: Config,
     pub palette: CommandPalette,
     pub file_picker: Option<Picker<String>>,
     pub buffer_picker: Option<Picker<BufferItem>>,
     pub global_search_picker: Option<Picker<GlobalSearchMatch>>,
+    /// Glob limiting the files searched by the next global search
+    pub global_search_scope: Option<String>,
+    /// Query and scope of the last global search, used by global replace
+    pub last_global_search: Option<(String, Option<String>)>,
+    pub refactor_job: Option<JobHandle<Result<Transaction>>>,
+    pub last_refactor: Option<AppliedRefactor>,
+    pub search_edits: SecondaryMap<BufferId, SearchEdit>,
+    /// Buffers showing the output of shell commands, tasks and tools
+    pub outputs: SecondaryMap<BufferId, Output>,
+    /// Buffers shown with their ansi escape sequences rendered
+    pub ansi_renders: SecondaryMap<BufferId, AnsiRender>,
+    pub project_picker: Option<Picker<String>>,
+    pub scratch_picker: Option<Picker<String>>,
+    pub register_picker: Option<Picker<RegisterItem>>,
+    pub local_history_picker: Option<Picker<SnapshotItem>>,
+    pub plugin_picker: Option<Picker<PluginItem>>,
+    pub identifier_stats_picker: Option<Picker<VariantStats>>,
+    pub identifier_stats_job: Option<JobHandle<Result<Vec<VariantStats>>>>,
+    /// Selects every match in a large buffer, the matches are tagged with the revision
+    /// of the buffer they were found in
+    pub select_matching_job: Option<(BufferId, ViewId, JobHandle<bool, MatchChunk>)>,
+    pub git_picker: Option<(GitPicker, Picker<GitItem>)>,
+    pub git_picker_job: Option<JobHandle<Result<GitPickerOutput>>>,
+    pub bookmark_picker: Option<Picker<BookmarkItem>>,
+    pub outline_picker: Option<Picker<OutlineItem>>,
+    pub plugins: PluginManager,
+    pub scripts: Scripts,
+    /// Name of the picker in the config, what is done with the choice and the picker
+    pub custom_picker: Option<(String, CustomPickerAction, Picker<String>)>,
+    pub job_picker: Option<Picker<JobItem>>,
+    pub completion: Option<Completion>,
+    word_index: WordIndex,
+    /// Abbreviations can be turned off temporarily with `toggle-abbreviations`
+    pub abbreviations_enabled: bool,
     pub branch_watcher: BranchWatcher,
     pub proxy: Box<dyn EventLoopProxy>,
     pub file_scanner: FileScanner,
     pub job_manager: JobManager,
     pub save_jobs: Vec<JobHandle<Result<SaveBufferJob>>>,
+    pub hook_jobs: Vec<JobHandle<Result<HookOutput>>>,
+    pub tool_jobs: Vec<JobHandle<Result<ToolResult>>>,
+    /// Pane showing the output of the last tool run with the panel output
+    pub tool_panel: Option<(BufferId, ViewId)>,
     pub shell_jobs: Vec<(Option<BufferId>, ShellJobHandle)>,
+    /// Pane showing the output of the last task
+    pub task_panel: Option<(BufferId, ViewId)>,
+    /// Name of the running task, the buffer its output is written to and the matcher used to find problems
+    pub running_task: Option<(String, BufferId, Regex)>,
+    pub location_list: LocationList,
+    pub git_status: GitStatus,
+    pub git_hunk_job: Option<(BufferId, ViewId, JobHandle<Result<Option<HunkOutput>>>)>,
     pub spinner: Spinner,
     pub logger_state: LoggerState,
+    pub notifications: Notifications,
+    /// If the file index was being scanned at the last poll, a notification is shown when it finishes
+    was_indexing: bool,
     pub chord: Option<String>,
     pub repeat: Option<String>,
+    pub registers: Registers,
+    /// Register used instead of the clipboard by the next copy, cut or paste
+    pub pending_register: Option<char>,
     pub last_render_time: Duration,
+    pub last_local_snapshot: Instant,
+    pub last_recovery_write: Instant,
+    pub recovery_job: Option<JobHandle<()>>,
+    /// If the last recovery write left a recovery file behind that has to be kept fresh
+    has_recovery_file: bool,
+    /// Value of `workspace_changes` the workspace snapshot kept for the panic hook was taken at
+    workspace_snapshot_changes: Option<u64>,
+    last_workspace_snapshot: Instant,
+    last_workspace_autosave: Instant,
+    /// Value of `workspace_changes` at the last autosave, the workspace is not saved again until it changes
+    autosave_changes: Option<u64>,
+    /// The workspace file written by the last autosave, it is only written again once it changes
+    autosaved_workspace: Option<(PathBuf, String)>,
+    /// Recovery files of sessions that crashed, the user is asked to restore them once on startup
+    pub crashed_sessions: Vec<PathBuf>,
+    pub recovery_prompted: bool,
+    pub follow_pane: Option<FollowPane>,
+    pub tags: Tags,
+    /// File to open or buffer to save once the user has entered a passphrase
+    pub passphrase_request: Option<PassphraseRequest>,
     pub start_of_events: Instant,
-    pub closed_buffers: Vec<PathBuf>,
+    pub closed_buffers: ClosedBuffers,
     pub buffer_watcher: Option<BufferWatcher>,
     pub buffer_area: Rect,
+    /// Pane under the mouse and when the mouse entered it, used by focus follows mouse.
+    /// The instant is cleared once the pane has been focused.
+    pub hovered_pane: Option<(PaneKind, Option<Instant>)>,
+    /// Pane that files dragged over the window will be opened in
+    pub drop_target: Option<PaneKind>,
     pub force_redraw: bool,
-    pub scale: f32,
+    /// Incremented when something shown by every pane may have changed such as the config, the
+    /// layout or the result of a background job. Inputs that only change the text or view of the
+    /// current buffer leave it alone, panes showing other buffers are not rendered again for them.
+    pub generation: u64,
+    /// Incremented by every input and by `invalidate`. Unlike `generation` it also counts inputs
+    /// that only change the current buffer, such as moving the cursor or scrolling, which are
+    /// part of the persisted workspace.
+    workspace_changes: u64,
+    /// Set by frontends that can change their font size
+    pub font_zoom: bool,
+    /// If the window or terminal has focus, animations are paused while it does not
+    pub focused: bool,
+    /// Set by frontends that can suspend to the shell
+    pub can_suspend: bool,
+    /// Set by `suspend`, frontends that can suspend reset it
+    pub suspend_requested: bool,
+    /// Serves `ferrite --remote` if this is the first instance
+    pub ipc_server: Option<ipc::Server>,
+    /// Remote clients waiting for a buffer to be closed
+    pub remote_waiters: Vec<(BufferId, mpsc::Sender<ipc::Response>)>,
+    /// Set when a remote client opened a file, frontends that can raise their window reset it
+    pub focus_requested: bool,
+    /// Runs without a ui for `ferrite exec`, the workspace is neither loaded nor saved
+    pub headless: bool,
+    /// Set when the editor exits because it was terminated, the unsaved buffers are kept for recovery
+    terminated: bool,
+    pub buffer_change_listeners: Vec<cb::Sender<(BufferId, BufferChange)>>,
+    pub buffer_change_receivers: SecondaryMap<BufferId, cb::Receiver<BufferChange>>,
+    /// Writes the inputs to the file given to `--record-input`
+    pub input_recorder: Option<InputRecorder>,
+    /// Feeds the inputs of the file given to `--replay` back in
+    pub input_replay: Option<InputReplay>,
 }
 
 #[profiling::all_functions]
@@ -89,7 +261,25 @@ impl Engine {
         proxy: Box<dyn EventLoopProxy>,
         recv: mpsc::Receiver<LogMessage>,
     ) -> Result<Self> {
-        buffer::set_buffer_proxy(proxy.dup());
+        Self::with_mode(args, proxy, recv, false)
+    }
+
+    /// Creates an engine that does not touch the workspace, the recovery files or the remote server
+    /// and does not start plugins. The workspace is only trusted if it was trusted before.
+    pub fn new_headless(
+        args: &Args,
+        proxy: Box<dyn EventLoopProxy>,
+        recv: mpsc::Receiver<LogMessage>,
+    ) -> Result<Self> {
+        Self::with_mode(args, proxy, recv, true)
+    }
+
+    fn with_mode(
+        args: &Args,
+        proxy: Box<dyn EventLoopProxy>,
+        recv: mpsc::Receiver<LogMessage>,
+        headless: bool,
+    ) -> Result<Self> {
         let mut palette = CommandPalette::new(proxy.dup());
 
         let config_path = Editor::get_default_location().ok();
@@ -126,27 +316,48 @@ impl Engine {
             }
         }
 
-        let keymap = Keymap::from_editor(&config);
-
         if config.local_clipboard {
             clipboard::set_local_clipboard(true);
         }
 
+        if let Err(err) = i18n::set_locale(&config.locale) {
+            palette.set_error(err);
+        }
+
         let themes = EditorTheme::load_themes();
         if !themes.contains_key(&config.theme) {
             config.theme = "default".into();
         }
+        let theme_watcher = ThemeWatcher::new(&config.theme, proxy.dup());
+
+        let ipc_server = match headless {
+            true => Ok(None),
+            false => ipc::Server::start(proxy.dup()),
+        };
+        let ipc_server = match ipc_server {
+            Ok(server) => server,
+            Err(err) => {
+                tracing::error!("Error starting remote server: {err}");
+                None
+            }
+        };
 
         let mut buffers: SlotMap<BufferId, _> = SlotMap::with_key();
         let mut current_buffer_id = BufferId::null();
+        let mut positions = HashMap::new();
+        let file_args = args.file_args();
 
-        for (i, file) in args.files.iter().enumerate() {
+        for (i, file_arg) in file_args.iter().enumerate() {
+            let file = &file_arg.path;
             if i == 0 && file.is_dir() {
                 continue;
             }
 
             let buffer = match Buffer::from_file(file) {
-                Ok(buffer) => buffer,
+                Ok(buffer) => {
+                    recent::add_file(file);
+                    buffer
+                }
                 Err(err) => match err.kind() {
                     io::ErrorKind::NotFound => match Buffer::with_path(file) {
                         Ok(buffer) => buffer,
@@ -162,11 +373,20 @@ impl Engine {
                 },
             };
             current_buffer_id = buffers.insert(buffer);
+            buffers[current_buffer_id].read_only = args.read_only;
+            if let Some(line) = file_arg.line {
+                let column = file_arg.column.unwrap_or(1);
+                positions.insert(
+                    current_buffer_id,
+                    Point::new(column.saturating_sub(1), line.saturating_sub(1)),
+                );
+            }
         }
 
         for (_, buffer) in &mut buffers {
+            apply_indent_override(&config, buffer);
             if let Some(language) = &args.language {
-                if let Err(err) = buffer.set_langauge(language, proxy.dup()) {
+                if let Err(err) = buffer.set_langauge(language) {
                     palette.set_error(err);
                 }
             }
@@ -175,13 +395,13 @@ impl Engine {
         let mut file_daemon = None;
         let mut file_finder = None;
 
-        if let Some(path) = args.files.first() {
+        if let Some(path) = file_args.first().map(|file_arg| &file_arg.path) {
             if path.is_dir() {
                 std::env::set_current_dir(path)?;
                 let daemon = FileScanner::new(std::env::current_dir()?, &config);
                 file_finder = Some(Picker::new(
                     FileFindProvider(daemon.subscribe()),
-                    Some(Box::new(FilePreviewer::new(proxy.dup()))),
+                    Some(Box::new(FilePreviewer::new(proxy.dup(), HashMap::new()))),
                     proxy.dup(),
                     None,
                 ));
@@ -195,9 +415,17 @@ impl Engine {
             FileScanner::new(std::env::current_dir()?, &config)
         };
 
+        if !headless {
+            recent::add_project(std::env::current_dir()?);
+        }
+
         let job_manager = JobManager::new(proxy.dup());
 
-        let mut workspace = match Workspace::load_workspace(buffers.is_empty(), proxy.dup()) {
+        let workspace = match headless {
+            true => Ok(Workspace::default()),
+            false => Workspace::load_workspace(buffers.is_empty(), proxy.dup()),
+        };
+        let mut workspace = match workspace {
             Ok(workspace) => workspace,
             Err(err) => {
                 tracing::error!("Error loading workspace: {err}");
@@ -207,11 +435,48 @@ impl Engine {
 
         if !buffers.is_empty() {
             workspace.buffers = buffers;
+            for buffer in workspace.buffers.values_mut() {
+                let buffer_data = workspace
+                    .buffer_extra_data
+                    .iter()
+                    .find(|buffer_data| buffer.file() == Some(&buffer_data.path));
+                if let Some(buffer_data) = buffer_data {
+                    buffer.set_bookmarks(&buffer_data.bookmarks);
+                }
+            }
             let buffer = &mut workspace.buffers[current_buffer_id];
             let view_id = buffer.create_view();
-            buffer.goto(view_id, args.line as i64);
+            match positions.remove(&current_buffer_id) {
+                Some(point) => buffer.jump_to(view_id, point),
+                None => buffer.goto(view_id, 0),
+            }
             workspace.panes = Panes::new(current_buffer_id, view_id);
+
+            // The other buffers get a view when they are shown, which loads their position from the buffer data
+            for (buffer_id, point) in positions {
+                let buffer = &mut workspace.buffers[buffer_id];
+                let Some(path) = buffer.file().map(Path::to_path_buf) else {
+                    continue;
+                };
+                let view_id = buffer.create_view();
+                buffer.jump_to(view_id, point);
+                let buffer_data = BufferData {
+                    path,
+                    cursors: buffer.views[view_id].cursors.clone(),
+                    line_pos: buffer.line_pos(view_id),
+                    col_pos: buffer.col_pos(view_id),
+                    indent: buffer.indent,
+                    language: buffer.language_name().into(),
+                    bookmarks: buffer.bookmarks(),
+                };
+                buffer.views.remove(view_id);
+                workspace
+                    .buffer_extra_data
+                    .retain(|data| data.path != buffer_data.path);
+                workspace.buffer_extra_data.push(buffer_data);
+            }
         }
+        workspace.set_proxy(proxy.dup());
 
         let branch_watcher = BranchWatcher::new(proxy.dup())?;
 
@@ -221,37 +486,106 @@ impl Engine {
             None
         };
 
-        let config = Config {
-            editor: config,
-            editor_path: config_path,
-            editor_watcher: config_watcher,
+        let config = Config::new(
+            config,
+            config_path,
+            config_watcher,
             languages,
             languages_path,
             languages_watcher,
-            keymap,
-        };
+        );
 
-        Ok(Self {
+        let max_log_messages = config.editor.logger.max_messages;
+        // Plugins are long running processes, a headless engine only runs its own commands
+        let plugins = match headless {
+            true => PluginManager::new(&IndexMap::new(), proxy.dup()),
+            false => PluginManager::new(&config.editor.plugins, proxy.dup()),
+        };
+        let location_list = LocationList::new(proxy.dup());
+        let (scripts, script_errors) = Scripts::load();
+        for err in &script_errors {
+            tracing::error!("{err}");
+        }
+        if let Some(err) = script_errors.into_iter().next() {
+            palette.set_error(err);
+        }
+        let mut engine = Self {
             workspace,
             themes,
+            theme_watcher,
             config,
             palette,
             file_picker: file_finder,
             buffer_picker: None,
             global_search_picker: None,
+            global_search_scope: None,
+            last_global_search: None,
+            refactor_job: None,
+            last_refactor: None,
+            search_edits: SecondaryMap::new(),
+            outputs: SecondaryMap::new(),
+            ansi_renders: SecondaryMap::new(),
+            project_picker: None,
+            scratch_picker: None,
+            register_picker: None,
+            local_history_picker: None,
+            plugin_picker: None,
+            identifier_stats_picker: None,
+            identifier_stats_job: None,
+            select_matching_job: None,
+            git_picker: None,
+            bookmark_picker: None,
+            outline_picker: None,
+            plugins,
+            scripts,
+            registers: Registers::default(),
+            pending_register: None,
+            custom_picker: None,
+            job_picker: None,
+            completion: None,
+            word_index: WordIndex::default(),
+            abbreviations_enabled: true,
             branch_watcher,
             proxy,
             file_scanner: file_daemon,
             job_manager,
             save_jobs: Default::default(),
+            hook_jobs: Default::default(),
+            tool_jobs: Default::default(),
+            tool_panel: None,
             shell_jobs: Default::default(),
+            task_panel: None,
+            running_task: None,
+            location_list,
+            git_status: GitStatus::default(),
+            git_hunk_job: None,
+            git_picker_job: None,
             spinner: Default::default(),
             chord: None,
             repeat: None,
-            logger_state: LoggerState::new(recv),
+            logger_state: LoggerState::new(recv, max_log_messages),
+            notifications: Notifications::new(),
+            was_indexing: false,
             last_render_time: Duration::ZERO,
+            last_local_snapshot: Instant::now(),
+            last_recovery_write: Instant::now(),
+            recovery_job: None,
+            has_recovery_file: false,
+            workspace_snapshot_changes: None,
+            last_workspace_snapshot: Instant::now(),
+            last_workspace_autosave: Instant::now(),
+            autosave_changes: None,
+            autosaved_workspace: None,
+            crashed_sessions: recovery::find_crashed_sessions().unwrap_or_else(|err| {
+                tracing::error!("Error finding recovery files: {err}");
+                Vec::new()
+            }),
+            recovery_prompted: headless,
+            follow_pane: None,
+            tags: Tags::default(),
+            passphrase_request: None,
             start_of_events: Instant::now(),
-            closed_buffers: Vec::new(),
+            closed_buffers: ClosedBuffers::default(),
             buffer_watcher,
             buffer_area: Rect {
                 x: 0,
@@ -259,13 +593,98 @@ impl Engine {
                 width: 10,
                 height: 10,
             },
+            hovered_pane: None,
+            drop_target: None,
             force_redraw: false,
-            scale: 1.0,
-        })
+            generation: 0,
+            workspace_changes: 0,
+            font_zoom: false,
+            focused: true,
+            can_suspend: false,
+            suspend_requested: false,
+            ipc_server,
+            remote_waiters: Vec::new(),
+            focus_requested: false,
+            headless,
+            terminated: false,
+            buffer_change_listeners: Vec::new(),
+            buffer_change_receivers: SecondaryMap::new(),
+            input_recorder: args
+                .record_input
+                .as_deref()
+                .map(InputRecorder::create)
+                .transpose()?,
+            input_replay: match &args.replay {
+                Some(path) if !headless => Some(InputReplay::load(path)?),
+                _ => None,
+            },
+        };
+        engine.check_workspace_trust();
+        // Buffers opened from the command line or restored from the workspace
+        let opened: Vec<_> = engine.workspace.buffers.keys().collect();
+        for buffer_id in opened {
+            engine.check_opened_buffer(buffer_id);
+        }
+
+        Ok(engine)
+    }
+
+    /// Tells the user about problems with the text of a buffer that was just opened
+    fn check_opened_buffer(&mut self, buffer_id: BufferId) {
+        let buffer = &self.workspace.buffers[buffer_id];
+        if buffer.has_mixed_line_endings() {
+            self.palette
+                .set_msg(tr!("buffer.mixed_line_endings", name = buffer.name()));
+        }
+    }
+
+    /// Maps the canonical path of every open buffer backed by a file to its text
+    pub fn get_open_buffer_ropes(&self) -> HashMap<PathBuf, Rope> {
+        self.workspace
+            .buffers
+            .values()
+            .filter_map(|buffer| {
+                let path = dunce::canonicalize(buffer.file()?).ok()?;
+                Some((path, buffer.rope().clone()))
+            })
+            .collect()
+    }
+
+    /// Returns a receiver that gets every change made to any buffer in the workspace.
+    /// Changes are forwarded when the engine polls so they may arrive slightly delayed.
+    pub fn subscribe_buffer_changes(&mut self) -> cb::Receiver<(BufferId, BufferChange)> {
+        let (tx, rx) = cb::unbounded();
+        self.buffer_change_listeners.push(tx);
+        rx
+    }
+
+    fn forward_buffer_changes(&mut self) {
+        if self.buffer_change_listeners.is_empty() {
+            self.buffer_change_receivers.clear();
+            return;
+        }
+
+        self.buffer_change_receivers
+            .retain(|buffer_id, _| self.workspace.buffers.contains_key(buffer_id));
+        for (buffer_id, buffer) in &mut self.workspace.buffers {
+            if !self.buffer_change_receivers.contains_key(buffer_id) {
+                self.buffer_change_receivers
+                    .insert(buffer_id, buffer.subscribe_changes());
+            }
+        }
+
+        for (buffer_id, receiver) in &self.buffer_change_receivers {
+            for change in receiver.try_iter() {
+                self.buffer_change_listeners
+                    .retain(|listener| listener.send((buffer_id, change.clone())).is_ok());
+            }
+        }
     }
 
     pub fn do_polling(&mut self, control_flow: &mut EventLoopControlFlow) {
         self.logger_state.update();
+        self.forward_buffer_changes();
+        self.handle_remote_requests();
 
         if !self.config.editor.watch_open_files {
             self.buffer_watcher = None;
@@ -277,14 +696,12 @@ impl Engine {
 
         if let Some(config_watcher) = &mut self.config.editor_watcher {
             if let Some(result) = config_watcher.poll_update() {
+                self.invalidate();
                 match result {
                     Ok(editor) => {
-                        self.config.editor = editor;
-                        if !self.themes.contains_key(&self.config.editor.theme) {
-                            self.config.editor.theme = "default".into();
-                        }
-                        self.palette.set_msg("Reloaded editor config");
-                        self.config.keymap = Keymap::from_editor(&self.config.editor);
+                        self.config.set_global_editor(editor);
+                        self.palette.set_msg(tr!("config.reloaded_editor"));
+                        self.config_updated();
                     }
                     Err(err) => self.palette.set_error(err),
                 }
@@ -293,16 +710,44 @@ impl Engine {
 
         if let Some(config_watcher) = &mut self.config.languages_watcher {
             if let Some(result) = config_watcher.poll_update() {
+                self.invalidate();
                 match result {
                     Ok(languages) => {
-                        self.config.languages = languages;
-                        self.palette.set_msg("Reloaded languages");
+                        self.config.set_global_languages(languages);
+                        self.palette.set_msg(tr!("config.reloaded_languages"));
                     }
                     Err(err) => self.palette.set_error(err),
                 }
             }
         }
 
+        if self.theme_watcher.name() != self.config.editor.theme {
+            self.theme_watcher = ThemeWatcher::new(&self.config.editor.theme, self.proxy.dup());
+        }
+        if let Some(result) = self.theme_watcher.poll_update() {
+            self.invalidate();
+            match result {
+                Ok(theme) => {
+                    let name = self.theme_watcher.name().to_string();
+                    self.palette
+                        .set_msg(tr!("config.reloaded_theme", name = &name));
+                    self.themes.insert(name, theme);
+                }
+                Err(err) => self.palette.set_error(err),
+            }
+        }
+
+        if let Some(result) = self.config.poll_workspace_overrides() {
+            self.invalidate();
+            match result {
+                Ok(()) => {
+                    self.palette.set_msg(tr!("config.reloaded_workspace"));
+                    self.config_updated();
+                }
+                Err(err) => self.palette.set_error(err),
+            }
+        }
+
         let mut new_buffers = Vec::new();
         for (_, buffer) in &mut self.workspace.buffers {
             if let Some(path) = buffer.file() {
@@ -325,6 +770,7 @@ impl Engine {
                             if buffer.language_name() != buffer_data.language {
                                 buffer_data.language = buffer.language_name().into();
                             }
+                            buffer_data.bookmarks = buffer.bookmarks();
                         }
                     }
                     None => {
@@ -336,6 +782,7 @@ impl Engine {
                                 col_pos: buffer.col_pos(view_id),
                                 indent: buffer.indent,
                                 language: buffer.language_name().into(),
+                                bookmarks: buffer.bookmarks(),
                             });
                         }
                     }
@@ -346,48 +793,212 @@ impl Engine {
             .buffer_extra_data
             .extend_from_slice(&new_buffers);
 
+        let mut finished_saves = Vec::new();
         for job in &mut self.save_jobs {
             if let Ok(result) = job.try_recv() {
-                match result {
-                    Ok(job) => {
-                        if let Some(buffer) = self.workspace.buffers.get_mut(job.buffer_id) {
-                            if job.last_edit <= buffer.get_last_edit() {
-                                buffer.mark_saved();
-                            } else {
-                                buffer.mark_history_dirty();
-                            }
+                finished_saves.push(result);
+            }
+        }
+        self.save_jobs.retain(|job| !job.is_finished());
+        for result in finished_saves {
+            self.invalidate();
+            match result {
+                Ok(job) => {
+                    if let Some(buffer) = self.workspace.buffers.get_mut(job.buffer_id) {
+                        if buffer.file() == Some(&job.path) {
+                            buffer.set_disk_state(job.disk_state);
+                        }
+                        if job.last_edit <= buffer.get_last_edit() {
+                            buffer.mark_saved();
+                        } else {
+                            buffer.mark_history_dirty();
                         }
+                        self.plugins
+                            .buffer_event(BufferEventKind::Save, job.buffer_id, buffer);
+                    }
+
+                    let path = job.path.file_name().unwrap_or_default().to_string_lossy();
+                    self.notify(
+                        Severity::Info,
+                        tr!(
+                            "buffer.written",
+                            name = path,
+                            size = format_byte_size(job.written)
+                        ),
+                    );
+                }
 
-                        let path = job.path.file_name().unwrap_or_default().to_string_lossy();
-                        self.palette.set_msg(format!(
-                            "`{}` written: {}",
-                            path,
-                            format_byte_size(job.written)
-                        ));
+                Err(e) => match e.downcast::<SaveConflict>() {
+                    Ok(conflict) => {
+                        let name = conflict.path.file_name().unwrap_or_default();
+                        let msg = if conflict.replaced {
+                            tr!("prompt.replaced_on_disk", name = name.to_string_lossy())
+                        } else {
+                            tr!("prompt.changed_on_disk", name = name.to_string_lossy())
+                        };
+                        self.palette.set_prompt(
+                            msg,
+                            ('o', PalettePromptEvent::OverwriteBuffer(conflict.buffer_id)),
+                            ('m', PalettePromptEvent::MergeBuffer(conflict.buffer_id)),
+                        );
                     }
+                    Err(e) => self.notify(Severity::Error, e),
+                },
+            }
+        }
+
+        let mut finished_hooks = Vec::new();
+        for job in &mut self.hook_jobs {
+            if let Ok(result) = job.try_recv() {
+                finished_hooks.push(result);
+            }
+        }
+        self.hook_jobs.retain(|job| !job.is_finished());
+        for result in finished_hooks {
+            self.invalidate();
+            self.finish_hook_job(result);
+        }
+
+        if let Some((action, result)) = self.git_status.poll(&mut self.job_manager) {
+            self.invalidate();
+            self.finish_git_action(action, result);
+        }
 
-                    Err(e) => self.palette.set_msg(e),
+        if self.tags.poll() {
+            if let Some(follow) = self
+                .follow_pane
+                .as_ref()
+                .filter(|follow| self.workspace.buffers.contains_key(follow.buffer_id))
+            {
+                if let Some(symbol) = follow.shown().map(str::to_string) {
+                    let (buffer_id, view_id) = (follow.buffer_id, follow.view_id);
+                    self.invalidate();
+                    self.show_definition(buffer_id, view_id, &symbol);
+                }
+            }
+        }
+
+        if let Some((buffer_id, view_id, job)) = &mut self.git_hunk_job {
+            if let Ok(result) = job.try_recv() {
+                let (buffer_id, view_id) = (*buffer_id, *view_id);
+                self.git_hunk_job = None;
+                self.invalidate();
+                self.finish_hunk_job(buffer_id, view_id, result);
+            }
+        }
+
+        let mut finished_tools = Vec::new();
+        for job in &mut self.tool_jobs {
+            if let Ok(result) = job.try_recv() {
+                finished_tools.push(result);
+            }
+        }
+        self.tool_jobs.retain(|job| !job.is_finished());
+        for result in finished_tools {
+            self.invalidate();
+            match result {
+                Ok(result) => self.show_tool_output(result),
+                Err(err) => self.notify(Severity::Error, err),
+            }
+        }
+
+        for (plugin, request) in self.plugins.poll() {
+            self.invalidate();
+            self.handle_plugin_request(&plugin, request);
+        }
+        self.plugins.buffer_changes(self.workspace.buffers.iter());
+
+        if let Some(job) = &mut self.refactor_job {
+            if let Ok(result) = job.try_recv() {
+                self.refactor_job = None;
+                self.invalidate();
+                match result {
+                    Ok(transaction) => {
+                        self.apply_refactor(transaction);
+                    }
+                    Err(err) => self.notify(Severity::Error, err),
+                }
+            }
+        }
+
+        for buffer in self.workspace.buffers.values_mut() {
+            buffer.poll_searches(&mut self.job_manager);
+        }
+        self.poll_select_matching_job();
+
+        if let Some(job) = &mut self.git_picker_job {
+            if let Ok(result) = job.try_recv() {
+                self.git_picker_job = None;
+                self.invalidate();
+                match result {
+                    Ok(GitPickerOutput::Items(GitPicker::Log { path, .. }, items))
+                        if items.is_empty() =>
+                    {
+                        self.palette.set_msg(tr!("git.no_commits", path = path));
+                    }
+                    Ok(GitPickerOutput::Items(git_picker, items)) => {
+                        self.open_git_picker(git_picker, items.into_iter().collect());
+                    }
+                    Ok(GitPickerOutput::Revision { path, hash, text }) => {
+                        self.open_revision(&path, &hash, &text);
+                    }
+                    Err(err) => self.palette.set_error(err),
+                }
+            }
+        }
+
+        if let Some(job) = &mut self.identifier_stats_job {
+            if let Ok(result) = job.try_recv() {
+                self.identifier_stats_job = None;
+                self.invalidate();
+                match result {
+                    Ok(variants) => self.open_identifier_stats_picker(variants),
+                    Err(err) => self.notify(Severity::Error, err),
                 }
             }
         }
-        self.save_jobs.retain(|job| !job.is_finished());
 
+        let mut finished_task = None;
+        let mut failed_task = false;
+        let mut shell_results = Vec::new();
         for (buffer_id, job) in &mut self.shell_jobs {
             if let Ok(result) = job.poll_progress() {
                 match result {
-                    Progress::End(Ok((buffer_id, rope))) => {
+                    Progress::End(Ok((buffer_id, rope, spans))) => {
                         if let Some(buffer_id) = buffer_id {
-                            if let Some(buffer) = self.workspace.buffers.get_mut(buffer_id) {
-                                buffer.replace_rope(rope);
+                            if self
+                                .running_task
+                                .as_ref()
+                                .is_some_and(|(_, task_buffer_id, _)| *task_buffer_id == buffer_id)
+                            {
+                                finished_task = Some(rope.to_string());
+                            }
+                            if let (Some(buffer), Some(output)) = (
+                                self.workspace.buffers.get_mut(buffer_id),
+                                self.outputs.get_mut(buffer_id),
+                            ) {
+                                output.spans = spans;
+                                output.show(buffer, rope);
                             }
                         } else {
-                            self.palette.set_msg(rope.to_string());
+                            shell_results.push((Severity::Info, rope.to_string()));
                         }
                     }
-                    Progress::End(Err(e)) => self.palette.set_error(e),
-                    Progress::Progress((buffer_id, rope)) => {
-                        if let Some(buffer) = self.workspace.buffers.get_mut(buffer_id) {
-                            buffer.replace_rope(rope);
+                    Progress::End(Err(e)) => {
+                        failed_task |= buffer_id.is_some_and(|buffer_id| {
+                            self.running_task
+                                .as_ref()
+                                .is_some_and(|(_, task_buffer_id, _)| *task_buffer_id == buffer_id)
+                        });
+                        shell_results.push((Severity::Error, e.to_string()));
+                    }
+                    Progress::Progress((buffer_id, rope, spans)) => {
+                        if let (Some(buffer), Some(output)) = (
+                            self.workspace.buffers.get_mut(buffer_id),
+                            self.outputs.get_mut(buffer_id),
+                        ) {
+                            output.spans.extend(spans);
+                            output.show(buffer, rope);
                             buffer.auto_detect_language();
                         }
                     }
@@ -396,11 +1007,20 @@ impl Engine {
 
             if let Some(buffer_id) = buffer_id {
                 if !self.workspace.buffers.contains_key(*buffer_id) {
-                    job.kill();
+                    job.cancel();
                 }
             }
         }
 
+        for (severity, msg) in shell_results {
+            self.notify(severity, msg);
+        }
+        if let Some(output) = finished_task {
+            self.finish_task(&output);
+        } else if failed_task {
+            self.running_task = None;
+        }
+
         for (buffer_id, buffer) in &mut self.workspace.buffers {
             for view_id in buffer.views.keys().collect::<Vec<_>>() {
                 if !self
@@ -417,13 +1037,194 @@ impl Engine {
 
         self.job_manager.poll_jobs();
 
-        let duration = self
-            .spinner
-            .update(!self.save_jobs.is_empty() || !self.shell_jobs.is_empty());
-        *control_flow = EventLoopControlFlow::WaitMax(duration);
+        let indexing = self.file_scanner.status().scanning;
+        if self.was_indexing && !indexing {
+            let files = self.file_scanner.files().count();
+            if self.file_scanner.status().cancelled {
+                self.notify(Severity::Warning, tr!("index.cancelled", files = files));
+            } else {
+                self.notify(Severity::Info, tr!("index.finished", files = files));
+            }
+        }
+        self.was_indexing = indexing;
+
+        if self.last_local_snapshot.elapsed() >= local_history::SNAPSHOT_INTERVAL {
+            self.snapshot_dirty_buffers();
+        }
+
+        self.update_emergency_snapshot();
+        if self.last_recovery_write.elapsed() >= recovery::RECOVERY_INTERVAL {
+            self.write_recovery();
+        }
+
+        if !self.headless {
+            if self.workspace_snapshot_changes != Some(self.workspace_changes)
+                && self.last_workspace_snapshot.elapsed() >= workspace::SNAPSHOT_INTERVAL
+            {
+                self.snapshot_workspace();
+            }
+            if self.autosave_changes != Some(self.workspace_changes)
+                && self.last_workspace_autosave.elapsed() >= workspace::AUTOSAVE_INTERVAL
+            {
+                self.autosave_workspace();
+            }
+        }
+
+        if !self.recovery_prompted && !self.crashed_sessions.is_empty() && !self.palette.has_focus()
+        {
+            self.recovery_prompted = true;
+            self.invalidate();
+            self.palette.set_prompt(
+                tr!("prompt.recover"),
+                ('y', PalettePromptEvent::RestoreRecovered),
+                ('n', PalettePromptEvent::DiscardRecovered),
+            );
+        }
+
+        // Only timers that are pending wake the event loop, an idle editor waits for the next event
+        let spinner = self.spinner.update(self.has_running_jobs(), self.focused);
+        let replay = self.replay_inputs(control_flow);
+        let hovered_pane = self.focus_hovered_pane();
+        let follow_pane = self.update_follow_pane();
+        let toast = self.notifications.toast_remaining();
+        // Keep the recovery file fresh while idle so it is not mistaken for one left by a crash
+        let recovery = (self.has_recovery_file || self.has_recoverable_buffers()).then(|| {
+            recovery::RECOVERY_INTERVAL.saturating_sub(self.last_recovery_write.elapsed())
+        });
+        let workspace_snapshot = (!self.headless
+            && self.workspace_snapshot_changes != Some(self.workspace_changes))
+        .then(|| {
+            workspace::SNAPSHOT_INTERVAL.saturating_sub(self.last_workspace_snapshot.elapsed())
+        });
+        let workspace_autosave =
+            (!self.headless && self.autosave_changes != Some(self.workspace_changes)).then(|| {
+                workspace::AUTOSAVE_INTERVAL.saturating_sub(self.last_workspace_autosave.elapsed())
+            });
+        let duration = [
+            spinner,
+            replay,
+            hovered_pane,
+            follow_pane,
+            toast,
+            recovery,
+            workspace_snapshot,
+            workspace_autosave,
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        *control_flow = match duration {
+            Some(duration) => EventLoopControlFlow::WaitMax(duration),
+            None => EventLoopControlFlow::Wait,
+        };
+    }
+
+    /// Feeds the recorded inputs that are due into the engine.
+    /// Returns the time until the next input is due.
+    fn replay_inputs(&mut self, control_flow: &mut EventLoopControlFlow) -> Option<Duration> {
+        let replay = self.input_replay.as_mut()?;
+        let mut due = Vec::new();
+        while let Some(event) = replay.next_due() {
+            due.push(event);
+        }
+        if replay.is_finished() {
+            let replay = self.input_replay.take()?;
+            self.notify(
+                Severity::Info,
+                tr!(
+                    "misc.replay_finished",
+                    count = replay.replayed(),
+                    path = replay.path().display()
+                ),
+            );
+        }
+        for event in due {
+            self.replay_event(event, control_flow);
+        }
+        self.input_replay.as_ref()?.until_next()
+    }
+
+    /// Gives a recorded input to the engine as if it came from the frontend
+    pub fn replay_event(&mut self, event: RecordedEvent, control_flow: &mut EventLoopControlFlow) {
+        self.invalidate();
+        match event {
+            RecordedEvent::BufferArea(area) => self.buffer_area = area,
+            RecordedEvent::Cmd(cmd) => self.handle_input_command(cmd, control_flow),
+            // The masked input was not recorded so the prompt is cancelled instead
+            RecordedEvent::Redacted => {
+                if self.palette.is_masked() {
+                    self.palette.reset();
+                }
+            }
+        }
+    }
+
+    /// Returns true while jobs that the spinner is shown for are running
+    pub fn has_running_jobs(&self) -> bool {
+        !self.save_jobs.is_empty()
+            || !self.hook_jobs.is_empty()
+            || !self.tool_jobs.is_empty()
+            || !self.shell_jobs.is_empty()
+            || self.git_status.is_busy()
+            || self.git_hunk_job.is_some()
+            || self.git_picker_job.is_some()
+            || self.refactor_job.is_some()
+            || self.identifier_stats_job.is_some()
+            || self.select_matching_job.is_some()
+            || !self.job_manager.jobs().is_empty()
+    }
+
+    /// The spinner followed by the progress of the most recently active job for the statusline
+    pub fn progress_indicator(&self) -> Option<String> {
+        let spinner = self.spinner.current()?;
+        match self.job_manager.current_job() {
+            Some(job) => Some(format!("{spinner} {}", job.summary())),
+            None => Some(spinner.to_string()),
+        }
+    }
+
+    /// Called by the frontends when the mouse moves, `pane` is the pane under the mouse
+    pub fn hover_pane(&mut self, pane: Option<PaneKind>) {
+        if !self.config.editor.mouse.focus_follows_mouse {
+            return;
+        }
+        match pane {
+            Some(pane) => {
+                if self.hovered_pane.is_none_or(|(hovered, _)| hovered != pane) {
+                    self.hovered_pane = Some((pane, Some(Instant::now())));
+                }
+            }
+            None => self.hovered_pane = None,
+        }
+    }
+
+    /// Focuses the hovered pane once the mouse has stayed over it for the configured delay.
+    /// Returns the time left until then if the delay has not passed yet.
+    fn focus_hovered_pane(&mut self) -> Option<Duration> {
+        let (pane, Some(entered)) = self.hovered_pane? else {
+            return None;
+        };
+        let delay = Duration::from_millis(self.config.editor.mouse.focus_delay_ms);
+        let elapsed = entered.elapsed();
+        if elapsed < delay {
+            return Some(delay - elapsed);
+        }
+        if self.workspace.panes.contains(pane) {
+            self.workspace.panes.make_current(pane);
+            self.invalidate();
+        }
+        self.hovered_pane = Some((pane, None));
+        None
     }
 
     pub fn handle_input_command(&mut self, input: Cmd, control_flow: &mut EventLoopControlFlow) {
+        if let Some(recorder) = &mut self.input_recorder {
+            if let Err(err) = recorder.record(self.buffer_area, &input, self.palette.is_masked()) {
+                self.input_recorder = None;
+                self.notify(Severity::Error, tr!("misc.record_failed", err = err));
+            }
+        }
+
         if let Some(repeat) = &mut self.repeat {
             match input {
                 Cmd::Char { ch } if ch.is_ascii_digit() => {
@@ -444,10 +1245,7 @@ impl Engine {
                         }
                     };
                     if input.is_repeatable() {
-                        self.palette.set_msg(format!("Repeated: {input}"));
-                        for _ in 0..number {
-                            self.handle_single_input_command(input.clone(), control_flow);
-                        }
+                        self.run_repeated(input, number, control_flow);
                     } else {
                         self.handle_single_input_command(input, control_flow);
                         self.repeat = None;
@@ -459,7 +1257,78 @@ impl Engine {
         }
 
         if let Some(repeat) = &self.repeat {
-            self.palette.set_msg(format!("Repeat: {repeat}"));
+            self.palette.set_msg(tr!("misc.repeat", count = repeat));
+        }
+    }
+
+    /// Marks everything shown as changed so every pane is rendered again on the next frame
+    pub fn invalidate(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        self.workspace_changes = self.workspace_changes.wrapping_add(1);
+    }
+
+    /// Returns true if `input` is handled by the current buffer and only changes its text or view
+    fn is_local_input(&self, input: &Cmd) -> bool {
+        input.is_local()
+            && self.repeat.is_none()
+            && self.chord.is_none()
+            && self.completion.is_none()
+            && !self.palette.has_focus()
+            && !self.has_open_picker()
+            && matches!(
+                self.workspace.panes.get_current_pane(),
+                PaneKind::Buffer(..)
+            )
+    }
+
+    /// Returns true if any picker is shown over the panes
+    pub fn has_open_picker(&self) -> bool {
+        self.file_picker.is_some()
+            || self.buffer_picker.is_some()
+            || self.global_search_picker.is_some()
+            || self.project_picker.is_some()
+            || self.scratch_picker.is_some()
+            || self.register_picker.is_some()
+            || self.local_history_picker.is_some()
+            || self.plugin_picker.is_some()
+            || self.identifier_stats_picker.is_some()
+            || self.git_picker.is_some()
+            || self.bookmark_picker.is_some()
+            || self.outline_picker.is_some()
+            || self.custom_picker.is_some()
+            || self.job_picker.is_some()
+    }
+
+    /// Closes every picker, openers call it first so only one picker is shown at a time
+    pub fn close_pickers(&mut self) {
+        self.file_picker = None;
+        self.buffer_picker = None;
+        self.global_search_picker = None;
+        self.project_picker = None;
+        self.scratch_picker = None;
+        self.register_picker = None;
+        self.local_history_picker = None;
+        self.plugin_picker = None;
+        self.identifier_stats_picker = None;
+        self.git_picker = None;
+        self.bookmark_picker = None;
+        self.outline_picker = None;
+        self.custom_picker = None;
+        self.job_picker = None;
+    }
+
+    /// Runs `cmd` `count` times, edits to the current buffer are undone as a single step
+    fn run_repeated(&mut self, cmd: Cmd, count: u16, control_flow: &mut EventLoopControlFlow) {
+        self.palette.set_msg(tr!("misc.repeated", cmd = cmd));
+        let buffer_id = self.get_current_buffer_id().map(|(buffer_id, _)| buffer_id);
+        if let Some(buffer_id) = buffer_id {
+            self.workspace.buffers[buffer_id].begin_undo_group();
+        }
+        for _ in 0..count {
+            self.handle_single_input_command(cmd.clone(), control_flow);
+        }
+        if let Some(buffer) = buffer_id.and_then(|id| self.workspace.buffers.get_mut(id)) {
+            buffer.end_undo_group();
         }
     }
 
@@ -468,11 +1337,23 @@ impl Engine {
         input: Cmd,
         control_flow: &mut EventLoopControlFlow,
     ) {
+        if self.is_local_input(&input) {
+            self.workspace_changes = self.workspace_changes.wrapping_add(1);
+        } else {
+            self.invalidate();
+        }
         if !matches!(input, Cmd::InputMode { .. }) {
             self.chord = None;
         }
         match input {
             Cmd::ForceRedraw => self.force_redraw = true,
+            Cmd::Suspend => {
+                if self.can_suspend {
+                    self.suspend_requested = true;
+                } else {
+                    self.palette.set_error(tr!("misc.suspend_unsupported"));
+                }
+            }
             Cmd::RotateFile => {
                 if let Some((buffer, _)) = self.get_current_buffer() {
                     match buffer.get_next_file() {
@@ -483,21 +1364,67 @@ impl Engine {
                     };
                 };
             }
+            Cmd::HistorySearch | Cmd::Repeat if self.palette.has_focus() => {
+                self.palette.history_search();
+            }
+            Cmd::HistorySearch => {
+                self.handle_single_input_command(Cmd::FocusPalette, control_flow);
+                self.palette.history_search();
+            }
             Cmd::Repeat => {
                 self.repeat = Some(String::new());
             }
+            Cmd::RepeatCommand { count, command } if command.is_repeatable() => {
+                self.run_repeated(*command, count, control_flow)
+            }
+            // Like a count typed before a command that cannot be repeated, it is run once
+            Cmd::RepeatCommand { command, .. } => {
+                self.handle_single_input_command(*command, control_flow)
+            }
             Cmd::ReopenBuffer => self.reopen_last_closed_buffer(),
+            Cmd::Recover => self.restore_crashed_sessions(),
+            Cmd::Doctor => self.doctor(),
+            Cmd::ShowLastError => self.show_last_error(),
+            Cmd::SelectRegister { register } if Registers::is_valid_name(register) => {
+                self.pending_register = Some(register);
+                self.palette
+                    .set_msg(tr!("misc.register_selected", register = register));
+            }
+            Cmd::SelectRegister { register } => self
+                .palette
+                .set_error(tr!("misc.invalid_register", register = register)),
+            Cmd::Copy if self.pending_register.is_some() => {
+                let register = self.pending_register.take().unwrap();
+                self.copy_to_register(register, false);
+            }
+            Cmd::Cut if self.pending_register.is_some() => {
+                let register = self.pending_register.take().unwrap();
+                self.copy_to_register(register, true);
+            }
+            Cmd::Paste if self.pending_register.is_some() => {
+                let register = self.pending_register.take().unwrap();
+                self.paste_from_register(register);
+            }
+            Cmd::CopyTo { register } => self.copy_to_register(register, false),
+            Cmd::PasteFrom { register } => self.paste_from_register(register),
+            Cmd::RegisterPicker => self.open_register_picker(),
+            Cmd::LocalHistory => self.open_local_history_picker(),
+            Cmd::PluginManager => self.open_plugin_picker(),
+            Cmd::ReloadScripts => self.reload_scripts(),
+            Cmd::FollowPane => self.toggle_follow_pane(),
             Cmd::UrlOpen => self.open_selected_url(),
+            Cmd::GotoFileUnderCursor => self.goto_file_under_cursor(),
+            Cmd::OpenShellPalette if !self.workspace.trusted => {
+                self.palette.set_error(tr!("workspace.safe_mode_error"));
+            }
             Cmd::OpenShellPalette => {
-                self.file_picker = None;
-                self.buffer_picker = None;
-                self.global_search_picker = None;
+                self.close_pickers();
                 self.palette.focus(
                     "$ ",
                     "shell",
                     CompleterContext::new(
                         self.themes.keys().cloned().collect(),
-                        self.workspace.config.actions.keys().cloned().collect(),
+                        self.action_names(),
                         true,
                         Some(CmdTemplateArg::Path),
                     ),
@@ -516,144 +1443,205 @@ impl Engine {
             Cmd::ShrinkPane => {
                 self.workspace.panes.shrink_current(self.buffer_area);
             }
+            Cmd::PaneResize { amount } => {
+                self.workspace
+                    .panes
+                    .resize_current(self.buffer_area, amount);
+            }
+            Cmd::PaneSetSize { percent } => {
+                if !(1..=99).contains(&percent) {
+                    self.palette
+                        .set_error(tr!("workspace.invalid_pane_size", percent = percent));
+                } else if !self.workspace.panes.set_current_size(percent as u8) {
+                    self.palette.set_error(tr!("workspace.pane_not_split"));
+                }
+            }
+            Cmd::RotatePanes { reverse } => self.workspace.panes.rotate(reverse),
+            Cmd::SwapPane { direction } => {
+                self.workspace
+                    .panes
+                    .swap_pane_direction(direction, self.buffer_area);
+            }
+            Cmd::EqualizePanes => self.workspace.panes.equalize(),
+            Cmd::TogglePaneZoom => {
+                if !self.workspace.panes.toggle_zoom() {
+                    self.palette.set_error(tr!("workspace.pane_not_split"));
+                }
+            }
+            Cmd::SaveLayout { name } => self.save_layout(name),
+            Cmd::LoadLayout { name } => self.load_layout(&name),
             Cmd::Quit => {
                 self.quit(control_flow);
             }
             Cmd::Escape if self.repeat.is_some() => {
                 self.repeat = None;
             }
+            Cmd::Escape if self.pending_register.is_some() => {
+                self.pending_register = None;
+                self.palette.reset();
+            }
             Cmd::Escape if self.palette.has_focus() => {
                 self.palette.reset();
             }
             Cmd::FocusPalette if !self.palette.has_focus() => {
-                self.file_picker = None;
-                self.buffer_picker = None;
-                self.global_search_picker = None;
+                self.close_pickers();
                 self.palette.focus(
                     "> ",
                     "command",
                     CompleterContext::new(
                         self.themes.keys().cloned().collect(),
-                        self.workspace.config.actions.keys().cloned().collect(),
+                        self.action_names(),
                         false,
                         None,
                     ),
                 );
             }
             Cmd::PromptGoto => {
-                self.file_picker = None;
-                self.buffer_picker = None;
-                self.global_search_picker = None;
+                self.close_pickers();
                 self.palette.focus(
                     "goto: ",
                     "goto",
                     CompleterContext::new(
                         self.themes.keys().cloned().collect(),
-                        self.workspace.config.actions.keys().cloned().collect(),
+                        self.action_names(),
                         false,
                         None,
                     ),
                 );
             }
+            Cmd::InfoLineClick { item } => match item.as_str() {
+                "encoding" | "language" => {
+                    self.palette.reset();
+                    self.handle_single_input_command(Cmd::FocusPalette, control_flow);
+                    self.palette.set_line(format!("{item} "));
+                }
+                "position" => {
+                    self.handle_single_input_command(Cmd::PromptGoto, control_flow);
+                }
+                "branch" => {
+                    self.handle_single_input_command(Cmd::GitBranches, control_flow);
+                }
+                "conflicts" => {
+                    self.handle_single_input_command(Cmd::NextConflict, control_flow);
+                }
+                "search" => {
+                    self.handle_single_input_command(Cmd::NextMatch, control_flow);
+                }
+                _ => (),
+            },
             Cmd::Search => self.search(),
+            Cmd::NextMatch => {
+                let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+                    return;
+                };
+                if buffer.next_match(view_id) {
+                    self.palette.set_msg(tr!("buffer.search_wrapped_bottom"));
+                }
+            }
+            Cmd::PrevMatch => {
+                let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+                    return;
+                };
+                if buffer.prev_match(view_id) {
+                    self.palette.set_msg(tr!("buffer.search_wrapped_top"));
+                }
+            }
             Cmd::Replace => self.start_replace(),
-            Cmd::GlobalSearch => self.global_search(),
+            Cmd::GlobalSearch => self.global_search(None),
+            Cmd::SearchIn { glob } => self.global_search(Some(glob)),
+            Cmd::GlobalReplace { replacement } => self.global_replace(replacement),
+            Cmd::UndoLastRefactor => self.undo_last_refactor(),
+            Cmd::IdentifierStats => self.identifier_stats(),
+            Cmd::SelectAllMatching if self.current_buffer_is_large() => {
+                self.select_all_matching_job();
+            }
+            Cmd::NextLocation => self.goto_location(true),
+            Cmd::PrevLocation => self.goto_location(false),
+            Cmd::LocationList if self.global_search_picker.is_some() => {
+                let mut picker = self.global_search_picker.take().unwrap();
+                let locations = picker
+                    .get_matches()
+                    .iter()
+                    .map(|(fuzzy_match, _)| {
+                        let m = &fuzzy_match.item;
+                        Location {
+                            path: m.path.clone(),
+                            line: m.match_location.0.line + 1,
+                            column: m.match_location.0.column + 1,
+                            message: m.line.trim().to_string(),
+                        }
+                    })
+                    .collect();
+                let title = match &self.last_global_search {
+                    Some((query, _)) => format!("global-search {query}"),
+                    None => String::from("global-search"),
+                };
+                self.set_locations(title, locations);
+                self.open_location_list();
+            }
+            Cmd::LocationList => self.open_location_list(),
+            Cmd::EditSearchResults if self.global_search_picker.is_some() => {
+                let mut picker = self.global_search_picker.take().unwrap();
+                let search_edit = SearchEdit::new(
+                    picker
+                        .get_matches()
+                        .iter()
+                        .map(|(fuzzy_match, _)| &fuzzy_match.item),
+                );
+                self.open_search_edit(search_edit);
+            }
+            Cmd::EditSearchResults => self.palette.set_error(tr!("search_edit.no_search")),
+            Cmd::SearchEditDiff => self.search_edit_diff(),
             Cmd::CaseInsensitive => {
+                // Smart case takes precedence so it is turned off for the toggle to have an effect
+                self.config.editor.smart_case_search = false;
                 self.config.editor.case_insensitive_search =
                     !self.config.editor.case_insensitive_search;
-                if let Some("search") = self.palette.mode() {
-                    self.palette.update_prompt(self.get_search_prompt(false));
-                }
-                if let Some("global-search") = self.palette.mode() {
-                    self.palette.update_prompt(self.get_search_prompt(true));
-                }
+                self.update_search_prompt();
             }
-            Cmd::Escape
-                if self.chord.is_some()
-                    || self.file_picker.is_some()
-                    || self.buffer_picker.is_some()
-                    || self.global_search_picker.is_some() =>
-            {
+            Cmd::SmartCase => {
+                self.config.editor.smart_case_search = !self.config.editor.smart_case_search;
+                self.update_search_prompt();
+            }
+            Cmd::WholeWord => {
+                self.config.editor.whole_word_search = !self.config.editor.whole_word_search;
+                self.update_search_prompt();
+            }
+            Cmd::Escape if self.chord.is_some() || self.has_open_picker() => {
                 self.chord = None;
-                self.file_picker = None;
-                self.buffer_picker = None;
-                self.global_search_picker = None;
+                self.close_pickers();
             }
             Cmd::OpenFilePicker => self.open_file_picker(),
             Cmd::OpenBufferPicker => self.open_buffer_picker(),
             Cmd::OpenFileExplorer { path } => self.open_file_explorer(path),
             Cmd::FilePickerReload => {
-                self.file_scanner = FileScanner::new(
+                self.file_scanner = FileScanner::rescan(
                     env::current_dir().unwrap_or(PathBuf::from(".")),
                     &self.config.editor,
                 );
             }
+            Cmd::IndexStatus => self.show_index_status(),
             Cmd::ReplaceAll { text } => {
                 if let Some((buffer, view_id)) = self.get_current_buffer_mut() {
                     buffer.replace_all(view_id, text);
                 }
             }
-            Cmd::SortLines { ascending } => {
-                if let Some((buffer, view_id)) = self.get_current_buffer_mut() {
-                    buffer.sort_lines(view_id, ascending);
-                }
-            }
             Cmd::Path => match self.try_get_current_buffer_path() {
                 Some(path) => self.palette.set_msg(path.to_string_lossy()),
-                None => self
-                    .palette
-                    .set_error("No path has been set for the current buffer"),
+                None => self.palette.set_error(tr!("buffer.no_path")),
             },
             Cmd::About => {
-                self.palette.set_msg(format!(
-                    "ferrite\nVersion: {}\nCommit: {}",
-                    env!("CARGO_PKG_VERSION"),
-                    env!("GIT_HASH"),
+                self.palette.set_msg(tr!(
+                    "misc.about",
+                    version = env!("CARGO_PKG_VERSION"),
+                    commit = env!("GIT_HASH"),
                 ));
             }
             Cmd::Pwd => match env::current_dir() {
                 Ok(path) => self.palette.set_msg(path.to_string_lossy()),
                 Err(err) => self.palette.set_error(err),
             },
-            Cmd::Cd { path } => {
-                if let Err(err) = self.workspace.save_workspace() {
-                    self.palette.set_error(err);
-                }
-                match env::set_current_dir(&path) {
-                    Ok(_) => {
-                        self.buffer_picker = None;
-                        self.file_picker = None;
-
-                        self.file_scanner = FileScanner::new(
-                            env::current_dir().unwrap_or(PathBuf::from(".")),
-                            &self.config.editor,
-                        );
-
-                        match BranchWatcher::new(self.proxy.dup()) {
-                            Ok(branch_watcher) => self.branch_watcher = branch_watcher,
-                            Err(err) => {
-                                let msg = format!("Error creating branch watcher: {err}");
-                                tracing::error!(msg);
-                                self.palette.set_error(msg);
-                            }
-                        }
-
-                        self.workspace = match Workspace::load_workspace(true, self.proxy.dup()) {
-                            Ok(workspace) => workspace,
-                            Err(err) => {
-                                let msg = format!("Error loading workspace: {err}");
-                                tracing::error!(msg);
-                                self.palette.set_error(msg);
-                                Workspace::default()
-                            }
-                        };
-
-                        self.palette
-                            .set_msg(format!("Set working dir to: {}", path.to_string_lossy()));
-                    }
-                    Err(err) => self.palette.set_error(format!("{err}")),
-                }
-            }
+            Cmd::Cd { path } => self.change_directory(path),
             Cmd::Split { direction } => {
                 let (buffer_id, view_id) = match self.workspace.panes.get_current_pane() {
                     PaneKind::Buffer(buffer_id, _) => {
@@ -674,7 +1662,7 @@ impl Engine {
                     .map(|s| String::from(s.to_string_lossy()))
                     .collect::<Vec<_>>()
                     .join(" ");
-                self.run_shell_command(cmd, pipe, false);
+                self.run_shell_command(cmd, pipe);
             }
             Cmd::Trash => {
                 let PaneKind::Buffer(buffer_id, _) = self.workspace.panes.get_current_pane() else {
@@ -686,17 +1674,16 @@ impl Engine {
                         let path = self.workspace.buffers[buffer_id].file().unwrap();
                         match trash::delete(path) {
                             Ok(_) => {
-                                self.palette.set_msg(format!(
-                                    "`{}` moved to trash",
-                                    path.to_string_lossy()
+                                self.palette.set_msg(tr!(
+                                    "buffer.moved_to_trash",
+                                    path = path.to_string_lossy()
                                 ));
                             }
                             Err(err) => self.palette.set_error(err),
                         }
                     }
                     Ok(false) => {
-                        self.palette
-                            .set_error("No path set for file, cannot move to trash");
+                        self.palette.set_error(tr!("buffer.no_path_trash"));
                     }
                     Err(e) => {
                         self.palette.set_error(e);
@@ -712,11 +1699,19 @@ impl Engine {
             Cmd::OpenFile { path } => {
                 self.open_file(path);
             }
+            Cmd::OpenFileSplit { path, direction } => {
+                self.open_file_in_split(path, direction);
+            }
+            Cmd::MoveBufferToPane { direction } => self.move_buffer_to_pane(direction),
             Cmd::Save { path } => {
                 let PaneKind::Buffer(buffer_id, _) = self.workspace.panes.get_current_pane() else {
                     return;
                 };
 
+                if path.is_none() && self.search_edits.contains_key(buffer_id) {
+                    self.apply_search_edit(buffer_id);
+                    return;
+                }
                 self.save_buffer(buffer_id, path);
             }
             Cmd::SaveAll => {
@@ -737,8 +1732,7 @@ impl Engine {
                 };
                 match language {
                     Some(language) => {
-                        if let Err(err) = self.workspace.buffers[buffer_id]
-                            .set_langauge(&language, self.proxy.dup())
+                        if let Err(err) = self.workspace.buffers[buffer_id].set_langauge(&language)
                         {
                             self.palette.set_error(err);
                         }
@@ -753,37 +1747,83 @@ impl Engine {
                     return;
                 };
                 match encoding {
-                    Some(encoding) => {
-                        match get_encoding(&encoding) {
-                            Some(encoding) => self.workspace.buffers[buffer_id].encoding = encoding,
-                            None => self.palette.set_error("unknown encoding, these encodings are supported: https://docs.rs/encoding_rs/latest/encoding_rs"),
-                        }
-                    }
+                    Some(encoding) => match get_encoding(&encoding) {
+                        Some(encoding) => self.workspace.buffers[buffer_id].encoding = encoding,
+                        None => self.palette.set_error(tr!("config.unknown_encoding")),
+                    },
                     None => self
-                    .palette
-                    .set_msg(self.workspace.buffers[buffer_id].encoding.name()),
+                        .palette
+                        .set_msg(self.workspace.buffers[buffer_id].encoding.name()),
                 }
             }
-            Cmd::Indent { indent } => {
+            Cmd::ReopenWithEncoding { encoding } => {
                 let PaneKind::Buffer(buffer_id, _) = self.workspace.panes.get_current_pane() else {
                     return;
                 };
-                match indent {
-                    Some(indent) => {
-                        if let Ok(spaces) = indent.parse::<NonZeroUsize>() {
-                            self.workspace.buffers[buffer_id].indent = Indentation::Spaces(spaces);
-                        } else if indent == "tabs" {
-                            self.workspace.buffers[buffer_id].indent =
-                                Indentation::Tabs(NonZeroUsize::new(1).unwrap());
-                        } else {
+                let Some(encoding) = get_encoding(&encoding) else {
+                    self.palette.set_error(tr!("config.unknown_encoding"));
+                    return;
+                };
+                let buffer = &mut self.workspace.buffers[buffer_id];
+                if buffer.is_dirty() {
+                    self.palette.set_error(tr!("buffer.reopen_unsaved"));
+                } else if let Err(err) = buffer.reopen_with_encoding(encoding) {
+                    self.palette.set_error(err);
+                }
+            }
+            Cmd::ConvertEncoding { encoding } => {
+                let PaneKind::Buffer(buffer_id, _) = self.workspace.panes.get_current_pane() else {
+                    return;
+                };
+                let Some(encoding) = get_encoding(&encoding) else {
+                    self.palette.set_error(tr!("config.unknown_encoding"));
+                    return;
+                };
+                let buffer = &mut self.workspace.buffers[buffer_id];
+                if buffer.read_only {
+                    return;
+                }
+                match buffer.convert_encoding(encoding) {
+                    0 => self
+                        .palette
+                        .set_msg(tr!("buffer.converted_encoding", encoding = encoding.name())),
+                    count => self.palette.set_error(tr!(
+                        "buffer.unmappable_chars",
+                        count = count,
+                        encoding = encoding.name()
+                    )),
+                }
+            }
+            Cmd::Bom { add } => {
+                let PaneKind::Buffer(buffer_id, _) = self.workspace.panes.get_current_pane() else {
+                    return;
+                };
+                let buffer = &mut self.workspace.buffers[buffer_id];
+                match add {
+                    Some(_) if buffer.read_only => (),
+                    Some(add) => {
+                        if !buffer.set_bom(add) {
                             self.palette
-                                .set_error("Indentation must be a number or `tabs`");
+                                .set_error(tr!("buffer.no_bom", encoding = buffer.encoding.name()));
                         }
                     }
+                    None if buffer.bom => self.palette.set_msg(tr!("buffer.has_bom")),
+                    None => self.palette.set_msg(tr!("buffer.has_no_bom")),
+                }
+            }
+            Cmd::Indent { indent } => {
+                let PaneKind::Buffer(buffer_id, _) = self.workspace.panes.get_current_pane() else {
+                    return;
+                };
+                match indent {
+                    Some(indent) => match Indentation::parse(&indent) {
+                        Some(indent) => self.workspace.buffers[buffer_id].indent = indent,
+                        None => self.palette.set_error(tr!("config.invalid_indent")),
+                    },
                     None => match self.workspace.buffers[buffer_id].indent {
-                        Indentation::Tabs(_) => self.palette.set_msg("tabs"),
+                        Indentation::Tabs(_) => self.palette.set_msg(tr!("buffer.tabs")),
                         Indentation::Spaces(amount) => {
-                            self.palette.set_msg(format!("{} space(s)", amount))
+                            self.palette.set_msg(tr!("buffer.spaces", amount = amount))
                         }
                     },
                 }
@@ -806,6 +1846,15 @@ impl Engine {
                     }
                 }
             }
+            Cmd::ShowWhitespace { mode } => {
+                self.config.editor.render_whitespace = match mode {
+                    Some(mode) => mode,
+                    None if self.config.editor.render_whitespace == RenderWhitespace::None => {
+                        RenderWhitespace::All
+                    }
+                    None => RenderWhitespace::None,
+                };
+            }
             Cmd::New { path } => {
                 if let Some(path) = path {
                     match Buffer::with_path(path) {
@@ -827,7 +1876,7 @@ impl Engine {
                 };
                 if self.workspace.buffers[buffer_id].is_dirty() {
                     self.palette.set_prompt(
-                        "The buffer is unsaved are you sure you want to reload?",
+                        tr!("prompt.reload_unsaved"),
                         ('y', PalettePromptEvent::Reload),
                         ('n', PalettePromptEvent::Nop),
                     );
@@ -839,7 +1888,7 @@ impl Engine {
                 for buffer in self.workspace.buffers.values_mut() {
                     if buffer.file().is_some() && buffer.is_dirty() {
                         self.palette
-                            .set_error(format!("`{}` is dirty cannot reload", buffer.name()));
+                            .set_error(tr!("buffer.dirty_cannot_reload", name = buffer.name()));
                         continue;
                     }
 
@@ -857,24 +1906,45 @@ impl Engine {
                 };
                 self.workspace.buffers[buffer_id].goto(view_id, line);
             }
+            Cmd::Complete => {
+                let PaneKind::Buffer(buffer_id, view_id) = self.workspace.panes.get_current_pane()
+                else {
+                    return;
+                };
+                self.update_completion(buffer_id, view_id, true);
+            }
+            Cmd::ToggleAbbreviations => {
+                self.abbreviations_enabled = !self.abbreviations_enabled;
+                if self.abbreviations_enabled {
+                    self.palette.set_msg(tr!("buffer.abbreviations_enabled"));
+                } else {
+                    self.palette.set_msg(tr!("buffer.abbreviations_disabled"));
+                }
+            }
             Cmd::Case { case } => {
                 let PaneKind::Buffer(buffer_id, view_id) = self.workspace.panes.get_current_pane()
                 else {
                     return;
                 };
-                self.workspace.buffers[buffer_id].transform_case(view_id, case);
+                let buffer = &mut self.workspace.buffers[buffer_id];
+                if !buffer.read_only {
+                    buffer.transform_case(view_id, case);
+                }
             }
             Cmd::ForceQuit => *control_flow = EventLoopControlFlow::Exit,
             Cmd::Logger => {
                 self.logger_state.lines_scrolled_up = 0.0;
                 self.workspace.panes.replace_current(PaneKind::Logger);
             }
+            Cmd::LoggerClear => self.logger_state.clear(),
+            Cmd::Notifications => self.open_notifications(),
+            Cmd::NotificationsClear => self.notifications.clear(),
             Cmd::Theme { theme } => match theme {
                 Some(theme) => {
                     if self.themes.contains_key(&theme) {
                         self.config.editor.theme = theme;
                     } else {
-                        self.palette.set_error("Theme not found");
+                        self.palette.set_error(tr!("config.theme_not_found"));
                     }
                 }
                 None => {
@@ -883,15 +1953,28 @@ impl Engine {
             },
             Cmd::BufferPickerOpen => self.open_buffer_picker(),
             Cmd::FilePickerOpen => {
-                if self.config.editor.picker.file_picker_auto_reload {
-                    self.file_scanner = FileScanner::new(
+                // A watched index is already up to date
+                if self.config.editor.picker.file_picker_auto_reload
+                    && !self.file_scanner.is_watching()
+                {
+                    self.file_scanner = FileScanner::rescan(
                         env::current_dir().unwrap_or(PathBuf::from(".")),
                         &self.config.editor,
                     );
                 }
                 self.open_file_picker();
             }
+            Cmd::RecentFilePickerOpen => self.open_recent_file_picker(),
+            Cmd::RecentProjectPickerOpen => self.open_recent_project_picker(),
+            Cmd::CustomPicker { name } => self.open_custom_picker(&name),
+            Cmd::TrustWorkspace => self.trust_workspace(),
+            Cmd::DiffSelectionClipboard => self.diff_selection_against_clipboard(),
+            Cmd::Scratch { name } => match name {
+                Some(name) => self.open_scratch(&name),
+                None => self.open_scratch_picker(),
+            },
             Cmd::OpenConfig => self.open_config(),
+            Cmd::EditTheme => self.edit_theme(),
             Cmd::DefaultConfig => self.open_default_config(),
             Cmd::OpenLanguages => self.open_languages(),
             Cmd::DefaultLanguages => self.open_default_languages(),
@@ -908,57 +1991,218 @@ impl Engine {
                 let _ = self.workspace.buffers[buffer_id].handle_input(view_id, Cmd::RevertBuffer);
             }
             Cmd::GitReload => self.branch_watcher.force_reload(),
+            Cmd::GitStatus => self.open_git_status(),
+            Cmd::GitCommit => self.git_commit(),
+            Cmd::GitBranches => self.open_branch_picker(),
+            Cmd::GitLog => self.open_git_log_picker(),
+            Cmd::NextHunk => self.hunk_action(HunkAction::Next),
+            Cmd::PrevHunk => self.hunk_action(HunkAction::Prev),
+            Cmd::RevertHunk => self.hunk_action(HunkAction::Revert),
+            Cmd::StageHunk => self.hunk_action(HunkAction::Stage),
+            Cmd::AcceptOurs => self.resolve_conflict(Resolution::Ours),
+            Cmd::AcceptTheirs => self.resolve_conflict(Resolution::Theirs),
+            Cmd::AcceptBoth => self.resolve_conflict(Resolution::Both),
+            Cmd::NormalizeUnicode => {
+                let Some((buffer, _)) = self.get_current_buffer_mut() else {
+                    return;
+                };
+                if buffer.read_only {
+                    return;
+                }
+                let count = buffer.normalize_unicode();
+                self.palette
+                    .set_msg(tr!("buffer.normalized_unicode", count = count));
+            }
+            Cmd::ToggleReadOnly => {
+                let Some((buffer_id, _)) = self.get_current_buffer_id() else {
+                    return;
+                };
+                if self.is_generated(buffer_id) {
+                    self.palette.set_error(tr!(
+                        "buffer.read_only_generated",
+                        name = self.workspace.buffers[buffer_id].name()
+                    ));
+                    return;
+                }
+                let buffer = &mut self.workspace.buffers[buffer_id];
+                buffer.read_only = !buffer.read_only;
+                if buffer.read_only {
+                    self.palette.set_msg(tr!("buffer.read_only_on"));
+                } else {
+                    self.palette.set_msg(tr!("buffer.read_only_off"));
+                }
+            }
+            Cmd::NormalizeLineEndings => {
+                let Some((buffer, _)) = self.get_current_buffer_mut() else {
+                    return;
+                };
+                if buffer.read_only {
+                    return;
+                }
+                let count = buffer.normalize_line_endings();
+                self.palette
+                    .set_msg(tr!("buffer.normalized_line_endings", count = count));
+            }
+            Cmd::NextConflict => {
+                let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+                    return;
+                };
+                if !buffer.next_conflict(view_id) {
+                    self.palette.set_msg(tr!("buffer.no_conflicts"));
+                }
+            }
+            Cmd::ToggleBookmark => {
+                let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+                    return;
+                };
+                let line = buffer.cursor_line_idx(view_id, 0) + 1;
+                if buffer.toggle_bookmark(view_id) {
+                    self.palette
+                        .set_msg(tr!("buffer.bookmark_added", line = line));
+                } else {
+                    self.palette
+                        .set_msg(tr!("buffer.bookmark_removed", line = line));
+                }
+            }
+            Cmd::NextBookmark => {
+                let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+                    return;
+                };
+                if !buffer.next_bookmark(view_id) {
+                    self.palette.set_msg(tr!("buffer.no_bookmarks"));
+                }
+            }
+            Cmd::PrevBookmark => {
+                let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+                    return;
+                };
+                if !buffer.prev_bookmark(view_id) {
+                    self.palette.set_msg(tr!("buffer.no_bookmarks"));
+                }
+            }
+            Cmd::BookmarkPicker => self.open_bookmark_picker(),
+            Cmd::Outline => self.open_outline_picker(),
+            Cmd::ParentSymbol => {
+                let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+                    return;
+                };
+                if !buffer.goto_parent_symbol(view_id) {
+                    self.palette.set_msg(tr!("buffer.no_parent_symbol"));
+                }
+            }
             Cmd::SwitchPane { direction } => {
                 self.workspace
                     .panes
                     .switch_pane_direction(direction, self.buffer_area);
             }
-            Cmd::ZoomIn => {
-                self.scale += 0.1;
-                self.palette
-                    .set_msg(format!("Zoom: {}%", (self.scale * 100.0).round() as u64));
+            Cmd::ZoomIn => self.set_scale(self.workspace.scale + 0.1),
+            Cmd::ZoomOut => self.set_scale(self.workspace.scale - 0.1),
+            Cmd::ResetZoom => self.set_scale(1.0),
+            Cmd::Rerun => self.rerun_output(),
+            Cmd::AnsiRender => {
+                let Some((buffer_id, _)) = self.get_current_buffer_id() else {
+                    return;
+                };
+                if self.ansi_renders.contains_key(buffer_id) {
+                    self.show_raw_ansi(buffer_id);
+                    self.palette.set_msg(tr!("buffer.ansi_raw"));
+                } else if self.render_ansi(buffer_id) {
+                    self.palette.set_msg(tr!("buffer.ansi_rendered"));
+                } else {
+                    self.palette.set_msg(tr!("buffer.ansi_none"));
+                }
             }
-            Cmd::ZoomOut => {
-                self.scale -= 0.1;
-                self.palette
-                    .set_msg(format!("Zoom: {}%", (self.scale * 100.0).round() as u64));
+            Cmd::ToggleFollowOutput => {
+                let Some((buffer_id, view_id)) = self.get_current_buffer_id() else {
+                    return;
+                };
+                let Some(output) = self.outputs.get_mut(buffer_id) else {
+                    self.palette.set_error(tr!("output.not_output"));
+                    return;
+                };
+                output.follow = !output.follow;
+                if output.follow {
+                    let buffer = &mut self.workspace.buffers[buffer_id];
+                    buffer.eof(view_id, false);
+                    buffer.scroll_to_end(view_id);
+                    self.palette.set_msg(tr!("output.following"));
+                } else {
+                    self.palette.set_msg(tr!("output.not_following"));
+                }
             }
-            Cmd::ResetZoom => {
-                self.scale = 1.0;
-                self.palette
-                    .set_msg(format!("Zoom: {}%", (self.scale * 100.0).round() as u64));
+            Cmd::Tail => {
+                let Some((buffer_id, view_id)) = self.get_current_buffer_id() else {
+                    return;
+                };
+                let buffer = &mut self.workspace.buffers[buffer_id];
+                if buffer.is_tailing() {
+                    buffer.stop_tail();
+                    self.palette.set_msg(tr!("buffer.tail_stopped"));
+                } else if !self.config.editor.watch_open_files {
+                    self.palette.set_error(tr!("buffer.tail_not_watching"));
+                } else if buffer.is_dirty() {
+                    self.palette.set_error(tr!("buffer.tail_dirty"));
+                } else if let Err(err) = buffer.start_tail() {
+                    self.palette.set_error(err);
+                } else {
+                    buffer.eof(view_id, false);
+                    buffer.scroll_to_end(view_id);
+                    self.palette.set_msg(tr!("buffer.tail_started"));
+                }
             }
+            Cmd::CancelJob => self.open_job_picker(),
             Cmd::KillJob => {
                 if let Some((current_buffer_id, _)) = self.get_current_buffer_id() {
                     for (buffer_id, job) in &mut self.shell_jobs {
                         if let Some(buffer_id) = buffer_id {
                             if *buffer_id == current_buffer_id {
-                                job.kill();
+                                job.cancel();
                             }
                         }
                     }
                 }
             }
+            Cmd::RunAction { name } if self.config.editor.tools.contains_key(&name) => {
+                self.run_tool(&name)
+            }
+            Cmd::RunAction { name } if self.tasks().contains_key(&name) => {
+                let task = self.tasks().swap_remove(&name).unwrap();
+                self.run_task(name, task);
+            }
             Cmd::RunAction { name } => match self.workspace.config.actions.get(&name) {
                 Some(args) => {
-                    self.run_shell_command(args.join(" "), true, false);
+                    self.run_shell_command(args.join(" "), true);
                 }
                 None => {
-                    self.palette.set_error(format!("Action '{name}' not found"));
+                    self.palette
+                        .set_error(tr!("config.action_not_found", name = name));
                 }
             },
             input => {
                 if self.palette.has_focus() {
                     let _ = self.palette.handle_input(input);
                 } else if let Some(picker) = &mut self.file_picker {
-                    let _ = picker.handle_input(input);
-                    if let Some(path) = picker.get_choice() {
-                        self.file_picker = None;
-                        self.open_file(path);
+                    if let Some(direction) = picker_split_direction(&input) {
+                        if let Some(path) = picker.get_selected() {
+                            self.file_picker = None;
+                            self.open_file_in_split(path, direction);
+                        }
+                    } else {
+                        let _ = picker.handle_input(input);
+                        if let Some(path) = picker.get_choice() {
+                            self.file_picker = None;
+                            self.open_file(path);
+                        }
                     }
                 } else if let Some(picker) = &mut self.buffer_picker {
-                    let _ = picker.handle_input(input);
-                    if let Some(choice) = picker.get_choice() {
+                    let (choice, direction) = match picker_split_direction(&input) {
+                        Some(direction) => (picker.get_selected(), Some(direction)),
+                        None => {
+                            let _ = picker.handle_input(input);
+                            (picker.get_choice(), None)
+                        }
+                    };
+                    if let Some(choice) = choice {
                         self.workspace.buffers[choice.id].update_interact(None);
                         self.buffer_picker = None;
 
@@ -966,52 +2210,168 @@ impl Engine {
                         let view_id = buffer.create_view();
                         self.load_view_data(choice.id, view_id);
 
-                        let old = self
-                            .workspace
-                            .panes
-                            .replace_current(PaneKind::Buffer(choice.id, view_id));
-                        if let PaneKind::Buffer(id, view_id) = old {
-                            let buffer = &mut self.workspace.buffers[id];
-                            buffer.remove_view(view_id);
-                            if buffer.is_disposable() {
-                                self.workspace.buffers.remove(id);
+                        if let Some(direction) = direction {
+                            self.workspace
+                                .panes
+                                .split(PaneKind::Buffer(choice.id, view_id), direction);
+                        } else {
+                            let old = self
+                                .workspace
+                                .panes
+                                .replace_current(PaneKind::Buffer(choice.id, view_id));
+                            if let PaneKind::Buffer(id, view_id) = old {
+                                let buffer = &mut self.workspace.buffers[id];
+                                buffer.remove_view(view_id);
+                                if buffer.is_disposable() {
+                                    self.workspace.buffers.remove(id);
+                                }
+                            }
+                        }
+                    }
+                } else if let Some(picker) = &mut self.project_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(path) = picker.get_choice() {
+                        self.project_picker = None;
+                        self.change_directory(path);
+                    }
+                } else if let Some(picker) = &mut self.scratch_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(name) = picker.get_choice() {
+                        self.scratch_picker = None;
+                        self.open_scratch(&name);
+                    }
+                } else if let Some(picker) = &mut self.register_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(item) = picker.get_choice() {
+                        self.register_picker = None;
+                        self.paste_from_register(item.name);
+                    }
+                } else if let Some(picker) = &mut self.local_history_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(item) = picker.get_choice() {
+                        self.local_history_picker = None;
+                        self.restore_snapshot(&item.snapshot);
+                    }
+                } else if let Some(picker) = &mut self.plugin_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(item) = picker.get_choice() {
+                        self.plugin_picker = None;
+                        self.toggle_plugin(&item.name);
+                    }
+                } else if let Some(picker) = &mut self.identifier_stats_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(variant) = picker.get_choice() {
+                        self.close_pickers();
+                        self.global_search_scope = None;
+                        // Only the exact variant that was picked is searched for
+                        self.start_global_search(
+                            variant.text.clone(),
+                            SearchOptions {
+                                case_insensitive: false,
+                                smart_case: false,
+                                whole_word: true,
+                            },
+                        );
+                        self.palette
+                            .set_msg(tr!("refactor.seeded", query = variant.text));
+                    }
+                } else if let Some((git_picker, picker)) = &mut self.git_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(item) = picker.get_choice() {
+                        let git_picker = git_picker.clone();
+                        self.close_pickers();
+                        self.handle_git_picker_choice(git_picker, item);
+                    }
+                } else if let Some(picker) = &mut self.outline_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(item) = picker.get_choice() {
+                        self.outline_picker = None;
+                        if let Some((buffer, view_id)) = self.get_current_buffer_mut() {
+                            buffer.jump_to(view_id, Point::new(0, item.symbol.line));
+                        }
+                    }
+                } else if let Some(picker) = &mut self.bookmark_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(item) = picker.get_choice() {
+                        self.close_pickers();
+                        if self.open_file(&item.path) {
+                            if let Some((buffer, view_id)) = self.get_current_buffer_mut() {
+                                let point = Point::new(0, item.line_idx);
+                                buffer.select_area(view_id, point, point, false);
+                                // A buffers default amount of lines when newly opened is too large
+                                // and the view will not jump to it.
+                                buffer.set_view_lines(view_id, 10);
+                                buffer.center_on_cursor(view_id);
                             }
                         }
                     }
+                } else if let Some((_, action, picker)) = &mut self.custom_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(choice) = picker.get_choice() {
+                        let action = action.clone();
+                        self.custom_picker = None;
+                        self.handle_custom_picker_choice(action, choice);
+                    }
+                } else if let Some(picker) = &mut self.job_picker {
+                    let _ = picker.handle_input(input);
+                    if let Some(item) = picker.get_choice() {
+                        self.job_picker = None;
+                        self.cancel_job(item.target);
+                    }
                 } else if let Some(picker) = &mut self.global_search_picker {
                     let _ = picker.handle_input(input);
                     if let Some(choice) = picker.get_choice() {
                         self.global_search_picker = None;
-                        let guard = choice.buffer.lock().unwrap();
-                        if let Some(file) = guard.file() {
-                            if self.open_file(file) {
-                                let view_id = guard.get_first_view().unwrap();
-                                let cursor_line = guard.cursor_line_idx(view_id, 0);
-                                let cursor_col = guard.cursor_grapheme_column(view_id, 0);
-                                let anchor_line = guard.anchor_line_idx(view_id, 0);
-                                let anchor_col = guard.anchor_grapheme_column(view_id, 0);
-                                if let Some((buffer, view_id)) = self.get_current_buffer_mut() {
-                                    buffer.select_area(
-                                        view_id,
-                                        Point::new(cursor_col, cursor_line),
-                                        Point::new(anchor_col, anchor_line),
-                                        false,
-                                    );
-                                    // A buffers default amount of lines when newly opened is too large
-                                    // and the view will not jump to it.
-                                    buffer.set_view_lines(view_id, 10);
-                                    buffer.center_on_cursor(view_id);
-                                }
+                        if self.open_file(&choice.path) {
+                            let (start, end) = choice.match_location;
+                            if let Some((buffer, view_id)) = self.get_current_buffer_mut() {
+                                buffer.select_area(view_id, start, end, false);
+                                // A buffers default amount of lines when newly opened is too large
+                                // and the view will not jump to it.
+                                buffer.set_view_lines(view_id, 10);
+                                buffer.center_on_cursor(view_id);
                             }
                         }
                     }
                 } else {
                     match self.workspace.panes.get_current_pane() {
                         PaneKind::Buffer(buffer_id, view_id) => {
-                            if let Err(err) =
-                                self.workspace.buffers[buffer_id].handle_input(view_id, input)
-                            {
-                                self.palette.set_error(err);
+                            if !self.handle_completion_input(buffer_id, view_id, &input) {
+                                let update_completion = matches!(
+                                    input,
+                                    Cmd::Char { .. } | Cmd::Insert { .. } | Cmd::Backspace
+                                );
+                                let typed = match input {
+                                    Cmd::Char { ch } => Some(ch),
+                                    _ => None,
+                                };
+                                match self.workspace.buffers[buffer_id].handle_input(view_id, input)
+                                {
+                                    Ok(()) => (),
+                                    Err(BufferError::ReadOnly) if self.is_generated(buffer_id) => {
+                                        self.palette.set_error(tr!(
+                                            "buffer.read_only_generated",
+                                            name = self.workspace.buffers[buffer_id].name()
+                                        ))
+                                    }
+                                    Err(BufferError::ReadOnly) => self.palette.set_prompt(
+                                        tr!(
+                                            "prompt.read_only",
+                                            name = self.workspace.buffers[buffer_id].name()
+                                        ),
+                                        ('y', PalettePromptEvent::MakeWritable(buffer_id)),
+                                        ('n', PalettePromptEvent::Nop),
+                                    ),
+                                    Err(err) => self.palette.set_error(err),
+                                }
+                                if let Some(ch) = typed {
+                                    self.expand_abbreviations(buffer_id, view_id, ch);
+                                }
+                                if update_completion {
+                                    self.update_completion(buffer_id, view_id, false);
+                                } else {
+                                    self.completion = None;
+                                }
                             }
                         }
                         PaneKind::FileExplorer(file_explorer_id) => {
@@ -1022,6 +2382,22 @@ impl Engine {
                             }
                         }
                         PaneKind::Logger => self.logger_state.handle_input(input),
+                        PaneKind::Notifications => self.notifications.handle_input(input),
+                        PaneKind::LocationList => {
+                            if let Some(location) = self.location_list.handle_input(input) {
+                                self.open_location(&location);
+                            }
+                        }
+                        PaneKind::Git => {
+                            match self.git_status.handle_input(input, &mut self.job_manager) {
+                                Some(GitInput::Open(path)) => {
+                                    self.focus_buffer_pane();
+                                    self.open_file(path);
+                                }
+                                Some(GitInput::Commit) => self.git_commit(),
+                                None => (),
+                            }
+                        }
                     }
                 }
             }
@@ -1029,9 +2405,20 @@ impl Engine {
     }
 
     pub fn handle_app_event(&mut self, event: UserEvent, control_flow: &mut EventLoopControlFlow) {
+        if !matches!(event, UserEvent::Wake) {
+            self.invalidate();
+        }
         match event {
             UserEvent::Wake => (),
             UserEvent::PaletteEvent { mode, content } => match mode.as_str() {
+                "command" if self.scripts.has_command(&content) => {
+                    self.palette.reset();
+                    self.run_script(&content);
+                }
+                "command" if self.plugins.find_command(&content).is_some() => {
+                    self.palette.reset();
+                    self.plugins.run_command(&content);
+                }
                 "command" => match cmd_parser::parse_cmd(&content) {
                     Ok(cmd) => {
                         self.palette.reset();
@@ -1039,6 +2426,37 @@ impl Engine {
                     }
                     Err(err) => self.palette.set_error(err),
                 },
+                "passphrase" => {
+                    self.palette.reset();
+                    match self.passphrase_request.take() {
+                        Some(PassphraseRequest::Open(path)) => {
+                            if let Some(filter) =
+                                encryption::filter_for(&self.config.editor.encryption, &path)
+                            {
+                                let encryption = Encryption::new(filter.clone(), Some(content));
+                                self.insert_opened_buffer(Buffer::from_encrypted_file(
+                                    &path, encryption,
+                                ));
+                            }
+                        }
+                        Some(PassphraseRequest::Save(buffer_id)) => {
+                            let Some(buffer) = self.workspace.buffers.get_mut(buffer_id) else {
+                                return;
+                            };
+                            let Some(filter) = buffer.file().and_then(|path| {
+                                encryption::filter_for(&self.config.editor.encryption, path)
+                            }) else {
+                                return;
+                            };
+                            buffer.set_encryption(Some(Encryption::new(
+                                filter.clone(),
+                                Some(content),
+                            )));
+                            self.save_buffer(buffer_id, None);
+                        }
+                        None => (),
+                    }
+                }
                 "goto" => {
                     self.palette.reset();
                     if let Ok(line) = content.trim().parse::<i64>() {
@@ -1058,9 +2476,8 @@ impl Engine {
                     };
                     self.workspace.buffers[buffer_id].start_search(
                         view_id,
-                        self.proxy.dup(),
                         content,
-                        self.config.editor.case_insensitive_search,
+                        self.search_options(),
                     );
                     self.palette.unfocus();
                 }
@@ -1076,21 +2493,11 @@ impl Engine {
                 }
                 "global-search" => {
                     self.palette.unfocus();
-                    let global_search_provider = GlobalSearchProvider::new(
-                        content,
-                        self.config.editor.picker,
-                        self.config.editor.case_insensitive_search,
-                    );
-                    self.global_search_picker = Some(Picker::new(
-                        global_search_provider,
-                        Some(Box::new(GlobalSearchPreviewer)),
-                        self.proxy.dup(),
-                        None,
-                    ));
+                    self.start_global_search(content, self.search_options());
                 }
                 "shell" => {
                     self.palette.reset();
-                    self.run_shell_command(content, self.config.editor.pipe_shell_palette, false);
+                    self.run_shell_command(content, self.config.editor.pipe_shell_palette);
                 }
                 _ => (),
             },
@@ -1105,13 +2512,48 @@ impl Engine {
                         self.palette.set_error(err);
                     }
                 }
+                PalettePromptEvent::OverwriteBuffer(buffer_id) => {
+                    if self.workspace.buffers.contains_key(buffer_id) {
+                        self.save_buffer_with(buffer_id, None, true);
+                    }
+                }
+                PalettePromptEvent::MergeBuffer(buffer_id) => self.diff_buffer_disk(buffer_id),
+                PalettePromptEvent::RestoreRecovered => self.restore_crashed_sessions(),
+                PalettePromptEvent::DiscardRecovered => {
+                    recovery::remove_sessions(&mem::take(&mut self.crashed_sessions));
+                }
                 PalettePromptEvent::Quit => *control_flow = EventLoopControlFlow::Exit,
                 PalettePromptEvent::CloseCurrent => self.force_close_current_buffer(),
+                PalettePromptEvent::MakeWritable(buffer_id) => {
+                    if !self.is_generated(buffer_id) {
+                        if let Some(buffer) = self.workspace.buffers.get_mut(buffer_id) {
+                            buffer.read_only = false;
+                        }
+                    }
+                }
+                PalettePromptEvent::TrustWorkspace => self.trust_workspace(),
+                PalettePromptEvent::DistrustWorkspace => {
+                    if let Err(err) = env::current_dir()
+                        .map_err(anyhow::Error::from)
+                        .and_then(|dir| trust::set_trusted(dir, false))
+                    {
+                        self.palette.set_error(err);
+                    } else {
+                        self.palette.set_msg(tr!("workspace.safe_mode"));
+                    }
+                }
+                PalettePromptEvent::RevertHunk(buffer_id, view_id, hunk) => {
+                    self.revert_hunk(buffer_id, view_id, &hunk);
+                }
             },
         }
     }
 
     pub fn format_selection_current_buffer(&mut self) {
+        if !self.workspace.trusted {
+            self.palette.set_error(tr!("workspace.safe_mode_error"));
+            return;
+        }
         let PaneKind::Buffer(buffer_id, view_id) = self.workspace.panes.get_current_pane() else {
             return;
         };
@@ -1119,14 +2561,14 @@ impl Engine {
         let config = self.config.languages.from_name(buffer_lang);
         let Some(config) = config else {
             self.palette
-                .set_error(format!("No language config found for `{buffer_lang}`"));
+                .set_error(tr!("config.no_language_config", language = buffer_lang));
 
             return;
         };
 
         let Some(fmt) = &config.format_selection else {
             self.palette
-                .set_error(format!("No selection formatter found for `{buffer_lang}`"));
+                .set_error(tr!("config.no_selection_formatter", language = buffer_lang));
             return;
         };
 
@@ -1136,17 +2578,21 @@ impl Engine {
     }
 
     pub fn format_buffer(&mut self, buffer_id: BufferId) {
+        if !self.workspace.trusted {
+            self.palette.set_error(tr!("workspace.safe_mode_error"));
+            return;
+        }
         let buffer_lang = self.workspace.buffers[buffer_id].language_name();
         let config = self.config.languages.from_name(buffer_lang);
         let Some(config) = config else {
             self.palette
-                .set_error(format!("No language config found for `{buffer_lang}`"));
+                .set_error(tr!("config.no_language_config", language = buffer_lang));
             return;
         };
 
         let Some(fmt) = &config.format else {
             self.palette
-                .set_error(format!("No formatter found for `{buffer_lang}`"));
+                .set_error(tr!("config.no_formatter", language = buffer_lang));
             return;
         };
 
@@ -1164,6 +2610,10 @@ impl Engine {
             }
         };
 
+        if !self.headless && !scratch::is_scratch_path(&real_path) {
+            recent::add_file(&real_path);
+        }
+
         match self.workspace.buffers.iter_mut().find(|(_, buffer)| {
             buffer
                 .file()
@@ -1184,23 +2634,465 @@ impl Engine {
                 }
                 true
             }
-            None => match Buffer::from_file(&real_path) {
-                Ok(mut buffer) => {
-                    let view_id = buffer.create_view();
-                    let (buffer_id, _) = self.insert_buffer(buffer, view_id, true);
-                    self.load_view_data(buffer_id, view_id);
+            None => {
+                let buffer =
+                    match encryption::filter_for(&self.config.editor.encryption, &real_path) {
+                        Some(filter) if filter.passphrase => {
+                            self.palette.focus_masked(
+                                tr!(
+                                    "prompt.passphrase",
+                                    name =
+                                        real_path.file_name().unwrap_or_default().to_string_lossy()
+                                ),
+                                "passphrase",
+                            );
+                            self.passphrase_request = Some(PassphraseRequest::Open(real_path));
+                            return false;
+                        }
+                        Some(filter) => Buffer::from_encrypted_file(
+                            &real_path,
+                            Encryption::new(filter.clone(), None),
+                        ),
+                        None => Buffer::from_file(&real_path),
+                    };
+                self.insert_opened_buffer(buffer)
+            }
+        }
+    }
 
-                    true
+    fn insert_opened_buffer(&mut self, buffer: Result<Buffer, io::Error>) -> bool {
+        match buffer {
+            Ok(mut buffer) => {
+                apply_indent_override(&self.config.editor, &mut buffer);
+                if buffer.file().is_some_and(|path| {
+                    is_read_only_file(&self.config.editor.read_only_files, path)
+                }) {
+                    buffer.read_only = true;
                 }
-                Err(err) => {
-                    self.palette.set_error(err);
-                    false
+                let view_id = buffer.create_view();
+                let (buffer_id, _) = self.insert_buffer(buffer, view_id, true);
+                self.load_view_data(buffer_id, view_id);
+                self.check_opened_buffer(buffer_id);
+                self.plugins.buffer_event(
+                    BufferEventKind::Open,
+                    buffer_id,
+                    &self.workspace.buffers[buffer_id],
+                );
+                self.run_hooks(buffer_id, HookKind::Open);
+
+                true
+            }
+            Err(err) => {
+                self.palette.set_error(err);
+                false
+            }
+        }
+    }
+
+    /// Expands the abbreviation before `ch` after it was typed
+    fn expand_abbreviations(&mut self, buffer_id: BufferId, view_id: ViewId, ch: char) {
+        if !self.abbreviations_enabled {
+            return;
+        }
+        let buffer = &mut self.workspace.buffers[buffer_id];
+        if buffer.read_only {
+            return;
+        }
+        let language = self.config.languages.from_name(buffer.language_name());
+        let global = &self.config.editor.abbreviations;
+        buffer.expand_abbreviations(view_id, ch, |abbreviation| {
+            language
+                .and_then(|language| language.abbreviations.get(abbreviation))
+                .or_else(|| global.get(abbreviation))
+                .map(String::as_str)
+        });
+    }
+
+    /// Returns true if the input was consumed by the completion popup
+    fn handle_completion_input(
+        &mut self,
+        buffer_id: BufferId,
+        view_id: ViewId,
+        input: &Cmd,
+    ) -> bool {
+        let Some(completion) = &mut self.completion else {
+            return false;
+        };
+        if completion.buffer_id != buffer_id || completion.view_id != view_id {
+            self.completion = None;
+            return false;
+        }
+
+        match input {
+            Cmd::TabOrIndent { back: false } => {
+                let completion = self.completion.take().unwrap();
+                let Some(buffer) = self.workspace.buffers.get_mut(buffer_id) else {
+                    return false;
+                };
+                let cursor = buffer.views[view_id].cursors.first();
+                if buffer.views[view_id].cursors.len() != 1
+                    || cursor.has_selection()
+                    || cursor.position != completion.range.end
+                {
+                    return false;
                 }
-            },
+
+                let item = completion.current();
+                let (text, snippet_cursor) = match item.kind {
+                    CompletionKind::Snippet => {
+                        let line_idx = buffer.rope().byte_to_line(completion.range.start);
+                        let indent: String = buffer
+                            .rope()
+                            .line(line_idx)
+                            .chars()
+                            .take_while(|ch| matches!(ch, ' ' | '\t'))
+                            .collect();
+                        completion::expand_snippet(&item.text, &indent)
+                    }
+                    CompletionKind::Word | CompletionKind::Path => (item.text.clone(), None),
+                };
+                let cursor = buffer.views[view_id].cursors.first_mut();
+                cursor.anchor = completion.range.start;
+                buffer.insert_text(view_id, &text, false);
+                if let Some(offset) = snippet_cursor {
+                    let cursor = buffer.views[view_id].cursors.first_mut();
+                    cursor.position = completion.range.start + offset;
+                    cursor.anchor = cursor.position;
+                    buffer.update_affinity(view_id);
+                }
+                true
+            }
+            Cmd::MoveDown {
+                expand_selection: false,
+                create_cursor: false,
+                ..
+            } => {
+                completion.next();
+                true
+            }
+            Cmd::MoveUp {
+                expand_selection: false,
+                create_cursor: false,
+                ..
+            } => {
+                completion.prev();
+                true
+            }
+            Cmd::Escape => {
+                self.completion = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Opens the completion popup for the text before the cursor.
+    /// Paths are completed inside of strings, otherwise snippets and words from all open buffers.
+    /// Words are only completed when `explicit` or `auto_complete` is enabled.
+    fn update_completion(&mut self, buffer_id: BufferId, view_id: ViewId, explicit: bool) {
+        self.completion = None;
+
+        let buffer = &self.workspace.buffers[buffer_id];
+        let cursors = &buffer.views[view_id].cursors;
+        if buffer.read_only || cursors.len() != 1 || cursors.first().has_selection() {
+            return;
+        }
+        let byte_idx = cursors.first().position;
+
+        let path_range = self
+            .config
+            .editor
+            .path_completion
+            .then(|| path_completion::find_partial_path(buffer.rope(), byte_idx))
+            .flatten();
+        let (range, options) = if let Some(range) = path_range {
+            let partial = buffer.rope().byte_slice(range.clone()).to_string();
+            let files = self.file_scanner.files();
+            let options =
+                path_completion::complete_path(files.iter().map(|(_, file)| file), &partial)
+                    .into_iter()
+                    .map(|path| CompletionItem {
+                        label: path.clone(),
+                        text: path,
+                        kind: CompletionKind::Path,
+                    })
+                    .collect();
+            (range, options)
+        } else if explicit || self.config.editor.auto_complete {
+            let Some(range) = completion::find_partial_word(buffer.rope(), byte_idx) else {
+                return;
+            };
+            // Completing after every typed character is noisy
+            if !explicit && range.len() < 2 {
+                return;
+            }
+            let partial = buffer.rope().byte_slice(range.clone()).to_string();
+            let mut options = self
+                .config
+                .languages
+                .from_name(buffer.language_name())
+                .map(|language| completion::complete_snippets(&language.snippets, &partial))
+                .unwrap_or_default();
+            let words = self.word_index.complete(&self.workspace.buffers, &partial);
+            options.extend(words.into_iter().map(|word| CompletionItem {
+                label: word.clone(),
+                text: word,
+                kind: CompletionKind::Word,
+            }));
+            options.truncate(completion::MAX_OPTIONS);
+            (range, options)
+        } else {
+            return;
+        };
+        if options.is_empty() {
+            return;
+        }
+
+        self.completion = Some(Completion {
+            buffer_id,
+            view_id,
+            range,
+            options,
+            selected: 0,
+        });
+    }
+
+    /// Prompts the user to trust the current workspace if it has never been opened before
+    fn check_workspace_trust(&mut self) {
+        let Ok(dir) = env::current_dir() else {
+            return;
+        };
+
+        // Nobody can answer the prompt of a headless engine, only workspaces trusted before are trusted
+        let trust = match self.headless {
+            true if trust::is_trusted(&dir) => Trust::Trusted,
+            true => Trust::Untrusted,
+            false => trust::get_trust(&dir),
+        };
+        match trust {
+            Trust::Trusted => {
+                if !self.workspace.trusted {
+                    self.workspace.trusted = true;
+                    self.workspace.load_config(self.proxy.dup());
+                }
+                self.load_workspace_overrides(&dir);
+            }
+            Trust::Untrusted => {
+                self.workspace.trusted = false;
+                self.config.clear_workspace_overrides();
+                self.config_updated();
+            }
+            Trust::Unknown => {
+                self.workspace.trusted = false;
+                self.config.clear_workspace_overrides();
+                self.config_updated();
+                self.palette.set_prompt(
+                    tr!("prompt.trust_workspace", path = dir.display()),
+                    ('y', PalettePromptEvent::TrustWorkspace),
+                    ('n', PalettePromptEvent::DistrustWorkspace),
+                );
+            }
+        }
+    }
+
+    pub fn trust_workspace(&mut self) {
+        let dir = match env::current_dir() {
+            Ok(dir) => dir,
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
+            }
+        };
+
+        if let Err(err) = trust::set_trusted(&dir, true) {
+            self.palette.set_error(err);
+            return;
+        }
+
+        if !self.workspace.trusted {
+            self.workspace.trusted = true;
+            self.workspace.load_config(self.proxy.dup());
+        }
+        self.load_workspace_overrides(&dir);
+        self.palette
+            .set_msg(tr!("workspace.trusted", path = dir.display()));
+    }
+
+    /// Workspace overrides can run arbitrary commands through formatters so they are only loaded for trusted workspaces
+    fn load_workspace_overrides(&mut self, dir: &Path) {
+        self.config
+            .load_workspace_overrides(dir, self.proxy.as_ref());
+        self.config_updated();
+    }
+
+    /// Fixes up state that depends on the effective config after it has changed
+    fn config_updated(&mut self) {
+        self.invalidate();
+        if !self.themes.contains_key(&self.config.editor.theme) {
+            self.config.editor.theme = "default".into();
+        }
+        if let Err(err) = i18n::set_locale(&self.config.editor.locale) {
+            self.palette.set_error(err);
+        }
+        self.logger_state.max_messages = self.config.editor.logger.max_messages;
+        if self.file_scanner.is_outdated(&self.config.editor) {
+            self.file_scanner = FileScanner::new(
+                env::current_dir().unwrap_or(PathBuf::from(".")),
+                &self.config.editor,
+            );
+        }
+    }
+
+    fn handle_remote_requests(&mut self) {
+        while let Some((request, tx)) = self
+            .ipc_server
+            .as_ref()
+            .and_then(|server| server.try_recv())
+        {
+            self.invalidate();
+            match request {
+                ipc::Request::Open {
+                    path,
+                    line,
+                    column,
+                    read_only,
+                    wait,
+                } => {
+                    let line = line.unwrap_or(1).saturating_sub(1);
+                    let column = column.unwrap_or(1).saturating_sub(1);
+                    if !self.open_file_at(&path, line, column) {
+                        let _ = tx.send(ipc::Response::Error {
+                            message: tr!("workspace.remote_open_failed", path = path.display()),
+                        });
+                        continue;
+                    }
+                    if read_only {
+                        if let Some((buffer, _)) = self.get_current_buffer_mut() {
+                            buffer.read_only = true;
+                        }
+                    }
+                    let _ = tx.send(ipc::Response::Opened);
+                    self.focus_requested = true;
+                    if let (true, Some((buffer_id, _))) = (wait, self.get_current_buffer_id()) {
+                        self.remote_waiters.push((buffer_id, tx));
+                    }
+                }
+            }
+        }
+
+        let buffers = &self.workspace.buffers;
+        self.remote_waiters.retain(|(buffer_id, tx)| {
+            let open = buffers.contains_key(*buffer_id);
+            if !open {
+                let _ = tx.send(ipc::Response::Closed);
+            }
+            open
+        });
+    }
+
+    /// Opens a file dropped on the window in the drop target, directories are opened as the workspace
+    pub fn open_dropped_path(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if let Some(pane) = self.drop_target.take() {
+            if self.workspace.panes.contains(pane) {
+                self.workspace.panes.make_current(pane);
+            }
+        }
+        if path.is_dir() {
+            self.change_directory(path);
+        } else {
+            self.open_file(path);
+        }
+    }
+
+    pub fn change_directory(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if let Err(err) = self.workspace.save_workspace() {
+            self.palette.set_error(err);
         }
+        match env::set_current_dir(path) {
+            Ok(_) => {
+                self.close_pickers();
+
+                let current_dir = env::current_dir().unwrap_or(PathBuf::from("."));
+                recent::add_project(&current_dir);
+                self.file_scanner = FileScanner::new(current_dir, &self.config.editor);
+
+                match BranchWatcher::new(self.proxy.dup()) {
+                    Ok(branch_watcher) => self.branch_watcher = branch_watcher,
+                    Err(err) => {
+                        let msg = tr!("workspace.branch_watcher_failed", err = err);
+                        tracing::error!(msg);
+                        self.palette.set_error(msg);
+                    }
+                }
+
+                self.workspace = match Workspace::load_workspace(true, self.proxy.dup()) {
+                    Ok(workspace) => workspace,
+                    Err(err) => {
+                        let msg = tr!("workspace.load_failed", err = err);
+                        tracing::error!(msg);
+                        self.palette.set_error(msg);
+                        Workspace::default()
+                    }
+                };
+                self.workspace.set_proxy(self.proxy.dup());
+                self.word_index.clear();
+
+                self.palette
+                    .set_msg(tr!("workspace.working_dir", path = path.display()));
+                self.check_workspace_trust();
+            }
+            Err(err) => self.palette.set_error(format!("{err}")),
+        }
+    }
+
+    /// Called by frontends when the window or terminal gains or loses focus
+    pub fn focus_changed(&mut self, focused: bool) {
+        if self.focused == focused {
+            return;
+        }
+        self.focused = focused;
+        self.invalidate();
+        if focused {
+            // Files on some file systems change without the watcher being notified
+            if let Some(buffer_watcher) = &mut self.buffer_watcher {
+                buffer_watcher.refresh(&mut self.workspace.buffers);
+            }
+        } else {
+            // Keeps the cursor positions and layout in case the editor is closed from the outside
+            if !self.headless {
+                self.autosave_workspace();
+            }
+            if !self.config.editor.save_on_focus_lost {
+                return;
+            }
+            let buffers_to_save: Vec<_> = self
+                .workspace
+                .buffers
+                .iter()
+                .filter(|(_, buffer)| {
+                    buffer.is_dirty() && buffer.file().is_some() && !buffer.read_only
+                })
+                .map(|(buffer_id, _)| buffer_id)
+                .collect();
+            for buffer_id in buffers_to_save {
+                self.save_buffer(buffer_id, None);
+            }
+        }
+    }
+
+    /// Exits without asking about unsaved buffers, used when the frontend is terminated by a signal.
+    /// Scratch buffers are saved like on quit, the other unsaved buffers are written to the recovery dir
+    /// and offered for recovery on the next start.
+    pub fn terminate(&mut self, control_flow: &mut EventLoopControlFlow) {
+        self.save_scratch_buffers();
+        self.terminated = true;
+        *control_flow = EventLoopControlFlow::Exit;
     }
 
     pub fn quit(&mut self, control_flow: &mut EventLoopControlFlow) {
+        self.save_scratch_buffers();
         let unsaved: Vec<_> = self
             .workspace
             .buffers
@@ -1216,17 +3108,17 @@ impl Engine {
 
         if !unsaved.is_empty() {
             self.palette.set_prompt(
-                format!(
-                    "You have {} unsaved buffer(s): {:?}, Are you sure you want to exit?",
-                    unsaved.len(),
-                    unsaved
+                tr!(
+                    "prompt.quit_unsaved",
+                    count = unsaved.len(),
+                    names = format!("{unsaved:?}")
                 ),
                 ('y', PalettePromptEvent::Quit),
                 ('n', PalettePromptEvent::Nop),
             );
         } else if self.config.editor.always_prompt_on_exit {
             self.palette.set_prompt(
-                "Are you sure you want to exit?",
+                tr!("prompt.quit"),
                 ('y', PalettePromptEvent::Quit),
                 ('n', PalettePromptEvent::Nop),
             );
@@ -1237,7 +3129,7 @@ impl Engine {
 
     pub fn open_buffer_picker(&mut self) {
         self.palette.reset();
-        self.file_picker = None;
+        self.close_pickers();
         let mut buffers: Vec<_> = self
             .workspace
             .buffers
@@ -1268,79 +3160,937 @@ impl Engine {
         ));
     }
 
-    pub fn open_file_picker(&mut self) {
+    pub fn open_recent_file_picker(&mut self) {
         self.palette.reset();
-        self.buffer_picker = None;
-        self.file_scanner = FileScanner::new(
-            env::current_dir().unwrap_or(PathBuf::from(".")),
-            &self.config.editor,
-        );
+        self.close_pickers();
+        let recent = Recent::load().unwrap_or_else(|err| {
+            tracing::error!("Error loading recent files: {err}");
+            Recent::default()
+        });
+        let files: boxcar::Vec<_> = recent
+            .existing_files()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
         self.file_picker = Some(Picker::new(
-            FileFindProvider(self.file_scanner.subscribe()),
-            Some(Box::new(FilePreviewer::new(self.proxy.dup()))),
+            RecentProvider(Arc::new(files)),
+            Some(Box::new(FilePreviewer::new(
+                self.proxy.dup(),
+                self.get_open_buffer_ropes(),
+            ))),
             self.proxy.dup(),
-            self.try_get_current_buffer_path(),
+            None,
         ));
     }
 
-    pub fn open_config(&mut self) {
-        match &self.config.editor_path {
-            Some(path) => {
-                self.open_file(path.clone());
+    pub fn open_recent_project_picker(&mut self) {
+        self.palette.reset();
+        self.close_pickers();
+        let recent = Recent::load().unwrap_or_else(|err| {
+            tracing::error!("Error loading recent projects: {err}");
+            Recent::default()
+        });
+        let projects: boxcar::Vec<_> = recent
+            .existing_projects()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        self.project_picker = Some(Picker::new(
+            RecentProvider(Arc::new(projects)),
+            None,
+            self.proxy.dup(),
+            None,
+        ));
+    }
+
+    pub fn open_scratch(&mut self, name: &str) {
+        let path = match scratch::get_scratch_path(name) {
+            Ok(path) => path,
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
             }
-            None => self.palette.set_error("Could not locate the config file"),
+        };
+
+        if !self.open_file(path) {
+            return;
         }
-    }
 
-    pub fn open_default_config(&mut self) {
-        let mut buffer = Buffer::with_name("default_config.toml");
-        buffer.set_text(Editor::DEFAULT);
-        let view_id = buffer.create_view();
-        self.insert_buffer(buffer, view_id, true);
+        let language = self.config.editor.scratch_language.clone();
+        if let Some((buffer, _)) = self.get_current_buffer_mut() {
+            if buffer.language_name() == "text" && buffer.len_bytes() == 0 {
+                if let Err(err) = buffer.set_langauge(&language) {
+                    self.palette.set_error(err);
+                }
+            }
+        }
     }
 
-    pub fn open_languages(&mut self) {
-        match &self.config.languages_path {
-            Some(path) => {
-                self.open_file(path.clone());
+    pub fn open_scratch_picker(&mut self) {
+        self.palette.reset();
+        self.close_pickers();
+        let scratches = match scratch::list_scratches() {
+            Ok(scratches) => scratches,
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
             }
-            None => self
-                .palette
-                .set_error("Could not locate the languages file"),
-        }
+        };
+        self.scratch_picker = Some(Picker::new(
+            RecentProvider(Arc::new(scratches.into_iter().collect())),
+            Some(Box::new(ScratchPreviewer(FilePreviewer::new(
+                self.proxy.dup(),
+                self.get_open_buffer_ropes(),
+            )))),
+            self.proxy.dup(),
+            None,
+        ));
     }
 
-    pub fn open_default_languages(&mut self) {
-        let mut buffer = Buffer::with_name("default_languages.toml");
-        buffer.set_text(Languages::DEFAULT);
-        let view_id = buffer.create_view();
-        self.insert_buffer(buffer, view_id, true);
+    pub fn open_register_picker(&mut self) {
+        self.palette.reset();
+        self.close_pickers();
+        let registers = self
+            .registers
+            .list()
+            .into_iter()
+            .map(|(name, text)| RegisterItem { name, text })
+            .collect();
+        self.register_picker = Some(Picker::new(
+            RegisterProvider(Arc::new(registers)),
+            Some(Box::new(RegisterPreviewer::default())),
+            self.proxy.dup(),
+            None,
+        ));
     }
 
-    pub fn open_keymap(&mut self) {
-        let keymap = self.config.keymap.to_map();
-        let mut buffer = Buffer::with_name("keymap.toml");
-        let data = toml::to_string_pretty(&keymap).unwrap();
-        buffer.set_text(&format!(
-            "# This are the current loaded keybinds. Editing this file does nothing.\n\n{}",
-            data
+    pub fn open_local_history_picker(&mut self) {
+        let Some((buffer, _)) = self.get_current_buffer() else {
+            return;
+        };
+        let Some(path) = buffer.file() else {
+            self.palette.set_error(tr!("buffer.no_path"));
+            return;
+        };
+        let snapshots = match local_history::list_snapshots(path) {
+            Ok(snapshots) => snapshots,
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
+            }
+        };
+        if snapshots.is_empty() {
+            self.palette
+                .set_msg(tr!("buffer.no_local_history", name = buffer.name()));
+            return;
+        }
+        let previewer = LocalHistoryPreviewer::new(
+            buffer.rope().to_string(),
+            buffer.name().to_string(),
+            self.proxy.dup(),
+        );
+
+        self.palette.reset();
+        self.close_pickers();
+        self.local_history_picker = Some(Picker::new(
+            LocalHistoryProvider(Arc::new(
+                snapshots.into_iter().map(SnapshotItem::new).collect(),
+            )),
+            Some(Box::new(previewer)),
+            self.proxy.dup(),
+            None,
         ));
-        buffer.read_only = false;
-        let view_id = buffer.create_view();
-        self.insert_buffer(buffer, view_id, true);
     }
 
-    pub fn open_default_keymap(&mut self) {
-        let keymap = Keymap::default().to_map();
-        let mut buffer = Buffer::with_name("default_keymap.toml");
-        buffer.set_text(&toml::to_string_pretty(&keymap).unwrap());
-        let view_id = buffer.create_view();
-        self.insert_buffer(buffer, view_id, true);
+    pub fn open_plugin_picker(&mut self) {
+        let plugins: boxcar::Vec<_> = self
+            .plugins
+            .plugins()
+            .map(|(name, plugin)| PluginItem::new(name, plugin))
+            .collect();
+        if plugins.count() == 0 {
+            self.palette.set_msg(tr!("misc.no_plugins"));
+            return;
+        }
+
+        self.palette.reset();
+        self.close_pickers();
+        self.plugin_picker = Some(Picker::new(
+            PluginProvider(Arc::new(plugins)),
+            None,
+            self.proxy.dup(),
+            None,
+        ));
     }
 
-    pub fn open_file_explorer(&mut self, path: Option<PathBuf>) {
-        let file_explorer_id =
-            self.workspace
+    /// Lists the running jobs that can be cancelled
+    pub fn open_job_picker(&mut self) {
+        let jobs: boxcar::Vec<_> = self
+            .job_manager
+            .jobs()
+            .iter()
+            .filter(|job| job.info.cancellable && !job.is_cancelled())
+            .map(JobItem::new)
+            .collect();
+        if self.file_scanner.status().scanning {
+            jobs.push(JobItem::indexing(tr!("job.indexing")));
+        }
+        if jobs.count() == 0 {
+            self.palette.set_msg(tr!("job.none"));
+            return;
+        }
+
+        self.palette.reset();
+        self.close_pickers();
+        self.job_picker = Some(Picker::new(
+            JobProvider(Arc::new(jobs)),
+            None,
+            self.proxy.dup(),
+            None,
+        ));
+    }
+
+    pub fn cancel_job(&mut self, target: JobTarget) {
+        match target {
+            JobTarget::Job(id) => {
+                if !self.job_manager.cancel(id) {
+                    self.palette.set_error(tr!("job.not_running"));
+                }
+            }
+            JobTarget::Indexing => self.file_scanner.cancel(),
+        }
+    }
+
+    pub fn toggle_plugin(&mut self, name: &str) {
+        if self.plugins.toggle(name) {
+            self.palette
+                .set_msg(tr!("misc.plugin_enabled", name = name));
+        } else {
+            self.palette
+                .set_msg(tr!("misc.plugin_disabled", name = name));
+        }
+    }
+
+    pub fn reload_scripts(&mut self) {
+        let (scripts, errors) = Scripts::load();
+        self.scripts = scripts;
+        for err in &errors {
+            tracing::error!("{err}");
+        }
+        match errors.into_iter().next() {
+            Some(err) => self.palette.set_error(err),
+            None => self
+                .palette
+                .set_msg(tr!("misc.scripts_loaded", count = self.scripts.len())),
+        }
+    }
+
+    /// Runs a script command on the current buffer
+    fn run_script(&mut self, input: &str) {
+        let Some((buffer_id, view_id)) = self.get_current_buffer_id() else {
+            return;
+        };
+        let buffer = mem::take(&mut self.workspace.buffers[buffer_id]);
+        let output = self.scripts.run(input, buffer, view_id);
+        self.workspace.buffers[buffer_id] = output.buffer;
+        match output.result {
+            Ok(()) => {
+                if !output.messages.is_empty() {
+                    self.palette.set_msg(output.messages.join("\n"));
+                }
+            }
+            Err(err) => self.palette.set_error(err),
+        }
+    }
+
+    fn handle_plugin_request(&mut self, plugin: &str, request: Message<Request>) {
+        let result = match request.body {
+            Request::ShowMessage { text, error } => {
+                if error {
+                    self.palette.set_error(text);
+                } else {
+                    self.palette.set_msg(text);
+                }
+                Ok(serde_json::Value::Null)
+            }
+            Request::CurrentBuffer => Ok(self
+                .get_current_buffer_id()
+                .and_then(|(buffer_id, _)| {
+                    let buffer = self.workspace.buffers.get(buffer_id)?;
+                    serde_json::to_value(plugins::buffer_info(buffer_id, buffer)).ok()
+                })
+                .unwrap_or_default()),
+            Request::GetText { buffer } => {
+                match self
+                    .workspace
+                    .buffers
+                    .get(plugins::buffer_id_from_ffi(buffer))
+                {
+                    Some(buffer) => Ok(buffer.rope().to_string().into()),
+                    None => Err(String::from("no such buffer")),
+                }
+            }
+            Request::SetText { buffer, text } => {
+                match self
+                    .workspace
+                    .buffers
+                    .get_mut(plugins::buffer_id_from_ffi(buffer))
+                {
+                    Some(buffer) if buffer.read_only => Err(String::from("buffer is read only")),
+                    Some(buffer) => {
+                        buffer.replace_transformed(&text);
+                        Ok(serde_json::Value::Null)
+                    }
+                    None => Err(String::from("no such buffer")),
+                }
+            }
+            Request::InsertText { buffer, text } => {
+                match self
+                    .workspace
+                    .buffers
+                    .get_mut(plugins::buffer_id_from_ffi(buffer))
+                {
+                    Some(buffer) if buffer.read_only => Err(String::from("buffer is read only")),
+                    Some(buffer) => {
+                        if let Some(view_id) = buffer.get_last_used_view() {
+                            buffer.insert_text(view_id, &text, false);
+                        }
+                        Ok(serde_json::Value::Null)
+                    }
+                    None => Err(String::from("no such buffer")),
+                }
+            }
+            Request::RegisterCommand { .. } | Request::Subscribe { .. } => return,
+        };
+        if let Some(id) = request.id {
+            self.plugins.respond(plugin, id, result);
+        }
+    }
+
+    /// Replaces the text of the current buffer with `snapshot`, the restore can be undone
+    pub fn restore_snapshot(&mut self, snapshot: &local_history::Snapshot) {
+        let text = match snapshot.read() {
+            Ok(text) => text,
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
+            }
+        };
+        let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+            return;
+        };
+        let len = buffer.len_bytes();
+        buffer.replace(view_id, 0..len, &text);
+    }
+
+    /// Snapshots every dirty buffer in the background so unsaved work shows up in the local history
+    fn snapshot_dirty_buffers(&mut self) {
+        self.last_local_snapshot = Instant::now();
+        let buffers: Vec<_> = self
+            .workspace
+            .buffers
+            .values()
+            .filter(|buffer| buffer.is_dirty() && buffer.encryption().is_none())
+            .filter_map(|buffer| Some((buffer.file()?.to_path_buf(), buffer.rope().clone())))
+            .collect();
+        if buffers.is_empty() {
+            return;
+        }
+        let _ = self.job_manager.spawn_foreground_job(
+            JobInfo::default(),
+            |_, _: &mut Progressor<()>, buffers: Vec<(PathBuf, Rope)>| {
+                for (path, rope) in buffers {
+                    if let Err(err) = local_history::snapshot(&path, &rope.to_string()) {
+                        tracing::error!("Error saving local history snapshot: {err}");
+                    }
+                }
+            },
+            buffers,
+        );
+    }
+
+    /// Keeps the unsaved buffers around so the panic hook can write them to the recovery file
+    fn update_emergency_snapshot(&self) {
+        let buffers = self
+            .workspace
+            .buffers
+            .values()
+            .filter(|buffer| buffer.is_dirty() && buffer.encryption().is_none())
+            .map(|buffer| EmergencyBuffer {
+                path: buffer.file().map(Path::to_path_buf),
+                name: buffer.name().to_string(),
+                rope: buffer.rope().clone(),
+                cursors: buffer
+                    .get_last_used_view()
+                    .map(|view_id| buffer.views[view_id].cursors.clone())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        recovery::set_emergency_snapshot(buffers);
+    }
+
+    /// Keeps the workspace around so the panic hook can save it
+    fn snapshot_workspace(&mut self) -> Option<(PathBuf, String)> {
+        self.workspace_snapshot_changes = Some(self.workspace_changes);
+        self.last_workspace_snapshot = Instant::now();
+        match self.workspace.snapshot() {
+            Ok((path, json)) => {
+                workspace::set_emergency_workspace(path.clone(), json.clone());
+                Some((path, json))
+            }
+            Err(err) => {
+                tracing::error!("Error taking workspace snapshot: {err}");
+                None
+            }
+        }
+    }
+
+    /// Saves the workspace if it has changed since the last autosave
+    fn autosave_workspace(&mut self) {
+        self.last_workspace_autosave = Instant::now();
+        self.autosave_changes = Some(self.workspace_changes);
+        let Some(snapshot) = self.snapshot_workspace() else {
+            return;
+        };
+        if self.autosaved_workspace.as_ref() == Some(&snapshot) {
+            return;
+        }
+        match workspace::write_workspace(&snapshot.0, &snapshot.1) {
+            Ok(()) => self.autosaved_workspace = Some(snapshot),
+            Err(err) => tracing::error!("Error saving workspace: {err}"),
+        }
+    }
+
+    /// If there are unsaved buffers that are written to the recovery file
+    fn has_recoverable_buffers(&self) -> bool {
+        self.workspace
+            .buffers
+            .values()
+            .any(|buffer| buffer.is_dirty() && buffer.encryption().is_none())
+    }
+
+    /// The unsaved buffers as they are written to the recovery file
+    fn recovered_buffers(&self) -> Vec<RecoveredBuffer> {
+        self.workspace
+            .buffers
+            .values()
+            .filter(|buffer| buffer.is_dirty() && buffer.encryption().is_none())
+            .map(|buffer| RecoveredBuffer {
+                path: buffer.file().map(Path::to_path_buf),
+                name: buffer.name().to_string(),
+                text: buffer.rope().to_string(),
+                cursors: buffer
+                    .get_last_used_view()
+                    .map(|view_id| buffer.views[view_id].cursors.clone())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Writes the unsaved buffers to this session's recovery file in the background
+    fn write_recovery(&mut self) {
+        self.last_recovery_write = Instant::now();
+        if let Some(job) = &mut self.recovery_job {
+            if job.try_recv().is_err() {
+                return;
+            }
+        }
+        let buffers = self.recovered_buffers();
+        if buffers.is_empty() && !self.has_recovery_file {
+            return;
+        }
+        self.has_recovery_file = !buffers.is_empty();
+        self.recovery_job = Some(self.job_manager.spawn_foreground_job(
+            JobInfo::default(),
+            |_, _: &mut Progressor<()>, buffers: Vec<RecoveredBuffer>| {
+                if let Err(err) = recovery::write_session(&buffers) {
+                    tracing::error!("Error writing recovery file: {err}");
+                }
+            },
+            buffers,
+        ));
+    }
+
+    /// Opens the buffers recovered from crashed sessions with their unsaved text
+    /// and shows a diff against the files on disk.
+    /// A file that is already open is not touched, its recovered text is opened in a new buffer instead.
+    pub fn restore_crashed_sessions(&mut self) {
+        let sessions = mem::take(&mut self.crashed_sessions);
+        let recovered = recovery::load_sessions(&sessions);
+        if recovered.is_empty() {
+            self.palette.set_msg(tr!("buffer.nothing_to_recover"));
+            recovery::remove_sessions(&sessions);
+            return;
+        }
+
+        let mut count = 0;
+        let mut unrestored = Vec::new();
+        let mut diffs = String::new();
+        for recovered in recovered {
+            let already_open = recovered
+                .path
+                .as_ref()
+                .and_then(|path| dunce::canonicalize(path).ok())
+                .is_some_and(|path| {
+                    self.workspace
+                        .buffers
+                        .values()
+                        .any(|buffer| buffer.file() == Some(&path))
+                });
+            let disk = match &recovered.path {
+                Some(path) if path.exists() && !already_open => {
+                    if !self.open_file(path) {
+                        unrestored.push(recovered);
+                        continue;
+                    }
+                    buffer::read::read_from_file(path)
+                        .map(|(_, rope)| rope.to_string())
+                        .unwrap_or_default()
+                }
+                path => {
+                    let mut buffer = match path {
+                        Some(path) if !already_open => match Buffer::with_path(path) {
+                            Ok(buffer) => buffer,
+                            Err(err) => {
+                                self.palette.set_error(err);
+                                unrestored.push(recovered);
+                                continue;
+                            }
+                        },
+                        _ => {
+                            let mut buffer = Buffer::new();
+                            if already_open {
+                                buffer.set_name(format!("{} (recovered)", recovered.name));
+                            } else {
+                                buffer.set_name(recovered.name.clone());
+                            }
+                            buffer
+                        }
+                    };
+                    let view_id = buffer.create_view();
+                    self.insert_buffer(buffer, view_id, true);
+                    match path {
+                        Some(path) if already_open => buffer::read::read_from_file(path)
+                            .map(|(_, rope)| rope.to_string())
+                            .unwrap_or_default(),
+                        _ => String::new(),
+                    }
+                }
+            };
+            let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+                unrestored.push(recovered);
+                continue;
+            };
+            let len = buffer.len_bytes();
+            buffer.replace(view_id, 0..len, &recovered.text);
+            buffer.views[view_id].cursors = recovered.cursors;
+            buffer.ensure_cursors_are_valid(view_id);
+            count += 1;
+            if disk != recovered.text {
+                diffs.push_str(&diff::unified_diff(
+                    &disk,
+                    &recovered.text,
+                    "disk",
+                    &recovered.name,
+                ));
+            }
+        }
+        // Buffers that could not be opened are kept so they are offered again on the next start
+        match recovery::write_unrestored(&unrestored) {
+            Ok(()) => recovery::remove_sessions(&sessions),
+            Err(err) => tracing::error!("Error keeping unrestored buffers: {err}"),
+        }
+
+        if !diffs.is_empty() {
+            self.open_diff_split("disk <-> recovered".into(), &diffs);
+        }
+        self.palette.set_msg(tr!("buffer.recovered", count = count));
+    }
+
+    /// Copies the selections of the current buffer to `register`, removing them if `cut` is set
+    pub fn copy_to_register(&mut self, register: char, cut: bool) {
+        if !Registers::is_valid_name(register) {
+            self.palette
+                .set_error(tr!("misc.invalid_register", register = register));
+            return;
+        }
+        let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+            return;
+        };
+        let text = if cut && !buffer.read_only {
+            buffer.cut_text(view_id)
+        } else {
+            buffer.copy_text(view_id)
+        };
+        self.registers.set(register, text);
+        self.palette
+            .set_msg(tr!("misc.copied_to_register", register = register));
+    }
+
+    pub fn paste_from_register(&mut self, register: char) {
+        if !Registers::is_valid_name(register) {
+            self.palette
+                .set_error(tr!("misc.invalid_register", register = register));
+            return;
+        }
+        let Some(text) = self.registers.get(register) else {
+            self.palette
+                .set_error(tr!("misc.register_empty", register = register));
+            return;
+        };
+        if let Some((buffer, view_id)) = self.get_current_buffer_mut() {
+            if !buffer.read_only {
+                buffer.paste_text(view_id, &text);
+            }
+        }
+    }
+
+    /// Writes all scratch buffers with unsaved changes to disk
+    pub fn save_scratch_buffers(&mut self) {
+        for buffer in self.workspace.buffers.values_mut() {
+            if !buffer.is_scratch() || !buffer.is_dirty() {
+                continue;
+            }
+            let Some(path) = buffer.file() else {
+                continue;
+            };
+            match buffer::write::write(
+                buffer.encoding,
+                buffer.bom,
+                buffer.line_ending,
+                buffer.rope().clone(),
+                path,
+            ) {
+                Ok(_) => buffer.mark_saved(),
+                Err(err) => tracing::error!("Error saving scratch buffer: {err}"),
+            }
+        }
+    }
+
+    pub fn open_custom_picker(&mut self, name: &str) {
+        let Some(custom) = self.config.editor.pickers.get(name) else {
+            self.palette
+                .set_error(tr!("config.picker_not_found", name = name));
+            return;
+        };
+
+        let previewer: Option<Box<dyn Previewer<String>>> =
+            if custom.action == CustomPickerAction::Open {
+                Some(Box::new(CustomPickerPreviewer(FilePreviewer::new(
+                    self.proxy.dup(),
+                    self.get_open_buffer_ropes(),
+                ))))
+            } else {
+                None
+            };
+        let action = custom.action.clone();
+        let picker = Picker::new(
+            CustomPickerProvider {
+                cmd: custom.cmd.clone(),
+            },
+            previewer,
+            self.proxy.dup(),
+            None,
+        );
+
+        self.palette.reset();
+        self.close_pickers();
+        self.custom_picker = Some((name.to_string(), action, picker));
+    }
+
+    fn handle_custom_picker_choice(&mut self, action: CustomPickerAction, choice: String) {
+        match action {
+            CustomPickerAction::Open => {
+                let (path, line) = parse_path_line(&choice);
+                if !self.open_file(path) {
+                    return;
+                }
+                if let (Some(line), Some((buffer, view_id))) = (line, self.get_current_buffer_mut())
+                {
+                    buffer.goto(view_id, line);
+                    buffer.set_view_lines(view_id, 10);
+                    buffer.center_on_cursor(view_id);
+                }
+            }
+            CustomPickerAction::Insert => {
+                if let Some((buffer, view_id)) = self.get_current_buffer_mut() {
+                    if !buffer.read_only {
+                        buffer.insert_text(view_id, &choice, false);
+                    }
+                }
+            }
+            CustomPickerAction::Run(cmd) => {
+                let cmd = cmd.replace("{}", &tools::shell_quote(&choice));
+                self.run_shell_command(cmd, self.config.editor.pipe_shell_palette);
+            }
+        }
+    }
+
+    /// Opens a diff of the clipboard and the current selection in a new pane
+    pub fn diff_selection_against_clipboard(&mut self) {
+        let Some((buffer, view_id)) = self.get_current_buffer() else {
+            return;
+        };
+        let selection = buffer.get_selection(view_id, 0);
+        if selection.is_empty() {
+            self.palette.set_error(tr!("buffer.nothing_selected"));
+            return;
+        }
+
+        let clipboard = clipboard::get_contents();
+        if clipboard == selection {
+            self.palette
+                .set_msg(tr!("buffer.selection_equals_clipboard"));
+            return;
+        }
+
+        let diff = diff::unified_diff(&clipboard, &selection, "clipboard", "selection");
+        self.open_diff_split("clipboard <-> selection".into(), &diff);
+    }
+
+    /// Opens a diff between the file on disk and the buffer so external changes can be merged by hand.
+    /// Afterwards the buffer counts as up to date with the file so the next save goes through.
+    pub fn diff_buffer_disk(&mut self, buffer_id: BufferId) {
+        let Some(buffer) = self.workspace.buffers.get_mut(buffer_id) else {
+            return;
+        };
+        let Some(path) = buffer.file().map(Path::to_path_buf) else {
+            return;
+        };
+        let disk = match buffer.encryption() {
+            Some(encryption) => encryption
+                .read_file(&path)
+                .and_then(|plaintext| buffer::read::read(&plaintext[..])),
+            None => buffer::read::read_from_file(&path),
+        };
+        let disk = match disk {
+            Ok((_, rope)) => rope.to_string(),
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
+            }
+        };
+        buffer.set_disk_state(DiskState::read(&path).ok());
+        let name = buffer.name().to_string();
+        let diff = diff::unified_diff(&disk, &buffer.rope().to_string(), "disk", &name);
+        self.open_diff_split(format!("disk <-> {name}"), &diff);
+    }
+
+    pub fn toggle_follow_pane(&mut self) {
+        if let Some(follow) = self.follow_pane.take() {
+            let pane = PaneKind::Buffer(follow.buffer_id, follow.view_id);
+            let current = self.workspace.panes.get_current_pane();
+            if self.workspace.panes.remove_pane(pane) {
+                if current != pane {
+                    self.workspace.panes.make_current(current);
+                }
+                self.workspace.buffers.remove(follow.buffer_id);
+            }
+            return;
+        }
+
+        let current = self.workspace.panes.get_current_pane();
+        let mut buffer = Buffer::new();
+        buffer.set_name(String::from("follow"));
+        buffer.read_only = true;
+        buffer.set_proxy(self.proxy.dup());
+        let view_id = buffer.create_view();
+        let buffer_id = self.workspace.buffers.insert(buffer);
+        self.workspace
+            .panes
+            .split(PaneKind::Buffer(buffer_id, view_id), Direction::Right);
+        self.workspace.panes.make_current(current);
+        self.follow_pane = Some(FollowPane::new(buffer_id, view_id));
+    }
+
+    /// Shows the definition of the symbol under the cursor in the follow pane.
+    /// Returns the time left until the pane should be updated.
+    fn update_follow_pane(&mut self) -> Option<Duration> {
+        let follow = self.follow_pane.as_mut()?;
+        if !self
+            .workspace
+            .panes
+            .contains(PaneKind::Buffer(follow.buffer_id, follow.view_id))
+        {
+            self.follow_pane = None;
+            return None;
+        }
+        let symbol = match self.workspace.panes.get_current_pane() {
+            PaneKind::Buffer(buffer_id, view_id) if buffer_id != follow.buffer_id => {
+                self.workspace.buffers[buffer_id].word_under_cursor(view_id)
+            }
+            _ => None,
+        };
+        match follow.update(symbol, Instant::now()) {
+            FollowUpdate::Idle => None,
+            FollowUpdate::Wait(remaining) => Some(remaining),
+            FollowUpdate::Show(symbol) => {
+                let (buffer_id, view_id) = (follow.buffer_id, follow.view_id);
+                self.show_definition(buffer_id, view_id, &symbol);
+                None
+            }
+        }
+    }
+
+    fn show_definition(&mut self, buffer_id: BufferId, view_id: ViewId, symbol: &str) {
+        let Some(tag) = self.tags.find(&mut self.job_manager, symbol).cloned() else {
+            return;
+        };
+        // Prefer the text of an open buffer so unsaved changes are shown
+        let open = self.workspace.buffers.values().find(|buffer| {
+            buffer
+                .file()
+                .is_some_and(|path| dunce::canonicalize(&tag.path).is_ok_and(|tag| tag == path))
+        });
+        let rope = match open {
+            Some(buffer) => buffer.rope().clone(),
+            None => match buffer::read::read_from_file(&tag.path) {
+                Ok((_, rope)) => rope,
+                Err(err) => {
+                    tracing::error!("Error reading `{}`: {err}", tag.path.display());
+                    return;
+                }
+            },
+        };
+        let line = tag.line(&rope).unwrap_or(0);
+
+        let buffer = &mut self.workspace.buffers[buffer_id];
+        buffer.set_text(&rope.to_string());
+        buffer.set_name(format!("follow: {symbol}"));
+        if let Some(language) = get_language_from_path(&tag.path) {
+            if let Err(err) = buffer.set_langauge(language) {
+                tracing::error!("Error setting language: {err}");
+            }
+        }
+        buffer.goto(view_id, line as i64 + 1);
+        buffer.center_on_cursor(view_id);
+    }
+
+    fn open_diff_split(&mut self, name: String, diff: &str) {
+        let mut buffer = Buffer::new();
+        buffer.set_name(name);
+        buffer.set_text(diff);
+        if let Err(err) = buffer.set_langauge("diff") {
+            tracing::error!("Error setting language: {err}");
+        }
+        buffer.read_only = true;
+        buffer.set_proxy(self.proxy.dup());
+        let view_id = buffer.create_view();
+        let buffer_id = self.workspace.buffers.insert(buffer);
+        self.workspace
+            .panes
+            .split(PaneKind::Buffer(buffer_id, view_id), Direction::Right);
+    }
+
+    pub fn open_file_picker(&mut self) {
+        self.palette.reset();
+        self.close_pickers();
+        self.file_picker = Some(Picker::new(
+            FileFindProvider(self.file_scanner.subscribe()),
+            Some(Box::new(FilePreviewer::new(
+                self.proxy.dup(),
+                self.get_open_buffer_ropes(),
+            ))),
+            self.proxy.dup(),
+            self.try_get_current_buffer_path(),
+        ));
+    }
+
+    pub fn show_index_status(&mut self) {
+        let status = self.file_scanner.status();
+        let files = self.file_scanner.files().count();
+        let refreshed = match status.refreshed_at {
+            Some(time) => tr!(
+                "index.refreshed",
+                elapsed = format_elapsed(time.elapsed().unwrap_or_default())
+            ),
+            None => tr!("index.never_refreshed"),
+        };
+        let state = if status.scanning {
+            tr!("index.scanning")
+        } else if status.cancelled {
+            tr!("index.cancelled_state")
+        } else if status.watching {
+            tr!("index.watching")
+        } else {
+            tr!("index.not_watching")
+        };
+        self.palette.set_msg(tr!(
+            "index.status",
+            files = files,
+            root = status.root.display(),
+            refreshed = refreshed,
+            state = state,
+        ));
+    }
+
+    pub fn open_config(&mut self) {
+        match &self.config.editor_path {
+            Some(path) => {
+                self.open_file(path.clone());
+            }
+            None => self.palette.set_error(tr!("config.config_not_found")),
+        }
+    }
+
+    /// Opens the file of the active theme, it is reloaded when saved
+    pub fn edit_theme(&mut self) {
+        let name = &self.config.editor.theme;
+        match EditorTheme::find_theme_file(name) {
+            Some(path) => {
+                self.open_file(path);
+            }
+            None => self
+                .palette
+                .set_error(tr!("config.theme_file_not_found", name = name)),
+        }
+    }
+
+    pub fn open_default_config(&mut self) {
+        let mut buffer = Buffer::with_name("default_config.toml");
+        buffer.set_text(Editor::DEFAULT);
+        let view_id = buffer.create_view();
+        self.insert_buffer(buffer, view_id, true);
+    }
+
+    pub fn open_languages(&mut self) {
+        match &self.config.languages_path {
+            Some(path) => {
+                self.open_file(path.clone());
+            }
+            None => self.palette.set_error(tr!("config.languages_not_found")),
+        }
+    }
+
+    pub fn open_default_languages(&mut self) {
+        let mut buffer = Buffer::with_name("default_languages.toml");
+        buffer.set_text(Languages::DEFAULT);
+        let view_id = buffer.create_view();
+        self.insert_buffer(buffer, view_id, true);
+    }
+
+    pub fn open_keymap(&mut self) {
+        let keymap = self.config.keymap.to_map();
+        let mut buffer = Buffer::with_name("keymap.toml");
+        let data = toml::to_string_pretty(&keymap).unwrap();
+        buffer.set_text(&format!(
+            "# This are the current loaded keybinds. Editing this file does nothing.\n\n{}",
+            data
+        ));
+        buffer.read_only = false;
+        let view_id = buffer.create_view();
+        self.insert_buffer(buffer, view_id, true);
+    }
+
+    pub fn open_default_keymap(&mut self) {
+        let keymap = Keymap::default().to_map();
+        let mut buffer = Buffer::with_name("default_keymap.toml");
+        buffer.set_text(&toml::to_string_pretty(&keymap).unwrap());
+        let view_id = buffer.create_view();
+        self.insert_buffer(buffer, view_id, true);
+    }
+
+    pub fn open_file_explorer(&mut self, path: Option<PathBuf>) {
+        let file_explorer_id =
+            self.workspace
                 .file_explorers
                 .insert(FileExplorer::new(path.unwrap_or_else(|| {
                     self.get_current_buffer()
@@ -1362,7 +4112,7 @@ impl Engine {
             PaneKind::FileExplorer(file_explorer_id) => {
                 self.workspace.file_explorers.remove(file_explorer_id);
             }
-            PaneKind::Logger => (),
+            _ => {}
         }
     }
 
@@ -1372,13 +4122,20 @@ impl Engine {
             return;
         };
 
-        if !buffer.is_dirty() {
+        let (scratch, dirty) = (buffer.is_scratch(), buffer.is_dirty());
+        if scratch {
+            self.save_scratch_buffers();
+            self.force_close_current_buffer();
+            return;
+        }
+
+        if !dirty {
             self.force_close_current_buffer();
             return;
         }
 
         self.palette.set_prompt(
-            "Current buffer has unsaved changes are you sure you want to close it?",
+            tr!("prompt.close_unsaved"),
             ('y', PalettePromptEvent::CloseCurrent),
             ('n', PalettePromptEvent::Nop),
         );
@@ -1425,6 +4182,7 @@ impl Engine {
 
         next_buffer.unwrap_or_else(|| {
             let mut buffer = Buffer::new();
+            buffer.set_proxy(self.proxy.dup());
             let view_id = buffer.create_view();
             (self.workspace.buffers.insert(buffer), view_id)
         })
@@ -1451,16 +4209,26 @@ impl Engine {
                 PaneKind::Logger => {
                     self.workspace.panes.remove_pane(PaneKind::Logger);
                 }
+                PaneKind::LocationList => {
+                    self.workspace.panes.remove_pane(PaneKind::LocationList);
+                }
+                PaneKind::Git => {
+                    self.workspace.panes.remove_pane(PaneKind::Git);
+                }
+                PaneKind::Notifications => {
+                    self.workspace.panes.remove_pane(PaneKind::Notifications);
+                }
             }
         }
     }
 
     pub fn force_close_current_buffer(&mut self) {
         if let Some((buffer_id, _)) = self.get_current_buffer_id() {
-            if let Some(path) = self.workspace.buffers[buffer_id].file() {
-                self.insert_removed_buffer(path.to_path_buf());
-            }
             let buffer = self.workspace.buffers.remove(buffer_id).unwrap();
+            if !buffer.is_disposable() {
+                self.closed_buffers
+                    .push(ClosedBuffer::new(&buffer, Instant::now()));
+            }
 
             let (new_buffer_id, new_view_id) = self.get_next_buffer();
             self.workspace
@@ -1485,35 +4253,110 @@ impl Engine {
     }
 
     pub fn reopen_last_closed_buffer(&mut self) {
-        while let Some(path) = self.closed_buffers.pop() {
-            if let Some((buffer, _)) = self.get_current_buffer() {
-                if buffer.file() == Some(&path) {
-                    continue;
+        let Some(closed) = self.closed_buffers.pop(Instant::now()) else {
+            self.palette.set_msg(tr!("buffer.nothing_to_reopen"));
+            return;
+        };
+        match &closed.path {
+            Some(path) => {
+                if !self.open_file(path) {
+                    return;
                 }
             }
-            self.open_file(path);
+            None => {
+                let mut buffer = Buffer::new();
+                buffer.set_name(closed.name.clone());
+                let view_id = buffer.create_view();
+                self.insert_buffer(buffer, view_id, true);
+            }
         }
-    }
-
-    fn insert_removed_buffer(&mut self, new: PathBuf) {
-        self.closed_buffers.retain(|path| &new != path);
-        self.closed_buffers.push(new);
+        let Some((buffer_id, view_id)) = self.get_current_buffer_id() else {
+            return;
+        };
+        let buffer = &mut self.workspace.buffers[buffer_id];
+        if let Some(content) = closed.content {
+            buffer.replace(view_id, 0..buffer.len_bytes(), &content.to_string());
+        }
+        if buffer.file().is_none() {
+            if let Err(err) = buffer.set_langauge(&closed.language) {
+                tracing::error!("Error setting language: {err}");
+            }
+        }
+        buffer.load_view_data(
+            view_id,
+            &BufferData {
+                path: closed.path.unwrap_or_default(),
+                cursors: closed.cursors,
+                line_pos: closed.line_pos,
+                col_pos: closed.col_pos,
+                language: closed.language,
+                indent: buffer.indent,
+                bookmarks: Vec::new(),
+            },
+        );
     }
 
     pub fn get_search_prompt(&self, global: bool) -> String {
         let mut prompt = if global {
-            String::from("global-search")
+            match &self.global_search_scope {
+                Some(scope) => format!("global-search in {scope}"),
+                None => String::from("global-search"),
+            }
         } else {
             String::from("search")
         };
-        if self.config.editor.case_insensitive_search {
-            prompt += " (i): ";
-        } else {
-            prompt += ": ";
+        let options = self.search_options();
+        let mut flags = String::new();
+        if options.smart_case {
+            flags.push('s');
+        } else if options.case_insensitive {
+            flags.push('i');
+        }
+        if options.whole_word {
+            flags.push('w');
         }
+        if !flags.is_empty() {
+            prompt += &format!(" ({flags})");
+        }
+        prompt += ": ";
         prompt
     }
 
+    fn update_search_prompt(&mut self) {
+        if let Some("search") = self.palette.mode() {
+            self.palette.update_prompt(self.get_search_prompt(false));
+        }
+        if let Some("global-search") = self.palette.mode() {
+            self.palette.update_prompt(self.get_search_prompt(true));
+        }
+    }
+
+    pub fn search_options(&self) -> SearchOptions {
+        SearchOptions {
+            case_insensitive: self.config.editor.case_insensitive_search,
+            smart_case: self.config.editor.smart_case_search,
+            whole_word: self.config.editor.whole_word_search,
+        }
+    }
+
+    /// Title for the window or terminal, the current file followed by the workspace
+    pub fn title(&self) -> String {
+        let workspace = env::current_dir().ok().and_then(|dir| {
+            dir.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        });
+        let buffer = self.get_current_buffer().map(|(buffer, _)| {
+            let dirty = if buffer.is_dirty() { "*" } else { "" };
+            format!("{}{dirty}", buffer.name())
+        });
+        buffer
+            .into_iter()
+            .chain(workspace)
+            .chain([String::from("ferrite")])
+            .collect::<Vec<_>>()
+            .join(" - ")
+    }
+
     pub fn get_current_buffer_id(&self) -> Option<(BufferId, ViewId)> {
         match self.workspace.panes.get_current_pane() {
             PaneKind::Buffer(buffer_id, view_id) => Some((buffer_id, view_id)),
@@ -1521,6 +4364,18 @@ impl Engine {
         }
     }
 
+    /// Buffers whose text is replaced by the editor, edits to them would be lost
+    /// so they cannot be made editable
+    fn is_generated(&self, buffer_id: BufferId) -> bool {
+        self.outputs.contains_key(buffer_id)
+            || self.ansi_renders.contains_key(buffer_id)
+            || self
+                .workspace
+                .buffers
+                .get(buffer_id)
+                .is_some_and(Buffer::is_tailing)
+    }
+
     pub fn get_current_buffer(&self) -> Option<(&Buffer, ViewId)> {
         let PaneKind::Buffer(buffer, view_id) = self.workspace.panes.get_current_pane() else {
             return None;
@@ -1537,6 +4392,30 @@ impl Engine {
         Some((self.workspace.buffers.get_mut(buffer)?, view_id))
     }
 
+    /// Opens a read only buffer with the report from `doctor::run`
+    pub fn doctor(&mut self) {
+        let mut buffer = Buffer::new();
+        buffer.set_name("doctor".into());
+        buffer.set_text(&doctor::run(&self.config, &self.themes));
+        buffer.read_only = true;
+        let view_id = buffer.create_view();
+        self.insert_buffer(buffer, view_id, true);
+    }
+
+    /// Opens a read only buffer with the whole last error shown in the palette
+    pub fn show_last_error(&mut self) {
+        let Some(error) = self.palette.last_error() else {
+            self.palette.set_msg(tr!("misc.no_last_error"));
+            return;
+        };
+        let mut buffer = Buffer::new();
+        buffer.set_name("last error".into());
+        buffer.set_text(error);
+        buffer.read_only = true;
+        let view_id = buffer.create_view();
+        self.insert_buffer(buffer, view_id, true);
+    }
+
     pub fn insert_buffer(
         &mut self,
         buffer: Buffer,
@@ -1544,6 +4423,7 @@ impl Engine {
         make_current: bool,
     ) -> (BufferId, &mut Buffer) {
         let buffer_id = self.workspace.buffers.insert(buffer);
+        self.workspace.buffers[buffer_id].set_proxy(self.proxy.dup());
         if make_current {
             if let PaneKind::Buffer(buffer_id, view_id) = self.workspace.panes.get_current_pane() {
                 self.workspace.buffers[buffer_id].remove_view(view_id);
@@ -1565,6 +4445,22 @@ impl Engine {
     }
 
     pub fn save_buffer(&mut self, buffer_id: BufferId, path: Option<PathBuf>) {
+        self.save_buffer_with(buffer_id, path, false);
+    }
+
+    /// Saves the buffer, if the file has been changed by another program since it was loaded
+    /// the save is aborted unless `overwrite` is set.
+    pub fn save_buffer_with(
+        &mut self,
+        buffer_id: BufferId,
+        path: Option<PathBuf>,
+        overwrite: bool,
+    ) {
+        if self.ansi_renders.contains_key(buffer_id) {
+            self.palette.set_error(tr!("buffer.ansi_save"));
+            return;
+        }
+
         let buffer = &mut self.workspace.buffers[buffer_id];
 
         if let Some(path) = path {
@@ -1579,6 +4475,20 @@ impl Engine {
             return;
         };
 
+        let filter = encryption::filter_for(&self.config.editor.encryption, &path);
+        if buffer.encryption().map(Encryption::filter) != filter {
+            match filter {
+                Some(filter) if filter.passphrase => {
+                    self.palette
+                        .focus_masked(tr!("prompt.passphrase", name = buffer.name()), "passphrase");
+                    self.passphrase_request = Some(PassphraseRequest::Save(buffer_id));
+                    return;
+                }
+                filter => buffer
+                    .set_encryption(filter.map(|filter| Encryption::new(filter.clone(), None))),
+            }
+        }
+
         let config = self.config.languages.from_name(buffer.language_name());
         let fmt = config.and_then(|config| config.format.clone());
         let auto_trim = config
@@ -1591,36 +4501,161 @@ impl Engine {
         if auto_trim {
             buffer.trim_trailing_whitespace();
         }
+        if self.config.editor.normalize_line_endings {
+            buffer.normalize_line_endings();
+        }
 
-        if auto_format {
+        if auto_format && self.workspace.trusted {
             if let Some(fmt) = fmt {
                 let _ = buffer.format(&fmt);
             }
         }
 
+        if self.run_hooks(buffer_id, HookKind::Save { overwrite }) {
+            return;
+        }
+        self.spawn_save_job(buffer_id, overwrite, None);
+    }
+
+    /// Writes the buffer to its file, or `transformed` instead of its text if a hook changed it
+    fn spawn_save_job(&mut self, buffer_id: BufferId, overwrite: bool, transformed: Option<Rope>) {
+        let buffer = &mut self.workspace.buffers[buffer_id];
+        let previous = buffer.disk_state();
+        let replace_link = !self.config.editor.save_through_symlinks && buffer.detach_link();
+        let Some(path) = buffer.file().map(|p| p.to_owned()) else {
+            self.palette.set_msg(buffer::error::BufferError::NoPathSet);
+            return;
+        };
+        let input = SaveBufferInput {
+            buffer_id,
+            encryption: buffer.encryption().cloned(),
+            encoding: buffer.encoding,
+            bom: buffer.bom,
+            line_ending: buffer.line_ending,
+            rope: transformed.unwrap_or_else(|| buffer.rope().clone()),
+            path,
+            last_edit: buffer.get_last_edit(),
+            disk_state: buffer.disk_state().filter(|_| !overwrite),
+            options: SaveOptions {
+                replace_link,
+                previous,
+            },
+        };
         let job = self.job_manager.spawn_foreground_job(
-            move |_, _, (buffer_id, encoding, line_ending, rope, path, last_edit)| {
-                let written = buffer::write::write(encoding, line_ending, rope.clone(), &path)?;
+            JobInfo::new(tr!("job.save", name = buffer.name())),
+            |_, _, input: SaveBufferInput| {
+                let SaveBufferInput {
+                    buffer_id,
+                    encryption,
+                    encoding,
+                    bom,
+                    line_ending,
+                    rope,
+                    path,
+                    last_edit,
+                    disk_state,
+                    options,
+                } = input;
+                if let Some(disk_state) = disk_state.filter(|disk_state| disk_state.changed(&path))
+                {
+                    let replaced = disk_state.replaced(&path);
+                    return Err(SaveConflict {
+                        buffer_id,
+                        path,
+                        replaced,
+                    }
+                    .into());
+                }
+                let written = buffer::write::save(
+                    encryption.as_ref(),
+                    encoding,
+                    bom,
+                    line_ending,
+                    rope.clone(),
+                    &path,
+                    options,
+                )?;
+                if encryption.is_none() {
+                    if let Err(err) = local_history::snapshot(&path, &rope.to_string()) {
+                        tracing::error!("Error saving local history snapshot: {err}");
+                    }
+                }
+                let disk_state = DiskState::read(&path).ok();
                 Ok(SaveBufferJob {
                     buffer_id,
                     path,
                     last_edit,
                     written,
+                    disk_state,
                 })
             },
-            (
-                buffer_id,
-                buffer.encoding,
-                buffer.line_ending,
-                buffer.rope().clone(),
-                path.to_path_buf(),
-                buffer.get_last_edit(),
-            ),
+            input,
         );
 
         self.save_jobs.push(job);
     }
 
+    /// Starts a job piping the buffer through the hooks matching its file,
+    /// returns false if there are none
+    fn run_hooks(&mut self, buffer_id: BufferId, kind: HookKind) -> bool {
+        if !self.workspace.trusted {
+            return false;
+        }
+        let buffer = &self.workspace.buffers[buffer_id];
+        let Some(path) = buffer.file() else {
+            return false;
+        };
+        let root = env::current_dir().unwrap_or(PathBuf::from("."));
+        let commands = hooks::matching_hooks(&self.config.editor.hooks, &root, path, kind);
+        if commands.is_empty() {
+            return false;
+        }
+        let job = self.job_manager.spawn_foreground_job(
+            JobInfo::new(tr!("job.hooks", name = buffer.name())),
+            hooks::run_hooks_job,
+            HookInput {
+                buffer_id,
+                kind,
+                text: buffer.rope().to_string(),
+                commands,
+                last_edit: buffer.get_last_edit(),
+            },
+        );
+        self.hook_jobs.push(job);
+        true
+    }
+
+    fn finish_hook_job(&mut self, result: Result<HookOutput>) {
+        let output = match result {
+            Ok(output) => output,
+            Err(err) => {
+                self.notify(Severity::Error, err);
+                return;
+            }
+        };
+        let Some(buffer) = self.workspace.buffers.get_mut(output.buffer_id) else {
+            return;
+        };
+        if buffer.get_last_edit() != output.last_edit {
+            let msg = tr!("buffer.hook_stale", name = buffer.name());
+            self.notify(Severity::Warning, msg);
+            return;
+        }
+        match output.kind {
+            // The output is what the file contains, it is not an edit
+            HookKind::Open => {
+                if buffer.rope() != output.text.as_str() {
+                    buffer.set_text(&output.text);
+                }
+            }
+            // Only the file gets the output, the buffer keeps the text that was edited
+            HookKind::Save { overwrite } => {
+                let transformed = Some(Rope::from(output.text));
+                self.spawn_save_job(output.buffer_id, overwrite, transformed);
+            }
+        }
+    }
+
     pub fn get_current_keymappings(&self) -> &[Keymapping] {
         if let Some(name) = &self.chord {
             self.config
@@ -1633,19 +4668,105 @@ impl Engine {
         }
     }
 
-    pub fn run_shell_command(&mut self, cmd: String, pipe: bool, read_only: bool) {
-        let buffer_id = if pipe {
-            let mut buffer = Buffer::new();
-            let view_id = buffer.create_view();
-            buffer.set_name(cmd.clone());
-            buffer.read_only = read_only;
-            Some(self.insert_buffer(buffer, view_id, true).0)
-        } else {
-            None
-        };
-
+    /// Runs `cmd`, when piped its output is shown in a new output buffer
+    pub fn run_shell_command(&mut self, cmd: String, pipe: bool) {
+        if !self.workspace.trusted {
+            self.palette.set_error(tr!("workspace.safe_mode_error"));
+            return;
+        }
+        let buffer_id = if pipe {
+            let mut buffer = Buffer::new();
+            let view_id = buffer.create_view();
+            buffer.set_name(cmd.clone());
+            buffer.read_only = true;
+            let buffer_id = self.insert_buffer(buffer, view_id, true).0;
+            self.set_output(buffer_id, OutputSource::Shell(cmd.clone()));
+            Some(buffer_id)
+        } else {
+            None
+        };
+        self.spawn_shell_job(cmd, pipe, buffer_id);
+    }
+
+    /// Renders the ansi escape sequences of a buffer and makes it read-only until the raw text
+    /// is shown again. Returns false if the buffer has no escape sequences.
+    pub fn render_ansi(&mut self, buffer_id: BufferId) -> bool {
+        let buffer = &mut self.workspace.buffers[buffer_id];
+        let raw = buffer.rope().clone();
+        if !raw.chunks().any(|chunk| chunk.contains('\x1b')) {
+            return false;
+        }
+        let (text, spans) = ansi::parse(&raw.to_string());
+        buffer.set_text(&text);
+        self.ansi_renders.insert(
+            buffer_id,
+            AnsiRender {
+                raw,
+                spans,
+                read_only: buffer.read_only,
+            },
+        );
+        buffer.read_only = true;
+        true
+    }
+
+    fn show_raw_ansi(&mut self, buffer_id: BufferId) {
+        let Some(render) = self.ansi_renders.remove(buffer_id) else {
+            return;
+        };
+        let buffer = &mut self.workspace.buffers[buffer_id];
+        buffer.set_rope(render.raw);
+        buffer.read_only = render.read_only;
+    }
+
+    /// Makes `buffer_id` an output buffer of `source`, the follow setting is kept when rerunning
+    fn set_output(&mut self, buffer_id: BufferId, source: OutputSource) {
+        let follow = self
+            .outputs
+            .get(buffer_id)
+            .is_none_or(|output| output.follow);
+        self.outputs.insert(buffer_id, Output::new(source, follow));
+    }
+
+    /// Runs the command, task or tool of the current output buffer again
+    fn rerun_output(&mut self) {
+        let Some((buffer_id, _)) = self.get_current_buffer_id() else {
+            return;
+        };
+        let Some(output) = self.outputs.get(buffer_id) else {
+            self.palette.set_error(tr!("output.not_output"));
+            return;
+        };
+        if self
+            .shell_jobs
+            .iter()
+            .any(|(job_buffer_id, _)| *job_buffer_id == Some(buffer_id))
+        {
+            self.palette.set_error(tr!("output.still_running"));
+            return;
+        }
+        match output.source.clone() {
+            OutputSource::Shell(cmd) => {
+                if !self.workspace.trusted {
+                    self.palette.set_error(tr!("workspace.safe_mode_error"));
+                    return;
+                }
+                self.workspace.buffers[buffer_id].set_text("");
+                self.set_output(buffer_id, OutputSource::Shell(cmd.clone()));
+                self.spawn_shell_job(cmd, true, Some(buffer_id));
+            }
+            OutputSource::Task(name, task) => self.run_task(name, task),
+            OutputSource::Tool(name, tool_buffer_id, view_id) => {
+                self.run_tool_with(&name, tool_buffer_id, view_id)
+            }
+        }
+    }
+
+    /// Runs `cmd` in the background, when piped the output is streamed into `buffer_id`
+    fn spawn_shell_job(&mut self, cmd: String, pipe: bool, buffer_id: Option<BufferId>) {
         let job = self.job_manager.spawn_foreground_job(
-            move |killed, progressor, ()| -> Result<_, anyhow::Error> {
+            JobInfo::new(tr!("job.shell", cmd = cmd)).cancellable(),
+            move |token, progressor, ()| -> Result<_, anyhow::Error> {
                 let mut command = get_exec(&cmd);
                 command.stdout(Stdio::piped());
                 command.stderr(Stdio::piped());
@@ -1667,6 +4788,9 @@ impl Engine {
                     unsafe { std::mem::transmute::<_, _>(progressor) };
                 let reader_thread = std::thread::spawn(move || {
                     let mut rope = Rope::new();
+                    let mut parser = AnsiParser::default();
+                    let mut spans = Vec::new();
+                    let mut new_spans = Vec::new();
                     let mut buffer = Vec::new();
                     let mut bytes = [0u8; 4096];
                     let mut dirty = false;
@@ -1682,8 +4806,9 @@ impl Engine {
                             while let Some(idx) = memchr::memchr(b'\n', slice) {
                                 let len = idx + 1;
                                 let line = String::from_utf8_lossy(&slice[..len]);
-                                let rope_line = Rope::from_str(&line);
-                                rope.append(rope_line);
+                                let (line, line_spans) = parser.parse(&line, rope.len_bytes());
+                                new_spans.extend(line_spans);
+                                rope.append(Rope::from_str(&line));
                                 slice = &slice[len..];
                                 total += len;
                                 dirty = true;
@@ -1691,16 +4816,22 @@ impl Engine {
                             buffer.drain(..total);
                         }
                         if let (Some(buffer_id), true) = (buffer_id, dirty) {
-                            progressor.make_progress((buffer_id, rope.clone()));
+                            spans.extend(new_spans.iter().cloned());
+                            progressor.make_progress((
+                                buffer_id,
+                                rope.clone(),
+                                mem::take(&mut new_spans),
+                            ));
                             dirty = false;
                         }
                     }
-                    rope
+                    spans.extend(new_spans);
+                    (rope, spans)
                 });
                 let status = loop {
                     match child.try_wait() {
                         Ok(None) => {
-                            if killed.load(Ordering::Relaxed) {
+                            if token.is_cancelled() {
                                 #[cfg(not(target_os = "linux"))]
                                 if let Err(err) = child.kill() {
                                     tracing::error!("Error killing child: {err}");
@@ -1725,17 +4856,699 @@ impl Engine {
                     }
                 };
 
-                let rope = reader_thread.join().unwrap();
+                let (rope, spans) = reader_thread.join().unwrap();
 
                 if !status.success() && !pipe {
                     return Err(anyhow::Error::msg(rope.to_string()));
                 }
 
-                Ok((buffer_id, rope))
+                Ok((buffer_id, rope, spans))
+            },
+            (),
+        );
+        self.shell_jobs.push((buffer_id, job));
+    }
+
+    /// Names completed by the `run` command, workspace actions followed by tools
+    fn action_names(&self) -> Vec<String> {
+        self.workspace
+            .config
+            .actions
+            .keys()
+            .chain(self.config.editor.tools.keys())
+            .cloned()
+            .chain(self.tasks().into_keys())
+            .collect()
+    }
+
+    /// Tasks from the editor config and the workspace, workspace tasks are only loaded in trusted workspaces
+    pub fn tasks(&self) -> IndexMap<String, Task> {
+        let mut tasks = self.config.editor.tasks.clone();
+        if let Some(workspace_tasks) = self
+            .config
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.tasks.as_ref())
+        {
+            tasks.extend(workspace_tasks.clone());
+        }
+        tasks
+    }
+
+    /// Runs a task in the task pane, its output is parsed for problems when it finishes
+    pub fn run_task(&mut self, name: String, task: Task) {
+        if !self.workspace.trusted {
+            self.palette.set_error(tr!("workspace.safe_mode_error"));
+            return;
+        }
+        if let Some((running, ..)) = &self.running_task {
+            self.palette
+                .set_error(tr!("task.already_running", name = running));
+            return;
+        }
+        let matcher = match tasks::problem_matcher(&task) {
+            Ok(matcher) => matcher,
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
+            }
+        };
+        let (buffer_id, view_id) = self.output_panel(self.task_panel, name.clone());
+        self.workspace.buffers[buffer_id].set_text("");
+        self.set_output(buffer_id, OutputSource::Task(name.clone(), task.clone()));
+        self.task_panel = Some((buffer_id, view_id));
+        self.running_task = Some((name, buffer_id, matcher));
+        self.spawn_shell_job(task.cmd, true, Some(buffer_id));
+    }
+
+    fn finish_task(&mut self, output: &str) {
+        let Some((name, _, matcher)) = self.running_task.take() else {
+            return;
+        };
+        let root = std::env::current_dir().unwrap_or_default();
+        let problems = tasks::match_problems(output, &matcher, &root);
+        let severity = if problems.is_empty() {
+            Severity::Info
+        } else {
+            Severity::Warning
+        };
+        self.notify(
+            severity,
+            tr!("task.finished", name = name, count = problems.len()),
+        );
+        self.set_locations(name, problems);
+    }
+
+    /// Replaces the contents of the location list
+    pub fn set_locations(&mut self, title: String, locations: Vec<Location>) {
+        let open_buffers = self.get_open_buffer_ropes();
+        self.location_list.set(title, locations, open_buffers);
+    }
+
+    /// Shows the location list in a pane below the current one
+    pub fn open_location_list(&mut self) {
+        if self.workspace.panes.contains(PaneKind::LocationList) {
+            self.workspace.panes.make_current(PaneKind::LocationList);
+        } else {
+            self.workspace
+                .panes
+                .split(PaneKind::LocationList, Direction::Down);
+        }
+    }
+
+    pub fn open_notifications(&mut self) {
+        self.notifications.scroll = 0;
+        self.notifications.dismiss_toast();
+        if self.workspace.panes.contains(PaneKind::Notifications) {
+            self.workspace.panes.make_current(PaneKind::Notifications);
+        } else {
+            self.workspace
+                .panes
+                .split(PaneKind::Notifications, Direction::Down);
+        }
+    }
+
+    /// Reports the result of a background job as a toast that is kept in the notification history.
+    /// Headless engines have nowhere to show toasts so the result is left in the palette instead.
+    fn notify(&mut self, severity: Severity, msg: impl Display) {
+        self.invalidate();
+        let msg = msg.to_string();
+        if self.headless {
+            match severity {
+                Severity::Error => self.palette.set_error(&msg),
+                Severity::Info | Severity::Warning => self.palette.set_msg(&msg),
+            }
+        }
+        self.notifications.push(severity, msg);
+    }
+
+    /// Makes the first buffer pane current, a new one is split above the current pane if there is none
+    fn focus_buffer_pane(&mut self) {
+        match self
+            .workspace
+            .panes
+            .get_panes()
+            .into_iter()
+            .find(|pane| matches!(pane, PaneKind::Buffer(..)))
+        {
+            Some(pane) => self.workspace.panes.make_current(pane),
+            None => {
+                let (buffer_id, view_id) = self.get_next_buffer();
+                self.workspace
+                    .panes
+                    .split(PaneKind::Buffer(buffer_id, view_id), Direction::Up);
+            }
+        }
+    }
+
+    /// Shows the working tree status in a pane below the current one
+    pub fn open_git_status(&mut self) {
+        if self.workspace.panes.contains(PaneKind::Git) {
+            self.workspace.panes.make_current(PaneKind::Git);
+        } else {
+            self.workspace.panes.split(PaneKind::Git, Direction::Down);
+        }
+        self.git_status.refresh(&mut self.job_manager);
+    }
+
+    /// Opens the commit message buffer, or commits with its text if it is the current buffer
+    pub fn git_commit(&mut self) {
+        let commit_buffer = self
+            .git_status
+            .commit_buffer
+            .filter(|buffer_id| self.workspace.buffers.contains_key(*buffer_id));
+        if let Some(buffer_id) = commit_buffer {
+            if self.get_current_buffer_id().map(|(id, _)| id) == Some(buffer_id) {
+                let text = self.workspace.buffers[buffer_id].rope().to_string();
+                let message = git::status::commit_message(&text);
+                if message.is_empty() {
+                    self.palette.set_error(tr!("git.empty_message"));
+                    return;
+                }
+                if self.git_status.root.is_none() {
+                    self.git_status.refresh(&mut self.job_manager);
+                    self.palette.set_error(tr!("git.not_loaded"));
+                    return;
+                }
+                self.git_status
+                    .run_action(GitAction::Commit(message), &mut self.job_manager);
+                return;
+            }
+        }
+
+        let buffer_id = match commit_buffer {
+            Some(buffer_id) => buffer_id,
+            None => {
+                let mut buffer = Buffer::with_text(&tr!("git.commit_template"));
+                buffer.set_name(String::from("COMMIT_EDITMSG"));
+                buffer.set_proxy(self.proxy.dup());
+                let buffer_id = self.workspace.buffers.insert(buffer);
+                self.git_status.commit_buffer = Some(buffer_id);
+                buffer_id
+            }
+        };
+        if self.workspace.panes.get_current_pane() == PaneKind::Git {
+            self.focus_buffer_pane();
+        }
+        let view_id = self.workspace.buffers[buffer_id].create_view();
+        let old = self
+            .workspace
+            .panes
+            .replace_current(PaneKind::Buffer(buffer_id, view_id));
+        if let PaneKind::Buffer(old_id, old_view_id) = old {
+            if let Some(buffer) = self.workspace.buffers.get_mut(old_id) {
+                buffer.remove_view(old_view_id);
+            }
+        }
+        if self.git_status.root.is_none() {
+            self.git_status.refresh(&mut self.job_manager);
+        }
+    }
+
+    fn finish_git_action(&mut self, action: GitAction, result: Result<String>) {
+        let output = match result {
+            Ok(output) => output,
+            Err(err) => {
+                self.notify(Severity::Error, err);
+                return;
+            }
+        };
+        self.branch_watcher.force_reload();
+        if let GitAction::Switch(branch) = &action {
+            self.notify(Severity::Info, tr!("git.switched", branch = branch));
+        }
+        if let GitAction::Commit(_) = action {
+            let summary = output.lines().next().unwrap_or_default().trim();
+            self.notify(Severity::Info, summary);
+            let Some(buffer_id) = self.git_status.commit_buffer.take() else {
+                return;
+            };
+            if self.get_current_buffer_id().map(|(id, _)| id) == Some(buffer_id) {
+                self.force_close_current_buffer();
+            } else if !self.workspace.panes.contains_buffer(buffer_id) {
+                self.workspace.buffers.remove(buffer_id);
+            }
+        }
+    }
+
+    fn open_git_picker(&mut self, git_picker: GitPicker, items: boxcar::Vec<GitItem>) {
+        self.palette.reset();
+        self.close_pickers();
+        let previewer = GitPreviewer::new(git_picker.clone(), self.proxy.dup());
+        self.git_picker = Some((
+            git_picker,
+            Picker::new(
+                GitProvider(Arc::new(items)),
+                Some(Box::new(previewer)),
+                self.proxy.dup(),
+                None,
+            ),
+        ));
+    }
+
+    /// Lists the local branches, the chosen one is checked out with `git switch`
+    pub fn open_branch_picker(&mut self) {
+        let dir = match env::current_dir() {
+            Ok(dir) => dir,
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
+            }
+        };
+        self.git_picker_job = Some(self.job_manager.spawn_foreground_job(
+            JobInfo::new(tr!("job.git")),
+            |_, _, dir: PathBuf| {
+                let root = git::log::repo_root(&dir)?;
+                let branches = git::log::list_branches(&root)?;
+                Ok(GitPickerOutput::Items(
+                    GitPicker::Branches { root },
+                    branches.into_iter().map(GitItem::Branch).collect(),
+                ))
+            },
+            dir,
+        ));
+    }
+
+    /// Lists the commits that changed the current file, the chosen revision is opened read only
+    pub fn open_git_log_picker(&mut self) {
+        let Some((buffer, _)) = self.get_current_buffer() else {
+            return;
+        };
+        let Some(file) = buffer.file() else {
+            self.palette.set_error(tr!("buffer.no_path"));
+            return;
+        };
+        let file = file.to_path_buf();
+        self.git_picker_job = Some(self.job_manager.spawn_foreground_job(
+            JobInfo::new(tr!("job.git")),
+            |_, _, file: PathBuf| {
+                let (root, path) = git::repo_path(&file)?;
+                let commits = git::log::file_log(&root, &path)?;
+                Ok(GitPickerOutput::Items(
+                    GitPicker::Log { root, path },
+                    commits.into_iter().map(GitItem::Commit).collect(),
+                ))
+            },
+            file,
+        ));
+    }
+
+    fn handle_git_picker_choice(&mut self, git_picker: GitPicker, item: GitItem) {
+        match (git_picker, item) {
+            (GitPicker::Branches { .. }, GitItem::Branch(branch)) if branch.current => (),
+            (GitPicker::Branches { root }, GitItem::Branch(branch)) => {
+                self.git_status.run_action_in(
+                    root,
+                    GitAction::Switch(branch.name),
+                    &mut self.job_manager,
+                );
+            }
+            (GitPicker::Log { root, path }, GitItem::Commit(commit)) => {
+                self.git_picker_job = Some(self.job_manager.spawn_foreground_job(
+                    JobInfo::new(tr!("job.git")),
+                    |_, _, (root, path, hash): (PathBuf, String, String)| {
+                        let text = git::log::show_file(&root, &hash, &path)?;
+                        Ok(GitPickerOutput::Revision { path, hash, text })
+                    },
+                    (root, path, commit.hash),
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    /// Opens `path` as it was at the revision `hash` in a read only buffer
+    fn open_revision(&mut self, path: &str, hash: &str, text: &str) {
+        let name = Path::new(path)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let mut buffer = Buffer::new();
+        buffer.set_name(format!("{name}@{hash}"));
+        buffer.set_text(text);
+        if let Some(language) = get_language_from_path(path) {
+            if let Err(err) = buffer.set_langauge(language) {
+                tracing::error!("Error setting language: {err}");
+            }
+        }
+        buffer.read_only = true;
+        let view_id = buffer.create_view();
+        self.insert_buffer(buffer, view_id, true);
+    }
+
+    /// Lists the bookmarks of every file in the workspace, open buffers first
+    pub fn open_bookmark_picker(&mut self) {
+        let cwd = env::current_dir().unwrap_or_default();
+        let name = |path: &Path| {
+            path.strip_prefix(&cwd)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+        let mut items = Vec::new();
+        let mut open = Vec::new();
+        for buffer in self.workspace.buffers.values() {
+            let Some(path) = buffer.file() else {
+                continue;
+            };
+            open.push(path.to_path_buf());
+            for line_idx in buffer.bookmarks() {
+                items.push(BookmarkItem::new(
+                    path.to_path_buf(),
+                    &name(path),
+                    line_idx,
+                    buffer.rope(),
+                ));
+            }
+        }
+        for buffer_data in &self.workspace.buffer_extra_data {
+            if buffer_data.bookmarks.is_empty() || open.contains(&buffer_data.path) {
+                continue;
+            }
+            let Ok(text) = fs::read_to_string(&buffer_data.path) else {
+                continue;
+            };
+            let rope = Rope::from_str(&text);
+            for line_idx in &buffer_data.bookmarks {
+                items.push(BookmarkItem::new(
+                    buffer_data.path.clone(),
+                    &name(&buffer_data.path),
+                    *line_idx,
+                    &rope,
+                ));
+            }
+        }
+
+        if items.is_empty() {
+            self.palette.set_msg(tr!("buffer.no_workspace_bookmarks"));
+            return;
+        }
+
+        self.palette.reset();
+        self.close_pickers();
+        let previewer = BookmarkPreviewer::new(self.proxy.dup(), self.get_open_buffer_ropes());
+        self.bookmark_picker = Some(Picker::new(
+            BookmarkProvider(Arc::new(items.into_iter().collect())),
+            Some(Box::new(previewer)),
+            self.proxy.dup(),
+            None,
+        ));
+    }
+
+    /// Lists the symbols of the current buffer
+    pub fn open_outline_picker(&mut self) {
+        let Some((buffer, _)) = self.get_current_buffer() else {
+            return;
+        };
+        let outline = buffer.outline();
+        if outline.is_empty() {
+            let msg = tr!("buffer.no_outline", name = buffer.name());
+            self.palette.set_msg(msg);
+            return;
+        }
+        let previewer = OutlinePreviewer::new(buffer.clone());
+        let items = outline.iter().cloned().map(OutlineItem::new).collect();
+
+        self.palette.reset();
+        self.close_pickers();
+        self.outline_picker = Some(Picker::new(
+            OutlineProvider(Arc::new(items)),
+            Some(Box::new(previewer)),
+            self.proxy.dup(),
+            None,
+        ));
+    }
+
+    /// Replaces the merge conflict under the cursor of the current buffer with the chosen side
+    pub fn resolve_conflict(&mut self, resolution: Resolution) {
+        let Some((buffer, view_id)) = self.get_current_buffer_mut() else {
+            return;
+        };
+        if buffer.read_only {
+            return;
+        }
+        if !buffer.resolve_conflict(view_id, resolution) {
+            self.palette.set_msg(tr!("buffer.no_conflict_under_cursor"));
+        }
+    }
+
+    /// Finds the hunk under or around the cursor of the current buffer in the background
+    pub fn hunk_action(&mut self, action: HunkAction) {
+        let Some((buffer_id, view_id)) = self.get_current_buffer_id() else {
+            return;
+        };
+        let buffer = &self.workspace.buffers[buffer_id];
+        let Some(file) = buffer.file() else {
+            self.palette.set_error(tr!("buffer.no_path"));
+            return;
+        };
+        let input = HunkInput {
+            file: file.to_path_buf(),
+            text: buffer.rope().to_string(),
+            line_idx: buffer.cursor_line_idx(view_id, 0),
+            action,
+        };
+        let job = self
+            .job_manager
+            .spawn_foreground_job(JobInfo::default(), hunks::hunk_job, input);
+        self.git_hunk_job = Some((buffer_id, view_id, job));
+    }
+
+    fn finish_hunk_job(
+        &mut self,
+        buffer_id: BufferId,
+        view_id: ViewId,
+        result: Result<Option<HunkOutput>>,
+    ) {
+        let output = match result {
+            Ok(Some(output)) => output,
+            Ok(None) => {
+                self.palette.set_msg(tr!("git.no_hunk"));
+                return;
+            }
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
+            }
+        };
+        let Some(buffer) = self.workspace.buffers.get_mut(buffer_id) else {
+            return;
+        };
+        if !buffer.views.contains_key(view_id) {
+            return;
+        }
+        match output {
+            HunkOutput::Goto(line_idx) => buffer.jump_to(view_id, Point::new(0, line_idx)),
+            HunkOutput::Revert(hunk) => {
+                let prompt = format!(
+                    "{}{}",
+                    hunk.preview(),
+                    tr!(
+                        "git.revert_hunk",
+                        new = hunk.new_lines.len(),
+                        old = hunk.old_lines.len()
+                    )
+                );
+                self.palette.set_prompt(
+                    prompt,
+                    (
+                        'y',
+                        PalettePromptEvent::RevertHunk(buffer_id, view_id, hunk),
+                    ),
+                    ('n', PalettePromptEvent::Nop),
+                );
+            }
+            HunkOutput::Staged(line_idx) => {
+                self.palette
+                    .set_msg(tr!("git.staged_hunk", line = line_idx + 1));
+                self.branch_watcher.force_reload();
+                if self.workspace.panes.contains(PaneKind::Git) {
+                    self.git_status.refresh(&mut self.job_manager);
+                }
+            }
+        }
+    }
+
+    /// Replaces the lines of a hunk with the content from the index, if the buffer has not changed since the preview
+    fn revert_hunk(&mut self, buffer_id: BufferId, view_id: ViewId, hunk: &hunks::BufferHunk) {
+        let Some(buffer) = self.workspace.buffers.get_mut(buffer_id) else {
+            return;
+        };
+        if !buffer.views.contains_key(view_id) {
+            return;
+        }
+        let rope = buffer.rope();
+        let start = rope.line_to_byte(hunk.new_lines.start.min(rope.len_lines()));
+        let end = rope.line_to_byte(hunk.new_lines.end.min(rope.len_lines()));
+        if rope.byte_slice(start..end) != hunk.new_text.as_str() {
+            self.palette.set_error(tr!("git.hunk_changed"));
+            return;
+        }
+        buffer.replace(view_id, start..end, &hunk.old_text);
+        buffer.mark_dirty();
+    }
+
+    /// Jumps to the next or previous entry in the location list
+    pub fn goto_location(&mut self, next: bool) {
+        let len = self.location_list.entries.len();
+        let location = if next {
+            self.location_list.select_next()
+        } else {
+            self.location_list.select_prev()
+        };
+        let Some(location) = location.cloned() else {
+            self.palette.set_msg(tr!("misc.no_locations"));
+            return;
+        };
+        let idx = self.location_list.index.unwrap_or(0);
+        if self.open_location(&location) {
+            self.palette.set_msg(tr!(
+                "misc.location",
+                index = idx + 1,
+                total = len,
+                message = location.message
+            ));
+        }
+    }
+
+    /// Opens a location in a buffer pane, the location list pane itself is never replaced
+    fn open_location(&mut self, location: &Location) -> bool {
+        if self.workspace.panes.get_current_pane() == PaneKind::LocationList {
+            self.focus_buffer_pane();
+        }
+        self.open_file_at(
+            &location.path,
+            location.line.saturating_sub(1),
+            location.column.saturating_sub(1),
+        )
+    }
+
+    /// Opens a file with the cursor at a zero based line and column
+    pub fn open_file_at(&mut self, path: impl AsRef<Path>, line: usize, column: usize) -> bool {
+        if !self.open_file(path) {
+            return false;
+        }
+        if let Some((buffer, view_id)) = self.get_current_buffer_mut() {
+            buffer.jump_to(view_id, Point::new(column, line));
+        }
+        true
+    }
+
+    /// Reuses `panel` if it is still open, otherwise splits a new read-only pane below the current one
+    fn output_panel(
+        &mut self,
+        panel: Option<(BufferId, ViewId)>,
+        name: String,
+    ) -> (BufferId, ViewId) {
+        if let Some((buffer_id, view_id)) = panel.filter(|(buffer_id, view_id)| {
+            self.workspace
+                .panes
+                .contains(PaneKind::Buffer(*buffer_id, *view_id))
+        }) {
+            self.workspace.buffers[buffer_id].set_name(name);
+            return (buffer_id, view_id);
+        }
+        let mut buffer = Buffer::new();
+        buffer.set_name(name);
+        buffer.read_only = true;
+        buffer.set_proxy(self.proxy.dup());
+        let view_id = buffer.create_view();
+        let buffer_id = self.workspace.buffers.insert(buffer);
+        self.workspace
+            .panes
+            .split(PaneKind::Buffer(buffer_id, view_id), Direction::Down);
+        (buffer_id, view_id)
+    }
+
+    /// Runs a tool from the config in the background with the current buffer as its context
+    pub fn run_tool(&mut self, name: &str) {
+        let Some((buffer_id, view_id)) = self.get_current_buffer_id() else {
+            return;
+        };
+        self.run_tool_with(name, buffer_id, view_id);
+    }
+
+    fn run_tool_with(&mut self, name: &str, buffer_id: BufferId, view_id: ViewId) {
+        if !self.workspace.trusted {
+            self.palette.set_error(tr!("workspace.safe_mode_error"));
+            return;
+        }
+        let Some(tool) = self.config.editor.tools.get(name) else {
+            self.palette
+                .set_error(tr!("config.action_not_found", name = name));
+            return;
+        };
+        let Some(buffer) = self
+            .workspace
+            .buffers
+            .get(buffer_id)
+            .filter(|buffer| buffer.views.contains_key(view_id))
+        else {
+            self.palette.set_error(tr!("output.tool_buffer_closed"));
+            return;
+        };
+        let cmd = tools::expand_command(
+            &tool.cmd,
+            buffer.file(),
+            &buffer.get_selection(view_id, 0),
+            buffer.cursor_line_idx(view_id, 0) + 1,
+        );
+        let job = self.job_manager.spawn_foreground_job(
+            JobInfo::new(tr!("job.tool", name = name)),
+            tools::run_tool_job,
+            ToolInput {
+                name: name.to_string(),
+                cmd,
+                output: tool.output,
+                buffer_id,
+                view_id,
+                last_edit: buffer.get_last_edit(),
             },
-            (),
         );
-        self.shell_jobs.push((buffer_id, job));
+        self.tool_jobs.push(job);
+    }
+
+    fn show_tool_output(&mut self, result: ToolResult) {
+        match result.output {
+            ToolOutput::ReplaceSelection | ToolOutput::InsertAtCursor => {
+                let Some(buffer) = self.workspace.buffers.get_mut(result.buffer_id) else {
+                    return;
+                };
+                if !buffer.views.contains_key(result.view_id) || buffer.read_only {
+                    return;
+                }
+                if buffer.get_last_edit() != result.last_edit {
+                    self.palette
+                        .set_error(tr!("buffer.tool_stale", name = buffer.name()));
+                    return;
+                }
+                if result.output == ToolOutput::InsertAtCursor {
+                    for cursor in buffer.views[result.view_id].cursors.iter_mut() {
+                        cursor.anchor = cursor.position;
+                    }
+                }
+                buffer.insert_text(result.view_id, &result.text, false);
+            }
+            ToolOutput::NewBuffer => {
+                let mut buffer = Buffer::with_text(&result.text);
+                buffer.set_name(result.name);
+                let view_id = buffer.create_view();
+                self.insert_buffer(buffer, view_id, true);
+            }
+            ToolOutput::Panel => {
+                let (buffer_id, view_id) = self.output_panel(self.tool_panel, result.name.clone());
+                let (text, spans) = ansi::parse(&result.text);
+                let buffer = &mut self.workspace.buffers[buffer_id];
+                buffer.set_text(&text);
+                buffer.goto(view_id, 0);
+                self.set_output(
+                    buffer_id,
+                    OutputSource::Tool(result.name, result.buffer_id, result.view_id),
+                );
+                self.outputs[buffer_id].spans = spans;
+                self.tool_panel = Some((buffer_id, view_id));
+            }
+        }
     }
 
     fn os_open_url(&mut self, url: impl AsRef<Path>) {
@@ -1769,17 +5582,197 @@ impl Engine {
         }
     }
 
+    /// Opens a file in a new pane next to the current one instead of replacing it
+    pub fn open_file_in_split(&mut self, path: impl AsRef<Path>, direction: Direction) -> bool {
+        if let Err(err) = dunce::canonicalize(&path) {
+            self.palette.set_error(err);
+            return false;
+        }
+        let (buffer_id, view_id) = match self.workspace.panes.get_current_pane() {
+            PaneKind::Buffer(buffer_id, _) => {
+                let view_id = self.workspace.buffers[buffer_id].create_view();
+                self.load_view_data(buffer_id, view_id);
+                (buffer_id, view_id)
+            }
+            _ => self.get_next_buffer(),
+        };
+        self.workspace
+            .panes
+            .split(PaneKind::Buffer(buffer_id, view_id), direction);
+        self.open_file(path)
+    }
+
+    /// Sets the font scale of the workspace, frontends without `font_zoom` only show a warning
+    pub fn set_scale(&mut self, scale: f32) {
+        if !self.font_zoom {
+            self.palette.set_error(tr!("misc.zoom_unsupported"));
+            return;
+        }
+        self.workspace.scale = ((scale * 10.0).round() / 10.0).clamp(MIN_SCALE, MAX_SCALE);
+        self.palette.set_msg(tr!(
+            "misc.zoom",
+            percent = (self.workspace.scale * 100.0).round() as u64
+        ));
+    }
+
+    /// Saves the current panes under `name`, panes that can't be restored are left out
+    pub fn save_layout(&mut self, name: String) {
+        let layout = Layout::from_panes(
+            &self.workspace.panes,
+            &self.workspace.buffers,
+            &self.workspace.file_explorers,
+        );
+        if layout.is_empty() {
+            self.palette.set_error(tr!("workspace.empty_layout"));
+            return;
+        }
+        self.palette
+            .set_msg(tr!("workspace.layout_saved", name = &name));
+        self.workspace.layouts.insert(name, layout);
+    }
+
+    /// Replaces the panes with a saved layout, files in it that are not open get opened
+    pub fn load_layout(&mut self, name: &str) {
+        let Some(layout) = self.workspace.layouts.get(name).cloned() else {
+            self.palette
+                .set_error(tr!("workspace.unknown_layout", name = name));
+            return;
+        };
+        for path in layout.paths() {
+            let is_open = self
+                .workspace
+                .buffers
+                .values()
+                .any(|buffer| buffer.file() == Some(&path));
+            if !is_open && path.exists() {
+                self.open_file(path);
+            }
+        }
+
+        let Some(panes) = layout.to_panes(
+            &mut self.workspace.buffers,
+            &mut self.workspace.file_explorers,
+        ) else {
+            self.palette.set_error(tr!("workspace.empty_layout"));
+            return;
+        };
+        let old = mem::replace(&mut self.workspace.panes, panes);
+        for pane in old.get_panes() {
+            match pane {
+                PaneKind::Buffer(buffer_id, view_id) => {
+                    if let Some(buffer) = self.workspace.buffers.get_mut(buffer_id) {
+                        buffer.remove_view(view_id);
+                        if buffer.is_disposable() {
+                            self.workspace.buffers.remove(buffer_id);
+                        }
+                    }
+                }
+                PaneKind::FileExplorer(file_explorer_id) => {
+                    self.workspace.file_explorers.remove(file_explorer_id);
+                }
+                PaneKind::Logger
+                | PaneKind::LocationList
+                | PaneKind::Git
+                | PaneKind::Notifications => (),
+            }
+        }
+    }
+
+    /// Moves the current buffer into the buffer pane next to it or a new split if there is none.
+    /// The pane it leaves shows the next buffer instead.
+    pub fn move_buffer_to_pane(&mut self, direction: Direction) {
+        let current = self.workspace.panes.get_current_pane();
+        if !matches!(current, PaneKind::Buffer(..)) {
+            return;
+        }
+        self.workspace
+            .panes
+            .switch_pane_direction(direction, self.buffer_area);
+        let target = self.workspace.panes.get_current_pane();
+
+        let (buffer_id, view_id) = self.get_next_buffer();
+        let next = PaneKind::Buffer(buffer_id, view_id);
+        self.workspace.panes.replace(current, next);
+        match target {
+            PaneKind::Buffer(buffer_id, view_id) if target != current => {
+                self.workspace.panes.replace(target, current);
+                self.workspace.panes.make_current(current);
+                self.workspace.buffers[buffer_id].remove_view(view_id);
+            }
+            // Panes that do not show a buffer are kept, the buffer is moved into a new split instead
+            _ => {
+                self.workspace.panes.make_current(next);
+                self.workspace.panes.split(current, direction);
+            }
+        }
+    }
+
+    /// Opens the path or url under the cursor, or in the selection if there is one.
+    /// `path:line:column` opens the file at that position.
+    pub fn goto_file_under_cursor(&mut self) {
+        let Some((buffer, view_id)) = self.get_current_buffer() else {
+            return;
+        };
+        let selection = buffer.get_selection(view_id, 0);
+        let token = if selection.trim().is_empty() {
+            let (column, line_idx) = buffer.cursor_byte_pos(view_id, 0);
+            let line = buffer.rope().line(line_idx).to_string();
+            goto_file::token_at(&line, column).map(|token| token.to_string())
+        } else {
+            Some(selection.trim().to_string())
+        };
+        let Some(token) = token else {
+            self.palette.set_error(tr!("buffer.no_file_under_cursor"));
+            return;
+        };
+        let buffer_dir = buffer
+            .file()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.to_path_buf());
+
+        match goto_file::parse_target(&token) {
+            FileTarget::Url(url) => {
+                if let Err(err) = opener::open(url) {
+                    self.palette.set_error(err);
+                }
+            }
+            FileTarget::Path { path, line, column } => {
+                let root = std::env::current_dir().unwrap_or_default();
+                let Some(resolved) = goto_file::resolve(&path, buffer_dir.as_deref(), &root) else {
+                    self.palette
+                        .set_error(tr!("buffer.file_not_found", path = path));
+                    return;
+                };
+                if resolved.is_dir() {
+                    self.open_file_explorer(Some(resolved));
+                    return;
+                }
+                match line {
+                    Some(line) => {
+                        self.open_file_at(
+                            resolved,
+                            line.saturating_sub(1),
+                            column.unwrap_or(1).saturating_sub(1),
+                        );
+                    }
+                    None => {
+                        self.open_file(resolved);
+                    }
+                }
+            }
+        }
+    }
+
     pub fn search(&mut self) {
         if let Some((buffer, view_id)) = self.get_current_buffer() {
             let selection = buffer.get_selection(view_id, 0);
-            self.file_picker = None;
-            self.buffer_picker = None;
+            self.close_pickers();
             self.palette.focus(
                 self.get_search_prompt(false),
                 "search",
                 CompleterContext::new(
                     self.themes.keys().cloned().collect(),
-                    self.workspace.config.actions.keys().cloned().collect(),
+                    self.action_names(),
                     false,
                     None,
                 ),
@@ -1793,19 +5786,20 @@ impl Engine {
         }
     }
 
-    pub fn global_search(&mut self) {
+    /// Opens the global search prompt, if `scope` is set only files matching that glob are searched
+    pub fn global_search(&mut self, scope: Option<String>) {
         let selection = self
             .get_current_buffer()
             .map(|(buffer, view_id)| buffer.get_selection(view_id, 0))
             .unwrap_or_default();
-        self.file_picker = None;
-        self.buffer_picker = None;
+        self.close_pickers();
+        self.global_search_scope = scope;
         self.palette.focus(
             self.get_search_prompt(true),
             "global-search",
             CompleterContext::new(
                 self.themes.keys().cloned().collect(),
-                self.workspace.config.actions.keys().cloned().collect(),
+                self.action_names(),
                 false,
                 None,
             ),
@@ -1818,6 +5812,347 @@ impl Engine {
         }
     }
 
+    /// Searches the workspace for `query` in the current global search scope
+    fn start_global_search(&mut self, query: String, options: SearchOptions) {
+        // A cached index that is being refreshed is complete enough to search
+        let status = self.file_scanner.status();
+        if (status.scanning && !status.from_cache) || status.cancelled {
+            let files = self.file_scanner.files().count();
+            self.notify(
+                Severity::Warning,
+                tr!("index.incomplete_search", files = files),
+            );
+        }
+        let open_buffers = self.get_open_buffer_ropes();
+        let scope = self.global_search_scope.take();
+        self.last_global_search = Some((query.clone(), scope.clone()));
+        let global_search_provider = GlobalSearchProvider::new(
+            &mut self.job_manager,
+            query,
+            self.file_scanner.status().root,
+            self.file_scanner.files(),
+            scope,
+            options,
+            open_buffers.clone(),
+        );
+        self.global_search_picker = Some(Picker::new(
+            global_search_provider,
+            Some(Box::new(GlobalSearchPreviewer::new(
+                self.proxy.dup(),
+                open_buffers,
+            ))),
+            self.proxy.dup(),
+            None,
+        ));
+    }
+
+    /// True if the current pane is a buffer that is too large to search on the main thread
+    fn current_buffer_is_large(&self) -> bool {
+        !self.palette.has_focus()
+            && !self.has_open_picker()
+            && self
+                .get_current_buffer()
+                .is_some_and(|(buffer, _)| buffer.rope().len_bytes() > ASYNC_SEARCH_BYTES)
+    }
+
+    /// Selects every match of the selection of the current buffer from a job,
+    /// the cursors are added while the matches are found
+    fn select_all_matching_job(&mut self) {
+        let Some((buffer_id, view_id)) = self.get_current_buffer_id() else {
+            return;
+        };
+        if let Some((.., job)) = self.select_matching_job.take() {
+            job.cancel();
+        }
+        let buffer = &mut self.workspace.buffers[buffer_id];
+        let term = buffer.matching_term(view_id);
+        let revision = buffer.revision();
+        self.select_matching_job = Some((
+            buffer_id,
+            view_id,
+            self.job_manager.spawn_foreground_job(
+                JobInfo::new(tr!("job.select_matching", query = &term)).cancellable(),
+                move |token, progressor, (rope, term): (Rope, String)| {
+                    stream_search(
+                        rope.slice(..),
+                        &term,
+                        SearchOptions::default(),
+                        revision,
+                        &token,
+                        progressor,
+                    )
+                },
+                (buffer.rope().clone(), term),
+            ),
+        ));
+    }
+
+    /// Adds the cursors of the matches found by the select matching job. The job is cancelled
+    /// if the buffer changes as the rest of its matches would be stale.
+    fn poll_select_matching_job(&mut self) {
+        let Some((buffer_id, view_id, job)) = &mut self.select_matching_job else {
+            return;
+        };
+        let (buffer_id, view_id) = (*buffer_id, *view_id);
+        let mut chunks = Vec::new();
+        let mut finished = false;
+        while let Ok(progress) = job.poll_progress() {
+            match progress {
+                Progress::Progress(chunk) => chunks.push(chunk),
+                Progress::End(_) => finished = true,
+            }
+        }
+        if chunks.is_empty() && !finished {
+            return;
+        }
+
+        let buffer = self
+            .workspace
+            .buffers
+            .get_mut(buffer_id)
+            .filter(|buffer| buffer.views.contains_key(view_id));
+        let Some(buffer) = buffer else {
+            job.cancel();
+            self.select_matching_job = None;
+            return;
+        };
+        for (revision, matches) in chunks {
+            if revision != buffer.revision() {
+                job.cancel();
+                finished = true;
+                break;
+            }
+            buffer.add_match_cursors(view_id, &matches);
+        }
+        if finished {
+            self.select_matching_job = None;
+        }
+        self.invalidate();
+    }
+
+    /// Counts the case variants of the selected identifier, or the one under the cursor, in the workspace
+    pub fn identifier_stats(&mut self) {
+        let Some((buffer, view_id)) = self.get_current_buffer() else {
+            return;
+        };
+        let selection = buffer.get_selection(view_id, 0);
+        let identifier = if selection.trim().is_empty() {
+            buffer.word_under_cursor(view_id)
+        } else {
+            Some(selection.trim().to_string())
+        };
+        let Some(identifier) = identifier else {
+            self.palette.set_error(tr!("refactor.no_identifier"));
+            return;
+        };
+        self.identifier_stats_job = Some(self.job_manager.spawn_foreground_job(
+            JobInfo::new(tr!("job.identifier_stats", identifier = identifier)).cancellable(),
+            identifier_stats::identifier_stats_job,
+            IdentifierStatsInput {
+                root: self.file_scanner.status().root,
+                files: self.file_scanner.files(),
+                identifier,
+            },
+        ));
+    }
+
+    fn open_identifier_stats_picker(&mut self, variants: Vec<VariantStats>) {
+        if variants.iter().all(|variant| variant.count == 0) {
+            let identifier = variants
+                .first()
+                .map(|variant| variant.text.as_str())
+                .unwrap_or_default();
+            self.palette.set_msg(tr!(
+                "refactor.identifier_not_found",
+                identifier = identifier
+            ));
+            return;
+        }
+
+        self.palette.reset();
+        self.close_pickers();
+        self.identifier_stats_picker = Some(Picker::new(
+            IdentifierStatsProvider(Arc::new(variants.into_iter().collect())),
+            Some(Box::new(IdentifierStatsPreviewer::default())),
+            self.proxy.dup(),
+            None,
+        ));
+    }
+
+    /// Replaces every match of the last global search in the workspace as a single refactor.
+    /// The edits are staged in the background and written once every file has been scanned.
+    pub fn global_replace(&mut self, replacement: String) {
+        let Some((query, scope)) = self.last_global_search.clone() else {
+            self.palette.set_error(tr!("refactor.no_global_search"));
+            return;
+        };
+        if self.refactor_job.is_some() {
+            self.palette.set_error(tr!("refactor.in_progress"));
+            return;
+        }
+        self.refactor_job = Some(self.job_manager.spawn_foreground_job(
+            JobInfo::new(tr!("job.refactor", query = query)).cancellable(),
+            refactor::stage_replace_job,
+            ReplaceInput {
+                root: self.file_scanner.status().root,
+                files: self.file_scanner.files(),
+                scope,
+                query,
+                replacement,
+                options: self.search_options(),
+            },
+        ));
+    }
+
+    /// Returns true if the transaction was written to disk
+    fn apply_refactor(&mut self, transaction: Transaction) -> bool {
+        if transaction.is_empty() {
+            self.palette.set_msg(tr!("refactor.no_matches"));
+            return false;
+        }
+        if let Some(name) = self.find_dirty_buffer(transaction.paths()) {
+            self.palette.set_error(tr!("refactor.dirty", name = name));
+            return false;
+        }
+        let (matches, files) = (transaction.match_count(), transaction.file_count());
+        match transaction.apply() {
+            Ok(applied) => {
+                self.reload_buffers(applied.paths());
+                self.last_refactor = Some(applied);
+                self.palette
+                    .set_msg(tr!("refactor.applied", matches = matches, files = files));
+                true
+            }
+            Err(err) => {
+                self.palette.set_error(err);
+                false
+            }
+        }
+    }
+
+    /// Opens the matches of a global search in a buffer where every line can be edited.
+    /// Saving the buffer writes the edited lines back to their files.
+    fn open_search_edit(&mut self, search_edit: SearchEdit) {
+        if search_edit.is_empty() {
+            self.palette.set_msg(tr!("search_edit.no_results"));
+            return;
+        }
+        let mut buffer = Buffer::new();
+        buffer.set_name("search-results".into());
+        buffer.set_text(&search_edit.text());
+        let view_id = buffer.create_view();
+        let (buffer_id, _) = self.insert_buffer(buffer, view_id, true);
+        self.search_edits.insert(buffer_id, search_edit);
+        self.palette.set_msg(tr!("search_edit.opened"));
+    }
+
+    /// Validates the edited search results and writes the changed lines to their files
+    fn apply_search_edit(&mut self, buffer_id: BufferId) {
+        let Some(search_edit) = self.search_edits.get(buffer_id) else {
+            return;
+        };
+        let rope = self.workspace.buffers[buffer_id].rope().clone();
+        let transaction = match search_edit.stage(&rope) {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                self.palette.set_error(err);
+                return;
+            }
+        };
+        if transaction.is_empty() {
+            self.workspace.buffers[buffer_id].mark_saved();
+            self.palette.set_msg(tr!("search_edit.unchanged"));
+            return;
+        }
+        if self.apply_refactor(transaction) {
+            if let Some(search_edit) = self.search_edits.get_mut(buffer_id) {
+                let _ = search_edit.commit(&rope);
+            }
+            self.workspace.buffers[buffer_id].mark_saved();
+        }
+    }
+
+    /// Shows the changes saving the search results buffer would make without writing them
+    fn search_edit_diff(&mut self) {
+        let PaneKind::Buffer(buffer_id, _) = self.workspace.panes.get_current_pane() else {
+            return;
+        };
+        let Some(search_edit) = self.search_edits.get(buffer_id) else {
+            self.palette.set_error(tr!("search_edit.not_results"));
+            return;
+        };
+        match search_edit.stage(self.workspace.buffers[buffer_id].rope()) {
+            Ok(transaction) if transaction.is_empty() => {
+                self.palette.set_msg(tr!("search_edit.unchanged"))
+            }
+            Ok(transaction) => {
+                self.open_diff_split("search-results dry run".into(), &transaction.diff())
+            }
+            Err(err) => self.palette.set_error(err),
+        }
+    }
+
+    /// Reverts every file changed by the last refactor
+    pub fn undo_last_refactor(&mut self) {
+        let Some(refactor) = self.last_refactor.take() else {
+            self.palette.set_error(tr!("refactor.nothing_to_undo"));
+            return;
+        };
+        if let Some(name) = self.find_dirty_buffer(refactor.paths()) {
+            self.palette.set_error(tr!("refactor.dirty", name = name));
+            self.last_refactor = Some(refactor);
+            return;
+        }
+        match refactor.undo() {
+            Ok(()) => {
+                self.reload_buffers(refactor.paths());
+                self.palette
+                    .set_msg(tr!("refactor.undone", files = refactor.file_count()));
+            }
+            Err(err) => {
+                self.palette.set_error(err);
+                self.last_refactor = Some(refactor);
+            }
+        }
+    }
+
+    /// Returns the name of an open buffer with unsaved changes to one of `paths`
+    fn find_dirty_buffer<'a>(&self, paths: impl Iterator<Item = &'a Path>) -> Option<String> {
+        let paths: HashSet<_> = paths
+            .filter_map(|path| dunce::canonicalize(path).ok())
+            .collect();
+        self.workspace
+            .buffers
+            .values()
+            .filter(|buffer| buffer.is_dirty())
+            .find(|buffer| {
+                buffer
+                    .file()
+                    .and_then(|file| dunce::canonicalize(file).ok())
+                    .is_some_and(|file| paths.contains(&file))
+            })
+            .map(|buffer| buffer.name().to_string())
+    }
+
+    fn reload_buffers<'a>(&mut self, paths: impl Iterator<Item = &'a Path>) {
+        let paths: HashSet<_> = paths
+            .filter_map(|path| dunce::canonicalize(path).ok())
+            .collect();
+        for buffer in self.workspace.buffers.values_mut() {
+            let Some(file) = buffer
+                .file()
+                .and_then(|file| dunce::canonicalize(file).ok())
+            else {
+                continue;
+            };
+            if paths.contains(&file) {
+                if let Err(err) = buffer.reload() {
+                    self.palette.set_error(err);
+                }
+            }
+        }
+    }
+
     pub fn start_replace(&mut self) {
         let PaneKind::Buffer(buffer_id, view_id) = self.workspace.panes.get_current_pane() else {
             return;
@@ -1829,7 +6164,7 @@ impl Engine {
                 "replace",
                 CompleterContext::new(
                     self.themes.keys().cloned().collect(),
-                    self.workspace.config.actions.keys().cloned().collect(),
+                    self.action_names(),
                     false,
                     None,
                 ),
@@ -1842,7 +6177,53 @@ impl Engine {
     }
 }
 
-fn get_exec(cmd: &str) -> Command {
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+/// Picker keys that open the selected option in a split, ctrl+enter splits right and ctrl+shift+enter down
+fn picker_split_direction(input: &Cmd) -> Option<Direction> {
+    match input {
+        Cmd::NewLineWithoutBreaking => Some(Direction::Right),
+        Cmd::NewLineAboveWithoutBreaking => Some(Direction::Down),
+        _ => None,
+    }
+}
+
+/// Replaces the detected indentation of a buffer that was just opened with the one set in the config
+fn apply_indent_override(config: &Editor, buffer: &mut Buffer) {
+    if let Some(indent) = config.indent.as_deref() {
+        match Indentation::parse(indent) {
+            Some(indent) => buffer.indent = indent,
+            None => tracing::error!("Invalid indentation in config: `{indent}`"),
+        }
+    }
+}
+
+/// Returns true if `path` matches one of the `read_only_files` globs of the workspace
+fn is_read_only_file(globs: &[String], path: &Path) -> bool {
+    if globs.is_empty() {
+        return false;
+    }
+    let root = env::current_dir().unwrap_or(PathBuf::from("."));
+    build_overrides(
+        &root,
+        &FilesConfig {
+            include: globs.to_vec(),
+            exclude: Vec::new(),
+        },
+    )
+    .matched(path, false)
+    .is_whitelist()
+}
+
+pub(crate) fn get_exec(cmd: &str) -> Command {
     #[cfg(unix)]
     pub const SHELL: [&str; 2] = ["sh", "-c"];
 
@@ -1871,11 +6252,31 @@ fn get_exec(cmd: &str) -> Command {
 
 impl Drop for Engine {
     fn drop(&mut self) {
-        if let Err(e) = self.workspace.save_workspace() {
-            tracing::error!("Error saving workspace: {e}");
-        };
+        self.save_scratch_buffers();
+        if let Some(job) = self.recovery_job.take() {
+            // Wait for the job so it does not write the recovery file after it has been removed
+            let _ = job.recv_progress();
+        }
+        if std::thread::panicking() {
+            // The recovery job may have replaced the file written by the panic hook
+            if let Err(err) = recovery::emergency_dump() {
+                tracing::error!("Error writing recovery file: {err}");
+            }
+        } else {
+            if self.terminated {
+                if let Err(err) = recovery::write_terminated_session(&self.recovered_buffers()) {
+                    tracing::error!("Error writing recovery file: {err}");
+                }
+            }
+            recovery::remove_session();
+        }
+        if !self.headless {
+            if let Err(e) = self.workspace.save_workspace() {
+                tracing::error!("Error saving workspace: {e}");
+            };
+        }
         for job in &mut self.shell_jobs {
-            job.1.kill();
+            job.1.cancel();
         }
     }
 }
@@ -15,6 +15,18 @@ pub trait EventLoopProxy: Send + Sync {
     fn dup(&self) -> Box<dyn EventLoopProxy>;
 }
 
+/// A proxy for when there is no event loop to wake, such as buffers used outside of the editor
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopProxy;
+
+impl EventLoopProxy for NoopProxy {
+    fn send(&self, _: UserEvent) {}
+    fn request_render(&self) {}
+    fn dup(&self) -> Box<dyn EventLoopProxy> {
+        Box::new(NoopProxy)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EventLoopControlFlow {
     Poll,
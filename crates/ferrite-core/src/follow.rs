@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+
+use crate::{buffer::ViewId, workspace::BufferId};
+
+/// How long the cursor has to stay on a symbol before the follow pane shows it
+pub const FOLLOW_DELAY: Duration = Duration::from_millis(300);
+
+/// A read-only pane that shows the definition of the symbol under the cursor in the current pane
+pub struct FollowPane {
+    pub buffer_id: BufferId,
+    pub view_id: ViewId,
+    shown: Option<String>,
+    /// Symbol under the cursor and when the cursor moved onto it
+    pending: Option<(String, Instant)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FollowUpdate {
+    /// Nothing to do until the cursor moves
+    Idle,
+    /// The cursor has not stayed on the symbol long enough yet
+    Wait(Duration),
+    Show(String),
+}
+
+impl FollowPane {
+    pub fn new(buffer_id: BufferId, view_id: ViewId) -> Self {
+        Self {
+            buffer_id,
+            view_id,
+            shown: None,
+            pending: None,
+        }
+    }
+
+    /// The symbol whose definition the pane shows
+    pub fn shown(&self) -> Option<&str> {
+        self.shown.as_deref()
+    }
+
+    /// Called with the symbol under the cursor every time the engine polls
+    pub fn update(&mut self, symbol: Option<String>, now: Instant) -> FollowUpdate {
+        let Some(symbol) = symbol.filter(|symbol| self.shown.as_ref() != Some(symbol)) else {
+            self.pending = None;
+            return FollowUpdate::Idle;
+        };
+        let since = match &self.pending {
+            Some((pending, since)) if *pending == symbol => *since,
+            _ => {
+                self.pending = Some((symbol, now));
+                return FollowUpdate::Wait(FOLLOW_DELAY);
+            }
+        };
+        let elapsed = now.duration_since(since);
+        if elapsed < FOLLOW_DELAY {
+            return FollowUpdate::Wait(FOLLOW_DELAY - elapsed);
+        }
+        self.pending = None;
+        self.shown = Some(symbol.clone());
+        FollowUpdate::Show(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use slotmap::KeyData;
+
+    use super::*;
+
+    #[test]
+    fn debounce() {
+        let mut follow = FollowPane::new(
+            BufferId::from(KeyData::from_ffi(0)),
+            ViewId::from(KeyData::from_ffi(0)),
+        );
+        let start = Instant::now();
+        let symbol = || Some(String::from("main"));
+        assert_eq!(
+            follow.update(symbol(), start),
+            FollowUpdate::Wait(FOLLOW_DELAY)
+        );
+        assert_eq!(
+            follow.update(symbol(), start + FOLLOW_DELAY / 2),
+            FollowUpdate::Wait(FOLLOW_DELAY / 2)
+        );
+        assert_eq!(
+            follow.update(symbol(), start + FOLLOW_DELAY),
+            FollowUpdate::Show("main".into())
+        );
+        assert_eq!(
+            follow.update(symbol(), start + FOLLOW_DELAY * 2),
+            FollowUpdate::Idle
+        );
+    }
+}
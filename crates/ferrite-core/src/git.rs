@@ -1 +1,54 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::Result;
+
 pub mod branch;
+pub mod hunks;
+pub mod log;
+pub mod status;
+
+/// Runs git in `dir` and returns its stdout, the error contains stderr if git fails
+pub fn run(dir: &Path, args: &[&str], input: Option<&str>) -> Result<String> {
+    let mut child = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {}: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Finds the root of the repository containing `file` and the path of the file relative to it
+pub fn repo_path(file: &Path) -> Result<(PathBuf, String)> {
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let output = run(
+        dir,
+        &["rev-parse", "--show-toplevel", "--show-prefix"],
+        None,
+    )?;
+    let mut lines = output.lines();
+    let root = PathBuf::from(lines.next().unwrap_or_default());
+    let prefix = lines.next().unwrap_or_default();
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    Ok((root, format!("{prefix}{name}")))
+}
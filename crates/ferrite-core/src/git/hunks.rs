@@ -0,0 +1,213 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use similar::{DiffTag, TextDiff};
+
+use crate::job_manager::{CancellationToken, Progressor};
+
+/// Max amount of lines of the old content shown before a hunk is reverted
+const MAX_PREVIEW_LINES: usize = 10;
+
+/// A changed range of a buffer compared to the version of its file in the git index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferHunk {
+    /// Lines of the index version that were replaced
+    pub old_lines: Range<usize>,
+    /// Lines of the buffer that replace them
+    pub new_lines: Range<usize>,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+impl BufferHunk {
+    /// True if the hunk covers `line_idx`, deletions cover the line after them
+    pub fn contains(&self, line_idx: usize) -> bool {
+        self.new_lines.contains(&line_idx)
+            || (self.new_lines.is_empty() && self.new_lines.start == line_idx)
+    }
+
+    /// A patch with no context that changes the index version of `path` to the buffer version of this hunk
+    pub fn patch(&self, path: &str) -> String {
+        let mut patch = format!(
+            "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -{} +{} @@\n",
+            hunk_range(&self.old_lines),
+            hunk_range(&self.new_lines)
+        );
+        push_patch_lines(&mut patch, '-', &self.old_text);
+        push_patch_lines(&mut patch, '+', &self.new_text);
+        patch
+    }
+
+    /// The first lines of the old content, shown in the revert prompt
+    pub fn preview(&self) -> String {
+        let mut preview = String::new();
+        for line in self.old_text.lines().take(MAX_PREVIEW_LINES) {
+            preview.push_str(line);
+            preview.push('\n');
+        }
+        let count = self.old_lines.len();
+        if count > MAX_PREVIEW_LINES {
+            preview.push_str(&format!("... {} more\n", count - MAX_PREVIEW_LINES));
+        }
+        preview
+    }
+}
+
+/// Formats a line range as a unified diff range, an empty range starts after the line before it
+fn hunk_range(lines: &Range<usize>) -> String {
+    if lines.is_empty() {
+        format!("{},0", lines.start)
+    } else {
+        format!("{},{}", lines.start + 1, lines.len())
+    }
+}
+
+fn push_patch_lines(patch: &mut String, prefix: char, text: &str) {
+    for line in text.split_inclusive('\n') {
+        patch.push(prefix);
+        patch.push_str(line);
+        if !line.ends_with('\n') {
+            patch.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
+
+/// Diffs the index version of a file against the text of its buffer
+pub fn buffer_hunks(old: &str, new: &str) -> Vec<BufferHunk> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut hunks: Vec<BufferHunk> = Vec::new();
+    for op in diff.ops() {
+        if op.tag() == DiffTag::Equal {
+            continue;
+        }
+        let (old_lines, new_lines) = (op.old_range(), op.new_range());
+        match hunks.last_mut() {
+            Some(last)
+                if last.old_lines.end == old_lines.start
+                    && last.new_lines.end == new_lines.start =>
+            {
+                last.old_lines.end = old_lines.end;
+                last.new_lines.end = new_lines.end;
+            }
+            _ => hunks.push(BufferHunk {
+                old_lines,
+                new_lines,
+                old_text: String::new(),
+                new_text: String::new(),
+            }),
+        }
+    }
+
+    for hunk in &mut hunks {
+        hunk.old_text = diff.old_slices()[hunk.old_lines.clone()].concat();
+        hunk.new_text = diff.new_slices()[hunk.new_lines.clone()].concat();
+    }
+    hunks
+}
+
+/// A file as it is in the git index
+pub struct IndexFile {
+    pub root: PathBuf,
+    /// Relative to the root of the repository
+    pub path: String,
+    pub text: String,
+}
+
+pub fn load_index_file(file: &Path) -> Result<IndexFile> {
+    let (root, path) = super::repo_path(file)?;
+    let text = super::run(&root, &["show", &format!(":{path}")], None)?;
+    Ok(IndexFile { root, path, text })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkAction {
+    Next,
+    Prev,
+    Revert,
+    Stage,
+}
+
+pub struct HunkInput {
+    pub file: PathBuf,
+    pub text: String,
+    pub line_idx: usize,
+    pub action: HunkAction,
+}
+
+pub enum HunkOutput {
+    /// Line of the hunk to move the cursor to
+    Goto(usize),
+    /// The hunk to revert once the preview has been confirmed
+    Revert(BufferHunk),
+    /// Line of the hunk that was added to the index
+    Staged(usize),
+}
+
+/// Finds the hunk the action applies to, returns `None` if there is no such hunk
+pub fn hunk_job(
+    _: CancellationToken,
+    _: &mut Progressor<()>,
+    input: HunkInput,
+) -> Result<Option<HunkOutput>> {
+    let index_file = load_index_file(&input.file)?;
+    let hunks = buffer_hunks(&index_file.text, &input.text);
+    let line_idx = input.line_idx;
+    let output = match input.action {
+        HunkAction::Next => hunks
+            .iter()
+            .find(|hunk| hunk.new_lines.start > line_idx)
+            .or(hunks.first())
+            .map(|hunk| HunkOutput::Goto(hunk.new_lines.start)),
+        HunkAction::Prev => hunks
+            .iter()
+            .rev()
+            .find(|hunk| hunk.new_lines.start < line_idx)
+            .or(hunks.last())
+            .map(|hunk| HunkOutput::Goto(hunk.new_lines.start)),
+        HunkAction::Revert => hunks
+            .into_iter()
+            .find(|hunk| hunk.contains(line_idx))
+            .map(HunkOutput::Revert),
+        HunkAction::Stage => match hunks.iter().find(|hunk| hunk.contains(line_idx)) {
+            Some(hunk) => {
+                super::run(
+                    &index_file.root,
+                    &["apply", "--cached", "--unidiff-zero", "-"],
+                    Some(&hunk.patch(&index_file.path)),
+                )?;
+                Some(HunkOutput::Staged(hunk.new_lines.start))
+            }
+            None => None,
+        },
+    };
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hunks_and_patch() {
+        let old = "a\nb\nc\nd\n";
+        let new = "a\nB\nc\nd\ne";
+        let hunks = buffer_hunks(old, new);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].old_lines, 1..2);
+        assert_eq!(hunks[0].new_lines, 1..2);
+        assert_eq!(hunks[0].old_text, "b\n");
+        assert!(hunks[0].contains(1));
+        assert_eq!(
+            hunks[1].patch("x.txt"),
+            "diff --git a/x.txt b/x.txt\n--- a/x.txt\n+++ b/x.txt\n@@ -4,0 +5,1 @@\n+e\n\\ No newline at end of file\n"
+        );
+
+        let hunks = buffer_hunks(old, "a\nd\n");
+        assert_eq!(hunks[0].new_lines, 1..1);
+        assert!(hunks[0].contains(1));
+        assert_eq!(hunks[0].preview(), "b\nc\n");
+    }
+}
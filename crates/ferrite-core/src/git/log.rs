@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+/// Max amount of commits listed in the log picker
+const MAX_COMMITS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Branch {
+    pub name: String,
+    /// True if the branch is checked out
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: String,
+    pub summary: String,
+    pub author: String,
+    /// Relative date like `3 days ago`
+    pub date: String,
+}
+
+/// Finds the root of the repository containing `dir`
+pub fn repo_root(dir: &Path) -> Result<PathBuf> {
+    let root = super::run(dir, &["rev-parse", "--show-toplevel"], None)?;
+    Ok(PathBuf::from(root.trim()))
+}
+
+/// Lists the local branches, the current branch first
+pub fn list_branches(root: &Path) -> Result<Vec<Branch>> {
+    let output = super::run(
+        root,
+        &["branch", "--format=%(HEAD)%00%(refname:short)"],
+        None,
+    )?;
+    Ok(parse_branches(&output))
+}
+
+fn parse_branches(output: &str) -> Vec<Branch> {
+    let mut branches: Vec<_> = output
+        .lines()
+        .filter_map(|line| {
+            let (head, name) = line.split_once('\0')?;
+            Some(Branch {
+                name: name.to_string(),
+                current: head == "*",
+            })
+        })
+        .collect();
+    branches.sort_by_key(|branch| !branch.current);
+    branches
+}
+
+/// Lists the commits that changed `path`, newest first
+pub fn file_log(root: &Path, path: &str) -> Result<Vec<Commit>> {
+    let output = super::run(
+        root,
+        &[
+            "log",
+            &format!("--max-count={MAX_COMMITS}"),
+            "--format=%h%x00%s%x00%an%x00%ar",
+            "--",
+            path,
+        ],
+        None,
+    )?;
+    Ok(parse_log(&output))
+}
+
+fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                summary: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The contents of `path` at the revision `hash`
+pub fn show_file(root: &Path, hash: &str, path: &str) -> Result<String> {
+    super::run(root, &["show", &format!("{hash}:{path}")], None)
+}
+
+/// The changes a commit made to `path`
+pub fn show_commit(root: &Path, hash: &str, path: &str) -> Result<String> {
+    super::run(root, &["show", hash, "--", path], None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branches_and_log() {
+        let branches = parse_branches(" \0feature\n*\0main\n");
+        assert_eq!(branches[0].name, "main");
+        assert!(branches[0].current);
+        assert!(!branches[1].current);
+
+        let commits = parse_log("abc123\0Fix bug\0Jane\02 days ago\n");
+        assert_eq!(
+            commits,
+            vec![Commit {
+                hash: "abc123".into(),
+                summary: "Fix bug".into(),
+                author: "Jane".into(),
+                date: "2 days ago".into(),
+            }]
+        );
+    }
+}
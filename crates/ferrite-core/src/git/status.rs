@@ -0,0 +1,389 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use ferrite_utility::line_ending::LineEnding;
+
+use crate::{
+    cmd::Cmd,
+    job_manager::{JobHandle, JobInfo, JobManager},
+    tr,
+    workspace::BufferId,
+};
+
+/// A changed file from `git status --porcelain`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStatus {
+    /// Relative to the root of the repository
+    pub path: String,
+    /// Status in the index, `?` for untracked files
+    pub index: char,
+    /// Status in the working tree
+    pub worktree: char,
+}
+
+impl FileStatus {
+    pub fn is_staged(&self) -> bool {
+        !matches!(self.index, ' ' | '?' | '!')
+    }
+
+    pub fn is_unstaged(&self) -> bool {
+        self.worktree != ' '
+    }
+}
+
+/// Parses the output of `git status --porcelain=v1 -z`
+pub fn parse_status(output: &str) -> Vec<FileStatus> {
+    let mut entries = Vec::new();
+    let mut fields = output.split('\0');
+    while let Some(field) = fields.next() {
+        let mut chars = field.chars();
+        let (Some(index), Some(worktree)) = (chars.next(), chars.next()) else {
+            continue;
+        };
+        let Some(path) = field.get(3..) else {
+            continue;
+        };
+        // Renames and copies are followed by the original path
+        if matches!(index, 'R' | 'C') {
+            fields.next();
+        }
+        entries.push(FileStatus {
+            path: path.to_string(),
+            index,
+            worktree,
+        });
+    }
+    entries
+}
+
+/// A hunk from the diff of a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// True if the hunk is from the diff of the index against `HEAD`
+    pub staged: bool,
+    /// The `diff --git`, `---` and `+++` lines of the file
+    pub file_header: String,
+    /// The `@@` line and the lines after it, a `\r` at the end of a line is part of the
+    /// content of a file with CRLF line endings and kept so the patch still applies
+    pub lines: Vec<String>,
+}
+
+impl Hunk {
+    /// A patch that applies only this hunk
+    pub fn patch(&self) -> String {
+        let mut patch = self.file_header.clone();
+        for line in &self.lines {
+            patch.push_str(line);
+            patch.push('\n');
+        }
+        patch
+    }
+}
+
+/// Splits the diff of a single file into its hunks
+pub fn parse_hunks(diff: &str, staged: bool) -> Vec<Hunk> {
+    let mut file_header = String::new();
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        if line.starts_with("@@") {
+            hunks.push(Hunk {
+                staged,
+                file_header: file_header.clone(),
+                lines: vec![line.to_string()],
+            });
+        } else if let Some(hunk) = hunks.last_mut() {
+            hunk.lines.push(line.to_string());
+        } else {
+            file_header.push_str(line);
+            file_header.push('\n');
+        }
+    }
+    hunks
+}
+
+/// What a git action changes, run as a job from the git pane
+#[derive(Debug, Clone)]
+pub enum GitAction {
+    Stage(String),
+    Unstage(String),
+    StageHunk(String),
+    UnstageHunk(String),
+    Commit(String),
+    Switch(String),
+}
+
+impl GitAction {
+    fn run(&self, root: &Path) -> Result<String> {
+        match self {
+            GitAction::Stage(path) => super::run(root, &["add", "--", path], None),
+            GitAction::Unstage(path) => {
+                super::run(root, &["restore", "--staged", "--", path], None)
+            }
+            GitAction::StageHunk(patch) => {
+                super::run(root, &["apply", "--cached", "-"], Some(patch))
+            }
+            GitAction::UnstageHunk(patch) => {
+                super::run(root, &["apply", "--cached", "--reverse", "-"], Some(patch))
+            }
+            GitAction::Commit(message) => super::run(root, &["commit", "-F", "-"], Some(message)),
+            GitAction::Switch(branch) => super::run(root, &["switch", branch], None),
+        }
+    }
+}
+
+/// Removes the comment lines and surrounding whitespace from the text of the commit buffer
+pub fn commit_message(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// What the git pane asks the engine to do
+pub enum GitInput {
+    Open(PathBuf),
+    Commit,
+}
+
+type StatusJob = JobHandle<Result<(PathBuf, Vec<FileStatus>)>>;
+type DiffJob = JobHandle<Result<(String, Vec<Hunk>)>>;
+type ActionJob = JobHandle<(GitAction, Result<String>)>;
+
+/// State of the git pane, which shows the working tree status and the hunks of the selected file
+#[derive(Default)]
+pub struct GitStatus {
+    /// Root of the repository, set once the status has been loaded
+    pub root: Option<PathBuf>,
+    pub entries: Vec<FileStatus>,
+    pub index: usize,
+    /// Hunks of the selected file, unstaged ones first
+    pub hunks: Vec<Hunk>,
+    /// The hunk that is staged or unstaged instead of the whole file
+    pub hunk_index: Option<usize>,
+    /// Shown instead of the entries, for example when the directory is not a repository
+    pub error: Option<String>,
+    /// Buffer the commit message is written in
+    pub commit_buffer: Option<BufferId>,
+    status_job: Option<StatusJob>,
+    diff_job: Option<DiffJob>,
+    action_job: Option<ActionJob>,
+}
+
+impl GitStatus {
+    pub fn selected(&self) -> Option<&FileStatus> {
+        self.entries.get(self.index)
+    }
+
+    /// Returns true while a stage, unstage or commit is running
+    pub fn is_busy(&self) -> bool {
+        self.action_job.is_some()
+    }
+
+    /// Reloads the status in the background
+    pub fn refresh(&mut self, job_manager: &mut JobManager) {
+        self.status_job = Some(job_manager.spawn_foreground_job(
+            JobInfo::new(tr!("job.git_status")),
+            |_, _, dir: PathBuf| {
+                let root = super::run(&dir, &["rev-parse", "--show-toplevel"], None)?;
+                let root = PathBuf::from(root.trim());
+                let status = super::run(&root, &["status", "--porcelain=v1", "-z"], None)?;
+                Ok((root, parse_status(&status)))
+            },
+            std::env::current_dir().unwrap_or_default(),
+        ));
+    }
+
+    fn load_hunks(&mut self, job_manager: &mut JobManager) {
+        self.hunks.clear();
+        self.hunk_index = None;
+        let (Some(root), Some(entry)) = (self.root.clone(), self.selected()) else {
+            return;
+        };
+        let path = entry.path.clone();
+        self.diff_job = Some(job_manager.spawn_foreground_job(
+            JobInfo::default(),
+            |_, _, (root, path): (PathBuf, String)| {
+                // The hunks are applied as patches so they must be plain git diffs
+                let unstaged = super::run(
+                    &root,
+                    &["diff", "--no-color", "--no-ext-diff", "--", &path],
+                    None,
+                )?;
+                let staged = super::run(
+                    &root,
+                    &[
+                        "diff",
+                        "--no-color",
+                        "--no-ext-diff",
+                        "--cached",
+                        "--",
+                        &path,
+                    ],
+                    None,
+                )?;
+                let mut hunks = parse_hunks(&unstaged, false);
+                hunks.extend(parse_hunks(&staged, true));
+                Ok((path, hunks))
+            },
+            (root, path),
+        ));
+    }
+
+    pub fn run_action(&mut self, action: GitAction, job_manager: &mut JobManager) {
+        let Some(root) = self.root.clone() else {
+            return;
+        };
+        self.run_action_in(root, action, job_manager);
+    }
+
+    /// Runs an action in a repository that may not have had its status loaded yet
+    pub fn run_action_in(
+        &mut self,
+        root: PathBuf,
+        action: GitAction,
+        job_manager: &mut JobManager,
+    ) {
+        self.action_job = Some(job_manager.spawn_foreground_job(
+            JobInfo::new(tr!("job.git")),
+            |_, _, (root, action): (PathBuf, GitAction)| {
+                let result = action.run(&root);
+                (action, result)
+            },
+            (root, action),
+        ));
+    }
+
+    /// Polls the background jobs, returns the result of a finished action
+    pub fn poll(&mut self, job_manager: &mut JobManager) -> Option<(GitAction, Result<String>)> {
+        if let Some(Ok(result)) = self.status_job.as_mut().map(|job| job.try_recv()) {
+            self.status_job = None;
+            match result {
+                Ok((root, entries)) => {
+                    let selected = self.selected().map(|entry| entry.path.clone());
+                    self.root = Some(root);
+                    self.entries = entries;
+                    self.error = None;
+                    self.index = selected
+                        .and_then(|path| self.entries.iter().position(|entry| entry.path == path))
+                        .unwrap_or(self.index)
+                        .min(self.entries.len().saturating_sub(1));
+                    self.load_hunks(job_manager);
+                }
+                Err(err) => {
+                    self.entries.clear();
+                    self.hunks.clear();
+                    self.error = Some(err.to_string());
+                }
+            }
+        }
+
+        if let Some(Ok(result)) = self.diff_job.as_mut().map(|job| job.try_recv()) {
+            self.diff_job = None;
+            match result {
+                Ok((path, hunks)) => {
+                    if self.selected().is_some_and(|entry| entry.path == path) {
+                        self.hunks = hunks;
+                    }
+                }
+                Err(err) => tracing::error!("Error loading diff: {err}"),
+            }
+        }
+
+        let result = self.action_job.as_mut()?.try_recv().ok()?;
+        self.action_job = None;
+        self.refresh(job_manager);
+        Some(result)
+    }
+
+    pub fn handle_input(&mut self, input: Cmd, job_manager: &mut JobManager) -> Option<GitInput> {
+        let ch = match input {
+            Cmd::MoveUp { .. } if !self.entries.is_empty() => {
+                self.index = (self.index + self.entries.len() - 1) % self.entries.len();
+                self.load_hunks(job_manager);
+                return None;
+            }
+            Cmd::MoveDown { .. } if !self.entries.is_empty() => {
+                self.index = (self.index + 1) % self.entries.len();
+                self.load_hunks(job_manager);
+                return None;
+            }
+            Cmd::Escape => {
+                self.hunk_index = None;
+                return None;
+            }
+            Cmd::Char { ch } => ch,
+            Cmd::Insert { text } if text.chars().count() == 1 => text.chars().next()?,
+            _ => return None,
+        };
+
+        if LineEnding::from_char(ch).is_some() {
+            let root = self.root.as_ref()?;
+            return Some(GitInput::Open(root.join(&self.selected()?.path)));
+        }
+
+        match ch {
+            ']' if !self.hunks.is_empty() => {
+                self.hunk_index = Some(self.hunk_index.map_or(0, |i| (i + 1) % self.hunks.len()));
+            }
+            '[' if !self.hunks.is_empty() => {
+                let len = self.hunks.len();
+                self.hunk_index = Some(self.hunk_index.map_or(len - 1, |i| (i + len - 1) % len));
+            }
+            's' => {
+                let action = match self.hunk_index.and_then(|i| self.hunks.get(i)) {
+                    Some(hunk) if !hunk.staged => GitAction::StageHunk(hunk.patch()),
+                    Some(_) => return None,
+                    None => GitAction::Stage(self.selected()?.path.clone()),
+                };
+                self.run_action(action, job_manager);
+            }
+            'u' => {
+                let action = match self.hunk_index.and_then(|i| self.hunks.get(i)) {
+                    Some(hunk) if hunk.staged => GitAction::UnstageHunk(hunk.patch()),
+                    Some(_) => return None,
+                    None => GitAction::Unstage(self.selected()?.path.clone()),
+                };
+                self.run_action(action, job_manager);
+            }
+            'c' => return Some(GitInput::Commit),
+            'r' => self.refresh(job_manager),
+            _ => (),
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_and_hunks() {
+        let status = " M src/main.rs\0R  new.rs\0old.rs\0?? notes.txt\0";
+        let entries = parse_status(status);
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_unstaged() && !entries[0].is_staged());
+        assert_eq!(entries[1].path, "new.rs");
+        assert!(entries[1].is_staged());
+        assert!(!entries[2].is_staged());
+
+        let diff = "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-a\n+b\n@@ -9 +9 @@\n-c\n+d\n";
+        let hunks = parse_hunks(diff, false);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            hunks[1].patch(),
+            "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -9 +9 @@\n-c\n+d\n"
+        );
+
+        let diff =
+            "diff --git a/a.txt b/a.txt\r\n--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-a\r\n+b\r\n";
+        let hunks = parse_hunks(diff, false);
+        assert_eq!(hunks[0].lines, ["@@ -1 +1 @@", "-a\r", "+b\r"]);
+        assert_eq!(hunks[0].patch(), diff);
+
+        assert_eq!(commit_message("Fix bug\n\n# comment\n"), "Fix bug");
+    }
+}
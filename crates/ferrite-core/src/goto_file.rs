@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use linkify::{LinkFinder, LinkKind};
+
+/// What the text under the cursor points to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileTarget {
+    Url(String),
+    /// `line` and `column` are one based
+    Path {
+        path: String,
+        line: Option<usize>,
+        column: Option<usize>,
+    },
+}
+
+fn is_path_char(ch: char) -> bool {
+    !ch.is_whitespace()
+        && !matches!(
+            ch,
+            '"' | '\'' | '`' | '(' | ')' | '[' | ']' | '{' | '}' | '<' | '>' | ',' | ';' | '|'
+        )
+}
+
+/// Returns the path-like token in `line` that contains the byte `idx`
+pub fn token_at(line: &str, idx: usize) -> Option<&str> {
+    let idx = idx.min(line.len());
+    let start = line[..idx]
+        .char_indices()
+        .rev()
+        .take_while(|(_, ch)| is_path_char(*ch))
+        .last()
+        .map_or(idx, |(i, _)| i);
+    let end = line[idx..]
+        .char_indices()
+        .find(|(_, ch)| !is_path_char(*ch))
+        .map_or(line.len(), |(i, _)| idx + i);
+    let token = line[start..end].trim_end_matches(['.', ':']);
+    (!token.is_empty()).then_some(token)
+}
+
+/// Splits `path:line:column` and `path:line` into their parts
+fn split_position(token: &str) -> (&str, Option<usize>, Option<usize>) {
+    let Some((rest, last)) = token.rsplit_once(':') else {
+        return (token, None, None);
+    };
+    let Ok(last) = last.parse() else {
+        return (token, None, None);
+    };
+    match rest.rsplit_once(':') {
+        Some((path, line)) => match line.parse() {
+            Ok(line) => (path, Some(line), Some(last)),
+            Err(_) => (rest, Some(last), None),
+        },
+        None => (rest, Some(last), None),
+    }
+}
+
+pub fn parse_target(token: &str) -> FileTarget {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Url]);
+    if let Some(link) = finder.links(token).find(|link| link.start() == 0) {
+        return FileTarget::Url(link.as_str().to_string());
+    }
+    let (path, line, column) = split_position(token);
+    FileTarget::Path {
+        path: path.to_string(),
+        line,
+        column,
+    }
+}
+
+/// Resolves a path against the directory of the buffer and then the workspace root,
+/// returns the first one that exists
+pub fn resolve(path: &str, buffer_dir: Option<&Path>, root: &Path) -> Option<PathBuf> {
+    let path = match path.strip_prefix('~') {
+        Some(rest) => directories::UserDirs::new()?
+            .home_dir()
+            .join(rest.trim_start_matches(['/', '\\'])),
+        None => PathBuf::from(path),
+    };
+    if path.is_absolute() {
+        return path.exists().then_some(path);
+    }
+    buffer_dir
+        .into_iter()
+        .chain([root])
+        .map(|dir| dir.join(&path))
+        .find(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn file_under_cursor() {
+        let line = "error at (src/main.rs:12:5).";
+        assert_eq!(token_at(line, 14), Some("src/main.rs:12:5"));
+        assert_eq!(token_at("see foo.rs.", 5), Some("foo.rs"));
+        assert_eq!(token_at("a  b", 2), None);
+
+        assert_eq!(
+            parse_target("src/main.rs:12:5"),
+            FileTarget::Path {
+                path: String::from("src/main.rs"),
+                line: Some(12),
+                column: Some(5),
+            }
+        );
+        assert_eq!(
+            parse_target("main.rs:7"),
+            FileTarget::Path {
+                path: String::from("main.rs"),
+                line: Some(7),
+                column: None,
+            }
+        );
+        assert_eq!(
+            parse_target("https://example.com/a:1"),
+            FileTarget::Url(String::from("https://example.com/a:1"))
+        );
+
+        let root = tempdir::TempDir::new("ferrite-goto-file").unwrap();
+        let dir = root.path().join("src");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("lib.rs"), "").unwrap();
+        fs::write(root.path().join("README.md"), "").unwrap();
+        assert_eq!(
+            resolve("lib.rs", Some(&dir), root.path()),
+            Some(dir.join("lib.rs"))
+        );
+        assert_eq!(
+            resolve("README.md", Some(&dir), root.path()),
+            Some(root.path().join("README.md"))
+        );
+        assert_eq!(resolve("missing.rs", Some(&dir), root.path()), None);
+    }
+}
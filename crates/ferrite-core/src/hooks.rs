@@ -0,0 +1,165 @@
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use subprocess::{Exec, Redirection};
+
+use crate::{
+    config::editor::{FilesConfig, Hook},
+    job_manager::{CancellationToken, Progressor},
+    picker::file_scanner::build_overrides,
+    workspace::BufferId,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    Open,
+    /// The buffer is saved once the hooks are done
+    Save {
+        overwrite: bool,
+    },
+}
+
+/// Returns the commands of every hook of `kind` whose glob matches `path`,
+/// globs are matched against the path relative to the workspace `root`
+pub fn matching_hooks(
+    hooks: &[Hook],
+    root: &Path,
+    path: &Path,
+    kind: HookKind,
+) -> Vec<(String, Duration)> {
+    hooks
+        .iter()
+        .filter(|hook| {
+            build_overrides(
+                root,
+                &FilesConfig {
+                    include: vec![hook.glob.clone()],
+                    exclude: Vec::new(),
+                },
+            )
+            .matched(path, false)
+            .is_whitelist()
+        })
+        .filter_map(|hook| {
+            let cmd = match kind {
+                HookKind::Open => hook.on_open.clone(),
+                HookKind::Save { .. } => hook.on_save.clone(),
+            }?;
+            Some((cmd, Duration::from_millis(hook.timeout_ms)))
+        })
+        .collect()
+}
+
+pub struct HookInput {
+    pub buffer_id: BufferId,
+    pub kind: HookKind,
+    pub text: String,
+    pub commands: Vec<(String, Duration)>,
+    /// Last edit of the buffer when the job started, the output is discarded if the buffer has changed since
+    pub last_edit: Instant,
+}
+
+pub struct HookOutput {
+    pub buffer_id: BufferId,
+    pub kind: HookKind,
+    pub text: String,
+    pub last_edit: Instant,
+}
+
+/// Pipes the text through every command in order
+pub fn run_hooks_job(
+    _: CancellationToken,
+    _: &mut Progressor<()>,
+    input: HookInput,
+) -> Result<HookOutput> {
+    let mut text = input.text;
+    for (cmd, timeout) in &input.commands {
+        text = run_hook(cmd, text, *timeout)
+            .map_err(|err| anyhow::anyhow!("Hook `{cmd}` failed: {err}"))?;
+    }
+    Ok(HookOutput {
+        buffer_id: input.buffer_id,
+        kind: input.kind,
+        text,
+        last_edit: input.last_edit,
+    })
+}
+
+fn run_hook(cmd: &str, text: String, timeout: Duration) -> Result<String> {
+    let mut parts = cmd.split_whitespace();
+    let Some(first) = parts.next() else {
+        bail!("invalid hook command");
+    };
+
+    let mut child = Exec::cmd(first)
+        .args(&parts.collect::<Vec<_>>())
+        .stdin(Redirection::Pipe)
+        .stdout(Redirection::Pipe)
+        .stderr(Redirection::Pipe)
+        .popen()?;
+
+    let mut com = child
+        .communicate_start(Some(text.into_bytes()))
+        .limit_time(timeout);
+    let (stdout, stderr) = match com.read() {
+        Ok(output) => output,
+        Err(err) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            if err.kind() == std::io::ErrorKind::TimedOut {
+                bail!("timed out after {}ms", timeout.as_millis());
+            }
+            return Err(err.error.into());
+        }
+    };
+    let exit_status = child.wait()?;
+
+    if !exit_status.success() {
+        bail!(
+            "{}",
+            String::from_utf8_lossy(&stderr.unwrap_or_default()).trim()
+        );
+    }
+    Ok(String::from_utf8(stdout.unwrap_or_default())?)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_hooks() {
+        let hook = Hook {
+            glob: String::from("*.txt"),
+            on_open: Some(String::from("sort")),
+            on_save: None,
+            timeout_ms: 3000,
+        };
+        let hooks = [hook];
+        let root = Path::new("/tmp");
+        let path = Path::new("/tmp/file.txt");
+        let commands = matching_hooks(&hooks, root, path, HookKind::Open);
+        assert_eq!(commands.len(), 1);
+        assert!(matching_hooks(&hooks, root, path, HookKind::Save { overwrite: false }).is_empty());
+        assert!(matching_hooks(&hooks, root, Path::new("/tmp/file.rs"), HookKind::Open).is_empty());
+
+        let nested = [Hook {
+            glob: String::from("src/**/*.txt"),
+            on_open: Some(String::from("sort")),
+            on_save: None,
+            timeout_ms: 3000,
+        }];
+        let path = Path::new("/tmp/src/a/file.txt");
+        assert_eq!(matching_hooks(&nested, root, path, HookKind::Open).len(), 1);
+        assert!(
+            matching_hooks(&nested, root, Path::new("/tmp/file.txt"), HookKind::Open).is_empty()
+        );
+
+        let text = run_hook(&commands[0].0, String::from("b\na\n"), commands[0].1).unwrap();
+        assert_eq!(text, "a\nb\n");
+        assert!(run_hook("sleep 5", String::new(), Duration::from_millis(50)).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs,
+    path::PathBuf,
+    sync::{OnceLock, RwLock},
+};
+
+use anyhow::Result;
+
+/// Locale used when a string is missing from the selected one
+pub const DEFAULT_LOCALE: &str = "en";
+
+const ENGLISH: &str = include_str!("../../../locales/en.toml");
+
+type Messages = HashMap<String, String>;
+
+struct Locale {
+    name: String,
+    messages: Messages,
+}
+
+static LOCALE: RwLock<Option<Locale>> = RwLock::new(None);
+
+fn english() -> &'static Messages {
+    static MESSAGES: OnceLock<Messages> = OnceLock::new();
+    MESSAGES.get_or_init(|| parse_messages(ENGLISH).unwrap())
+}
+
+/// Flattens nested tables into keys separated by `.`
+fn parse_messages(text: &str) -> Result<Messages> {
+    fn flatten(prefix: &str, table: toml::Table, messages: &mut Messages) {
+        for (key, value) in table {
+            let key = if prefix.is_empty() {
+                key
+            } else {
+                format!("{prefix}.{key}")
+            };
+            match value {
+                toml::Value::Table(table) => flatten(&key, table, messages),
+                toml::Value::String(message) => {
+                    messages.insert(key, message);
+                }
+                value => tracing::warn!("Ignoring non string message `{key}`: {value}"),
+            }
+        }
+    }
+
+    let mut messages = Messages::new();
+    flatten("", toml::from_str(text)?, &mut messages);
+    Ok(messages)
+}
+
+pub fn get_locale_dir() -> Result<PathBuf> {
+    let Some(directories) = directories::ProjectDirs::from("", "", "ferrite") else {
+        return Err(anyhow::Error::msg("Unable to find project directory"));
+    };
+    Ok(directories.config_dir().join("locales"))
+}
+
+/// Loads `locales/<locale>.toml` from the config dir. Does nothing if the locale is already loaded.
+pub fn set_locale(locale: &str) -> Result<()> {
+    let mut current = LOCALE.write().unwrap();
+    if current.as_ref().map(|current| current.name.as_str()) == Some(locale)
+        || (current.is_none() && locale == DEFAULT_LOCALE)
+    {
+        return Ok(());
+    }
+
+    if locale == DEFAULT_LOCALE {
+        *current = None;
+        return Ok(());
+    }
+
+    let path = get_locale_dir()?.join(format!("{locale}.toml"));
+    let text = fs::read_to_string(&path)
+        .map_err(|err| anyhow::anyhow!("Error loading locale `{}`: {err}", path.display()))?;
+    *current = Some(Locale {
+        name: locale.to_string(),
+        messages: parse_messages(&text)?,
+    });
+    Ok(())
+}
+
+fn format_message(message: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut output = message.to_string();
+    for (name, value) in args {
+        output = output.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    output
+}
+
+/// Looks up `key` in the selected locale falling back to english and then the key itself.
+/// Prefer the `tr!` macro over calling this directly.
+pub fn translate(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let current = LOCALE.read().unwrap();
+    let message = current
+        .as_ref()
+        .and_then(|locale| locale.messages.get(key))
+        .or_else(|| english().get(key));
+    match message {
+        Some(message) => format_message(message, args),
+        None => {
+            tracing::warn!("Missing translation for `{key}`");
+            key.to_string()
+        }
+    }
+}
+
+/// Translates a message from the locale files, arguments are given as `name = value`
+/// and replace `{name}` in the message.
+#[macro_export]
+macro_rules! tr {
+    ($key:literal) => {
+        $crate::i18n::translate($key, &[])
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate(
+            $key,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_messages() {
+        assert_eq!(tr!("picker.open_file"), "Open file");
+        assert_eq!(tr!("misc.zoom", percent = 150), "Zoom: 150%");
+        assert_eq!(tr!("missing.key"), "missing.key");
+
+        let messages = parse_messages("[a]\nb = \"c {x}\"").unwrap();
+        assert_eq!(format_message(&messages["a.b"], &[("x", &"d")]), "c d");
+    }
+}
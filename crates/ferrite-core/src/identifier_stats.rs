@@ -0,0 +1,172 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{bail, Result};
+
+use crate::{
+    buffer::case::Case,
+    job_manager::{CancellationToken, Progressor},
+    picker::file_previewer::is_text_file,
+};
+
+/// Case styles an identifier is counted in
+const CASES: [(Case, &str); 6] = [
+    (Case::Snake, "snake"),
+    (Case::Camel, "camel"),
+    (Case::Pascal, "pascal"),
+    (Case::Kebab, "kebab"),
+    (Case::ScreamingSnake, "screaming-snake"),
+    (Case::ScreamingKebab, "screaming-kebab"),
+];
+
+/// Occurrences of one spelling of an identifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantStats {
+    pub text: String,
+    /// Every case style that produces this spelling
+    pub cases: Vec<&'static str>,
+    pub count: usize,
+    /// Files relative to the workspace root and the number of occurrences in each, most first
+    pub files: Vec<(String, usize)>,
+}
+
+/// Returns every distinct spelling of `identifier` in the supported case styles
+pub fn variants(identifier: &str) -> Vec<VariantStats> {
+    let mut variants: Vec<VariantStats> = Vec::new();
+    for (case, name) in CASES {
+        let text = case.transform(identifier);
+        if text.is_empty() {
+            continue;
+        }
+        match variants.iter_mut().find(|variant| variant.text == text) {
+            Some(variant) => variant.cases.push(name),
+            None => variants.push(VariantStats {
+                text,
+                cases: vec![name],
+                count: 0,
+                files: Vec::new(),
+            }),
+        }
+    }
+    variants
+}
+
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Counts occurrences of `needle` that are not part of a longer identifier
+fn count_occurrences(haystack: &str, needle: &str) -> usize {
+    haystack
+        .match_indices(needle)
+        .filter(|(idx, _)| {
+            let before = haystack[..*idx].chars().next_back();
+            let after = haystack[idx + needle.len()..].chars().next();
+            !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char)
+        })
+        .count()
+}
+
+pub struct IdentifierStatsInput {
+    pub root: PathBuf,
+    pub files: Arc<boxcar::Vec<String>>,
+    pub identifier: String,
+}
+
+pub fn identifier_stats_job(
+    token: CancellationToken,
+    progressor: &mut Progressor<()>,
+    input: IdentifierStatsInput,
+) -> Result<Vec<VariantStats>> {
+    count_variants(
+        &token,
+        &input.root,
+        &input.files,
+        &input.identifier,
+        |done, total| progressor.report(done as u64, total as u64, ""),
+    )
+}
+
+/// Counts every case variant of `identifier` in `files`, variants are sorted by count.
+/// `files` are paths relative to `root`, `progress` is called with the amount of files searched.
+pub fn count_variants(
+    token: &CancellationToken,
+    root: &Path,
+    files: &boxcar::Vec<String>,
+    identifier: &str,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Vec<VariantStats>> {
+    let mut variants = variants(identifier);
+    let total = files.count();
+    for (i, file) in files.iter() {
+        if token.is_cancelled() {
+            bail!("Identifier stats cancelled");
+        }
+        progress(i + 1, total);
+        let path = root.join(file);
+        if !is_text_file(&path).unwrap_or(false) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for variant in &mut variants {
+            let count = count_occurrences(&content, &variant.text);
+            if count > 0 {
+                variant.count += count;
+                variant.files.push((file.clone(), count));
+            }
+        }
+    }
+    for variant in &mut variants {
+        variant
+            .files
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    }
+    variants.sort_by_key(|variant| std::cmp::Reverse(variant.count));
+    Ok(variants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_variants() {
+        let texts: Vec<_> = variants("fooBar")
+            .into_iter()
+            .map(|variant| variant.text)
+            .collect();
+        assert_eq!(
+            texts,
+            ["foo_bar", "fooBar", "FooBar", "foo-bar", "FOO_BAR", "FOO-BAR"]
+        );
+        assert_eq!(variants("foo")[0].cases, ["snake", "camel", "kebab"]);
+
+        let dir = tempdir::TempDir::new("ferrite-identifier-stats").unwrap();
+        let files = boxcar::Vec::new();
+        for (name, content) in [
+            ("a.rs", "let foo_bar = fooBar(foo_bar);"),
+            ("b.rs", "foo_barbaz FOO_BAR my_foo_bar"),
+        ] {
+            fs::write(dir.path().join(name), content).unwrap();
+            files.push(name.to_string());
+        }
+        let stats = count_variants(
+            &CancellationToken::new(),
+            dir.path(),
+            &files,
+            "foo_bar",
+            |_, _| (),
+        )
+        .unwrap();
+        assert_eq!(stats[0].text, "foo_bar");
+        assert_eq!(stats[0].count, 2);
+        assert_eq!(stats[0].files, [(String::from("a.rs"), 2)]);
+        let screaming = stats.iter().find(|v| v.text == "FOO_BAR").unwrap();
+        assert_eq!(screaming.files, [(String::from("b.rs"), 1)]);
+    }
+}
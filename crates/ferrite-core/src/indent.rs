@@ -19,6 +19,17 @@ impl Default for Indentation {
 }
 
 impl Indentation {
+    /// Parses a number of spaces or `tabs`
+    pub fn parse(indent: &str) -> Option<Indentation> {
+        if let Ok(spaces) = indent.parse::<NonZeroUsize>() {
+            Some(Indentation::Spaces(spaces))
+        } else if indent == "tabs" {
+            Some(Indentation::Tabs(NonZeroUsize::new(1).unwrap()))
+        } else {
+            None
+        }
+    }
+
     pub fn detect_indent_rope(rope: RopeSlice) -> Indentation {
         let mut buffer = String::with_capacity(10240);
         for chunk in rope.chunks() {
@@ -0,0 +1,249 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::mpsc,
+    thread,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::event_loop_proxy::EventLoopProxy;
+
+/// Sent by `ferrite --remote` to the running instance, one json object per line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// `line` and `column` are one based
+    Open {
+        path: PathBuf,
+        line: Option<usize>,
+        column: Option<usize>,
+        #[serde(default)]
+        read_only: bool,
+        /// Reply with `Closed` when the buffer is closed
+        wait: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Opened,
+    Closed,
+    Error { message: String },
+}
+
+/// A request and where to send the responses to it
+pub type Message = (Request, mpsc::Sender<Response>);
+
+#[cfg(unix)]
+mod platform {
+    use std::{
+        fs, io,
+        os::unix::{
+            fs::{DirBuilderExt, PermissionsExt},
+            net::{UnixListener, UnixStream},
+        },
+        path::{Path, PathBuf},
+    };
+
+    pub type Listener = UnixListener;
+    pub type Stream = UnixStream;
+
+    pub fn address() -> Option<PathBuf> {
+        let dirs = directories::ProjectDirs::from("", "", "ferrite")?;
+        let dir = dirs.runtime_dir().unwrap_or(dirs.data_dir());
+        Some(dir.join("remote").join("ferrite.sock"))
+    }
+
+    pub fn connect(address: &Path) -> io::Result<Stream> {
+        UnixStream::connect(address)
+    }
+
+    pub fn bind(address: &Path) -> io::Result<Listener> {
+        // Only the user can enter the directory of the socket so other users can never connect
+        // and open files in the editor, not even before the permissions of the socket are set
+        if let Some(dir) = address.parent() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)?;
+            fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+        }
+        // Nothing answered on the socket so it was left behind by an instance that crashed
+        if address.exists() {
+            fs::remove_file(address)?;
+        }
+        let listener = UnixListener::bind(address)?;
+        fs::set_permissions(address, fs::Permissions::from_mode(0o600))?;
+        Ok(listener)
+    }
+}
+
+/// Other platforms have no socket in std that only the user can connect to,
+/// so remote mode is disabled and every instance opens its own files
+#[cfg(not(unix))]
+mod platform {
+    use std::{
+        io,
+        net::{TcpListener, TcpStream},
+        path::{Path, PathBuf},
+    };
+
+    pub type Listener = TcpListener;
+    pub type Stream = TcpStream;
+
+    pub fn address() -> Option<PathBuf> {
+        None
+    }
+
+    pub fn connect(_address: &Path) -> io::Result<Stream> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn bind(_address: &Path) -> io::Result<Listener> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// Accepts requests from other instances. Only the first instance started becomes the server.
+pub struct Server {
+    address: PathBuf,
+    rx: mpsc::Receiver<Message>,
+}
+
+impl Server {
+    /// Returns `None` if another instance is already serving
+    pub fn start(proxy: Box<dyn EventLoopProxy>) -> Result<Option<Self>> {
+        let Some(address) = platform::address() else {
+            return Ok(None);
+        };
+        if platform::connect(&address).is_ok() {
+            return Ok(None);
+        }
+        if let Some(dir) = address.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let listener = platform::bind(&address)?;
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let tx = tx.clone();
+                        let proxy = proxy.dup();
+                        thread::spawn(move || {
+                            if let Err(err) = serve_client(stream, tx, proxy) {
+                                tracing::error!("Error serving remote client: {err}");
+                            }
+                        });
+                    }
+                    Err(err) => tracing::error!("Error accepting remote client: {err}"),
+                }
+            }
+        });
+
+        Ok(Some(Self { address, rx }))
+    }
+
+    pub fn try_recv(&self) -> Option<Message> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.address);
+    }
+}
+
+fn serve_client(
+    stream: platform::Stream,
+    tx: mpsc::Sender<Message>,
+    proxy: Box<dyn EventLoopProxy>,
+) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    let (response_tx, response_rx) = mpsc::channel();
+    for line in BufReader::new(stream).lines() {
+        let request: Request = serde_json::from_str(&line?)?;
+        tx.send((request, response_tx.clone()))?;
+        proxy.request_render();
+    }
+    drop(response_tx);
+
+    // The client stops sending once all requests are written and then waits for the responses.
+    // The editor drops the sender of a request once it has nothing more to say about it
+    // so the connection is closed when every request is done.
+    for response in response_rx {
+        writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+    }
+    Ok(())
+}
+
+/// Sends the requests to the running instance and waits for it to answer all of them.
+/// Returns `Ok(false)` if no instance is running.
+pub fn send(requests: &[Request]) -> Result<bool> {
+    let Some(address) = platform::address() else {
+        return Ok(false);
+    };
+    let Ok(mut stream) = platform::connect(&address) else {
+        return Ok(false);
+    };
+    for request in requests {
+        writeln!(stream, "{}", serde_json::to_string(request)?)?;
+    }
+    stream.flush()?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let mut errors = Vec::new();
+    for line in BufReader::new(stream).lines() {
+        if let Response::Error { message } = serde_json::from_str(&line?)? {
+            errors.push(message);
+        }
+    }
+    if !errors.is_empty() {
+        anyhow::bail!(errors.join("\n"));
+    }
+    Ok(true)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::net::UnixStream;
+
+    use super::*;
+    use crate::event_loop_proxy::NoopProxy;
+
+    #[test]
+    fn serve() {
+        let (mut client, server) = UnixStream::pair().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::spawn(move || serve_client(server, tx, Box::new(NoopProxy)));
+
+        let request = Request::Open {
+            path: PathBuf::from("/tmp/main.rs"),
+            line: Some(42),
+            column: None,
+            read_only: false,
+            wait: true,
+        };
+        writeln!(client, "{}", serde_json::to_string(&request).unwrap()).unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+
+        let (received, response_tx) = rx.recv().unwrap();
+        assert_eq!(received, request);
+        response_tx.send(Response::Opened).unwrap();
+        response_tx.send(Response::Closed).unwrap();
+        drop(response_tx);
+        handle.join().unwrap().unwrap();
+
+        let responses: Vec<Response> = BufReader::new(client)
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect();
+        assert_eq!(responses, [Response::Opened, Response::Closed]);
+    }
+}
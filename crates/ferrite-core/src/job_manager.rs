@@ -4,15 +4,104 @@ use std::{
         mpsc, Arc,
     },
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
 use crate::event_loop_proxy::EventLoopProxy;
 
+/// Progress reported more often than this is dropped so busy jobs do not flood the event loop
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JobId(u64);
+
+/// Shared flag that asks a job to stop, jobs check it between units of work and return early
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How far a job has come, `total` is 0 when it is not known
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobProgress {
+    pub done: u64,
+    pub total: u64,
+    pub message: String,
+}
+
+/// Describes a job in the statusline and the notification pane while it runs
+#[derive(Debug, Clone, Default)]
+pub struct JobInfo {
+    /// Jobs without a name such as periodic snapshots are not shown
+    pub name: String,
+    /// The job checks its cancellation token and stops early when it is cancelled
+    pub cancellable: bool,
+}
+
+impl JobInfo {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            cancellable: false,
+        }
+    }
+
+    pub fn cancellable(mut self) -> Self {
+        self.cancellable = true;
+        self
+    }
+}
+
+/// A running job that is shown in the statusline and the notification pane
+#[derive(Debug)]
+pub struct JobStatus {
+    pub id: JobId,
+    pub info: JobInfo,
+    pub progress: Option<JobProgress>,
+    /// When the job was started or last reported progress
+    pub updated: Instant,
+    token: CancellationToken,
+}
+
+impl JobStatus {
+    /// Short description for the statusline such as `Searching 40%`
+    pub fn summary(&self) -> String {
+        match &self.progress {
+            Some(progress) if progress.total > 0 => format!(
+                "{} {}%",
+                self.info.name,
+                (progress.done.min(progress.total) * 100) / progress.total
+            ),
+            Some(progress) if progress.done > 0 => {
+                format!("{} {}", self.info.name, progress.done)
+            }
+            _ => self.info.name.clone(),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
 pub struct JobHandle<T, P = ()> {
+    id: JobId,
     end_recv: mpsc::Receiver<T>,
     progress_recv: mpsc::Receiver<P>,
     finished: bool,
-    killed: Arc<AtomicBool>,
+    token: CancellationToken,
 }
 
 pub enum Progress<T, P> {
@@ -29,6 +118,15 @@ impl<T> JobHandle<T, ()> {
 }
 
 impl<T, P> JobHandle<T, P> {
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    /// Blocks until the job makes progress, returns an error once the job has finished
+    pub fn recv_progress(&self) -> Result<P, mpsc::RecvError> {
+        self.progress_recv.recv()
+    }
+
     pub fn poll_progress(&mut self) -> Result<Progress<T, P>, mpsc::TryRecvError> {
         if let Ok(progress) = self.progress_recv.try_recv() {
             return Ok(Progress::Progress(progress));
@@ -39,8 +137,14 @@ impl<T, P> JobHandle<T, P> {
         Ok(Progress::End(result))
     }
 
-    pub fn kill(&mut self) {
-        self.killed.store(true, Ordering::Relaxed);
+    /// Asks the job to stop, it still has to be polled until it has finished
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Token that cancels the job, can be shared with code that does not own the handle
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.token.clone()
     }
 
     pub fn is_finished(&self) -> bool {
@@ -48,82 +152,279 @@ impl<T, P> JobHandle<T, P> {
     }
 }
 
+enum ProgressSender<T> {
+    Unbounded(mpsc::Sender<T>),
+    Bounded(mpsc::SyncSender<T>),
+}
+
 pub struct Progressor<T> {
-    sender: mpsc::Sender<T>,
+    sender: ProgressSender<T>,
+    id: JobId,
+    status_sender: mpsc::Sender<(JobId, JobProgress)>,
+    proxy: Box<dyn EventLoopProxy>,
+    last_report: Option<Instant>,
 }
 
 impl<T> Progressor<T> {
+    /// If the job was spawned with a bounded progress channel this blocks while the channel is full
     pub fn make_progress(&mut self, t: T) {
-        let _ = self.sender.send(t);
+        let _ = match &self.sender {
+            ProgressSender::Unbounded(sender) => sender.send(t),
+            ProgressSender::Bounded(sender) => sender.send(t),
+        };
+    }
+
+    /// Reports how far the job has come to show it in the statusline, `total` is 0 when it is not known
+    pub fn report(&mut self, done: u64, total: u64, message: impl Into<String>) {
+        let finished = total > 0 && done >= total;
+        if !finished
+            && self
+                .last_report
+                .is_some_and(|last_report| last_report.elapsed() < REPORT_INTERVAL)
+        {
+            return;
+        }
+        self.last_report = Some(Instant::now());
+        let progress = JobProgress {
+            done,
+            total,
+            message: message.into(),
+        };
+        let _ = self.status_sender.send((self.id, progress));
+        self.proxy.request_render();
+    }
+}
+
+impl<T> Clone for Progressor<T> {
+    fn clone(&self) -> Self {
+        let sender = match &self.sender {
+            ProgressSender::Unbounded(sender) => ProgressSender::Unbounded(sender.clone()),
+            ProgressSender::Bounded(sender) => ProgressSender::Bounded(sender.clone()),
+        };
+        Self {
+            sender,
+            id: self.id,
+            status_sender: self.status_sender.clone(),
+            proxy: self.proxy.dup(),
+            last_report: None,
+        }
     }
 }
 
 pub struct JobManager {
     proxy: Box<dyn EventLoopProxy>,
-    foreground_job: Vec<JoinHandle<()>>,
+    foreground_job: Vec<(JobId, JoinHandle<()>)>,
+    /// Running jobs that have a name, oldest first
+    statuses: Vec<JobStatus>,
+    status_sender: mpsc::Sender<(JobId, JobProgress)>,
+    status_recv: mpsc::Receiver<(JobId, JobProgress)>,
+    next_id: u64,
 }
 
 impl JobManager {
     pub fn new(proxy: Box<dyn EventLoopProxy>) -> Self {
+        let (status_sender, status_recv) = mpsc::channel();
         Self {
             proxy,
             foreground_job: Vec::new(),
+            statuses: Vec::new(),
+            status_sender,
+            status_recv,
+            next_id: 0,
         }
     }
 
     pub fn poll_jobs(&mut self) {
+        while let Ok((id, progress)) = self.status_recv.try_recv() {
+            if let Some(status) = self.statuses.iter_mut().find(|status| status.id == id) {
+                status.progress = Some(progress);
+                status.updated = Instant::now();
+            }
+        }
+
         let mut removed = 0;
         for i in 0..self.foreground_job.len() {
-            if self.foreground_job[i - removed].is_finished() {
-                let _ = self.foreground_job.remove(i - removed).join();
+            if self.foreground_job[i - removed].1.is_finished() {
+                let (id, handle) = self.foreground_job.remove(i - removed);
+                let _ = handle.join();
+                self.statuses.retain(|status| status.id != id);
                 removed += 1;
             }
         }
     }
 
+    /// Running jobs that have a name, oldest first
+    pub fn jobs(&self) -> &[JobStatus] {
+        &self.statuses
+    }
+
+    /// The job that most recently started or reported progress
+    pub fn current_job(&self) -> Option<&JobStatus> {
+        self.statuses.iter().max_by_key(|status| status.updated)
+    }
+
+    /// Cancels a cancellable job, returns false if the job cannot be cancelled
+    pub fn cancel(&mut self, id: JobId) -> bool {
+        match self
+            .statuses
+            .iter()
+            .find(|status| status.id == id && status.info.cancellable)
+        {
+            Some(status) => {
+                status.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
     /// A foreground job is a job that displays a working spinner
     /// All foreground jobs are required to finish before application exit
     pub fn spawn_foreground_job<
         I: Send + 'static,
         O: Send + 'static,
         P: Send + 'static,
-        F: FnOnce(Arc<AtomicBool>, &mut Progressor<P>, I) -> O + Send + 'static,
+        F: FnOnce(CancellationToken, &mut Progressor<P>, I) -> O + Send + 'static,
     >(
         &mut self,
+        info: JobInfo,
         f: F,
         input: I,
     ) -> JobHandle<O, P> {
-        let killed = Arc::new(AtomicBool::new(false));
-        let (end_tx, end_rx) = mpsc::channel();
         let (progress_tx, progress_rx) = mpsc::channel();
+        self.spawn_job(
+            info,
+            f,
+            input,
+            ProgressSender::Unbounded(progress_tx),
+            progress_rx,
+        )
+    }
+
+    /// Same as `spawn_foreground_job` but at most `capacity` progress values are buffered.
+    /// The job is blocked on `make_progress` until the handle has received some of them.
+    pub fn spawn_bounded_job<
+        I: Send + 'static,
+        O: Send + 'static,
+        P: Send + 'static,
+        F: FnOnce(CancellationToken, &mut Progressor<P>, I) -> O + Send + 'static,
+    >(
+        &mut self,
+        info: JobInfo,
+        f: F,
+        input: I,
+        capacity: usize,
+    ) -> JobHandle<O, P> {
+        let (progress_tx, progress_rx) = mpsc::sync_channel(capacity);
+        self.spawn_job(
+            info,
+            f,
+            input,
+            ProgressSender::Bounded(progress_tx),
+            progress_rx,
+        )
+    }
+
+    fn spawn_job<
+        I: Send + 'static,
+        O: Send + 'static,
+        P: Send + 'static,
+        F: FnOnce(CancellationToken, &mut Progressor<P>, I) -> O + Send + 'static,
+    >(
+        &mut self,
+        info: JobInfo,
+        f: F,
+        input: I,
+        sender: ProgressSender<P>,
+        progress_recv: mpsc::Receiver<P>,
+    ) -> JobHandle<O, P> {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+        let token = CancellationToken::new();
+        if !info.name.is_empty() {
+            self.statuses.push(JobStatus {
+                id,
+                info,
+                progress: None,
+                updated: Instant::now(),
+                token: token.clone(),
+            });
+        }
+
+        let (end_tx, end_rx) = mpsc::channel();
         let proxy = self.proxy.dup();
-        let thread_killed = killed.clone();
+        let mut progressor = Progressor {
+            sender,
+            id,
+            status_sender: self.status_sender.clone(),
+            proxy: self.proxy.dup(),
+            last_report: None,
+        };
+        let thread_token = token.clone();
         let handle = thread::spawn(move || {
-            let output = f(
-                thread_killed,
-                &mut Progressor {
-                    sender: progress_tx,
-                },
-                input,
-            );
+            let output = f(thread_token, &mut progressor, input);
             let _ = end_tx.send(output);
             proxy.request_render();
         });
 
-        self.foreground_job.push(handle);
+        self.foreground_job.push((id, handle));
         JobHandle {
+            id,
             end_recv: end_rx,
-            progress_recv: progress_rx,
+            progress_recv,
             finished: false,
-            killed,
+            token,
         }
     }
 }
 
 impl Drop for JobManager {
     fn drop(&mut self) {
-        for handle in self.foreground_job.drain(..) {
+        for (_, handle) in self.foreground_job.drain(..) {
             let _ = handle.join();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_loop_proxy::NoopProxy;
+
+    #[test]
+    fn report_and_cancel() {
+        let mut job_manager = JobManager::new(Box::new(NoopProxy));
+        let (reported_tx, reported_rx) = mpsc::channel();
+        let job = job_manager.spawn_foreground_job(
+            JobInfo::new("Searching").cancellable(),
+            move |token: CancellationToken, progressor: &mut Progressor<()>, _| {
+                progressor.report(2, 5, "");
+                let _ = reported_tx.send(());
+                while !token.is_cancelled() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            },
+            (),
+        );
+        let _ = job_manager.spawn_foreground_job(
+            JobInfo::default(),
+            |_, _: &mut Progressor<()>, _| {},
+            (),
+        );
+        reported_rx.recv().unwrap();
+        job_manager.poll_jobs();
+
+        assert_eq!(job_manager.jobs().len(), 1);
+        let status = job_manager.current_job().unwrap();
+        assert_eq!(status.id, job.id());
+        assert_eq!(status.summary(), "Searching 40%");
+
+        assert!(job_manager.cancel(job.id()));
+        job.end_recv
+            .recv_timeout(Duration::from_secs(5))
+            .expect("job was not cancelled");
+        while !job_manager.jobs().is_empty() {
+            job_manager.poll_jobs();
+        }
+    }
+}
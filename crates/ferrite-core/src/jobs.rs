@@ -1,15 +1,64 @@
-use std::{path::PathBuf, time::Instant};
+use std::{error::Error, fmt, path::PathBuf, time::Instant};
 
+use encoding_rs::Encoding;
+use ferrite_utility::line_ending::LineEnding;
 use ropey::Rope;
 
-use crate::{job_manager::JobHandle, workspace::BufferId};
+use crate::{
+    ansi::AnsiSpan,
+    buffer::write::{DiskState, SaveOptions},
+    encryption::Encryption,
+    job_manager::JobHandle,
+    workspace::BufferId,
+};
+
+/// What a save job needs to write a buffer
+pub struct SaveBufferInput {
+    pub buffer_id: BufferId,
+    pub encryption: Option<Encryption>,
+    pub encoding: &'static Encoding,
+    pub bom: bool,
+    pub line_ending: LineEnding,
+    pub rope: Rope,
+    pub path: PathBuf,
+    pub last_edit: Instant,
+    /// State of the file when the buffer was loaded, the file is not overwritten if it has changed since
+    pub disk_state: Option<DiskState>,
+    pub options: SaveOptions,
+}
 
 pub struct SaveBufferJob {
     pub buffer_id: BufferId,
     pub path: PathBuf,
     pub last_edit: Instant,
     pub written: usize,
+    pub disk_state: Option<DiskState>,
+}
+
+/// Returned by a save job instead of overwriting a file that was changed by another program
+#[derive(Debug)]
+pub struct SaveConflict {
+    pub buffer_id: BufferId,
+    pub path: PathBuf,
+    /// The file was replaced by another file, such as one moved over it, instead of being edited
+    pub replaced: bool,
 }
 
-pub type ShellJobHandle =
-    JobHandle<Result<(Option<BufferId>, Rope), anyhow::Error>, (BufferId, Rope)>;
+impl fmt::Display for SaveConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.replaced {
+            write!(f, "`{}` has been replaced on disk", self.path.display())
+        } else {
+            write!(f, "`{}` has changed on disk", self.path.display())
+        }
+    }
+}
+
+impl Error for SaveConflict {}
+
+/// Progress is the output so far and the styles parsed since the last progress,
+/// the result contains all styles
+pub type ShellJobHandle = JobHandle<
+    Result<(Option<BufferId>, Rope, Vec<AnsiSpan>), anyhow::Error>,
+    (BufferId, Rope, Vec<AnsiSpan>),
+>;
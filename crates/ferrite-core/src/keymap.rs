@@ -8,6 +8,7 @@ use serde::{
 };
 
 use crate::{
+    buffer::case::Case,
     cmd::{Cmd, LineMoveDir},
     config::keymap::Keymapping,
     layout::panes::Direction,
@@ -287,6 +288,26 @@ pub fn get_default_mappings() -> Vec<(Key, Cmd, bool)> {
             Cmd::CaseInsensitive,
             false,
         ),
+        (
+            Key::new(KeyCode::Char('c'), KeyModifiers::ALT),
+            Cmd::SmartCase,
+            false,
+        ),
+        (
+            Key::new(KeyCode::Char('w'), KeyModifiers::ALT),
+            Cmd::WholeWord,
+            false,
+        ),
+        (
+            Key::new(KeyCode::Char('l'), KeyModifiers::ALT),
+            Cmd::LocationList,
+            false,
+        ),
+        (
+            Key::new(KeyCode::Char('e'), KeyModifiers::ALT),
+            Cmd::EditSearchResults,
+            false,
+        ),
         (
             Key::new(KeyCode::Char('p'), KeyModifiers::ALT),
             Cmd::PrevMatch,
@@ -617,6 +638,41 @@ pub fn get_default_mappings() -> Vec<(Key, Cmd, bool)> {
             },
             false,
         ),
+        (
+            Key::new(KeyCode::Char('d'), KeyModifiers::ALT),
+            Cmd::DuplicateLines,
+            false,
+        ),
+        (
+            Key::new(KeyCode::Char('j'), KeyModifiers::ALT),
+            Cmd::JoinLines,
+            false,
+        ),
+        (
+            Key::new(KeyCode::Char('a'), KeyModifiers::ALT),
+            Cmd::IncrementNumber { count: 1 },
+            false,
+        ),
+        (
+            Key::new(KeyCode::Char('x'), KeyModifiers::ALT),
+            Cmd::DecrementNumber { count: 1 },
+            false,
+        ),
+        (
+            Key::new(KeyCode::Char(' '), KeyModifiers::CONTROL),
+            Cmd::Complete,
+            false,
+        ),
+        (
+            Key::new(KeyCode::Char('u'), KeyModifiers::ALT),
+            Cmd::Case { case: Case::Upper },
+            false,
+        ),
+        (
+            Key::new(KeyCode::Char('u'), KeyModifiers::ALT | KeyModifiers::SHIFT),
+            Cmd::Case { case: Case::Lower },
+            false,
+        ),
         (
             Key::new(KeyCode::Char('+'), KeyModifiers::ALT),
             Cmd::GrowPane,
@@ -670,6 +726,11 @@ pub fn get_default_mappings() -> Vec<(Key, Cmd, bool)> {
             Cmd::ZoomOut,
             false,
         ),
+        (
+            Key::new(KeyCode::F5, KeyModifiers::SHIFT),
+            Cmd::Rerun,
+            false,
+        ),
         (
             Key::new(KeyCode::F5, KeyModifiers::empty()),
             Cmd::RunAction {
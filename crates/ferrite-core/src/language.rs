@@ -4,17 +4,21 @@ use std::{
     sync::{Arc, LazyLock, OnceLock},
 };
 
-use tree_sitter::Language;
+use tree_sitter::{Language, Query};
 
 use self::syntax::HighlightConfiguration;
 
+pub mod auto_indent;
 pub mod detect;
+pub mod outline;
 pub mod syntax;
 
 #[derive(Clone)]
 pub struct TreeSitterConfig {
     pub name: String,
     pub highlight_config: Arc<HighlightConfiguration>,
+    pub indent_query: Option<Arc<Query>>,
+    pub outline_query: Option<Arc<Query>>,
 }
 
 impl TreeSitterConfig {
@@ -36,8 +40,26 @@ impl TreeSitterConfig {
                 )
                 .unwrap(),
             ),
+            indent_query: None,
+            outline_query: None,
         }
     }
+
+    pub fn with_indent_query(mut self, indent_query: &str) -> Self {
+        match Query::new(self.highlight_config.language, indent_query) {
+            Ok(query) => self.indent_query = Some(Arc::new(query)),
+            Err(err) => tracing::error!("Error loading indent query for `{}`: {err}", self.name),
+        }
+        self
+    }
+
+    pub fn with_outline_query(mut self, outline_query: &str) -> Self {
+        match Query::new(self.highlight_config.language, outline_query) {
+            Ok(query) => self.outline_query = Some(Arc::new(query)),
+            Err(err) => tracing::error!("Error loading outline query for `{}`: {err}", self.name),
+        }
+        self
+    }
 }
 
 static LANGUAGES: LazyLock<HashMap<&'static str, OnceLock<TreeSitterConfig>>> =
@@ -61,6 +83,8 @@ static LANGUAGES: LazyLock<HashMap<&'static str, OnceLock<TreeSitterConfig>>> =
         langs.insert("html", OnceLock::new());
         #[cfg(feature = "lang-md")]
         langs.insert("markdown", OnceLock::new());
+        #[cfg(feature = "lang-md")]
+        langs.insert("markdown.inline", OnceLock::new());
         #[cfg(feature = "lang-python")]
         langs.insert("python", OnceLock::new());
         #[cfg(feature = "lang-toml")]
@@ -122,7 +146,9 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/rust/highlights.scm"),
             include_str!("../../../queries/rust/injections.scm"),
             include_str!("../../../queries/rust/locals.scm"),
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/rust/indents.scm"))
+        .with_outline_query(include_str!("../../../queries/rust/outline.scm")),
         #[cfg(feature = "lang-json")]
         "json" => TreeSitterConfig::new(
             "json",
@@ -130,7 +156,8 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/json/highlights.scm"),
             "",
             "",
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/json/indents.scm")),
         #[cfg(feature = "lang-c")]
         "c" => TreeSitterConfig::new(
             "c",
@@ -138,7 +165,9 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/c/highlights.scm"),
             include_str!("../../../queries/c/injections.scm"),
             "",
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/c/indents.scm"))
+        .with_outline_query(include_str!("../../../queries/c/outline.scm")),
         #[cfg(feature = "lang-cpp")]
         "cpp" => TreeSitterConfig::new(
             "cpp",
@@ -146,7 +175,8 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/cpp/highlights.scm"),
             include_str!("../../../queries/cpp/injections.scm"),
             "",
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/cpp/indents.scm")),
         #[cfg(feature = "lang-cmake")]
         "cmake" => TreeSitterConfig::new(
             "cmake",
@@ -162,7 +192,8 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/css/highlights.scm"),
             include_str!("../../../queries/css/injections.scm"),
             "",
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/css/indents.scm")),
         #[cfg(feature = "lang-glsl")]
         "glsl" => TreeSitterConfig::new(
             "glsl",
@@ -187,6 +218,14 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/markdown/injections.scm"),
             "",
         ),
+        #[cfg(feature = "lang-md")]
+        "markdown.inline" => TreeSitterConfig::new(
+            "markdown.inline",
+            ferrite_tree_sitter::tree_sitter_md::inline_language(),
+            include_str!("../../../queries/markdown.inline/highlights.scm"),
+            "",
+            "",
+        ),
         #[cfg(feature = "lang-python")]
         "python" => TreeSitterConfig::new(
             "python",
@@ -194,7 +233,9 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/python/highlights.scm"),
             include_str!("../../../queries/python/injections.scm"),
             include_str!("../../../queries/python/locals.scm"),
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/python/indents.scm"))
+        .with_outline_query(include_str!("../../../queries/python/outline.scm")),
         #[cfg(feature = "lang-toml")]
         "toml" => TreeSitterConfig::new(
             "toml",
@@ -258,7 +299,9 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/javascript/highlights.scm"),
             include_str!("../../../queries/javascript/injections.scm"),
             include_str!("../../../queries/javascript/locals.scm"),
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/javascript/indents.scm"))
+        .with_outline_query(include_str!("../../../queries/javascript/outline.scm")),
         #[cfg(feature = "lang-ron")]
         "ron" => TreeSitterConfig::new(
             "ron",
@@ -298,7 +341,9 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/go/highlights.scm"),
             include_str!("../../../queries/go/injections.scm"),
             include_str!("../../../queries/go/locals.scm"),
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/go/indents.scm"))
+        .with_outline_query(include_str!("../../../queries/go/outline.scm")),
         #[cfg(feature = "lang-typescript")]
         "typescript" => TreeSitterConfig::new(
             "typescript",
@@ -306,7 +351,9 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/typescript/highlights.scm"),
             include_str!("../../../queries/typescript/injections.scm"),
             include_str!("../../../queries/typescript/locals.scm"),
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/typescript/indents.scm"))
+        .with_outline_query(include_str!("../../../queries/typescript/outline.scm")),
         #[cfg(feature = "lang-ini")]
         "ini" => TreeSitterConfig::new(
             "ini",
@@ -370,7 +417,8 @@ fn get_lang_config(name: &str) -> Option<TreeSitterConfig> {
             include_str!("../../../queries/lua/highlights.scm"),
             include_str!("../../../queries/lua/injections.scm"),
             "",
-        ),
+        )
+        .with_indent_query(include_str!("../../../queries/lua/indents.scm")),
         #[cfg(feature = "lang-nu")]
         "nu" => TreeSitterConfig::new(
             "nu",
@@ -417,7 +465,7 @@ pub fn get_language_from_path(path: impl AsRef<Path>) -> Option<&'static str> {
         (Suffix(".f"), "fortran"),
         (Suffix(".zig"), "zig"),
         (Suffix(".go"), "go"),
-        (Suffix(".ts"), "ts"),
+        (Suffix(".ts"), "typescript"),
         (Suffix(".proto"), "protobuf"),
         (Suffix(".lua"), "lua"),
         (Suffix(".nu"), "nu"),
@@ -512,8 +560,54 @@ pub fn get_tree_sitter_language(language: &str) -> Option<&'static TreeSitterCon
         .map(|cell| cell.get_or_init(|| get_lang_config(language).unwrap()))
 }
 
+/// Languages that are only highlighted inside of other languages
+const INJECTED_ONLY: &[&str] = &["markdown.inline"];
+
+/// Names that code blocks and injection queries use for languages
+/// besides the names of the languages and their file extensions
+const INJECTION_ALIASES: &[(&str, &str)] = &[
+    ("shell", "bash"),
+    ("console", "bash"),
+    ("golang", "go"),
+    ("csharp", "c-sharp"),
+    ("c#", "c-sharp"),
+    ("docker", "dockerfile"),
+    ("patch", "diff"),
+    ("jsx", "javascript"),
+    ("tsx", "typescript"),
+    ("nushell", "nu"),
+];
+
+/// The name of the language an injection refers to. Besides the names of the languages
+/// this accepts file extensions such as `rs`, aliases such as `shell`
+/// and code block info strings such as `rust,ignore` or `{.python}`.
+pub fn injection_language_name(name: &str) -> Option<&'static str> {
+    let name = name.trim().trim_start_matches(['{', '.']);
+    let name = name
+        .split([',', ' ', '}'])
+        .next()
+        .unwrap_or(name)
+        .to_lowercase();
+    if let Some((language, _)) = LANGUAGES.get_key_value(name.as_str()) {
+        return Some(*language);
+    }
+    if let Some((_, language)) = INJECTION_ALIASES.iter().find(|(alias, _)| *alias == name) {
+        return Some(*language);
+    }
+    get_language_from_path(format!("injection.{name}"))
+}
+
+/// The language an injection refers to, see `injection_language_name`
+pub fn get_injection_language(name: &str) -> Option<&'static TreeSitterConfig> {
+    get_tree_sitter_language(injection_language_name(name)?)
+}
+
 pub fn get_available_languages() -> Vec<&'static str> {
-    LANGUAGES.keys().copied().collect()
+    LANGUAGES
+        .keys()
+        .copied()
+        .filter(|language| !INJECTED_ONLY.contains(language))
+        .collect()
 }
 
 #[cfg(test)]
@@ -526,4 +620,16 @@ mod tests {
             assert!(get_lang_config(*k).is_some())
         }
     }
+
+    #[test]
+    fn injection_names() {
+        assert_eq!(injection_language_name("rs"), Some("rust"));
+        assert_eq!(injection_language_name(" rs,ignore "), Some("rust"));
+        assert_eq!(injection_language_name("{.py}"), Some("python"));
+        assert_eq!(injection_language_name("yml"), Some("yaml"));
+        assert_eq!(injection_language_name("ts"), Some("typescript"));
+        assert_eq!(injection_language_name("Shell"), Some("bash"));
+        assert_eq!(injection_language_name("c++"), Some("cpp"));
+        assert_eq!(injection_language_name("not-a-language"), None);
+    }
 }
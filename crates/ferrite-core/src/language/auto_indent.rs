@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+
+use ferrite_utility::graphemes::RopeGraphemeExt;
+use ropey::Rope;
+use tree_sitter::{Node, Parser, QueryCursor};
+
+use super::{syntax::RopeProvider, TreeSitterConfig};
+
+/// Files larger than this are not parsed when computing indentation
+const MAX_PARSE_SIZE: usize = 4_000_000;
+
+/// The indentation widths that a newline inserted at a byte index should get.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewlineIndent {
+    /// Width of the indentation of the new line
+    pub width: usize,
+    /// If the text after the cursor starts with a closing character it is moved
+    /// to its own line with this indentation width
+    pub closing_width: Option<usize>,
+}
+
+fn closing_char(opening: char) -> Option<char> {
+    Some(match opening {
+        '{' => '}',
+        '[' => ']',
+        '(' => ')',
+        _ => return None,
+    })
+}
+
+fn opening_char(closing: char) -> Option<char> {
+    Some(match closing {
+        '}' => '{',
+        ']' => '[',
+        ')' => '(',
+        _ => return None,
+    })
+}
+
+/// Returns true if typing `ch` at the start of a line should reindent that line
+pub fn is_dedent_char(ch: char) -> bool {
+    opening_char(ch).is_some()
+}
+
+/// Computes the indentation of a newline inserted at `byte_idx`.
+/// Uses the indent query of the language if there is one and falls back to
+/// a heuristic based on the brackets and colons surrounding the cursor.
+pub fn newline_indent(
+    config: Option<&TreeSitterConfig>,
+    rope: &Rope,
+    byte_idx: usize,
+    indent_width: usize,
+    colon_opens_block: bool,
+) -> NewlineIndent {
+    if let Some(indent) =
+        config.and_then(|config| query_indent(config, rope, byte_idx, indent_width))
+    {
+        return indent;
+    }
+    heuristic_indent(rope, byte_idx, indent_width, colon_opens_block)
+}
+
+/// Computes the indentation width a line should get when `closing` is typed
+/// as the first non whitespace character of the line containing `byte_idx`.
+pub fn dedent_width(rope: &Rope, byte_idx: usize, closing: char, indent_width: usize) -> usize {
+    let line_idx = rope.byte_to_line(byte_idx);
+    let current = rope.get_text_start_col(line_idx);
+    let Some(opening) = opening_char(closing) else {
+        return current;
+    };
+
+    let mut depth = 0usize;
+    let char_idx = rope.byte_to_char(byte_idx);
+    for (i, ch) in rope.chars_at(char_idx).reversed().enumerate() {
+        // Don't scan forever in huge files
+        if i > 100_000 {
+            break;
+        }
+
+        if ch == closing {
+            depth += 1;
+        } else if ch == opening {
+            if depth == 0 {
+                let opening_line = rope.char_to_line(char_idx - i - 1);
+                return rope.get_text_start_col(opening_line);
+            }
+            depth -= 1;
+        }
+    }
+
+    current.saturating_sub(indent_width)
+}
+
+fn heuristic_indent(
+    rope: &Rope,
+    byte_idx: usize,
+    indent_width: usize,
+    colon_opens_block: bool,
+) -> NewlineIndent {
+    let line_idx = rope.byte_to_line(byte_idx);
+    let line_start = rope.line_to_byte(line_idx);
+    let base = rope.get_text_start_col(line_idx);
+
+    let before = rope.byte_slice(line_start..byte_idx).to_string();
+    let before = before.trim_end();
+    let last = before.chars().next_back();
+
+    let opens_block = match last {
+        Some(':') => colon_opens_block,
+        Some(ch) => closing_char(ch).is_some(),
+        None => false,
+    };
+
+    if !opens_block {
+        return NewlineIndent {
+            width: base,
+            closing_width: None,
+        };
+    }
+
+    let next = rope
+        .byte_slice(byte_idx..rope.end_of_line_byte(line_idx))
+        .chars()
+        .find(|ch| !ch.is_whitespace());
+
+    let closes_block = last
+        .and_then(closing_char)
+        .is_some_and(|ch| Some(ch) == next);
+
+    NewlineIndent {
+        width: base + indent_width,
+        closing_width: closes_block.then_some(base),
+    }
+}
+
+fn query_indent(
+    config: &TreeSitterConfig,
+    rope: &Rope,
+    byte_idx: usize,
+    indent_width: usize,
+) -> Option<NewlineIndent> {
+    let query = config.indent_query.as_ref()?;
+    if rope.len_bytes() > MAX_PARSE_SIZE {
+        return None;
+    }
+
+    let indent_capture = query.capture_index_for_name("indent");
+    let outdent_capture = query.capture_index_for_name("outdent");
+
+    let mut parser = Parser::new();
+    parser.set_language(config.highlight_config.language).ok()?;
+    let tree = parser.parse_with(
+        &mut |byte, _| {
+            if byte <= rope.len_bytes() {
+                let (chunk, start_byte, _, _) = rope.chunk_at_byte(byte);
+                &chunk.as_bytes()[byte - start_byte..]
+            } else {
+                &[]
+            }
+        },
+        None,
+    )?;
+
+    let line_idx = rope.byte_to_line(byte_idx);
+    let line_end = rope.end_of_line_byte(line_idx);
+    let next_byte = rope
+        .byte_slice(byte_idx..line_end)
+        .chars()
+        .take_while(|ch| ch.is_whitespace())
+        .map(char::len_utf8)
+        .sum::<usize>()
+        + byte_idx;
+
+    let mut indent_nodes = HashSet::new();
+    let mut outdent_nodes = HashSet::new();
+    let mut cursor = QueryCursor::new();
+    cursor.set_byte_range(byte_idx.saturating_sub(1)..(next_byte + 1).min(rope.len_bytes()));
+    for (query_match, index) in
+        cursor.captures(query, tree.root_node(), RopeProvider(rope.slice(..)))
+    {
+        let capture = query_match.captures[index];
+        if Some(capture.index) == indent_capture {
+            indent_nodes.insert(capture.node.id());
+        } else if Some(capture.index) == outdent_capture {
+            outdent_nodes.insert(capture.node.id());
+        }
+    }
+
+    let contains = |node: &Node| {
+        node.start_byte() < byte_idx
+            && (node.end_byte() > byte_idx
+                || (node.end_byte() == byte_idx
+                    && node
+                        .child(node.child_count().saturating_sub(1))
+                        .is_some_and(|child| child.is_missing())))
+    };
+
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(byte_idx.saturating_sub(1), byte_idx)?;
+    let anchor = loop {
+        if indent_nodes.contains(&node.id()) && contains(&node) {
+            break node;
+        }
+        node = node.parent()?;
+    };
+
+    let base = rope.get_text_start_col(anchor.start_position().row);
+
+    let closes_anchor = next_byte < line_end
+        && tree
+            .root_node()
+            .descendant_for_byte_range(next_byte, next_byte)
+            .is_some_and(|closing| {
+                outdent_nodes.contains(&closing.id()) && closing.parent() == Some(anchor)
+            });
+
+    Some(NewlineIndent {
+        width: base + indent_width,
+        closing_width: closes_anchor.then_some(base),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heuristic() {
+        let rope = Rope::from_str("    if x {}\n    foo:");
+        assert_eq!(
+            newline_indent(None, &rope, 10, 4, false),
+            NewlineIndent {
+                width: 8,
+                closing_width: Some(4)
+            }
+        );
+        let end = rope.len_bytes();
+        assert_eq!(newline_indent(None, &rope, end, 4, false).width, 4);
+        assert_eq!(newline_indent(None, &rope, end, 4, true).width, 8);
+
+        let rope = Rope::from_str("fn a() {\n    if x {\n        ");
+        assert_eq!(dedent_width(&rope, rope.len_bytes(), '}', 4), 4);
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn rust_indent_query() {
+        let config = super::super::get_tree_sitter_language("rust").unwrap();
+        assert!(config.indent_query.is_some());
+
+        let text = "fn a() {\n    let x = foo(1,\n        2);\n}";
+        let rope = Rope::from_str(text);
+
+        let byte_idx = text.find("foo(1,").unwrap() + "foo(1,".len();
+        assert_eq!(
+            newline_indent(Some(config), &rope, byte_idx, 4, false).width,
+            8
+        );
+
+        let byte_idx = text.find(';').unwrap() + 1;
+        assert_eq!(
+            newline_indent(Some(config), &rope, byte_idx, 4, false).width,
+            4
+        );
+
+        let rope = Rope::from_str("fn a() {}");
+        assert_eq!(
+            newline_indent(Some(config), &rope, 8, 4, false),
+            NewlineIndent {
+                width: 4,
+                closing_width: Some(0)
+            }
+        );
+    }
+}
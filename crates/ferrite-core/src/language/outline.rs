@@ -0,0 +1,128 @@
+use std::ops::Range;
+
+use ropey::Rope;
+use tree_sitter::{Query, QueryCursor, Tree};
+
+use super::{syntax::RopeProvider, TreeSitterConfig};
+
+/// Files larger than this are not parsed for their outline
+const MAX_PARSE_SIZE: usize = 10_000_000;
+
+/// A named item from the outline query of a language, like a function or a module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    /// Suffix of the item capture, `function` for `@item.function`
+    pub kind: String,
+    /// Byte range of the whole item
+    pub range: Range<usize>,
+    pub line: usize,
+    /// Amount of symbols this symbol is nested in
+    pub depth: usize,
+}
+
+/// Lists the symbols captured by the outline query in `tree` in the order they appear.
+/// `tree` is the tree the highlighter parsed from `rope` so the text is not parsed again.
+/// Queries capture the item node as `@item.<kind>` and the name of the item as `@name`.
+pub fn outline(config: &TreeSitterConfig, tree: &Tree, rope: &Rope) -> Option<Vec<Symbol>> {
+    let query = config.outline_query.as_ref()?;
+    if rope.len_bytes() > MAX_PARSE_SIZE {
+        return None;
+    }
+
+    let name_capture = query.capture_index_for_name("name");
+    let mut symbols = Vec::new();
+    let mut cursor = QueryCursor::new();
+    for query_match in cursor.matches(query, tree.root_node(), RopeProvider(rope.slice(..))) {
+        let mut item = None;
+        let mut name = None;
+        for capture in query_match.captures {
+            if Some(capture.index) == name_capture {
+                name = Some(capture.node);
+            } else if let Some(kind) = item_kind(query, capture.index) {
+                item = Some((capture.node, kind));
+            }
+        }
+        let (Some((node, kind)), Some(name)) = (item, name) else {
+            continue;
+        };
+        let name = rope.byte_slice(name.byte_range()).to_string();
+        symbols.push(Symbol {
+            name: name.split_whitespace().collect::<Vec<_>>().join(" "),
+            kind: kind.to_string(),
+            range: node.byte_range(),
+            line: node.start_position().row,
+            depth: 0,
+        });
+    }
+
+    symbols.sort_by(|a, b| {
+        a.range
+            .start
+            .cmp(&b.range.start)
+            .then(b.range.end.cmp(&a.range.end))
+    });
+    symbols.dedup_by(|a, b| a.range == b.range);
+
+    let mut parents: Vec<usize> = Vec::new();
+    for symbol in &mut symbols {
+        while parents.last().is_some_and(|end| *end <= symbol.range.start) {
+            parents.pop();
+        }
+        symbol.depth = parents.len();
+        parents.push(symbol.range.end);
+    }
+    Some(symbols)
+}
+
+fn item_kind(query: &Query, index: u32) -> Option<&str> {
+    let name = query.capture_names().get(index as usize)?;
+    name.strip_prefix("item.")
+}
+
+/// The symbols containing `byte_idx`, outermost first
+pub fn symbols_at(symbols: &[Symbol], byte_idx: usize) -> Vec<Symbol> {
+    symbols
+        .iter()
+        .filter(|symbol| symbol.range.start <= byte_idx && byte_idx < symbol.range.end)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rust_outline() {
+        let config = super::super::get_tree_sitter_language("rust").unwrap();
+        let rope =
+            Rope::from_str("mod a {\n    impl Foo {\n        fn bar() {}\n    }\n}\nfn baz() {}\n");
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(config.highlight_config.language)
+            .unwrap();
+        let tree = parser.parse(rope.to_string(), None).unwrap();
+        let symbols = outline(config, &tree, &rope).unwrap();
+        let names: Vec<_> = symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.kind.as_str(), symbol.depth))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("a", "module", 0),
+                ("Foo", "impl", 1),
+                ("bar", "function", 2),
+                ("baz", "function", 0)
+            ]
+        );
+
+        let byte_idx = rope.line_to_byte(2) + 10;
+        let path: Vec<_> = symbols_at(&symbols, byte_idx)
+            .into_iter()
+            .map(|symbol| symbol.name)
+            .collect();
+        assert_eq!(path, vec!["a", "Foo", "bar"]);
+    }
+}
@@ -1,23 +1,169 @@
 use std::{
     borrow::Cow,
     fmt, iter, mem, ops,
-    sync::{Arc, Mutex, MutexGuard},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
     thread,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Result};
 use cb::Sender;
 use ropey::{Rope, RopeSlice};
 use tree_sitter::{
-    Language, Node, Parser, Point, Query, QueryCaptures, QueryCursor, QueryError, QueryMatch,
-    Range, TextProvider, Tree,
+    InputEdit, Language, Node, Parser, Point, Query, QueryCaptures, QueryCursor, QueryError,
+    QueryMatch, Range, TextProvider, Tree,
 };
 
-use super::{get_tree_sitter_language, TreeSitterConfig};
+use super::{
+    get_injection_language, get_tree_sitter_language,
+    outline::{self, Symbol},
+    TreeSitterConfig,
+};
 use crate::event_loop_proxy::EventLoopProxy;
 
-type HighlightResult = Arc<Mutex<Option<(Rope, Vec<HighlightEvent>)>>>;
+/// Text larger than this is parsed after a pause in edits and its viewport is highlighted first
+const LARGE_TEXT_BYTES: usize = 256 * 1024;
+
+/// How long to wait for more edits before parsing large text
+const DEBOUNCE: Duration = Duration::from_millis(75);
+
+type HighlightResult = Arc<Mutex<Option<Highlights>>>;
+type OutlineResult = Arc<Mutex<Arc<Vec<Symbol>>>>;
+/// Proxy woken by the provider thread, shared so it can be replaced while the thread runs
+type SharedProxy = Arc<Mutex<Box<dyn EventLoopProxy>>>;
+
+/// A range of the highlighted text, `highlight` is `None` for text without a highlight
+#[derive(Copy, Clone, Debug)]
+pub struct HighlightSpan {
+    pub start: usize,
+    pub end: usize,
+    pub highlight: Option<Highlight>,
+}
+
+/// Highlights of the last parsed text, the spans are sorted and do not overlap
+pub struct Highlights {
+    pub rope: Rope,
+    pub spans: Vec<HighlightSpan>,
+}
+
+impl Highlights {
+    /// Spans that intersect `range`
+    pub fn spans_in(&self, range: ops::Range<usize>) -> &[HighlightSpan] {
+        let start = self.spans.partition_point(|span| span.end < range.start);
+        let end = self.spans.partition_point(|span| span.start <= range.end);
+        &self.spans[start..end.max(start)]
+    }
+}
+
+/// Turns highlight events into spans clipped to `range`
+fn collect_spans(
+    events: impl Iterator<Item = Result<HighlightEvent, Error>>,
+    range: ops::Range<usize>,
+) -> Vec<HighlightSpan> {
+    let mut highlight_stack = Vec::new();
+    let mut spans = Vec::new();
+    for event in events.filter_map(|event| event.ok()) {
+        match event {
+            HighlightEvent::Source { start, end } => {
+                let (start, end) = (start.max(range.start), end.min(range.end));
+                if start < end {
+                    spans.push(HighlightSpan {
+                        start,
+                        end,
+                        highlight: highlight_stack.last().copied(),
+                    });
+                }
+            }
+            HighlightEvent::HighlightStart(highlight) => highlight_stack.push(highlight),
+            HighlightEvent::HighlightEnd => drop(highlight_stack.pop()),
+        }
+    }
+    spans
+}
+
+/// The bytes that changed between `old` and `new` as a single edit, `None` if they are equal
+fn text_edit(old: &Rope, new: &Rope) -> Option<InputEdit> {
+    let prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix == old.len_bytes() && prefix == new.len_bytes() {
+        return None;
+    }
+    let max_suffix = old.len_bytes().min(new.len_bytes()) - prefix;
+    let suffix = old
+        .bytes_at(old.len_bytes())
+        .reversed()
+        .zip(new.bytes_at(new.len_bytes()).reversed())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let point = |rope: &Rope, byte: usize| {
+        let row = rope.byte_to_line(byte);
+        Point::new(row, byte - rope.line_to_byte(row))
+    };
+    let old_end_byte = old.len_bytes() - suffix;
+    let new_end_byte = new.len_bytes() - suffix;
+    Some(InputEdit {
+        start_byte: prefix,
+        old_end_byte,
+        new_end_byte,
+        start_position: point(old, prefix),
+        old_end_position: point(old, old_end_byte),
+        new_end_position: point(new, new_end_byte),
+    })
+}
+
+/// Moves spans of the old text to where their text is after `edit`,
+/// spans inside the edit are stretched over the new text
+fn shift_spans(spans: &mut Vec<HighlightSpan>, edit: &InputEdit) {
+    let shift = |byte: usize| {
+        if byte <= edit.start_byte {
+            byte
+        } else if byte >= edit.old_end_byte {
+            byte - edit.old_end_byte + edit.new_end_byte
+        } else {
+            byte.min(edit.new_end_byte)
+        }
+    };
+    for span in spans.iter_mut() {
+        span.start = shift(span.start);
+        span.end = shift(span.end);
+    }
+    spans.retain(|span| span.start < span.end);
+}
+
+/// Replaces the part of `stale` inside `range` with `fresh`
+fn splice_spans(
+    stale: Vec<HighlightSpan>,
+    range: ops::Range<usize>,
+    fresh: Vec<HighlightSpan>,
+) -> Vec<HighlightSpan> {
+    let mut spans = Vec::with_capacity(stale.len() + fresh.len());
+    let mut fresh = Some(fresh);
+    for span in stale {
+        if span.start < range.start {
+            spans.push(HighlightSpan {
+                end: span.end.min(range.start),
+                ..span
+            });
+        }
+        if span.end > range.end {
+            spans.extend(fresh.take().into_iter().flatten());
+            spans.push(HighlightSpan {
+                start: span.start.max(range.end),
+                ..span
+            });
+        }
+    }
+    spans.extend(fresh.into_iter().flatten());
+    spans
+}
 
 struct SyntaxProvider {
     pub language: &'static TreeSitterConfig,
@@ -27,8 +173,11 @@ struct SyntaxProvider {
 impl SyntaxProvider {
     pub fn new(
         language: &'static TreeSitterConfig,
-        proxy: Box<dyn EventLoopProxy>,
+        proxy: SharedProxy,
         result: HighlightResult,
+        outline_result: OutlineResult,
+        viewport: Arc<Mutex<ops::Range<usize>>>,
+        generation: Arc<AtomicU64>,
     ) -> Result<Self> {
         let (rope_tx, rope_rx) = cb::unbounded::<Rope>();
 
@@ -37,38 +186,111 @@ impl SyntaxProvider {
         thread::spawn(move || {
             tracing::info!("Highlight thread started for `{name}`");
             let mut highlighter = Highlighter::default();
-            let mut rope;
+            // The text the tree of the highlighter was parsed from
+            let mut parsed: Option<Rope> = None;
 
             loop {
-                rope = match rope_rx.recv() {
+                let mut rope = match rope_rx.recv() {
                     Ok(rope) => rope,
                     Err(err) => {
                         tracing::info!("Exiting highlight thread: {err}");
                         break;
                     }
                 };
+                while let Ok(newer) = rope_rx.try_recv() {
+                    rope = newer;
+                }
 
-                if !rope_rx.is_empty() {
-                    continue;
+                let large = rope.len_bytes() > LARGE_TEXT_BYTES;
+                if large {
+                    while let Ok(newer) = rope_rx.recv_timeout(DEBOUNCE) {
+                        rope = newer;
+                    }
+                }
+
+                if let Some(edit) = parsed.as_ref().and_then(|parsed| text_edit(parsed, &rope)) {
+                    highlighter.edit(&edit);
+                }
+                parsed = Some(rope.clone());
+
+                if large {
+                    let time = Instant::now();
+                    let range = viewport.lock().unwrap().clone();
+                    highlighter.byte_range = Some(range.clone());
+                    if let Ok(iterator) =
+                        highlighter.highlight(&highlight_config, rope.slice(..), |name| {
+                            get_injection_language(name).map(|language| &*language.highlight_config)
+                        })
+                    {
+                        let fresh = collect_spans(iterator, range.clone());
+                        let mut result = result.lock().unwrap();
+                        let stale = match result.take() {
+                            Some(Highlights {
+                                rope: stale_rope,
+                                mut spans,
+                            }) => {
+                                if let Some(edit) = text_edit(&stale_rope, &rope) {
+                                    shift_spans(&mut spans, &edit);
+                                }
+                                spans
+                            }
+                            None => Vec::new(),
+                        };
+                        *result = Some(Highlights {
+                            rope: rope.clone(),
+                            spans: splice_spans(stale, range, fresh),
+                        });
+                        generation.fetch_add(1, Ordering::Relaxed);
+                        proxy.lock().unwrap().request_render();
+                    }
+                    highlighter.byte_range = None;
+                    tracing::trace!(
+                        "viewport highlight took: {}us or {}ms",
+                        time.elapsed().as_micros(),
+                        time.elapsed().as_millis()
+                    );
+
+                    // The rest of the text keeps its stale highlights until the edits stop
+                    if !rope_rx.is_empty() {
+                        continue;
+                    }
                 }
 
                 let time = Instant::now();
+                let mut highlighted = false;
                 if let Ok(iterator) =
-                    highlighter.highlight(&highlight_config.clone(), rope.slice(..), |name| {
-                        get_tree_sitter_language(name).map(|language| &*language.highlight_config)
+                    highlighter.highlight(&highlight_config, rope.slice(..), |name| {
+                        get_injection_language(name).map(|language| &*language.highlight_config)
                     })
                 {
-                    *result.lock().unwrap() = Some((
-                        rope.clone(),
-                        iterator.filter_map(|event| event.ok()).collect(),
-                    ));
-                    proxy.request_render();
+                    *result.lock().unwrap() = Some(Highlights {
+                        rope: rope.clone(),
+                        spans: collect_spans(iterator, 0..usize::MAX),
+                    });
+                    generation.fetch_add(1, Ordering::Relaxed);
+                    proxy.lock().unwrap().request_render();
+                    highlighted = true;
                 }
                 tracing::trace!(
                     "highlight took: {}us or {}ms",
                     time.elapsed().as_micros(),
                     time.elapsed().as_millis()
                 );
+
+                // The outline is queried from the tree that was just parsed for the highlights
+                let symbols = match highlighter.tree() {
+                    Some(tree) if highlighted => outline::outline(language, tree, &rope),
+                    _ => None,
+                };
+                if let Some(symbols) = symbols {
+                    let mut outline = outline_result.lock().unwrap();
+                    if **outline != symbols {
+                        *outline = Arc::new(symbols);
+                        drop(outline);
+                        generation.fetch_add(1, Ordering::Relaxed);
+                        proxy.lock().unwrap().request_render();
+                    }
+                }
             }
 
             tracing::info!("Syntax provider thread exit");
@@ -85,7 +307,13 @@ impl SyntaxProvider {
 pub struct Syntax {
     syntax_provder: Option<SyntaxProvider>,
     result: HighlightResult,
-    proxy: Box<dyn EventLoopProxy>,
+    outline: OutlineResult,
+    /// Incremented every time new highlights or a new outline is published
+    generation: Arc<AtomicU64>,
+    /// Bytes of the text that were last rendered, they are highlighted first in large text
+    viewport: Arc<Mutex<ops::Range<usize>>>,
+    proxy: SharedProxy,
+    revision: Option<u64>,
 }
 
 impl Syntax {
@@ -93,7 +321,11 @@ impl Syntax {
         Self {
             syntax_provder: None,
             result: Arc::new(Mutex::new(None)),
-            proxy,
+            outline: Arc::default(),
+            generation: Arc::default(),
+            viewport: Arc::default(),
+            proxy: Arc::new(Mutex::new(proxy)),
+            revision: None,
         }
     }
 
@@ -106,27 +338,61 @@ impl Syntax {
                 tracing::info!("set lang to `{language}`");
                 self.syntax_provder = Some(SyntaxProvider::new(
                     lang,
-                    self.proxy.dup(),
+                    self.proxy.clone(),
                     self.result.clone(),
+                    self.outline.clone(),
+                    self.viewport.clone(),
+                    self.generation.clone(),
                 )?);
                 *self.result.lock().unwrap() = None;
+                *self.outline.lock().unwrap() = Arc::default();
+                self.generation.fetch_add(1, Ordering::Relaxed);
+                self.revision = None;
                 Ok(())
             }
             None => bail!("Unknown language: `{language}`"),
         }
     }
 
+    /// Replaces the proxy that is woken when new highlights are published
+    pub fn set_proxy(&self, proxy: Box<dyn EventLoopProxy>) {
+        *self.proxy.lock().unwrap() = proxy;
+    }
+
     pub fn get_language_name(&self) -> Option<&str> {
         Some(&self.syntax_provder.as_ref()?.language.name)
     }
 
-    pub fn update_text(&mut self, rope: Rope) {
+    pub fn get_config(&self) -> Option<&'static TreeSitterConfig> {
+        Some(self.syntax_provder.as_ref()?.language)
+    }
+
+    /// Symbols of the last parsed text, empty if the language has no outline query
+    pub fn outline(&self) -> Arc<Vec<Symbol>> {
+        self.outline.lock().unwrap().clone()
+    }
+
+    /// Queues the text for highlighting unless the same revision has already been queued
+    pub fn update_text(&mut self, rope: Rope, revision: u64) {
+        if self.revision == Some(revision) {
+            return;
+        }
         if let Some(syntax) = &self.syntax_provder {
+            self.revision = Some(revision);
             syntax.update_text(rope);
         }
     }
 
-    pub fn get_highlight_events(&self) -> MutexGuard<Option<(Rope, Vec<HighlightEvent>)>> {
+    /// Sets the bytes that are visible, they are highlighted before the rest of large text
+    pub fn set_viewport(&self, range: ops::Range<usize>) {
+        *self.viewport.lock().unwrap() = range;
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    pub fn get_highlights(&self) -> MutexGuard<Option<Highlights>> {
         self.result.lock().unwrap()
     }
 }
@@ -214,6 +480,10 @@ pub struct HighlightConfiguration {
 pub struct Highlighter {
     parser: Parser,
     cursors: Vec<QueryCursor>,
+    /// Tree of the root layer from the last parse, reused to parse incrementally
+    tree: Option<Tree>,
+    /// Only highlights captures in this range when set
+    pub byte_range: Option<ops::Range<usize>>,
 }
 
 #[derive(Debug)]
@@ -259,6 +529,8 @@ impl Default for Highlighter {
         Self {
             parser: Parser::new(),
             cursors: Vec::new(),
+            tree: None,
+            byte_range: None,
         }
     }
 }
@@ -269,6 +541,18 @@ impl Highlighter {
         &mut self.parser
     }
 
+    /// Tree of the root layer from the last parse
+    pub fn tree(&self) -> Option<&Tree> {
+        self.tree.as_ref()
+    }
+
+    /// Applies an edit of the text to the tree of the last parse so the next parse is incremental
+    pub fn edit(&mut self, edit: &InputEdit) {
+        if let Some(tree) = &mut self.tree {
+            tree.edit(edit);
+        }
+    }
+
     /// Iterate over the highlighted regions for a given slice of source code.
     pub fn highlight<'a>(
         &'a mut self,
@@ -446,6 +730,12 @@ impl<'a> HighlightIterLayer<'a> {
                     .set_language(config.language)
                     .map_err(|_| Error::InvalidLanguage)?;
 
+                // Only the root layer spans the whole text so only its tree can be reused
+                let old_tree = if depth == 0 {
+                    highlighter.tree.clone()
+                } else {
+                    None
+                };
                 let time = Instant::now();
                 let tree = highlighter
                     .parser
@@ -459,9 +749,12 @@ impl<'a> HighlightIterLayer<'a> {
                                 &[]
                             }
                         },
-                        None,
+                        old_tree.as_ref(),
                     )
                     .ok_or(Error::Cancelled)?;
+                if depth == 0 {
+                    highlighter.tree = Some(tree.clone());
+                }
                 tracing::trace!(
                     "parsing took: {}us or {}ms",
                     time.elapsed().as_micros(),
@@ -469,6 +762,7 @@ impl<'a> HighlightIterLayer<'a> {
                 );
 
                 let mut cursor = highlighter.cursors.pop().unwrap_or(QueryCursor::new());
+                cursor.set_byte_range(highlighter.byte_range.clone().unwrap_or(0..usize::MAX));
 
                 // Process combined injections.
                 if let Some(combined_injections_query) = &config.combined_injections_query {
@@ -1052,3 +1346,42 @@ fn injection_for_match<'a>(
 fn byte_range_to_str(range: std::ops::Range<usize>, source: RopeSlice) -> Cow<str> {
     Cow::from(source.byte_slice(range))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: usize, end: usize) -> HighlightSpan {
+        HighlightSpan {
+            start,
+            end,
+            highlight: None,
+        }
+    }
+
+    fn bounds(spans: &[HighlightSpan]) -> Vec<(usize, usize)> {
+        spans.iter().map(|span| (span.start, span.end)).collect()
+    }
+
+    #[test]
+    fn edit_and_splice_spans() {
+        let old = Rope::from_str("let a = 1;\nlet b = 2;\n");
+        let new = Rope::from_str("let a = 1;\nlet bc = 2;\n");
+        assert!(text_edit(&old, &old).is_none());
+        let edit = text_edit(&old, &new).unwrap();
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (16, 16, 17)
+        );
+        assert_eq!(edit.start_position, Point::new(1, 5));
+
+        let mut spans = vec![span(0, 3), span(15, 16), span(16, 20)];
+        shift_spans(&mut spans, &edit);
+        assert_eq!(bounds(&spans), [(0, 3), (15, 16), (16, 21)]);
+
+        let spans = splice_spans(spans, 2..16, vec![span(2, 10), span(10, 16)]);
+        assert_eq!(bounds(&spans), [(0, 2), (2, 10), (10, 16), (16, 21)]);
+        let highlights = Highlights { rope: new, spans };
+        assert_eq!(bounds(highlights.spans_in(11..12)), [(10, 16)]);
+    }
+}
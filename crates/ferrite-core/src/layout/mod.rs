@@ -1 +1,2 @@
+pub mod overlay;
 pub mod panes;
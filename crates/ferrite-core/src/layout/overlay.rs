@@ -0,0 +1,56 @@
+use super::panes::Rect;
+use crate::config::editor::OverlayLayout;
+
+/// Space kept between a floating overlay and the edges of the editor
+const MARGIN_X: usize = 5;
+const MARGIN_Y: usize = 2;
+
+/// Returns the area of an overlay drawn in `area`. Floating overlays are centered and
+/// no taller than `height` if it is set, docked overlays cover all of `area`.
+pub fn overlay_area(layout: &OverlayLayout, area: Rect, height: Option<usize>) -> Rect {
+    if !layout.floating {
+        return area;
+    }
+    let width = area
+        .width
+        .saturating_sub(MARGIN_X * 2)
+        .min(layout.max_width.unwrap_or(usize::MAX));
+    let height = area
+        .height
+        .saturating_sub(MARGIN_Y * 2)
+        .min(layout.max_height.unwrap_or(usize::MAX))
+        .min(height.unwrap_or(usize::MAX));
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::editor::{default_palette_layout, default_picker_layout};
+
+    #[test]
+    fn floating_area() {
+        let area = Rect::new(0, 0, 100, 40);
+        let picker = overlay_area(&default_picker_layout(), area, None);
+        assert_eq!(
+            (picker.x, picker.y, picker.width, picker.height),
+            (5, 2, 90, 36)
+        );
+
+        let mut layout = default_palette_layout();
+        let docked = overlay_area(&layout, area, Some(5));
+        assert_eq!((docked.width, docked.height), (100, 40));
+
+        layout.floating = true;
+        let palette = overlay_area(&layout, area, Some(5));
+        assert_eq!(
+            (palette.x, palette.y, palette.width, palette.height),
+            (10, 17, 80, 5)
+        );
+    }
+}
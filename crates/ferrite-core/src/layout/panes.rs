@@ -6,7 +6,7 @@ use slotmap::Key;
 
 use crate::{buffer::ViewId, file_explorer::FileExplorerId, workspace::BufferId};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Rect {
     pub x: usize,
     pub y: usize,
@@ -37,9 +37,12 @@ pub enum PaneKind {
     Buffer(BufferId, ViewId),
     FileExplorer(FileExplorerId),
     Logger,
+    LocationList,
+    Git,
+    Notifications,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Split {
     Horizontal,
     Vertical,
@@ -109,6 +112,63 @@ impl Pane {
         }
     }
 
+    fn get_leaves(&self, leaves: &mut Vec<PaneKind>) {
+        match self {
+            Pane::Leaf(leaf) => leaves.push(*leaf),
+            Pane::Internal { left, right, .. } => {
+                left.get_leaves(leaves);
+                right.get_leaves(leaves);
+            }
+        }
+    }
+
+    /// Replaces the leaves from left to right and top to bottom
+    fn set_leaves(&mut self, leaves: &mut impl Iterator<Item = PaneKind>) {
+        match self {
+            Pane::Leaf(leaf) => {
+                if let Some(new) = leaves.next() {
+                    *leaf = new;
+                }
+            }
+            Pane::Internal { left, right, .. } => {
+                left.set_leaves(leaves);
+                right.set_leaves(leaves);
+            }
+        }
+    }
+
+    /// Number of panes next to each other along `along`
+    fn span(&self, along: Split) -> usize {
+        match self {
+            Pane::Leaf(_) => 1,
+            Pane::Internal {
+                left, right, split, ..
+            } => {
+                if *split == along {
+                    left.span(along) + right.span(along)
+                } else {
+                    left.span(along).max(right.span(along))
+                }
+            }
+        }
+    }
+
+    /// Sets the ratios so panes along the same split get the same size
+    fn equalize(&mut self) {
+        if let Pane::Internal {
+            left,
+            right,
+            split,
+            ratio,
+        } = self
+        {
+            left.equalize();
+            right.equalize();
+            let left_span = left.span(*split);
+            *ratio = left_span as f32 / (left_span + right.span(*split)) as f32;
+        }
+    }
+
     pub fn remove(&mut self, pane_kind: PaneKind) -> Option<PaneKind> {
         let mut new = None;
         let mut output = None;
@@ -248,6 +308,9 @@ impl Pane {
                 PaneKind::Buffer(buffer_id, _) => *buffer_id == id,
                 PaneKind::FileExplorer(_) => false,
                 PaneKind::Logger => false,
+                PaneKind::LocationList => false,
+                PaneKind::Git => false,
+                PaneKind::Notifications => false,
             },
             Pane::Internal { left, right, .. } => {
                 left.contains_buffer(id) || right.contains_buffer(id)
@@ -302,8 +365,8 @@ impl Pane {
         rect
     }
 
-    pub fn resize_pane(&mut self, pane_kind: PaneKind, rect: Rect, direction: f32) {
-        debug_assert!(direction == -1.0 || direction == 1.0);
+    /// Grows the pane by `cells` rows or columns, shrinks it if `cells` is negative
+    pub fn resize_pane(&mut self, pane_kind: PaneKind, rect: Rect, cells: i64) {
         let rect = self.get_parent_size(pane_kind, rect);
         if let Pane::Internal {
             left,
@@ -317,7 +380,7 @@ impl Pane {
                 Split::Vertical => rect.width,
             };
 
-            let diff = (1.0 / size as f32) * direction;
+            let diff = (1.0 / size as f32) * cells as f32;
 
             match &mut **left {
                 Pane::Leaf(leaf) => {
@@ -327,7 +390,7 @@ impl Pane {
                         return;
                     }
                 }
-                node => node.resize_pane(pane_kind, rect, direction),
+                node => node.resize_pane(pane_kind, rect, cells),
             }
 
             match &mut **right {
@@ -337,16 +400,38 @@ impl Pane {
                         *ratio = ratio.clamp(0.0, 1.0);
                     }
                 }
-                node => node.resize_pane(pane_kind, rect, direction),
+                node => node.resize_pane(pane_kind, rect, cells),
             }
         }
     }
+
+    /// Makes the pane take up `fraction` of the split it is in.
+    /// Returns false if the pane is not part of a split.
+    pub fn set_pane_size(&mut self, pane_kind: PaneKind, fraction: f32) -> bool {
+        let Pane::Internal {
+            left, right, ratio, ..
+        } = self
+        else {
+            return false;
+        };
+        if matches!(&**left, Pane::Leaf(leaf) if *leaf == pane_kind) {
+            *ratio = fraction.clamp(0.0, 1.0);
+            return true;
+        }
+        if matches!(&**right, Pane::Leaf(leaf) if *leaf == pane_kind) {
+            *ratio = 1.0 - fraction.clamp(0.0, 1.0);
+            return true;
+        }
+        left.set_pane_size(pane_kind, fraction) || right.set_pane_size(pane_kind, fraction)
+    }
 }
 
 #[derive(Debug)]
 pub struct Panes {
     node: Pane,
     current_pane: PaneKind,
+    /// The current pane is shown on its own without changing the layout
+    zoomed: bool,
 }
 
 impl Panes {
@@ -354,6 +439,7 @@ impl Panes {
         Self {
             node: Pane::Leaf(PaneKind::Buffer(buffer_id, view_id)),
             current_pane: PaneKind::Buffer(buffer_id, view_id),
+            zoomed: false,
         }
     }
 
@@ -378,6 +464,7 @@ impl Panes {
 
     pub fn remove_pane(&mut self, pane_kind: PaneKind) -> bool {
         if self.node.num_panes() > 1 {
+            self.zoomed = false;
             self.current_pane = self.node.remove(pane_kind).unwrap();
             true
         } else {
@@ -387,6 +474,7 @@ impl Panes {
 
     pub fn split(&mut self, new_pane: PaneKind, direction: Direction) {
         if self.node.split(self.current_pane, new_pane, direction) {
+            self.zoomed = false;
             self.current_pane = new_pane;
         }
     }
@@ -396,11 +484,21 @@ impl Panes {
     }
 
     pub fn get_pane_bounds(&self, rect: Rect) -> Vec<(PaneKind, Rect)> {
+        if self.zoomed {
+            return vec![(self.current_pane, rect)];
+        }
         let mut bounds = Vec::new();
         self.node.get_pane_bounds(&mut bounds, rect);
         bounds
     }
 
+    /// Every pane from left to right and top to bottom
+    pub fn get_panes(&self) -> Vec<PaneKind> {
+        let mut leaves = Vec::new();
+        self.node.get_leaves(&mut leaves);
+        leaves
+    }
+
     pub fn make_current(&mut self, pane_kind: PaneKind) {
         if self.node.contains(pane_kind) {
             self.current_pane = pane_kind;
@@ -410,11 +508,73 @@ impl Panes {
     }
 
     pub fn grow_current(&mut self, rect: Rect) {
-        self.node.resize_pane(self.current_pane, rect, 1.0);
+        self.resize_current(rect, 1);
     }
 
     pub fn shrink_current(&mut self, rect: Rect) {
-        self.node.resize_pane(self.current_pane, rect, -1.0);
+        self.resize_current(rect, -1);
+    }
+
+    pub fn resize_current(&mut self, rect: Rect, cells: i64) {
+        self.node.resize_pane(self.current_pane, rect, cells);
+    }
+
+    /// Makes the current pane take up `percent` of the split it is in
+    pub fn set_current_size(&mut self, percent: u8) -> bool {
+        self.node
+            .set_pane_size(self.current_pane, f32::from(percent.min(100)) / 100.0)
+    }
+
+    /// Moves every pane one step forward, or backward if `reverse` is set.
+    /// The current pane stays current.
+    pub fn rotate(&mut self, reverse: bool) {
+        let mut leaves = self.get_panes();
+        if reverse {
+            leaves.rotate_left(1);
+        } else {
+            leaves.rotate_right(1);
+        }
+        self.node.set_leaves(&mut leaves.into_iter());
+    }
+
+    /// Swaps the current pane with the one next to it in `dir`.
+    /// Returns false if there is no pane in that direction.
+    pub fn swap_pane_direction(&mut self, dir: Direction, rect: Rect) -> bool {
+        let Some(other) = self.pane_in_direction(dir, rect) else {
+            return false;
+        };
+        self.zoomed = false;
+        let current = self.current_pane;
+        let leaves = self.get_panes().into_iter().map(|pane| {
+            if pane == current {
+                other
+            } else if pane == other {
+                current
+            } else {
+                pane
+            }
+        });
+        self.node
+            .set_leaves(&mut leaves.collect::<Vec<_>>().into_iter());
+        true
+    }
+
+    /// Gives panes along the same split the same size
+    pub fn equalize(&mut self) {
+        self.node.equalize();
+    }
+
+    /// Toggles showing only the current pane, returns false if there is only one pane
+    pub fn toggle_zoom(&mut self) -> bool {
+        if self.num_panes() < 2 {
+            return false;
+        }
+        self.zoomed = !self.zoomed;
+        true
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.zoomed
     }
 
     pub fn contains(&self, pane_kind: PaneKind) -> bool {
@@ -433,7 +593,15 @@ impl Panes {
     }
 
     pub fn switch_pane_direction(&mut self, dir: Direction, rect: Rect) {
-        let bounds = self.get_pane_bounds(rect);
+        if let Some(pane) = self.pane_in_direction(dir, rect) {
+            self.current_pane = pane;
+            self.zoomed = false;
+        }
+    }
+
+    fn pane_in_direction(&self, dir: Direction, rect: Rect) -> Option<PaneKind> {
+        let mut bounds = Vec::new();
+        self.node.get_pane_bounds(&mut bounds, rect);
         let current = self.get_current_pane();
         let (_, cb) = bounds.iter().find(|(pane, _)| *pane == current).unwrap();
         let bounds_check = match dir {
@@ -445,15 +613,13 @@ impl Panes {
 
         // This is retarded and I can't be bother to figure out why its needed
         if cb.y == 0 && dir == Direction::Up {
-            return;
+            return None;
         }
 
-        if let Some((new_pane, _)) = bounds
+        bounds
             .iter()
             .find(|(pane, bounds)| *pane != current && bounds.intersects(&bounds_check))
-        {
-            self.current_pane = *new_pane;
-        };
+            .map(|(pane, _)| *pane)
     }
 }
 
@@ -481,6 +647,73 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn set_current_size() {
+        let first = PaneKind::Buffer(
+            BufferId::from(KeyData::from_ffi(0)),
+            ViewId::from(KeyData::from_ffi(0)),
+        );
+        let second = PaneKind::Buffer(
+            BufferId::from(KeyData::from_ffi(1)),
+            ViewId::from(KeyData::from_ffi(1)),
+        );
+        let mut panes = Panes::new(
+            BufferId::from(KeyData::from_ffi(0)),
+            ViewId::from(KeyData::from_ffi(0)),
+        );
+        assert!(!panes.set_current_size(25));
+
+        panes.split(second, Direction::Right);
+        assert!(panes.set_current_size(25));
+        let rect = Rect::new(0, 0, 101, 10);
+        let bounds = panes.get_pane_bounds(rect);
+        assert_eq!(bounds[0].0, first);
+        assert_eq!(bounds[0].1.width, 75);
+
+        panes.resize_current(rect, 5);
+        let bounds = panes.get_pane_bounds(rect);
+        assert_eq!(bounds[0].1.width, 70);
+    }
+
+    #[test]
+    fn rearrange() {
+        let pane = |i| {
+            PaneKind::Buffer(
+                BufferId::from(KeyData::from_ffi(i)),
+                ViewId::from(KeyData::from_ffi(i)),
+            )
+        };
+        let mut panes = Panes::new(
+            BufferId::from(KeyData::from_ffi(0)),
+            ViewId::from(KeyData::from_ffi(0)),
+        );
+        panes.split(pane(1), Direction::Right);
+        panes.split(pane(2), Direction::Right);
+        assert_eq!(panes.get_panes(), [pane(0), pane(1), pane(2)]);
+
+        let rect = Rect::new(0, 0, 92, 10);
+        panes.equalize();
+        let widths: Vec<_> = panes
+            .get_pane_bounds(rect)
+            .iter()
+            .map(|(_, rect)| rect.width)
+            .collect();
+        assert_eq!(widths, [30, 30, 30]);
+
+        panes.rotate(false);
+        assert_eq!(panes.get_panes(), [pane(2), pane(0), pane(1)]);
+        assert_eq!(panes.get_current_pane(), pane(2));
+
+        assert!(panes.swap_pane_direction(Direction::Right, rect));
+        assert_eq!(panes.get_panes(), [pane(0), pane(2), pane(1)]);
+        assert_eq!(panes.get_current_pane(), pane(2));
+
+        assert!(panes.toggle_zoom());
+        assert_eq!(panes.get_pane_bounds(rect).len(), 1);
+        assert!(panes.toggle_zoom());
+        assert_eq!(panes.get_pane_bounds(rect).len(), 3);
+    }
 }
 
 pub mod layout {
@@ -501,13 +734,13 @@ pub mod layout {
         workspace::BufferId,
     };
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct Layout {
         node: Option<Node>,
         current_pane: Option<PaneKind>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     enum Node {
         Leaf(PaneKind),
         Internal {
@@ -529,6 +762,17 @@ pub mod layout {
             }
         }
 
+        fn get_paths(&self, paths: &mut Vec<PathBuf>) {
+            match self {
+                Node::Leaf(PaneKind::Buffer { path, .. }) => paths.push(path.clone()),
+                Node::Leaf(_) => (),
+                Node::Internal { left, right, .. } => {
+                    left.get_paths(paths);
+                    right.get_paths(paths);
+                }
+            }
+        }
+
         fn from_pane_node(
             pane: &Pane,
             buffers: &SlotMap<BufferId, Buffer>,
@@ -555,6 +799,10 @@ pub mod layout {
                         }))
                     }
                     super::PaneKind::Logger => Some(Self::Leaf(PaneKind::Logger)),
+                    // The locations are not saved so neither is the pane
+                    super::PaneKind::LocationList => None,
+                    super::PaneKind::Git => None,
+                    super::PaneKind::Notifications => None,
                 },
                 Pane::Internal {
                     left,
@@ -663,44 +911,44 @@ pub mod layout {
             file_explorers: &mut SlotMap<FileExplorerId, FileExplorer>,
         ) -> Option<super::Panes> {
             let pane = self.node.as_ref()?.to_pane(buffers, file_explorers)?;
-            let pane_kind = match &self.current_pane {
-                Some(PaneKind::Buffer {
-                    path,
-                    cursor,
-                    line_pos,
-                    col_pos,
-                }) => {
-                    match buffers
-                        .iter_mut()
-                        .find(|(_, buffer)| buffer.file() == Some(path))
-                    {
-                        Some((buffer_id, buffer)) => {
-                            let view_id = buffer.create_view();
-                            let view = &mut buffer.views[view_id];
-                            view.cursors = Vec1::new(*cursor);
-                            view.line_pos = *line_pos as f64;
-                            view.col_pos = *col_pos as f64;
-                            super::PaneKind::Buffer(buffer_id, view_id)
-                        }
-                        None => pane.get_first_leaf(),
-                    }
-                }
-                Some(PaneKind::FileExplorer { path, history }) => {
-                    let mut fe = FileExplorer::new(path.into());
-                    fe.history = history.clone();
-                    let file_explorer_id = file_explorers.insert(fe);
-                    super::PaneKind::FileExplorer(file_explorer_id)
-                }
-                Some(PaneKind::Logger) => super::PaneKind::Logger,
-                None => pane.get_first_leaf(),
-            };
-            let current_pane = pane_kind;
+            // The current pane is one of the restored panes so its view is reused
+            let mut leaves = Vec::new();
+            pane.get_leaves(&mut leaves);
+            let current_pane = leaves
+                .into_iter()
+                .find(|leaf| match (&self.current_pane, leaf) {
+                    (
+                        Some(PaneKind::Buffer { path, .. }),
+                        super::PaneKind::Buffer(buffer_id, _),
+                    ) => buffers[*buffer_id].file() == Some(path),
+                    (
+                        Some(PaneKind::FileExplorer { path, .. }),
+                        super::PaneKind::FileExplorer(file_explorer_id),
+                    ) => file_explorers[*file_explorer_id].directory() == path,
+                    (Some(PaneKind::Logger), super::PaneKind::Logger) => true,
+                    _ => false,
+                })
+                .unwrap_or_else(|| pane.get_first_leaf());
             Some(super::Panes {
                 node: pane,
                 current_pane,
+                zoomed: false,
             })
         }
 
+        /// The files shown in the layout
+        pub fn paths(&self) -> Vec<PathBuf> {
+            let mut paths = Vec::new();
+            if let Some(node) = &self.node {
+                node.get_paths(&mut paths);
+            }
+            paths
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.node.is_none()
+        }
+
         pub fn from_panes(
             panes: &Panes,
             buffers: &SlotMap<BufferId, Buffer>,
@@ -736,6 +984,9 @@ pub mod layout {
                     })
                 }
                 super::PaneKind::Logger => Some(PaneKind::Logger),
+                super::PaneKind::LocationList
+                | super::PaneKind::Git
+                | super::PaneKind::Notifications => None,
             };
             Self { node, current_pane }
         }
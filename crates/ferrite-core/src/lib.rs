@@ -1,25 +1,56 @@
+pub mod ansi;
+pub mod batch;
 pub mod buffer;
 pub mod buffer_watcher;
 pub mod byte_size;
 pub mod clipboard;
+pub mod closed_buffers;
 pub mod cmd;
+pub mod completion;
 pub mod config;
+pub mod diff;
+pub mod doctor;
+pub mod encryption;
 pub mod engine;
 pub mod event_loop_proxy;
 pub mod file_explorer;
+pub mod follow;
 pub mod git;
+pub mod goto_file;
+pub mod hooks;
+pub mod i18n;
+pub mod identifier_stats;
 pub mod indent;
+pub mod ipc;
 pub mod job_manager;
 pub mod jobs;
 pub mod keymap;
 pub mod language;
 pub mod layout;
+pub mod local_history;
+pub mod location_list;
 pub mod logger;
+pub mod notifications;
+pub mod output;
 pub mod palette;
+pub mod path_completion;
 pub mod picker;
+pub mod plugins;
 pub mod promise;
 pub mod pubsub;
+pub mod recent;
+pub mod recovery;
+pub mod refactor;
+pub mod registers;
+pub mod replay;
+pub mod scratch;
+pub mod scripts;
+pub mod search_edit;
 pub mod spinner;
+pub mod tags;
+pub mod tasks;
 pub mod theme;
+pub mod tools;
+pub mod trust;
 pub mod watcher;
 pub mod workspace;
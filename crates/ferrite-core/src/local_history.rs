@@ -0,0 +1,133 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+
+/// Max number of snapshots kept for each file, the oldest snapshots are removed first
+const MAX_SNAPSHOTS: usize = 100;
+
+/// How often the contents of dirty buffers are snapshotted
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(120);
+
+/// Directory in the data dir where the local history of every file is stored
+pub fn get_local_history_dir() -> Option<&'static Path> {
+    static LOCAL_HISTORY_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    LOCAL_HISTORY_DIR
+        .get_or_init(|| {
+            let directories = directories::ProjectDirs::from("", "", "ferrite")?;
+            Some(directories.data_dir().join("local-history"))
+        })
+        .as_deref()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Path of the file holding the snapshot
+    pub path: PathBuf,
+    pub time: SystemTime,
+}
+
+impl Snapshot {
+    pub fn read(&self) -> Result<String> {
+        Ok(fs::read_to_string(&self.path)?)
+    }
+}
+
+/// Saves `text` as a new snapshot of `path` unless it is identical to the latest snapshot
+pub fn snapshot(path: &Path, text: &str) -> Result<()> {
+    let Some(dir) = get_local_history_dir() else {
+        anyhow::bail!("Unable to find project directory");
+    };
+    snapshot_in(dir, path, text, SystemTime::now())
+}
+
+/// Snapshots of `path`, most recent first
+pub fn list_snapshots(path: &Path) -> Result<Vec<Snapshot>> {
+    let Some(dir) = get_local_history_dir() else {
+        anyhow::bail!("Unable to find project directory");
+    };
+    list_snapshots_in(dir, path)
+}
+
+fn file_history_dir(dir: &Path, path: &Path) -> PathBuf {
+    let path = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let hash = blake3::hash(path.as_os_str().as_encoded_bytes());
+    dir.join(&hash.to_hex()[..32])
+}
+
+fn snapshot_in(dir: &Path, path: &Path, text: &str, time: SystemTime) -> Result<()> {
+    let snapshots = list_snapshots_in(dir, path)?;
+    if let Some(latest) = snapshots.first() {
+        if latest.read().is_ok_and(|latest| latest == text) {
+            return Ok(());
+        }
+    }
+
+    let file_dir = file_history_dir(dir, path);
+    fs::create_dir_all(&file_dir)?;
+    let millis = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    fs::write(file_dir.join(millis.to_string()), text)?;
+
+    for old in snapshots.iter().skip(MAX_SNAPSHOTS - 1) {
+        if let Err(err) = fs::remove_file(&old.path) {
+            tracing::error!("Error removing snapshot `{}`: {err}", old.path.display());
+        }
+    }
+    Ok(())
+}
+
+fn list_snapshots_in(dir: &Path, path: &Path) -> Result<Vec<Snapshot>> {
+    let file_dir = file_history_dir(dir, path);
+    if !file_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(file_dir)? {
+        let entry = entry?;
+        let Some(millis) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        snapshots.push(Snapshot {
+            path: entry.path(),
+            time: SystemTime::UNIX_EPOCH + Duration::from_millis(millis),
+        });
+    }
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.time));
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        let dir = tempdir::TempDir::new("ferrite-local-history").unwrap();
+        let file = dir.path().join("file.txt");
+        let time = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+
+        snapshot_in(dir.path(), &file, "a", time(1)).unwrap();
+        snapshot_in(dir.path(), &file, "a", time(2)).unwrap();
+        snapshot_in(dir.path(), &file, "b", time(3)).unwrap();
+
+        let snapshots = list_snapshots_in(dir.path(), &file).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].time, time(3));
+        assert_eq!(snapshots[0].read().unwrap(), "b");
+        assert_eq!(snapshots[1].read().unwrap(), "a");
+        assert!(list_snapshots_in(dir.path(), &dir.path().join("other.txt"))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use ferrite_utility::{line_ending::LineEnding, point::Point};
+use ropey::Rope;
+
+use crate::{
+    cmd::Cmd,
+    event_loop_proxy::EventLoopProxy,
+    picker::{file_previewer::FilePreviewer, Preview},
+};
+
+/// A position in a file found by a task, a search or another subsystem.
+/// `line` and `column` are one based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// Locations shown in the location list pane, replaced by whatever populated it last
+pub struct LocationList {
+    /// What the locations came from
+    pub title: String,
+    pub entries: Vec<Location>,
+    /// The current entry, `None` until one is selected
+    pub index: Option<usize>,
+    previewer: FilePreviewer,
+    proxy: Box<dyn EventLoopProxy>,
+}
+
+impl LocationList {
+    pub fn new(proxy: Box<dyn EventLoopProxy>) -> Self {
+        Self {
+            title: String::new(),
+            entries: Vec::new(),
+            index: None,
+            previewer: FilePreviewer::new(proxy.dup(), HashMap::new()),
+            proxy,
+        }
+    }
+
+    /// Replaces the locations, files in `open_buffers` are previewed using the text of the open buffer
+    pub fn set(
+        &mut self,
+        title: String,
+        entries: Vec<Location>,
+        open_buffers: HashMap<PathBuf, Rope>,
+    ) {
+        self.title = title;
+        self.entries = entries;
+        self.index = None;
+        self.previewer = FilePreviewer::new(self.proxy.dup(), open_buffers);
+    }
+
+    pub fn current(&self) -> Option<&Location> {
+        self.entries.get(self.index?)
+    }
+
+    /// Selects the next location, wrapping around at the end
+    pub fn select_next(&mut self) -> Option<&Location> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let index = self
+            .index
+            .map_or(0, |index| (index + 1) % self.entries.len());
+        self.index = Some(index);
+        self.entries.get(index)
+    }
+
+    /// Selects the previous location, wrapping around at the start
+    pub fn select_prev(&mut self) -> Option<&Location> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let len = self.entries.len();
+        let index = self.index.map_or(len - 1, |index| (index + len - 1) % len);
+        self.index = Some(index);
+        self.entries.get(index)
+    }
+
+    /// Returns the location to jump to when enter is pressed
+    pub fn handle_input(&mut self, input: Cmd) -> Option<Location> {
+        match input {
+            Cmd::MoveUp { .. } => {
+                self.select_prev();
+            }
+            Cmd::MoveDown { .. } => {
+                self.select_next();
+            }
+            Cmd::Char { ch } if LineEnding::from_char(ch).is_some() => {
+                return self.current().or(self.entries.first()).cloned();
+            }
+            Cmd::Insert { text } if text.contains('\n') => {
+                return self.current().or(self.entries.first()).cloned();
+            }
+            _ => (),
+        }
+        None
+    }
+
+    /// Preview of the file around the current location
+    pub fn preview(&mut self) -> Option<Preview<'_>> {
+        let location = self.entries.get(self.index.unwrap_or(0))?;
+        match self.previewer.preview_path(&location.path) {
+            Preview::Buffer(buffer) => {
+                let point = Point::new(
+                    location.column.saturating_sub(1),
+                    location.line.saturating_sub(1),
+                );
+                let view_id = buffer.get_first_view_or_create();
+                buffer.select_area(view_id, point, point, false);
+                buffer.views[view_id].clamp_cursor = true;
+                buffer.center_on_cursor(view_id);
+                Some(Preview::Buffer(buffer))
+            }
+            preview => Some(preview),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_loop_proxy::NoopProxy;
+
+    fn location(line: usize) -> Location {
+        Location {
+            path: PathBuf::from("main.rs"),
+            line,
+            column: 1,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn select() {
+        let mut list = LocationList::new(Box::new(NoopProxy));
+        assert!(list.select_next().is_none());
+        list.set(
+            String::from("test"),
+            vec![location(1), location(2), location(3)],
+            HashMap::new(),
+        );
+        assert_eq!(list.select_prev().unwrap().line, 3);
+        assert_eq!(list.select_next().unwrap().line, 1);
+        list.handle_input(Cmd::MoveDown {
+            expand_selection: false,
+            create_cursor: false,
+            distance: 1,
+        });
+        assert_eq!(list.handle_input(Cmd::Char { ch: '\n' }).unwrap().line, 2);
+    }
+}
@@ -1,7 +1,10 @@
 use std::{
     collections::VecDeque,
     io::Write,
-    sync::{mpsc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Mutex,
+    },
 };
 
 use serde::Deserialize;
@@ -10,17 +13,28 @@ use crate::{cmd::Cmd, event_loop_proxy::EventLoopProxy};
 
 static PROXY: Mutex<Option<Box<dyn EventLoopProxy>>> = Mutex::new(None);
 
+/// Messages dropped because the channel to the logger pane was full
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// Max number of messages waiting to be received by the logger pane.
+/// Messages logged while the channel is full are dropped instead of blocking the logging thread.
+pub const CHANNEL_CAPACITY: usize = 4096;
+
+pub fn channel() -> (mpsc::SyncSender<LogMessage>, mpsc::Receiver<LogMessage>) {
+    mpsc::sync_channel(CHANNEL_CAPACITY)
+}
+
 pub fn set_proxy(proxy: Box<dyn EventLoopProxy>) {
     *PROXY.lock().unwrap() = Some(proxy);
 }
 
 pub struct LoggerSink {
     bytes: Vec<u8>,
-    sender: mpsc::Sender<LogMessage>,
+    sender: mpsc::SyncSender<LogMessage>,
 }
 
 impl LoggerSink {
-    pub fn new(sender: mpsc::Sender<LogMessage>) -> Self {
+    pub fn new(sender: mpsc::SyncSender<LogMessage>) -> Self {
         Self {
             bytes: Vec::new(),
             sender,
@@ -41,7 +55,9 @@ impl Write for LoggerSink {
         let mut last_line_start = 0;
         for line_start in line_starts {
             if let Ok(msg) = serde_json::from_slice(&self.bytes[last_line_start..line_start]) {
-                let _ = self.sender.send(msg);
+                if let Err(mpsc::TrySendError::Full(_)) = self.sender.try_send(msg) {
+                    DROPPED.fetch_add(1, Ordering::Relaxed);
+                }
             }
 
             last_line_start = line_start;
@@ -79,32 +95,56 @@ pub struct Fields {
 #[derive(Debug)]
 pub struct LoggerState {
     pub lines_scrolled_up: f64,
+    /// Newest message first, capped at `max_messages`
     pub messages: VecDeque<LogMessage>,
+    pub max_messages: usize,
+    /// Messages that were discarded because a limit was reached
+    pub dropped: usize,
     recv: mpsc::Receiver<LogMessage>,
 }
 
 impl LoggerState {
-    pub fn new(recv: mpsc::Receiver<LogMessage>) -> Self {
+    pub fn new(recv: mpsc::Receiver<LogMessage>, max_messages: usize) -> Self {
         Self {
             lines_scrolled_up: 0.0,
             messages: VecDeque::new(),
+            max_messages,
+            dropped: 0,
             recv,
         }
     }
 
     pub fn update(&mut self) {
+        self.dropped += DROPPED.swap(0, Ordering::Relaxed);
+
         while let Ok(msg) = self.recv.try_recv() {
-            self.messages.push_front(msg);
-            if self.lines_scrolled_up != 0.0 {
-                self.lines_scrolled_up += 1.0;
-            }
+            self.push(msg);
+        }
+
+        self.truncate();
+    }
+
+    fn push(&mut self, msg: LogMessage) {
+        self.messages.push_front(msg);
+        if self.lines_scrolled_up != 0.0 {
+            self.lines_scrolled_up += 1.0;
         }
+        self.truncate();
+    }
 
-        while self.messages.len() > 5000 {
+    fn truncate(&mut self) {
+        while self.messages.len() > self.max_messages {
             self.messages.pop_back();
+            self.dropped += 1;
         }
     }
 
+    pub fn clear(&mut self) {
+        self.messages.clear();
+        self.lines_scrolled_up = 0.0;
+        self.dropped = 0;
+    }
+
     pub fn handle_input(&mut self, input: Cmd) {
         match input {
             Cmd::VerticalScroll { distance } => {
@@ -116,3 +156,39 @@ impl LoggerState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(text: &str) -> LogMessage {
+        LogMessage {
+            timestamp: String::new(),
+            level: "INFO".into(),
+            target: "test".into(),
+            fields: Fields {
+                message: text.into(),
+            },
+        }
+    }
+
+    #[test]
+    fn ring_buffer() {
+        let (_tx, rx) = channel();
+        let mut state = LoggerState::new(rx, 2);
+        for text in ["a", "b", "c"] {
+            state.push(message(text));
+        }
+        let messages: Vec<_> = state
+            .messages
+            .iter()
+            .map(|msg| msg.fields.message.as_str())
+            .collect();
+        assert_eq!(messages, vec!["c", "b"]);
+        assert_eq!(state.dropped, 1);
+
+        state.clear();
+        assert!(state.messages.is_empty());
+        assert_eq!(state.dropped, 0);
+    }
+}
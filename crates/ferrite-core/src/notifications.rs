@@ -0,0 +1,140 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::cmd::Cmd;
+
+/// Max number of notifications kept in the history
+pub const MAX_NOTIFICATIONS: usize = 200;
+
+/// How long a new notification is shown as a toast
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// The diagnostic level the notification is styled as
+    pub fn level(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub severity: Severity,
+    pub message: String,
+    pub time: SystemTime,
+}
+
+/// Results of background jobs such as saves, formatters and shell commands.
+/// The newest notification is shown as a toast and the rest are kept as a history.
+#[derive(Debug, Default)]
+pub struct Notifications {
+    /// Newest notification first, capped at `MAX_NOTIFICATIONS`
+    pub notifications: VecDeque<Notification>,
+    /// Amount of the newest notifications scrolled past
+    pub scroll: usize,
+    /// When the toast of the newest notification was shown
+    toast_shown_at: Option<Instant>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, severity: Severity, message: impl Display) {
+        let message = message.to_string();
+        match severity {
+            Severity::Info => tracing::info!("{message}"),
+            Severity::Warning => tracing::warn!("{message}"),
+            Severity::Error => tracing::error!("{message}"),
+        }
+        self.notifications.push_front(Notification {
+            severity,
+            message,
+            time: SystemTime::now(),
+        });
+        self.notifications.truncate(MAX_NOTIFICATIONS);
+        if self.scroll != 0 {
+            self.scroll += 1;
+        }
+        self.toast_shown_at = Some(Instant::now());
+    }
+
+    /// The newest notification while its toast has not timed out or been dismissed
+    pub fn toast(&self) -> Option<&Notification> {
+        self.toast_remaining()?;
+        self.notifications.front()
+    }
+
+    /// Time until the toast is hidden, used to wake up the event loop to hide it
+    pub fn toast_remaining(&self) -> Option<Duration> {
+        TOAST_DURATION
+            .checked_sub(self.toast_shown_at?.elapsed())
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    pub fn dismiss_toast(&mut self) {
+        self.toast_shown_at = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.notifications.clear();
+        self.scroll = 0;
+        self.toast_shown_at = None;
+    }
+
+    pub fn handle_input(&mut self, input: Cmd) {
+        match input {
+            Cmd::VerticalScroll { distance } => {
+                self.scroll = self
+                    .scroll
+                    .saturating_add_signed(distance as isize)
+                    .min(self.notifications.len().saturating_sub(1));
+            }
+            Cmd::MoveDown { .. } => {
+                self.scroll = (self.scroll + 1).min(self.notifications.len().saturating_sub(1));
+            }
+            Cmd::MoveUp { .. } => {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
+            Cmd::Home { .. } | Cmd::Escape { .. } => self.scroll = 0,
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_and_toast() {
+        let mut notifications = Notifications::new();
+        assert!(notifications.toast().is_none());
+        for i in 0..MAX_NOTIFICATIONS + 1 {
+            notifications.push(Severity::Info, i);
+        }
+        notifications.push(Severity::Error, "save failed");
+        assert_eq!(notifications.notifications.len(), MAX_NOTIFICATIONS);
+
+        let toast = notifications.toast().unwrap();
+        assert_eq!(toast.severity, Severity::Error);
+        assert_eq!(toast.message, "save failed");
+        notifications.dismiss_toast();
+        assert!(notifications.toast().is_none());
+        assert!(notifications.toast_remaining().is_none());
+    }
+}
@@ -0,0 +1,66 @@
+use ropey::Rope;
+
+use crate::{
+    ansi::AnsiSpan,
+    buffer::{Buffer, ViewId},
+    config::editor::Task,
+    workspace::BufferId,
+};
+
+/// What produced the text of an output buffer, used to run it again
+#[derive(Debug, Clone)]
+pub enum OutputSource {
+    Shell(String),
+    Task(String, Task),
+    /// A tool and the buffer it was run with
+    Tool(String, BufferId, ViewId),
+}
+
+/// A read-only buffer showing the output of a shell command, task or tool
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub source: OutputSource,
+    /// Keeps the end of the output in view while it is streamed in
+    pub follow: bool,
+    /// Styles of the ansi escape sequences that were stripped from the output, sorted by position
+    pub spans: Vec<AnsiSpan>,
+}
+
+impl Output {
+    pub fn new(source: OutputSource, follow: bool) -> Self {
+        Self {
+            source,
+            follow,
+            spans: Vec::new(),
+        }
+    }
+
+    /// Replaces the text of `buffer` with more of the streamed output.
+    /// When not following the cursors and scroll position of the views are kept.
+    pub fn show(&self, buffer: &mut Buffer, rope: Rope) {
+        let views: Vec<_> = if self.follow {
+            Vec::new()
+        } else {
+            buffer
+                .views
+                .iter()
+                .map(|(view_id, view)| (view_id, view.cursors.clone(), view.line_pos))
+                .collect()
+        };
+
+        buffer.replace_rope(rope);
+
+        if self.follow {
+            for view_id in buffer.views.keys().collect::<Vec<_>>() {
+                buffer.scroll_to_end(view_id);
+            }
+        } else {
+            for (view_id, cursors, line_pos) in views {
+                let view = &mut buffer.views[view_id];
+                view.cursors = cursors;
+                view.line_pos = line_pos;
+            }
+            buffer.ensure_every_cursor_is_valid();
+        }
+    }
+}
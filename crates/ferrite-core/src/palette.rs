@@ -1,9 +1,10 @@
 use std::{
+    borrow::Cow,
     collections::HashMap,
     fmt::{self, Display},
 };
 
-use ferrite_utility::{graphemes::RopeGraphemeExt, line_ending::LineEnding};
+use ferrite_utility::{graphemes::RopeGraphemeExt, line_ending::LineEnding, wrap::wrap_text};
 use history::History;
 use ropey::RopeSlice;
 
@@ -13,18 +14,41 @@ use crate::{
     buffer::ViewId,
     cmd::Cmd,
     event_loop_proxy::{EventLoopProxy, UserEvent},
+    git::hunks::BufferHunk,
+    tr,
+    workspace::BufferId,
 };
 
 pub mod cmd_parser;
 pub mod completer;
 mod history;
 
+/// Messages with more lines than this are scrolled
+pub const MAX_MESSAGE_HEIGHT: usize = 10;
+
 #[derive(Debug, Clone)]
 pub enum PalettePromptEvent {
     Nop,
     Quit,
     Reload,
+    OverwriteBuffer(BufferId),
+    MergeBuffer(BufferId),
+    RestoreRecovered,
+    DiscardRecovered,
     CloseCurrent,
+    MakeWritable(BufferId),
+    TrustWorkspace,
+    DistrustWorkspace,
+    RevertHunk(BufferId, ViewId, BufferHunk),
+}
+
+/// State of a reverse search through the history of the current palette mode
+pub struct HistorySearch {
+    pub query: String,
+    /// Number of newer matches that have been skipped
+    skip: usize,
+    /// The prompt to restore when the search ends
+    prompt: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -44,6 +68,9 @@ pub enum PaletteState {
         completer: Completer,
         history_index: usize,
         old_line: String,
+        history_search: Option<HistorySearch>,
+        /// Hide the input and keep it out of the history, used for passphrases
+        masked: bool,
     },
     Prompt {
         selected: SelectedPrompt,
@@ -62,6 +89,9 @@ pub struct CommandPalette {
     proxy: Box<dyn EventLoopProxy>,
     state: PaletteState,
     histories: HashMap<String, History>,
+    /// Lines of the shown message that are scrolled past
+    message_scroll: usize,
+    last_error: Option<String>,
 }
 
 impl CommandPalette {
@@ -69,12 +99,18 @@ impl CommandPalette {
         Self {
             state: PaletteState::Nothing,
             proxy,
-            histories: Default::default(),
+            message_scroll: 0,
+            last_error: None,
+            histories: history::load_histories().unwrap_or_else(|err| {
+                tracing::error!("Error loading palette history: {err}");
+                HashMap::new()
+            }),
         }
     }
 
     pub fn set_msg(&mut self, msg: impl Display) {
         self.state = PaletteState::Message(msg.to_string());
+        self.message_scroll = 0;
     }
 
     pub fn set_error(&mut self, msg: impl fmt::Display) {
@@ -84,11 +120,21 @@ impl CommandPalette {
             PaletteState::Error(error) => {
                 error.push('\n');
                 error.push_str(&msg);
+                self.last_error = Some(error.clone());
+            }
+            state => {
+                self.last_error = Some(msg.clone());
+                *state = PaletteState::Error(msg);
+                self.message_scroll = 0;
             }
-            state => *state = PaletteState::Error(msg),
         }
     }
 
+    /// The last error that was shown, including the errors that were shown together with it
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
     pub fn reset(&mut self) {
         self.state = PaletteState::Nothing;
     }
@@ -98,6 +144,27 @@ impl CommandPalette {
         prompt: impl Into<String>,
         mode: impl Into<String>,
         ctx: CompleterContext,
+    ) {
+        self.focus_input(prompt, mode, ctx, false);
+    }
+
+    /// Focuses an input that is not shown on screen or saved in the history
+    pub fn focus_masked(&mut self, prompt: impl Into<String>, mode: impl Into<String>) {
+        self.reset();
+        self.focus_input(
+            prompt,
+            mode,
+            CompleterContext::new(Vec::new(), Vec::new(), false, None),
+            true,
+        );
+    }
+
+    fn focus_input(
+        &mut self,
+        prompt: impl Into<String>,
+        mode: impl Into<String>,
+        ctx: CompleterContext,
+        masked: bool,
     ) {
         let mut buffer = Buffer::new();
         let view_id = buffer.create_view();
@@ -124,16 +191,82 @@ impl CommandPalette {
             view_id,
             history_index: 0,
             old_line: String::new(),
+            history_search: None,
+            masked,
+        };
+    }
+
+    /// Starts a reverse fuzzy search through the history of the focused input,
+    /// if a search is already running the next older match is selected instead.
+    pub fn history_search(&mut self) {
+        let PaletteState::Input {
+            buffer,
+            view_id,
+            prompt,
+            mode,
+            focused: true,
+            history_search,
+            masked: false,
+            ..
+        } = &mut self.state
+        else {
+            return;
         };
+        match history_search {
+            Some(search) => search.skip += 1,
+            None => {
+                *history_search = Some(HistorySearch {
+                    query: String::new(),
+                    skip: 0,
+                    prompt: prompt.clone(),
+                })
+            }
+        }
+        let search = history_search.as_mut().unwrap();
+        Self::update_history_search(
+            self.histories.get(mode.as_str()),
+            search,
+            buffer,
+            *view_id,
+            prompt,
+        );
+    }
+
+    fn update_history_search(
+        history: Option<&History>,
+        search: &mut HistorySearch,
+        buffer: &mut Buffer,
+        view_id: ViewId,
+        prompt: &mut String,
+    ) {
+        let found = history.and_then(|history| {
+            // Stay on the oldest match when there are no more
+            history.search(&search.query, search.skip).or_else(|| {
+                search.skip = search.skip.saturating_sub(1);
+                history.search(&search.query, search.skip)
+            })
+        });
+        if let Some(found) = found {
+            buffer.replace(view_id, 0..buffer.rope().len_bytes(), found);
+            buffer.eof(view_id, false);
+        }
+        *prompt = tr!("prompt.history_search", query = search.query);
     }
 
     pub fn set_line(&mut self, content: impl AsRef<str>) {
         if let PaletteState::Input {
-            buffer, view_id, ..
+            buffer,
+            view_id,
+            mode,
+            completer,
+            ..
         } = &mut self.state
         {
             buffer.replace(*view_id, 0..buffer.rope().len_bytes(), content.as_ref());
             buffer.eof(*view_id, false);
+            if mode == "command" || mode == "shell" {
+                completer.update_text(buffer);
+            }
         }
     }
 
@@ -171,6 +304,7 @@ impl CommandPalette {
             alt2_char: alt2_char.to_ascii_lowercase(),
             alt2_event,
         };
+        self.message_scroll = 0;
     }
 
     pub fn has_focus(&self) -> bool {
@@ -180,6 +314,18 @@ impl CommandPalette {
         )
     }
 
+    /// Returns true while a masked input such as a passphrase prompt has focus
+    pub fn is_masked(&self) -> bool {
+        matches!(
+            self.state,
+            PaletteState::Input {
+                focused: true,
+                masked: true,
+                ..
+            }
+        )
+    }
+
     pub fn unfocus(&mut self) {
         if let PaletteState::Input { focused, .. } = &mut self.state {
             *focused = false;
@@ -198,23 +344,68 @@ impl CommandPalette {
         }
     }
 
-    pub fn height(&self) -> usize {
+    /// Text of the shown message, error or prompt
+    fn message(&self) -> Option<Cow<'_, str>> {
         match &self.state {
-            PaletteState::Message(string) => string.lines().count(),
-            PaletteState::Error(string) => string.lines().count(),
+            PaletteState::Message(string) | PaletteState::Error(string) => {
+                Some(Cow::Borrowed(string))
+            }
             PaletteState::Prompt {
                 selected,
                 prompt,
                 alt1_char,
                 alt2_char,
                 ..
-            } => Self::get_prompt(*selected, prompt, *alt1_char, *alt2_char)
-                .lines()
-                .count(),
-            _ => 1,
+            } => Some(Cow::Owned(Self::get_prompt(
+                *selected, prompt, *alt1_char, *alt2_char,
+            ))),
+            _ => None,
+        }
+    }
+
+    /// Height of the palette when its messages are wrapped to `width` columns
+    pub fn height(&self, width: usize) -> usize {
+        match self.message() {
+            Some(message) => wrap_text(&message, width).len().min(MAX_MESSAGE_HEIGHT),
+            None => 1,
         }
         .max(1)
     }
+
+    /// The lines of the shown message wrapped to `width` columns that fit in the palette.
+    /// If there are more lines below them the last line says how many.
+    pub fn message_lines(&mut self, width: usize) -> Vec<String> {
+        let Some(message) = self.message() else {
+            return Vec::new();
+        };
+        let lines = wrap_text(&message, width);
+        let max_scroll = lines.len().saturating_sub(MAX_MESSAGE_HEIGHT);
+        let scroll = self.message_scroll.min(max_scroll);
+        let mut visible: Vec<_> = lines
+            .iter()
+            .skip(scroll)
+            .take(MAX_MESSAGE_HEIGHT)
+            .map(|line| line.to_string())
+            .collect();
+        if scroll < max_scroll {
+            let hidden = lines.len() - scroll - (MAX_MESSAGE_HEIGHT - 1);
+            if let Some(last) = visible.last_mut() {
+                *last = tr!("misc.more_lines", count = hidden);
+            }
+        }
+        drop(message);
+        self.message_scroll = scroll;
+        visible
+    }
+
+    /// Scrolls the shown message by `distance` lines, returns false if no message is shown
+    pub fn scroll_message(&mut self, distance: isize) -> bool {
+        if self.message().is_none() {
+            return false;
+        }
+        self.message_scroll = self.message_scroll.saturating_add_signed(distance);
+        true
+    }
 }
 
 impl CommandPalette {
@@ -227,8 +418,45 @@ impl CommandPalette {
                 completer,
                 history_index,
                 old_line,
+                history_search,
+                prompt,
+                masked,
                 ..
             } => {
+                if let Some(search) = history_search {
+                    let query_len = search.query.len();
+                    let edited = match &input {
+                        Cmd::Char { ch } if LineEnding::from_char(*ch).is_none() => {
+                            search.query.push(*ch);
+                            true
+                        }
+                        Cmd::Insert { text } if !text.contains(['\n', '\r']) => {
+                            search.query.push_str(text);
+                            true
+                        }
+                        Cmd::Backspace => {
+                            search.query.pop();
+                            true
+                        }
+                        _ => false,
+                    };
+                    if edited {
+                        if search.query.len() != query_len {
+                            search.skip = 0;
+                        }
+                        Self::update_history_search(
+                            self.histories.get(mode.as_str()),
+                            search,
+                            buffer,
+                            *view_id,
+                            prompt,
+                        );
+                        return Ok(());
+                    }
+                    // Any other input ends the search and is handled as usual
+                    *prompt = history_search.take().unwrap().prompt;
+                }
+
                 let mut enter = false;
                 buffer.mark_clean();
                 match input {
@@ -265,6 +493,7 @@ impl CommandPalette {
                             buffer.mark_dirty();
                         }
                     }
+                    Cmd::MoveUp { .. } | Cmd::MoveDown { .. } if *masked => (),
                     Cmd::MoveUp { .. } => {
                         if let Some(history) = self.histories.get(mode) {
                             *history_index += 1;
@@ -302,8 +531,13 @@ impl CommandPalette {
                 }
 
                 if enter && buffer.rope().len_bytes() > 0 {
-                    let history = self.histories.get_mut(mode).unwrap();
-                    history.add(buffer.rope().to_string());
+                    if !*masked {
+                        let history = self.histories.get_mut(mode).unwrap();
+                        history.add(buffer.rope().to_string());
+                        if let Err(err) = history::save_histories(&self.histories) {
+                            tracing::error!("Error saving palette history: {err}");
+                        }
+                    }
                     self.proxy.send(UserEvent::PaletteEvent {
                         mode: mode.clone(),
                         content: buffer.rope().to_string(),
@@ -380,3 +614,40 @@ impl CommandPalette {
         format!("{prompt}: {alt1} / {alt2}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_loop_proxy::NoopProxy;
+
+    #[test]
+    fn wrap_messages() {
+        assert_eq!(wrap_text("one two three", 8), vec!["one two", "three"]);
+        assert_eq!(wrap_text("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+
+        let mut palette = CommandPalette::new(Box::new(NoopProxy));
+        palette.set_error("first error");
+        palette.set_error("the second error");
+        assert_eq!(palette.height(10), 4);
+        assert_eq!(
+            palette.message_lines(10),
+            vec!["first", "error", "the second", "error"]
+        );
+        assert_eq!(palette.last_error(), Some("first error\nthe second error"));
+
+        palette.set_msg(
+            (0..15)
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+        assert_eq!(palette.height(80), MAX_MESSAGE_HEIGHT);
+        let lines = palette.message_lines(80);
+        assert_eq!(lines[0], "0");
+        assert_eq!(lines[9], tr!("misc.more_lines", count = 6));
+        assert!(palette.scroll_message(100));
+        let lines = palette.message_lines(80);
+        assert_eq!(lines[0], "5");
+        assert_eq!(lines[9], "14");
+    }
+}
@@ -11,8 +11,24 @@ use error::CommandParseError;
 
 pub fn parse_cmd(input: &str) -> Result<Cmd, CommandParseError> {
     assert!(!input.is_empty());
+    let input = input.trim();
     let (name, tokens) = lexer::tokenize(input);
 
+    // `repeat <count> <command>` runs another command count times
+    if name.text == "repeat" && !tokens.is_empty() {
+        let count = tokens[0].text.parse()?;
+        let Some(cmd) = tokens.get(1) else {
+            return Err(CommandParseError::MissingArgs(
+                "repeat <count> <command>".into(),
+            ));
+        };
+        let cmd = parse_cmd(&input[cmd.start..])?;
+        return Ok(Cmd::RepeatCommand {
+            count,
+            command: Box::new(cmd),
+        });
+    }
+
     let Some(cmd) = COMMANDS.iter().find(|cmd| cmd.matches(&name.text)) else {
         return Err(CommandParseError::UnkownCommand(name.text));
     };
@@ -33,3 +49,22 @@ pub fn get_command_input_type(name: &str) -> Option<&'static CmdTemplateArg> {
         .map(|cmd| cmd.args.as_ref().map(|(_, input_type)| input_type))
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeat_command() {
+        assert_eq!(
+            parse_cmd("repeat 3 goto 10").unwrap(),
+            Cmd::RepeatCommand {
+                count: 3,
+                command: Box::new(Cmd::Goto { line: 10 }),
+            }
+        );
+        assert_eq!(parse_cmd("repeat").unwrap(), Cmd::Repeat);
+        assert!(parse_cmd("repeat 3").is_err());
+        assert!(parse_cmd("repeat x goto 10").is_err());
+    }
+}
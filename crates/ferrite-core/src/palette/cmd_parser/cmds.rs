@@ -6,28 +6,82 @@ use super::generic_cmd::{CmdBuilder, CmdTemplateArg, CommandTemplate};
 use crate::{
     buffer::{case::Case, encoding::get_encoding_names},
     cmd::Cmd,
+    config::editor::RenderWhitespace,
     language::get_available_languages,
     layout::panes::Direction,
+    registers::Registers,
 };
 
 pub static COMMANDS: LazyLock<Vec<CommandTemplate>> = LazyLock::new(|| {
     let mut cmds = vec![
         CmdBuilder::new("force-redraw", None, true).build(|_| Cmd::ForceRedraw),
+        CmdBuilder::new("suspend", None, true).build(|_| Cmd::Suspend),
         CmdBuilder::new("pwd", None, true).build(|_| Cmd::Pwd),
         CmdBuilder::new("replace", None, true).build(|_| Cmd::Replace),
         CmdBuilder::new("search", None, true).build(|_| Cmd::Search),
+        CmdBuilder::new("search-edit-diff", None, true).build(|_| Cmd::SearchEditDiff),
+        CmdBuilder::new("search-in", Some(("glob", CmdTemplateArg::String)), false).build(|args| Cmd::SearchIn { glob: args[0].take().unwrap().unwrap_string() }),
+        CmdBuilder::new("global-replace", Some(("replacement", CmdTemplateArg::String)), false).build(|args| Cmd::GlobalReplace { replacement: args[0].take().unwrap().unwrap_string() }),
+        CmdBuilder::new("undo-last-refactor", None, true).build(|_| Cmd::UndoLastRefactor),
+        CmdBuilder::new("identifier-stats", None, true).build(|_| Cmd::IdentifierStats),
+        CmdBuilder::new("location-list", None, true).build(|_| Cmd::LocationList),
+        CmdBuilder::new("lnext", None, true).add_alias("next-error").build(|_| Cmd::NextLocation),
+        CmdBuilder::new("lprev", None, true).add_alias("prev-error").build(|_| Cmd::PrevLocation),
         CmdBuilder::new("about", None, true).build(|_| Cmd::About),
         CmdBuilder::new("path", None, true).build(|_| Cmd::Path),
         CmdBuilder::new("git-reload", None, true).build(|_| Cmd::GitReload),
+        CmdBuilder::new("git-status", None, true).build(|_| Cmd::GitStatus),
+        CmdBuilder::new("git-commit", None, true).build(|_| Cmd::GitCommit),
+        CmdBuilder::new("git-branches", None, true).build(|_| Cmd::GitBranches),
+        CmdBuilder::new("git-log", None, true).build(|_| Cmd::GitLog),
+        CmdBuilder::new("next-hunk", None, true).build(|_| Cmd::NextHunk),
+        CmdBuilder::new("prev-hunk", None, true).build(|_| Cmd::PrevHunk),
+        CmdBuilder::new("revert-hunk", None, true).build(|_| Cmd::RevertHunk),
+        CmdBuilder::new("stage-hunk", None, true).build(|_| Cmd::StageHunk),
         CmdBuilder::new("reload", None, true).build(|_| Cmd::Reload),
         CmdBuilder::new("reload-all", None, true).build(|_| Cmd::ReloadAll),
-        CmdBuilder::new("logger", None, true).add_alias("log").build(|_| Cmd::Logger),
+        CmdBuilder::new("repeat", None, true).build(|_| Cmd::Repeat),
+        CmdBuilder::new("history-search", None, true).build(|_| Cmd::HistorySearch),
+        CmdBuilder::new("doctor", None, true).build(|_| Cmd::Doctor),
+        CmdBuilder::new("show-last-error", None, true).build(|_| Cmd::ShowLastError),
+        CmdBuilder::new("recover", None, true).build(|_| Cmd::Recover),
+        CmdBuilder::new("copy-to", Some(("register", CmdTemplateArg::Alternatives(Registers::names()))), false).build(|args| Cmd::CopyTo { register: args[0].take().unwrap().unwrap_string().chars().next().unwrap() }),
+        CmdBuilder::new("paste-from", Some(("register", CmdTemplateArg::Alternatives(Registers::names()))), false).build(|args| Cmd::PasteFrom { register: args[0].take().unwrap().unwrap_string().chars().next().unwrap() }),
+        CmdBuilder::new("register", Some(("register", CmdTemplateArg::Alternatives(Registers::names()))), true).build(|args| match args[0].take() {
+            Some(register) => Cmd::SelectRegister { register: register.unwrap_string().chars().next().unwrap() },
+            None => Cmd::RegisterPicker,
+        }),
+        CmdBuilder::new("local-history", None, true).build(|_| Cmd::LocalHistory),
+        CmdBuilder::new("plugins", None, true).build(|_| Cmd::PluginManager),
+        CmdBuilder::new("reload-scripts", None, true).build(|_| Cmd::ReloadScripts),
+        CmdBuilder::new("follow-pane", None, true).build(|_| Cmd::FollowPane),
+        CmdBuilder::new("reopen-closed", None, true).build(|_| Cmd::ReopenBuffer),
+        CmdBuilder::new("logger", Some(("action", CmdTemplateArg::Alternatives(vec!["clear".into()]))), true).add_alias("log").build(|args| {
+            match args[0].take() {
+                Some(_) => Cmd::LoggerClear,
+                None => Cmd::Logger,
+            }
+        }),
+        CmdBuilder::new("notifications", Some(("action", CmdTemplateArg::Alternatives(vec!["clear".into()]))), true).build(|args| {
+            match args[0].take() {
+                Some(_) => Cmd::NotificationsClear,
+                None => Cmd::Notifications,
+            }
+        }),
         CmdBuilder::new("quit!", None, true).add_alias("q!").build(|_| Cmd::ForceQuit),
         CmdBuilder::new("quit", None, true).add_alias("q").build(|_| Cmd::Quit),
         CmdBuilder::new("buffer-picker", None, true).build(|_| Cmd::BufferPickerOpen),
         CmdBuilder::new("file-picker", None, true).build(|_| Cmd::FilePickerOpen),
         CmdBuilder::new("file-picker-reload", None, true).build(|_| Cmd::FilePickerReload),
+        CmdBuilder::new("index-status", None, true).build(|_| Cmd::IndexStatus),
+        CmdBuilder::new("recent-files", None, true).build(|_| Cmd::RecentFilePickerOpen),
+        CmdBuilder::new("recent-projects", None, true).build(|_| Cmd::RecentProjectPickerOpen),
+        CmdBuilder::new("diff-selection-clipboard", None, true).build(|_| Cmd::DiffSelectionClipboard),
+        CmdBuilder::new("trust-workspace", None, true).build(|_| Cmd::TrustWorkspace),
+        CmdBuilder::new("picker", Some(("name", CmdTemplateArg::String)), false).build(|args| Cmd::CustomPicker { name: args[0].take().unwrap().unwrap_string() }),
+        CmdBuilder::new("scratch", Some(("name", CmdTemplateArg::String)), true).build(|args| Cmd::Scratch { name: args[0].take().map(|name| name.unwrap_string())}),
         CmdBuilder::new("open-config", None, true).build(|_| Cmd::OpenConfig),
+        CmdBuilder::new("theme-edit", None, true).build(|_| Cmd::EditTheme),
         CmdBuilder::new("default-config", None, true).build(|_| Cmd::DefaultConfig),
         CmdBuilder::new("open-languages", None, true).build(|_| Cmd::OpenLanguages),
         CmdBuilder::new("default-languages", None, true).build(|_| Cmd::DefaultLanguages),
@@ -43,17 +97,56 @@ pub static COMMANDS: LazyLock<Vec<CommandTemplate>> = LazyLock::new(|| {
         CmdBuilder::new("format-selection", None, true).build(|_| Cmd::FormatSelection),
         CmdBuilder::new("trash", None, true).build(|_| Cmd::Trash),
         CmdBuilder::new("url-open", None, true).build(|_| Cmd::UrlOpen),
+        CmdBuilder::new("goto-file", None, true).add_alias("gf").build(|_| Cmd::GotoFileUnderCursor),
         CmdBuilder::new("save-all", None, true).build(|_| Cmd::SaveAll),
+        CmdBuilder::new("zoom-in", None, true).build(|_| Cmd::ZoomIn),
+        CmdBuilder::new("zoom-out", None, true).build(|_| Cmd::ZoomOut),
         CmdBuilder::new("zoom-reset", None, true).build(|_| Cmd::ResetZoom),
         CmdBuilder::new("kill-job", None, true).build(|_| Cmd::KillJob),
+        CmdBuilder::new("cancel-job", None, true).build(|_| Cmd::CancelJob),
         CmdBuilder::new("trim-trailing-whitespace", None, true).build(|_| Cmd::TrimTrailingWhitespace),
+        CmdBuilder::new("accept-ours", None, true).build(|_| Cmd::AcceptOurs),
+        CmdBuilder::new("accept-theirs", None, true).build(|_| Cmd::AcceptTheirs),
+        CmdBuilder::new("accept-both", None, true).build(|_| Cmd::AcceptBoth),
+        CmdBuilder::new("normalize-unicode", None, true).build(|_| Cmd::NormalizeUnicode),
+        CmdBuilder::new("normalize-line-endings", None, true).build(|_| Cmd::NormalizeLineEndings),
+        CmdBuilder::new("read-only", None, true).build(|_| Cmd::ToggleReadOnly),
+        CmdBuilder::new("complete", None, true).build(|_| Cmd::Complete),
+        CmdBuilder::new("toggle-abbreviations", None, true).build(|_| Cmd::ToggleAbbreviations),
+        CmdBuilder::new("rerun", None, true).build(|_| Cmd::Rerun),
+        CmdBuilder::new("ansi-render", None, true).build(|_| Cmd::AnsiRender),
+        CmdBuilder::new("follow-output", None, true).build(|_| Cmd::ToggleFollowOutput),
+        CmdBuilder::new("tail", None, true).build(|_| Cmd::Tail),
+        CmdBuilder::new("next-conflict", None, true).build(|_| Cmd::NextConflict),
+        CmdBuilder::new("toggle-bookmark", None, true).build(|_| Cmd::ToggleBookmark),
+        CmdBuilder::new("next-bookmark", None, true).build(|_| Cmd::NextBookmark),
+        CmdBuilder::new("prev-bookmark", None, true).build(|_| Cmd::PrevBookmark),
+        CmdBuilder::new("bookmarks", None, true).build(|_| Cmd::BookmarkPicker),
+        CmdBuilder::new("outline", None, true).build(|_| Cmd::Outline),
+        CmdBuilder::new("parent-symbol", None, true).build(|_| Cmd::ParentSymbol),
         CmdBuilder::new("run", Some(("action", CmdTemplateArg::Action)), false).add_alias("r").build(|args| Cmd::RunAction { name: args[0].take().unwrap().unwrap_string() }),
         CmdBuilder::new("open-file-explorer", Some(("path", CmdTemplateArg::Path)), true).build(|args| Cmd::OpenFileExplorer { path: args[0].take().map(|arg| arg.unwrap_path())}),
-        CmdBuilder::new("number", Some(("start", CmdTemplateArg::Int)), true).build(|args| Cmd::Number { start: args[0].take().map(|arg| arg.unwrap_int())}),
+        CmdBuilder::new("sequence", Some(("start step", CmdTemplateArg::Int)), true).add_alias("number").build(|args| Cmd::InsertSequence { start: args[0].take().map(|arg| arg.unwrap_int()), step: args.get_mut(1).and_then(|arg| arg.take()).map(|arg| arg.unwrap_int()) }),
+        CmdBuilder::new("increment", Some(("count", CmdTemplateArg::Int)), true).add_alias("inc").build(|args| Cmd::IncrementNumber { count: args[0].take().map(|arg| arg.unwrap_int()).unwrap_or(1) }),
+        CmdBuilder::new("decrement", Some(("count", CmdTemplateArg::Int)), true).add_alias("dec").build(|args| Cmd::DecrementNumber { count: args[0].take().map(|arg| arg.unwrap_int()).unwrap_or(1) }),
         CmdBuilder::new("revert-buffer", None, true).add_alias("rb").build(|_| Cmd::RevertBuffer),
         CmdBuilder::new("open", Some(("path", CmdTemplateArg::Path)), false).add_alias("o").build(|args| Cmd::OpenFile { path: args[0].take().unwrap().unwrap_path()}),
+        CmdBuilder::new("open-right", Some(("path", CmdTemplateArg::Path)), false).add_alias("vsplit").build(|args| Cmd::OpenFileSplit { path: args[0].take().unwrap().unwrap_path(), direction: Direction::Right }),
+        CmdBuilder::new("open-down", Some(("path", CmdTemplateArg::Path)), false).add_alias("hsplit").build(|args| Cmd::OpenFileSplit { path: args[0].take().unwrap().unwrap_path(), direction: Direction::Down }),
         CmdBuilder::new("cd", Some(("path", CmdTemplateArg::Path)), false).build(|args| Cmd::Cd { path: args[0].take().unwrap().unwrap_path()}),
         CmdBuilder::new("save", Some(("path", CmdTemplateArg::Path)), true).add_alias("s").build(|args| Cmd::Save {path: args[0].take().map(|arg| arg.unwrap_path())}),
+        CmdBuilder::new("pane-resize", Some(("amount", CmdTemplateArg::Int)), false).build(|args| Cmd::PaneResize { amount: args[0].take().unwrap().unwrap_int()}),
+        CmdBuilder::new("pane-set-size", Some(("percent", CmdTemplateArg::Int)), false).build(|args| Cmd::PaneSetSize { percent: args[0].take().unwrap().unwrap_int()}),
+        CmdBuilder::new("rotate-panes", Some(("direction", CmdTemplateArg::Alternatives(vec!["forward".into(), "backward".into()]))), true).build(|args| {
+            Cmd::RotatePanes { reverse: args[0].take().is_some_and(|arg| arg.unwrap_string() == "backward") }
+        }),
+        CmdBuilder::new("swap-pane", Some(("direction", CmdTemplateArg::Alternatives(["up", "down", "left", "right"].iter().map(|s| s.to_string()).collect()))), false).build(|args| {
+            Cmd::SwapPane { direction: Direction::from_str(args[0].take().unwrap().unwrap_string().as_str()).unwrap()}
+        }),
+        CmdBuilder::new("equalize-panes", None, true).add_alias("balance-panes").build(|_| Cmd::EqualizePanes),
+        CmdBuilder::new("zoom-pane", None, true).add_alias("maximize-pane").build(|_| Cmd::TogglePaneZoom),
+        CmdBuilder::new("save-layout", Some(("name", CmdTemplateArg::String)), false).build(|args| Cmd::SaveLayout { name: args[0].take().unwrap().unwrap_string()}),
+        CmdBuilder::new("load-layout", Some(("name", CmdTemplateArg::String)), false).build(|args| Cmd::LoadLayout { name: args[0].take().unwrap().unwrap_string()}),
         CmdBuilder::new("goto", Some(("line", CmdTemplateArg::Int)), false).add_alias("g").build(|args| Cmd::Goto { line: args[0].take().unwrap().unwrap_int()}),
         CmdBuilder::new("theme", Some(("theme", CmdTemplateArg::Theme)), true).build(|args| Cmd::Theme { theme: args[0].take().map(|theme| theme.unwrap_string())}),
         CmdBuilder::new("new", Some(("path", CmdTemplateArg::Path)), true).add_alias("n").build(|args| Cmd::New { path: args[0].take().map(|arg| arg.unwrap_path())}),
@@ -73,13 +166,21 @@ pub static COMMANDS: LazyLock<Vec<CommandTemplate>> = LazyLock::new(|| {
             }
             Cmd::RunShellCmd { args: paths, pipe: false }
         }),
-        CmdBuilder::new("sort", Some(("order", CmdTemplateArg::Alternatives(["asc", "desc"].iter().map(|s| s.to_string()).collect()))), true).build(|args| {
-            Cmd::SortLines { ascending: args[0].take().map(|o|o.unwrap_string() == "asc").unwrap_or(true)}
+        CmdBuilder::new("sort", Some(("order", CmdTemplateArg::Alternatives(["asc", "desc", "unique"].iter().map(|s| s.to_string()).collect()))), true).build(|args| {
+            let order = args[0].take().map(|o| o.unwrap_string());
+            Cmd::SortLines { ascending: order.as_deref() != Some("desc"), unique: order.as_deref() == Some("unique") }
         }),
+        CmdBuilder::new("reverse-lines", None, true).build(|_| Cmd::ReverseLines),
+        CmdBuilder::new("align", Some(("pattern", CmdTemplateArg::String)), false).build(|args| Cmd::Align { pattern: args[0].take().unwrap().unwrap_string() }),
+        CmdBuilder::new("duplicate-lines", None, true).build(|_| Cmd::DuplicateLines),
+        CmdBuilder::new("join-lines", None, true).build(|_| Cmd::JoinLines),
         CmdBuilder::new("split", Some(("direction", CmdTemplateArg::Alternatives(["up", "down", "left", "right"].iter().map(|s| s.to_string()).collect()))), false).build(|args| {
             Cmd::Split { direction: Direction::from_str(args[0].take().unwrap().unwrap_string().as_str()).unwrap()}
         }),
-        CmdBuilder::new("case", Some(("case", CmdTemplateArg::Alternatives(["lower", "upper", "snake", "kebab", "camel", "pascal", "title", "train", "screaming-snake", "screaming-kebab"].iter().map(|s| s.to_string()).collect()))), false).build(|args| {
+        CmdBuilder::new("move-to-pane", Some(("direction", CmdTemplateArg::Alternatives(["up", "down", "left", "right"].iter().map(|s| s.to_string()).collect()))), false).build(|args| {
+            Cmd::MoveBufferToPane { direction: Direction::from_str(args[0].take().unwrap().unwrap_string().as_str()).unwrap()}
+        }),
+        CmdBuilder::new("case", Some(("case", CmdTemplateArg::Alternatives(Case::NAMES.iter().map(|s| s.to_string()).collect()))), false).build(|args| {
             Cmd::Case { case: Case::from_str(args[0].take().unwrap().unwrap_string().as_str()).unwrap()}
         }),
         CmdBuilder::new("encoding", Some(("encoding", CmdTemplateArg::Alternatives(get_encoding_names().iter().map(|s| s.to_string()).collect()))), true)
@@ -87,9 +188,32 @@ pub static COMMANDS: LazyLock<Vec<CommandTemplate>> = LazyLock::new(|| {
             .build(|args| {
                 Cmd::Encoding { encoding: args[0].take().map(|encoding| encoding.unwrap_string())}
             }),
+        CmdBuilder::new("reopen-with-encoding", Some(("encoding", CmdTemplateArg::Alternatives(get_encoding_names().iter().map(|s| s.to_string()).collect()))), false)
+            .set_custom_alternative_error(|encoding, _| format!("`{encoding}` is unknown an encoding, these encodings are supported: https://docs.rs/encoding_rs/latest/encoding_rs"))
+            .build(|args| {
+                Cmd::ReopenWithEncoding { encoding: args[0].take().unwrap().unwrap_string() }
+            }),
+        CmdBuilder::new("convert-encoding", Some(("encoding", CmdTemplateArg::Alternatives(get_encoding_names().iter().map(|s| s.to_string()).collect()))), false)
+            .set_custom_alternative_error(|encoding, _| format!("`{encoding}` is unknown an encoding, these encodings are supported: https://docs.rs/encoding_rs/latest/encoding_rs"))
+            .build(|args| {
+                Cmd::ConvertEncoding { encoding: args[0].take().unwrap().unwrap_string() }
+            }),
+        CmdBuilder::new("bom", Some(("action", CmdTemplateArg::Alternatives(vec!["add".into(), "remove".into()]))), true)
+            .build(|args| Cmd::Bom { add: args[0].take().map(|action| action.unwrap_string() == "add") }),
         CmdBuilder::new("language", Some(("language", CmdTemplateArg::Alternatives(get_available_languages().iter().map(|s| s.to_string()).collect()))), true)
             .add_alias("lang")
             .build(|args| Cmd::Language { language: args[0].take().map(|language| language.unwrap_string())}),
+        CmdBuilder::new("show-whitespace", Some(("mode", CmdTemplateArg::Alternatives(["off", "selection", "trailing", "all"].iter().map(|s| s.to_string()).collect()))), true)
+            .build(|args| {
+                Cmd::ShowWhitespace { mode: args[0].take().map(|mode| {
+                    match mode.unwrap_string().as_str() {
+                        "selection" => RenderWhitespace::Selection,
+                        "trailing" => RenderWhitespace::Trailing,
+                        "all" => RenderWhitespace::All,
+                        _ => RenderWhitespace::None,
+                    }
+                })}
+            }),
         CmdBuilder::new("line-ending", Some(("line-ending", CmdTemplateArg::Alternatives(vec!["lf".into(), "crlf".into()]))), true)
             .build(|args| {
                 Cmd::LineEnding{ line_ending: args[0].take().map(|line_ending| {
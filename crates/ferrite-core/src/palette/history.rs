@@ -1,4 +1,10 @@
-use std::collections::VecDeque;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::PathBuf,
+};
+
+use anyhow::Result;
 
 #[derive(Debug)]
 pub struct History {
@@ -39,4 +45,74 @@ impl History {
     pub fn get(&self, index: usize) -> Option<&str> {
         self.entires.get(index).map(|s| s.as_str())
     }
+
+    /// Finds the entry fuzzy matching `query` skipping the `skip` most recent unique matches
+    pub fn search(&self, query: &str, skip: usize) -> Option<&str> {
+        let mut seen = HashSet::new();
+        self.entires
+            .iter()
+            .rev()
+            .filter(|entry| query.is_empty() || sublime_fuzzy::best_match(query, entry).is_some())
+            .filter(|entry| seen.insert(entry.as_str()))
+            .nth(skip)
+            .map(|s| s.as_str())
+    }
+}
+
+pub fn get_history_path() -> Result<PathBuf> {
+    let Some(directories) = directories::ProjectDirs::from("", "", "ferrite") else {
+        return Err(anyhow::Error::msg("Unable to find project directory"));
+    };
+    Ok(directories.data_dir().join("palette-history.json"))
+}
+
+/// Loads the history of every palette mode saved by `save_histories`
+pub fn load_histories() -> Result<HashMap<String, History>> {
+    let path = get_history_path()?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let histories: HashMap<String, Vec<String>> = serde_json::from_slice(&fs::read(path)?)?;
+    Ok(histories
+        .into_iter()
+        .map(|(mode, entires)| {
+            (
+                mode,
+                History {
+                    entires: entires.into(),
+                },
+            )
+        })
+        .collect())
+}
+
+pub fn save_histories(histories: &HashMap<String, History>) -> Result<()> {
+    let path = get_history_path()?;
+    let histories: HashMap<_, _> = histories
+        .iter()
+        .map(|(mode, history)| (mode, &history.entires))
+        .collect();
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, serde_json::to_vec(&histories)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_history() {
+        let mut history = History {
+            entires: VecDeque::new(),
+        };
+        for entry in ["open foo.rs", "goto 10", "open bar.rs", "open foo.rs"] {
+            history.add(entry.into());
+        }
+        assert_eq!(history.search("", 0), Some("open foo.rs"));
+        assert_eq!(history.search("opn", 0), Some("open foo.rs"));
+        assert_eq!(history.search("opn", 1), Some("open bar.rs"));
+        assert_eq!(history.search("opn", 2), None);
+        assert_eq!(history.search("gt1", 0), Some("goto 10"));
+    }
 }
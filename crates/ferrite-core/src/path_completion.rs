@@ -0,0 +1,114 @@
+use std::ops::Range;
+
+use ropey::Rope;
+
+use crate::completion::MAX_OPTIONS;
+
+fn is_path_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '/' | '\\' | '.' | '_' | '-' | '~' | '+' | '@')
+}
+
+/// Finds the byte range of a partial path ending at `byte_idx`.
+/// Text is considered a path if it contains a path separator or is the start of a quoted string.
+pub fn find_partial_path(rope: &Rope, byte_idx: usize) -> Option<Range<usize>> {
+    let line_idx = rope.byte_to_line(byte_idx);
+    let line_start = rope.line_to_byte(line_idx);
+    let before = rope.byte_slice(line_start..byte_idx).to_string();
+
+    let token = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, ch)| is_path_char(*ch))
+        .last()
+        .map(|(i, _)| i)?;
+    let text = &before[token..];
+
+    let quoted = before[..token].ends_with(['"', '\'', '`']);
+    let has_separator = text.contains(['/', std::path::MAIN_SEPARATOR]);
+    // Urls are not paths
+    if before[..token].ends_with(':') || text.contains("//") {
+        return None;
+    }
+    if !has_separator && !quoted {
+        return None;
+    }
+
+    // The leading ./ is kept when accepting as the index is relative to the workspace root
+    let relative = text
+        .strip_prefix("./")
+        .or_else(|| text.strip_prefix(".\\"))
+        .unwrap_or(text);
+    let start = line_start + token + (text.len() - relative.len());
+    if relative.is_empty() {
+        return None;
+    }
+
+    Some(start..byte_idx)
+}
+
+/// Returns the indexed files that complete `partial`, shortest match first.
+/// Paths that start with `partial` are preferred over paths with a directory or file name starting with it.
+pub fn complete_path<'a>(files: impl Iterator<Item = &'a String>, partial: &str) -> Vec<String> {
+    let mut options: Vec<_> = files
+        .filter(|file| file.as_str() != partial)
+        .filter_map(|file| {
+            if file.starts_with(partial) {
+                Some((0, file))
+            } else if file
+                .match_indices(partial)
+                .any(|(i, _)| file[..i].ends_with(['/', std::path::MAIN_SEPARATOR]))
+            {
+                Some((1, file))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    options.sort_by(|(a_prio, a), (b_prio, b)| {
+        a_prio
+            .cmp(b_prio)
+            .then(a.len().cmp(&b.len()))
+            .then(a.cmp(b))
+    });
+    options.truncate(MAX_OPTIONS);
+    options.into_iter().map(|(_, file)| file.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_path() {
+        let rope = Rope::from_str("let x = \"./src/ma");
+        let range = find_partial_path(&rope, rope.len_bytes()).unwrap();
+        assert_eq!(rope.byte_slice(range).to_string(), "src/ma");
+
+        let rope = Rope::from_str("include \"Car");
+        let range = find_partial_path(&rope, rope.len_bytes()).unwrap();
+        assert_eq!(rope.byte_slice(range).to_string(), "Car");
+
+        let rope = Rope::from_str("let foo");
+        assert_eq!(find_partial_path(&rope, rope.len_bytes()), None);
+
+        let rope = Rope::from_str("see https://example.com/fo");
+        assert_eq!(find_partial_path(&rope, rope.len_bytes()), None);
+    }
+
+    #[test]
+    fn completions() {
+        let files = [
+            "src/main.rs".to_string(),
+            "src/lib.rs".to_string(),
+            "crates/core/src/main.rs".to_string(),
+            "Cargo.toml".to_string(),
+        ];
+        assert_eq!(
+            complete_path(files.iter(), "src/ma"),
+            vec!["src/main.rs", "crates/core/src/main.rs"]
+        );
+        assert_eq!(complete_path(files.iter(), "Car"), vec!["Cargo.toml"]);
+        assert!(complete_path(files.iter(), "Cargo.toml").is_empty());
+    }
+}
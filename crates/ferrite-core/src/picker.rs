@@ -13,12 +13,23 @@ use self::fuzzy_match::FuzzyMatch;
 use super::buffer::{error::BufferError, Buffer};
 use crate::{buffer::ViewId, cmd::Cmd, event_loop_proxy::EventLoopProxy};
 
+pub mod bookmark_picker;
 pub mod buffer_picker;
+pub mod custom_picker;
+pub mod file_index;
 pub mod file_picker;
 pub mod file_previewer;
 pub mod file_scanner;
 pub mod fuzzy_match;
+pub mod git_picker;
 pub mod global_search_picker;
+pub mod identifier_stats_picker;
+pub mod job_picker;
+pub mod local_history_picker;
+pub mod outline_picker;
+pub mod plugin_picker;
+pub mod recent_picker;
+pub mod register_picker;
 
 pub enum Preview<'a> {
     Buffer(&'a mut Buffer),
@@ -156,6 +167,15 @@ where
         }
     }
 
+    /// The highlighted option
+    pub fn get_selected(&mut self) -> Option<M> {
+        let selected = self.selected;
+        self.get_matches()
+            .get(selected)
+            .map(|(FuzzyMatch { item, .. }, _)| item)
+            .cloned()
+    }
+
     pub fn get_matches(&mut self) -> &[(FuzzyMatch<M>, usize)] {
         self.poll_rx();
         &self.result.matches
@@ -0,0 +1,80 @@
+use std::{borrow::Cow, collections::HashMap, path::PathBuf, sync::Arc};
+
+use ferrite_utility::point::Point;
+use ropey::Rope;
+
+use super::{file_previewer::FilePreviewer, Matchable, PickerOptionProvider, Preview, Previewer};
+use crate::event_loop_proxy::EventLoopProxy;
+
+pub struct BookmarkProvider(pub Arc<boxcar::Vec<BookmarkItem>>);
+
+impl PickerOptionProvider for BookmarkProvider {
+    type Matchable = BookmarkItem;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let _ = tx.send(self.0.clone());
+        rx
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BookmarkItem {
+    pub path: PathBuf,
+    pub line_idx: usize,
+    label: String,
+}
+
+impl BookmarkItem {
+    /// `name` is the path shown in the picker, the bookmarked line is read from `rope`
+    pub fn new(path: PathBuf, name: &str, line_idx: usize, rope: &Rope) -> Self {
+        let text = rope
+            .get_line(line_idx)
+            .map(|line| line.to_string())
+            .unwrap_or_default();
+        Self {
+            label: format!("{name}:{}: {}", line_idx + 1, text.trim()),
+            path,
+            line_idx,
+        }
+    }
+}
+
+impl Matchable for BookmarkItem {
+    fn as_match_str(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+
+    fn display(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+}
+
+/// Shows the file of a bookmark centered on the bookmarked line
+pub struct BookmarkPreviewer {
+    previewer: FilePreviewer,
+}
+
+impl BookmarkPreviewer {
+    pub fn new(proxy: Box<dyn EventLoopProxy>, open_buffers: HashMap<PathBuf, Rope>) -> Self {
+        Self {
+            previewer: FilePreviewer::new(proxy, open_buffers),
+        }
+    }
+}
+
+impl Previewer<BookmarkItem> for BookmarkPreviewer {
+    fn request_preview(&mut self, m: &BookmarkItem) -> Preview<'_> {
+        match self.previewer.preview_path(&m.path) {
+            Preview::Buffer(buffer) => {
+                let view_id = buffer.get_first_view_or_create();
+                let point = Point::new(0, m.line_idx);
+                buffer.select_area(view_id, point, point, false);
+                buffer.views[view_id].clamp_cursor = true;
+                buffer.center_on_cursor(view_id);
+                Preview::Buffer(buffer)
+            }
+            preview => preview,
+        }
+    }
+}
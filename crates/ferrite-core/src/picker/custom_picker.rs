@@ -0,0 +1,78 @@
+use std::{path::Path, process::Stdio, sync::Arc, thread};
+
+use super::{file_previewer::FilePreviewer, PickerOptionProvider, Preview, Previewer};
+use crate::engine::get_exec;
+
+/// Runs a shell command and provides every non empty line it prints as an option
+pub struct CustomPickerProvider {
+    pub cmd: String,
+}
+
+impl PickerOptionProvider for CustomPickerProvider {
+    type Matchable = String;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let cmd = self.cmd.clone();
+        thread::spawn(move || {
+            let mut command = get_exec(&cmd);
+            command.stdout(Stdio::piped());
+            command.stderr(Stdio::null());
+            command.stdin(Stdio::null());
+            let output = match command.output() {
+                Ok(output) => output,
+                Err(err) => {
+                    tracing::error!("Error running picker command `{cmd}`: {err}");
+                    return;
+                }
+            };
+
+            let options: boxcar::Vec<_> = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.to_string())
+                .collect();
+            let _ = tx.send(Arc::new(options));
+        });
+        rx
+    }
+}
+
+/// Splits an option in the `path:line` format.
+/// Anything after the line number such as a column or the matched text from grep is ignored.
+pub fn parse_path_line(option: &str) -> (&str, Option<i64>) {
+    for (idx, _) in option.match_indices(':') {
+        let rest = &option[idx + 1..];
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        if digits > 0 && rest[digits..].chars().next().is_none_or(|ch| ch == ':') {
+            return (&option[..idx], rest[..digits].parse().ok());
+        }
+    }
+    (option, None)
+}
+
+pub struct CustomPickerPreviewer(pub FilePreviewer);
+
+impl Previewer<String> for CustomPickerPreviewer {
+    fn request_preview(&mut self, option: &String) -> Preview<'_> {
+        let (path, _) = parse_path_line(option);
+        self.0.preview_path(Path::new(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_line() {
+        assert_eq!(parse_path_line("src/main.rs"), ("src/main.rs", None));
+        assert_eq!(parse_path_line("src/main.rs:12"), ("src/main.rs", Some(12)));
+        assert_eq!(
+            parse_path_line("src/main.rs:12:fn main() {"),
+            ("src/main.rs", Some(12))
+        );
+        assert_eq!(parse_path_line("a:b:3:4"), ("a:b", Some(3)));
+        assert_eq!(parse_path_line("notes: todo"), ("notes: todo", None));
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::editor::{FilesConfig, PickerConfig};
+
+/// File index of a workspace persisted between sessions so the file picker can be populated instantly
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersistedIndex {
+    pub root: PathBuf,
+    pub picker_config: PickerConfig,
+    pub files_config: FilesConfig,
+    pub refreshed_at: SystemTime,
+    /// Directories that are not ignored, relative to the root
+    pub dirs: Vec<String>,
+    /// Indexed files relative to the root
+    pub files: Vec<String>,
+}
+
+impl PersistedIndex {
+    /// Loads the index of `root` if it was created with the same config
+    pub fn load(
+        root: &Path,
+        picker_config: &PickerConfig,
+        files_config: &FilesConfig,
+    ) -> Option<Self> {
+        let path = get_index_path(root).ok()?;
+        if !path.exists() {
+            return None;
+        }
+        let index: Self = match fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| Ok(serde_json::from_slice(&data)?))
+        {
+            Ok(index) => index,
+            Err(err) => {
+                tracing::error!("Error loading file index: {err}");
+                return None;
+            }
+        };
+        (index.root == root
+            && index.picker_config == *picker_config
+            && index.files_config == *files_config)
+            .then_some(index)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = get_index_path(&self.root)?;
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+}
+
+/// Each workspace gets its own file named after a hash of the root path
+pub fn get_index_path(root: &Path) -> Result<PathBuf> {
+    let Some(directories) = directories::ProjectDirs::from("", "", "ferrite") else {
+        return Err(anyhow::Error::msg("Unable to find project directory"));
+    };
+    let mut hasher = DefaultHasher::new();
+    root.hash(&mut hasher);
+    Ok(directories
+        .data_dir()
+        .join("index")
+        .join(format!("{:016x}.json", hasher.finish())))
+}
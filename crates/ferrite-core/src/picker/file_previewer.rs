@@ -2,9 +2,11 @@ use std::{
     collections::{hash_map::Entry, HashMap},
     fs::{self, File},
     io::{self, Read},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
+use ropey::Rope;
+
 use crate::{
     buffer::Buffer,
     event_loop_proxy::EventLoopProxy,
@@ -12,6 +14,8 @@ use crate::{
     promise::Promise,
 };
 
+const MAX_PREVIEW_SIZE: u64 = 1_000_000;
+
 pub fn is_text_file(path: impl AsRef<Path>) -> Result<bool, io::Error> {
     let mut file = File::open(&path)?;
 
@@ -22,55 +26,93 @@ pub fn is_text_file(path: impl AsRef<Path>) -> Result<bool, io::Error> {
     Ok(content_type.is_text())
 }
 
+/// Loads a read-only preview of a file.
+/// If the file is already open the text of the open buffer is used instead of the file on disk.
+fn load_preview(
+    path: &Path,
+    open_buffer: Option<Rope>,
+    proxy: Box<dyn EventLoopProxy>,
+) -> Result<Option<Buffer>, io::Error> {
+    let mut buffer = match open_buffer {
+        Some(rope) => {
+            let mut buffer = Buffer::with_path(path).map_err(io::Error::other)?;
+            buffer.replace_rope(rope);
+            buffer
+        }
+        None => {
+            if !is_text_file(path)? {
+                return Ok(None);
+            }
+            Buffer::from_file(path)?
+        }
+    };
+    buffer.read_only = true;
+    buffer.set_proxy(proxy);
+    Ok(Some(buffer))
+}
+
 pub struct FilePreviewer {
-    files: HashMap<String, Result<Option<Buffer>, io::Error>>,
-    loading: HashMap<String, Promise<Result<Option<Buffer>, io::Error>>>,
+    files: HashMap<PathBuf, Result<Option<Buffer>, io::Error>>,
+    loading: HashMap<PathBuf, Promise<Result<Option<Buffer>, io::Error>>>,
+    open_buffers: HashMap<PathBuf, Rope>,
     proxy: Box<dyn EventLoopProxy>,
 }
 
 impl FilePreviewer {
-    pub fn new(proxy: Box<dyn EventLoopProxy>) -> Self {
+    /// `open_buffers` maps the canonical paths of buffers that are open to their text
+    pub fn new(proxy: Box<dyn EventLoopProxy>, open_buffers: HashMap<PathBuf, Rope>) -> Self {
         Self {
             files: HashMap::new(),
             loading: HashMap::new(),
+            open_buffers,
             proxy,
         }
     }
-}
 
-impl Previewer<String> for FilePreviewer {
-    fn request_preview(&mut self, m: &String) -> Preview {
-        if let Entry::Occupied(mut entry) = self.loading.entry(m.clone()) {
-            if let Some(result) = entry.get_mut().poll() {
-                let (k, _) = entry.remove_entry();
-                self.files.insert(k, result);
+    pub fn preview_path(&mut self, path: &Path) -> Preview<'_> {
+        if let Entry::Occupied(mut entry) = self.loading.entry(path.to_path_buf()) {
+            match entry.get_mut().poll() {
+                Some(result) => {
+                    let (k, _) = entry.remove_entry();
+                    self.files.insert(k, result);
+                }
+                None => return Preview::Loading,
             }
         }
 
-        match self.files.get_mut(m) {
+        match self.files.get_mut(path) {
             Some(Ok(Some(buffer))) => return Preview::Buffer(buffer),
             Some(Ok(None)) => return Preview::Binary,
             Some(Err(_)) => return Preview::Err,
             None => (),
         }
 
-        let path = m.clone();
-        if let Ok(metadata) = fs::metadata(&path) {
-            const MAX_PREVIEW_SIZE: u64 = 1_000_000;
-            if metadata.len() > MAX_PREVIEW_SIZE {
-                return Preview::TooLarge;
+        let open_buffer = dunce::canonicalize(path)
+            .ok()
+            .and_then(|path| self.open_buffers.get(&path).cloned());
+
+        if open_buffer.is_none() {
+            if let Ok(metadata) = fs::metadata(path) {
+                if metadata.len() > MAX_PREVIEW_SIZE {
+                    return Preview::TooLarge;
+                }
             }
         }
 
+        let owned_path = path.to_path_buf();
+        let proxy = self.proxy.dup();
         self.loading.insert(
-            m.clone(),
+            path.to_path_buf(),
             Promise::spawn(self.proxy.dup(), move || {
-                if !is_text_file(&path)? {
-                    return Ok(None);
-                }
-                Ok(Some(Buffer::from_file(&path)?))
+                load_preview(&owned_path, open_buffer, proxy)
             }),
         );
         Preview::Loading
     }
 }
+
+impl Previewer<String> for FilePreviewer {
+    fn request_preview(&mut self, m: &String) -> Preview {
+        self.preview_path(Path::new(m))
+    }
+}
@@ -1,31 +1,28 @@
 use std::{
-    fs::File,
+    collections::{BTreeSet, HashSet},
+    fs::{self, File},
     io::Read,
     path::{Path, PathBuf},
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    sync::{mpsc, Arc, Mutex},
     thread,
-    time::Instant,
+    time::{Duration, Instant, SystemTime},
 };
 
 use ferrite_utility::trim::trim_path;
+use ignore::{
+    overrides::{Override, OverrideBuilder},
+    DirEntry,
+};
+use notify_debouncer_full::{new_debouncer, notify::RecursiveMode, DebounceEventResult};
 use rayon::prelude::*;
 
+use super::file_index::PersistedIndex;
 use crate::{
-    config::editor::{Editor, PickerConfig},
+    config::editor::{Editor, FilesConfig, PickerConfig},
+    job_manager::CancellationToken,
     pubsub::{self, Publisher, Subscriber},
 };
 
-fn get_text_file_path(path: PathBuf) -> Option<PathBuf> {
-    if is_text_file(&path) {
-        Some(path)
-    } else {
-        None
-    }
-}
-
 fn is_text_file(path: impl AsRef<Path>) -> bool {
     let Ok(mut file) = File::open(&path) else {
         return false;
@@ -40,115 +37,579 @@ fn is_text_file(path: impl AsRef<Path>) -> bool {
     content_type.is_text()
 }
 
+/// Builds the include and exclude globs used when walking `root`.
+/// Invalid globs are logged and skipped.
+pub fn build_overrides(root: &Path, files: &FilesConfig) -> Override {
+    let mut builder = OverrideBuilder::new(root);
+    let globs = files
+        .include
+        .iter()
+        .map(|glob| glob.to_string())
+        .chain(files.exclude.iter().map(|glob| format!("!{glob}")));
+    for glob in globs {
+        if let Err(err) = builder.add(&glob) {
+            tracing::error!("Invalid glob `{glob}`: {err}");
+        }
+    }
+    builder.build().unwrap_or_else(|err| {
+        tracing::error!("Error building file globs: {err}");
+        Override::empty()
+    })
+}
+
+/// State of the file index shown by `index-status`
+#[derive(Debug, Clone)]
+pub struct IndexStatus {
+    pub root: PathBuf,
+    /// When the index was last walked or updated from a filesystem event
+    pub refreshed_at: Option<SystemTime>,
+    pub scanning: bool,
+    pub watching: bool,
+    pub from_cache: bool,
+    /// The scan was cancelled before every file was found
+    pub cancelled: bool,
+}
+
+/// Indexes the files of a workspace in the background.
+/// The index is persisted in the data dir and kept up to date with filesystem events
+/// so the directory only has to be walked the first time a workspace is opened
+/// or when it changed while the editor was closed.
 pub struct FileScanner {
     subscriber: Subscriber<boxcar::Vec<String>>,
-    running: Arc<AtomicBool>,
+    token: CancellationToken,
+    scan_token: CancellationToken,
+    picker_config: PickerConfig,
+    files: FilesConfig,
+    status: Arc<Mutex<IndexStatus>>,
 }
 
 impl FileScanner {
+    /// Uses the persisted index of `path` if there is one, otherwise walks the directory
     pub fn new(path: PathBuf, config: &Editor) -> Self {
+        Self::start(path, config, false)
+    }
+
+    /// Walks the directory even if there is a persisted index
+    pub fn rescan(path: PathBuf, config: &Editor) -> Self {
+        Self::start(path, config, true)
+    }
+
+    fn start(path: PathBuf, config: &Editor, force_scan: bool) -> Self {
         let (publisher, subscriber): (Publisher<boxcar::Vec<String>>, _) =
             pubsub::create(boxcar::Vec::new());
-        let path_to_search = path.clone();
-        let picker_config = config.picker;
-        let running = Arc::new(AtomicBool::new(true));
-
-        let thread_runnig = running.clone();
-        thread::spawn(move || {
-            scan_files(
-                &publisher,
-                path_to_search.clone(),
-                picker_config,
-                thread_runnig.clone(),
-            );
-        });
+        let token = CancellationToken::new();
+        let scan_token = CancellationToken::new();
+        let status = Arc::new(Mutex::new(IndexStatus {
+            root: path.clone(),
+            refreshed_at: None,
+            scanning: false,
+            watching: false,
+            from_cache: false,
+            cancelled: false,
+        }));
+
+        let mut indexer = Indexer {
+            publisher,
+            overrides: build_overrides(&path, &config.files),
+            root: path,
+            picker_config: config.picker,
+            files_config: config.files.clone(),
+            token: token.clone(),
+            scan_token: scan_token.clone(),
+            status: status.clone(),
+            dirs: BTreeSet::new(),
+            files: BTreeSet::new(),
+            complete: false,
+        };
+        thread::spawn(move || indexer.run(force_scan));
 
         Self {
             subscriber,
-            running,
+            token,
+            scan_token,
+            picker_config: config.picker,
+            files: config.files.clone(),
+            status,
         }
     }
 
+    /// Returns true if `config` would index a different set of files
+    pub fn is_outdated(&self, config: &Editor) -> bool {
+        self.picker_config != config.picker || self.files != config.files
+    }
+
+    /// Returns true if the index is kept up to date by filesystem events
+    pub fn is_watching(&self) -> bool {
+        self.status.lock().unwrap().watching
+    }
+
+    pub fn status(&self) -> IndexStatus {
+        self.status.lock().unwrap().clone()
+    }
+
     pub fn subscribe(&self) -> Subscriber<boxcar::Vec<String>> {
         self.subscriber.clone()
     }
+
+    /// Stops walking the directory, the files found so far are kept and still watched for changes
+    pub fn cancel(&self) {
+        self.scan_token.cancel();
+    }
+
+    /// The files found so far relative to the scanned directory
+    pub fn files(&self) -> Arc<boxcar::Vec<String>> {
+        self.subscriber.get()
+    }
 }
 
 impl Drop for FileScanner {
     fn drop(&mut self) {
-        self.running.store(false, Ordering::Relaxed);
+        self.token.cancel();
     }
 }
 
-fn scan_files(
-    publisher: &Publisher<boxcar::Vec<String>>,
-    path: PathBuf,
-    config: PickerConfig,
-    running: Arc<AtomicBool>,
-) {
-    if publisher.publish().is_err() {
-        return;
-    }
-
-    let path_str = path.to_string_lossy().into_owned();
-    let mut iterator = ignore::WalkBuilder::new(&path)
-        .follow_links(false)
-        .hidden(!config.show_hidden)
-        .ignore(config.follow_ignore)
-        .git_global(config.follow_git_global)
-        .git_ignore(config.follow_gitignore)
-        .git_exclude(config.follow_git_exclude)
-        .sort_by_file_path(|lhs, rhs| {
-            lexical_sort::natural_lexical_cmp(&lhs.to_string_lossy(), &rhs.to_string_lossy())
-        })
-        .build()
-        .filter_map(|result| result.ok());
-
-    let mut tracked_files = Vec::new();
-    let start = Instant::now();
-
-    loop {
-        if !running.load(Ordering::Relaxed) {
-            return;
+struct Indexer {
+    publisher: Publisher<boxcar::Vec<String>>,
+    root: PathBuf,
+    picker_config: PickerConfig,
+    files_config: FilesConfig,
+    overrides: Override,
+    /// Cancelled when the scanner is dropped
+    token: CancellationToken,
+    /// Cancelled to stop walking the directory
+    scan_token: CancellationToken,
+    status: Arc<Mutex<IndexStatus>>,
+    /// Directories that are not ignored, relative to the root.
+    /// Ordered so everything below a removed directory is found without a scan.
+    dirs: BTreeSet<String>,
+    files: BTreeSet<String>,
+    /// The index has every file or is the persisted one that was fresh when loaded.
+    /// It is not saved after a cancelled scan.
+    complete: bool,
+}
+
+impl Indexer {
+    fn run(&mut self, force_scan: bool) {
+        let persisted = if force_scan {
+            None
+        } else {
+            PersistedIndex::load(&self.root, &self.picker_config, &self.files_config)
+        };
+
+        match persisted {
+            Some(index) => {
+                self.dirs = index.dirs.into_iter().collect();
+                self.files = index.files.into_iter().collect();
+                self.complete = true;
+                self.publish_all();
+                let mut status = self.status.lock().unwrap();
+                status.refreshed_at = Some(index.refreshed_at);
+                status.from_cache = true;
+                drop(status);
+                // The cached files are shown while the directory is walked again
+                if self.is_stale(index.refreshed_at) {
+                    tracing::info!("File index is outdated, rescanning");
+                    self.status.lock().unwrap().scanning = true;
+                    let completed = self.scan(false);
+                    let mut status = self.status.lock().unwrap();
+                    status.scanning = false;
+                    status.cancelled = self.scan_token.is_cancelled();
+                    status.from_cache = !completed;
+                    drop(status);
+                    self.complete = completed;
+                    if completed {
+                        self.save();
+                    } else if !self.scan_token.is_cancelled() {
+                        return;
+                    }
+                }
+            }
+            None => {
+                self.status.lock().unwrap().scanning = true;
+                let completed = self.scan(true);
+                let mut status = self.status.lock().unwrap();
+                status.scanning = false;
+                status.cancelled = self.scan_token.is_cancelled();
+                drop(status);
+                self.complete = completed;
+                if completed {
+                    self.save();
+                } else if !self.scan_token.is_cancelled() {
+                    return;
+                }
+            }
         }
 
-        let entries: Vec<_> = iterator.by_ref().take(1000).collect();
+        self.watch();
+    }
+
+    fn walker(&self, path: &Path) -> ignore::WalkBuilder {
+        let config = self.picker_config;
+        let mut builder = ignore::WalkBuilder::new(path);
+        builder
+            .follow_links(false)
+            .hidden(!config.show_hidden)
+            .ignore(config.follow_ignore)
+            .git_global(config.follow_git_global)
+            .git_ignore(config.follow_gitignore)
+            .git_exclude(config.follow_git_exclude)
+            .overrides(self.overrides.clone())
+            .sort_by_file_path(|lhs, rhs| {
+                lexical_sort::natural_lexical_cmp(&lhs.to_string_lossy(), &rhs.to_string_lossy())
+            });
+        builder
+    }
+
+    fn is_indexed_file(&self, entry: &DirEntry) -> bool {
+        entry.file_type().is_some_and(|f| f.is_file())
+            && (!self.picker_config.show_only_text_files || is_text_file(entry.path()))
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        trim_path(&self.root.to_string_lossy(), path)
+    }
+
+    /// Files created or removed while the index was not watched change the modification
+    /// time of their directory, a directory that no longer exists also makes the index stale
+    fn is_stale(&self, refreshed_at: SystemTime) -> bool {
+        std::iter::once(self.root.clone())
+            .chain(self.dirs.iter().map(|dir| self.root.join(dir)))
+            .any(|dir| {
+                fs::metadata(dir)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .is_none_or(|modified| modified > refreshed_at)
+            })
+    }
 
-        if entries.is_empty() {
-            break;
+    /// Walks the root directory replacing the index. If `incremental` is true files are
+    /// published as they are found, otherwise the published files are replaced at the end.
+    /// Returns false if the scan was cancelled or the scanner was dropped before finishing,
+    /// a cancelled incremental scan keeps the files it found and any other scan keeps the old index.
+    fn scan(&mut self, incremental: bool) -> bool {
+        if self.publisher.publish().is_err() {
+            return false;
         }
+        let mut dirs = BTreeSet::new();
+        let mut files = BTreeSet::new();
+
+        let mut iterator = self
+            .walker(&self.root)
+            .build()
+            .filter_map(|result| result.ok());
+
+        let mut tracked_files = Vec::new();
+        let start = Instant::now();
+
+        loop {
+            if self.token.is_cancelled() || self.scan_token.is_cancelled() {
+                if incremental {
+                    self.dirs = dirs;
+                    self.files = files;
+                }
+                return false;
+            }
+
+            let entries: Vec<_> = iterator.by_ref().take(1000).collect();
+
+            if entries.is_empty() {
+                break;
+            }
+
+            for entry in &entries {
+                if entry.depth() > 0 && entry.file_type().is_some_and(|f| f.is_dir()) {
+                    dirs.insert(self.relative(entry.path()));
+                }
+            }
 
-        tracked_files.par_extend(
-            entries
-                .par_iter()
-                .filter(|entry| entry.file_type().map(|f| f.is_file()).unwrap_or(false))
-                .filter_map(|entry| {
-                    if config.show_only_text_files {
-                        get_text_file_path(entry.path().to_path_buf())
-                    } else {
-                        Some(entry.path().to_path_buf())
+            tracked_files.par_extend(
+                entries
+                    .par_iter()
+                    .filter(|entry| self.is_indexed_file(entry))
+                    .map(|entry| self.relative(entry.path())),
+            );
+
+            if incremental {
+                self.publisher.modify(|published_files| {
+                    for file in &tracked_files {
+                        published_files.push(file.clone());
                     }
-                }),
+                });
+                if self.publisher.publish().is_err() {
+                    return false;
+                }
+            }
+
+            files.extend(tracked_files.drain(..));
+        }
+
+        self.dirs = dirs;
+        self.files = files;
+        if !incremental {
+            self.publish_all();
+            if self.publisher.publish().is_err() {
+                return false;
+            }
+        }
+
+        tracing::info!(
+            "Found {} files in {}ms",
+            self.files.len(),
+            start.elapsed().as_millis()
+        );
+        self.status.lock().unwrap().refreshed_at = Some(SystemTime::now());
+        true
+    }
+
+    fn watch(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        let debouncer = new_debouncer(
+            Duration::from_millis(500),
+            None,
+            move |result: DebounceEventResult| {
+                if let Ok(events) = result {
+                    let paths: Vec<_> = events
+                        .into_iter()
+                        .flat_map(|event| event.event.paths)
+                        .collect();
+                    let _ = tx.send(paths);
+                }
+            },
         );
+        let mut debouncer = match debouncer {
+            Ok(debouncer) => debouncer,
+            Err(err) => {
+                tracing::error!("Error starting file index watcher: {err}");
+                return;
+            }
+        };
+        if let Err(err) = debouncer.watch(&self.root, RecursiveMode::Recursive) {
+            tracing::error!("Error watching `{}`: {err}", self.root.display());
+            return;
+        }
+        self.status.lock().unwrap().watching = true;
 
-        publisher.modify(|published_files| {
-            for file in tracked_files.iter().map(|path| trim_path(&path_str, path)) {
-                published_files.push(file);
+        while !self.token.is_cancelled() {
+            match rx.recv_timeout(Duration::from_millis(500)) {
+                Ok(paths) => {
+                    if self.update(&paths) {
+                        self.publish_all();
+                        if self.publisher.publish().is_err() {
+                            break;
+                        }
+                        self.status.lock().unwrap().refreshed_at = Some(SystemTime::now());
+                        self.save();
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => (),
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        self.status.lock().unwrap().watching = false;
+    }
+
+    /// Updates the index for paths that changed on disk, returns true if the index changed
+    fn update(&mut self, paths: &[PathBuf]) -> bool {
+        let mut changed = false;
+        let mut seen = HashSet::new();
+        for path in paths {
+            if !seen.insert(path) || !path.starts_with(&self.root) || path == &self.root {
+                continue;
+            }
+
+            let relative = self.relative(path);
+            // Paths inside of ignored directories are never indexed
+            let parent = path.parent().map(|parent| self.relative(parent));
+            if parent
+                .as_ref()
+                .is_some_and(|parent| !parent.is_empty() && !self.dirs.contains(parent))
+            {
+                continue;
             }
-        });
 
-        tracked_files.clear();
+            // Content changes to indexed files do not change the index
+            if path.is_file() && self.files.contains(&relative) {
+                continue;
+            }
+
+            changed |= self.remove(&relative);
+            if path.exists() {
+                changed |= self.add(path);
+            }
+        }
+        changed
+    }
+
+    /// Removes a file or a directory and everything in it from the index
+    fn remove(&mut self, relative: &str) -> bool {
+        let files = remove_tree(&mut self.files, relative);
+        let dirs = remove_tree(&mut self.dirs, relative);
+        files || dirs
+    }
 
-        if publisher.publish().is_err() {
+    /// Adds a path to the index if it is not ignored, directories are walked recursively
+    fn add(&mut self, path: &Path) -> bool {
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+        // Walking the parent applies the ignore files of all parent directories
+        let entry = self
+            .walker(parent)
+            .max_depth(Some(1))
+            .build()
+            .filter_map(|result| result.ok())
+            .find(|entry| entry.path() == path);
+        let Some(entry) = entry else {
+            return false;
+        };
+
+        if entry.file_type().is_some_and(|f| f.is_dir()) {
+            let entries: Vec<_> = self
+                .walker(path)
+                .build()
+                .filter_map(|result| result.ok())
+                .collect();
+            for entry in entries {
+                if entry.file_type().is_some_and(|f| f.is_dir()) {
+                    self.dirs.insert(self.relative(entry.path()));
+                } else if self.is_indexed_file(&entry) {
+                    self.files.insert(self.relative(entry.path()));
+                }
+            }
+            true
+        } else if self.is_indexed_file(&entry) {
+            self.files.insert(self.relative(path));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn publish_all(&self) {
+        let mut files: Vec<_> = self.files.iter().cloned().collect();
+        files.sort_by(|lhs, rhs| lexical_sort::natural_lexical_cmp(lhs, rhs));
+        self.publisher
+            .replace(files.into_iter().collect::<boxcar::Vec<_>>());
+    }
+
+    fn save(&self) {
+        if !self.complete {
             return;
         }
+        let index = PersistedIndex {
+            root: self.root.clone(),
+            picker_config: self.picker_config,
+            files_config: self.files_config.clone(),
+            refreshed_at: self
+                .status
+                .lock()
+                .unwrap()
+                .refreshed_at
+                .unwrap_or_else(SystemTime::now),
+            dirs: self.dirs.iter().cloned().collect(),
+            files: self.files.iter().cloned().collect(),
+        };
+        if let Err(err) = index.save() {
+            tracing::error!("Error saving file index: {err}");
+        }
     }
+}
 
-    publisher.modify(|published_files| {
-        tracing::info!(
-            "Found {} files in {}ms",
-            published_files.count(),
-            start.elapsed().as_millis()
+/// Removes `relative` and every path below it from `paths`, returns true if anything was removed
+fn remove_tree(paths: &mut BTreeSet<String>, relative: &str) -> bool {
+    let mut removed = paths.remove(relative);
+    for separator in ['/', std::path::MAIN_SEPARATOR] {
+        let prefix = format!("{relative}{separator}");
+        let children: Vec<_> = paths
+            .range(prefix.clone()..)
+            .take_while(|path| path.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for child in children {
+            removed |= paths.remove(&child);
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_globs() {
+        let root = Path::new("/project");
+        let files = FilesConfig {
+            include: vec!["src/**".into()],
+            exclude: vec!["*.snap".into()],
+        };
+        let overrides = build_overrides(root, &files);
+        assert!(overrides
+            .matched(root.join("src/main.rs"), false)
+            .is_whitelist());
+        assert!(overrides
+            .matched(root.join("src/test.snap"), false)
+            .is_ignore());
+        assert!(overrides.matched(root.join("README.md"), false).is_ignore());
+
+        let overrides = build_overrides(root, &FilesConfig::default());
+        assert!(overrides.matched(root.join("README.md"), false).is_none());
+    }
+
+    #[test]
+    fn incremental_update() {
+        let dir = tempdir::TempDir::new("ferrite-index").unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let (publisher, _subscriber) = pubsub::create(boxcar::Vec::new());
+        let mut indexer = Indexer {
+            publisher,
+            overrides: Override::empty(),
+            root: root.clone(),
+            picker_config: PickerConfig::default(),
+            files_config: FilesConfig::default(),
+            token: CancellationToken::new(),
+            scan_token: CancellationToken::new(),
+            status: Arc::new(Mutex::new(IndexStatus {
+                root: root.clone(),
+                refreshed_at: None,
+                scanning: false,
+                watching: false,
+                from_cache: false,
+                cancelled: false,
+            })),
+            dirs: BTreeSet::new(),
+            files: BTreeSet::new(),
+            complete: false,
+        };
+        assert!(indexer.scan(true));
+        assert_eq!(
+            indexer.files,
+            BTreeSet::from([format!("src{}main.rs", std::path::MAIN_SEPARATOR)])
         );
-    })
+        assert!(!indexer.is_stale(SystemTime::now() + Duration::from_secs(60)));
+
+        std::fs::create_dir(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/index.md"), "# Docs").unwrap();
+        std::fs::remove_file(root.join("src/main.rs")).unwrap();
+        assert!(indexer.update(&[root.join("docs"), root.join("src/main.rs")]));
+        assert_eq!(
+            indexer.files,
+            BTreeSet::from([format!("docs{}index.md", std::path::MAIN_SEPARATOR)])
+        );
+
+        std::fs::remove_dir_all(root.join("docs")).unwrap();
+        assert!(indexer.is_stale(SystemTime::now() + Duration::from_secs(60)));
+        assert!(indexer.update(&[root.join("docs")]));
+        assert!(indexer.files.is_empty());
+        assert!(!indexer.dirs.contains("docs"));
+
+        // A cancelled rescan keeps the index it was replacing
+        indexer.files.insert(String::from("cached.rs"));
+        indexer.scan_token.cancel();
+        assert!(!indexer.scan(false));
+        assert!(indexer.files.contains("cached.rs"));
+    }
 }
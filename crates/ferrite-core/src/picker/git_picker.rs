@@ -0,0 +1,132 @@
+use std::{borrow::Cow, path::PathBuf, sync::Arc};
+
+use super::{Matchable, PickerOptionProvider, Preview, Previewer};
+use crate::{
+    buffer::Buffer,
+    event_loop_proxy::EventLoopProxy,
+    git::{
+        self,
+        log::{self, Branch, Commit},
+    },
+};
+
+/// What the git picker lists, along with the repository it was listed from
+#[derive(Debug, Clone)]
+pub enum GitPicker {
+    Branches { root: PathBuf },
+    Log { root: PathBuf, path: String },
+}
+
+/// What a git picker job loaded, git is never run on the main thread
+pub enum GitPickerOutput {
+    /// The items of a picker that is opened when the job is done
+    Items(GitPicker, Vec<GitItem>),
+    /// A file at the revision that was chosen in the log picker
+    Revision {
+        path: String,
+        hash: String,
+        text: String,
+    },
+}
+
+pub struct GitProvider(pub Arc<boxcar::Vec<GitItem>>);
+
+impl PickerOptionProvider for GitProvider {
+    type Matchable = GitItem;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let _ = tx.send(self.0.clone());
+        rx
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GitItem {
+    Branch(Branch),
+    Commit(Commit),
+}
+
+impl GitItem {
+    fn key(&self) -> &str {
+        match self {
+            GitItem::Branch(branch) => &branch.name,
+            GitItem::Commit(commit) => &commit.hash,
+        }
+    }
+}
+
+impl Matchable for GitItem {
+    fn as_match_str(&self) -> Cow<'_, str> {
+        match self {
+            GitItem::Branch(branch) => Cow::Borrowed(&branch.name),
+            GitItem::Commit(commit) => format!("{} {}", commit.hash, commit.summary).into(),
+        }
+    }
+
+    fn display(&self) -> Cow<'_, str> {
+        match self {
+            GitItem::Branch(branch) if branch.current => format!("* {}", branch.name).into(),
+            GitItem::Branch(branch) => format!("  {}", branch.name).into(),
+            GitItem::Commit(commit) => format!(
+                "{} {} ({}, {})",
+                commit.hash, commit.summary, commit.author, commit.date
+            )
+            .into(),
+        }
+    }
+}
+
+/// Shows the recent commits of a branch or the changes a commit made to the file
+pub struct GitPreviewer {
+    picker: GitPicker,
+    proxy: Box<dyn EventLoopProxy>,
+    current: Option<(String, Buffer)>,
+}
+
+impl GitPreviewer {
+    pub fn new(picker: GitPicker, proxy: Box<dyn EventLoopProxy>) -> Self {
+        Self {
+            picker,
+            proxy,
+            current: None,
+        }
+    }
+}
+
+impl Previewer<GitItem> for GitPreviewer {
+    fn request_preview(&mut self, m: &GitItem) -> Preview<'_> {
+        if !matches!(&self.current, Some((key, _)) if key == m.key()) {
+            let (text, language) = match (&self.picker, m) {
+                (GitPicker::Branches { root }, GitItem::Branch(branch)) => (
+                    git::run(
+                        root,
+                        &["log", "--oneline", "--max-count=100", &branch.name, "--"],
+                        None,
+                    ),
+                    None,
+                ),
+                (GitPicker::Log { root, path }, GitItem::Commit(commit)) => {
+                    (log::show_commit(root, &commit.hash, path), Some("diff"))
+                }
+                _ => return Preview::Err,
+            };
+            let Ok(text) = text else {
+                return Preview::Err;
+            };
+            let mut buffer = Buffer::with_text(&text);
+            buffer.set_proxy(self.proxy.dup());
+            if let Some(language) = language {
+                if let Err(err) = buffer.set_langauge(language) {
+                    tracing::error!("Error setting language: {err}");
+                }
+            }
+            buffer.read_only = true;
+            self.current = Some((m.key().to_string(), buffer));
+        }
+        match &mut self.current {
+            Some((_, buffer)) => Preview::Buffer(buffer),
+            None => Preview::Err,
+        }
+    }
+}
@@ -1,24 +1,37 @@
 use std::{
     borrow::Cow,
+    collections::HashMap,
     io::Read,
     iter::Peekable,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
     ptr,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     thread,
 };
 
 use ferrite_utility::{graphemes::RopeGraphemeExt, point::Point};
 use grep_matcher::Matcher as _;
-use grep_regex::RegexMatcherBuilder;
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
 use grep_searcher::{sinks::UTF8, Searcher};
-use ignore::{WalkBuilder, WalkState};
+use ignore::overrides::Override;
 use ropey::{iter::Chunks, Rope};
 
-use super::{file_previewer::is_text_file, Matchable, PickerOptionProvider};
+use super::{
+    file_previewer::{is_text_file, FilePreviewer},
+    file_scanner::build_overrides,
+    Matchable, PickerOptionProvider,
+};
 use crate::{
-    buffer::Buffer,
-    config::editor::PickerConfig,
+    buffer::{read, search::SearchOptions},
+    config::editor::FilesConfig,
+    event_loop_proxy::EventLoopProxy,
+    job_manager::{CancellationToken, JobHandle, JobInfo, JobManager, Progress, Progressor},
     picker::{Preview, Previewer},
+    tr,
 };
 
 struct RopeReader<'a> {
@@ -61,135 +74,211 @@ impl Read for RopeReader<'_> {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Number of files worth of matches that can be buffered before the search workers have to wait
+const RESULT_CHANNEL_CAPACITY: usize = 256;
 
-    #[test]
-    fn rope_reader() {
-        let text = include_str!("../../../../Cargo.toml");
-        let rope = Rope::from(text);
-        let mut buffer = Vec::new();
-        let mut reader = RopeReader::new(&rope);
-        let _ = reader.read_to_end(&mut buffer);
-        assert_eq!(rope.to_string().as_bytes(), buffer);
-    }
+struct SearchInput {
+    query: String,
+    root: PathBuf,
+    files: Arc<boxcar::Vec<String>>,
+    scope: Option<String>,
+    options: SearchOptions,
+    open_buffers: HashMap<PathBuf, Rope>,
 }
 
+/// Searches the files of the file index on a pool of worker threads.
+/// Matches are streamed to the picker as they are found and the search is cancelled when the provider is dropped.
 pub struct GlobalSearchProvider {
-    output: Arc<boxcar::Vec<GlobalSearchMatch>>,
-    config: PickerConfig,
-    case_insenstive: bool,
-    query: String,
+    job: Mutex<Option<JobHandle<(), Vec<GlobalSearchMatch>>>>,
+    token: CancellationToken,
 }
 
 impl GlobalSearchProvider {
-    pub fn new(query: String, config: PickerConfig, case_insenstive: bool) -> Self {
+    /// `files` are paths relative to `root`, usually from the file index.
+    /// Files in `open_buffers` are searched using the text of the open buffer instead of the file on disk.
+    /// If `scope` is set only files matching that glob are searched.
+    pub fn new(
+        job_manager: &mut JobManager,
+        query: String,
+        root: PathBuf,
+        files: Arc<boxcar::Vec<String>>,
+        scope: Option<String>,
+        options: SearchOptions,
+        open_buffers: HashMap<PathBuf, Rope>,
+    ) -> Self {
+        let job = job_manager.spawn_bounded_job(
+            JobInfo::new(tr!("job.search", query = query)).cancellable(),
+            search_files,
+            SearchInput {
+                query,
+                root,
+                files,
+                scope,
+                options,
+                open_buffers,
+            },
+            RESULT_CHANNEL_CAPACITY,
+        );
         Self {
-            output: Arc::new(boxcar::Vec::new()),
-            config,
-            case_insenstive,
-            query,
+            token: job.cancellation_token(),
+            job: Mutex::new(Some(job)),
         }
     }
 }
 
+impl Drop for GlobalSearchProvider {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
 impl PickerOptionProvider for GlobalSearchProvider {
     type Matchable = GlobalSearchMatch;
 
     fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
-        let (tx, rx) = cb::unbounded();
-        let case_insenstive = self.case_insenstive;
-        let query = self.query.clone();
-        let config = self.config;
-        let output = self.output.clone();
+        let (tx, rx) = cb::bounded(1);
+        let Some(mut job) = self.job.lock().unwrap().take() else {
+            return rx;
+        };
 
         thread::spawn(move || {
-            let matcher = RegexMatcherBuilder::new()
-                .fixed_strings(true)
-                .multi_line(false)
-                .case_insensitive(case_insenstive)
-                .build(&query)
-                .unwrap();
-
-            let mut builder = WalkBuilder::new(std::env::current_dir().unwrap());
-            let walk_parallel = builder
-                .follow_links(false)
-                .ignore(config.follow_ignore)
-                .git_global(config.follow_git_global)
-                .git_ignore(config.follow_gitignore)
-                .git_exclude(config.follow_git_exclude)
-                .build_parallel();
-
-            walk_parallel.run(move || {
-                let matcher = matcher.clone();
-                let output = output.clone();
-                let tx = tx.clone();
-
-                Box::new(move |result| {
-                    let dir_entry = match result {
-                        Ok(entry) => {
-                            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
-                                return WalkState::Continue;
-                            }
-                            entry
-                        }
-                        Err(_) => return WalkState::Continue,
-                    };
-
-                    let path = dir_entry.path();
-                    if !is_text_file(path).unwrap_or(false) {
-                        return WalkState::Continue;
-                    }
-                    let Ok(mut buffer) = Buffer::from_file(path) else {
-                        return WalkState::Continue;
-                    };
-
-                    let view_id = buffer.create_view();
-                    buffer.views[view_id].clamp_cursor = true;
-                    let name = buffer.name().to_string();
-                    let rope = buffer.rope().clone();
-                    let buffer = Arc::new(Mutex::new(buffer));
-
-                    if let Err(err) = Searcher::new().search_reader(
-                        &matcher,
-                        RopeReader::new(&rope.clone()),
-                        UTF8(|lnum, line| {
-                            if let Some(mymatch) = matcher.find(line.as_bytes())? {
-                                let lnum = lnum as usize - 1;
-                                let rope_line = rope.line(lnum);
-                                let start_col = rope_line.byte_to_col(mymatch.start());
-                                let end_col = rope_line.byte_to_col(mymatch.end());
-                                output.push(GlobalSearchMatch {
-                                    buffer: buffer.clone(),
-                                    name: name.clone(),
-                                    line: rope_line.trim_start_whitespace().to_string(),
-                                    match_location: (
-                                        Point::new(start_col, lnum),
-                                        Point::new(end_col, lnum),
-                                    ),
-                                });
-                                let _ = tx.send(output.clone());
-                            }
-                            Ok(true)
-                        }),
-                    ) {
-                        tracing::error!("Search error: {err}");
+            let output = Arc::new(boxcar::Vec::new());
+            while let Ok(matches) = job.recv_progress() {
+                for m in matches {
+                    output.push(m);
+                }
+                while let Ok(Progress::Progress(matches)) = job.poll_progress() {
+                    for m in matches {
+                        output.push(m);
                     }
-
-                    WalkState::Continue
-                })
-            });
+                }
+                if tx.send(output.clone()).is_err() {
+                    job.cancel();
+                    break;
+                }
+            }
         });
 
         rx
     }
 }
 
+fn search_files(
+    token: CancellationToken,
+    progressor: &mut Progressor<Vec<GlobalSearchMatch>>,
+    input: SearchInput,
+) {
+    let SearchInput {
+        query,
+        root,
+        files,
+        scope,
+        options,
+        open_buffers,
+    } = input;
+
+    let matcher = RegexMatcherBuilder::new()
+        .fixed_strings(true)
+        .multi_line(false)
+        .case_insensitive(options.ignore_case(&query))
+        .word(options.whole_word)
+        .build(&query)
+        .unwrap();
+
+    let scope = match scope {
+        Some(glob) => build_overrides(
+            &root,
+            &FilesConfig {
+                include: vec![glob],
+                exclude: Vec::new(),
+            },
+        ),
+        None => Override::empty(),
+    };
+
+    let next_file = AtomicUsize::new(0);
+    let workers = thread::available_parallelism().map_or(4, NonZeroUsize::get);
+    // Workers only borrow the shared state, each of them gets its own progressor
+    let (root, files, open_buffers) = (&root, &files, &open_buffers);
+    let (token, next_file, scope, matcher) = (&token, &next_file, &scope, &matcher);
+    thread::scope(|s| {
+        for _ in 0..workers {
+            let mut progressor = progressor.clone();
+            s.spawn(move || loop {
+                if token.is_cancelled() {
+                    break;
+                }
+                let index = next_file.fetch_add(1, Ordering::Relaxed);
+                let Some(file) = files.get(index) else {
+                    break;
+                };
+                progressor.report(index as u64 + 1, files.count() as u64, "");
+                let path = root.join(file);
+                if scope.matched(&path, false).is_ignore() {
+                    continue;
+                }
+                let matches = search_file(matcher, &path, open_buffers.get(&path));
+                if !matches.is_empty() {
+                    progressor.make_progress(matches);
+                }
+            });
+        }
+    });
+}
+
+pub(crate) fn search_file(
+    matcher: &RegexMatcher,
+    path: &Path,
+    open_buffer: Option<&Rope>,
+) -> Vec<GlobalSearchMatch> {
+    let rope = match open_buffer {
+        Some(rope) => rope.clone(),
+        None => {
+            if !is_text_file(path).unwrap_or(false) {
+                return Vec::new();
+            }
+            let Ok((_, rope)) = read::read_from_file(path) else {
+                return Vec::new();
+            };
+            rope
+        }
+    };
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut matches = Vec::new();
+    if let Err(err) = Searcher::new().search_reader(
+        matcher,
+        RopeReader::new(&rope),
+        UTF8(|lnum, line| {
+            if let Some(mymatch) = matcher.find(line.as_bytes())? {
+                let lnum = lnum as usize - 1;
+                let rope_line = rope.line(lnum);
+                let start_col = rope_line.byte_to_col(mymatch.start());
+                let end_col = rope_line.byte_to_col(mymatch.end());
+                matches.push(GlobalSearchMatch {
+                    path: path.to_path_buf(),
+                    name: name.clone(),
+                    line: rope_line.to_string(),
+                    match_location: (Point::new(start_col, lnum), Point::new(end_col, lnum)),
+                });
+            }
+            Ok(true)
+        }),
+    ) {
+        tracing::error!("Search error: {err}");
+    }
+    matches
+}
+
 #[derive(Clone)]
 pub struct GlobalSearchMatch {
-    pub buffer: Arc<Mutex<Buffer>>,
+    pub path: PathBuf,
     pub name: String,
+    /// The whole line including indentation, it is only trimmed for display
     pub line: String,
     pub match_location: (Point<usize>, Point<usize>),
 }
@@ -202,23 +291,71 @@ impl Matchable for GlobalSearchMatch {
     fn display(&self) -> Cow<str> {
         format!(
             "{}:{}: {}",
-            self.name, self.match_location.0.line, self.line
+            self.name,
+            self.match_location.0.line,
+            self.line.trim_start()
         )
         .into()
     }
 }
 
-pub struct GlobalSearchPreviewer;
+pub struct GlobalSearchPreviewer {
+    previewer: FilePreviewer,
+}
+
+impl GlobalSearchPreviewer {
+    pub fn new(proxy: Box<dyn EventLoopProxy>, open_buffers: HashMap<PathBuf, Rope>) -> Self {
+        Self {
+            previewer: FilePreviewer::new(proxy, open_buffers),
+        }
+    }
+}
+
 impl Previewer<GlobalSearchMatch> for GlobalSearchPreviewer {
     fn request_preview(&mut self, m: &GlobalSearchMatch) -> Preview {
-        {
-            let mut guard = m.buffer.lock().unwrap();
-            let (start, end) = m.match_location;
-            let view_id = guard.get_first_view().unwrap();
-            guard.select_area(view_id, start, end, false);
-            guard.views[view_id].clamp_cursor = true;
-            guard.center_on_cursor(view_id);
+        match self.previewer.preview_path(&m.path) {
+            Preview::Buffer(buffer) => {
+                let (start, end) = m.match_location;
+                let view_id = buffer.get_first_view_or_create();
+                buffer.select_area(view_id, start, end, false);
+                buffer.views[view_id].clamp_cursor = true;
+                buffer.center_on_cursor(view_id);
+                Preview::Buffer(buffer)
+            }
+            preview => preview,
         }
-        Preview::SharedBuffer(m.buffer.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rope_reader() {
+        let text = include_str!("../../../../Cargo.toml");
+        let rope = Rope::from(text);
+        let mut buffer = Vec::new();
+        let mut reader = RopeReader::new(&rope);
+        let _ = reader.read_to_end(&mut buffer);
+        assert_eq!(rope.to_string().as_bytes(), buffer);
+    }
+
+    #[test]
+    fn search_open_buffer() {
+        let matcher = RegexMatcherBuilder::new()
+            .fixed_strings(true)
+            .build("foo")
+            .unwrap();
+        let rope = Rope::from("foo\nbar\n  a foo\n");
+        let matches = search_file(&matcher, Path::new("src/a.rs"), Some(&rope));
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[1].name, "a.rs");
+        assert_eq!(matches[1].line, "  a foo\n");
+        assert_eq!(matches[1].display(), "a.rs:2: a foo\n");
+        assert_eq!(
+            matches[1].match_location,
+            (Point::new(4, 2), Point::new(7, 2))
+        );
     }
 }
@@ -0,0 +1,57 @@
+use std::{borrow::Cow, sync::Arc};
+
+use super::{Matchable, PickerOptionProvider, Preview, Previewer};
+use crate::{buffer::Buffer, identifier_stats::VariantStats};
+
+pub struct IdentifierStatsProvider(pub Arc<boxcar::Vec<VariantStats>>);
+
+impl PickerOptionProvider for IdentifierStatsProvider {
+    type Matchable = VariantStats;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let _ = tx.send(self.0.clone());
+        rx
+    }
+}
+
+impl Matchable for VariantStats {
+    fn as_match_str(&self) -> Cow<'_, str> {
+        self.text.as_str().into()
+    }
+
+    fn display(&self) -> Cow<'_, str> {
+        format!(
+            "{:>6}  {}  ({})",
+            self.count,
+            self.text,
+            self.cases.join(", ")
+        )
+        .into()
+    }
+}
+
+/// Shows how many times the selected variant occurs in each file
+#[derive(Default)]
+pub struct IdentifierStatsPreviewer {
+    current: Option<(String, Buffer)>,
+}
+
+impl Previewer<VariantStats> for IdentifierStatsPreviewer {
+    fn request_preview(&mut self, m: &VariantStats) -> Preview<'_> {
+        if !matches!(&self.current, Some((text, _)) if *text == m.text) {
+            let report: String = m
+                .files
+                .iter()
+                .map(|(file, count)| format!("{count:>6}  {file}\n"))
+                .collect();
+            let mut buffer = Buffer::with_text(&report);
+            buffer.read_only = true;
+            self.current = Some((m.text.clone(), buffer));
+        }
+        match &mut self.current {
+            Some((_, buffer)) => Preview::Buffer(buffer),
+            None => Preview::Err,
+        }
+    }
+}
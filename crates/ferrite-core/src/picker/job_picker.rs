@@ -0,0 +1,56 @@
+use std::{borrow::Cow, sync::Arc};
+
+use super::{Matchable, PickerOptionProvider};
+use crate::job_manager::{JobId, JobStatus};
+
+pub struct JobProvider(pub Arc<boxcar::Vec<JobItem>>);
+
+impl PickerOptionProvider for JobProvider {
+    type Matchable = JobItem;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let _ = tx.send(self.0.clone());
+        rx
+    }
+}
+
+/// Something running in the background that can be cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobTarget {
+    Job(JobId),
+    /// The walk of the workspace by the file index
+    Indexing,
+}
+
+#[derive(Debug, Clone)]
+pub struct JobItem {
+    pub target: JobTarget,
+    label: String,
+}
+
+impl JobItem {
+    pub fn new(job: &JobStatus) -> Self {
+        Self {
+            target: JobTarget::Job(job.id),
+            label: job.summary(),
+        }
+    }
+
+    pub fn indexing(label: String) -> Self {
+        Self {
+            target: JobTarget::Indexing,
+            label,
+        }
+    }
+}
+
+impl Matchable for JobItem {
+    fn as_match_str(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+
+    fn display(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+}
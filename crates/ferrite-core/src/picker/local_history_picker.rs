@@ -0,0 +1,90 @@
+use std::{borrow::Cow, path::PathBuf, sync::Arc};
+
+use super::{Matchable, PickerOptionProvider, Preview, Previewer};
+use crate::{
+    buffer::Buffer, diff, engine::format_elapsed, event_loop_proxy::EventLoopProxy,
+    local_history::Snapshot,
+};
+
+pub struct LocalHistoryProvider(pub Arc<boxcar::Vec<SnapshotItem>>);
+
+impl PickerOptionProvider for LocalHistoryProvider {
+    type Matchable = SnapshotItem;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let _ = tx.send(self.0.clone());
+        rx
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SnapshotItem {
+    pub snapshot: Snapshot,
+    label: String,
+}
+
+impl SnapshotItem {
+    pub fn new(snapshot: Snapshot) -> Self {
+        let elapsed = snapshot.time.elapsed().unwrap_or_default();
+        Self {
+            label: format!("{} ago", format_elapsed(elapsed)),
+            snapshot,
+        }
+    }
+}
+
+impl Matchable for SnapshotItem {
+    fn as_match_str(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+
+    fn display(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+}
+
+/// Shows a diff between the current text of the buffer and the selected snapshot
+pub struct LocalHistoryPreviewer {
+    current: String,
+    name: String,
+    proxy: Box<dyn EventLoopProxy>,
+    preview: Option<(PathBuf, Buffer)>,
+}
+
+impl LocalHistoryPreviewer {
+    pub fn new(current: String, name: String, proxy: Box<dyn EventLoopProxy>) -> Self {
+        Self {
+            current,
+            name,
+            proxy,
+            preview: None,
+        }
+    }
+}
+
+impl Previewer<SnapshotItem> for LocalHistoryPreviewer {
+    fn request_preview(&mut self, m: &SnapshotItem) -> Preview<'_> {
+        if !matches!(&self.preview, Some((path, _)) if *path == m.snapshot.path) {
+            let Ok(snapshot) = m.snapshot.read() else {
+                return Preview::Err;
+            };
+            let mut buffer = Buffer::with_text(&diff::unified_diff(
+                &self.current,
+                &snapshot,
+                &self.name,
+                "snapshot",
+            ));
+            buffer.set_proxy(self.proxy.dup());
+            if let Err(err) = buffer.set_langauge("diff") {
+                tracing::error!("Error setting language: {err}");
+            }
+            buffer.read_only = true;
+            self.preview = Some((m.snapshot.path.clone(), buffer));
+        }
+        match &mut self.preview {
+            Some((_, buffer)) => Preview::Buffer(buffer),
+            None => Preview::Err,
+        }
+    }
+}
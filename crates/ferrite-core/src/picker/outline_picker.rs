@@ -0,0 +1,71 @@
+use std::{borrow::Cow, sync::Arc};
+
+use ferrite_utility::point::Point;
+
+use super::{Matchable, PickerOptionProvider, Preview, Previewer};
+use crate::{buffer::Buffer, language::outline::Symbol};
+
+pub struct OutlineProvider(pub Arc<boxcar::Vec<OutlineItem>>);
+
+impl PickerOptionProvider for OutlineProvider {
+    type Matchable = OutlineItem;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let _ = tx.send(self.0.clone());
+        rx
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutlineItem {
+    pub symbol: Symbol,
+    label: String,
+}
+
+impl OutlineItem {
+    pub fn new(symbol: Symbol) -> Self {
+        Self {
+            label: format!(
+                "{}{} {}",
+                "  ".repeat(symbol.depth),
+                symbol.kind,
+                symbol.name
+            ),
+            symbol,
+        }
+    }
+}
+
+impl Matchable for OutlineItem {
+    fn as_match_str(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.symbol.name)
+    }
+
+    fn display(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+}
+
+/// Shows the buffer the outline was made from centered on the selected symbol
+pub struct OutlinePreviewer {
+    buffer: Buffer,
+}
+
+impl OutlinePreviewer {
+    pub fn new(mut buffer: Buffer) -> Self {
+        buffer.read_only = true;
+        Self { buffer }
+    }
+}
+
+impl Previewer<OutlineItem> for OutlinePreviewer {
+    fn request_preview(&mut self, m: &OutlineItem) -> Preview<'_> {
+        let view_id = self.buffer.get_first_view_or_create();
+        let point = Point::new(0, m.symbol.line);
+        self.buffer.select_area(view_id, point, point, false);
+        self.buffer.views[view_id].clamp_cursor = true;
+        self.buffer.center_on_cursor(view_id);
+        Preview::Buffer(&mut self.buffer)
+    }
+}
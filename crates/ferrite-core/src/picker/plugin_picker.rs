@@ -0,0 +1,55 @@
+use std::{borrow::Cow, sync::Arc};
+
+use super::{Matchable, PickerOptionProvider};
+use crate::plugins::Plugin;
+
+pub struct PluginProvider(pub Arc<boxcar::Vec<PluginItem>>);
+
+impl PickerOptionProvider for PluginProvider {
+    type Matchable = PluginItem;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let _ = tx.send(self.0.clone());
+        rx
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginItem {
+    pub name: String,
+    status: String,
+}
+
+impl PluginItem {
+    pub fn new(name: &str, plugin: &Plugin) -> Self {
+        let status = if !plugin.enabled {
+            String::from("disabled")
+        } else if let Some(error) = &plugin.error {
+            format!("failed: {error}")
+        } else if plugin.is_running() {
+            let commands: Vec<_> = plugin
+                .commands
+                .iter()
+                .map(|cmd| cmd.name.as_str())
+                .collect();
+            format!("running  {}", commands.join(", "))
+        } else {
+            String::from("stopped")
+        };
+        Self {
+            name: name.to_string(),
+            status,
+        }
+    }
+}
+
+impl Matchable for PluginItem {
+    fn as_match_str(&self) -> Cow<'_, str> {
+        self.name.as_str().into()
+    }
+
+    fn display(&self) -> Cow<'_, str> {
+        format!("{}  {}", self.name, self.status).into()
+    }
+}
@@ -0,0 +1,16 @@
+use std::sync::Arc;
+
+use super::PickerOptionProvider;
+
+/// Provides a fixed list of paths ordered from most to least recently used
+pub struct RecentProvider(pub Arc<boxcar::Vec<String>>);
+
+impl PickerOptionProvider for RecentProvider {
+    type Matchable = String;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let _ = tx.send(self.0.clone());
+        rx
+    }
+}
@@ -0,0 +1,53 @@
+use std::{borrow::Cow, sync::Arc};
+
+use super::{Matchable, PickerOptionProvider, Preview, Previewer};
+use crate::buffer::Buffer;
+
+pub struct RegisterProvider(pub Arc<boxcar::Vec<RegisterItem>>);
+
+impl PickerOptionProvider for RegisterProvider {
+    type Matchable = RegisterItem;
+
+    fn get_options_reciver(&self) -> cb::Receiver<Arc<boxcar::Vec<Self::Matchable>>> {
+        let (tx, rx) = cb::bounded(1);
+        let _ = tx.send(self.0.clone());
+        rx
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RegisterItem {
+    pub name: char,
+    pub text: String,
+}
+
+impl Matchable for RegisterItem {
+    fn as_match_str(&self) -> Cow<'_, str> {
+        self.name.to_string().into()
+    }
+
+    fn display(&self) -> Cow<'_, str> {
+        let first_line = self.text.lines().next().unwrap_or_default();
+        format!("{}  {first_line}", self.name).into()
+    }
+}
+
+/// Shows the full contents of the selected register
+#[derive(Default)]
+pub struct RegisterPreviewer {
+    current: Option<(char, Buffer)>,
+}
+
+impl Previewer<RegisterItem> for RegisterPreviewer {
+    fn request_preview(&mut self, m: &RegisterItem) -> Preview<'_> {
+        if !matches!(&self.current, Some((name, _)) if *name == m.name) {
+            let mut buffer = Buffer::with_text(&m.text);
+            buffer.read_only = true;
+            self.current = Some((m.name, buffer));
+        }
+        match &mut self.current {
+            Some((_, buffer)) => Preview::Buffer(buffer),
+            None => Preview::Err,
+        }
+    }
+}
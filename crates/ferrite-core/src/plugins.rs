@@ -0,0 +1,366 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{BufRead, BufReader, Write},
+    process::{Child, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ferrite_plugin::{
+    read_message, write_message, BufferEventKind, BufferInfo, Message, Notification, Request,
+    Response, PROTOCOL_VERSION,
+};
+use indexmap::IndexMap;
+use slotmap::{Key, KeyData};
+
+use crate::{
+    buffer::Buffer,
+    config::editor::PluginConfig,
+    engine::get_exec,
+    event_loop_proxy::EventLoopProxy,
+    palette::cmd_parser::{get_command_names, lexer},
+    workspace::BufferId,
+};
+
+/// How long a plugin gets to exit on its own after being told to shut down
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct PluginCommand {
+    pub name: String,
+    pub description: String,
+}
+
+pub struct Plugin {
+    pub config: PluginConfig,
+    pub enabled: bool,
+    /// Why the plugin is not running
+    pub error: Option<String>,
+    pub commands: Vec<PluginCommand>,
+    subscriptions: HashSet<BufferEventKind>,
+    process: Option<PluginProcess>,
+}
+
+impl Plugin {
+    pub fn is_running(&self) -> bool {
+        self.process.is_some()
+    }
+}
+
+struct PluginProcess {
+    child: Child,
+    /// Serialized messages written to the stdin of the plugin by a writer thread,
+    /// so a plugin that does not read its input cannot block the editor
+    stdin_tx: cb::Sender<Vec<u8>>,
+    rx: cb::Receiver<Message<Request>>,
+}
+
+impl PluginProcess {
+    fn spawn(cmd: &str, proxy: Box<dyn EventLoopProxy>) -> std::io::Result<Self> {
+        let mut command = get_exec(cmd);
+        command.stdin(Stdio::piped());
+        command.stdout(Stdio::piped());
+        command.stderr(Stdio::piped());
+        let mut child = command.spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let stderr = BufReader::new(child.stderr.take().unwrap());
+
+        let (tx, rx) = cb::unbounded();
+        thread::spawn(move || loop {
+            match read_message(&mut stdout) {
+                Ok(Some(message)) => {
+                    if tx.send(message).is_err() {
+                        break;
+                    }
+                    proxy.request_render();
+                }
+                Ok(None) => {
+                    // Wake the engine so it notices the plugin has exited
+                    proxy.request_render();
+                    break;
+                }
+                Err(err) => tracing::error!("Invalid message from plugin: {err}"),
+            }
+        });
+        thread::spawn(move || {
+            for line in stderr.lines().map_while(Result::ok) {
+                tracing::info!("plugin: {line}");
+            }
+        });
+        // Stdin is closed once the sender is dropped and every message has been written
+        let (stdin_tx, stdin_rx) = cb::unbounded::<Vec<u8>>();
+        thread::spawn(move || {
+            for message in stdin_rx {
+                if let Err(err) = stdin.write_all(&message).and_then(|_| stdin.flush()) {
+                    tracing::error!("Error writing to plugin: {err}");
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin_tx,
+            rx,
+        })
+    }
+
+    fn send<T: serde::Serialize>(&self, message: &T) {
+        let mut bytes = Vec::new();
+        if let Err(err) = write_message(&mut bytes, message) {
+            tracing::error!("Error serializing plugin message: {err}");
+            return;
+        }
+        // Sending only fails after the writer thread has stopped on a write error
+        let _ = self.stdin_tx.send(bytes);
+    }
+
+    fn shutdown(self) {
+        self.send(&Message::notification(Notification::Shutdown));
+        drop(self.stdin_tx);
+        let mut child = self.child;
+        thread::spawn(move || {
+            let start = Instant::now();
+            while start.elapsed() < SHUTDOWN_GRACE {
+                if !matches!(child.try_wait(), Ok(None)) {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        });
+    }
+}
+
+/// Starts, stops and talks to the plugins from the config
+pub struct PluginManager {
+    plugins: IndexMap<String, Plugin>,
+    last_edits: HashMap<BufferId, Instant>,
+    proxy: Box<dyn EventLoopProxy>,
+}
+
+impl PluginManager {
+    pub fn new(configs: &IndexMap<String, PluginConfig>, proxy: Box<dyn EventLoopProxy>) -> Self {
+        let mut manager = Self {
+            plugins: IndexMap::new(),
+            last_edits: HashMap::new(),
+            proxy,
+        };
+        for (name, config) in configs {
+            manager.plugins.insert(
+                name.clone(),
+                Plugin {
+                    config: config.clone(),
+                    enabled: config.enabled,
+                    error: None,
+                    commands: Vec::new(),
+                    subscriptions: HashSet::new(),
+                    process: None,
+                },
+            );
+            if config.enabled {
+                manager.start(name);
+            }
+        }
+        manager
+    }
+
+    pub fn plugins(&self) -> impl Iterator<Item = (&String, &Plugin)> {
+        self.plugins.iter()
+    }
+
+    fn start(&mut self, name: &str) {
+        let Some(plugin) = self.plugins.get_mut(name) else {
+            return;
+        };
+        match PluginProcess::spawn(&plugin.config.cmd, self.proxy.dup()) {
+            Ok(mut process) => {
+                process.send(&Message::notification(Notification::Initialize {
+                    protocol_version: PROTOCOL_VERSION,
+                }));
+                plugin.process = Some(process);
+                plugin.error = None;
+            }
+            Err(err) => {
+                tracing::error!("Error starting plugin `{name}`: {err}");
+                plugin.error = Some(err.to_string());
+            }
+        }
+    }
+
+    fn stop(&mut self, name: &str) {
+        let Some(plugin) = self.plugins.get_mut(name) else {
+            return;
+        };
+        plugin.commands.clear();
+        plugin.subscriptions.clear();
+        if let Some(process) = plugin.process.take() {
+            process.shutdown();
+        }
+    }
+
+    /// Enables or disables a plugin for this session, returns true if it is now enabled
+    pub fn toggle(&mut self, name: &str) -> bool {
+        let Some(plugin) = self.plugins.get_mut(name) else {
+            return false;
+        };
+        plugin.enabled = !plugin.enabled;
+        let enabled = plugin.enabled;
+        if enabled {
+            self.start(name);
+        } else {
+            self.stop(name);
+        }
+        enabled
+    }
+
+    /// Handles requests that only concern the manager and returns the rest
+    /// along with the name of the plugin that sent them
+    pub fn poll(&mut self) -> Vec<(String, Message<Request>)> {
+        let mut requests = Vec::new();
+        for (name, plugin) in &mut self.plugins {
+            let Some(process) = &mut plugin.process else {
+                continue;
+            };
+            let mut exited = false;
+            loop {
+                let message = match process.rx.try_recv() {
+                    Ok(message) => message,
+                    Err(cb::TryRecvError::Empty) => break,
+                    Err(cb::TryRecvError::Disconnected) => {
+                        exited = true;
+                        break;
+                    }
+                };
+                let result = match &message.body {
+                    Request::RegisterCommand { name, description } => {
+                        if get_command_names().contains(&name.as_str()) {
+                            Err(format!("`{name}` is a builtin command"))
+                        } else {
+                            plugin.commands.retain(|command| command.name != *name);
+                            plugin.commands.push(PluginCommand {
+                                name: name.clone(),
+                                description: description.clone(),
+                            });
+                            Ok(serde_json::Value::Null)
+                        }
+                    }
+                    Request::Subscribe { events } => {
+                        plugin.subscriptions.extend(events);
+                        Ok(serde_json::Value::Null)
+                    }
+                    _ => {
+                        requests.push((name.clone(), message));
+                        continue;
+                    }
+                };
+                if let Some(id) = message.id {
+                    process.send(&Response::new(id, result));
+                }
+            }
+            if exited {
+                tracing::error!("Plugin `{name}` exited");
+                plugin.error = Some(String::from("exited"));
+                plugin.commands.clear();
+                plugin.subscriptions.clear();
+                if let Some(mut process) = plugin.process.take() {
+                    let _ = process.child.wait();
+                }
+            }
+        }
+        requests
+    }
+
+    pub fn respond(&mut self, plugin: &str, id: u64, result: Result<serde_json::Value, String>) {
+        if let Some(process) = self
+            .plugins
+            .get_mut(plugin)
+            .and_then(|plugin| plugin.process.as_mut())
+        {
+            process.send(&Response::new(id, result));
+        }
+    }
+
+    pub fn buffer_event(&mut self, event: BufferEventKind, buffer_id: BufferId, buffer: &Buffer) {
+        for plugin in self.plugins.values_mut() {
+            if !plugin.subscriptions.contains(&event) {
+                continue;
+            }
+            if let Some(process) = &mut plugin.process {
+                process.send(&Message::notification(Notification::BufferEvent {
+                    event,
+                    buffer: buffer_info(buffer_id, buffer),
+                }));
+            }
+        }
+    }
+
+    /// Sends change events for buffers edited since the last call
+    pub fn buffer_changes<'a>(&mut self, buffers: impl Iterator<Item = (BufferId, &'a Buffer)>) {
+        let subscribed = self
+            .plugins
+            .values()
+            .any(|plugin| plugin.subscriptions.contains(&BufferEventKind::Change));
+        let mut last_edits = HashMap::new();
+        for (buffer_id, buffer) in buffers {
+            let last_edit = buffer.get_last_edit();
+            let changed = self
+                .last_edits
+                .get(&buffer_id)
+                .is_some_and(|old| *old != last_edit);
+            if subscribed && changed {
+                self.buffer_event(BufferEventKind::Change, buffer_id, buffer);
+            }
+            last_edits.insert(buffer_id, last_edit);
+        }
+        self.last_edits = last_edits;
+    }
+
+    /// Returns the plugin that registered the command typed in the palette
+    pub fn find_command(&self, input: &str) -> Option<&str> {
+        let name = input.split_whitespace().next()?;
+        self.plugins
+            .iter()
+            .find(|(_, plugin)| plugin.commands.iter().any(|cmd| cmd.name == name))
+            .map(|(plugin, _)| plugin.as_str())
+    }
+
+    pub fn run_command(&mut self, input: &str) {
+        let Some(plugin) = self.find_command(input).map(|name| name.to_string()) else {
+            return;
+        };
+        let (name, args) = lexer::tokenize(input.trim());
+        if let Some(process) = &mut self.plugins[&plugin].process {
+            process.send(&Message::notification(Notification::RunCommand {
+                name: name.text,
+                args: args.into_iter().map(|token| token.text).collect(),
+            }));
+        }
+    }
+}
+
+impl Drop for PluginManager {
+    fn drop(&mut self) {
+        for plugin in self.plugins.values_mut() {
+            if let Some(process) = plugin.process.take() {
+                process.shutdown();
+            }
+        }
+    }
+}
+
+pub fn buffer_info(buffer_id: BufferId, buffer: &Buffer) -> BufferInfo {
+    BufferInfo {
+        id: buffer_id.data().as_ffi(),
+        name: buffer.name().to_string(),
+        path: buffer.file().map(|path| path.to_path_buf()),
+        language: buffer.language_name().to_string(),
+        dirty: buffer.is_dirty(),
+    }
+}
+
+pub fn buffer_id_from_ffi(id: u64) -> BufferId {
+    BufferId::from(KeyData::from_ffi(id))
+}
@@ -27,10 +27,15 @@ impl<T: 'static + Send> Promise<T> {
         }
     }
 
+    /// Returns the value if it is ready without blocking
     pub fn poll(&mut self) -> Option<T> {
         let mut inner = Kind::Consumed;
         mem::swap(&mut self.inner, &mut inner);
         match inner {
+            Kind::Thread(thread) if !thread.is_finished() => {
+                self.inner = Kind::Thread(thread);
+                None
+            }
             Kind::Thread(thread) => Some(thread.join().unwrap()),
             Kind::Ready(value) => Some(value),
             Kind::Consumed => None,
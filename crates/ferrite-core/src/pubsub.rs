@@ -1,15 +1,21 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use cb::{Receiver, RecvError, SendError, Sender};
 
 pub struct Publisher<T> {
     sender: Sender<()>,
-    data: Arc<T>,
+    data: Arc<Mutex<Arc<T>>>,
 }
 
 impl<T> Publisher<T> {
     pub fn modify(&self, f: impl FnOnce(&T)) {
-        (f)(&*self.data);
+        let data = self.data.lock().unwrap().clone();
+        (f)(&*data);
+    }
+
+    /// Replaces the published value, subscribers see the new value after the next publish
+    pub fn replace(&self, value: T) {
+        *self.data.lock().unwrap() = Arc::new(value);
     }
 
     pub fn publish(&self) -> Result<(), SendError<()>> {
@@ -18,7 +24,7 @@ impl<T> Publisher<T> {
 }
 
 pub struct Subscriber<T> {
-    data: Arc<T>,
+    data: Arc<Mutex<Arc<T>>>,
     reciver: Receiver<()>,
     has_recived: bool,
 }
@@ -27,15 +33,15 @@ impl<T> Subscriber<T> {
     pub fn recive(&mut self) -> Result<Arc<T>, RecvError> {
         if !self.has_recived {
             self.has_recived = true;
-            return Ok(self.data.clone());
+            return Ok(self.get());
         }
 
         self.reciver.recv()?;
-        Ok(self.data.clone())
+        Ok(self.get())
     }
 
     pub fn get(&self) -> Arc<T> {
-        self.data.clone()
+        self.data.lock().unwrap().clone()
     }
 }
 
@@ -51,7 +57,7 @@ impl<T> Clone for Subscriber<T> {
 
 pub fn create<T>(value: T) -> (Publisher<T>, Subscriber<T>) {
     let (sender, reciver) = cb::unbounded::<()>();
-    let data = Arc::new(value);
+    let data = Arc::new(Mutex::new(Arc::new(value)));
     (
         Publisher {
             sender,
@@ -0,0 +1,108 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Max number of entries kept in each list
+const MAX_ENTRIES: usize = 100;
+
+/// Most recently used files and project directories shared between all instances of the editor.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Recent {
+    #[serde(default)]
+    pub files: Vec<PathBuf>,
+    #[serde(default)]
+    pub projects: Vec<PathBuf>,
+}
+
+impl Recent {
+    pub fn load() -> Result<Self> {
+        let path = get_recent_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = get_recent_path()?;
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Existing files ordered from most to least recently used
+    pub fn existing_files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.iter().filter(|path| path.is_file())
+    }
+
+    /// Existing project directories ordered from most to least recently used
+    pub fn existing_projects(&self) -> impl Iterator<Item = &PathBuf> {
+        self.projects.iter().filter(|path| path.is_dir())
+    }
+}
+
+fn push_front(list: &mut Vec<PathBuf>, path: &Path) {
+    list.retain(|entry| entry != path);
+    list.insert(0, path.to_path_buf());
+    list.truncate(MAX_ENTRIES);
+}
+
+/// Moves `path` to the front of the recent files list on disk.
+/// The list is reread before writing so that other instances entries are kept.
+pub fn add_file(path: impl AsRef<Path>) {
+    update(|recent, path| push_front(&mut recent.files, path), path);
+}
+
+/// Moves `path` to the front of the recent projects list on disk.
+pub fn add_project(path: impl AsRef<Path>) {
+    update(|recent, path| push_front(&mut recent.projects, path), path);
+}
+
+fn update(f: impl FnOnce(&mut Recent, &Path), path: impl AsRef<Path>) {
+    let path = match dunce::canonicalize(path) {
+        Ok(path) => path,
+        Err(err) => {
+            tracing::error!("Error adding recent entry: {err}");
+            return;
+        }
+    };
+
+    let mut recent = Recent::load().unwrap_or_else(|err| {
+        tracing::error!("Error loading recent entries: {err}");
+        Recent::default()
+    });
+    f(&mut recent, &path);
+    if let Err(err) = recent.save() {
+        tracing::error!("Error saving recent entries: {err}");
+    }
+}
+
+pub fn get_recent_path() -> Result<PathBuf> {
+    let Some(directories) = directories::ProjectDirs::from("", "", "ferrite") else {
+        return Err(anyhow::Error::msg("Unable to find project directory"));
+    };
+    Ok(directories.data_dir().join("ferrite-recent.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_recent_first() {
+        let mut list = Vec::new();
+        push_front(&mut list, Path::new("a"));
+        push_front(&mut list, Path::new("b"));
+        push_front(&mut list, Path::new("a"));
+        assert_eq!(list, vec![PathBuf::from("a"), PathBuf::from("b")]);
+
+        for i in 0..MAX_ENTRIES + 10 {
+            push_front(&mut list, Path::new(&i.to_string()));
+        }
+        assert_eq!(list.len(), MAX_ENTRIES);
+    }
+}
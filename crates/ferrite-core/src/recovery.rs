@@ -0,0 +1,339 @@
+use std::{
+    fs::{self, File, TryLockError},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock, PoisonError},
+    time::Duration,
+};
+
+use anyhow::Result;
+use ferrite_utility::vec1::Vec1;
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+
+use crate::buffer::Cursor;
+
+/// How often the contents of unsaved buffers are written to the recovery dir
+pub const RECOVERY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Directory in the data dir where every running editor keeps a copy of its unsaved buffers
+pub fn get_recovery_dir() -> Option<&'static Path> {
+    static RECOVERY_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    RECOVERY_DIR
+        .get_or_init(|| {
+            let directories = directories::ProjectDirs::from("", "", "ferrite")?;
+            Some(directories.data_dir().join("recovery"))
+        })
+        .as_deref()
+}
+
+/// An unsaved buffer as it was when the recovery file was written
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveredBuffer {
+    pub path: Option<PathBuf>,
+    pub name: String,
+    pub text: String,
+    pub cursors: Vec1<Cursor>,
+}
+
+/// An unsaved buffer kept in memory so it can be written out if the editor panics.
+/// Cloning a rope is cheap so the snapshot can be refreshed every time the engine polls.
+#[derive(Debug, Clone)]
+pub struct EmergencyBuffer {
+    pub path: Option<PathBuf>,
+    pub name: String,
+    pub rope: Rope,
+    pub cursors: Vec1<Cursor>,
+}
+
+static EMERGENCY_SNAPSHOT: Mutex<Vec<EmergencyBuffer>> = Mutex::new(Vec::new());
+
+/// Lock held on this session's lock file for as long as it has a recovery file.
+/// The OS releases it when the process dies so a recovery file whose lock
+/// can be taken belongs to a session that is no longer running.
+static SESSION_LOCK: Mutex<Option<File>> = Mutex::new(None);
+
+/// Replaces the buffers written by `emergency_dump`
+pub fn set_emergency_snapshot(buffers: Vec<EmergencyBuffer>) {
+    *EMERGENCY_SNAPSHOT
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = buffers;
+}
+
+/// Writes the last emergency snapshot to this session's recovery file.
+/// Meant to be called from a panic hook so it never blocks on the snapshot lock.
+pub fn emergency_dump() -> Result<()> {
+    let Some(dir) = get_recovery_dir() else {
+        anyhow::bail!("Unable to find project directory");
+    };
+    lock_session(dir)?;
+    emergency_dump_to(&session_path(dir))
+}
+
+fn emergency_dump_to(path: &Path) -> Result<()> {
+    let buffers: Vec<_> = match EMERGENCY_SNAPSHOT.try_lock() {
+        Ok(snapshot) => snapshot.clone(),
+        Err(std::sync::TryLockError::Poisoned(snapshot)) => snapshot.into_inner().clone(),
+        Err(std::sync::TryLockError::WouldBlock) => {
+            anyhow::bail!("Emergency snapshot is locked")
+        }
+    };
+    let buffers: Vec<_> = buffers
+        .into_iter()
+        .map(|buffer| RecoveredBuffer {
+            path: buffer.path,
+            name: buffer.name,
+            text: buffer.rope.to_string(),
+            cursors: buffer.cursors,
+        })
+        .collect();
+    write_session_in(path, &buffers)
+}
+
+/// Names the files of this session, pids are reused so a random part keeps a new session
+/// from mistaking the files of a crashed one with the same pid for its own
+fn session_id() -> &'static str {
+    static SESSION_ID: OnceLock<String> = OnceLock::new();
+    SESSION_ID.get_or_init(|| format!("{}-{:016x}", std::process::id(), rand::random::<u64>()))
+}
+
+fn session_path(dir: &Path) -> PathBuf {
+    dir.join(format!("{}.json", session_id()))
+}
+
+fn lock_path(session: &Path) -> PathBuf {
+    session.with_extension("lock")
+}
+
+/// Takes the lock that marks the recovery file of this session as belonging to a running editor
+fn lock_session(dir: &Path) -> Result<()> {
+    let mut lock = match SESSION_LOCK.try_lock() {
+        Ok(lock) => lock,
+        Err(std::sync::TryLockError::Poisoned(lock)) => lock.into_inner(),
+        Err(std::sync::TryLockError::WouldBlock) => anyhow::bail!("Session lock is locked"),
+    };
+    if lock.is_none() {
+        *lock = Some(lock_file(&lock_path(&session_path(dir)))?);
+    }
+    Ok(())
+}
+
+fn lock_file(path: &Path) -> Result<File> {
+    fs::create_dir_all(path.parent().unwrap())?;
+    let file = File::create(path)?;
+    file.try_lock()?;
+    Ok(file)
+}
+
+/// A session is running if something holds the lock on its lock file
+fn is_running(session: &Path) -> bool {
+    let Ok(file) = File::open(lock_path(session)) else {
+        return false;
+    };
+    matches!(file.try_lock(), Err(TryLockError::WouldBlock))
+}
+
+/// Prefix of recovery files left by sessions that were terminated by a signal
+const TERMINATED_PREFIX: &str = "terminated-";
+/// Prefix of recovery files with buffers that could not be restored
+const UNRESTORED_PREFIX: &str = "unrestored-";
+
+fn terminated_session_path(dir: &Path) -> PathBuf {
+    dir.join(format!("{TERMINATED_PREFIX}{}.json", session_id()))
+}
+
+fn unrestored_session_path(dir: &Path) -> PathBuf {
+    dir.join(format!("{UNRESTORED_PREFIX}{}.json", session_id()))
+}
+
+/// Writes the unsaved buffers of a session that is exiting because it was terminated.
+/// Unlike the recovery file of a running session it is recovered on the next start right away.
+pub fn write_terminated_session(buffers: &[RecoveredBuffer]) -> Result<()> {
+    let Some(dir) = get_recovery_dir() else {
+        anyhow::bail!("Unable to find project directory");
+    };
+    write_session_in(&terminated_session_path(dir), buffers)
+}
+
+/// Keeps recovered buffers that could not be opened so they are recovered on the next start
+pub fn write_unrestored(buffers: &[RecoveredBuffer]) -> Result<()> {
+    let Some(dir) = get_recovery_dir() else {
+        anyhow::bail!("Unable to find project directory");
+    };
+    write_session_in(&unrestored_session_path(dir), buffers)
+}
+
+/// Replaces the recovery file of this session, the file is removed if there are no unsaved buffers
+pub fn write_session(buffers: &[RecoveredBuffer]) -> Result<()> {
+    let Some(dir) = get_recovery_dir() else {
+        anyhow::bail!("Unable to find project directory");
+    };
+    if !buffers.is_empty() {
+        lock_session(dir)?;
+    }
+    write_session_in(&session_path(dir), buffers)
+}
+
+/// Removes the recovery file of this session, called when the editor exits cleanly
+pub fn remove_session() {
+    if let Some(dir) = get_recovery_dir() {
+        let path = session_path(dir);
+        if path.exists() {
+            if let Err(err) = fs::remove_file(&path) {
+                tracing::error!("Error removing recovery file: {err}");
+            }
+        }
+        let lock = SESSION_LOCK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if lock.is_some() {
+            drop(lock);
+            if let Err(err) = fs::remove_file(lock_path(&path)) {
+                tracing::error!("Error removing recovery lock file: {err}");
+            }
+        }
+    }
+}
+
+/// Recovery files left behind by sessions that did not exit cleanly
+pub fn find_crashed_sessions() -> Result<Vec<PathBuf>> {
+    let Some(dir) = get_recovery_dir() else {
+        anyhow::bail!("Unable to find project directory");
+    };
+    find_crashed_sessions_in(dir, &session_path(dir))
+}
+
+/// Reads the buffers from every recovery file in `sessions`, unreadable files are skipped
+pub fn load_sessions(sessions: &[PathBuf]) -> Vec<RecoveredBuffer> {
+    let mut buffers = Vec::new();
+    for session in sessions {
+        match fs::read(session)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(serde_json::from_slice::<Vec<RecoveredBuffer>>(&bytes)?))
+        {
+            Ok(recovered) => buffers.extend(recovered),
+            Err(err) => tracing::error!("Error reading `{}`: {err}", session.display()),
+        }
+    }
+    buffers
+}
+
+pub fn remove_sessions(sessions: &[PathBuf]) {
+    for session in sessions {
+        if let Err(err) = fs::remove_file(session) {
+            tracing::error!("Error removing `{}`: {err}", session.display());
+        }
+        let _ = fs::remove_file(lock_path(session));
+    }
+}
+
+fn write_session_in(path: &Path, buffers: &[RecoveredBuffer]) -> Result<()> {
+    if buffers.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    fs::create_dir_all(path.parent().unwrap())?;
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    fs::write(&tmp, serde_json::to_vec(buffers)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn find_crashed_sessions_in(dir: &Path, current: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut sessions = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path == current || path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let terminated = path.file_name().is_some_and(|name| {
+            let name = name.to_string_lossy();
+            name.starts_with(TERMINATED_PREFIX) || name.starts_with(UNRESTORED_PREFIX)
+        });
+        if terminated || !is_running(&path) {
+            sessions.push(path);
+        }
+    }
+    Ok(sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crashed_sessions() {
+        let dir = tempdir::TempDir::new("ferrite-recovery").unwrap();
+        let buffer = RecoveredBuffer {
+            path: Some("foo.rs".into()),
+            name: "foo.rs".into(),
+            text: "fn main() {}".into(),
+            cursors: Vec1::new(Cursor::default()),
+        };
+        let current = dir.path().join("1.json");
+        let running = dir.path().join("2.json");
+        let crashed = dir.path().join("3.json");
+        write_session_in(&current, std::slice::from_ref(&buffer)).unwrap();
+        write_session_in(&running, std::slice::from_ref(&buffer)).unwrap();
+        write_session_in(&crashed, std::slice::from_ref(&buffer)).unwrap();
+
+        // Another editor holds the lock of its session, the crashed one left its lock file behind
+        let running_lock = lock_file(&lock_path(&running)).unwrap();
+        drop(lock_file(&lock_path(&crashed)).unwrap());
+
+        let sessions = find_crashed_sessions_in(dir.path(), &current).unwrap();
+        assert_eq!(sessions, vec![crashed.clone()]);
+        assert_eq!(load_sessions(&sessions), vec![buffer.clone()]);
+        remove_sessions(&sessions);
+        assert!(!lock_path(&crashed).exists());
+
+        drop(running_lock);
+        assert_eq!(
+            find_crashed_sessions_in(dir.path(), &current).unwrap(),
+            vec![running]
+        );
+
+        write_session_in(&current, &[]).unwrap();
+        assert!(!current.exists());
+
+        let terminated = terminated_session_path(dir.path());
+        let unrestored = unrestored_session_path(dir.path());
+        write_session_in(&terminated, std::slice::from_ref(&buffer)).unwrap();
+        write_session_in(&unrestored, std::slice::from_ref(&buffer)).unwrap();
+        let sessions = find_crashed_sessions_in(dir.path(), &current).unwrap();
+        assert!(sessions.contains(&terminated));
+        assert!(sessions.contains(&unrestored));
+
+        // A crashed session with the same pid is not mistaken for this one
+        let same_pid = dir.path().join(format!("{}.json", std::process::id()));
+        write_session_in(&same_pid, std::slice::from_ref(&buffer)).unwrap();
+        assert_ne!(session_path(dir.path()), same_pid);
+        assert!(
+            find_crashed_sessions_in(dir.path(), &session_path(dir.path()))
+                .unwrap()
+                .contains(&same_pid)
+        );
+    }
+
+    #[test]
+    fn emergency() {
+        let dir = tempdir::TempDir::new("ferrite-recovery").unwrap();
+        let path = dir.path().join("1.json");
+        set_emergency_snapshot(vec![EmergencyBuffer {
+            path: None,
+            name: "scratch".into(),
+            rope: Rope::from("unsaved"),
+            cursors: Vec1::new(Cursor::default()),
+        }]);
+        emergency_dump_to(&path).unwrap();
+        let recovered = load_sessions(&[path]);
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].text, "unsaved");
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{bail, Result};
+use grep_matcher::Matcher as _;
+use grep_regex::RegexMatcherBuilder;
+
+use crate::{
+    buffer::{search::SearchOptions, write},
+    config::editor::FilesConfig,
+    diff,
+    job_manager::{CancellationToken, Progressor},
+    picker::{file_previewer::is_text_file, file_scanner::build_overrides},
+};
+
+/// The new content of a single file together with the content it was staged from
+#[derive(Debug, Clone)]
+struct StagedEdit {
+    path: PathBuf,
+    original: Vec<u8>,
+    modified: Vec<u8>,
+}
+
+/// Edits to multiple files that are applied all at once or not at all
+#[derive(Debug, Default)]
+pub struct Transaction {
+    edits: Vec<StagedEdit>,
+    matches: usize,
+}
+
+impl Transaction {
+    /// Stages replacing every occurrence of `query` with `replacement` in `files`.
+    /// `files` are paths relative to `root`, if `scope` is set only files matching that glob are staged.
+    /// Files that are not valid utf-8 text are skipped.
+    pub fn stage_replace(
+        token: &CancellationToken,
+        root: &Path,
+        files: &boxcar::Vec<String>,
+        scope: Option<String>,
+        query: &str,
+        replacement: &str,
+        options: SearchOptions,
+    ) -> Result<Self> {
+        let matcher = RegexMatcherBuilder::new()
+            .fixed_strings(true)
+            .case_insensitive(options.ignore_case(query))
+            .word(options.whole_word)
+            .build(query)?;
+        let scope = scope.map(|glob| {
+            build_overrides(
+                root,
+                &FilesConfig {
+                    include: vec![glob],
+                    exclude: Vec::new(),
+                },
+            )
+        });
+
+        let mut transaction = Self::default();
+        for (_, file) in files.iter() {
+            if token.is_cancelled() {
+                bail!("Refactor cancelled");
+            }
+            let path = root.join(file);
+            if scope
+                .as_ref()
+                .is_some_and(|scope| scope.matched(&path, false).is_ignore())
+            {
+                continue;
+            }
+            if !is_text_file(&path).unwrap_or(false) {
+                continue;
+            }
+            let Ok(original) = fs::read(&path) else {
+                continue;
+            };
+            if std::str::from_utf8(&original).is_err() {
+                continue;
+            }
+
+            let mut matches = 0;
+            let mut modified = Vec::with_capacity(original.len());
+            matcher.replace(&original, &mut modified, |_, dst| {
+                matches += 1;
+                dst.extend_from_slice(replacement.as_bytes());
+                true
+            })?;
+            if matches > 0 {
+                transaction.matches += matches;
+                transaction.stage(path, original, modified);
+            }
+        }
+        Ok(transaction)
+    }
+
+    pub fn stage(&mut self, path: PathBuf, original: Vec<u8>, modified: Vec<u8>) {
+        self.edits.push(StagedEdit {
+            path,
+            original,
+            modified,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.edits.len()
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.matches
+    }
+
+    pub fn set_match_count(&mut self, matches: usize) {
+        self.matches = matches;
+    }
+
+    /// Unified diff of every staged edit, for reviewing the transaction before applying it
+    pub fn diff(&self) -> String {
+        self.edits
+            .iter()
+            .map(|edit| {
+                let name = edit.path.to_string_lossy();
+                diff::unified_diff(
+                    &String::from_utf8_lossy(&edit.original),
+                    &String::from_utf8_lossy(&edit.modified),
+                    &name,
+                    &name,
+                )
+            })
+            .collect()
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.edits.iter().map(|edit| edit.path.as_path())
+    }
+
+    /// Writes every staged edit to disk. Fails without touching any file if one of them
+    /// has changed since it was staged, if a write fails the files already written are restored.
+    pub fn apply(self) -> Result<AppliedRefactor> {
+        swap_all(
+            self.edits
+                .iter()
+                .map(|edit| (edit.path.as_path(), &*edit.original, &*edit.modified)),
+        )?;
+        Ok(AppliedRefactor { edits: self.edits })
+    }
+}
+
+/// A transaction that has been written to disk and can be reverted
+#[derive(Debug)]
+pub struct AppliedRefactor {
+    edits: Vec<StagedEdit>,
+}
+
+impl AppliedRefactor {
+    pub fn file_count(&self) -> usize {
+        self.edits.len()
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.edits.iter().map(|edit| edit.path.as_path())
+    }
+
+    /// Restores the content every file had before the refactor.
+    /// Fails without touching any file if one of them was changed after the refactor.
+    pub fn undo(&self) -> Result<()> {
+        swap_all(
+            self.edits
+                .iter()
+                .map(|edit| (edit.path.as_path(), &*edit.modified, &*edit.original)),
+        )
+    }
+}
+
+/// Replaces the content of every file with the new content if all of them still contain the expected content
+fn swap_all<'a>(edits: impl Iterator<Item = (&'a Path, &'a [u8], &'a [u8])> + Clone) -> Result<()> {
+    for (path, expected, _) in edits.clone() {
+        match fs::read(path) {
+            Ok(current) if current == expected => (),
+            Ok(_) => bail!("`{}` has changed on disk", path.display()),
+            Err(err) => bail!("Unable to read `{}`: {err}", path.display()),
+        }
+    }
+
+    let mut written = Vec::new();
+    for (path, expected, new) in edits {
+        if let Err(err) = write::write_bytes(path, new) {
+            for (path, expected) in written {
+                if let Err(err) = write::write_bytes(path, expected) {
+                    tracing::error!("Unable to restore `{}`: {err}", path.display());
+                }
+            }
+            bail!("Unable to write `{}`: {err}", path.display());
+        }
+        written.push((path, expected));
+    }
+    Ok(())
+}
+
+/// Input of the job that stages a global replace
+pub struct ReplaceInput {
+    pub root: PathBuf,
+    pub files: Arc<boxcar::Vec<String>>,
+    pub scope: Option<String>,
+    pub query: String,
+    pub replacement: String,
+    pub options: SearchOptions,
+}
+
+pub fn stage_replace_job(
+    token: CancellationToken,
+    _: &mut Progressor<()>,
+    input: ReplaceInput,
+) -> Result<Transaction> {
+    Transaction::stage_replace(
+        &token,
+        &input.root,
+        &input.files,
+        input.scope,
+        &input.query,
+        &input.replacement,
+        input.options,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_and_undo() {
+        let dir = tempdir::TempDir::new("ferrite-refactor").unwrap();
+        let files = boxcar::Vec::new();
+        for (name, content) in [("a.txt", "foo bar"), ("b.txt", "bar"), ("c.txt", "Foo foo")] {
+            fs::write(dir.path().join(name), content).unwrap();
+            files.push(name.to_string());
+        }
+
+        let transaction = Transaction::stage_replace(
+            &CancellationToken::new(),
+            dir.path(),
+            &files,
+            None,
+            "foo",
+            "baz",
+            SearchOptions {
+                case_insensitive: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(transaction.file_count(), 2);
+        assert_eq!(transaction.match_count(), 3);
+
+        let applied = transaction.apply().unwrap();
+        let read = |name| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("a.txt"), "baz bar");
+        assert_eq!(read("c.txt"), "baz baz");
+
+        fs::write(dir.path().join("c.txt"), "changed").unwrap();
+        assert!(applied.undo().is_err());
+        assert_eq!(read("a.txt"), "baz bar");
+
+        fs::write(dir.path().join("c.txt"), "baz baz").unwrap();
+        applied.undo().unwrap();
+        assert_eq!(read("a.txt"), "foo bar");
+        assert_eq!(read("b.txt"), "bar");
+        assert_eq!(read("c.txt"), "Foo foo");
+    }
+}
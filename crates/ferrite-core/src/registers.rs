@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use crate::clipboard;
+
+/// Register that reads and writes the system clipboard
+pub const CLIPBOARD_REGISTER: char = '+';
+
+/// Named registers that text can be copied to and pasted from without touching the clipboard
+#[derive(Debug, Default)]
+pub struct Registers {
+    named: BTreeMap<char, String>,
+}
+
+impl Registers {
+    /// Registers are named by a single ascii letter or digit, or `+` for the clipboard
+    pub fn is_valid_name(name: char) -> bool {
+        name.is_ascii_alphanumeric() || name == CLIPBOARD_REGISTER
+    }
+
+    /// Every valid register name, used for palette completion
+    pub fn names() -> Vec<String> {
+        std::iter::once(CLIPBOARD_REGISTER)
+            .chain('a'..='z')
+            .chain('0'..='9')
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn get(&self, name: char) -> Option<String> {
+        if name == CLIPBOARD_REGISTER {
+            return Some(clipboard::get_contents());
+        }
+        self.named.get(&name).cloned()
+    }
+
+    pub fn set(&mut self, name: char, text: String) {
+        if name == CLIPBOARD_REGISTER {
+            clipboard::set_contents(text);
+        } else {
+            self.named.insert(name, text);
+        }
+    }
+
+    /// All registers with content, the clipboard register first
+    pub fn list(&self) -> Vec<(char, String)> {
+        std::iter::once((CLIPBOARD_REGISTER, clipboard::get_contents()))
+            .chain(self.named.iter().map(|(name, text)| (*name, text.clone())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_registers() {
+        let mut registers = Registers::default();
+        registers.set('b', "two".into());
+        registers.set('a', "one".into());
+        assert_eq!(registers.get('a').as_deref(), Some("one"));
+        assert_eq!(registers.get('c'), None);
+        let names: Vec<_> = registers.list().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ['+', 'a', 'b']);
+        assert!(Registers::is_valid_name('z'));
+        assert!(!Registers::is_valid_name('"'));
+    }
+}
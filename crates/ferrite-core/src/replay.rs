@@ -0,0 +1,239 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use ferrite_cli::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    batch::BatchProxy, cmd::Cmd, engine::Engine, event_loop_proxy::EventLoopControlFlow,
+    layout::panes::Rect, logger::LogMessage,
+};
+
+/// Something the engine was given while recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedEvent {
+    /// The area the panes are laid out in, recorded before the first input and when it changes
+    BufferArea(Rect),
+    Cmd(Cmd),
+    /// Input typed into a masked prompt, only recorded as having happened
+    Redacted,
+}
+
+/// A line of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedInput {
+    /// Milliseconds since the recording started
+    pub time: u64,
+    pub event: RecordedEvent,
+}
+
+/// Writes every input given to the engine to a file, one json object per line.
+/// Every line is flushed right away so the recording is complete even if the editor panics.
+pub struct InputRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+    buffer_area: Option<Rect>,
+    /// The last input was redacted, the rest of the masked input is dropped
+    redacted: bool,
+}
+
+impl InputRecorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            start: Instant::now(),
+            buffer_area: None,
+            redacted: false,
+        })
+    }
+
+    /// Records `cmd`, input given to a masked prompt such as a passphrase is never written.
+    /// A single `Redacted` event stands in for all of it.
+    pub fn record(&mut self, buffer_area: Rect, cmd: &Cmd, masked: bool) -> io::Result<()> {
+        if masked {
+            if !self.redacted {
+                self.redacted = true;
+                self.write(RecordedEvent::Redacted)?;
+            }
+            return Ok(());
+        }
+        self.redacted = false;
+        if self.buffer_area != Some(buffer_area) {
+            self.buffer_area = Some(buffer_area);
+            self.write(RecordedEvent::BufferArea(buffer_area))?;
+        }
+        self.write(RecordedEvent::Cmd(cmd.clone()))
+    }
+
+    fn write(&mut self, event: RecordedEvent) -> io::Result<()> {
+        let input = RecordedInput {
+            time: self.start.elapsed().as_millis() as u64,
+            event,
+        };
+        serde_json::to_writer(&mut self.writer, &input)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+/// Parses a recording written by `InputRecorder`
+pub fn parse_recording(recording: &str) -> Result<Vec<RecordedInput>> {
+    recording
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).with_context(|| format!("Invalid input on line {}", i + 1))
+        })
+        .collect()
+}
+
+/// Feeds a recording back into the engine in the order it was recorded.
+/// The replay starts from the files given on the command line, so it should be started with
+/// the same files as the recording.
+pub struct InputReplay {
+    path: PathBuf,
+    inputs: VecDeque<RecordedInput>,
+    start: Instant,
+    replayed: usize,
+}
+
+impl InputReplay {
+    pub fn load(path: &Path) -> Result<Self> {
+        let recording = fs::read_to_string(path)
+            .with_context(|| format!("Error reading recording `{}`", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            inputs: parse_recording(&recording)?.into(),
+            start: Instant::now(),
+            replayed: 0,
+        })
+    }
+
+    /// Takes the next input if the time it was recorded at has passed since the replay started
+    pub fn next_due(&mut self) -> Option<RecordedEvent> {
+        let elapsed = self.start.elapsed().as_millis() as u64;
+        if self.inputs.front()?.time > elapsed {
+            return None;
+        }
+        self.take_next()
+    }
+
+    /// Takes the next input regardless of when it was recorded
+    pub fn take_next(&mut self) -> Option<RecordedEvent> {
+        let input = self.inputs.pop_front()?;
+        self.replayed += 1;
+        Some(input.event)
+    }
+
+    /// Time until the next input is due
+    pub fn until_next(&self) -> Option<Duration> {
+        let time = Duration::from_millis(self.inputs.front()?.time);
+        Some(time.saturating_sub(self.start.elapsed()))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Amount of inputs that have been replayed
+    pub fn replayed(&self) -> usize {
+        self.replayed
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Replays a recording in a headless engine as fast as possible.
+/// Returns the amount of inputs that were replayed.
+pub fn run_headless(args: &Args, path: &Path, recv: mpsc::Receiver<LogMessage>) -> Result<usize> {
+    let mut replay = InputReplay::load(path)?;
+    let (tx, rx) = mpsc::channel();
+    let mut engine = Engine::new_headless(args, Box::new(BatchProxy(tx)), recv)?;
+    let mut control_flow = EventLoopControlFlow::Poll;
+
+    while let Some(event) = replay.take_next() {
+        engine.replay_event(event, &mut control_flow);
+        while let Ok(event) = rx.try_recv() {
+            engine.handle_app_event(event, &mut control_flow);
+        }
+        engine.do_polling(&mut control_flow);
+        if control_flow == EventLoopControlFlow::Exit {
+            break;
+        }
+    }
+
+    Ok(replay.replayed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_parse() {
+        let dir = tempdir::TempDir::new("ferrite-replay").unwrap();
+        let path = dir.path().join("recording.jsonl");
+        let area = Rect::new(0, 0, 80, 24);
+        let mut recorder = InputRecorder::create(&path).unwrap();
+        recorder
+            .record(area, &Cmd::Insert { text: "a".into() }, false)
+            .unwrap();
+        recorder.record(area, &Cmd::Undo, false).unwrap();
+        drop(recorder);
+
+        let mut replay = InputReplay::load(&path).unwrap();
+        assert_eq!(replay.take_next(), Some(RecordedEvent::BufferArea(area)));
+        assert_eq!(
+            replay.take_next(),
+            Some(RecordedEvent::Cmd(Cmd::Insert { text: "a".into() }))
+        );
+        assert_eq!(replay.take_next(), Some(RecordedEvent::Cmd(Cmd::Undo)));
+        assert!(replay.is_finished());
+        assert_eq!(replay.replayed(), 3);
+
+        assert!(parse_recording("{}\n").is_err());
+    }
+
+    #[test]
+    fn masked_input_is_redacted() {
+        let dir = tempdir::TempDir::new("ferrite-replay").unwrap();
+        let path = dir.path().join("recording.jsonl");
+        let area = Rect::new(0, 0, 80, 24);
+        let mut recorder = InputRecorder::create(&path).unwrap();
+        for ch in "hunter2".chars() {
+            recorder.record(area, &Cmd::Char { ch }, true).unwrap();
+        }
+        recorder
+            .record(area, &Cmd::Char { ch: '\n' }, true)
+            .unwrap();
+        recorder.record(area, &Cmd::Undo, false).unwrap();
+        drop(recorder);
+
+        let recording = fs::read_to_string(&path).unwrap();
+        assert!(!recording.contains("hunter2") && !recording.contains("\"h\""));
+        let events: Vec<_> = parse_recording(&recording)
+            .unwrap()
+            .into_iter()
+            .map(|input| input.event)
+            .collect();
+        assert_eq!(
+            events,
+            [
+                RecordedEvent::Redacted,
+                RecordedEvent::BufferArea(area),
+                RecordedEvent::Cmd(Cmd::Undo)
+            ]
+        );
+    }
+}
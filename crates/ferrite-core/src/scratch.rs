@@ -0,0 +1,80 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::SystemTime,
+};
+
+use anyhow::Result;
+
+use crate::picker::{file_previewer::FilePreviewer, Preview, Previewer};
+
+/// Directory in the data dir where scratch buffers are stored
+pub fn get_scratch_dir() -> Option<&'static Path> {
+    static SCRATCH_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    SCRATCH_DIR
+        .get_or_init(|| {
+            let directories = directories::ProjectDirs::from("", "", "ferrite")?;
+            let path = directories.data_dir().join("scratch");
+            if let Err(err) = fs::create_dir_all(&path) {
+                tracing::error!("Error creating scratch dir: {err}");
+            }
+            Some(dunce::canonicalize(&path).unwrap_or(path))
+        })
+        .as_deref()
+}
+
+pub fn is_scratch_path(path: impl AsRef<Path>) -> bool {
+    get_scratch_dir().is_some_and(|dir| path.as_ref().parent() == Some(dir))
+}
+
+/// Path of the scratch buffer called `name`, creating the file if it does not exist.
+pub fn get_scratch_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\', std::path::MAIN_SEPARATOR])
+    {
+        anyhow::bail!("Invalid scratch name: `{name}`");
+    }
+    let Some(dir) = get_scratch_dir() else {
+        anyhow::bail!("Unable to find project directory");
+    };
+    let path = dir.join(name);
+    if !path.exists() {
+        fs::write(&path, "")?;
+    }
+    Ok(path)
+}
+
+/// Names of all scratch buffers, most recently modified first
+pub fn list_scratches() -> Result<Vec<String>> {
+    let Some(dir) = get_scratch_dir() else {
+        anyhow::bail!("Unable to find project directory");
+    };
+    let mut scratches = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let modified = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        scratches.push((modified, entry.file_name().to_string_lossy().into_owned()));
+    }
+    scratches.sort_by(|a, b| b.cmp(a));
+    Ok(scratches.into_iter().map(|(_, name)| name).collect())
+}
+
+pub struct ScratchPreviewer(pub FilePreviewer);
+
+impl Previewer<String> for ScratchPreviewer {
+    fn request_preview(&mut self, name: &String) -> Preview<'_> {
+        match get_scratch_dir() {
+            Some(dir) => self.0.preview_path(&dir.join(name)),
+            None => Preview::Err,
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use rhai::{Dynamic, FnAccess, Scope, AST};
+
+use crate::{
+    buffer::{Buffer, ViewId},
+    palette::cmd_parser::get_command_names,
+};
+
+/// Scripts are stopped after running this many operations, so an endless loop cannot hang the editor
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// Scripts are stopped once they have been running for this long
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Directory in the config dir containing the `*.rhai` scripts loaded at startup
+pub fn get_scripts_dir() -> Result<PathBuf> {
+    let Some(directories) = directories::ProjectDirs::from("", "", "ferrite") else {
+        return Err(anyhow::Error::msg("Unable to find project directory"));
+    };
+    Ok(directories.config_dir().join("scripts"))
+}
+
+/// A function in a script that is run as a palette command.
+/// Functions are named like commands with `_` in place of `-`.
+struct ScriptCommand {
+    function: String,
+    arity: usize,
+    ast: Rc<AST>,
+}
+
+/// User commands defined by the public functions in the scripts dir
+#[derive(Default)]
+pub struct Scripts {
+    commands: IndexMap<String, ScriptCommand>,
+}
+
+/// What a script can reach while it runs
+struct ScriptState {
+    buffer: Buffer,
+    view_id: ViewId,
+    messages: Vec<String>,
+}
+
+/// The result of running a script command
+pub struct ScriptOutput {
+    pub buffer: Buffer,
+    pub messages: Vec<String>,
+    pub result: Result<()>,
+}
+
+impl Scripts {
+    /// Compiles every script in the scripts dir, returns the scripts that failed to compile as errors
+    pub fn load() -> (Self, Vec<anyhow::Error>) {
+        match get_scripts_dir() {
+            Ok(dir) => Self::load_from(&dir),
+            Err(err) => (Self::default(), vec![err]),
+        }
+    }
+
+    fn load_from(dir: &Path) -> (Self, Vec<anyhow::Error>) {
+        let mut scripts = Self::default();
+        let mut errors = Vec::new();
+        let Ok(entries) = fs::read_dir(dir) else {
+            return (scripts, errors);
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+
+        let engine = rhai::Engine::new();
+        let builtins = get_command_names();
+        for path in paths {
+            let ast = match engine.compile_file(path.clone()) {
+                Ok(ast) => Rc::new(ast),
+                Err(err) => {
+                    errors.push(anyhow::anyhow!("Error loading `{}`: {err}", path.display()));
+                    continue;
+                }
+            };
+            for function in ast.iter_functions() {
+                if function.access == FnAccess::Private {
+                    continue;
+                }
+                let name = function.name.replace('_', "-");
+                if builtins.contains(&name.as_str()) {
+                    errors.push(anyhow::anyhow!(
+                        "`{name}` in `{}` is a builtin command",
+                        path.display()
+                    ));
+                    continue;
+                }
+                scripts.commands.insert(
+                    name,
+                    ScriptCommand {
+                        function: function.name.to_string(),
+                        arity: function.params.len(),
+                        ast: ast.clone(),
+                    },
+                );
+            }
+        }
+        (scripts, errors)
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Returns true if the input typed in the palette runs a script command
+    pub fn has_command(&self, input: &str) -> bool {
+        input
+            .split_whitespace()
+            .next()
+            .is_some_and(|name| self.commands.contains_key(name))
+    }
+
+    /// Runs the command typed in the palette on `buffer`, the remaining words are passed as string arguments.
+    /// All edits made by the script are undone as a single step.
+    pub fn run(&self, input: &str, mut buffer: Buffer, view_id: ViewId) -> ScriptOutput {
+        let (name, args) = crate::palette::cmd_parser::lexer::tokenize(input.trim());
+        let Some(command) = self.commands.get(&name.text) else {
+            return ScriptOutput {
+                buffer,
+                messages: Vec::new(),
+                result: Err(anyhow::anyhow!("Unknown command `{}`", name.text)),
+            };
+        };
+        if args.len() != command.arity {
+            return ScriptOutput {
+                buffer,
+                messages: Vec::new(),
+                result: Err(anyhow::anyhow!(
+                    "`{}` takes {} argument(s)",
+                    name.text,
+                    command.arity
+                )),
+            };
+        }
+        let args: Vec<Dynamic> = args
+            .into_iter()
+            .map(|token| Dynamic::from(token.text))
+            .collect();
+
+        buffer.begin_undo_group();
+        let state = Rc::new(RefCell::new(ScriptState {
+            buffer,
+            view_id,
+            messages: Vec::new(),
+        }));
+        let result = {
+            let engine = create_engine(&state);
+            engine
+                .call_fn::<Dynamic>(&mut Scope::new(), &command.ast, &command.function, args)
+                .map(|_| ())
+                .map_err(|err| anyhow::anyhow!("Error running `{}`: {err}", name.text))
+        };
+        let mut state = Rc::into_inner(state)
+            .expect("the script engine has been dropped")
+            .into_inner();
+        state.buffer.end_undo_group();
+        ScriptOutput {
+            buffer: state.buffer,
+            messages: state.messages,
+            result,
+        }
+    }
+}
+
+/// Creates an engine with the editor api bound to `state`.
+/// Lines, char offsets and columns are zero based, columns are measured in display width.
+fn create_engine(state: &Rc<RefCell<ScriptState>>) -> rhai::Engine {
+    let mut engine = rhai::Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    let start = Instant::now();
+    engine.on_progress(move |_| {
+        (start.elapsed() > SCRIPT_TIMEOUT).then(|| Dynamic::from("the script timed out"))
+    });
+
+    let s = state.clone();
+    engine.register_fn("text", move || s.borrow().buffer.rope().to_string());
+
+    let s = state.clone();
+    engine.register_fn("buffer_name", move || s.borrow().buffer.name().to_string());
+
+    let s = state.clone();
+    engine.register_fn("selection", move || {
+        let state = s.borrow();
+        state.buffer.get_selection(state.view_id, 0)
+    });
+
+    let s = state.clone();
+    engine.register_fn("replace_selection", move |text: &str| {
+        let state = &mut *s.borrow_mut();
+        if !state.buffer.read_only {
+            state.clear_extra_cursors();
+            state.buffer.insert_text(state.view_id, text, false);
+        }
+    });
+
+    let s = state.clone();
+    engine.register_fn("replace_range", move |start: i64, end: i64, text: &str| {
+        let state = &mut *s.borrow_mut();
+        if state.buffer.read_only {
+            return;
+        }
+        let rope = state.buffer.rope();
+        let len = rope.len_chars();
+        let start = (start.max(0) as usize).min(len);
+        let end = (end.max(0) as usize).clamp(start, len);
+        let range = rope.char_to_byte(start)..rope.char_to_byte(end);
+        state.buffer.replace(state.view_id, range, text);
+    });
+
+    let s = state.clone();
+    engine.register_fn("cursor_line", move || {
+        let state = s.borrow();
+        state.buffer.cursor_line_idx(state.view_id, 0) as i64
+    });
+
+    let s = state.clone();
+    engine.register_fn("cursor_column", move || {
+        let state = s.borrow();
+        state.buffer.cursor_grapheme_column(state.view_id, 0) as i64
+    });
+
+    let s = state.clone();
+    engine.register_fn("move_cursor", move |line: i64, column: i64| {
+        let state = &mut *s.borrow_mut();
+        state.clear_extra_cursors();
+        state.buffer.set_cursor_pos(
+            state.view_id,
+            0,
+            column.max(0) as usize,
+            line.max(0) as usize,
+        );
+    });
+
+    let s = state.clone();
+    engine.register_fn("show_message", move |text: &str| {
+        s.borrow_mut().messages.push(text.to_string());
+    });
+
+    engine
+}
+
+impl ScriptState {
+    fn clear_extra_cursors(&mut self) {
+        self.buffer.views[self.view_id].cursors.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_script() {
+        let dir = tempdir::TempDir::new("ferrite-scripts").unwrap();
+        fs::write(
+            dir.path().join("edit.rhai"),
+            r#"
+                fn shout() {
+                    replace_range(0, 5, text().sub_string(0, 5).to_upper());
+                    move_cursor(0, 3);
+                    show_message(`${cursor_column()}`);
+                }
+                fn greet(name) { show_message(`hello ${name}`); }
+                private fn helper() {}
+            "#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("broken.rhai"), "fn (").unwrap();
+        let (scripts, errors) = Scripts::load_from(dir.path());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(scripts.len(), 2);
+        assert!(scripts.has_command("greet world"));
+        assert!(!scripts.has_command("helper"));
+
+        let mut buffer = Buffer::with_text("hello world");
+        let view_id = buffer.create_view();
+        let output = scripts.run("shout", buffer, view_id);
+        output.result.unwrap();
+        assert_eq!(output.buffer.rope().to_string(), "HELLO world");
+        assert_eq!(output.messages, ["3"]);
+
+        let output = scripts.run("greet world", output.buffer, view_id);
+        assert_eq!(output.messages, ["hello world"]);
+        assert!(scripts.run("greet", output.buffer, view_id).result.is_err());
+    }
+
+    #[test]
+    fn script_limits() {
+        let dir = tempdir::TempDir::new("ferrite-scripts").unwrap();
+        fs::write(
+            dir.path().join("edit.rhai"),
+            r#"
+                fn spin() { loop {} }
+                fn twice() {
+                    replace_range(0, 1, "a");
+                    replace_range(1, 2, "b");
+                }
+            "#,
+        )
+        .unwrap();
+        let (scripts, errors) = Scripts::load_from(dir.path());
+        assert!(errors.is_empty());
+
+        let mut buffer = Buffer::with_text("xy");
+        let view_id = buffer.create_view();
+        let output = scripts.run("spin", buffer, view_id);
+        assert!(output.result.is_err());
+
+        let mut output = scripts.run("twice", output.buffer, view_id);
+        output.result.unwrap();
+        assert_eq!(output.buffer.rope().to_string(), "ab");
+        output.buffer.undo(view_id);
+        assert_eq!(output.buffer.rope().to_string(), "xy");
+    }
+}
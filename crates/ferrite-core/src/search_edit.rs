@@ -0,0 +1,216 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use ropey::Rope;
+
+use crate::{picker::global_search_picker::GlobalSearchMatch, refactor::Transaction};
+
+/// A line containing a global search match
+#[derive(Debug, Clone)]
+struct Entry {
+    path: PathBuf,
+    name: String,
+    line_idx: usize,
+    /// Text of the line when it was found, without the line ending
+    text: String,
+}
+
+impl Entry {
+    fn prefix(&self) -> String {
+        format!("{}:{}: ", self.name, self.line_idx + 1)
+    }
+}
+
+/// Global search results shown one line per row in a buffer.
+/// Edits to the lines are written back to the files the lines came from.
+#[derive(Debug, Clone, Default)]
+pub struct SearchEdit {
+    entries: Vec<Entry>,
+}
+
+impl SearchEdit {
+    /// Lines with several matches are only listed once
+    pub fn new<'a>(matches: impl IntoIterator<Item = &'a GlobalSearchMatch>) -> Self {
+        let mut seen = HashSet::new();
+        let entries = matches
+            .into_iter()
+            .filter(|m| seen.insert((m.path.clone(), m.match_location.0.line)))
+            .map(|m| Entry {
+                path: m.path.clone(),
+                name: m.name.clone(),
+                line_idx: m.match_location.0.line,
+                text: m.line.trim_end_matches(['\n', '\r']).to_string(),
+            })
+            .collect();
+        Self { entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Text of the results buffer, every line starts with `path:line: `
+    pub fn text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{}{}\n", entry.prefix(), entry.text))
+            .collect()
+    }
+
+    /// Stages the lines that were changed in `rope`, the edited text of the results buffer.
+    /// Fails if lines were added or removed, if a `path:line:` prefix was changed
+    /// or if a changed line no longer has the same content on disk as when it was found.
+    pub fn stage(&self, rope: &Rope) -> Result<Transaction> {
+        let lines = self.edited_lines(rope)?;
+
+        let mut changes: BTreeMap<&Path, Vec<(&Entry, &str)>> = BTreeMap::new();
+        for (entry, text) in self.entries.iter().zip(&lines) {
+            if entry.text != *text {
+                changes
+                    .entry(&entry.path)
+                    .or_default()
+                    .push((entry, text.as_str()));
+            }
+        }
+
+        let mut transaction = Transaction::default();
+        let mut count = 0;
+        for (path, changes) in changes {
+            let original =
+                fs::read(path).with_context(|| format!("Unable to read `{}`", path.display()))?;
+            let Ok(content) = std::str::from_utf8(&original) else {
+                bail!("`{}` is not valid utf-8", path.display());
+            };
+            let mut file_lines: Vec<String> =
+                content.split_inclusive('\n').map(String::from).collect();
+            for (entry, text) in changes {
+                let Some(line) = file_lines.get_mut(entry.line_idx) else {
+                    bail!("`{}` has changed since it was searched", entry.name);
+                };
+                let body_len = line.trim_end_matches(['\n', '\r']).len();
+                if line[..body_len] != entry.text {
+                    bail!("`{}` has changed since it was searched", entry.name);
+                }
+                line.replace_range(..body_len, text);
+                count += 1;
+            }
+            transaction.stage(
+                path.to_path_buf(),
+                original,
+                file_lines.concat().into_bytes(),
+            );
+        }
+        transaction.set_match_count(count);
+        Ok(transaction)
+    }
+
+    /// Makes the text of `rope` the new original after it has been written to disk
+    pub fn commit(&mut self, rope: &Rope) -> Result<()> {
+        let lines = self.edited_lines(rope)?;
+        for (entry, text) in self.entries.iter_mut().zip(lines) {
+            entry.text = text;
+        }
+        Ok(())
+    }
+
+    /// The text of every line after the prefix
+    fn edited_lines(&self, rope: &Rope) -> Result<Vec<String>> {
+        let mut lines: Vec<String> = rope
+            .lines()
+            .map(|line| line.to_string().trim_end_matches(['\n', '\r']).to_string())
+            .collect();
+        if lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        if lines.len() != self.entries.len() {
+            bail!(
+                "The results have {} line(s) but the search found {}, lines cannot be added or removed",
+                lines.len(),
+                self.entries.len()
+            );
+        }
+        self.entries
+            .iter()
+            .zip(lines)
+            .enumerate()
+            .map(|(i, (entry, line))| {
+                let prefix = entry.prefix();
+                match line.strip_prefix(&prefix) {
+                    Some(text) => Ok(text.to_string()),
+                    None => bail!(
+                        "The prefix `{}` of line {} was changed",
+                        prefix.trim_end(),
+                        i + 1
+                    ),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrite_utility::point::Point;
+
+    use super::*;
+
+    #[test]
+    fn stage_edited_lines() {
+        let dir = tempdir::TempDir::new("ferrite-search-edit").unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "foo\nbar\r\nfoo foo\n").unwrap();
+        let found = |line: usize, text: &str| GlobalSearchMatch {
+            path: path.clone(),
+            name: "a.txt".into(),
+            line: text.into(),
+            match_location: (Point::new(0, line), Point::new(3, line)),
+        };
+        let matches = [
+            found(0, "foo\n"),
+            found(2, "foo foo\n"),
+            found(2, "foo foo\n"),
+        ];
+        let search_edit = SearchEdit::new(&matches);
+        assert_eq!(search_edit.text(), "a.txt:1: foo\na.txt:3: foo foo\n");
+
+        let edited = Rope::from_str("a.txt:1: baz\na.txt:3: foo foo\n");
+        let transaction = search_edit.stage(&edited).unwrap();
+        assert_eq!(transaction.match_count(), 1);
+        transaction.apply().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "baz\nbar\r\nfoo foo\n");
+
+        // The file no longer contains the original line
+        assert!(search_edit.stage(&edited).is_err());
+        assert!(search_edit
+            .stage(&Rope::from_str("a.txt:2: baz\na.txt:3: foo foo\n"))
+            .is_err());
+        assert!(search_edit
+            .stage(&Rope::from_str("a.txt:1: foo\n"))
+            .is_err());
+    }
+
+    #[test]
+    fn stage_indented_line() {
+        let dir = tempdir::TempDir::new("ferrite-search-edit").unwrap();
+        let path = dir.path().join("a.rs");
+        fs::write(&path, "fn main() {\n    foo();\n}\n").unwrap();
+        let matcher = grep_regex::RegexMatcherBuilder::new()
+            .fixed_strings(true)
+            .build("foo")
+            .unwrap();
+        let matches = crate::picker::global_search_picker::search_file(&matcher, &path, None);
+        let search_edit = SearchEdit::new(&matches);
+        assert_eq!(search_edit.text(), "a.rs:2:     foo();\n");
+
+        let edited = Rope::from_str("a.rs:2:     bar();\n");
+        search_edit.stage(&edited).unwrap().apply().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "fn main() {\n    bar();\n}\n"
+        );
+    }
+}
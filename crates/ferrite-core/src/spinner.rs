@@ -2,46 +2,72 @@ use std::time::{Duration, Instant};
 
 const FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
+/// How long each frame is shown
+const FRAME_TIME: Duration = Duration::from_millis(80);
+
+/// The frame is derived from the time since the spinner started so it does not depend on how
+/// often the engine is polled
+#[derive(Default)]
 pub struct Spinner {
-    last_update: Instant,
-    current: usize,
-    is_spinning: bool,
+    /// When the spinner started spinning, `None` while it is stopped
+    start: Option<Instant>,
+    /// Frame that is shown while the spinner is paused
+    paused_frame: Option<usize>,
 }
 
 impl Spinner {
-    pub fn update(&mut self, spin: bool) -> Duration {
-        self.is_spinning = spin;
-        if spin {
-            let frame_time = Duration::from_millis(80);
-            let now = Instant::now();
-            let since = now.duration_since(self.last_update);
-            if since >= frame_time {
-                self.last_update = now;
-                self.current += 1;
-                self.current %= FRAMES.len();
-                return frame_time;
-            } else {
-                return frame_time - since;
-            }
+    /// Starts or stops the spinner, a spinner that is not animated keeps showing its frame.
+    /// Returns the time until the next frame is due, `None` if it does not have to be drawn again.
+    pub fn update(&mut self, spin: bool, animate: bool) -> Option<Duration> {
+        if !spin {
+            self.start = None;
+            self.paused_frame = None;
+            return None;
+        }
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let elapsed = start.elapsed();
+        if !animate {
+            self.paused_frame.get_or_insert(frame_at(elapsed));
+            return None;
         }
-        Duration::MAX
+        self.paused_frame = None;
+        let into_frame = elapsed.as_nanos() % FRAME_TIME.as_nanos();
+        Some(FRAME_TIME - Duration::from_nanos(into_frame as u64))
     }
 
     pub fn current(&self) -> Option<char> {
-        if self.is_spinning {
-            Some(FRAMES[self.current])
-        } else {
-            None
-        }
+        let start = self.start?;
+        let frame = self
+            .paused_frame
+            .unwrap_or_else(|| frame_at(start.elapsed()));
+        Some(FRAMES[frame])
     }
 }
 
-impl Default for Spinner {
-    fn default() -> Self {
-        Self {
-            last_update: Instant::now(),
-            current: 0,
-            is_spinning: false,
-        }
+fn frame_at(elapsed: Duration) -> usize {
+    (elapsed.as_nanos() / FRAME_TIME.as_nanos()) as usize % FRAMES.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadlines() {
+        let mut spinner = Spinner::default();
+        assert_eq!(spinner.update(false, true), None);
+        assert_eq!(spinner.current(), None);
+
+        let until_next = spinner.update(true, true).unwrap();
+        assert!(until_next <= FRAME_TIME);
+        assert!(spinner.current().is_some());
+
+        assert_eq!(spinner.update(true, false), None);
+        let paused = spinner.current();
+        std::thread::sleep(FRAME_TIME * 2);
+        assert_eq!(spinner.current(), paused);
+
+        spinner.update(false, false);
+        assert_eq!(spinner.current(), None);
     }
 }
@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use ropey::Rope;
+
+use crate::job_manager::{JobHandle, JobInfo, JobManager};
+
+/// Where a tag is located in its file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagAddress {
+    /// Zero based line number
+    Line(usize),
+    /// Text of the line the tag is defined on, `anchored` if it has to match the whole line
+    Pattern { pattern: String, anchored: bool },
+}
+
+/// A single symbol from a ctags `tags` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub path: PathBuf,
+    pub address: TagAddress,
+}
+
+impl Tag {
+    /// Zero based line of the tag in `rope`
+    pub fn line(&self, rope: &Rope) -> Option<usize> {
+        match &self.address {
+            TagAddress::Line(line) => Some(*line),
+            TagAddress::Pattern { pattern, anchored } => rope.lines().position(|line| {
+                let line = line.to_string();
+                let line = line.trim_end_matches(['\n', '\r']);
+                if *anchored {
+                    line == pattern
+                } else {
+                    line.contains(pattern.as_str())
+                }
+            }),
+        }
+    }
+}
+
+/// Symbols from the `tags` file in the working directory, reloaded in the background when the file changes
+#[derive(Default)]
+pub struct Tags {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    tags: HashMap<String, Vec<Tag>>,
+    load_job: Option<JobHandle<Option<LoadedTags>>>,
+}
+
+struct LoadedTags {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    tags: HashMap<String, Vec<Tag>>,
+}
+
+impl Tags {
+    /// Looks `name` up in the tags that are loaded and checks if the file has changed in the background
+    pub fn find(&mut self, job_manager: &mut JobManager, name: &str) -> Option<&Tag> {
+        if self.load_job.is_none() {
+            let path = std::env::current_dir().unwrap_or_default().join("tags");
+            let cached = (path == self.path).then_some(self.modified);
+            self.load_job = Some(job_manager.spawn_foreground_job(
+                JobInfo::default(),
+                |_, _, (path, cached): (PathBuf, Option<Option<SystemTime>>)| {
+                    load_tags(path, cached)
+                },
+                (path, cached),
+            ));
+        }
+        self.tags.get(name)?.first()
+    }
+
+    /// Returns true when tags that differ from the ones loaded before have been received
+    pub fn poll(&mut self) -> bool {
+        let Some(Ok(loaded)) = self.load_job.as_mut().map(|job| job.try_recv()) else {
+            return false;
+        };
+        self.load_job = None;
+        let Some(loaded) = loaded else {
+            return false;
+        };
+        self.path = loaded.path;
+        self.modified = loaded.modified;
+        self.tags = loaded.tags;
+        true
+    }
+}
+
+/// Reads and parses the tags file at `path`,
+/// returns `None` if it has the modification time `cached` it was loaded with before
+fn load_tags(path: PathBuf, cached: Option<Option<SystemTime>>) -> Option<LoadedTags> {
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+    if cached == Some(modified) {
+        return None;
+    }
+    let tags = match fs::read_to_string(&path) {
+        Ok(content) => parse_tags(&content, path.parent().unwrap_or(Path::new(""))),
+        Err(_) => HashMap::new(),
+    };
+    Some(LoadedTags {
+        path,
+        modified,
+        tags,
+    })
+}
+
+fn parse_tags(content: &str, dir: &Path) -> HashMap<String, Vec<Tag>> {
+    let mut tags: HashMap<String, Vec<Tag>> = HashMap::new();
+    for line in content.lines() {
+        if line.starts_with("!_TAG_") {
+            continue;
+        }
+        let mut fields = line.splitn(3, '\t');
+        let (Some(name), Some(file), Some(address)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let address = address.split(";\"").next().unwrap_or(address);
+        let Some(address) = parse_address(address) else {
+            continue;
+        };
+        tags.entry(name.to_string()).or_default().push(Tag {
+            path: dir.join(file),
+            address,
+        });
+    }
+    tags
+}
+
+fn parse_address(address: &str) -> Option<TagAddress> {
+    if let Ok(line) = address.parse::<usize>() {
+        return Some(TagAddress::Line(line.saturating_sub(1)));
+    }
+    let pattern = address
+        .strip_prefix('/')
+        .and_then(|p| p.strip_suffix('/'))
+        .or_else(|| address.strip_prefix('?').and_then(|p| p.strip_suffix('?')))?;
+    let (pattern, start) = match pattern.strip_prefix('^') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let (pattern, end) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let pattern = pattern
+        .replace("\\/", "/")
+        .replace("\\?", "?")
+        .replace("\\\\", "\\");
+    Some(TagAddress::Pattern {
+        pattern,
+        anchored: start && end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let content = "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+            main\tsrc/main.rs\t/^fn main() {$/;\"\tf\n\
+            Foo\tsrc/foo.rs\t12;\"\ts\n";
+        let tags = parse_tags(content, Path::new("/project"));
+        let main = &tags["main"][0];
+        assert_eq!(main.path, Path::new("/project/src/main.rs"));
+        assert_eq!(
+            main.line(&Rope::from("use std::io;\n\nfn main() {\n}\n")),
+            Some(2)
+        );
+        assert_eq!(tags["Foo"][0].address, TagAddress::Line(11));
+        assert_eq!(tags.len(), 2);
+    }
+
+    #[test]
+    fn reload_only_when_changed() {
+        let dir = tempdir::TempDir::new("ferrite-tags").unwrap();
+        let path = dir.path().join("tags");
+        assert!(load_tags(path.clone(), None).unwrap().tags.is_empty());
+        assert!(load_tags(path.clone(), Some(None)).is_none());
+
+        fs::write(&path, "main\tmain.rs\t1\n").unwrap();
+        let loaded = load_tags(path.clone(), Some(None)).unwrap();
+        assert_eq!(loaded.tags["main"][0].path, dir.path().join("main.rs"));
+        assert!(load_tags(path, Some(loaded.modified)).is_none());
+    }
+}
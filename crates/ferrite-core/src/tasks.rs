@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::{config::editor::Task, location_list::Location};
+
+/// Matches `file:line:column: message` as printed by most compilers
+/// and the `--> file:line:column` locations printed by rustc
+pub const DEFAULT_PROBLEM_MATCHER: &str = r"^\s*(?:-->\s*)?(?P<file>(?:[A-Za-z]:)?[^\s:]+):(?P<line>\d+):(?P<column>\d+)(?::\s*(?P<message>.+))?$";
+
+pub fn get_tasks_path(workspace_path: impl AsRef<Path>) -> PathBuf {
+    workspace_path.as_ref().join(".ferrite/tasks.toml")
+}
+
+pub fn problem_matcher(task: &Task) -> Result<Regex> {
+    Ok(Regex::new(
+        task.problem_matcher
+            .as_deref()
+            .unwrap_or(DEFAULT_PROBLEM_MATCHER),
+    )?)
+}
+
+/// Finds the problems in the output of a task. Locations that are not files in `root` are skipped.
+/// When the matcher has no message the last line before the location is used instead.
+pub fn match_problems(output: &str, matcher: &Regex, root: &Path) -> Vec<Location> {
+    let mut problems: Vec<Location> = Vec::new();
+    let mut last_line = "";
+    for line in output.lines() {
+        let Some(captures) = matcher.captures(line) else {
+            if !line.trim().is_empty() {
+                last_line = line.trim();
+            }
+            continue;
+        };
+        let (Some(file), Some(line), Some(column)) = (
+            captures.name("file"),
+            captures.name("line").and_then(|m| m.as_str().parse().ok()),
+            captures
+                .name("column")
+                .and_then(|m| m.as_str().parse().ok()),
+        ) else {
+            continue;
+        };
+        let path = root.join(file.as_str());
+        if !path.is_file() {
+            continue;
+        }
+        let message = captures
+            .name("message")
+            .map_or(last_line, |message| message.as_str().trim())
+            .to_string();
+        let problem = Location {
+            path,
+            line,
+            column,
+            message,
+        };
+        if !problems.contains(&problem) {
+            problems.push(problem);
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn match_output() {
+        let dir = tempdir::TempDir::new("ferrite-tasks").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("main.c"), "").unwrap();
+
+        let output = "\
+   Compiling foo v0.1.0
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:2:5
+  |
+main.c:3:7: error: expected ';'
+12:30:45: not a file
+";
+        let matcher = Regex::new(DEFAULT_PROBLEM_MATCHER).unwrap();
+        let problems = match_problems(output, &matcher, dir.path());
+        assert_eq!(
+            problems,
+            [
+                Location {
+                    path: dir.path().join("src/main.rs"),
+                    line: 2,
+                    column: 5,
+                    message: String::from("error[E0425]: cannot find value `x` in this scope"),
+                },
+                Location {
+                    path: dir.path().join("main.c"),
+                    line: 3,
+                    column: 7,
+                    message: String::from("error: expected ';'"),
+                },
+            ]
+        );
+    }
+}
@@ -10,13 +10,20 @@ use std::{
 use anyhow::Result;
 use memchr::memrchr;
 use serde::Deserialize;
-use style::{Color, ParseColorError};
+use style::{Color, Modifiers, ParseColorError};
+
+use crate::{
+    ansi::AnsiColor,
+    event_loop_proxy::EventLoopProxy,
+    watcher::{ConfigType, FileWatcher},
+};
 
 pub mod style;
 
 #[derive(Debug)]
 pub enum StyleLoadError {
     InvalidColor(ParseColorError),
+    InvalidModifier(String),
     StyleNotFound(String),
 }
 
@@ -24,6 +31,7 @@ impl fmt::Display for StyleLoadError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             StyleLoadError::InvalidColor(err) => err.fmt(f),
+            StyleLoadError::InvalidModifier(s) => write!(f, "unknown modifier: {s}"),
             StyleLoadError::StyleNotFound(s) => write!(f, "style not found: {s}"),
         }
     }
@@ -48,6 +56,9 @@ impl Error for StyleLoadError {
 struct Style {
     fg: Option<String>,
     bg: Option<String>,
+    /// Any of `bold`, `italic`, `underlined` and `crossed_out`
+    #[serde(default)]
+    modifiers: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,8 +67,30 @@ struct Theme {
     #[serde(flatten)]
     items: HashMap<String, Style>,
     syntax: HashMap<String, Style>,
+    /// Palette colors used for the 16 basic ansi colors, keyed by `ANSI_NAMES`
+    #[serde(default)]
+    ansi: HashMap<String, String>,
 }
 
+const ANSI_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+
 impl Theme {
     pub fn get_style(&self, name: &str) -> Result<style::Style> {
         match self.items.get(name) {
@@ -65,6 +98,21 @@ impl Theme {
             None => Err(StyleLoadError::StyleNotFound(name.to_string()))?,
         }
     }
+
+    /// Gets the style `name` or the first of `fallbacks` the theme has so older themes keep working.
+    /// Fallbacks starting with `syntax:` are looked up in the syntax table.
+    pub fn get_style_or(&self, name: &str, fallbacks: &[&str]) -> Result<style::Style> {
+        for name in [name].iter().chain(fallbacks) {
+            let style = match name.strip_prefix("syntax:") {
+                Some(name) => self.syntax.get(name),
+                None => self.items.get(*name),
+            };
+            if let Some(style) = style {
+                return raw_style_to_style(style, &self.palette);
+            }
+        }
+        Err(StyleLoadError::StyleNotFound(name.to_string()))?
+    }
 }
 
 fn raw_style_to_style(s: &Style, palette: &HashMap<String, String>) -> Result<style::Style> {
@@ -84,6 +132,13 @@ fn raw_style_to_style(s: &Style, palette: &HashMap<String, String>) -> Result<st
         }
     }
 
+    for name in &s.modifiers {
+        match Modifiers::from_name(name) {
+            Some(modifier) => style.modifiers |= modifier,
+            None => Err(StyleLoadError::InvalidModifier(name.clone()))?,
+        }
+    }
+
     Ok(style)
 }
 
@@ -105,6 +160,28 @@ pub struct EditorTheme {
     pub completer: style::Style,
     pub completer_selected: style::Style,
     pub cursorline: style::Style,
+    pub cursor: style::Style,
+    pub secondary_cursor: style::Style,
+    pub drop_target: style::Style,
+    pub picker_selection: style::Style,
+    pub tab_bar: style::Style,
+    pub tab_bar_active: style::Style,
+    pub diff_add: style::Style,
+    pub diff_remove: style::Style,
+    pub conflict_marker: style::Style,
+    pub conflict_ours: style::Style,
+    pub conflict_theirs: style::Style,
+    pub bookmark: style::Style,
+    pub breadcrumbs: style::Style,
+    pub trailing_whitespace: style::Style,
+    pub invisible: style::Style,
+    pub suspicious_char: style::Style,
+    pub diagnostic_error: style::Style,
+    pub diagnostic_warning: style::Style,
+    pub diagnostic_info: style::Style,
+    pub diagnostic_hint: style::Style,
+    /// The 16 basic ansi colors, xterm colors are used for the ones the theme does not set
+    pub ansi: [Color; 16],
     // syntax styles
     syntax: HashMap<String, style::Style>,
 }
@@ -131,33 +208,105 @@ impl EditorTheme {
             completer: theme.get_style("editor.completer")?,
             completer_selected: theme.get_style("editor.completer.selected")?,
             cursorline: theme.get_style("editor.cursorline")?,
+            cursor: theme.get_style_or("editor.cursor", &["editor.text"])?,
+            secondary_cursor: theme.get_style_or(
+                "editor.cursor.secondary",
+                &["editor.dim_text", "editor.text"],
+            )?,
+            drop_target: theme.get_style_or("editor.drop_target", &["editor.selection"])?,
+            picker_selection: theme
+                .get_style_or("editor.picker.selection", &["editor.selection"])?,
+            tab_bar: theme.get_style_or("editor.tab_bar", &["editor.info_line.unfocused"])?,
+            tab_bar_active: theme.get_style_or("editor.tab_bar.active", &["editor.info_line"])?,
+            diff_add: theme
+                .get_style_or("editor.diff.add", &["syntax:diff.plus", "editor.text"])?,
+            diff_remove: theme.get_style_or(
+                "editor.diff.remove",
+                &["syntax:diff.minus", "editor.error_text"],
+            )?,
+            conflict_marker: theme.get_style_or(
+                "editor.conflict.marker",
+                &["editor.diagnostic.warning", "syntax:warning", "editor.text"],
+            )?,
+            conflict_ours: theme.get_style_or(
+                "editor.conflict.ours",
+                &["editor.diff.add", "syntax:diff.plus", "editor.text"],
+            )?,
+            conflict_theirs: theme.get_style_or(
+                "editor.conflict.theirs",
+                &[
+                    "editor.diff.remove",
+                    "syntax:diff.minus",
+                    "editor.error_text",
+                ],
+            )?,
+            bookmark: theme.get_style_or(
+                "editor.bookmark",
+                &["editor.current_line_nr", "editor.line_nr"],
+            )?,
+            breadcrumbs: theme
+                .get_style_or("editor.breadcrumbs", &["editor.info_line.unfocused"])?,
+            trailing_whitespace: theme.get_style_or(
+                "editor.whitespace.trailing",
+                &["editor.diagnostic.warning", "editor.error_text"],
+            )?,
+            invisible: theme.get_style_or(
+                "editor.whitespace.invisible",
+                &["editor.whitespace.trailing", "editor.error_text"],
+            )?,
+            suspicious_char: theme.get_style_or(
+                "editor.unicode.suspicious",
+                &["editor.diagnostic.warning", "editor.error_text"],
+            )?,
+            diagnostic_error: theme
+                .get_style_or("editor.diagnostic.error", &["editor.error_text"])?,
+            diagnostic_warning: theme.get_style_or(
+                "editor.diagnostic.warning",
+                &["syntax:warning", "editor.error_text"],
+            )?,
+            diagnostic_info: theme
+                .get_style_or("editor.diagnostic.info", &["syntax:info", "editor.text"])?,
+            diagnostic_hint: theme.get_style_or(
+                "editor.diagnostic.hint",
+                &["syntax:hint", "editor.dim_text"],
+            )?,
+            ansi: {
+                let mut ansi = [Color::default(); 16];
+                for (i, name) in ANSI_NAMES.iter().enumerate() {
+                    ansi[i] = match theme
+                        .ansi
+                        .get(*name)
+                        .and_then(|color| theme.palette.get(color))
+                    {
+                        Some(color) => Color::from_str(color)?,
+                        None => Color::from(style::ansi256_to_rgb(i as u8)),
+                    };
+                }
+                ansi
+            },
 
             syntax: {
                 let mut syntax = HashMap::new();
                 for (key, style) in theme.syntax.into_iter() {
                     syntax.insert(key, raw_style_to_style(&style, &theme.palette)?);
                 }
-                syntax
+                inherit_scopes(&syntax)
             },
         })
     }
 
+    /// Style of the syntax scope `name`, scopes the theme does not have fall back to their
+    /// closest parent so `keyword.control.conditional` can fall back to `keyword`
     pub fn get_syntax(&self, name: &str) -> style::Style {
-        let mut name = name;
-        loop {
-            match self.syntax.get(name) {
-                Some(style) => return style.clone(),
-                None => match memrchr(b'.', name.as_bytes()) {
-                    Some(i) => name = &name[..i],
-                    None => break,
-                },
-            }
+        if let Some(style) = closest_scope(&self.syntax, name) {
+            return style.clone();
         }
 
         thread_local! {
             static MISSING: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
         }
 
+        let name = name.split('.').next().unwrap_or(name);
         MISSING.with(|missing| {
             let mut missing = missing.borrow_mut();
             if !missing.contains(name) {
@@ -173,11 +322,43 @@ impl EditorTheme {
         Self::parse_theme(&fs::read_to_string(path)?)
     }
 
-    pub fn load_themes() -> HashMap<String, EditorTheme> {
+    pub fn ansi_color(&self, color: AnsiColor) -> Color {
+        match color {
+            AnsiColor::Indexed(i) if i < 16 => self.ansi[i as usize],
+            AnsiColor::Indexed(i) => Color::from(style::ansi256_to_rgb(i)),
+            AnsiColor::Rgb(rgb) => Color::from(rgb),
+        }
+    }
+
+    /// Style for a logger or diagnostic level such as `ERROR` or `warning`
+    pub fn diagnostic(&self, level: &str) -> &style::Style {
+        match level.trim().to_ascii_lowercase().as_str() {
+            "error" => &self.diagnostic_error,
+            "warn" | "warning" => &self.diagnostic_warning,
+            "info" => &self.diagnostic_info,
+            _ => &self.diagnostic_hint,
+        }
+    }
+
+    /// Directories themes are loaded from, earlier directories take precedence
+    fn theme_dirs() -> Vec<PathBuf> {
         let mut theme_dirs = vec![PathBuf::from("themes")];
         if let Some(dirs) = directories::ProjectDirs::from("", "", "ferrite") {
             theme_dirs.push(dirs.config_dir().join("themes"));
         }
+        theme_dirs
+    }
+
+    /// Returns the file the theme called `name` is loaded from
+    pub fn find_theme_file(name: &str) -> Option<PathBuf> {
+        Self::theme_dirs()
+            .into_iter()
+            .map(|dir| dir.join(format!("{name}.toml")))
+            .find(|path| path.is_file())
+    }
+
+    pub fn load_themes() -> HashMap<String, EditorTheme> {
+        let theme_dirs = Self::theme_dirs();
 
         tracing::info!("Loading themes from: {:#?}", theme_dirs);
 
@@ -225,6 +406,75 @@ impl EditorTheme {
     }
 }
 
+/// The style of the scope `name` or of its closest parent scope
+fn closest_scope<'a>(
+    scopes: &'a HashMap<String, style::Style>,
+    name: &str,
+) -> Option<&'a style::Style> {
+    let mut name = name;
+    loop {
+        match scopes.get(name) {
+            Some(style) => return Some(style),
+            None => name = &name[..memrchr(b'.', name.as_bytes())?],
+        }
+    }
+}
+
+/// Fills in what the scopes do not set from their parent scopes,
+/// so `keyword.control` set to italic keeps the color of `keyword`
+fn inherit_scopes(scopes: &HashMap<String, style::Style>) -> HashMap<String, style::Style> {
+    scopes
+        .iter()
+        .map(|(name, style)| {
+            let mut style = style.clone();
+            let mut parent = name.as_str();
+            while let Some(i) = memrchr(b'.', parent.as_bytes()) {
+                parent = &parent[..i];
+                if let Some(parent_style) = scopes.get(parent) {
+                    style = style.inherit(parent_style);
+                }
+            }
+            (name.clone(), style)
+        })
+        .collect()
+}
+
+pub struct ThemeFile;
+
+impl ConfigType<EditorTheme> for ThemeFile {
+    fn from_file(path: impl AsRef<Path>) -> Result<EditorTheme> {
+        EditorTheme::load_theme(path)
+    }
+}
+
+/// Reloads the active theme when its file changes
+pub struct ThemeWatcher {
+    name: String,
+    watcher: Option<FileWatcher<EditorTheme, ThemeFile>>,
+}
+
+impl ThemeWatcher {
+    pub fn new(name: &str, proxy: Box<dyn EventLoopProxy>) -> Self {
+        let watcher = EditorTheme::find_theme_file(name).and_then(|path| {
+            FileWatcher::new(path, proxy)
+                .inspect_err(|err| tracing::error!("Error watching theme `{name}`: {err}"))
+                .ok()
+        });
+        Self {
+            name: name.to_string(),
+            watcher,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn poll_update(&mut self) -> Option<Result<EditorTheme>> {
+        self.watcher.as_mut()?.poll_update()
+    }
+}
+
 impl Default for EditorTheme {
     fn default() -> Self {
         EditorTheme::parse_theme(include_str!("../../../themes/catppuccin_mocha.toml")).unwrap()
@@ -289,6 +539,85 @@ mod tests {
         let _ = EditorTheme::default();
     }
 
+    /// The styles every theme has to set
+    const REQUIRED_STYLES: &str = r##"
+"editor.line_nr" = { fg = "fg" }
+"editor.current_line_nr" = { fg = "fg" }
+"editor.text" = { fg = "fg" }
+"editor.dim_text" = { fg = "fg" }
+"editor.info_line" = { fg = "fg" }
+"editor.info_line.unfocused" = { fg = "fg" }
+"editor.background" = { bg = "bg" }
+"editor.selection" = { bg = "sel" }
+"editor.border" = { fg = "fg" }
+"editor.pane_border" = { fg = "fg" }
+"editor.ruler" = { fg = "fg" }
+"editor.error_text" = { fg = "red" }
+"editor.search.match" = { bg = "sel" }
+"editor.fuzzy.match" = { fg = "red" }
+"editor.completer" = { fg = "fg" }
+"editor.completer.selected" = { fg = "fg" }
+"editor.cursorline" = { bg = "bg" }
+"##;
+
+    #[test]
+    fn fallback_styles() {
+        let theme = EditorTheme::parse_theme(&format!(
+            r##"{REQUIRED_STYLES}
+[syntax]
+"diff.plus" = {{ fg = "green" }}
+
+[palette]
+fg = "#ffffff"
+bg = "#000000"
+sel = "#333333"
+red = "#ff0000"
+green = "#00ff00"
+"##
+        ))
+        .unwrap();
+        assert_eq!(theme.picker_selection.bg.unwrap().r, 0x33 as f32 / 255.0);
+        assert_eq!(theme.diff_add.fg.unwrap().g, 1.0);
+        assert_eq!(theme.diff_remove.fg.unwrap().r, 1.0);
+        assert_eq!(theme.diagnostic("WARN").fg.unwrap().r, 1.0);
+    }
+
+    #[test]
+    fn syntax_scopes() {
+        let theme = EditorTheme::parse_theme(&format!(
+            r##"{REQUIRED_STYLES}
+[syntax]
+"keyword" = {{ fg = "red" }}
+"keyword.control" = {{ modifiers = ["italic"] }}
+"keyword.control.conditional" = {{ fg = "green", modifiers = ["bold"] }}
+
+[palette]
+fg = "#ffffff"
+bg = "#000000"
+sel = "#333333"
+red = "#ff0000"
+green = "#00ff00"
+"##
+        ))
+        .unwrap();
+        let control = theme.get_syntax("keyword.control.repeat");
+        assert_eq!(control.fg.unwrap().r, 1.0);
+        assert_eq!(control.modifiers, Modifiers::ITALIC);
+        let conditional = theme.get_syntax("keyword.control.conditional");
+        assert_eq!(conditional.fg.unwrap().g, 1.0);
+        assert_eq!(conditional.modifiers, Modifiers::ITALIC | Modifiers::BOLD);
+        assert!(theme.get_syntax("string").modifiers.is_empty());
+
+        let invalid = REQUIRED_STYLES.replace(
+            r#""editor.text" = { fg = "fg" }"#,
+            r#""editor.text" = { fg = "fg", modifiers = ["blinking"] }"#,
+        );
+        assert!(EditorTheme::parse_theme(&format!(
+            "{invalid}\n[syntax]\n[palette]\nfg = \"#ffffff\"\n"
+        ))
+        .is_err());
+    }
+
     #[cfg(feature = "embed-themes")]
     #[test]
     fn parse_embedded_themes() {
@@ -55,8 +55,140 @@ impl FromStr for Color {
     }
 }
 
+impl From<[u8; 3]> for Color {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Self {
+            r: f32::from(r) / 255.0,
+            g: f32::from(g) / 255.0,
+            b: f32::from(b) / 255.0,
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// How text is drawn besides its colors
+    #[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+    pub struct Modifiers: u8 {
+        const BOLD = 0b0000_0001;
+        const ITALIC = 0b0000_0010;
+        const UNDERLINED = 0b0000_0100;
+        const CROSSED_OUT = 0b0000_1000;
+    }
+}
+
+impl Modifiers {
+    /// The modifier called `name` in themes
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bold" => Some(Self::BOLD),
+            "italic" => Some(Self::ITALIC),
+            "underlined" => Some(Self::UNDERLINED),
+            "crossed_out" => Some(Self::CROSSED_OUT),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Style {
     pub fg: Option<Color>,
     pub bg: Option<Color>,
+    pub modifiers: Modifiers,
+}
+
+impl Style {
+    /// `self` with the colors it does not set taken from `parent` and the modifiers of both
+    pub fn inherit(&self, parent: &Style) -> Style {
+        Style {
+            fg: self.fg.or(parent.fg),
+            bg: self.bg.or(parent.bg),
+            modifiers: self.modifiers | parent.modifiers,
+        }
+    }
+}
+
+/// The 16 basic ansi colors as rendered by xterm
+const ANSI16: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (i32::from(*a) - i32::from(b)).pow(2) as u32)
+        .sum()
+}
+
+/// Color of `index` in the xterm 256 color palette
+pub fn ansi256_to_rgb(index: u8) -> [u8; 3] {
+    match index {
+        0..=15 => ANSI16[index as usize],
+        16..=231 => {
+            let index = index - 16;
+            [index / 36, index / 6 % 6, index % 6].map(|i| CUBE_LEVELS[i as usize])
+        }
+        _ => [8 + (index - 232) * 10; 3],
+    }
+}
+
+/// Index of the closest of the 16 basic ansi colors
+pub fn rgb_to_ansi16(rgb: [u8; 3]) -> u8 {
+    (0..ANSI16.len())
+        .min_by_key(|i| distance(rgb, ANSI16[*i]))
+        .unwrap() as u8
+}
+
+/// Index of the closest color in the 6x6x6 cube or gray ramp of the xterm 256 color palette
+pub fn rgb_to_ansi256(rgb: [u8; 3]) -> u8 {
+    let level = |v: u8| match v {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (v - 35) / 40,
+    };
+    let [r, g, b] = rgb.map(level);
+    let cube = [r, g, b].map(|i| CUBE_LEVELS[i as usize]);
+
+    let average = (rgb.iter().map(|v| u32::from(*v)).sum::<u32>() / 3) as u8;
+    let gray_index = (average.saturating_sub(3) / 10).min(23);
+    let gray = 8 + gray_index * 10;
+
+    if distance(rgb, [gray; 3]) < distance(rgb, cube) {
+        232 + gray_index
+    } else {
+        16 + 36 * r + 6 * g + b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantize() {
+        assert_eq!(rgb_to_ansi16([250, 10, 10]), 9);
+        assert_eq!(rgb_to_ansi16([20, 20, 30]), 0);
+        assert_eq!(rgb_to_ansi256([255, 0, 0]), 196);
+        assert_eq!(rgb_to_ansi256([0, 0, 0]), 16);
+        assert_eq!(rgb_to_ansi256([128, 128, 128]), 244);
+        assert_eq!(rgb_to_ansi256([0x89, 0xb4, 0xfa]), 111);
+        assert_eq!(ansi256_to_rgb(196), [255, 0, 0]);
+        assert_eq!(ansi256_to_rgb(244), [128, 128, 128]);
+    }
 }
@@ -0,0 +1,126 @@
+use std::{path::Path, process::Stdio, time::Instant};
+
+use anyhow::Result;
+
+use crate::{
+    buffer::ViewId,
+    config::editor::ToolOutput,
+    engine::get_exec,
+    job_manager::{CancellationToken, Progressor},
+    workspace::BufferId,
+};
+
+/// Quotes `s` so the shell passes it on as a single argument
+pub(crate) fn shell_quote(s: &str) -> String {
+    if cfg!(windows) {
+        // cmd.exe expands `%VAR%` even inside of quotes, `%cd:~,%` expands to nothing
+        // so the `%` before it is left alone
+        format!("\"{}\"", s.replace('"', "\"\"").replace('%', "%%cd:~,%"))
+    } else {
+        format!("'{}'", s.replace('\'', r"'\''"))
+    }
+}
+
+/// Replaces the `$FILE`, `$SELECTION` and `$LINE` variables in a tool command.
+/// `line` is one based. The command is expanded in a single pass so variables
+/// that appear in the substituted values are left alone.
+pub fn expand_command(cmd: &str, file: Option<&Path>, selection: &str, line: usize) -> String {
+    let file = file
+        .map(|file| file.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let variables = [
+        ("$FILE", shell_quote(&file)),
+        ("$SELECTION", shell_quote(selection)),
+        ("$LINE", line.to_string()),
+    ];
+    let mut expanded = String::with_capacity(cmd.len());
+    let mut rest = cmd;
+    while let Some(idx) = rest.find('$') {
+        expanded.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        match variables
+            .iter()
+            .find(|(variable, _)| rest.starts_with(variable))
+        {
+            Some((variable, value)) => {
+                expanded.push_str(value);
+                rest = &rest[variable.len()..];
+            }
+            None => {
+                expanded.push('$');
+                rest = &rest[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+pub struct ToolInput {
+    pub name: String,
+    pub cmd: String,
+    pub output: ToolOutput,
+    pub buffer_id: BufferId,
+    pub view_id: ViewId,
+    /// Last edit of the buffer when the tool was started, edits in place are discarded if it has changed since
+    pub last_edit: Instant,
+}
+
+pub struct ToolResult {
+    pub name: String,
+    pub output: ToolOutput,
+    pub buffer_id: BufferId,
+    pub view_id: ViewId,
+    pub last_edit: Instant,
+    pub text: String,
+}
+
+pub fn run_tool_job(
+    _: CancellationToken,
+    _: &mut Progressor<()>,
+    input: ToolInput,
+) -> Result<ToolResult> {
+    let mut command = get_exec(&input.cmd);
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+    let output = command.output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "`{}` failed: {}",
+            input.name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(ToolResult {
+        name: input.name,
+        output: input.output,
+        buffer_id: input.buffer_id,
+        view_id: input.view_id,
+        last_edit: input.last_edit,
+        text: String::from_utf8_lossy(&output.stdout).into_owned(),
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand() {
+        assert_eq!(
+            expand_command(
+                "grep -n $SELECTION $FILE +$LINE",
+                Some(Path::new("src/it's.rs")),
+                "a b",
+                12
+            ),
+            r"grep -n 'a b' 'src/it'\''s.rs' +12"
+        );
+        assert_eq!(expand_command("echo $FILE", None, "", 1), "echo ''");
+        assert_eq!(
+            expand_command("echo $SELECTION $HOME", None, "$FILE", 1),
+            "echo '$FILE' $HOME"
+        );
+    }
+}
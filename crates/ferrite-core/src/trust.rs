@@ -0,0 +1,88 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trust {
+    Trusted,
+    Untrusted,
+    /// The workspace has never been opened before
+    Unknown,
+}
+
+/// Workspaces the user has been asked about, shared between all instances of the editor
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrustedWorkspaces {
+    #[serde(default)]
+    trusted: Vec<PathBuf>,
+    #[serde(default)]
+    untrusted: Vec<PathBuf>,
+}
+
+impl TrustedWorkspaces {
+    fn load() -> Result<Self> {
+        let path = get_trust_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = get_trust_path()?;
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+pub fn get_trust(workspace_path: impl AsRef<Path>) -> Trust {
+    let Ok(path) = dunce::canonicalize(workspace_path) else {
+        return Trust::Unknown;
+    };
+    let workspaces = match TrustedWorkspaces::load() {
+        Ok(workspaces) => workspaces,
+        Err(err) => {
+            tracing::error!("Error loading trusted workspaces: {err}");
+            return Trust::Unknown;
+        }
+    };
+
+    if workspaces.trusted.contains(&path) {
+        Trust::Trusted
+    } else if workspaces.untrusted.contains(&path) {
+        Trust::Untrusted
+    } else {
+        Trust::Unknown
+    }
+}
+
+pub fn is_trusted(workspace_path: impl AsRef<Path>) -> bool {
+    get_trust(workspace_path) == Trust::Trusted
+}
+
+pub fn set_trusted(workspace_path: impl AsRef<Path>, trusted: bool) -> Result<()> {
+    let path = dunce::canonicalize(workspace_path)?;
+    let mut workspaces = TrustedWorkspaces::load()?;
+    workspaces.trusted.retain(|entry| *entry != path);
+    workspaces.untrusted.retain(|entry| *entry != path);
+    if trusted {
+        workspaces.trusted.push(path);
+    } else {
+        workspaces.untrusted.push(path);
+    }
+    workspaces.save()
+}
+
+pub fn get_trust_path() -> Result<PathBuf> {
+    let Some(directories) = directories::ProjectDirs::from("", "", "ferrite") else {
+        return Err(anyhow::Error::msg("Unable to find project directory"));
+    };
+    Ok(directories
+        .data_dir()
+        .join("ferrite-trusted-workspaces.json"))
+}
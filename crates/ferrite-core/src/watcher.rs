@@ -49,7 +49,7 @@ pub struct FileWatcher<T, C> {
 
 impl<T, C> FileWatcher<T, C>
 where
-    T: 'static + for<'a> Deserialize<'a> + Send,
+    T: 'static + Send,
     C: ConfigType<T>,
 {
     pub fn new(path: impl AsRef<Path>, proxy: Box<dyn EventLoopProxy>) -> Result<Self> {
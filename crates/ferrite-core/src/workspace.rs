@@ -2,6 +2,8 @@ use std::{
     collections::HashMap,
     fs,
     path::{Path, PathBuf},
+    sync::{Mutex, PoisonError, TryLockError},
+    time::Duration,
 };
 
 use anyhow::Result;
@@ -16,9 +18,16 @@ use crate::{
     file_explorer::{FileExplorer, FileExplorerId},
     indent::Indentation,
     layout::panes::{layout::Layout, PaneKind, Panes},
+    trust,
     watcher::{FileWatcher, TomlConfig},
 };
 
+/// How often the workspace is saved while the editor is running
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Least amount of time between two workspace snapshots kept for the panic hook
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
 slotmap::new_key_type! {
     pub struct BufferId;
 }
@@ -41,8 +50,14 @@ pub struct Workspace {
     pub file_explorers: SlotMap<FileExplorerId, FileExplorer>,
     pub buffer_extra_data: Vec<BufferData>,
     pub panes: Panes,
+    /// Layouts saved by name with `save-layout`
+    pub layouts: HashMap<String, Layout>,
+    /// Font scale of the gui
+    pub scale: f32,
     pub config: WorkspaceConfig,
     pub config_watcher: Option<FileWatcher<WorkspaceConfig, TomlConfig>>,
+    /// Untrusted workspaces run in safe mode where shell commands, formatters and the workspace config are disabled
+    pub trusted: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -50,6 +65,14 @@ pub struct WorkspaceData {
     buffers: Vec<BufferData>,
     open_buffers: Vec<PathBuf>,
     layout: Layout,
+    #[serde(default)]
+    layouts: HashMap<String, Layout>,
+    #[serde(default = "default_scale")]
+    scale: f32,
+}
+
+fn default_scale() -> f32 {
+    1.0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +83,9 @@ pub struct BufferData {
     pub col_pos: usize,
     pub language: String,
     pub indent: Indentation,
+    /// Bookmarked lines
+    #[serde(default)]
+    pub bookmarks: Vec<usize>,
 }
 
 impl Default for Workspace {
@@ -73,20 +99,26 @@ impl Default for Workspace {
             file_explorers: SlotMap::with_key(),
             buffer_extra_data: Vec::new(),
             panes: Panes::new(buffer_id, view_id),
+            layouts: HashMap::new(),
+            scale: default_scale(),
             config: WorkspaceConfig::default(),
             config_watcher: None,
+            trusted: false,
         }
     }
 }
 
 impl Workspace {
-    pub fn save_workspace(&self) -> Result<()> {
+    /// The path of the workspace file of the current directory and the workspace serialized into it
+    pub fn snapshot(&self) -> Result<(PathBuf, String)> {
         let workspace_dir = std::env::current_dir()?;
         let workspace_file = get_workspace_path(workspace_dir)?;
         let mut workspace_data = WorkspaceData {
             buffers: self.buffer_extra_data.clone(),
             open_buffers: Vec::new(),
             layout: Layout::from_panes(&self.panes, &self.buffers, &self.file_explorers),
+            layouts: self.layouts.clone(),
+            scale: self.scale,
         };
 
         for (path, buffer) in self
@@ -102,11 +134,15 @@ impl Workspace {
             workspace_data.open_buffers.push(path.to_path_buf());
         }
 
-        fs::create_dir_all(workspace_file.parent().unwrap())?;
-        fs::write(
-            &workspace_file,
-            serde_json::to_string_pretty(&workspace_data)?.as_bytes(),
-        )?;
+        Ok((
+            workspace_file,
+            serde_json::to_string_pretty(&workspace_data)?,
+        ))
+    }
+
+    pub fn save_workspace(&self) -> Result<()> {
+        let (workspace_file, json) = self.snapshot()?;
+        write_workspace(&workspace_file, &json)?;
         tracing::info!("Save workspace to: {workspace_file:?}");
         Ok(())
     }
@@ -173,26 +209,87 @@ impl Workspace {
             buffer.ensure_every_cursor_is_valid();
         }
 
-        let config = WorkspaceConfig::load(&workspace_dir).unwrap_or_else(|err| {
+        let mut workspace = Self {
+            buffers,
+            file_explorers,
+            buffer_extra_data: workspace.buffers.clone(),
+            panes,
+            layouts: workspace.layouts,
+            scale: workspace.scale,
+            config: WorkspaceConfig::default(),
+            config_watcher: None,
+            trusted: trust::is_trusted(&workspace_dir),
+        };
+        if workspace.trusted {
+            workspace.load_config(proxy);
+        }
+
+        Ok(workspace)
+    }
+
+    /// Loads the config of the workspace in the current directory
+    /// Sets the proxy every buffer wakes once its syntax has been highlighted
+    pub fn set_proxy(&mut self, proxy: Box<dyn EventLoopProxy>) {
+        for buffer in self.buffers.values_mut() {
+            buffer.set_proxy(proxy.dup());
+        }
+    }
+
+    pub fn load_config(&mut self, proxy: Box<dyn EventLoopProxy>) {
+        let workspace_dir = match std::env::current_dir() {
+            Ok(dir) => dir,
+            Err(err) => {
+                tracing::error!("Error loading workspace config: {err}");
+                return;
+            }
+        };
+
+        self.config = WorkspaceConfig::load(&workspace_dir).unwrap_or_else(|err| {
             tracing::error!("Error loading workspace config: {err}");
             WorkspaceConfig::default()
         });
 
-        let mut config_watcher = None;
-        match FileWatcher::new(get_config_path(&workspace_dir), proxy.dup()) {
-            Ok(watcher) => config_watcher = Some(watcher),
+        match FileWatcher::new(get_config_path(&workspace_dir), proxy) {
+            Ok(watcher) => self.config_watcher = Some(watcher),
             Err(err) => tracing::error!("Error starting language config watcher: {err}"),
         }
+    }
+}
 
-        Ok(Self {
-            buffers,
-            file_explorers,
-            buffer_extra_data: workspace.buffers.clone(),
-            panes,
-            config,
-            config_watcher,
-        })
+/// Writes the workspace file through a temporary file so a crash while writing it
+/// does not leave a half written workspace behind
+pub fn write_workspace(path: &Path, json: &str) -> Result<()> {
+    fs::create_dir_all(path.parent().unwrap())?;
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    fs::write(&tmp, json.as_bytes())?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// The workspace as it was at the last snapshot, written by the panic hook as `Drop` does not
+/// run when the editor aborts
+static EMERGENCY_WORKSPACE: Mutex<Option<(PathBuf, String)>> = Mutex::new(None);
+
+/// Replaces the workspace written by `emergency_save_workspace`
+pub fn set_emergency_workspace(path: PathBuf, json: String) {
+    *EMERGENCY_WORKSPACE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some((path, json));
+}
+
+/// Writes the last workspace snapshot to its workspace file.
+/// Meant to be called from a panic hook so it never blocks on the snapshot lock.
+pub fn emergency_save_workspace() -> Result<()> {
+    let snapshot = match EMERGENCY_WORKSPACE.try_lock() {
+        Ok(snapshot) => snapshot.clone(),
+        Err(TryLockError::Poisoned(snapshot)) => snapshot.into_inner().clone(),
+        Err(TryLockError::WouldBlock) => anyhow::bail!("Workspace snapshot is locked"),
+    };
+    if let Some((path, json)) = snapshot {
+        write_workspace(&path, &json)?;
     }
+    Ok(())
 }
 
 pub fn get_workspace_path(workspace_path: impl AsRef<Path>) -> Result<PathBuf> {
@@ -216,3 +313,22 @@ pub fn get_workspace_path(workspace_path: impl AsRef<Path>) -> Result<PathBuf> {
 pub fn get_config_path(workspace_path: impl AsRef<Path>) -> PathBuf {
     workspace_path.as_ref().join(".editor/ferrite/config.toml")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emergency_save() {
+        let dir = tempdir::TempDir::new("ferrite-workspace").unwrap();
+        let path = dir.path().join("workspace").join("workspace.json");
+        set_emergency_workspace(path.clone(), "{}".into());
+        emergency_save_workspace().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+        assert!(!dir
+            .path()
+            .join("workspace")
+            .join("workspace.json.tmp")
+            .exists());
+    }
+}
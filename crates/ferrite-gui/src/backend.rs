@@ -1,6 +1,9 @@
-use std::mem;
+use std::{mem, time::Instant};
 
-use ferrite_core::{config::editor::FontWeight, theme::EditorTheme};
+use ferrite_core::{
+    config::editor::{CursorType, FontWeight, Gui},
+    theme::EditorTheme,
+};
 use glyphon::{
     cosmic_text::Scroll, Attrs, AttrsList, Buffer, BufferLine, Color, Family, FontSystem, Metrics,
     Shaping, TextArea, TextBounds, Weight,
@@ -14,6 +17,8 @@ use tui::{
 use unicode_width::UnicodeWidthStr;
 
 use crate::{
+    cursor::CursorAnimation,
+    fonts::FontFallback,
     glue::convert_style,
     renderer::{
         geometry_renderer::{Geometry, Quad},
@@ -26,6 +31,36 @@ const FONT_SIZE: f32 = 14.0;
 const REPLACED_SYMBOLS: &[&str] = &["☺️", "☹️"];
 const REPLACEMENT_SYMBOLS: &[&str] = &["☺️ ", "☹️ "];
 
+/// A grapheme cluster that is drawn in `width` cells, starting at `col_idx`
+struct GlyphCluster<'a> {
+    col_idx: usize,
+    cell: &'a Cell,
+    symbol: &'a str,
+    width: usize,
+}
+
+/// Glyph clusters of a line, the cells covered by a wide cluster are skipped
+fn glyph_clusters(line: &[Cell]) -> impl Iterator<Item = GlyphCluster<'_>> {
+    let mut col_idx = 0;
+    std::iter::from_fn(move || {
+        let cell = line.get(col_idx)?;
+        let symbol = match REPLACED_SYMBOLS.iter().position(|s| *s == cell.symbol()) {
+            Some(idx) => REPLACEMENT_SYMBOLS[idx],
+            None => cell.symbol(),
+        };
+        // The replacement symbols are only wider for shaping, the cell keeps the width it was laid out with
+        let width = cell.symbol().width();
+        let cluster = GlyphCluster {
+            col_idx,
+            cell,
+            symbol,
+            width,
+        };
+        col_idx += width.max(1);
+        Some(cluster)
+    })
+}
+
 fn calculate_cell_size(
     font_system: &mut FontSystem,
     metrics: Metrics,
@@ -48,6 +83,40 @@ fn calculate_cell_size(
     (w, metrics.line_height)
 }
 
+/// Quad covering the part of the cell at `x`, `y` that the cursor is drawn on
+fn cursor_quad(
+    cursor_type: CursorType,
+    x: f32,
+    y: f32,
+    (cell_width, cell_height): (f32, f32),
+    thickness: f32,
+    color: Color,
+) -> Quad {
+    match cursor_type {
+        CursorType::Block => Quad {
+            x,
+            y,
+            width: cell_width,
+            height: cell_height,
+            color,
+        },
+        CursorType::Line => Quad {
+            x,
+            y,
+            width: thickness,
+            height: cell_height,
+            color,
+        },
+        CursorType::Underline => Quad {
+            x,
+            y: y + cell_height - thickness,
+            width: cell_width,
+            height: thickness,
+            color,
+        },
+    }
+}
+
 pub struct WgpuBackend {
     width: f32,
     height: f32,
@@ -62,6 +131,9 @@ pub struct WgpuBackend {
     // font config
     font_family: String,
     font_weight: FontWeight,
+    fonts: FontFallback,
+    ligatures: bool,
+    cursor: CursorAnimation,
 }
 
 #[profiling::all_functions]
@@ -106,6 +178,9 @@ impl WgpuBackend {
             scale: 1.0,
             font_family,
             font_weight,
+            fonts: FontFallback::default(),
+            ligatures: true,
+            cursor: CursorAnimation::default(),
         }
     }
 
@@ -125,7 +200,12 @@ impl WgpuBackend {
         let _ = self.clear();
     }
 
-    pub fn prepare(&mut self, theme: &EditorTheme, font_system: &mut FontSystem) -> Bundle {
+    pub fn prepare(
+        &mut self,
+        theme: &EditorTheme,
+        gui: &Gui,
+        font_system: &mut FontSystem,
+    ) -> Bundle {
         let mut top_geometry = Geometry::default();
         let mut bottom_geometry = Geometry::default();
         self.buffer
@@ -138,6 +218,31 @@ impl WgpuBackend {
             .1
             .unwrap_or(glyphon::Color::rgb(255, 255, 255));
 
+        let cursor_color = convert_style(&theme.cursor).0.unwrap_or(default_fg);
+        let secondary_cursor_color = convert_style(&theme.secondary_cursor)
+            .0
+            .unwrap_or(cursor_color);
+
+        let now = Instant::now();
+        let primary_cursor = self.cells.iter().enumerate().find_map(|(line_idx, line)| {
+            line.iter()
+                .position(|cell| cell.modifier.contains(tui::style::Modifier::SLOW_BLINK))
+                .map(|col_idx| (col_idx, line_idx))
+        });
+        match primary_cursor {
+            Some((col_idx, line_idx)) => self.cursor.set_target(
+                (
+                    col_idx as f32 * self.cell_width,
+                    line_idx as f32 * self.cell_height,
+                ),
+                gui,
+                now,
+            ),
+            None => self.cursor.hide(),
+        }
+        let cursor_visible = self.cursor.is_visible(gui, now);
+        let cursor_animating = self.cursor.is_animating(gui, now);
+
         let default_attrs = Attrs::new()
             .weight(Weight(self.font_weight as u16))
             .color(default_fg)
@@ -148,20 +253,34 @@ impl WgpuBackend {
                 "",
                 glyphon::cosmic_text::LineEnding::Lf,
                 AttrsList::new(default_attrs),
-                Shaping::Basic,
+                Shaping::Advanced,
             ),
         );
         for (line_idx, line) in self.cells.iter_mut().enumerate() {
-            let mut skip_next = false;
             let mut attr_list = AttrsList::new(default_attrs);
             let mut line_text = String::new();
             let mut idx = 0;
-            for (col_idx, cell) in line.iter().enumerate() {
-                if skip_next {
-                    skip_next = false;
-                    continue;
+            for (cluster_idx, cluster) in glyph_clusters(line).enumerate() {
+                let GlyphCluster {
+                    col_idx,
+                    cell,
+                    symbol,
+                    width: symbol_width,
+                } = cluster;
+                let family = cell
+                    .symbol()
+                    .chars()
+                    .next()
+                    .and_then(|ch| self.fonts.font_for(font_system, ch));
+                let mut attrs = match family {
+                    Some(family) => default_attrs.family(Family::Name(family)),
+                    None => default_attrs,
+                };
+                // Runs with different metadata are shaped separately, alternating it between
+                // neighbouring clusters keeps ligatures from forming across them
+                if !self.ligatures {
+                    attrs = attrs.metadata(cluster_idx % 2);
                 }
-                let mut attrs = default_attrs;
                 let mut fg = default_fg;
                 let mut bg = None;
                 if let tui::style::Color::Rgb(r, g, b) = cell.fg {
@@ -172,23 +291,31 @@ impl WgpuBackend {
                     bg = Some(glyphon::Color::rgb(r, g, b));
                 }
 
-                if cell.modifier.contains(tui::style::Modifier::REVERSED) {
+                let primary = cell.modifier.contains(tui::style::Modifier::SLOW_BLINK);
+                let cursor_type = if primary {
+                    gui.cursor_type
+                } else {
+                    gui.secondary_cursor_type()
+                };
+                let is_cursor =
+                    primary || cell.modifier.contains(tui::style::Modifier::RAPID_BLINK);
+                // The animated cursor is drawn after the cells
+                let draw_cursor = is_cursor && cursor_visible && !(primary && cursor_animating);
+
+                if cell.modifier.contains(tui::style::Modifier::REVERSED)
+                    && !(is_cursor && cursor_type == CursorType::Block && !draw_cursor)
+                {
                     let mut tmp = bg.unwrap_or(default_bg);
                     mem::swap(&mut fg, &mut tmp);
                     bg = Some(tmp);
                 }
 
                 attrs = attrs.color(fg);
-                let symbol =
-                    if let Some(idx) = REPLACED_SYMBOLS.iter().position(|s| *s == cell.symbol()) {
-                        REPLACEMENT_SYMBOLS[idx]
-                    } else {
-                        cell.symbol()
-                    };
-
-                let symbol_width = symbol.width();
-                if symbol_width > 1 {
-                    skip_next = true;
+                if cell.modifier.contains(tui::style::Modifier::BOLD) {
+                    attrs = attrs.weight(Weight::BOLD);
+                }
+                if cell.modifier.contains(tui::style::Modifier::ITALIC) {
+                    attrs = attrs.style(glyphon::Style::Italic);
                 }
                 line_text.push_str(symbol);
                 attr_list.add_span(idx..(idx + symbol.len()), attrs);
@@ -202,24 +329,71 @@ impl WgpuBackend {
                     color: bg.unwrap_or(Color::rgba(0, 0, 0, 0)),
                 });
 
-                if cell.modifier.contains(tui::style::Modifier::SLOW_BLINK) {
-                    let cursor_width = 2.0 * self.scale;
+                if cell.modifier.contains(tui::style::Modifier::UNDERLINED) {
+                    let thickness = self.scale.max(1.0);
+                    top_geometry.quads.push(Quad {
+                        x: col_idx as f32 * self.cell_width,
+                        y: (line_idx + 1) as f32 * self.cell_height - thickness,
+                        width: self.cell_width * symbol_width as f32,
+                        height: thickness,
+                        color: fg,
+                    });
+                }
+
+                if cell.modifier.contains(tui::style::Modifier::CROSSED_OUT) {
+                    let thickness = self.scale.max(1.0);
                     top_geometry.quads.push(Quad {
                         x: col_idx as f32 * self.cell_width,
-                        y: line_idx as f32 * self.cell_height,
-                        width: cursor_width,
-                        height: self.cell_height,
-                        color: Color::rgb(82, 139, 255),
+                        y: (line_idx as f32 + 0.5) * self.cell_height - thickness / 2.0,
+                        width: self.cell_width * symbol_width as f32,
+                        height: thickness,
+                        color: fg,
                     });
                 }
+
+                if draw_cursor && cursor_type != CursorType::Block {
+                    top_geometry.quads.push(cursor_quad(
+                        cursor_type,
+                        col_idx as f32 * self.cell_width,
+                        line_idx as f32 * self.cell_height,
+                        (self.cell_width, self.cell_height),
+                        2.0 * self.scale,
+                        if primary {
+                            cursor_color
+                        } else {
+                            secondary_cursor_color
+                        },
+                    ));
+                }
             }
 
-            self.buffer.lines[line_idx] = BufferLine::new(
-                &line_text,
-                glyphon::cosmic_text::LineEnding::Lf,
-                attr_list,
-                Shaping::Advanced,
-            );
+            if gui.reuse_text_lines {
+                // Keeps the shaping of the line if it did not change
+                self.buffer.lines[line_idx].set_text(
+                    &line_text,
+                    glyphon::cosmic_text::LineEnding::Lf,
+                    attr_list,
+                );
+            } else {
+                self.buffer.lines[line_idx] = BufferLine::new(
+                    &line_text,
+                    glyphon::cosmic_text::LineEnding::Lf,
+                    attr_list,
+                    Shaping::Advanced,
+                );
+            }
+        }
+
+        if cursor_visible && cursor_animating {
+            let (x, y) = self.cursor.position(gui, now);
+            top_geometry.quads.push(cursor_quad(
+                gui.cursor_type,
+                x,
+                y,
+                (self.cell_width, self.cell_height),
+                2.0 * self.scale,
+                cursor_color,
+            ));
         }
 
         self.buffer.set_scroll(Scroll {
@@ -268,6 +442,29 @@ impl WgpuBackend {
         }
     }
 
+    /// Stops blinking the cursor while the window is unfocused
+    pub fn set_focused(&mut self, focused: bool) {
+        self.cursor.set_blink_paused(!focused);
+        self.redraw = true;
+    }
+
+    /// When the cursor has to be drawn again to blink or animate it
+    pub fn next_cursor_frame(&self, gui: &Gui, now: Instant) -> Option<Instant> {
+        self.cursor.next_frame(gui, now)
+    }
+
+    pub fn set_fonts(&mut self, font_system: &mut FontSystem, gui: &Gui) {
+        if self.fonts.update(gui) {
+            font_system.shape_run_cache.trim(0);
+            self.buffer.lines.clear();
+            self.redraw = true;
+        }
+        if self.ligatures != gui.ligatures {
+            self.ligatures = gui.ligatures;
+            self.redraw = true;
+        }
+    }
+
     pub fn set_scale(&mut self, font_system: &mut FontSystem, scale: f32) {
         if self.scale != scale {
             self.scale = scale;
@@ -356,3 +553,37 @@ impl Backend for WgpuBackend {
         Ok(Size::new(self.columns, self.lines))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(symbols: &[&str]) -> Vec<Cell> {
+        symbols
+            .iter()
+            .map(|symbol| {
+                let mut cell = Cell::default();
+                cell.set_symbol(symbol);
+                cell
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clusters_follow_symbol_width() {
+        let line = line(&["=", "😀", " ", ">", "☺️", " ", "a"]);
+        let clusters: Vec<_> = glyph_clusters(&line)
+            .map(|cluster| (cluster.col_idx, cluster.symbol, cluster.width))
+            .collect();
+        assert_eq!(
+            clusters,
+            [
+                (0, "=", 1),
+                (1, "😀", 2),
+                (3, ">", 1),
+                (4, "☺️ ", 2),
+                (6, "a", 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::time::{Duration, Instant};
+
+use ferrite_core::config::editor::Gui;
+
+/// Where the primary cursor is drawn while it slides between cells and whether it is blinked on
+pub struct CursorAnimation {
+    from: (f32, f32),
+    to: (f32, f32),
+    start: Instant,
+    shown: bool,
+    /// The cursor is shown without blinking while the window is unfocused
+    blink_paused: bool,
+}
+
+impl Default for CursorAnimation {
+    fn default() -> Self {
+        Self {
+            from: (0.0, 0.0),
+            to: (0.0, 0.0),
+            start: Instant::now(),
+            shown: false,
+            blink_paused: false,
+        }
+    }
+}
+
+impl CursorAnimation {
+    /// Moves the cursor to `target`, the slide starts from where the cursor is currently drawn
+    pub fn set_target(&mut self, target: (f32, f32), gui: &Gui, now: Instant) {
+        if self.shown && self.to == target {
+            return;
+        }
+        self.from = if self.shown {
+            self.position(gui, now)
+        } else {
+            target
+        };
+        self.to = target;
+        self.start = now;
+        self.shown = true;
+    }
+
+    pub fn set_blink_paused(&mut self, paused: bool) {
+        self.blink_paused = paused;
+    }
+
+    /// Called when there is no primary cursor on screen
+    pub fn hide(&mut self) {
+        self.shown = false;
+    }
+
+    fn progress(&self, gui: &Gui, now: Instant) -> f32 {
+        if !gui.cursor_animation || gui.cursor_animation_duration == 0 {
+            return 1.0;
+        }
+        let duration = Duration::from_millis(gui.cursor_animation_duration);
+        (now.duration_since(self.start).as_secs_f32() / duration.as_secs_f32()).min(1.0)
+    }
+
+    pub fn position(&self, gui: &Gui, now: Instant) -> (f32, f32) {
+        // Cubic ease out
+        let t = 1.0 - (1.0 - self.progress(gui, now)).powi(3);
+        (
+            self.from.0 + (self.to.0 - self.from.0) * t,
+            self.from.1 + (self.to.1 - self.from.1) * t,
+        )
+    }
+
+    pub fn is_animating(&self, gui: &Gui, now: Instant) -> bool {
+        self.shown && self.progress(gui, now) < 1.0
+    }
+
+    /// Every move restarts the blink with the cursor visible
+    pub fn is_visible(&self, gui: &Gui, now: Instant) -> bool {
+        if self.blink_paused || !gui.cursor_blink || gui.cursor_blink_interval == 0 {
+            return true;
+        }
+        let elapsed = now.duration_since(self.start).as_millis();
+        (elapsed / u128::from(gui.cursor_blink_interval)) % 2 == 0
+    }
+
+    /// When the cursor has to be drawn again
+    pub fn next_frame(&self, gui: &Gui, now: Instant) -> Option<Instant> {
+        if !self.shown {
+            return None;
+        }
+        if self.is_animating(gui, now) {
+            return Some(now);
+        }
+        if self.blink_paused || !gui.cursor_blink || gui.cursor_blink_interval == 0 {
+            return None;
+        }
+        let interval = gui.cursor_blink_interval;
+        let elapsed = now.duration_since(self.start).as_millis() as u64;
+        Some(self.start + Duration::from_millis((elapsed / interval + 1) * interval))
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use ferrite_core::config::editor::{FontWeight, Gui};
+use glyphon::{
+    cosmic_text::fontdb::{Family, Query},
+    FontSystem, Weight,
+};
+
+/// The script names that can be used as keys in `script_fonts`
+pub fn script_of(ch: char) -> Option<&'static str> {
+    let script = match ch as u32 {
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => "greek",
+        0x0400..=0x052F | 0x2DE0..=0x2DFF | 0xA640..=0xA69F => "cyrillic",
+        0x0590..=0x05FF | 0xFB1D..=0xFB4F => "hebrew",
+        0x0600..=0x06FF | 0x0750..=0x077F | 0x08A0..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => {
+            "arabic"
+        }
+        0x0900..=0x097F | 0xA8E0..=0xA8FF => "devanagari",
+        0x0E00..=0x0E7F => "thai",
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xA960..=0xA97F | 0xAC00..=0xD7FF => "hangul",
+        0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => "kana",
+        0x2E80..=0x2FDF | 0x3000..=0x303F | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF => {
+            "han"
+        }
+        0x20000..=0x3FFFF => "han",
+        0x2600..=0x27BF | 0x1F000..=0x1FAFF => "emoji",
+        _ => return None,
+    };
+    Some(script)
+}
+
+/// Picks fonts for characters that are missing from the main font
+#[derive(Default)]
+pub struct FontFallback {
+    font_family: String,
+    font_weight: FontWeight,
+    fallback_fonts: Vec<String>,
+    script_fonts: Vec<(String, String)>,
+    cache: HashMap<char, Option<usize>>,
+}
+
+impl FontFallback {
+    /// Returns true if the configuration changed
+    pub fn update(&mut self, gui: &Gui) -> bool {
+        if self.font_family == gui.font_family
+            && self.font_weight == gui.font_weight
+            && self.fallback_fonts == gui.fallback_fonts
+            && self
+                .script_fonts
+                .iter()
+                .map(|(script, font)| (script, font))
+                .eq(&gui.script_fonts)
+        {
+            return false;
+        }
+        self.font_family = gui.font_family.clone();
+        self.font_weight = gui.font_weight;
+        self.fallback_fonts = gui.fallback_fonts.clone();
+        self.script_fonts = gui
+            .script_fonts
+            .iter()
+            .map(|(script, font)| (script.clone(), font.clone()))
+            .collect();
+        self.cache.clear();
+        true
+    }
+
+    /// Name of the font to use for `ch` or `None` if the main font should be used
+    pub fn font_for(&mut self, font_system: &mut FontSystem, ch: char) -> Option<&str> {
+        if ch.is_ascii() || (self.fallback_fonts.is_empty() && self.script_fonts.is_empty()) {
+            return None;
+        }
+
+        let index = match self.cache.get(&ch) {
+            Some(index) => *index,
+            None => {
+                let index = self.find_font(font_system, ch);
+                self.cache.insert(ch, index);
+                index
+            }
+        };
+        // The script font is stored after the fallback fonts
+        index.map(|index| match index.checked_sub(self.fallback_fonts.len()) {
+            Some(index) => self.script_fonts[index].1.as_str(),
+            None => self.fallback_fonts[index].as_str(),
+        })
+    }
+
+    fn find_font(&self, font_system: &mut FontSystem, ch: char) -> Option<usize> {
+        if self.has_glyph(font_system, &self.font_family, ch) {
+            return None;
+        }
+
+        let script = script_of(ch);
+        if let Some(index) = self
+            .script_fonts
+            .iter()
+            .position(|(name, _)| Some(name.as_str()) == script)
+        {
+            if self.has_glyph(font_system, &self.script_fonts[index].1, ch) {
+                return Some(self.fallback_fonts.len() + index);
+            }
+        }
+
+        self.fallback_fonts
+            .iter()
+            .position(|font| self.has_glyph(font_system, font, ch))
+    }
+
+    fn has_glyph(&self, font_system: &mut FontSystem, family: &str, ch: char) -> bool {
+        let query = Query {
+            families: &[Family::Name(family)],
+            weight: Weight(self.font_weight as u16),
+            ..Default::default()
+        };
+        let Some(id) = font_system.db().query(&query) else {
+            return false;
+        };
+        font_system
+            .get_font(id)
+            .is_some_and(|font| font.as_swash().charmap().map(ch) != 0)
+    }
+}
@@ -0,0 +1,52 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Limits how often frames are drawn and keeps track of the frames drawn in the last second
+#[derive(Default)]
+pub struct FramePacer {
+    /// When the frames of the last second were presented
+    frames: VecDeque<Instant>,
+    /// Something changed that has not been drawn yet
+    pending: bool,
+}
+
+impl FramePacer {
+    /// Called when what is shown has changed and a frame has to be drawn
+    pub fn request_frame(&mut self) {
+        self.pending = true;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// When the requested frame may be drawn without going over `max_fps`,
+    /// `None` if it can be drawn right away. A `max_fps` of 0 does not limit the frames.
+    pub fn next_frame(&self, max_fps: u32, now: Instant) -> Option<Instant> {
+        if max_fps == 0 {
+            return None;
+        }
+        let frame_time = Duration::from_secs(1) / max_fps;
+        let next = *self.frames.back()? + frame_time;
+        (next > now).then_some(next)
+    }
+
+    pub fn frame_presented(&mut self, now: Instant) {
+        self.pending = false;
+        self.frames.push_back(now);
+        while self
+            .frames
+            .front()
+            .is_some_and(|frame| now.duration_since(*frame) > Duration::from_secs(1))
+        {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Frames presented in the last second
+    pub fn fps(&self) -> usize {
+        self.frames.len()
+    }
+}
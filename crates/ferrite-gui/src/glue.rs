@@ -43,6 +43,10 @@ pub fn convert_keycode(
         NamedKey::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
             ferrite_core::keymap::keycode::KeyCode::BackTab
         }
+        // Plain spaces are inserted as text
+        NamedKey::Space if modifiers.contains(KeyModifiers::CONTROL) => {
+            ferrite_core::keymap::keycode::KeyCode::Char(' ')
+        }
         NamedKey::Delete => ferrite_core::keymap::keycode::KeyCode::Delete,
         NamedKey::Insert => ferrite_core::keymap::keycode::KeyCode::Insert,
         NamedKey::Escape => ferrite_core::keymap::keycode::KeyCode::Esc,
@@ -0,0 +1,55 @@
+use ferrite_core::{config::editor::CursorType, theme::EditorTheme};
+use ferrite_tui::glue::cursor_style;
+use tui::{buffer::Buffer, layout::Position, style::Modifier};
+use unicode_width::UnicodeWidthStr;
+
+/// Text that is being composed with an input method
+pub struct Preedit {
+    pub text: String,
+    /// Byte range of the cursor in `text`, `None` hides the cursor
+    pub cursor: Option<(usize, usize)>,
+}
+
+/// Cell of the primary cursor
+pub fn find_primary_cursor(buf: &Buffer) -> Option<Position> {
+    let index = buf
+        .content
+        .iter()
+        .position(|cell| cell.modifier.contains(Modifier::SLOW_BLINK))?;
+    Some(buf.pos_of(index).into())
+}
+
+impl Preedit {
+    /// Draws the composed text underlined at `position` and moves the primary cursor into it
+    pub fn render(
+        &self,
+        buf: &mut Buffer,
+        position: Position,
+        theme: &EditorTheme,
+        cursor_type: CursorType,
+    ) {
+        let area = buf.area;
+        if let Some(cell) = buf.cell_mut(position) {
+            cell.modifier
+                .remove(Modifier::SLOW_BLINK | Modifier::REVERSED);
+        }
+
+        let width = area.right().saturating_sub(position.x);
+        buf.set_stringn(
+            position.x,
+            position.y,
+            &self.text,
+            width.into(),
+            tui::style::Style::default().add_modifier(Modifier::UNDERLINED),
+        );
+
+        if let Some((start, _)) = self.cursor {
+            let offset = self.text.get(..start).map_or(0, |text| text.width()) as u16;
+            let cursor =
+                Position::new(position.x + offset.min(width.saturating_sub(1)), position.y);
+            if let Some(cell) = buf.cell_mut(cursor) {
+                cell.set_style(cursor_style(theme, cursor_type, true));
+            }
+        }
+    }
+}
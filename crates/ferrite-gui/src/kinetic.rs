@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+
+const MIN_FRICTION: f64 = 1.0;
+const MAX_FRICTION: f64 = 20.0;
+/// Below this many lines per second the scroll stops
+const MIN_VELOCITY: f64 = 0.5;
+/// Scroll events further apart than this do not count towards the velocity
+const MAX_EVENT_GAP: Duration = Duration::from_millis(100);
+
+/// Keeps a touchpad scroll going with a decaying velocity after the fingers are lifted
+pub struct KineticScroll {
+    /// Lines and columns per second
+    velocity: (f64, f64),
+    last_event: Option<Instant>,
+    last_frame: Instant,
+    active: bool,
+}
+
+impl Default for KineticScroll {
+    fn default() -> Self {
+        Self {
+            velocity: (0.0, 0.0),
+            last_event: None,
+            last_frame: Instant::now(),
+            active: false,
+        }
+    }
+}
+
+impl KineticScroll {
+    /// Records a scroll of `delta` columns and lines made while the fingers are on the touchpad
+    pub fn track(&mut self, delta: (f64, f64), now: Instant) {
+        self.active = false;
+        let velocity = match self.last_event {
+            Some(last_event) if now.duration_since(last_event) < MAX_EVENT_GAP => {
+                let dt = now.duration_since(last_event).as_secs_f64().max(0.001);
+                (delta.0 / dt, delta.1 / dt)
+            }
+            _ => (0.0, 0.0),
+        };
+        // Smooth out the uneven spacing of the events
+        self.velocity = (
+            self.velocity.0 * 0.2 + velocity.0 * 0.8,
+            self.velocity.1 * 0.2 + velocity.1 * 0.8,
+        );
+        self.last_event = Some(now);
+    }
+
+    /// Starts the kinetic scroll when the fingers are lifted
+    pub fn release(&mut self, now: Instant) {
+        let recent = self
+            .last_event
+            .is_some_and(|last_event| now.duration_since(last_event) < MAX_EVENT_GAP);
+        self.active = recent && self.velocity.0.hypot(self.velocity.1) > MIN_VELOCITY;
+        self.last_event = None;
+        self.last_frame = now;
+    }
+
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.velocity = (0.0, 0.0);
+        self.last_event = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Columns and lines to scroll since the last step
+    pub fn step(&mut self, friction: f64, now: Instant) -> Option<(f64, f64)> {
+        if !self.active {
+            return None;
+        }
+        let friction = friction.clamp(MIN_FRICTION, MAX_FRICTION);
+        let dt = now.duration_since(self.last_frame).as_secs_f64();
+        self.last_frame = now;
+        // Integral of the exponentially decaying velocity over the step
+        let decay = (-friction * dt).exp();
+        let distance = (
+            self.velocity.0 * (1.0 - decay) / friction,
+            self.velocity.1 * (1.0 - decay) / friction,
+        );
+        self.velocity = (self.velocity.0 * decay, self.velocity.1 * decay);
+        if self.velocity.0.hypot(self.velocity.1) < MIN_VELOCITY {
+            self.stop();
+        }
+        Some(distance)
+    }
+}
@@ -1,7 +1,7 @@
 use std::{
     env, iter,
     sync::{mpsc, Arc},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
@@ -12,10 +12,12 @@ use ferrite_core::{
     clipboard,
     cmd::Cmd,
     config::editor::{default_font, FontWeight},
+    engine::{MAX_SCALE, MIN_SCALE},
     event_loop_proxy::{EventLoopControlFlow, UserEvent},
     keymap::{self, keycode::KeyModifiers},
     layout::panes::PaneKind,
     logger::LogMessage,
+    recovery, workspace,
 };
 use ferrite_tui::{
     glue::{ferrite_to_tui_rect, tui_to_ferrite_rect},
@@ -23,20 +25,28 @@ use ferrite_tui::{
     TuiApp,
 };
 use ferrite_utility::{line_ending::LineEnding, point::Point};
+use frame_pacer::FramePacer;
 use glue::convert_keycode;
-use renderer::{Layer, Renderer};
+use ime::{find_primary_cursor, Preedit};
+use kinetic::KineticScroll;
+use renderer::{Layer, LayerStats, Renderer};
 use tui::{layout::Position, Terminal};
 use winit::{
-    dpi::PhysicalPosition,
-    event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, Event, Ime, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent},
     event_loop::{EventLoop, EventLoopBuilder, EventLoopWindowTarget},
     keyboard::{Key, ModifiersState, NamedKey},
     window::{CursorIcon, Window, WindowBuilder},
 };
 
 mod backend;
+mod cursor;
 mod event_loop_wrapper;
+mod fonts;
+mod frame_pacer;
 mod glue;
+mod ime;
+mod kinetic;
 pub mod renderer;
 pub mod srgb;
 
@@ -44,7 +54,12 @@ pub fn run(args: &Args, rx: mpsc::Receiver<LogMessage>) -> Result<()> {
     {
         std::panic::set_hook(Box::new(move |info| {
             println!();
-            let _ = std::fs::write("./panic.txt", format!("{info:?}"));
+            if let Err(err) = recovery::emergency_dump() {
+                println!("Error saving unsaved buffers: {err}");
+            }
+            if let Err(err) = workspace::emergency_save_workspace() {
+                println!("Error saving workspace: {err}");
+            }
             let backtrace = std::backtrace::Backtrace::force_capture();
             let panic_info = format!("{backtrace}\n{info}");
             let _ = std::fs::write("panic.txt", &panic_info);
@@ -75,6 +90,13 @@ struct GuiApp {
     modifiers: KeyModifiers,
     mouse_position: PhysicalPosition<f64>,
     primary_mouse_button_pressed: bool,
+    preedit: Option<Preedit>,
+    /// Cell the input method candidate window was last placed at
+    ime_position: Option<Position>,
+    kinetic_scroll: KineticScroll,
+    /// Unrounded scale while pinching
+    pinch_scale: Option<f32>,
+    frame_pacer: FramePacer,
 }
 
 impl GuiApp {
@@ -91,6 +113,7 @@ impl GuiApp {
                 .build(event_loop)
                 .unwrap(),
         );
+        window.set_ime_allowed(true);
         let size = window.inner_size();
 
         let mut backends = if cfg!(windows) {
@@ -175,13 +198,14 @@ impl GuiApp {
         ))?;
 
         let term_size = base_terminal.size()?;
-        let tui_app = TuiApp::new(
+        let mut tui_app = TuiApp::new(
             args,
             event_loop_wrapper,
             rx,
             term_size.width,
             term_size.height,
         )?;
+        tui_app.engine.font_zoom = true;
 
         let terminals = [base_terminal, overlay_terminal];
 
@@ -206,6 +230,11 @@ impl GuiApp {
             modifiers: KeyModifiers::empty(),
             mouse_position: PhysicalPosition::default(),
             primary_mouse_button_pressed: false,
+            preedit: None,
+            ime_position: None,
+            kinetic_scroll: KineticScroll::default(),
+            pinch_scale: None,
+            frame_pacer: FramePacer::default(),
         })
     }
 
@@ -239,15 +268,33 @@ impl GuiApp {
 
                     for terminal in &mut self.terminals {
                         let backend = terminal.backend_mut();
-                        if backend.scale() != self.tui_app.engine.scale {
+                        if backend.scale() != self.tui_app.engine.workspace.scale {
                             backend.set_scale(
                                 &mut self.renderer.font_system,
-                                self.tui_app.engine.scale,
+                                self.tui_app.engine.workspace.scale,
                             );
                         }
                     }
 
+                    let friction = self
+                        .tui_app
+                        .engine
+                        .config
+                        .editor
+                        .gui
+                        .touchpad
+                        .kinetic_friction;
+                    if let Some((columns, lines)) =
+                        self.kinetic_scroll.step(friction, Instant::now())
+                    {
+                        self.scroll(columns, lines);
+                        self.window.request_redraw();
+                    }
+
                     self.tui_app.engine.do_polling(&mut self.control_flow);
+                    if std::mem::take(&mut self.tui_app.engine.focus_requested) {
+                        self.window.focus_window();
+                    }
                     match self.control_flow {
                         EventLoopControlFlow::Poll => {
                             event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
@@ -271,15 +318,67 @@ impl GuiApp {
                             &mut self.renderer.font_system,
                             self.tui_app.engine.config.editor.gui.font_weight,
                         );
+                        terminal.backend_mut().set_fonts(
+                            &mut self.renderer.font_system,
+                            &self.tui_app.engine.config.editor.gui,
+                        );
                     }
 
-                    self.render_tui();
+                    self.update_present_mode();
+
+                    let gui = &self.tui_app.engine.config.editor.gui;
+                    // Font changes and the overlay change what is shown without the engine changing
+                    if gui.debug_overlay || self.terminals.iter().any(|t| t.backend().redraw) {
+                        self.tui_app.damage.damage_frame();
+                    }
+                    let size = self.terminals[0].size().unwrap_or_default();
+                    if self.tui_app.needs_render(tui::layout::Rect::new(
+                        0,
+                        0,
+                        size.width,
+                        size.height,
+                    )) {
+                        self.render_tui();
+                    }
                     if self.terminals.iter().any(|t| t.backend().redraw) {
-                        self.window.request_redraw();
+                        self.frame_pacer.request_frame();
                         for terminal in &mut self.terminals {
                             terminal.backend_mut().redraw = false;
                         }
                     }
+
+                    let gui = &self.tui_app.engine.config.editor.gui;
+                    let now = Instant::now();
+                    let next_cursor_frame = self
+                        .terminals
+                        .iter()
+                        .filter_map(|t| t.backend().next_cursor_frame(gui, now))
+                        .min();
+                    let mut wake_at = next_cursor_frame.filter(|frame| *frame > now);
+                    if next_cursor_frame.is_some_and(|frame| frame <= now) {
+                        self.frame_pacer.request_frame();
+                    }
+                    if self.frame_pacer.is_pending() {
+                        match self.frame_pacer.next_frame(gui.max_fps, now) {
+                            Some(next_frame) => {
+                                wake_at = Some(wake_at.map_or(next_frame, |at| at.min(next_frame)))
+                            }
+                            None => self.window.request_redraw(),
+                        }
+                    }
+                    if let Some(wake_at) = wake_at {
+                        match self.control_flow {
+                            EventLoopControlFlow::Wait => event_loop.set_control_flow(
+                                winit::event_loop::ControlFlow::WaitUntil(wake_at),
+                            ),
+                            EventLoopControlFlow::WaitMax(duration) => event_loop.set_control_flow(
+                                winit::event_loop::ControlFlow::WaitUntil(
+                                    wake_at.min(now + duration),
+                                ),
+                            ),
+                            _ => (),
+                        }
+                    }
                 }
                 _event => (),
             })
@@ -311,9 +410,20 @@ impl GuiApp {
     }
 
     pub fn input(&mut self, event_loop: &EventLoopWindowTarget<UserEvent>, event: WindowEvent) {
+        // Moving the mouse only changes what is shown once it selects text or focuses a pane
+        if !matches!(event, WindowEvent::CursorMoved { .. }) {
+            self.tui_app.damage.damage_frame();
+        }
         match event {
-            WindowEvent::Focused(false) => {
-                self.modifiers = KeyModifiers::empty();
+            WindowEvent::Focused(focused) => {
+                if !focused {
+                    self.modifiers = KeyModifiers::empty();
+                }
+                for terminal in &mut self.terminals {
+                    terminal.backend_mut().set_focused(focused);
+                }
+                self.tui_app.engine.focus_changed(focused);
+                self.window.request_redraw();
             }
             WindowEvent::Resized(physical_size) => {
                 self.resize(physical_size);
@@ -322,24 +432,69 @@ impl GuiApp {
             WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
                 self.scale_factor = scale_factor;
             }
-            WindowEvent::MouseWheel { delta, .. } => match delta {
-                MouseScrollDelta::LineDelta(_, y) => {
-                    self.tui_app.engine.handle_single_input_command(
-                        Cmd::VerticalScroll {
-                            distance: -y as f64 * 3.0,
-                        },
-                        &mut EventLoopControlFlow::Poll,
-                    );
+            WindowEvent::MouseWheel { delta, .. }
+                if self.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                let y = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y as f64,
+                    MouseScrollDelta::PixelDelta(physical_pos) => physical_pos.y,
+                };
+                let cmd = if y > 0.0 {
+                    Cmd::ZoomIn
+                } else if y < 0.0 {
+                    Cmd::ZoomOut
+                } else {
+                    return;
+                };
+                self.tui_app
+                    .engine
+                    .handle_single_input_command(cmd, &mut EventLoopControlFlow::Poll);
+            }
+            WindowEvent::MouseWheel { delta, phase, .. } => {
+                let (columns, lines) = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => (-x as f64 * 3.0, -y as f64 * 3.0),
+                    MouseScrollDelta::PixelDelta(physical_pos) => {
+                        let backend = self.terminals[0].backend();
+                        (
+                            physical_pos.x / backend.cell_width as f64,
+                            physical_pos.y / backend.line_height() as f64,
+                        )
+                    }
+                };
+                let backend = self.terminals[0].backend();
+                let line = (self.mouse_position.y / backend.cell_height as f64) as u16;
+                if self.tui_app.scroll_palette(line, lines.round() as isize) {
+                    return;
                 }
-                MouseScrollDelta::PixelDelta(physical_pos) => {
-                    let line_height = self.terminals[0].backend().line_height() as f64;
-                    let distance = physical_pos.y / line_height;
-                    self.tui_app.engine.handle_single_input_command(
-                        Cmd::VerticalScroll { distance },
-                        &mut EventLoopControlFlow::Poll,
-                    );
+                let touchpad = &self.tui_app.engine.config.editor.gui.touchpad;
+                if matches!(delta, MouseScrollDelta::PixelDelta(_)) && touchpad.kinetic_scrolling {
+                    let now = Instant::now();
+                    match phase {
+                        TouchPhase::Moved => self.kinetic_scroll.track((columns, lines), now),
+                        TouchPhase::Ended => self.kinetic_scroll.release(now),
+                        TouchPhase::Started | TouchPhase::Cancelled => self.kinetic_scroll.stop(),
+                    }
                 }
-            },
+                self.scroll(columns, lines);
+            }
+            WindowEvent::TouchpadMagnify { delta, phase, .. }
+                if self.tui_app.engine.config.editor.gui.touchpad.pinch_zoom =>
+            {
+                let current = self.tui_app.engine.workspace.scale;
+                match phase {
+                    TouchPhase::Started => self.pinch_scale = Some(current),
+                    TouchPhase::Moved => {
+                        let scale = self.pinch_scale.get_or_insert(current);
+                        *scale = (*scale * (1.0 + delta as f32)).clamp(MIN_SCALE, MAX_SCALE);
+                        // The engine rounds the scale so only changes that survive rounding are sent
+                        if (*scale * 10.0).round() != (current * 10.0).round() {
+                            let scale = *scale;
+                            self.tui_app.engine.set_scale(scale);
+                        }
+                    }
+                    TouchPhase::Ended | TouchPhase::Cancelled => self.pinch_scale = None,
+                }
+            }
             WindowEvent::ModifiersChanged(modifiers) => {
                 let modifiers = modifiers.state();
                 self.modifiers.set(
@@ -353,8 +508,27 @@ impl GuiApp {
                     modifiers.contains(ModifiersState::SHIFT),
                 );
             }
+            WindowEvent::Ime(ime) => match ime {
+                Ime::Preedit(text, cursor) => {
+                    self.preedit = (!text.is_empty()).then_some(Preedit { text, cursor });
+                }
+                Ime::Commit(text) => {
+                    self.preedit = None;
+                    let mut control_flow = self.control_flow;
+                    self.tui_app
+                        .engine
+                        .handle_input_command(Cmd::Insert { text }, &mut control_flow);
+                    if control_flow == EventLoopControlFlow::Exit {
+                        event_loop.exit();
+                    }
+                }
+                Ime::Enabled | Ime::Disabled => self.preedit = None,
+            },
+            // Keys belong to the input method while composing
+            WindowEvent::KeyboardInput { .. } if self.preedit.is_some() => (),
             WindowEvent::KeyboardInput { event, .. } => {
                 tracing::trace!("{:?}", event);
+                self.kinetic_scroll.stop();
                 let mut control_flow = self.control_flow;
 
                 if let Key::Named(key) = event.logical_key {
@@ -446,12 +620,28 @@ impl GuiApp {
 
                 let column = (self.mouse_position.x / backend.cell_width as f64).round() as u16;
                 let line = (self.mouse_position.y / backend.cell_height as f64) as u16;
-                if self.primary_mouse_button_pressed {
+                if self.tui_app.engine.drop_target.is_some() {
+                    self.update_drop_target();
+                } else if self.primary_mouse_button_pressed {
                     self.handle_drag(column, line);
+                } else {
+                    let pane = self.tui_app.pane_at(column, line);
+                    self.tui_app.engine.hover_pane(pane);
                 }
                 self.handle_hover(column, line);
             }
+            WindowEvent::HoveredFile(_) => self.update_drop_target(),
+            WindowEvent::HoveredFileCancelled => self.tui_app.engine.drop_target = None,
+            WindowEvent::DroppedFile(path) => {
+                if self.tui_app.engine.drop_target.is_none() {
+                    self.update_drop_target();
+                }
+                self.tui_app.engine.open_dropped_path(path);
+                self.window.focus_window();
+            }
+            WindowEvent::CursorLeft { .. } => self.tui_app.engine.hover_pane(None),
             WindowEvent::MouseInput { state, button, .. } => {
+                self.kinetic_scroll.stop();
                 let backend = self.terminals[0].backend();
 
                 let column = (self.mouse_position.x / backend.cell_width as f64).round() as u16;
@@ -462,6 +652,14 @@ impl GuiApp {
         }
     }
 
+    /// Highlights the pane under the mouse as the target for dropped files
+    fn update_drop_target(&mut self) {
+        let backend = self.terminals[0].backend();
+        let column = (self.mouse_position.x / backend.cell_width as f64).round() as u16;
+        let line = (self.mouse_position.y / backend.cell_height as f64) as u16;
+        self.tui_app.engine.drop_target = self.tui_app.pane_at(column, line);
+    }
+
     pub fn handle_hover(&mut self, column: u16, line: u16) {
         let mut cursor = CursorIcon::Default;
         for (pane_kind, pane_rect) in self
@@ -472,12 +670,14 @@ impl GuiApp {
             .get_pane_bounds(tui_to_ferrite_rect(self.tui_app.buffer_area))
         {
             if let PaneKind::Buffer(buffer_id, _) = pane_kind {
+                let header = self.tui_app.buffer_header_height(buffer_id);
                 let buffer = &self.tui_app.engine.workspace.buffers[buffer_id];
                 let (_, left_offset) = lines_to_left_offset(buffer.len_lines());
                 let mut rect = ferrite_to_tui_rect(pane_rect);
                 rect.x += left_offset as u16;
                 rect.width = rect.width.saturating_sub(left_offset as u16);
-                rect.height = rect.height.saturating_sub(1);
+                rect.y += header as u16;
+                rect.height = rect.height.saturating_sub(1 + header as u16);
                 if rect.contains(Position::new(column, line)) {
                     cursor = CursorIcon::Text
                 }
@@ -506,13 +706,14 @@ impl GuiApp {
                         if ferrite_to_tui_rect(pane_rect).contains(Position::new(column, line)) {
                             self.tui_app.engine.workspace.panes.make_current(pane_kind);
                             if let PaneKind::Buffer(buffer_id, view_id) = pane_kind {
+                                let header = self.tui_app.buffer_header_height(buffer_id);
                                 let buffer = &self.tui_app.engine.workspace.buffers[buffer_id];
                                 let (_, left_offset) = lines_to_left_offset(buffer.len_lines());
                                 let column = ((column as usize) + buffer.col_pos(view_id))
                                     .saturating_sub(pane_rect.x)
                                     .saturating_sub(left_offset);
                                 let line = (line as usize + buffer.line_pos(view_id))
-                                    .saturating_sub(pane_rect.y);
+                                    .saturating_sub(pane_rect.y + header);
                                 break 'block Some(Cmd::PastePrimary { column, line });
                             }
                         }
@@ -521,6 +722,25 @@ impl GuiApp {
                     None
                 }
                 (ElementState::Pressed, MouseButton::Left) => {
+                    if let Some(job) = self.tui_app.cancel_item_at(column, line) {
+                        self.tui_app.engine.job_manager.cancel(job);
+                        break 'block None;
+                    }
+                    if let Some(item) = self.tui_app.info_line_item_at(column, line) {
+                        let item = item.to_string();
+                        if let Some(pane) = self.tui_app.pane_at(column, line) {
+                            self.tui_app.engine.workspace.panes.make_current(pane);
+                        }
+                        break 'block Some(Cmd::InfoLineClick { item });
+                    }
+                    if let Some(symbol_line) = self.tui_app.breadcrumb_at(column, line) {
+                        if let Some(pane) = self.tui_app.pane_at(column, line) {
+                            self.tui_app.engine.workspace.panes.make_current(pane);
+                        }
+                        break 'block Some(Cmd::Goto {
+                            line: symbol_line as i64 + 1,
+                        });
+                    }
                     self.primary_mouse_button_pressed = true;
                     for (pane_kind, pane_rect) in self
                         .tui_app
@@ -532,6 +752,7 @@ impl GuiApp {
                         if ferrite_to_tui_rect(pane_rect).contains(Position::new(column, line)) {
                             self.tui_app.engine.workspace.panes.make_current(pane_kind);
                             if let PaneKind::Buffer(buffer_id, view_id) = pane_kind {
+                                let header = self.tui_app.buffer_header_height(buffer_id);
                                 let buffer = &self.tui_app.engine.workspace.buffers[buffer_id];
                                 self.tui_app.drag_start = Some(Point::new(
                                     column as usize + buffer.col_pos(view_id),
@@ -543,7 +764,7 @@ impl GuiApp {
                                     .saturating_sub(pane_rect.x)
                                     .saturating_sub(left_offset);
                                 let line = (line as usize + buffer.line_pos(view_id))
-                                    .saturating_sub(pane_rect.y);
+                                    .saturating_sub(pane_rect.y + header);
                                 break 'block Some(Cmd::ClickCell {
                                     spawn_cursor: self.modifiers.contains(KeyModifiers::ALT),
                                     column,
@@ -584,6 +805,7 @@ impl GuiApp {
                 if ferrite_to_tui_rect(pane_rect).contains(Position::new(drag_column, drag_line)) {
                     self.tui_app.engine.workspace.panes.make_current(pane_kind);
                     if let PaneKind::Buffer(buffer_id, view_id) = pane_kind {
+                        let header = self.tui_app.buffer_header_height(buffer_id);
                         // TODO maybe scroll more of the buffer into view when going outside its bounds
                         if let Some(Point { line, column }) = self.tui_app.drag_start {
                             let buffer = &mut self.tui_app.engine.workspace.buffers[buffer_id];
@@ -593,7 +815,7 @@ impl GuiApp {
                                 let column = column
                                     .saturating_sub(left_offset)
                                     .saturating_sub(pane_rect.x);
-                                let line = line.saturating_sub(pane_rect.y);
+                                let line = line.saturating_sub(pane_rect.y + header);
                                 Point::new(column, line)
                             };
 
@@ -602,7 +824,7 @@ impl GuiApp {
                                     .saturating_sub(left_offset)
                                     .saturating_sub(pane_rect.x);
                                 let line = (drag_line as usize + buffer.line_pos(view_id))
-                                    .saturating_sub(pane_rect.y);
+                                    .saturating_sub(pane_rect.y + header);
                                 Point::new(column, line)
                             };
 
@@ -623,13 +845,86 @@ impl GuiApp {
         }
     }
 
+    /// Scrolls the current pane by fractional columns and lines
+    fn scroll(&mut self, columns: f64, lines: f64) {
+        if lines != 0.0 {
+            self.tui_app.engine.handle_single_input_command(
+                Cmd::VerticalScroll { distance: lines },
+                &mut EventLoopControlFlow::Poll,
+            );
+        }
+        if columns != 0.0
+            && self
+                .tui_app
+                .engine
+                .config
+                .editor
+                .gui
+                .touchpad
+                .horizontal_scroll
+        {
+            self.tui_app.engine.handle_single_input_command(
+                Cmd::HorizontalScroll { distance: columns },
+                &mut EventLoopControlFlow::Poll,
+            );
+        }
+    }
+
+    /// Switches between waiting for vsync and presenting right away when the config changes
+    fn update_present_mode(&mut self) {
+        let present_mode = if self.tui_app.engine.config.editor.gui.vsync {
+            wgpu::PresentMode::Fifo
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        };
+        if self.config.present_mode != present_mode {
+            self.config.present_mode = present_mode;
+            self.surface.configure(&self.device, &self.config);
+        }
+    }
+
     pub fn render_tui(&mut self) {
+        let mut ime_position = None;
+        let fps = self.frame_pacer.fps();
         self.terminals[0]
             .draw(|f| {
                 let area = f.area();
                 self.tui_app.render(f.buffer_mut(), area);
+                if self.tui_app.engine.config.editor.gui.debug_overlay {
+                    render_debug_overlay(
+                        f.buffer_mut(),
+                        area,
+                        fps,
+                        self.tui_app.engine.last_render_time,
+                        self.renderer.stats(),
+                    );
+                }
+                ime_position = find_primary_cursor(f.buffer_mut());
+                if let (Some(preedit), Some(position)) = (&self.preedit, ime_position) {
+                    let engine = &self.tui_app.engine;
+                    preedit.render(
+                        f.buffer_mut(),
+                        position,
+                        &engine.themes[&engine.config.editor.theme],
+                        engine.config.editor.gui.cursor_type,
+                    );
+                }
             })
             .unwrap();
+
+        if ime_position != self.ime_position {
+            self.ime_position = ime_position;
+            if let Some(position) = ime_position {
+                let backend = self.terminals[0].backend();
+                self.window.set_ime_cursor_area(
+                    PhysicalPosition::new(
+                        position.x as f32 * backend.cell_width,
+                        position.y as f32 * backend.cell_height,
+                    ),
+                    PhysicalSize::new(backend.cell_width, backend.cell_height),
+                );
+            }
+        }
     }
 
     pub fn render(&mut self) -> std::result::Result<(), wgpu::SurfaceError> {
@@ -650,14 +945,23 @@ impl GuiApp {
             .map(|t| {
                 t.backend_mut().prepare(
                     &self.tui_app.engine.themes[&self.tui_app.engine.config.editor.theme],
+                    &self.tui_app.engine.config.editor.gui,
                     &mut self.renderer.font_system,
                 )
             })
             .collect();
         let layers = vec![Layer { bundles }];
 
-        self.renderer
-            .prepare(&self.device, &self.queue, &self.config, layers);
+        self.renderer.prepare(
+            &self.device,
+            &self.queue,
+            &self.config,
+            &self.tui_app.engine.config.editor.gui,
+            layers,
+        );
+        for (layer_idx, stats) in self.renderer.stats().enumerate() {
+            tracing::trace!("layer {layer_idx}: {stats}");
+        }
 
         {
             let color = theme.background.bg.unwrap_or_default();
@@ -685,6 +989,8 @@ impl GuiApp {
 
         self.queue.submit(iter::once(encoder.finish()));
         output.present();
+        self.renderer.trim();
+        self.frame_pacer.frame_presented(Instant::now());
 
         self.tui_app.engine.last_render_time =
             Instant::now().duration_since(self.tui_app.engine.start_of_events);
@@ -693,6 +999,36 @@ impl GuiApp {
     }
 }
 
+/// Draws the frames per second, the time it took to show the last frame
+/// and what each layer of the renderer drew in the top right corner
+fn render_debug_overlay<'a>(
+    buf: &mut tui::buffer::Buffer,
+    area: tui::layout::Rect,
+    fps: usize,
+    last_render_time: Duration,
+    layer_stats: impl Iterator<Item = &'a LayerStats>,
+) {
+    let lines = iter::once(format!(
+        " {fps} fps {:.1} ms ",
+        last_render_time.as_secs_f64() * 1000.0
+    ))
+    .chain(
+        layer_stats
+            .enumerate()
+            .map(|(layer_idx, stats)| format!(" layer {layer_idx}: {stats} ")),
+    );
+    for (y, text) in (area.y..area.bottom()).zip(lines) {
+        let width = (text.len() as u16).min(area.width);
+        buf.set_stringn(
+            area.right().saturating_sub(width),
+            y,
+            text,
+            width.into(),
+            tui::style::Style::default().add_modifier(tui::style::Modifier::REVERSED),
+        );
+    }
+}
+
 impl Drop for GuiApp {
     fn drop(&mut self) {
         clipboard::uninit();
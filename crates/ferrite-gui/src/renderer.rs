@@ -1,8 +1,10 @@
-use std::ops::Range;
+use std::{collections::HashSet, fmt, ops::Range};
 
+use ferrite_core::config::editor::Gui;
 use geometry_renderer::{Geometry, GeometryRenderer};
 use glyphon::{
-    Cache, FontSystem, Resolution, SwashCache, TextArea, TextAtlas, TextRenderer, Viewport,
+    cosmic_text::CacheKey, Cache, FontSystem, PrepareError, Resolution, SwashCache, TextArea,
+    TextAtlas, TextRenderer, Viewport,
 };
 
 pub mod geometry_renderer;
@@ -22,6 +24,140 @@ pub struct LayerRenderer {
     text_renderer: TextRenderer,
     top_geometry_index_range: Range<u32>,
     bottom_geometry_index_range: Range<u32>,
+    /// Glyphs that have been handed to the atlas since it was created, only tracked for the stats.
+    /// glyphon does not expose what is in its atlas, glyphs it evicted to make room for others
+    /// are still counted so this is an upper bound.
+    cached_glyphs: HashSet<CacheKey>,
+    /// Glyphs drawn in the current frame, kept to reuse the allocation
+    frame_glyphs: HashSet<CacheKey>,
+    /// Only collected while the debug overlay is shown
+    stats: LayerStats,
+}
+
+impl LayerRenderer {
+    fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        cache: &Cache,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let (atlas, text_renderer) = create_text_renderer(device, queue, cache, format);
+        Self {
+            atlas,
+            text_renderer,
+            top_geometry_index_range: 0..0,
+            bottom_geometry_index_range: 0..0,
+            cached_glyphs: HashSet::new(),
+            frame_glyphs: HashSet::new(),
+            stats: LayerStats::default(),
+        }
+    }
+
+    /// Counts what is drawn from the text areas and which of the glyphs were already in the atlas
+    fn count_text(&mut self, text_areas: &[TextArea]) {
+        self.stats.text_areas = text_areas.len();
+        self.stats.glyphs = 0;
+        self.frame_glyphs.clear();
+        for text_area in text_areas {
+            for run in text_area.buffer.layout_runs() {
+                for glyph in run.glyphs {
+                    let physical = glyph.physical((text_area.left, text_area.top), text_area.scale);
+                    self.stats.glyphs += 1;
+                    self.frame_glyphs.insert(physical.cache_key);
+                }
+            }
+        }
+        for glyph in &self.frame_glyphs {
+            if self.cached_glyphs.insert(*glyph) {
+                self.stats.glyph_cache_misses += 1;
+            } else {
+                self.stats.glyph_cache_hits += 1;
+            }
+        }
+        self.stats.cached_glyphs = self.cached_glyphs.len();
+    }
+
+    /// Drops the stats and the glyphs tracked for them
+    fn clear_stats(&mut self) {
+        if self.cached_glyphs.capacity() != 0 || self.frame_glyphs.capacity() != 0 {
+            self.cached_glyphs = HashSet::new();
+            self.frame_glyphs = HashSet::new();
+            self.stats = LayerStats::default();
+        }
+    }
+
+    fn reset_atlas(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        cache: &Cache,
+        format: wgpu::TextureFormat,
+    ) {
+        tracing::debug!("Resetting full glyph atlas");
+        (self.atlas, self.text_renderer) = create_text_renderer(device, queue, cache, format);
+        self.cached_glyphs.clear();
+        self.stats.atlas_resets += 1;
+    }
+}
+
+fn create_text_renderer(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    cache: &Cache,
+    format: wgpu::TextureFormat,
+) -> (TextAtlas, TextRenderer) {
+    let mut atlas = TextAtlas::new(device, queue, cache, format);
+    let text_renderer = TextRenderer::new(
+        &mut atlas,
+        device,
+        wgpu::MultisampleState {
+            count: 1,
+            ..Default::default()
+        },
+        None,
+    );
+    (atlas, text_renderer)
+}
+
+/// What a layer drew in the last frame
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LayerStats {
+    pub text_areas: usize,
+    pub glyphs: usize,
+    pub quads: usize,
+    /// Glyphs handed to the atlas of the layer since it was created, including evicted ones.
+    /// Every subpixel offset of a glyph is rasterized and counted separately.
+    pub cached_glyphs: usize,
+    /// Distinct glyphs of each frame that had been handed to the atlas before
+    pub glyph_cache_hits: u64,
+    pub glyph_cache_misses: u64,
+    /// Times glyphon could not grow the atlas to fit a frame and it was replaced
+    pub atlas_resets: usize,
+}
+
+impl LayerStats {
+    pub fn glyph_cache_hit_rate(&self) -> f64 {
+        let lookups = self.glyph_cache_hits + self.glyph_cache_misses;
+        if lookups == 0 {
+            return 1.0;
+        }
+        self.glyph_cache_hits as f64 / lookups as f64
+    }
+}
+
+impl fmt::Display for LayerStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} areas {} glyphs {} quads, atlas {} glyphs {:.1}% hits {} resets",
+            self.text_areas,
+            self.glyphs,
+            self.quads,
+            self.cached_glyphs,
+            self.glyph_cache_hit_rate() * 100.0,
+            self.atlas_resets,
+        )
+    }
 }
 
 pub struct Renderer {
@@ -69,6 +205,7 @@ impl Renderer {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         config: &wgpu::SurfaceConfiguration,
+        gui: &Gui,
         layers: Vec<Layer>,
     ) {
         self.geometry_renderer.clear();
@@ -81,22 +218,12 @@ impl Renderer {
         );
 
         while self.layer_renderers.len() < layers.len() {
-            let mut atlas = TextAtlas::new(device, queue, &self.cache, config.format);
-            let text_renderer = TextRenderer::new(
-                &mut atlas,
+            self.layer_renderers.push(LayerRenderer::new(
                 device,
-                wgpu::MultisampleState {
-                    count: 1,
-                    ..Default::default()
-                },
-                None,
-            );
-            self.layer_renderers.push(LayerRenderer {
-                text_renderer,
-                atlas,
-                top_geometry_index_range: 0..0,
-                bottom_geometry_index_range: 0..0,
-            });
+                queue,
+                &self.cache,
+                config.format,
+            ));
         }
 
         for (renderer, layer) in self.layer_renderers.iter_mut().zip(layers) {
@@ -118,23 +245,48 @@ impl Renderer {
                 let end = self.geometry_renderer.num_indices();
                 renderer.top_geometry_index_range = start..end;
             }
+            renderer.stats.quads = (renderer.bottom_geometry_index_range.len()
+                + renderer.top_geometry_index_range.len())
+                / 6;
             let text_areas: Vec<_> = layer
                 .bundles
                 .into_iter()
                 .map(|bundle| bundle.text_area)
                 .collect();
-            renderer
-                .text_renderer
-                .prepare(
-                    device,
-                    queue,
-                    &mut self.font_system,
-                    &mut renderer.atlas,
-                    &self.viewport,
-                    text_areas,
-                    &mut self.swash_cache,
-                )
-                .unwrap();
+            if gui.debug_overlay {
+                renderer.count_text(&text_areas);
+            } else {
+                renderer.clear_stats();
+            }
+            let result = renderer.text_renderer.prepare(
+                device,
+                queue,
+                &mut self.font_system,
+                &mut renderer.atlas,
+                &self.viewport,
+                text_areas.iter().cloned(),
+                &mut self.swash_cache,
+            );
+            // glyphon could not grow the atlas to fit the glyphs of the frame,
+            // an empty atlas only has to hold the glyphs of this frame
+            let result = match result {
+                Err(PrepareError::AtlasFull) => {
+                    renderer.reset_atlas(device, queue, &self.cache, config.format);
+                    renderer.text_renderer.prepare(
+                        device,
+                        queue,
+                        &mut self.font_system,
+                        &mut renderer.atlas,
+                        &self.viewport,
+                        text_areas,
+                        &mut self.swash_cache,
+                    )
+                }
+                result => result,
+            };
+            if let Err(err) = result {
+                tracing::error!("Error preparing text: {err}");
+            }
         }
         self.geometry_renderer.prepare(device, queue);
     }
@@ -155,6 +307,19 @@ impl Renderer {
         }
     }
 
+    /// Lets the atlases evict glyphs that were not drawn in the frame that was just presented
+    /// instead of growing when new glyphs are drawn
+    pub fn trim(&mut self) {
+        for layer in &mut self.layer_renderers {
+            layer.atlas.trim();
+        }
+    }
+
+    /// What each layer drew in the last frame
+    pub fn stats(&self) -> impl Iterator<Item = &LayerStats> {
+        self.layer_renderers.iter().map(|layer| &layer.stats)
+    }
+
     pub fn resize(&mut self, width: f32, height: f32) {
         self.width = width;
         self.height = height;
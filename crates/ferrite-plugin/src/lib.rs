@@ -0,0 +1,213 @@
+//! The protocol spoken between ferrite and its plugins.
+//!
+//! A plugin is a long-lived process started by the editor. Both sides exchange
+//! JSON-RPC 2.0 messages over stdio, one message per line. The editor sends
+//! notifications to the plugin and answers the requests it receives, the plugin
+//! never has to answer the editor.
+
+use std::{
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// A JSON-RPC request or notification, notifications have no `id`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Message<T> {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub body: T,
+}
+
+impl<T> Message<T> {
+    pub fn notification(body: T) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id: None,
+            body,
+        }
+    }
+
+    pub fn request(id: u64, body: T) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id: Some(id),
+            body,
+        }
+    }
+}
+
+/// Answer to a request from a plugin
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: u64,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+impl Response {
+    pub fn new(id: u64, result: Result<serde_json::Value, String>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id,
+            outcome: match result {
+                Ok(value) => Outcome::Result(value),
+                Err(message) => Outcome::Error(RpcError {
+                    code: ERROR_CODE,
+                    message,
+                }),
+            },
+        }
+    }
+}
+
+/// JSON-RPC error code used for every failed request
+pub const ERROR_CODE: i64 = -32000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Result(serde_json::Value),
+    Error(RpcError),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+/// Notifications sent from the editor to a plugin
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Notification {
+    /// Always the first message a plugin receives
+    Initialize { protocol_version: u32 },
+    /// Only sent for the event kinds the plugin has subscribed to
+    BufferEvent {
+        event: BufferEventKind,
+        buffer: BufferInfo,
+    },
+    /// The user ran one of the commands registered by the plugin
+    RunCommand { name: String, args: Vec<String> },
+    /// The plugin is about to be stopped and should exit
+    Shutdown,
+}
+
+/// Requests sent from a plugin to the editor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Request {
+    /// Adds a command to the palette, it cannot shadow a builtin command
+    RegisterCommand { name: String, description: String },
+    /// Starts sending buffer events of these kinds
+    Subscribe { events: Vec<BufferEventKind> },
+    ShowMessage {
+        text: String,
+        #[serde(default)]
+        error: bool,
+    },
+    /// Returns the [`BufferInfo`] of the current buffer or null
+    CurrentBuffer,
+    /// Returns the text of a buffer
+    GetText { buffer: u64 },
+    /// Replaces the text of a buffer, keeping cursors where they are
+    SetText { buffer: u64, text: String },
+    /// Inserts text at every cursor of a buffer
+    InsertText { buffer: u64, text: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BufferEventKind {
+    Open,
+    Save,
+    Change,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BufferInfo {
+    /// Stays the same for as long as the buffer is open
+    pub id: u64,
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub language: String,
+    pub dirty: bool,
+}
+
+/// A message read by a plugin
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum EditorMessage {
+    Response(Response),
+    Notification(Message<Notification>),
+}
+
+/// Writes a message followed by a newline and flushes the writer
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// Reads the next message, returns `None` when the other side has closed the stream.
+/// Empty lines are skipped.
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            return Ok(Some(serde_json::from_str(&line)?));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wire_format() {
+        let request: Message<Request> = serde_json::from_str(
+            r#"{"jsonrpc":"2.0","id":3,"method":"get_text","params":{"buffer":7}}"#,
+        )
+        .unwrap();
+        assert_eq!(request, Message::request(3, Request::GetText { buffer: 7 }));
+        let request: Message<Request> =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","method":"current_buffer"}"#).unwrap();
+        assert_eq!(request, Message::notification(Request::CurrentBuffer));
+
+        let mut out = Vec::new();
+        write_message(&mut out, &Response::new(3, Err("no such buffer".into()))).unwrap();
+        write_message(&mut out, &Message::notification(Notification::Shutdown)).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&out),
+            "{\"jsonrpc\":\"2.0\",\"id\":3,\"error\":{\"code\":-32000,\"message\":\"no such buffer\"}}\n\
+            {\"jsonrpc\":\"2.0\",\"method\":\"shutdown\"}\n"
+        );
+
+        let mut reader = &out[..];
+        assert!(matches!(
+            read_message(&mut reader).unwrap(),
+            Some(EditorMessage::Response(_))
+        ));
+        assert!(matches!(
+            read_message(&mut reader).unwrap(),
+            Some(EditorMessage::Notification(_))
+        ));
+        assert!(read_message::<EditorMessage>(&mut reader)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,82 @@
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor,
+    event::{
+        self, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    style::Print,
+    terminal,
+};
+use ferrite_cli::TermBackend;
+
+/// Prepares the terminal for drawing the editor.
+/// Returns true if keyboard enhancement flags were pushed and have to be popped by `leave`.
+pub fn enter(backend: TermBackend, out: &mut impl Write) -> io::Result<bool> {
+    terminal::enable_raw_mode()?;
+    match backend {
+        TermBackend::Crossterm => {
+            execute!(
+                out,
+                event::EnableBracketedPaste,
+                terminal::EnterAlternateScreen,
+                // Saves the title so it can be restored by `leave`
+                Print("\x1b[22;0t"),
+                terminal::Clear(terminal::ClearType::Purge),
+                event::EnableMouseCapture,
+                event::EnableFocusChange,
+            )?;
+
+            if terminal::supports_keyboard_enhancement()? {
+                execute!(
+                    out,
+                    PushKeyboardEnhancementFlags(
+                        KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    )
+                )?;
+                return Ok(true);
+            }
+            Ok(false)
+        }
+        TermBackend::Plain => {
+            execute!(
+                out,
+                terminal::Clear(terminal::ClearType::All),
+                cursor::MoveTo(0, 0)
+            )?;
+            Ok(false)
+        }
+    }
+}
+
+/// Restores the terminal to the state it was in before `enter`.
+/// Every step is attempted even if an earlier one fails so a broken terminal is restored as far as possible,
+/// the first error is returned.
+pub fn leave(
+    backend: TermBackend,
+    keyboard_enhancement: bool,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut results = Vec::new();
+    if keyboard_enhancement {
+        results.push(execute!(out, PopKeyboardEnhancementFlags));
+    }
+    results.push(terminal::disable_raw_mode());
+    match backend {
+        TermBackend::Crossterm => {
+            results.push(execute!(out, event::DisableMouseCapture));
+            results.push(execute!(out, event::DisableFocusChange));
+            results.push(execute!(out, event::DisableBracketedPaste));
+            results.push(execute!(out, terminal::LeaveAlternateScreen));
+            results.push(execute!(out, Print("\x1b[23;0t")));
+        }
+        TermBackend::Plain => results.push(execute!(
+            out,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0)
+        )),
+    }
+    results.push(execute!(out, cursor::Show));
+    results.into_iter().collect()
+}
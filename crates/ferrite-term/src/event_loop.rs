@@ -10,6 +10,12 @@ pub enum TuiEvent {
     Render,
     AppEvent(UserEvent),
     Crossterm(crossterm::event::Event),
+    /// The process received SIGTERM or SIGHUP
+    Terminate,
+    /// The process received SIGTSTP
+    Suspend,
+    /// The process received SIGCONT after being stopped
+    Resume,
 }
 
 pub struct TuiEventLoop {
@@ -68,10 +74,48 @@ impl TuiEventLoop {
             }
         });
 
+        let (signal_tx, signal_rx) = mpsc::channel();
+        #[cfg(unix)]
+        {
+            use signal_hook::{consts::signal, iterator::Signals};
+
+            match Signals::new([
+                signal::SIGTERM,
+                signal::SIGHUP,
+                signal::SIGTSTP,
+                signal::SIGCONT,
+            ]) {
+                Ok(mut signals) => {
+                    let waker_tx = proxy.waker_tx.clone();
+                    thread::spawn(move || {
+                        for signal in signals.forever() {
+                            tracing::info!("Received signal {signal}");
+                            let event = match signal {
+                                signal::SIGTSTP => TuiEvent::Suspend,
+                                signal::SIGCONT => TuiEvent::Resume,
+                                _ => TuiEvent::Terminate,
+                            };
+                            let _ = signal_tx.send(event);
+                            let _ = waker_tx.send(());
+                        }
+                    });
+                }
+                Err(err) => tracing::error!("Error registering signal handlers: {err}"),
+            }
+        }
+        #[cfg(not(unix))]
+        drop(signal_tx);
+
         'main: loop {
             let mut control_flow = EventLoopControlFlow::Wait;
             handler(&proxy, TuiEvent::StartOfEvents, &mut control_flow);
 
+            while let Ok(event) = signal_rx.try_recv() {
+                handler(&proxy, event, &mut control_flow);
+                if control_flow == EventLoopControlFlow::Exit {
+                    break 'main;
+                }
+            }
             while let Ok(event) = crossterm_rx.try_recv() {
                 handler(&proxy, TuiEvent::Crossterm(event), &mut control_flow);
                 if control_flow == EventLoopControlFlow::Exit {
@@ -1,3 +1,5 @@
+use ferrite_core::{config::editor::ColorMode, theme::style};
+
 pub fn convert_keycode(
     keycode: crossterm::event::KeyCode,
 ) -> ferrite_core::keymap::keycode::KeyCode {
@@ -169,3 +171,59 @@ pub fn convert_modifier(
 
     output
 }
+
+fn quantize_color(color: tui::style::Color, colors: ColorMode) -> tui::style::Color {
+    use tui::style::Color;
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    match colors {
+        ColorMode::Ansi256 => Color::Indexed(style::rgb_to_ansi256([r, g, b])),
+        ColorMode::Ansi16 => match style::rgb_to_ansi16([r, g, b]) {
+            0 => Color::Black,
+            1 => Color::Red,
+            2 => Color::Green,
+            3 => Color::Yellow,
+            4 => Color::Blue,
+            5 => Color::Magenta,
+            6 => Color::Cyan,
+            7 => Color::Gray,
+            8 => Color::DarkGray,
+            9 => Color::LightRed,
+            10 => Color::LightGreen,
+            11 => Color::LightYellow,
+            12 => Color::LightBlue,
+            13 => Color::LightMagenta,
+            14 => Color::LightCyan,
+            _ => Color::White,
+        },
+        ColorMode::Auto | ColorMode::Truecolor => color,
+    }
+}
+
+/// Replaces `background` with the default background of the terminal
+/// and reduces the colors of the rendered buffer to what the terminal supports
+/// Removes the markers the gui uses to find cursors so the terminal does not blink them
+pub fn strip_cursor_markers(buf: &mut tui::buffer::Buffer) {
+    for cell in &mut buf.content {
+        cell.modifier
+            .remove(tui::style::Modifier::SLOW_BLINK | tui::style::Modifier::RAPID_BLINK);
+    }
+}
+
+pub fn adapt_colors(
+    buf: &mut tui::buffer::Buffer,
+    colors: ColorMode,
+    background: Option<tui::style::Color>,
+) {
+    if colors == ColorMode::Truecolor && background.is_none() {
+        return;
+    }
+    for cell in &mut buf.content {
+        if Some(cell.bg) == background {
+            cell.bg = tui::style::Color::Reset;
+        }
+        cell.fg = quantize_color(cell.fg, colors);
+        cell.bg = quantize_color(cell.bg, colors);
+    }
+}
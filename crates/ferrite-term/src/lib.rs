@@ -1,38 +1,67 @@
 use std::{
-    io::{self, IsTerminal, Read, Stdout},
-    sync::mpsc,
+    fs::OpenOptions,
+    io::{self, IsTerminal, Read, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
     time::Instant,
 };
 
 use anyhow::{bail, Result};
 use crossterm::{
-    event::{
-        self, Event, KeyEventKind, KeyboardEnhancementFlags, MouseButton, MouseEventKind,
-        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
-    },
+    event::{self, Event, KeyEventKind, MouseButton, MouseEventKind},
     execute, terminal,
 };
 use event_loop::{TuiEvent, TuiEventLoop, TuiEventLoopProxy};
-use ferrite_cli::Args;
+use ferrite_cli::{Args, TermBackend};
 use ferrite_core::{
-    buffer::Buffer, clipboard, cmd::Cmd, config::editor::CursorType,
-    event_loop_proxy::EventLoopControlFlow, keymap, layout::panes::PaneKind, logger::LogMessage,
+    buffer::{self, Buffer},
+    clipboard,
+    cmd::Cmd,
+    config::editor::{ColorMode, CursorType, Osc52Clipboard},
+    event_loop_proxy::EventLoopControlFlow,
+    keymap,
+    layout::panes::PaneKind,
+    logger::LogMessage,
+    recovery,
+    workspace::{self, BufferId},
 };
 use ferrite_tui::{
-    glue::{ferrite_to_tui_rect, tui_to_ferrite_rect},
+    glue::{convert_style, ferrite_to_tui_rect, tui_to_ferrite_rect},
     widgets::editor_widget::lines_to_left_offset,
     TuiApp,
 };
 use ferrite_utility::point::Point;
-use glue::{convert_keycode, convert_modifier};
-use tui::{layout::Position, Terminal};
+use glue::{adapt_colors, convert_keycode, convert_modifier, strip_cursor_markers};
+use tui::{
+    layout::{Position, Rect},
+    Terminal,
+};
 
+mod backend;
 mod event_loop;
 mod glue;
 
+/// Where the editor is drawn
+type Output = Box<dyn Write>;
+
+/// Opens the terminal directly when stdout is used for the output of a pipeline
+fn open_output(pipe: bool) -> io::Result<Output> {
+    if !pipe {
+        return Ok(Box::new(io::stdout()));
+    }
+    #[cfg(unix)]
+    let path = "/dev/tty";
+    #[cfg(not(unix))]
+    let path = "CONOUT$";
+    Ok(Box::new(OpenOptions::new().write(true).open(path)?))
+}
+
 pub fn run(args: &Args, recv: mpsc::Receiver<LogMessage>) -> Result<()> {
     let event_loop = TuiEventLoop::new();
-    let backend = tui::backend::CrosstermBackend::new(std::io::stdout());
+    let pipe = args.pipe && !io::stdin().is_terminal() && !io::stdout().is_terminal();
+    let backend = tui::backend::CrosstermBackend::new(open_output(pipe)?);
     let terminal = Terminal::new(backend)?;
     let size = terminal.size()?;
     let mut tui_app = TuiApp::new(
@@ -42,6 +71,7 @@ pub fn run(args: &Args, recv: mpsc::Receiver<LogMessage>) -> Result<()> {
         size.width,
         size.height,
     )?;
+    let mut piped_buffer = None;
     if !io::stdin().is_terminal() {
         let mut stdin = io::stdin().lock();
         let mut bytes = Vec::new();
@@ -49,69 +79,115 @@ pub fn run(args: &Args, recv: mpsc::Receiver<LogMessage>) -> Result<()> {
         let mut buffer = Buffer::from_bytes(&bytes)?;
         let view_id = buffer.create_view();
         buffer.goto(view_id, args.line as i64);
-        tui_app.engine.insert_buffer(buffer, view_id, true);
+        buffer.read_only = args.read_only;
+        let (buffer_id, _) = tui_app.engine.insert_buffer(buffer, view_id, true);
+        // The text is written back to stdout in a pipeline so it is left as is
+        if !pipe && tui_app.engine.config.editor.ansi_render {
+            tui_app.engine.render_ansi(buffer_id);
+        }
+        piped_buffer = Some(buffer_id);
     }
 
-    if !io::stdout().is_terminal() {
+    if !pipe && !io::stdout().is_terminal() {
         bail!("stdout must is not a tty");
     }
 
+    let backend = args
+        .term_backend
+        .unwrap_or(tui_app.engine.config.editor.term_backend);
+    tracing::info!("Using terminal backend: {backend:?}");
+    tui_app.engine.can_suspend = cfg!(unix);
     let term_app = TermApp {
         tui_app,
         terminal,
-        keyboard_enhancement: false,
+        backend,
+        keyboard_enhancement: Arc::new(AtomicBool::new(false)),
+        detected_colors: ColorMode::detect(),
+        remote_session: clipboard::is_remote_session(),
+        title: String::new(),
+        pipe,
+        piped_buffer: piped_buffer.filter(|_| pipe),
     };
-    term_app.run(event_loop);
+    if let Some(output) = term_app.run(event_loop) {
+        io::stdout().lock().write_all(&output)?;
+    }
     Ok(())
 }
 
 pub struct TermApp {
     tui_app: TuiApp,
-    terminal: tui::Terminal<tui::backend::CrosstermBackend<Stdout>>,
-    keyboard_enhancement: bool,
+    terminal: tui::Terminal<tui::backend::CrosstermBackend<Output>>,
+    backend: TermBackend,
+    /// Shared with the panic hook so it knows if the keyboard enhancement flags have to be popped
+    keyboard_enhancement: Arc<AtomicBool>,
+    detected_colors: ColorMode,
+    remote_session: bool,
+    /// Last title set on the terminal
+    title: String,
+    /// The editor is drawn on the terminal instead of stdout
+    pipe: bool,
+    /// Buffer read from stdin that is written to stdout on exit
+    piped_buffer: Option<BufferId>,
 }
 
 impl TermApp {
-    pub fn run(mut self, event_loop: TuiEventLoop) {
+    /// Returns the encoded text of the buffer read from stdin if it should be written to stdout.
+    /// The terminal is restored before returning so the text does not end up on the screen.
+    pub fn run(mut self, event_loop: TuiEventLoop) -> Option<Vec<u8>> {
         tracing::info!("Starting tui app");
-        let mut stdout = std::io::stdout();
-        terminal::enable_raw_mode().unwrap();
-        execute!(
-            stdout,
-            event::EnableBracketedPaste,
-            terminal::EnterAlternateScreen,
-            terminal::Clear(terminal::ClearType::Purge),
-            event::EnableMouseCapture,
-        )
-        .unwrap();
 
-        if terminal::supports_keyboard_enhancement().unwrap() {
-            execute!(
-                stdout,
-                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
-            )
-            .unwrap();
-        }
-
-        // Reset terminal to non raw mode on panic
+        // Restore the terminal and save unsaved buffers on panic.
+        // The hook is set before entering so a failure half way through `enter` is also cleaned up.
         {
+            let backend = self.backend;
+            let pipe = self.pipe;
+            let keyboard_enhancement = self.keyboard_enhancement.clone();
             std::panic::set_hook(Box::new(move |info| {
-                let _ = execute!(
-                    io::stdout(),
-                    event::DisableMouseCapture,
-                    event::DisableBracketedPaste,
-                    terminal::LeaveAlternateScreen,
-                );
-                _ = terminal::disable_raw_mode();
-                println!();
+                if let Ok(mut out) = open_output(pipe) {
+                    let _ = backend::leave(
+                        backend,
+                        keyboard_enhancement.load(Ordering::Relaxed),
+                        &mut out,
+                    );
+                }
+                // Stdout can be piped into another program, the report goes to stderr
+                eprintln!();
+                if let Err(err) = recovery::emergency_dump() {
+                    eprintln!("Error saving unsaved buffers: {err}");
+                }
+                if let Err(err) = workspace::emergency_save_workspace() {
+                    eprintln!("Error saving workspace: {err}");
+                }
                 let backtrace = std::backtrace::Backtrace::force_capture();
                 let panic_info = format!("{backtrace}\n{info}");
                 let _ = std::fs::write("panic.txt", &panic_info);
-                println!("{}", panic_info);
+                eprintln!("{}", panic_info);
             }));
         }
 
+        let keyboard_enhancement =
+            backend::enter(self.backend, self.terminal.backend_mut()).unwrap();
+        self.keyboard_enhancement
+            .store(keyboard_enhancement, Ordering::Relaxed);
+
         event_loop.run(|proxy, event, control_flow| self.handle_event(proxy, event, control_flow));
+
+        let buffer = self
+            .tui_app
+            .engine
+            .workspace
+            .buffers
+            .get(self.piped_buffer?)?;
+        let mut output = Vec::new();
+        buffer::write::write_to(
+            buffer.encoding,
+            buffer.bom,
+            buffer.line_ending,
+            buffer.rope().clone(),
+            &mut output,
+        )
+        .ok()?;
+        Some(output)
     }
 
     pub fn handle_event(
@@ -125,22 +201,58 @@ impl TermApp {
                 self.tui_app.start_of_events();
             }
             event_loop::TuiEvent::Crossterm(event) => {
+                // Moving the mouse only changes what is shown once it focuses a pane
+                if !matches!(&event, Event::Mouse(mouse) if mouse.kind == MouseEventKind::Moved) {
+                    self.tui_app.damage.damage_frame();
+                }
                 self.handle_crossterm_event(proxy, event, control_flow)
             }
+            event_loop::TuiEvent::Terminate => self.tui_app.engine.terminate(control_flow),
+            event_loop::TuiEvent::Suspend => self.suspend(),
+            event_loop::TuiEvent::Resume => {
+                // The process was stopped by something other than `suspend`, the shell may have
+                // reset the terminal modes while it was stopped
+                let _ = terminal::enable_raw_mode();
+                self.redraw();
+            }
             event_loop::TuiEvent::AppEvent(event) => {
                 self.tui_app.engine.handle_app_event(event, control_flow)
             }
             event_loop::TuiEvent::Render => {
+                if std::mem::take(&mut self.tui_app.engine.suspend_requested) {
+                    self.suspend();
+                }
                 self.tui_app.engine.do_polling(control_flow);
                 self.tui_app.engine.config.editor.gui.cursor_type = CursorType::Block;
+                self.tui_app.engine.config.editor.gui.secondary_cursor_type = None;
+                self.update_clipboard_and_title();
                 if self.tui_app.engine.force_redraw {
                     self.tui_app.engine.force_redraw = false;
-                    let _ = self.terminal.clear();
+                    self.redraw();
                 }
+                let size = self.terminal.size().unwrap_or_default();
+                if !self
+                    .tui_app
+                    .needs_render(Rect::new(0, 0, size.width, size.height))
+                {
+                    return;
+                }
+                let editor = &self.tui_app.engine.config.editor;
+                let colors = match editor.terminal.colors {
+                    ColorMode::Auto => self.detected_colors,
+                    colors => colors,
+                };
+                let background = if editor.terminal.transparent_background {
+                    convert_style(&self.tui_app.engine.themes[&editor.theme].background).bg
+                } else {
+                    None
+                };
                 self.terminal
                     .draw(|f| {
                         let area = f.area();
                         self.tui_app.render(f.buffer_mut(), area);
+                        strip_cursor_markers(f.buffer_mut());
+                        adapt_colors(f.buffer_mut(), colors, background);
                     })
                     .unwrap();
                 self.tui_app.engine.last_render_time =
@@ -149,6 +261,60 @@ impl TermApp {
         }
     }
 
+    /// Clears the terminal so the next frame is drawn from scratch
+    fn redraw(&mut self) {
+        self.tui_app.damage.damage_frame();
+        let _ = self.terminal.clear();
+    }
+
+    /// Gives the terminal back to the shell and stops the process like the default handler of
+    /// SIGTSTP, once the process is continued with `fg` the terminal is set up and drawn again
+    fn suspend(&mut self) {
+        #[cfg(unix)]
+        {
+            let _ = backend::leave(
+                self.backend,
+                self.keyboard_enhancement.load(Ordering::Relaxed),
+                self.terminal.backend_mut(),
+            );
+            // Returns once the process has been continued
+            if let Err(err) =
+                signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)
+            {
+                tracing::error!("Error suspending: {err}");
+            }
+            match backend::enter(self.backend, self.terminal.backend_mut()) {
+                Ok(keyboard_enhancement) => self
+                    .keyboard_enhancement
+                    .store(keyboard_enhancement, Ordering::Relaxed),
+                Err(err) => tracing::error!("Error restoring terminal: {err}"),
+            }
+            // The title is set again by the next frame
+            self.title.clear();
+            self.redraw();
+        }
+    }
+
+    fn update_clipboard_and_title(&mut self) {
+        let editor = &self.tui_app.engine.config.editor;
+        clipboard::set_osc52(match editor.terminal.osc52_clipboard {
+            Osc52Clipboard::Auto => {
+                !editor.local_clipboard
+                    && (self.remote_session || !clipboard::has_system_clipboard())
+            }
+            Osc52Clipboard::Always => true,
+            Osc52Clipboard::Never => false,
+        });
+
+        if editor.terminal.set_title && self.backend == TermBackend::Crossterm {
+            let title = self.tui_app.engine.title();
+            if title != self.title {
+                let _ = execute!(self.terminal.backend_mut(), terminal::SetTitle(&title));
+                self.title = title;
+            }
+        }
+    }
+
     pub fn handle_crossterm_event(
         &mut self,
         _proxy: &TuiEventLoopProxy,
@@ -171,9 +337,20 @@ impl TermApp {
                         }
                     }
                     Event::Mouse(event) => match event.kind {
-                        // TODO allow scoll when using cmd palette
+                        MouseEventKind::ScrollUp if self.tui_app.scroll_palette(event.row, -3) => {
+                            None
+                        }
+                        MouseEventKind::ScrollDown if self.tui_app.scroll_palette(event.row, 3) => {
+                            None
+                        }
                         MouseEventKind::ScrollUp => Some(Cmd::VerticalScroll { distance: -3.0 }),
                         MouseEventKind::ScrollDown => Some(Cmd::VerticalScroll { distance: 3.0 }),
+                        MouseEventKind::ScrollLeft => {
+                            Some(Cmd::HorizontalScroll { distance: -3.0 })
+                        }
+                        MouseEventKind::ScrollRight => {
+                            Some(Cmd::HorizontalScroll { distance: 3.0 })
+                        }
                         MouseEventKind::Down(MouseButton::Middle) => {
                             for (pane_kind, pane_rect) in self
                                 .tui_app
@@ -187,6 +364,7 @@ impl TermApp {
                                 {
                                     self.tui_app.engine.workspace.panes.make_current(pane_kind);
                                     if let PaneKind::Buffer(buffer_id, view_id) = pane_kind {
+                                        let header = self.tui_app.buffer_header_height(buffer_id);
                                         let buffer =
                                             &self.tui_app.engine.workspace.buffers[buffer_id];
                                         let (_, left_offset) =
@@ -196,7 +374,7 @@ impl TermApp {
                                         .saturating_sub(pane_rect.x)
                                         .saturating_sub(left_offset);
                                         let line = (event.row as usize + buffer.line_pos(view_id))
-                                            .saturating_sub(pane_rect.y);
+                                            .saturating_sub(pane_rect.y + header);
                                         break 'block Some(Cmd::PastePrimary { column, line });
                                     }
                                 }
@@ -205,6 +383,30 @@ impl TermApp {
                             None
                         }
                         MouseEventKind::Down(MouseButton::Left) => {
+                            if let Some(job) = self.tui_app.cancel_item_at(event.column, event.row)
+                            {
+                                self.tui_app.engine.job_manager.cancel(job);
+                                break 'block None;
+                            }
+                            if let Some(item) =
+                                self.tui_app.info_line_item_at(event.column, event.row)
+                            {
+                                let item = item.to_string();
+                                if let Some(pane) = self.tui_app.pane_at(event.column, event.row) {
+                                    self.tui_app.engine.workspace.panes.make_current(pane);
+                                }
+                                break 'block Some(Cmd::InfoLineClick { item });
+                            }
+                            if let Some(symbol_line) =
+                                self.tui_app.breadcrumb_at(event.column, event.row)
+                            {
+                                if let Some(pane) = self.tui_app.pane_at(event.column, event.row) {
+                                    self.tui_app.engine.workspace.panes.make_current(pane);
+                                }
+                                break 'block Some(Cmd::Goto {
+                                    line: symbol_line as i64 + 1,
+                                });
+                            }
                             for (pane_kind, pane_rect) in self
                                 .tui_app
                                 .engine
@@ -217,6 +419,7 @@ impl TermApp {
                                 {
                                     self.tui_app.engine.workspace.panes.make_current(pane_kind);
                                     if let PaneKind::Buffer(buffer_id, view_id) = pane_kind {
+                                        let header = self.tui_app.buffer_header_height(buffer_id);
                                         let buffer =
                                             &self.tui_app.engine.workspace.buffers[buffer_id];
                                         self.tui_app.drag_start = Some(Point::new(
@@ -231,7 +434,7 @@ impl TermApp {
                                         .saturating_sub(pane_rect.x)
                                         .saturating_sub(left_offset);
                                         let line = (event.row as usize + buffer.line_pos(view_id))
-                                            .saturating_sub(pane_rect.y);
+                                            .saturating_sub(pane_rect.y + header);
                                         break 'block Some(Cmd::ClickCell {
                                             spawn_cursor: false,
                                             column,
@@ -247,6 +450,11 @@ impl TermApp {
                             self.tui_app.drag_start = None;
                             None
                         }
+                        MouseEventKind::Moved => {
+                            let pane = self.tui_app.pane_at(event.column, event.row);
+                            self.tui_app.engine.hover_pane(pane);
+                            None
+                        }
                         MouseEventKind::Drag(MouseButton::Left) => {
                             for (pane_kind, pane_rect) in self
                                 .tui_app
@@ -260,6 +468,7 @@ impl TermApp {
                                 {
                                     self.tui_app.engine.workspace.panes.make_current(pane_kind);
                                     if let PaneKind::Buffer(buffer_id, view_id) = pane_kind {
+                                        let header = self.tui_app.buffer_header_height(buffer_id);
                                         // TODO maybe scroll more of the buffer into view when going outside its bounds
                                         if let Some(Point { line, column }) =
                                             self.tui_app.drag_start
@@ -273,7 +482,8 @@ impl TermApp {
                                                 let column = column
                                                     .saturating_sub(left_offset)
                                                     .saturating_sub(pane_rect.x);
-                                                let line = line.saturating_sub(pane_rect.y);
+                                                let line =
+                                                    line.saturating_sub(pane_rect.y + header);
                                                 Point::new(column, line)
                                             };
 
@@ -284,7 +494,7 @@ impl TermApp {
                                                 .saturating_sub(pane_rect.x);
                                                 let line = (event.row as usize
                                                     + buffer.line_pos(view_id))
-                                                .saturating_sub(pane_rect.y);
+                                                .saturating_sub(pane_rect.y + header);
                                                 Point::new(column, line)
                                             };
 
@@ -299,6 +509,14 @@ impl TermApp {
                         _ => None,
                     },
                     Event::Paste(text) => Some(Cmd::Insert { text }),
+                    Event::FocusGained => {
+                        self.tui_app.engine.focus_changed(true);
+                        None
+                    }
+                    Event::FocusLost => {
+                        self.tui_app.engine.focus_changed(false);
+                        None
+                    }
                     _ => None,
                 }
             };
@@ -315,15 +533,10 @@ impl TermApp {
 
 impl Drop for TermApp {
     fn drop(&mut self) {
-        if self.keyboard_enhancement {
-            let _ = execute!(io::stdout(), PopKeyboardEnhancementFlags);
-        }
-        let _ = terminal::disable_raw_mode();
-        let _ = execute!(
+        let _ = backend::leave(
+            self.backend,
+            self.keyboard_enhancement.load(Ordering::Relaxed),
             self.terminal.backend_mut(),
-            event::DisableMouseCapture,
-            event::DisableBracketedPaste,
-            terminal::LeaveAlternateScreen,
         );
         let _ = self.terminal.show_cursor();
         clipboard::uninit();
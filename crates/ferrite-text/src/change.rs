@@ -0,0 +1,21 @@
+use std::ops::Range;
+
+/// A single edit applied to the text of a buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferChange {
+    /// Revision of the buffer after the change was applied
+    pub revision: u64,
+    /// Byte range in the text before the change that was replaced
+    pub range: Range<usize>,
+    /// Length in bytes of the text that was replaced
+    pub old_len: usize,
+    /// Length in bytes of the text that replaced it
+    pub new_len: usize,
+}
+
+impl BufferChange {
+    /// Byte range of the new text after the change was applied
+    pub fn new_range(&self) -> Range<usize> {
+        self.range.start..(self.range.start + self.new_len)
+    }
+}
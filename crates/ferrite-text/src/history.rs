@@ -4,7 +4,7 @@ use ferrite_utility::{graphemes::RopeGraphemeExt, vec1::Vec1};
 use ropey::Rope;
 use slotmap::SecondaryMap;
 
-use super::{Cursor, ViewId};
+use crate::{Cursor, ViewId};
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 enum EditClass {
@@ -46,6 +46,15 @@ enum EditKind {
 }
 
 impl EditKind {
+    /// The replaced byte range and the length of the new text
+    fn change(&self) -> (Range<usize>, usize) {
+        match self {
+            Self::Insert { byte_idx, text } => (*byte_idx..*byte_idx, text.len()),
+            Self::Replace { range, text } => (range.clone(), text.len()),
+            Self::Remove { range } => (range.clone(), 0),
+        }
+    }
+
     fn get_class(&self) -> EditClass {
         match self {
             EditKind::Insert { text, .. } => EditClass::from(text.as_str()),
@@ -97,6 +106,9 @@ struct Frame {
 pub struct History {
     stack: Vec<Frame>,
     current_frame: i64,
+    changes: Vec<(Range<usize>, usize)>,
+    /// Set while grouping edits, contains the frame all grouped edits are added to once it has begun
+    group: Option<Option<i64>>,
 }
 
 impl Default for History {
@@ -104,6 +116,8 @@ impl Default for History {
         Self {
             stack: Vec::new(),
             current_frame: -1,
+            changes: Vec::new(),
+            group: None,
         }
     }
 }
@@ -113,6 +127,7 @@ impl History {
         match self.stack.last_mut() {
             Some(frame) => {
                 frame.edit_class = edit.get_class();
+                self.changes.push(edit.change());
                 let inverse = edit.apply(rope);
                 frame.edits.push(inverse);
             }
@@ -141,7 +156,24 @@ impl History {
         self.edit(rope, replace);
     }
 
+    /// Adds all edits until `end_group` to a single frame so they are undone together
+    pub fn begin_group(&mut self) {
+        self.group = Some(None);
+    }
+
+    pub fn end_group(&mut self) {
+        if let Some(Some(_)) = self.group.take() {
+            self.finish();
+        }
+    }
+
     pub fn begin(&mut self, cursors: SecondaryMap<ViewId, Vec1<Cursor>>, dirty: bool) {
+        match self.group {
+            Some(Some(frame)) if frame == self.current_frame => return,
+            Some(_) => self.group = Some(Some(self.current_frame + 1)),
+            None => (),
+        }
+
         self.stack.truncate((self.current_frame + 1) as usize);
 
         self.stack.push(Frame {
@@ -157,6 +189,9 @@ impl History {
     }
 
     pub fn finish(&mut self) {
+        if matches!(self.group, Some(Some(_))) {
+            return;
+        }
         if let Some(frame) = self.stack.get_mut(self.current_frame as usize) {
             if !frame.finished {
                 frame.finished = true;
@@ -178,6 +213,7 @@ impl History {
 
         while let Some(frame) = &mut self.stack.get_mut(self.current_frame as usize) {
             for edit in frame.edits.iter_mut().rev() {
+                self.changes.push(edit.change());
                 *edit = edit.apply(rope);
             }
             mem::swap(&mut frame.cursors, cursors);
@@ -219,6 +255,7 @@ impl History {
             let frame = &mut self.stack[self.current_frame as usize];
 
             for edit in &mut frame.edits {
+                self.changes.push(edit.change());
                 *edit = edit.apply(rope);
             }
             mem::swap(&mut frame.cursors, cursors);
@@ -250,9 +287,48 @@ impl History {
         self.stack[self.current_frame as usize].dirty = false;
     }
 
+    /// Takes the byte ranges replaced since the last call and the length of the text replacing them
+    pub fn take_changes(&mut self) -> Vec<(Range<usize>, usize)> {
+        mem::take(&mut self.changes)
+    }
+
     pub fn mark_all_dirty(&mut self) {
         for frame in &mut self.stack {
             frame.dirty = true;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use slotmap::SlotMap;
+
+    use super::*;
+
+    #[test]
+    fn undo_and_redo_restore_cursors() {
+        let mut views: SlotMap<ViewId, ()> = SlotMap::with_key();
+        let view_id = views.insert(());
+        let mut cursors = SecondaryMap::new();
+        cursors.insert(view_id, Vec1::new(Cursor::default()));
+
+        let mut rope = Rope::from_str("hello");
+        let mut history = History::default();
+        history.begin(cursors.clone(), false);
+        history.insert(&mut rope, 5, " world");
+        history.finish();
+        assert_eq!(history.take_changes(), vec![(5..5, 6)]);
+
+        let mut dirty = true;
+        cursors[view_id].first_mut().position = 11;
+        history.undo(&mut rope, &mut cursors, &mut dirty);
+        assert_eq!(rope.to_string(), "hello");
+        assert_eq!(cursors[view_id].first().position, 0);
+        assert!(!dirty);
+
+        history.redo(&mut rope, &mut cursors, &mut dirty);
+        assert_eq!(rope.to_string(), "hello world");
+        assert_eq!(cursors[view_id].first().position, 11);
+        assert!(dirty);
+    }
+}
@@ -0,0 +1,20 @@
+//! Text primitives of ferrite: cursors, changes, undo history and search over ropes.
+//! Nothing in this crate depends on the editor, its ui or its event loop
+//! so it can be used to edit text from other tools.
+//!
+//! This is not the whole editing core, the `Buffer` of `ferrite-core` with its edit and motion
+//! commands is built on top of it and stays there as it is tied to syntax highlighting and the config.
+//! A `Buffer` does not need an event loop either, its render proxy is set with `Buffer::set_proxy`
+//! and does nothing until then.
+
+pub mod change;
+pub mod cursor;
+pub mod history;
+pub mod search;
+
+pub use cursor::Cursor;
+
+slotmap::new_key_type! {
+    /// A view of a text, every view has its own cursors
+    pub struct ViewId;
+}
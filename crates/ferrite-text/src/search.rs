@@ -0,0 +1,158 @@
+use ferrite_utility::{
+    graphemes::{is_word_char, RopeGraphemeExt as _},
+    point::Point,
+};
+use ropey::RopeSlice;
+
+/// Amount of chars searched between steps reporting the progress of `find_matches`
+pub const PROGRESS_CHARS: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SearchMatch {
+    pub start: Point<usize>,
+    pub end: Point<usize>,
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
+
+/// How a query is matched against the text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub case_insensitive: bool,
+    /// A query without uppercase letters is matched case insensitively, otherwise case sensitively.
+    /// Takes precedence over `case_insensitive`.
+    pub smart_case: bool,
+    /// Only match the query when it is not surrounded by word characters
+    pub whole_word: bool,
+}
+
+impl SearchOptions {
+    pub fn ignore_case(&self, query: &str) -> bool {
+        if self.smart_case {
+            !query.chars().any(char::is_uppercase)
+        } else {
+            self.case_insensitive
+        }
+    }
+}
+
+/// A step of `find_matches`
+pub enum SearchStep {
+    Match(SearchMatch),
+    /// Amount of chars that have been searched so far
+    Progress(usize),
+}
+
+/// Calls `step` for every match of `query` in `rope` and every `PROGRESS_CHARS` chars.
+/// The search stops when `step` returns false. Returns false if the search was stopped.
+pub fn find_matches(
+    rope: RopeSlice,
+    query: &str,
+    options: SearchOptions,
+    mut step: impl FnMut(SearchStep) -> bool,
+) -> bool {
+    let chars: Vec<_> = query.chars().collect();
+    if chars.is_empty() {
+        return true;
+    }
+    let case_insensitive = options.ignore_case(query);
+    let mut query_idx = 0;
+
+    for (current_char, ch) in (1..).zip(rope.chars()) {
+        if compare_char(&ch, &chars[query_idx], case_insensitive) {
+            query_idx += 1;
+        } else {
+            query_idx = 0;
+            if compare_char(&ch, &chars[query_idx], case_insensitive) {
+                query_idx += 1;
+            }
+        }
+
+        if query_idx >= chars.len() {
+            let start_char = current_char - chars.len();
+            if !options.whole_word || is_whole_word(rope, start_char, current_char) {
+                let start_byte = rope.char_to_byte(start_char);
+                let end_byte = rope.char_to_byte(current_char);
+                let m = SearchMatch {
+                    start: rope.byte_to_point(start_byte),
+                    end: rope.byte_to_point(end_byte),
+                    start_byte,
+                    end_byte,
+                };
+                if !step(SearchStep::Match(m)) {
+                    return false;
+                }
+            }
+            query_idx = 0;
+        }
+        if current_char % PROGRESS_CHARS == 0 && !step(SearchStep::Progress(current_char)) {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn search_rope(
+    rope: RopeSlice,
+    query: String,
+    options: SearchOptions,
+    stop_at_first: bool,
+) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    find_matches(rope, &query, options, |step| match step {
+        SearchStep::Match(m) => {
+            matches.push(m);
+            !stop_at_first
+        }
+        SearchStep::Progress(_) => true,
+    });
+    matches
+}
+
+/// True if the chars in `start..end` are not directly preceded or followed by a word character
+fn is_whole_word(rope: RopeSlice, start: usize, end: usize) -> bool {
+    let before = start.checked_sub(1).and_then(|idx| rope.get_char(idx));
+    !before.is_some_and(is_word_char) && !rope.get_char(end).is_some_and(is_word_char)
+}
+
+#[inline(always)]
+pub fn compare_char(lhs: &char, rhs: &char, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        lhs.eq_ignore_ascii_case(rhs)
+    } else {
+        lhs == rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use super::*;
+
+    fn find(text: &str, query: &str, options: SearchOptions) -> Vec<usize> {
+        search_rope(Rope::from_str(text).slice(..), query.into(), options, false)
+            .into_iter()
+            .map(|m| m.start_byte)
+            .collect()
+    }
+
+    #[test]
+    fn smart_case_and_whole_word() {
+        let text = "foo Foo foobar foo_x (foo)";
+        let smart_case = SearchOptions {
+            smart_case: true,
+            ..Default::default()
+        };
+        assert_eq!(find(text, "foo", smart_case), vec![0, 4, 8, 15, 22]);
+        assert_eq!(find(text, "Foo", smart_case), vec![4]);
+
+        let whole_word = SearchOptions {
+            case_insensitive: true,
+            whole_word: true,
+            ..Default::default()
+        };
+        assert_eq!(find(text, "foo", whole_word), vec![0, 4, 22]);
+        assert!(find(text, "", whole_word).is_empty());
+    }
+}
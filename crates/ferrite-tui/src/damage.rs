@@ -0,0 +1,193 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use ferrite_core::{buffer::ViewId, notifications::Notification, workspace::BufferId};
+use tui::{
+    buffer::{Buffer, Cell},
+    layout::Rect,
+};
+
+use crate::widgets::{breadcrumbs::BreadcrumbItem, info_line::InfoLineItem};
+
+/// Hash of everything shown in a frame from the engine `generation`, the size of the frame,
+/// the toast and the keys of the panes. `None` if one of the panes has no key.
+pub fn frame_key(
+    generation: u64,
+    size: Rect,
+    toast: Option<&Notification>,
+    pane_keys: impl IntoIterator<Item = Option<u64>>,
+) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    generation.hash(&mut hasher);
+    size.hash(&mut hasher);
+    toast
+        .map(|toast| (toast.severity, &toast.message, toast.time))
+        .hash(&mut hasher);
+    for key in pane_keys {
+        key?.hash(&mut hasher);
+    }
+    Some(hasher.finish())
+}
+
+/// A buffer pane as it was rendered in an earlier frame
+struct CachedPane {
+    /// Hash of everything the pane was rendered from
+    key: u64,
+    area: Rect,
+    cells: Vec<Cell>,
+    info_line_items: Vec<InfoLineItem>,
+    breadcrumb_items: Vec<BreadcrumbItem>,
+}
+
+/// Keeps the cells of rendered buffer panes so panes that have not changed since the last frame
+/// are copied instead of rendered again, and tracks if anything changed since the last frame.
+#[derive(Default)]
+pub struct Damage {
+    panes: HashMap<(BufferId, ViewId), CachedPane>,
+    /// Key of the last drawn frame, `None` if the next frame has to be drawn
+    frame: Option<u64>,
+}
+
+impl Damage {
+    /// Forces the next frame to be drawn, used for events that can change what is shown
+    /// without changing the engine such as resizes and scrolling the palette
+    pub fn damage_frame(&mut self) {
+        self.frame = None;
+    }
+
+    /// Returns true if the frame has changed since it was last drawn.
+    /// Frames without a key show something that changes by itself and are always drawn.
+    pub fn frame_changed(&mut self, key: Option<u64>) -> bool {
+        let changed = key.is_none() || key != self.frame;
+        self.frame = key;
+        changed
+    }
+
+    /// Copies the cells of the pane into `buf` if it was last rendered into `area` from `key`.
+    /// Returns the info line items and breadcrumbs that were rendered with it.
+    pub fn restore(
+        &self,
+        pane: (BufferId, ViewId),
+        key: u64,
+        area: Rect,
+        buf: &mut Buffer,
+    ) -> Option<(&[InfoLineItem], &[BreadcrumbItem])> {
+        let area = area.intersection(buf.area);
+        let cached = self
+            .panes
+            .get(&pane)
+            .filter(|cached| cached.key == key && cached.area == area)?;
+        for (position, cell) in area.positions().zip(&cached.cells) {
+            if let Some(target) = buf.cell_mut(position) {
+                *target = cell.clone();
+            }
+        }
+        Some((&cached.info_line_items, &cached.breadcrumb_items))
+    }
+
+    /// Keeps the cells of the pane rendered into `area` of `buf`
+    pub fn store(
+        &mut self,
+        pane: (BufferId, ViewId),
+        key: u64,
+        area: Rect,
+        buf: &Buffer,
+        info_line_items: &[InfoLineItem],
+        breadcrumb_items: &[BreadcrumbItem],
+    ) {
+        let area = area.intersection(buf.area);
+        let cells = area
+            .positions()
+            .map(|position| buf.cell(position).cloned().unwrap_or_default())
+            .collect();
+        self.panes.insert(
+            pane,
+            CachedPane {
+                key,
+                area,
+                cells,
+                info_line_items: info_line_items.to_vec(),
+                breadcrumb_items: breadcrumb_items.to_vec(),
+            },
+        );
+    }
+
+    /// Drops the cached panes that are no longer shown
+    pub fn retain(&mut self, shown: &[(BufferId, ViewId)]) {
+        self.panes.retain(|pane, _| shown.contains(pane));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use ferrite_core::{notifications::Severity, workspace::Workspace};
+
+    use super::*;
+
+    fn panes() -> [(BufferId, ViewId); 2] {
+        let mut workspace = Workspace::default();
+        [(); 2].map(|_| {
+            let mut buffer = ferrite_core::buffer::Buffer::new();
+            let view_id = buffer.create_view();
+            (workspace.buffers.insert(buffer), view_id)
+        })
+    }
+
+    #[test]
+    fn restore_stored_pane() {
+        let [first, second] = panes();
+        let area = Rect::new(0, 0, 4, 2);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "ab", tui::style::Style::default());
+        let mut damage = Damage::default();
+        damage.store(first, 7, area, &buf, &[], &[]);
+
+        let mut target = Buffer::empty(area);
+        assert!(damage.restore(first, 7, area, &mut target).is_some());
+        assert_eq!(target, buf);
+        // A different key, area or pane is rendered again
+        assert!(damage.restore(first, 8, area, &mut target).is_none());
+        assert!(damage
+            .restore(first, 7, Rect::new(0, 0, 4, 1), &mut target)
+            .is_none());
+        assert!(damage.restore(second, 7, area, &mut target).is_none());
+
+        damage.retain(&[second]);
+        assert!(damage.restore(first, 7, area, &mut target).is_none());
+    }
+
+    #[test]
+    fn frame_changes() {
+        let mut damage = Damage::default();
+        assert!(damage.frame_changed(Some(1)));
+        assert!(!damage.frame_changed(Some(1)));
+        assert!(damage.frame_changed(Some(2)));
+        assert!(damage.frame_changed(None));
+        assert!(damage.frame_changed(None));
+        assert!(damage.frame_changed(Some(2)));
+        damage.damage_frame();
+        assert!(damage.frame_changed(Some(2)));
+    }
+
+    #[test]
+    fn frame_key_invalidation() {
+        let size = Rect::new(0, 0, 80, 24);
+        let toast = |message: &str| Notification {
+            severity: Severity::Info,
+            message: message.into(),
+            time: SystemTime::UNIX_EPOCH,
+        };
+        let key = frame_key(1, size, Some(&toast("saved")), [Some(1)]);
+        assert!(key.is_some());
+        assert_eq!(key, frame_key(1, size, Some(&toast("saved")), [Some(1)]));
+        assert_ne!(key, frame_key(1, size, Some(&toast("failed")), [Some(1)]));
+        assert_ne!(key, frame_key(1, size, None, [Some(1)]));
+        assert_ne!(key, frame_key(2, size, Some(&toast("saved")), [Some(1)]));
+        assert_ne!(key, frame_key(1, size, Some(&toast("saved")), [Some(2)]));
+        assert_eq!(frame_key(1, size, None, [Some(1), None]), None);
+    }
+}
@@ -1,5 +1,23 @@
 
 pub fn convert_style(style: &ferrite_core::theme::style::Style) -> tui::style::Style {
+    use ferrite_core::theme::style::Modifiers;
+    let mut modifier = tui::style::Modifier::empty();
+    modifier.set(
+        tui::style::Modifier::BOLD,
+        style.modifiers.contains(Modifiers::BOLD),
+    );
+    modifier.set(
+        tui::style::Modifier::ITALIC,
+        style.modifiers.contains(Modifiers::ITALIC),
+    );
+    modifier.set(
+        tui::style::Modifier::UNDERLINED,
+        style.modifiers.contains(Modifiers::UNDERLINED),
+    );
+    modifier.set(
+        tui::style::Modifier::CROSSED_OUT,
+        style.modifiers.contains(Modifiers::CROSSED_OUT),
+    );
     tui::style::Style {
         fg: style.fg.as_ref().map(|color| {
             tui::style::Color::Rgb(
@@ -15,6 +33,58 @@ pub fn convert_style(style: &ferrite_core::theme::style::Style) -> tui::style::S
                 (color.b * 255.0) as u8,
             )
         }),
+        add_modifier: modifier,
+        ..Default::default()
+    }
+}
+
+/// Style of a cursor cell. Block cursors reverse the cell, every cursor is tagged with
+/// `SLOW_BLINK` if it is the primary cursor or `RAPID_BLINK` otherwise so the gui can draw,
+/// blink and animate it.
+pub fn cursor_style(
+    theme: &ferrite_core::theme::EditorTheme,
+    cursor_type: ferrite_core::config::editor::CursorType,
+    primary: bool,
+) -> tui::style::Style {
+    use ferrite_core::config::editor::CursorType;
+    let marker = if primary {
+        tui::style::Modifier::SLOW_BLINK
+    } else {
+        tui::style::Modifier::RAPID_BLINK
+    };
+    let style = match cursor_type {
+        CursorType::Block => convert_style(if primary {
+            &theme.cursor
+        } else {
+            &theme.secondary_cursor
+        })
+        .add_modifier(tui::style::Modifier::REVERSED),
+        CursorType::Line | CursorType::Underline => tui::style::Style::default(),
+    };
+    style.add_modifier(marker)
+}
+
+/// Style of text styled by ansi escape sequences, colors are resolved with the theme
+pub fn convert_ansi_style(
+    theme: &ferrite_core::theme::EditorTheme,
+    style: &ferrite_core::ansi::AnsiStyle,
+) -> tui::style::Style {
+    let color = |color| {
+        let color = theme.ansi_color(color);
+        tui::style::Color::Rgb(
+            (color.r * 255.0) as u8,
+            (color.g * 255.0) as u8,
+            (color.b * 255.0) as u8,
+        )
+    };
+    let mut modifier = tui::style::Modifier::empty();
+    modifier.set(tui::style::Modifier::BOLD, style.bold);
+    modifier.set(tui::style::Modifier::ITALIC, style.italic);
+    modifier.set(tui::style::Modifier::UNDERLINED, style.underline);
+    tui::style::Style {
+        fg: style.fg.map(color),
+        bg: style.bg.map(color),
+        add_modifier: modifier,
         ..Default::default()
     }
 }
@@ -36,3 +106,13 @@ pub fn ferrite_to_tui_rect(rect: ferrite_core::layout::panes::Rect) -> tui::layo
         height: rect.height.try_into().unwrap(),
     }
 }
+
+pub fn convert_border(border: ferrite_core::config::editor::BorderStyle) -> tui::widgets::BorderType {
+    use ferrite_core::config::editor::BorderStyle;
+    match border {
+        BorderStyle::Plain => tui::widgets::BorderType::Plain,
+        BorderStyle::Rounded => tui::widgets::BorderType::Rounded,
+        BorderStyle::Double => tui::widgets::BorderType::Double,
+        BorderStyle::Thick => tui::widgets::BorderType::Thick,
+    }
+}
@@ -1,29 +1,59 @@
-use std::{sync::mpsc, time::Instant};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::mpsc,
+    time::Instant,
+};
 
 use anyhow::Result;
+use damage::Damage;
 use ferrite_cli::Args;
 use ferrite_core::{
     buffer::ViewId,
     engine::Engine,
     event_loop_proxy::EventLoopProxy,
     file_explorer::FileExplorerId,
-    layout::panes::PaneKind,
+    identifier_stats::VariantStats,
+    job_manager::JobId,
+    layout::{overlay, panes::PaneKind},
     logger::{self, LogMessage},
-    picker::{buffer_picker::BufferItem, global_search_picker::GlobalSearchMatch},
+    picker::{
+        bookmark_picker::BookmarkItem,
+        buffer_picker::BufferItem,
+        git_picker::{GitItem, GitPicker},
+        global_search_picker::GlobalSearchMatch,
+        job_picker::JobItem,
+        local_history_picker::SnapshotItem,
+        outline_picker::OutlineItem,
+        plugin_picker::PluginItem,
+        register_picker::RegisterItem,
+    },
+    tr,
     workspace::BufferId,
 };
 use ferrite_utility::point::Point;
 use glue::{convert_style, ferrite_to_tui_rect, tui_to_ferrite_rect};
 use tui::{
-    layout::{Margin, Rect},
+    layout::{Position, Rect},
     widgets::{StatefulWidget, Widget},
 };
 use widgets::{
-    background_widget::BackgroundWidget, chord_widget::ChordWidget, editor_widget::EditorWidget,
-    file_explorer_widget::FileExplorerWidget, logger_widget::LoggerWidget,
-    palette_widget::CmdPaletteWidget, picker_widget::PickerWidget, splash::SplashWidget,
+    background_widget::BackgroundWidget,
+    breadcrumbs::{breadcrumbs_height, BreadcrumbItem},
+    chord_widget::ChordWidget,
+    editor_widget::EditorWidget,
+    file_explorer_widget::FileExplorerWidget,
+    git_status_widget::GitStatusWidget,
+    info_line::InfoLineItem,
+    location_list_widget::LocationListWidget,
+    logger_widget::LoggerWidget,
+    notifications_widget::{CancelItem, NotificationsWidget, ToastWidget},
+    palette_widget::CmdPaletteWidget,
+    picker_widget::PickerWidget,
+    splash::SplashWidget,
 };
 
+pub mod damage;
 #[rustfmt::skip]
 pub mod glue;
 pub mod rect_ext;
@@ -34,6 +64,14 @@ pub struct TuiApp {
     pub drag_start: Option<Point<usize>>,
     pub engine: Engine,
     pub keyboard_enhancement: bool,
+    /// Info line items rendered in the last frame, used to handle clicks on them
+    pub info_line_items: Vec<InfoLineItem>,
+    /// Breadcrumbs rendered in the last frame, used to handle clicks on them
+    pub breadcrumb_items: Vec<BreadcrumbItem>,
+    /// Cancel buttons of running jobs rendered in the last frame, used to handle clicks on them
+    pub cancel_items: Vec<CancelItem>,
+    /// Buffer panes from earlier frames and if anything changed since the last frame
+    pub damage: Damage,
 }
 
 #[profiling::all_functions]
@@ -59,6 +97,10 @@ impl TuiApp {
             drag_start: None,
             engine,
             keyboard_enhancement: false,
+            info_line_items: Vec::new(),
+            breadcrumb_items: Vec::new(),
+            cancel_items: Vec::new(),
+            damage: Damage::default(),
         })
     }
 
@@ -69,6 +111,58 @@ impl TuiApp {
         profiling::finish_frame!();
     }
 
+    /// Returns the pane that contains the cell at `column` and `line`
+    pub fn pane_at(&self, column: u16, line: u16) -> Option<PaneKind> {
+        self.engine
+            .workspace
+            .panes
+            .get_pane_bounds(tui_to_ferrite_rect(self.buffer_area))
+            .into_iter()
+            .find(|(_, rect)| ferrite_to_tui_rect(*rect).contains(Position::new(column, line)))
+            .map(|(pane_kind, _)| pane_kind)
+    }
+
+    /// Returns the name of the info line item at `column` and `line`
+    pub fn info_line_item_at(&self, column: u16, line: u16) -> Option<&str> {
+        self.info_line_items
+            .iter()
+            .find(|item| item.area.contains(Position::new(column, line)))
+            .map(|item| item.name.as_str())
+    }
+
+    /// Returns the line of the symbol in the breadcrumbs at `column` and `line`
+    pub fn breadcrumb_at(&self, column: u16, line: u16) -> Option<usize> {
+        self.breadcrumb_items
+            .iter()
+            .find(|item| item.area.contains(Position::new(column, line)))
+            .map(|item| item.line)
+    }
+
+    /// Returns the job of the cancel button at `column` and `line`
+    pub fn cancel_item_at(&self, column: u16, line: u16) -> Option<JobId> {
+        self.cancel_items
+            .iter()
+            .find(|item| item.area.contains(Position::new(column, line)))
+            .map(|item| item.job)
+    }
+
+    /// Amount of rows used by the palette, messages are wrapped to the width of the palette
+    pub fn palette_height(&self, width: u16) -> u16 {
+        self.engine.palette.height(width.saturating_sub(1).into()) as u16
+    }
+
+    /// Scrolls the message shown in the palette if `line` is in the palette,
+    /// returns true if it was scrolled
+    pub fn scroll_palette(&mut self, line: u16, distance: isize) -> bool {
+        line >= self.buffer_area.bottom() && self.engine.palette.scroll_message(distance)
+    }
+
+    /// Amount of rows above the text of a buffer pane
+    pub fn buffer_header_height(&self, buffer_id: BufferId) -> usize {
+        let has_outline = self.engine.workspace.buffers[buffer_id].has_outline();
+        breadcrumbs_height(&self.engine.config.editor, has_outline).into()
+    }
+
     pub fn draw_pane_borders(&mut self, buf: &mut tui::buffer::Buffer, size: Rect) {
         let theme = &self.engine.themes[&self.engine.config.editor.theme];
         for x in size.x..(size.x + size.width) {
@@ -89,19 +183,60 @@ impl TuiApp {
     ) {
         profiling::scope!("render tui editor");
         let current_pane = self.engine.workspace.panes.get_current_pane();
+        let has_focus = !self.engine.palette.has_focus()
+            && self.engine.file_picker.is_none()
+            && self.engine.buffer_picker.is_none()
+            && self.engine.project_picker.is_none()
+            && self.engine.scratch_picker.is_none()
+            && self.engine.register_picker.is_none()
+            && self.engine.local_history_picker.is_none()
+            && self.engine.plugin_picker.is_none()
+            && self.engine.identifier_stats_picker.is_none()
+            && self.engine.git_picker.is_none()
+            && self.engine.bookmark_picker.is_none()
+            && self.engine.outline_picker.is_none()
+            && self.engine.custom_picker.is_none()
+            && self.engine.job_picker.is_none()
+            && current_pane == PaneKind::Buffer(buffer_id, view_id);
+        let key = self.buffer_pane_key(buffer_id, view_id, area, has_focus);
+        if let Some(key) = key {
+            if let Some((info_line_items, breadcrumb_items)) =
+                self.damage.restore((buffer_id, view_id), key, area, buf)
+            {
+                self.info_line_items.extend_from_slice(info_line_items);
+                self.breadcrumb_items.extend_from_slice(breadcrumb_items);
+                return;
+            }
+        }
+        let info_line_start = self.info_line_items.len();
+        let breadcrumb_start = self.breadcrumb_items.len();
+
         let theme = &self.engine.themes[&self.engine.config.editor.theme];
-        EditorWidget::new(
+        let mut editor_widget = EditorWidget::new(
             theme,
             &self.engine.config.editor,
             view_id,
-            !self.engine.palette.has_focus()
-                && self.engine.file_picker.is_none()
-                && self.engine.buffer_picker.is_none()
-                && current_pane == PaneKind::Buffer(buffer_id, view_id),
+            has_focus,
             self.engine.branch_watcher.current_branch(),
-            self.engine.spinner.current(),
-        )
-        .render(area, buf, &mut self.engine.workspace.buffers[buffer_id]);
+            self.engine.progress_indicator(),
+        );
+        editor_widget.completion = self.engine.completion.as_ref().filter(|completion| {
+            completion.buffer_id == buffer_id && completion.view_id == view_id
+        });
+        editor_widget.ansi_spans = self
+            .engine
+            .outputs
+            .get(buffer_id)
+            .map(|output| output.spans.as_slice())
+            .or_else(|| {
+                self.engine
+                    .ansi_renders
+                    .get(buffer_id)
+                    .map(|render| render.spans.as_slice())
+            });
+        editor_widget.info_line_items = Some(&mut self.info_line_items);
+        editor_widget.breadcrumb_items = Some(&mut self.breadcrumb_items);
+        editor_widget.render(area, buf, &mut self.engine.workspace.buffers[buffer_id]);
 
         if self.engine.config.editor.show_splash && self.engine.workspace.panes.num_panes() == 1 {
             let buffer = &mut self.engine.workspace.buffers[buffer_id];
@@ -113,6 +248,97 @@ impl TuiApp {
                 SplashWidget::new(theme).render(area, buf);
             }
         }
+
+        if let Some(key) = key {
+            self.damage.store(
+                (buffer_id, view_id),
+                key,
+                area,
+                buf,
+                &self.info_line_items[info_line_start..],
+                &self.breadcrumb_items[breadcrumb_start..],
+            );
+        }
+    }
+
+    /// Hash of everything a buffer pane is rendered from. Panes showing something that changes
+    /// without the buffer or engine changing, such as search matches or completions, have no key.
+    fn buffer_pane_key(
+        &self,
+        buffer_id: BufferId,
+        view_id: ViewId,
+        area: Rect,
+        has_focus: bool,
+    ) -> Option<u64> {
+        let buffer = &self.engine.workspace.buffers[buffer_id];
+        let has_completion = self.engine.completion.as_ref().is_some_and(|completion| {
+            completion.buffer_id == buffer_id && completion.view_id == view_id
+        });
+        if has_completion || buffer.get_searcher(view_id).is_some() {
+            return None;
+        }
+        let view = &buffer.views[view_id];
+        let mut hasher = DefaultHasher::new();
+        self.engine.generation.hash(&mut hasher);
+        buffer.generation().hash(&mut hasher);
+        buffer.is_dirty().hash(&mut hasher);
+        view.line_pos.to_bits().hash(&mut hasher);
+        view.col_pos.to_bits().hash(&mut hasher);
+        view.cursors
+            .iter()
+            .for_each(|cursor| cursor.hash(&mut hasher));
+        area.hash(&mut hasher);
+        has_focus.hash(&mut hasher);
+        self.engine
+            .branch_watcher
+            .current_branch()
+            .hash(&mut hasher);
+        self.engine.progress_indicator().hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    /// Hash of everything shown in a frame, `None` if it shows something that changes by itself
+    /// such as a picker or a pane other than a buffer pane
+    fn frame_key(&self, size: Rect) -> Option<u64> {
+        if self.engine.has_open_picker() {
+            return None;
+        }
+        let editor_size = Rect {
+            height: size.height.saturating_sub(self.palette_height(size.width)),
+            ..size
+        };
+        let current_pane = self.engine.workspace.panes.get_current_pane();
+        let pane_keys = self
+            .engine
+            .workspace
+            .panes
+            .get_pane_bounds(tui_to_ferrite_rect(editor_size))
+            .into_iter()
+            .map(|(pane, pane_rect)| {
+                let PaneKind::Buffer(buffer_id, view_id) = pane else {
+                    return None;
+                };
+                let has_focus = !self.engine.palette.has_focus() && current_pane == pane;
+                self.buffer_pane_key(
+                    buffer_id,
+                    view_id,
+                    ferrite_to_tui_rect(pane_rect),
+                    has_focus,
+                )
+            });
+        damage::frame_key(
+            self.engine.generation,
+            size,
+            self.engine.notifications.toast(),
+            pane_keys,
+        )
+    }
+
+    /// Returns false if nothing shown has changed since the last frame so drawing it can be skipped
+    pub fn needs_render(&mut self, size: Rect) -> bool {
+        profiling::scope!("check damage");
+        let key = self.frame_key(size);
+        self.damage.frame_changed(key)
     }
 
     pub fn draw_file_explorer(
@@ -126,6 +352,17 @@ impl TuiApp {
         let has_focus = !self.engine.palette.has_focus()
             && self.engine.file_picker.is_none()
             && self.engine.buffer_picker.is_none()
+            && self.engine.project_picker.is_none()
+            && self.engine.scratch_picker.is_none()
+            && self.engine.register_picker.is_none()
+            && self.engine.local_history_picker.is_none()
+            && self.engine.plugin_picker.is_none()
+            && self.engine.identifier_stats_picker.is_none()
+            && self.engine.git_picker.is_none()
+            && self.engine.bookmark_picker.is_none()
+            && self.engine.outline_picker.is_none()
+            && self.engine.custom_picker.is_none()
+            && self.engine.job_picker.is_none()
             && current_pane == PaneKind::FileExplorer(file_explorer_id);
         FileExplorerWidget::new(
             &self.engine.themes[&self.engine.config.editor.theme],
@@ -145,6 +382,17 @@ impl TuiApp {
         let has_focus = !self.engine.palette.has_focus()
             && self.engine.file_picker.is_none()
             && self.engine.buffer_picker.is_none()
+            && self.engine.project_picker.is_none()
+            && self.engine.scratch_picker.is_none()
+            && self.engine.register_picker.is_none()
+            && self.engine.local_history_picker.is_none()
+            && self.engine.plugin_picker.is_none()
+            && self.engine.identifier_stats_picker.is_none()
+            && self.engine.git_picker.is_none()
+            && self.engine.bookmark_picker.is_none()
+            && self.engine.outline_picker.is_none()
+            && self.engine.custom_picker.is_none()
+            && self.engine.job_picker.is_none()
             && current_pane == PaneKind::Logger;
         LoggerWidget::new(
             &self.engine.themes[&self.engine.config.editor.theme],
@@ -154,56 +402,260 @@ impl TuiApp {
         .render(area, buf, &mut self.engine.logger_state);
     }
 
+    pub fn draw_location_list(&mut self, buf: &mut tui::buffer::Buffer, area: Rect) {
+        profiling::scope!("render tui location list");
+        let current_pane = self.engine.workspace.panes.get_current_pane();
+        let has_focus = !self.engine.palette.has_focus()
+            && self.engine.file_picker.is_none()
+            && self.engine.buffer_picker.is_none()
+            && self.engine.project_picker.is_none()
+            && self.engine.scratch_picker.is_none()
+            && self.engine.register_picker.is_none()
+            && self.engine.local_history_picker.is_none()
+            && self.engine.plugin_picker.is_none()
+            && self.engine.identifier_stats_picker.is_none()
+            && self.engine.git_picker.is_none()
+            && self.engine.bookmark_picker.is_none()
+            && self.engine.outline_picker.is_none()
+            && self.engine.custom_picker.is_none()
+            && self.engine.job_picker.is_none()
+            && current_pane == PaneKind::LocationList;
+        LocationListWidget::new(
+            &self.engine.themes[&self.engine.config.editor.theme],
+            &self.engine.config.editor,
+            has_focus,
+        )
+        .render(area, buf, &mut self.engine.location_list);
+    }
+
+    pub fn draw_notifications(&mut self, buf: &mut tui::buffer::Buffer, area: Rect) {
+        profiling::scope!("render tui notifications");
+        let current_pane = self.engine.workspace.panes.get_current_pane();
+        let has_focus = !self.engine.palette.has_focus()
+            && self.engine.file_picker.is_none()
+            && self.engine.buffer_picker.is_none()
+            && self.engine.project_picker.is_none()
+            && self.engine.scratch_picker.is_none()
+            && self.engine.register_picker.is_none()
+            && self.engine.local_history_picker.is_none()
+            && self.engine.plugin_picker.is_none()
+            && self.engine.identifier_stats_picker.is_none()
+            && self.engine.git_picker.is_none()
+            && self.engine.bookmark_picker.is_none()
+            && self.engine.outline_picker.is_none()
+            && self.engine.custom_picker.is_none()
+            && self.engine.job_picker.is_none()
+            && current_pane == PaneKind::Notifications;
+        let mut notifications_widget = NotificationsWidget::new(
+            &self.engine.themes[&self.engine.config.editor.theme],
+            has_focus,
+            self.engine.job_manager.jobs(),
+        );
+        notifications_widget.cancel_items = Some(&mut self.cancel_items);
+        notifications_widget.render(area, buf, &mut self.engine.notifications);
+    }
+
+    pub fn draw_git_status(&mut self, buf: &mut tui::buffer::Buffer, area: Rect) {
+        profiling::scope!("render tui git status");
+        let current_pane = self.engine.workspace.panes.get_current_pane();
+        let has_focus = !self.engine.palette.has_focus()
+            && self.engine.file_picker.is_none()
+            && self.engine.buffer_picker.is_none()
+            && self.engine.project_picker.is_none()
+            && self.engine.scratch_picker.is_none()
+            && self.engine.register_picker.is_none()
+            && self.engine.local_history_picker.is_none()
+            && self.engine.plugin_picker.is_none()
+            && self.engine.identifier_stats_picker.is_none()
+            && self.engine.git_picker.is_none()
+            && self.engine.bookmark_picker.is_none()
+            && self.engine.outline_picker.is_none()
+            && self.engine.custom_picker.is_none()
+            && self.engine.job_picker.is_none()
+            && current_pane == PaneKind::Git;
+        GitStatusWidget::new(
+            &self.engine.themes[&self.engine.config.editor.theme],
+            self.engine.branch_watcher.current_branch(),
+            has_focus,
+        )
+        .render(area, buf, &mut self.engine.git_status);
+    }
+
     pub fn draw_overlays(&mut self, buf: &mut tui::buffer::Buffer, size: Rect) {
+        let picker_area = ferrite_to_tui_rect(overlay::overlay_area(
+            &self.engine.config.editor.overlay.picker,
+            tui_to_ferrite_rect(size),
+            None,
+        ));
         if let Some(file_picker) = &mut self.engine.file_picker {
             profiling::scope!("render tui file picker");
-            let size = size.inner(Margin {
-                horizontal: 5,
-                vertical: 2,
-            });
             PickerWidget::new(
                 &self.engine.themes[&self.engine.config.editor.theme],
                 &self.engine.config.editor,
-                "Open file",
+                &tr!("picker.open_file"),
             )
-            .render(size, buf, file_picker);
+            .render(picker_area, buf, file_picker);
         }
 
         if let Some(buffer_picker) = &mut self.engine.buffer_picker {
             profiling::scope!("render tui buffer picker");
-            let size = size.inner(Margin {
-                horizontal: 5,
-                vertical: 2,
-            });
             PickerWidget::<BufferItem>::new(
                 &self.engine.themes[&self.engine.config.editor.theme],
                 &self.engine.config.editor,
-                "Open buffer",
+                &tr!("picker.open_buffer"),
+            )
+            .render(picker_area, buf, buffer_picker);
+        }
+
+        if let Some(project_picker) = &mut self.engine.project_picker {
+            profiling::scope!("render tui project picker");
+            PickerWidget::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &tr!("picker.open_project"),
             )
-            .render(size, buf, buffer_picker);
+            .render(picker_area, buf, project_picker);
+        }
+
+        if let Some(scratch_picker) = &mut self.engine.scratch_picker {
+            profiling::scope!("render tui scratch picker");
+            PickerWidget::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &tr!("picker.open_scratch"),
+            )
+            .render(picker_area, buf, scratch_picker);
+        }
+
+        if let Some(register_picker) = &mut self.engine.register_picker {
+            profiling::scope!("render tui register picker");
+            PickerWidget::<RegisterItem>::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &tr!("picker.registers"),
+            )
+            .render(picker_area, buf, register_picker);
+        }
+
+        if let Some(local_history_picker) = &mut self.engine.local_history_picker {
+            profiling::scope!("render tui local history picker");
+            PickerWidget::<SnapshotItem>::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &tr!("picker.local_history"),
+            )
+            .render(picker_area, buf, local_history_picker);
+        }
+
+        if let Some(plugin_picker) = &mut self.engine.plugin_picker {
+            profiling::scope!("render tui plugin picker");
+            PickerWidget::<PluginItem>::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &tr!("picker.plugins"),
+            )
+            .render(picker_area, buf, plugin_picker);
+        }
+
+        if let Some(identifier_stats_picker) = &mut self.engine.identifier_stats_picker {
+            profiling::scope!("render tui identifier stats picker");
+            PickerWidget::<VariantStats>::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &tr!("picker.identifier_stats"),
+            )
+            .render(picker_area, buf, identifier_stats_picker);
+        }
+
+        if let Some((git_picker, picker)) = &mut self.engine.git_picker {
+            profiling::scope!("render tui git picker");
+            let title = match git_picker {
+                GitPicker::Branches { .. } => tr!("picker.git_branches"),
+                GitPicker::Log { .. } => tr!("picker.git_log"),
+            };
+            PickerWidget::<GitItem>::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &title,
+            )
+            .render(picker_area, buf, picker);
+        }
+
+        if let Some(bookmark_picker) = &mut self.engine.bookmark_picker {
+            profiling::scope!("render tui bookmark picker");
+            PickerWidget::<BookmarkItem>::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &tr!("picker.bookmarks"),
+            )
+            .set_text_align(widgets::picker_widget::TextAlign::Left)
+            .render(picker_area, buf, bookmark_picker);
+        }
+
+        if let Some(outline_picker) = &mut self.engine.outline_picker {
+            profiling::scope!("render tui outline picker");
+            PickerWidget::<OutlineItem>::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &tr!("picker.outline"),
+            )
+            .set_text_align(widgets::picker_widget::TextAlign::Left)
+            .render(picker_area, buf, outline_picker);
+        }
+
+        if let Some((name, _, custom_picker)) = &mut self.engine.custom_picker {
+            profiling::scope!("render tui custom picker");
+            PickerWidget::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                name,
+            )
+            .render(picker_area, buf, custom_picker);
+        }
+
+        if let Some(job_picker) = &mut self.engine.job_picker {
+            profiling::scope!("render tui job picker");
+            PickerWidget::<JobItem>::new(
+                &self.engine.themes[&self.engine.config.editor.theme],
+                &self.engine.config.editor,
+                &tr!("picker.jobs"),
+            )
+            .render(picker_area, buf, job_picker);
         }
 
         if let Some(global_search_picker) = &mut self.engine.global_search_picker {
             profiling::scope!("render tui search picker");
-            let size = size.inner(Margin {
-                horizontal: 5,
-                vertical: 2,
-            });
             PickerWidget::<GlobalSearchMatch>::new(
                 &self.engine.themes[&self.engine.config.editor.theme],
                 &self.engine.config.editor,
-                "Matches",
+                &tr!("picker.matches"),
             )
             .set_text_align(widgets::picker_widget::TextAlign::Left)
-            .render(size, buf, global_search_picker);
+            .render(picker_area, buf, global_search_picker);
         }
 
+        // The history already shows the notification while the pane is open
+        if !self
+            .engine
+            .workspace
+            .panes
+            .contains(PaneKind::Notifications)
+        {
+            if let Some(notification) = self.engine.notifications.toast() {
+                ToastWidget::new(
+                    &self.engine.themes[&self.engine.config.editor.theme],
+                    notification,
+                )
+                .render(size, buf);
+            }
+        }
+
+        let palette_height = self.palette_height(size.width);
         let palette_size = Rect::new(
             size.left(),
-            size.bottom()
-                .saturating_sub(self.engine.palette.height() as u16),
+            size.bottom().saturating_sub(palette_height),
             size.width,
-            (self.engine.palette.height() as u16).min(size.height),
+            palette_height.min(size.height),
         );
         CmdPaletteWidget::new(
             &self.engine.themes[&self.engine.config.editor.theme],
@@ -229,12 +681,15 @@ impl TuiApp {
             size.x,
             size.y,
             size.width,
-            size.height
-                .saturating_sub(self.engine.palette.height() as u16),
+            size.height.saturating_sub(self.palette_height(size.width)),
         );
         self.draw_pane_borders(buf, editor_size);
 
         self.buffer_area = editor_size;
+        self.info_line_items.clear();
+        self.breadcrumb_items.clear();
+        self.cancel_items.clear();
+        let mut shown = Vec::new();
         for (pane, pane_rect) in self
             .engine
             .workspace
@@ -244,6 +699,7 @@ impl TuiApp {
             match pane {
                 PaneKind::Buffer(buffer_id, view_id) => {
                     self.draw_buffer(buf, ferrite_to_tui_rect(pane_rect), buffer_id, view_id);
+                    shown.push((buffer_id, view_id));
                 }
                 PaneKind::FileExplorer(file_explorer_id) => {
                     self.draw_file_explorer(buf, ferrite_to_tui_rect(pane_rect), file_explorer_id);
@@ -251,9 +707,27 @@ impl TuiApp {
                 PaneKind::Logger => {
                     self.draw_logger(buf, ferrite_to_tui_rect(pane_rect));
                 }
+                PaneKind::LocationList => {
+                    self.draw_location_list(buf, ferrite_to_tui_rect(pane_rect));
+                }
+                PaneKind::Git => {
+                    self.draw_git_status(buf, ferrite_to_tui_rect(pane_rect));
+                }
+                PaneKind::Notifications => {
+                    self.draw_notifications(buf, ferrite_to_tui_rect(pane_rect));
+                }
+            }
+            if self.engine.drop_target == Some(pane) {
+                let theme = &self.engine.themes[&self.engine.config.editor.theme];
+                buf.set_style(
+                    ferrite_to_tui_rect(pane_rect),
+                    convert_style(&theme.drop_target),
+                );
             }
         }
 
+        self.damage.retain(&shown);
+
         self.draw_overlays(buf, size);
     }
 }
@@ -0,0 +1,66 @@
+use ferrite_core::{config::editor::Editor, language::outline::Symbol, theme::EditorTheme};
+use tui::{layout::Rect, widgets::Widget};
+use unicode_width::UnicodeWidthStr;
+
+use crate::glue::convert_style;
+
+const SEPARATOR: &str = " › ";
+
+/// Height of the breadcrumbs line shown above the text of a buffer, zero if it is hidden
+pub fn breadcrumbs_height(config: &Editor, has_outline: bool) -> u16 {
+    (config.breadcrumbs && has_outline) as u16
+}
+
+/// The path of symbols containing the cursor
+pub struct Breadcrumbs<'a> {
+    pub theme: &'a EditorTheme,
+    pub symbols: &'a [Symbol],
+}
+
+impl Breadcrumbs<'_> {
+    /// Areas of the symbols rendered in `area`, used for hit testing mouse clicks
+    pub fn items(&self, area: Rect) -> Vec<BreadcrumbItem> {
+        let mut items = Vec::new();
+        let mut x = area.x + 1;
+        for symbol in self.symbols {
+            let width = symbol.name.width() as u16;
+            let item = Rect::new(x, area.y, width, 1).intersection(area);
+            if !item.is_empty() {
+                items.push(BreadcrumbItem {
+                    line: symbol.line,
+                    area: item,
+                });
+            }
+            x = x.saturating_add(width + SEPARATOR.width() as u16);
+        }
+        items
+    }
+}
+
+/// A symbol of the breadcrumbs and where it was rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreadcrumbItem {
+    /// Line the symbol starts on
+    pub line: usize,
+    pub area: Rect,
+}
+
+impl Widget for Breadcrumbs<'_> {
+    fn render(self, area: Rect, buf: &mut tui::buffer::Buffer) {
+        let style = convert_style(&self.theme.breadcrumbs);
+        buf.set_style(area, style);
+        let text = self
+            .symbols
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .collect::<Vec<_>>()
+            .join(SEPARATOR);
+        buf.set_stringn(
+            area.x + 1,
+            area.y,
+            &text,
+            area.width.saturating_sub(1).into(),
+            style,
+        );
+    }
+}
@@ -12,6 +12,28 @@ impl<'a> CompleterWidget<'a> {
     pub fn new(theme: &'a EditorTheme) -> Self {
         Self { theme }
     }
+
+    /// Width of a column and the number of columns and rows the options need in `width`
+    fn grid(completer: &Completer, width: u16) -> (usize, usize, usize) {
+        let widest = completer
+            .options()
+            .iter()
+            .map(|option| option.display().width())
+            .max()
+            .unwrap_or(0)
+            + 8;
+        let columns = (width as usize / widest).max(1);
+        let rows = (completer.options().len() / columns).clamp(1, 10);
+        (widest, columns, rows)
+    }
+
+    /// Number of rows the completer takes up when rendered `width` columns wide
+    pub fn height(completer: &Completer, width: u16) -> u16 {
+        if completer.options().is_empty() {
+            return 0;
+        }
+        Self::grid(completer, width).2 as u16
+    }
 }
 
 impl StatefulWidget for CompleterWidget<'_> {
@@ -27,17 +49,8 @@ impl StatefulWidget for CompleterWidget<'_> {
             return;
         }
 
-        let widest = completer
-            .options()
-            .iter()
-            .map(|option| option.display().width())
-            .max()
-            .unwrap()
-            + 8;
-        let columns = (area.width as usize / widest).max(1);
-        let rows = (completer.options().len() / columns)
-            .clamp(1, 10)
-            .min(area.height.into());
+        let (widest, columns, rows) = Self::grid(completer, area.width);
+        let rows = rows.min(area.height.into());
 
         let completer_area =
             tui::layout::Rect::new(area.x, area.bottom() - rows as u16, area.width, rows as u16);
@@ -0,0 +1,74 @@
+use ferrite_core::{completion::Completion, theme::EditorTheme};
+use tui::{
+    layout::{Position, Rect},
+    widgets::{Clear, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{glue::convert_style, rect_ext::RectExt};
+
+/// Popup listing completions below or above `anchor`
+pub struct CompletionWidget<'a> {
+    theme: &'a EditorTheme,
+    completion: &'a Completion,
+    anchor: Position,
+}
+
+impl<'a> CompletionWidget<'a> {
+    pub fn new(theme: &'a EditorTheme, completion: &'a Completion, anchor: Position) -> Self {
+        Self {
+            theme,
+            completion,
+            anchor,
+        }
+    }
+}
+
+impl Widget for CompletionWidget<'_> {
+    fn render(self, area: Rect, buf: &mut tui::buffer::Buffer) {
+        let options = &self.completion.options;
+        let width = options
+            .iter()
+            .map(|option| option.label.width())
+            .max()
+            .unwrap_or_default()
+            + 2;
+        let height = options.len() as u16;
+
+        let y =
+            if self.anchor.y + 1 + height <= area.bottom() || self.anchor.y < area.top() + height {
+                self.anchor.y + 1
+            } else {
+                self.anchor.y - height
+            };
+        let x = self
+            .anchor
+            .x
+            .min(area.right().saturating_sub(width as u16))
+            .max(area.left());
+
+        let popup_area = Rect::new(x, y, width as u16, height).clamp_within(area);
+        Clear.render(popup_area, buf);
+        buf.set_style(popup_area, convert_style(&self.theme.completer));
+
+        for (i, option) in options.iter().enumerate() {
+            let y = y + i as u16;
+            if y >= popup_area.bottom() {
+                break;
+            }
+            let style = if i == self.completion.selected {
+                convert_style(&self.theme.completer_selected)
+            } else {
+                convert_style(&self.theme.completer)
+            };
+            buf.set_style(Rect::new(popup_area.x, y, popup_area.width, 1), style);
+            buf.set_stringn(
+                popup_area.x + 1,
+                y,
+                &option.label,
+                popup_area.width.saturating_sub(2).into(),
+                style,
+            );
+        }
+    }
+}
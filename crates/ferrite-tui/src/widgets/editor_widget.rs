@@ -1,12 +1,14 @@
 use std::ops::Add;
 
 use ferrite_core::{
-    buffer::{search::SearchMatch, Buffer, Selection, ViewId},
+    ansi::AnsiSpan,
+    buffer::{search::SearchMatch, tail, Buffer, Selection, ViewId},
+    completion::Completion,
     config::{
         self,
-        editor::{CursorType, Editor, LineNumber},
+        editor::{Editor, LineNumber},
     },
-    language::syntax::{Highlight, HighlightEvent},
+    diff::WordChange,
     theme::EditorTheme,
 };
 use ferrite_utility::{
@@ -24,8 +26,15 @@ use tui::{
 };
 use unicode_width::UnicodeWidthStr;
 
-use super::info_line::InfoLine;
-use crate::{glue::convert_style, rect_ext::RectExt};
+use super::{
+    breadcrumbs::{breadcrumbs_height, BreadcrumbItem, Breadcrumbs},
+    completion_widget::CompletionWidget,
+    info_line::{InfoLine, InfoLineItem},
+};
+use crate::{
+    glue::{convert_ansi_style, convert_style, cursor_style},
+    rect_ext::RectExt,
+};
 
 pub fn lines_to_left_offset(lines: usize) -> (usize, usize) {
     let line_number_max_width = lines.to_string().len().add(1).max(4);
@@ -35,19 +44,23 @@ pub fn lines_to_left_offset(lines: usize) -> (usize, usize) {
     (line_number_max_width, left_offset)
 }
 
-fn intersects(start1: usize, end1: usize, start2: usize, end2: usize) -> bool {
-    !(start1 > end2 || end1 < start2)
-}
-
 pub struct EditorWidget<'a> {
     theme: &'a EditorTheme,
     config: &'a Editor,
     view_id: ViewId,
     has_focus: bool,
     branch: Option<String>,
-    spinner: Option<char>,
+    progress: Option<String>,
     pub line_nr: bool,
     pub info_line: bool,
+    pub breadcrumbs: bool,
+    pub completion: Option<&'a Completion>,
+    /// Styles of stripped ansi escape sequences, sorted by position
+    pub ansi_spans: Option<&'a [AnsiSpan]>,
+    /// Receives the areas of the rendered info line items
+    pub info_line_items: Option<&'a mut Vec<InfoLineItem>>,
+    /// Receives the areas of the rendered breadcrumbs
+    pub breadcrumb_items: Option<&'a mut Vec<BreadcrumbItem>>,
 }
 
 impl<'a> EditorWidget<'a> {
@@ -57,7 +70,7 @@ impl<'a> EditorWidget<'a> {
         view_id: ViewId,
         has_focus: bool,
         branch: Option<String>,
-        spinner: Option<char>,
+        progress: Option<String>,
     ) -> Self {
         Self {
             theme,
@@ -65,9 +78,14 @@ impl<'a> EditorWidget<'a> {
             view_id,
             has_focus,
             branch,
-            spinner,
+            progress,
             line_nr: true,
             info_line: true,
+            breadcrumbs: true,
+            completion: None,
+            ansi_spans: None,
+            info_line_items: None,
+            breadcrumb_items: None,
         }
     }
 }
@@ -93,9 +111,13 @@ impl StatefulWidget for EditorWidget<'_> {
             view_id,
             has_focus,
             branch,
-            spinner,
+            progress,
             line_nr,
             info_line,
+            breadcrumbs,
+            completion,
+            info_line_items,
+            breadcrumb_items,
         } = self;
 
         let (line_number_max_width, left_offset) =
@@ -105,13 +127,30 @@ impl StatefulWidget for EditorWidget<'_> {
                 (0, 0)
             };
 
+        let header = match breadcrumbs {
+            true => breadcrumbs_height(config, buffer.has_outline()).min(area.height),
+            false => 0,
+        };
         let text_area = Rect {
             x: area.x + left_offset as u16,
-            y: area.y,
+            y: area.y + header,
             width: area.width.saturating_sub(left_offset as u16),
-            height: area.height - info_line as u16,
+            height: (area.height - header).saturating_sub(info_line as u16),
         };
 
+        if header > 0 {
+            let symbols = buffer.breadcrumbs(view_id);
+            let breadcrumbs = Breadcrumbs {
+                theme,
+                symbols: &symbols,
+            };
+            let breadcrumbs_area = Rect::new(area.x, area.y, area.width, header);
+            if let Some(items) = breadcrumb_items {
+                items.extend(breadcrumbs.items(breadcrumbs_area));
+            }
+            breadcrumbs.render(breadcrumbs_area, buf);
+        }
+
         buffer.set_view_lines(view_id, text_area.height.into());
 
         buffer.set_view_columns(
@@ -124,18 +163,25 @@ impl StatefulWidget for EditorWidget<'_> {
             buf.set_style(
                 Rect {
                     x: area.left(),
-                    y: area.top(),
+                    y: text_area.top(),
                     width: (line_number_max_width as u16).min(area.width),
-                    height: area.height,
+                    height: area.height - header,
                 },
                 convert_style(&theme.line_nr),
             );
         }
 
         let cursor_line_number = buffer.cursor_line_idx(view_id, 0) + 1;
+        let bookmarks = buffer.bookmarks();
 
         // We have to overwrite all rendered whitespace with the correct color
         let mut dim_cells = Vec::new();
+        let mut trailing_cells = Vec::new();
+        let mut invisible_cells = Vec::new();
+        let selections = match config.render_whitespace {
+            config::editor::RenderWhitespace::Selection => buffer.get_view_selection(view_id),
+            _ => Vec::new(),
+        };
         let mut grapheme_buffer = String::new();
         let view = buffer.get_buffer_view(view_id);
         {
@@ -148,6 +194,7 @@ impl StatefulWidget for EditorWidget<'_> {
             {
                 if line_nr {
                     let is_current_line = line_number == cursor_line_number;
+                    let bookmarked = bookmarks.binary_search(&(line_number - 1)).is_ok();
                     let line_number =
                         if (config.line_number == LineNumber::Absolute) || is_current_line {
                             line_number
@@ -172,12 +219,22 @@ impl StatefulWidget for EditorWidget<'_> {
 
                     buf.set_stringn(
                         area.x,
-                        area.y + i as u16,
+                        text_area.y + i as u16,
                         &line_number_str,
                         area.width.into(),
                         line_nr_theme,
                     );
 
+                    if bookmarked && (line_number_max_width as u16) < area.width {
+                        buf.set_stringn(
+                            area.x + line_number_max_width as u16,
+                            text_area.y + i as u16,
+                            "●",
+                            1,
+                            convert_style(&theme.bookmark),
+                        );
+                    }
+
                     let start_offset = " ".repeat(line.col_start_offset);
                     if text_area.width > 0 {
                         buf.set_stringn(
@@ -208,6 +265,12 @@ impl StatefulWidget for EditorWidget<'_> {
                         config::editor::RenderWhitespace::All => true,
                         config::editor::RenderWhitespace::None => false,
                         config::editor::RenderWhitespace::Trailing => col >= text_end_col,
+                        config::editor::RenderWhitespace::Selection => {
+                            let point = Point::new(col as i64, i as i64);
+                            selections
+                                .iter()
+                                .any(|Selection { start, end }| point >= *start && point < *end)
+                        }
                     }
                 };
 
@@ -221,6 +284,9 @@ impl StatefulWidget for EditorWidget<'_> {
                         let tab_width = tab_width_at(current_width, TAB_WIDTH);
                         if render_whitespace(current_width, line.text_end_col) {
                             dim_cells.push((current_width, i));
+                            if current_width >= line.text_end_col {
+                                trailing_cells.push((current_width, tab_width, i));
+                            }
                             grapheme_buffer.push('→');
                         } else {
                             grapheme_buffer.push(' ');
@@ -239,10 +305,34 @@ impl StatefulWidget for EditorWidget<'_> {
                     if grapheme.chars().any(|ch| ch.is_ascii_control()) {
                         current_width +=
                             render_text("�", convert_style(&theme.text), current_width);
+                    } else if grapheme.chars().any(is_invisible) {
+                        // Invisible characters are always shown so they can't hide in the text
+                        let width = grapheme.width(current_width);
+                        invisible_cells.push((current_width, width.max(1), i));
+                        if grapheme.is_whitespace() {
+                            current_width +=
+                                render_text("⍽", convert_style(&theme.text), current_width);
+                            for _ in 0..width.saturating_sub(1) {
+                                current_width +=
+                                    render_text(" ", convert_style(&theme.text), current_width);
+                            }
+                        } else {
+                            grapheme_buffer
+                                .extend(grapheme.chars().filter(|ch| !is_invisible(*ch)));
+                            current_width += render_text(
+                                &grapheme_buffer,
+                                convert_style(&theme.text),
+                                current_width,
+                            );
+                            grapheme_buffer.clear();
+                        }
                     } else if grapheme.is_whitespace() {
                         let width = grapheme.width(current_width);
                         if render_whitespace(current_width, line.text_end_col) {
                             dim_cells.push((current_width, i));
+                            if current_width >= line.text_end_col {
+                                trailing_cells.push((current_width, width.max(1), i));
+                            }
                             current_width +=
                                 render_text("·", convert_style(&theme.dim_text), current_width);
                         } else {
@@ -314,13 +404,22 @@ impl StatefulWidget for EditorWidget<'_> {
 
             let mut cursor_rects = Vec::new();
             if has_focus {
+                let primary = (
+                    buffer
+                        .cursor_grapheme_column(view_id, 0)
+                        .wrapping_sub(buffer.views[view_id].col_pos_floored()),
+                    buffer
+                        .cursor_line_idx(view_id, 0)
+                        .wrapping_sub(buffer.views[view_id].line_pos_floored()),
+                );
                 for (column, row) in cursor_view_pos {
-                    cursor_rects.push(Rect {
+                    let rect = Rect {
                         x: text_area.x + column as u16,
                         y: text_area.y + row as u16,
                         width: 1,
                         height: 1,
-                    });
+                    };
+                    cursor_rects.push((rect, (column, row) == primary));
                 }
             }
 
@@ -330,32 +429,19 @@ impl StatefulWidget for EditorWidget<'_> {
             let mut highlights = Vec::new();
             let mut syntax_rope = None;
             {
-                // TODO do this async on syntax thread
-                profiling::scope!("collect syntax events");
+                profiling::scope!("collect syntax highlights");
                 if let Some(syntax) = buffer.get_syntax() {
-                    if let Some((rope, events)) = &*syntax.get_highlight_events() {
-                        syntax_rope = Some(rope.clone());
-                        let mut highlight_stack: Vec<Highlight> = Vec::new();
-                        for event in events {
-                            match event {
-                                HighlightEvent::Source { start, end } => {
-                                    if intersects(*start, *end, range.start, range.end) {
-                                        let mut style = convert_style(&theme.text);
-                                        if let Some(highlight) = highlight_stack.last() {
-                                            if let Some(name) = highlight
-                                                .query
-                                                .capture_names()
-                                                .get(highlight.capture_index)
-                                            {
-                                                style = convert_style(&self.theme.get_syntax(name));
-                                            }
-                                        }
-                                        highlights.push((*start, *end, style));
-                                    }
-                                }
-                                HighlightEvent::HighlightStart(h) => highlight_stack.push(*h),
-                                HighlightEvent::HighlightEnd => drop(highlight_stack.pop()),
+                    syntax.set_viewport(range.clone());
+                    if let Some(syntax_highlights) = &*syntax.get_highlights() {
+                        syntax_rope = Some(syntax_highlights.rope.clone());
+                        for span in syntax_highlights.spans_in(range.clone()) {
+                            let mut style = convert_style(&theme.text);
+                            if let Some(name) = span.highlight.and_then(|highlight| {
+                                highlight.query.capture_names().get(highlight.capture_index)
+                            }) {
+                                style = convert_style(&self.theme.get_syntax(name));
                             }
+                            highlights.push((span.start, span.end, style));
                         }
                     }
                 }
@@ -423,6 +509,147 @@ impl StatefulWidget for EditorWidget<'_> {
                 }
             }
 
+            if self.config.word_diff && buffer.language_name() == "diff" {
+                profiling::scope!("word diff");
+                let visible_lines = line_pos..(line_pos + text_area.height as usize);
+                let changes = buffer.word_changes(visible_lines.clone());
+                let rope = buffer.rope();
+                for WordChange { range, added } in changes.iter().cloned() {
+                    let start = rope.byte_to_point(range.start);
+                    let end = rope.byte_to_point(range.end);
+                    if !visible_lines.contains(&start.line) {
+                        continue;
+                    }
+                    let width = text_area.width as usize;
+                    let start_x = start.column.saturating_sub(col_pos).min(width);
+                    let end_x = end.column.saturating_sub(col_pos).min(width);
+                    let highlight_area = Rect {
+                        x: start_x as u16 + text_area.x,
+                        y: (start.line - line_pos) as u16 + text_area.y,
+                        width: end_x.saturating_sub(start_x) as u16,
+                        height: 1,
+                    };
+                    let style = if added {
+                        &theme.diff_add
+                    } else {
+                        &theme.diff_remove
+                    };
+                    buf.set_style(
+                        highlight_area.clamp_within(text_area),
+                        convert_style(style).add_modifier(tui::style::Modifier::REVERSED),
+                    );
+                }
+            }
+
+            let conflicts = buffer.conflicts();
+            if !conflicts.is_empty() {
+                profiling::scope!("conflicts");
+                let visible_lines = line_pos..(line_pos + text_area.height as usize);
+                for conflict in conflicts.iter() {
+                    for line_idx in conflict.lines() {
+                        if !visible_lines.contains(&line_idx) {
+                            continue;
+                        }
+                        let style = if conflict.is_marker(line_idx) {
+                            &theme.conflict_marker
+                        } else if conflict.ours().contains(&line_idx) {
+                            &theme.conflict_ours
+                        } else if conflict.theirs().contains(&line_idx) {
+                            &theme.conflict_theirs
+                        } else {
+                            continue;
+                        };
+                        let line_area = Rect {
+                            x: text_area.x,
+                            y: (line_idx - line_pos) as u16 + text_area.y,
+                            width: text_area.width,
+                            height: 1,
+                        };
+                        buf.set_style(line_area, convert_style(style));
+                    }
+                }
+            }
+
+            if config.tail_highlight && buffer.is_tailing() {
+                profiling::scope!("tail levels");
+                let rope = buffer.rope();
+                let end_line = (line_pos + text_area.height as usize).min(rope.len_lines());
+                for line_idx in line_pos..end_line {
+                    let line = rope.line_without_line_ending(line_idx).to_string();
+                    let Some(level) = tail::line_level(&line) else {
+                        continue;
+                    };
+                    let line_area = Rect {
+                        x: text_area.x,
+                        y: (line_idx - line_pos) as u16 + text_area.y,
+                        width: text_area.width,
+                        height: 1,
+                    };
+                    buf.set_style(line_area, convert_style(theme.diagnostic(level)));
+                }
+            }
+
+            if let Some(spans) = self.ansi_spans {
+                profiling::scope!("ansi spans");
+                let rope = buffer.rope();
+                let start_byte = rope.line_to_byte(line_pos.min(rope.len_lines()));
+                let end_byte =
+                    rope.line_to_byte((line_pos + text_area.height as usize).min(rope.len_lines()));
+                let first = spans.partition_point(|span| span.range.end <= start_byte);
+                for span in spans[first..]
+                    .iter()
+                    .take_while(|span| span.range.start < end_byte)
+                {
+                    if span.range.end > rope.len_bytes() {
+                        break;
+                    }
+                    let start = rope.byte_to_point(span.range.start);
+                    let end_column =
+                        start.column + rope.byte_slice(span.range.clone()).width(start.column);
+                    if end_column <= col_pos {
+                        continue;
+                    }
+                    let x = start.column.max(col_pos) - col_pos;
+                    let cell_area = Rect {
+                        x: x as u16 + text_area.x,
+                        y: (start.line - line_pos) as u16 + text_area.y,
+                        width: (end_column - col_pos - x) as u16,
+                        height: 1,
+                    };
+                    buf.set_style(
+                        cell_area.clamp_within(text_area),
+                        convert_ansi_style(theme, &span.style),
+                    );
+                }
+            }
+
+            {
+                profiling::scope!("suspicious chars");
+                let rope = buffer.rope();
+                for found in
+                    buffer.suspicious_chars(line_pos..(line_pos + text_area.height as usize))
+                {
+                    // Columns are display columns, zero width characters still get one cell
+                    let start = rope.byte_to_point(found.range.start);
+                    let end_column =
+                        start.column + rope.byte_slice(found.range).width(start.column).max(1);
+                    if end_column <= col_pos {
+                        continue;
+                    }
+                    let x = start.column.max(col_pos) - col_pos;
+                    let cell_area = Rect {
+                        x: x as u16 + text_area.x,
+                        y: (start.line - line_pos) as u16 + text_area.y,
+                        width: (end_column - col_pos - x) as u16,
+                        height: 1,
+                    };
+                    buf.set_style(
+                        cell_area.clamp_within(text_area),
+                        convert_style(&theme.suspicious_char),
+                    );
+                }
+            }
+
             // Stupid hack to fix tree sitter writing over rendered whitespace
             for (col, line) in dim_cells {
                 let cell_area = Rect {
@@ -434,13 +661,28 @@ impl StatefulWidget for EditorWidget<'_> {
                 buf.set_style(cell_area, convert_style(&theme.dim_text));
             }
 
+            for (cells, style) in [
+                (trailing_cells, &theme.trailing_whitespace),
+                (invisible_cells, &theme.invisible),
+            ] {
+                for (col, width, line) in cells {
+                    let cell_area = Rect {
+                        x: col as u16 + text_area.x,
+                        y: line as u16 + text_area.y,
+                        width: width as u16,
+                        height: 1,
+                    };
+                    buf.set_style(cell_area.clamp_within(text_area), convert_style(style));
+                }
+            }
+
             for ruler in config.rulers.iter().copied() {
                 let real_col = ruler as i64 - buffer.col_pos(view_id) as i64
                     + area.x as i64
                     + left_offset as i64
                     + 1;
                 if (area.left().into()..area.right().into()).contains(&real_col) {
-                    for y in area.top()..(area.bottom() - 1) {
+                    for y in text_area.top()..text_area.bottom() {
                         let cell = buf.cell_mut((real_col as u16, y)).unwrap();
                         if cell.symbol().chars().all(|ch| ch.is_whitespace()) {
                             cell.set_symbol("│");
@@ -456,22 +698,13 @@ impl StatefulWidget for EditorWidget<'_> {
                 cell.set_style(convert_style(&self.theme.ruler));
             }
 
-            for rect in cursor_rects {
-                match self.config.gui.cursor_type {
-                    CursorType::Block => {
-                        buf.set_style(
-                            rect,
-                            convert_style(&theme.text).add_modifier(tui::style::Modifier::REVERSED),
-                        );
-                    }
-                    CursorType::Line => {
-                        buf.set_style(
-                            rect,
-                            tui::style::Style::default()
-                                .add_modifier(tui::style::Modifier::SLOW_BLINK),
-                        );
-                    }
-                }
+            for (rect, primary) in cursor_rects {
+                let cursor_type = if primary {
+                    self.config.gui.cursor_type
+                } else {
+                    self.config.gui.secondary_cursor_type()
+                };
+                buf.set_style(rect, cursor_style(theme, cursor_type, primary));
             }
 
             draw_cursor_line &= !buffer.views[view_id]
@@ -497,9 +730,6 @@ impl StatefulWidget for EditorWidget<'_> {
                 .get_searcher(view_id)
                 .map(|searcher| searcher.get_matches());
             if let Some(matches) = matches {
-                let matches = matches.lock().unwrap();
-                let matches = &*matches.0;
-
                 for SearchMatch { start, end, .. } in matches {
                     if start.line >= buffer.line_pos(view_id)
                         && end.line + 2 < buffer.line_pos(view_id) + buffer.get_view_lines(view_id)
@@ -552,6 +782,22 @@ impl StatefulWidget for EditorWidget<'_> {
                 }
             }
 
+            if let Some(completion) = completion.filter(|_| has_focus) {
+                let cursor_view_pos = buffer.cursor_view_pos(
+                    view_id,
+                    text_area.width.into(),
+                    text_area.height.into(),
+                );
+                let partial = buffer.rope().get_byte_slice(completion.range.clone());
+                if let (Some(&(column, row)), Some(partial)) = (cursor_view_pos.first(), partial) {
+                    let anchor = Position::new(
+                        text_area.x + column.saturating_sub(partial.width(0)) as u16,
+                        text_area.y + row as u16,
+                    );
+                    CompletionWidget::new(theme, completion, anchor).render(text_area, buf);
+                }
+            }
+
             if info_line {
                 let path = if let Some(path) = buffer.file() {
                     path.to_string_lossy().into()
@@ -564,6 +810,7 @@ impl StatefulWidget for EditorWidget<'_> {
                     config: &self.config.info_line,
                     focus: self.has_focus,
                     encoding: buffer.encoding,
+                    bom: buffer.bom,
                     path,
                     line: buffer.cursor_line_idx(view_id, 0) + 1,
                     column: buffer.cursor_grapheme_column(view_id, 0) + 1,
@@ -571,14 +818,41 @@ impl StatefulWidget for EditorWidget<'_> {
                     branch: &branch,
                     language: buffer.language_name().into(),
                     size: buffer.rope().len_bytes(),
-                    read_only: buffer.read_only_file,
-                    spinner,
+                    read_only: buffer.read_only_file || buffer.read_only,
+                    conflicts: conflicts.len(),
+                    search: buffer
+                        .get_searcher(view_id)
+                        .map(|searcher| searcher.match_position()),
+                    progress,
                 };
-                info_line.render(
-                    Rect::new(area.x, text_area.height + text_area.y, area.width, 1),
-                    buf,
-                );
+                let info_line_area =
+                    Rect::new(area.x, text_area.height + text_area.y, area.width, 1);
+                if let Some(items) = info_line_items {
+                    items.extend(info_line.items(info_line_area));
+                }
+                info_line.render(info_line_area, buf);
             }
         }
     }
 }
+
+/// Characters that render as nothing or as a regular space and could hide in the text.
+/// Bidi controls are included so they are never sent to the terminal.
+/// The zero width joiner is left out as it is part of many emoji.
+fn is_invisible(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{00A0}'
+            | '\u{2007}'
+            | '\u{202F}'
+            | '\u{200B}'
+            | '\u{200C}'
+            | '\u{2060}'
+            | '\u{FEFF}'
+            | '\u{061C}'
+            | '\u{200E}'
+            | '\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2066}'..='\u{2069}'
+    )
+}
@@ -62,7 +62,7 @@ impl StatefulWidget for FileExplorerWidget<'_> {
                 }
 
                 let style = if i as usize + start == state.index() {
-                    convert_style(&self.theme.selection)
+                    convert_style(&self.theme.picker_selection)
                 } else {
                     convert_style(&self.theme.text)
                 };
@@ -0,0 +1,190 @@
+use ferrite_core::{git::status::GitStatus, theme::EditorTheme};
+use tui::{
+    layout::Rect,
+    widgets::{Block, BorderType, Borders, Clear, StatefulWidget, Widget},
+};
+
+use super::centered_text_widget::CenteredTextWidget;
+use crate::glue::convert_style;
+
+pub struct GitStatusWidget<'a> {
+    theme: &'a EditorTheme,
+    branch: Option<String>,
+    has_focus: bool,
+}
+
+impl<'a> GitStatusWidget<'a> {
+    pub fn new(theme: &'a EditorTheme, branch: Option<String>, has_focus: bool) -> Self {
+        Self {
+            theme,
+            branch,
+            has_focus,
+        }
+    }
+}
+
+impl StatefulWidget for GitStatusWidget<'_> {
+    type State = GitStatus;
+
+    fn render(
+        self,
+        area: tui::layout::Rect,
+        buf: &mut tui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        if area.area() == 0 {
+            return;
+        }
+
+        Clear.render(area, buf);
+        buf.set_style(area, convert_style(&self.theme.background));
+
+        let list_area = Rect {
+            height: area.height - 1,
+            ..area
+        };
+        let (list_area, diff_area) = if list_area.width > 60 {
+            let width = list_area.width / 3;
+            (
+                Rect { width, ..list_area },
+                Rect {
+                    x: list_area.x + width + 1,
+                    width: list_area.width - width - 1,
+                    ..list_area
+                },
+            )
+        } else {
+            (list_area, Rect::new(0, 0, 0, 0))
+        };
+
+        if let Some(error) = &state.error {
+            CenteredTextWidget::new(self.theme, error).render(list_area, buf);
+        } else if state.entries.is_empty() && state.root.is_some() {
+            CenteredTextWidget::new(self.theme, "Nothing to commit").render(list_area, buf);
+        } else if list_area.height > 0 {
+            let start = state.index / list_area.height as usize * list_area.height as usize;
+            for (i, entry) in state
+                .entries
+                .iter()
+                .enumerate()
+                .skip(start)
+                .take(list_area.height as usize)
+            {
+                let y = list_area.y + (i - start) as u16;
+                let x = list_area.x + 1;
+                buf.set_stringn(
+                    x,
+                    y,
+                    entry.index.to_string(),
+                    1,
+                    convert_style(&self.theme.diff_add),
+                );
+                buf.set_stringn(
+                    x + 1,
+                    y,
+                    entry.worktree.to_string(),
+                    1,
+                    convert_style(&self.theme.diff_remove),
+                );
+                buf.set_stringn(
+                    x + 3,
+                    y,
+                    &entry.path,
+                    list_area.width.saturating_sub(4).into(),
+                    convert_style(&self.theme.text),
+                );
+                if state.index == i {
+                    buf.set_style(
+                        Rect {
+                            y,
+                            height: 1,
+                            ..list_area
+                        },
+                        convert_style(&self.theme.picker_selection),
+                    );
+                }
+            }
+        }
+
+        if diff_area.area() > 0 {
+            let line_area = Rect::new(diff_area.x - 1, diff_area.y, 1, diff_area.height);
+            Block::default()
+                .borders(Borders::LEFT)
+                .border_style(convert_style(&self.theme.border))
+                .border_type(BorderType::Plain)
+                .style(convert_style(&self.theme.background))
+                .render(line_area, buf);
+
+            // Start at the selected hunk so it is always visible
+            let lines = state
+                .hunks
+                .iter()
+                .enumerate()
+                .skip(state.hunk_index.unwrap_or(0))
+                .flat_map(|(i, hunk)| {
+                    hunk.lines.iter().enumerate().map(move |(line_idx, line)| {
+                        let header = line_idx == 0;
+                        (i, hunk.staged, header, line.trim_end_matches('\r'))
+                    })
+                });
+            for (y, (i, staged, header, line)) in
+                (diff_area.y..diff_area.y + diff_area.height).zip(lines)
+            {
+                let style = if header {
+                    &self.theme.dim_text
+                } else if line.starts_with('+') {
+                    &self.theme.diff_add
+                } else if line.starts_with('-') {
+                    &self.theme.diff_remove
+                } else {
+                    &self.theme.text
+                };
+                let text = if header && staged {
+                    format!("{line} (staged)")
+                } else {
+                    line.to_string()
+                };
+                buf.set_stringn(
+                    diff_area.x,
+                    y,
+                    text,
+                    diff_area.width.into(),
+                    convert_style(style),
+                );
+                if header && state.hunk_index == Some(i) {
+                    buf.set_style(
+                        Rect {
+                            y,
+                            height: 1,
+                            ..diff_area
+                        },
+                        convert_style(&self.theme.picker_selection),
+                    );
+                }
+            }
+        }
+
+        let line_area = Rect {
+            y: area.y + area.height - 1,
+            height: 1,
+            ..area
+        };
+        let style = convert_style(if self.has_focus {
+            &self.theme.info_line
+        } else {
+            &self.theme.info_line_unfocused
+        });
+        buf.set_style(line_area, style);
+        let branch = self.branch.unwrap_or_default();
+        buf.set_stringn(
+            line_area.x,
+            line_area.y,
+            format!(
+                " Git: {branch} [{}] s: stage, u: unstage, [ ]: select hunk, c: commit, r: refresh",
+                state.entries.len()
+            ),
+            line_area.width.into(),
+            style,
+        );
+    }
+}
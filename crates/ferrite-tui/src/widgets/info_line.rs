@@ -1,8 +1,9 @@
 use encoding_rs::Encoding;
 use ferrite_core::{
-    byte_size::format_byte_size, config::editor::InfoLineConfig, theme::EditorTheme,
+    byte_size::format_byte_size, config::editor::InfoLineConfig, theme::EditorTheme, tr,
 };
 use tui::{
+    layout::Rect,
     style::Style,
     widgets::{Clear, Widget},
 };
@@ -15,6 +16,7 @@ pub struct InfoLine<'a> {
     pub config: &'a InfoLineConfig,
     pub focus: bool,
     pub encoding: &'static Encoding,
+    pub bom: bool,
     pub path: String,
     pub column: usize,
     pub line: usize,
@@ -22,8 +24,12 @@ pub struct InfoLine<'a> {
     pub branch: &'a Option<String>,
     pub language: String,
     pub size: usize,
-    pub spinner: Option<char>,
+    /// Spinner and progress of the most recent running job
+    pub progress: Option<String>,
     pub read_only: bool,
+    pub conflicts: usize,
+    /// Index of the current match and the total amount of matches while searching
+    pub search: Option<(Option<usize>, usize)>,
 }
 
 impl InfoLine<'_> {
@@ -45,20 +51,98 @@ impl InfoLine<'_> {
                 }
                 Some(file)
             }
+            "encoding" if self.bom => Some(tr!("info_line.bom", encoding = self.encoding.name())),
             "encoding" => Some(self.encoding.name().to_string()),
             "language" => Some(self.language.clone()),
             "position" => Some(format!("{}:{}", self.line, self.column)),
             "branch" => self.branch.clone(),
             "size" => Some(format_byte_size(self.size)),
-            "spinner" => Some(self.spinner.unwrap_or(' ').to_string()),
+            "spinner" => Some(self.progress.clone().unwrap_or_else(|| " ".into())),
             "read_only" if self.read_only => Some("🔒".into()),
+            "conflicts" if self.conflicts > 0 => {
+                Some(tr!("info_line.conflicts", count = self.conflicts))
+            }
+            "search" => match self.search? {
+                (Some(index), total) => {
+                    Some(tr!("info_line.match", index = index + 1, total = total))
+                }
+                (None, total) => Some(tr!("info_line.matches", total = total)),
+            },
             _ => None,
         }
     }
 }
 
+impl InfoLine<'_> {
+    /// Joins the items of a section, returning the text and the name, offset and width of every item
+    fn section(&self, items: &[String]) -> (String, Vec<(String, u16, u16)>) {
+        let mut text = String::from(" ");
+        let mut offsets = Vec::new();
+        for name in items {
+            if let Some(item) = self.get_info_item(name) {
+                offsets.push((name.clone(), text.width() as u16, item.width() as u16));
+                text.push_str(&item);
+                text.push_str(&" ".repeat(self.config.padding));
+            }
+        }
+        (text, offsets)
+    }
+
+    /// Returns the x position of the left, center and right sections, or `None` if a section does not fit
+    fn section_positions(
+        area: Rect,
+        left_width: usize,
+        center_width: usize,
+        right_width: usize,
+    ) -> [Option<u16>; 3] {
+        let right = (area.width as usize > left_width + right_width)
+            .then(|| (area.x + area.width) - right_width as u16);
+        let center = (area.width as usize > left_width + center_width + right_width).then(|| {
+            let center_max_width = area.width as usize - left_width - right_width;
+            let padding = (center_max_width - center_width / 2) / 2;
+            area.x + padding as u16
+        });
+        [Some(area.x), center, right]
+    }
+
+    /// Areas of the items rendered in `area`, used for hit testing mouse clicks
+    pub fn items(&self, area: Rect) -> Vec<InfoLineItem> {
+        let sections = [
+            self.section(&self.config.left),
+            self.section(&self.config.center),
+            self.section(&self.config.right),
+        ];
+        let positions = Self::section_positions(
+            area,
+            sections[0].0.width(),
+            sections[1].0.width(),
+            sections[2].0.width(),
+        );
+        let mut items = Vec::new();
+        for ((_, offsets), x) in sections.into_iter().zip(positions) {
+            let Some(x) = x else {
+                continue;
+            };
+            for (name, offset, width) in offsets {
+                let item = Rect::new(x + offset, area.y, width, 1).intersection(area);
+                if !item.is_empty() {
+                    items.push(InfoLineItem { name, area: item });
+                }
+            }
+        }
+        items
+    }
+}
+
+/// An item of the info line and where it was rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfoLineItem {
+    pub name: String,
+    pub area: Rect,
+}
+
 impl Widget for InfoLine<'_> {
-    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+    fn render(self, area: Rect, buf: &mut tui::buffer::Buffer) {
         let style = match self.focus {
             true => convert_style(&self.theme.info_line),
             false => convert_style(&self.theme.info_line_unfocused),
@@ -66,51 +150,20 @@ impl Widget for InfoLine<'_> {
 
         Clear.render(area, buf);
 
-        let mut left = String::from(" ");
-        for item in &self.config.left {
-            if let Some(item) = self.get_info_item(item) {
-                left.push_str(&item);
-                left.push_str(&" ".repeat(self.config.padding));
-            }
-        }
-        let left_width = left.width();
-
-        let mut center = String::from(" ");
-        for item in &self.config.center {
-            if let Some(item) = self.get_info_item(item) {
-                center.push_str(&item);
-                center.push_str(&" ".repeat(self.config.padding));
-            }
-        }
-        let center_width = center.width();
-
-        let mut right = String::from(" ");
-        for item in &self.config.right {
-            if let Some(item) = self.get_info_item(item) {
-                right.push_str(&item);
-                right.push_str(&" ".repeat(self.config.padding));
-            }
-        }
-        let right_width = right.width();
+        let (left, _) = self.section(&self.config.left);
+        let (center, _) = self.section(&self.config.center);
+        let (right, _) = self.section(&self.config.right);
+        let [_, center_x, right_x] =
+            Self::section_positions(area, left.width(), center.width(), right.width());
 
         buf.set_stringn(area.x, area.y, &left, area.width.into(), style);
 
-        if area.width as usize > left_width + right_width {
-            let mut output_area = area;
-            output_area.x = (output_area.x + output_area.width) - right_width as u16;
-            buf.set_string(output_area.x, output_area.y, &right, Style::default());
+        if let Some(x) = right_x {
+            buf.set_string(x, area.y, &right, Style::default());
         }
 
-        if area.width as usize > left_width + center_width + right_width {
-            let center_max_width = area.width as usize - left_width - right_width;
-            let padding = (center_max_width - center_width / 2) / 2;
-            buf.set_stringn(
-                area.x + padding as u16,
-                area.y,
-                &center,
-                area.width.into(),
-                style,
-            );
+        if let Some(x) = center_x {
+            buf.set_stringn(x, area.y, &center, area.width.into(), style);
         }
 
         buf.set_style(area, style);
@@ -0,0 +1,170 @@
+use ferrite_core::{
+    config::editor::Editor, location_list::LocationList, picker::Preview, theme::EditorTheme,
+};
+use ferrite_utility::trim::trim_path;
+use tui::{
+    layout::Rect,
+    widgets::{Block, BorderType, Borders, Clear, StatefulWidget, Widget},
+};
+
+use super::{centered_text_widget::CenteredTextWidget, editor_widget::EditorWidget};
+use crate::glue::convert_style;
+
+pub struct LocationListWidget<'a> {
+    theme: &'a EditorTheme,
+    config: &'a Editor,
+    has_focus: bool,
+}
+
+impl<'a> LocationListWidget<'a> {
+    pub fn new(theme: &'a EditorTheme, config: &'a Editor, has_focus: bool) -> Self {
+        Self {
+            theme,
+            config,
+            has_focus,
+        }
+    }
+}
+
+impl StatefulWidget for LocationListWidget<'_> {
+    type State = LocationList;
+
+    fn render(
+        self,
+        area: tui::layout::Rect,
+        buf: &mut tui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        if area.area() == 0 {
+            return;
+        }
+
+        Clear.render(area, buf);
+        buf.set_style(area, convert_style(&self.theme.background));
+
+        let list_area = Rect {
+            height: area.height - 1,
+            ..area
+        };
+        let (list_area, preview_area) = if list_area.width > 60 {
+            let width = list_area.width / 2;
+            (
+                Rect { width, ..list_area },
+                Rect {
+                    x: list_area.x + width + 1,
+                    width: list_area.width - width - 1,
+                    ..list_area
+                },
+            )
+        } else {
+            (list_area, Rect::new(0, 0, 0, 0))
+        };
+
+        if list_area.height > 0 {
+            let cwd = std::env::current_dir().unwrap_or_default();
+            let cwd = cwd.to_string_lossy();
+            let selected = state.index.unwrap_or(0);
+            let start = selected / list_area.height as usize * list_area.height as usize;
+            for (i, location) in state
+                .entries
+                .iter()
+                .enumerate()
+                .skip(start)
+                .take(list_area.height as usize)
+            {
+                let y = list_area.y + (i - start) as u16;
+                let line = format!(
+                    " {}:{}:{} {}",
+                    trim_path(&cwd, &location.path),
+                    location.line,
+                    location.column,
+                    location.message
+                );
+                buf.set_stringn(
+                    list_area.x,
+                    y,
+                    line,
+                    list_area.width.into(),
+                    convert_style(&self.theme.text),
+                );
+                if state.index == Some(i) {
+                    buf.set_style(
+                        Rect {
+                            y,
+                            height: 1,
+                            ..list_area
+                        },
+                        convert_style(&self.theme.picker_selection),
+                    );
+                }
+            }
+        }
+
+        if preview_area.area() > 0 {
+            let line_area = Rect::new(preview_area.x - 1, preview_area.y, 1, preview_area.height);
+            Block::default()
+                .borders(Borders::LEFT)
+                .border_style(convert_style(&self.theme.border))
+                .border_type(BorderType::Plain)
+                .style(convert_style(&self.theme.background))
+                .render(line_area, buf);
+
+            match state.preview() {
+                Some(Preview::Buffer(buffer)) => {
+                    let view_id = buffer.get_first_view_or_create();
+                    let mut preview =
+                        EditorWidget::new(self.theme, self.config, view_id, false, None, None);
+                    preview.info_line = false;
+                    preview.breadcrumbs = false;
+                    preview.render(preview_area, buf, buffer);
+                }
+                Some(Preview::SharedBuffer(buffer)) => {
+                    let mut guard = buffer.lock().unwrap();
+                    let view_id = guard.get_first_view_or_create();
+                    let mut preview =
+                        EditorWidget::new(self.theme, self.config, view_id, false, None, None);
+                    preview.info_line = false;
+                    preview.breadcrumbs = false;
+                    preview.render(preview_area, buf, &mut *guard);
+                }
+                Some(Preview::TooLarge) => {
+                    CenteredTextWidget::new(self.theme, "Too large").render(preview_area, buf);
+                }
+                Some(Preview::Binary) => {
+                    CenteredTextWidget::new(self.theme, "Binary file").render(preview_area, buf);
+                }
+                Some(Preview::Err) => {
+                    CenteredTextWidget::new(self.theme, "Error loading preview")
+                        .render(preview_area, buf);
+                }
+                Some(Preview::Loading) => {
+                    CenteredTextWidget::new(self.theme, "Loading...").render(preview_area, buf);
+                }
+                None => (),
+            }
+        }
+
+        let line_area = Rect {
+            y: area.y + area.height - 1,
+            height: 1,
+            ..area
+        };
+        let style = convert_style(if self.has_focus {
+            &self.theme.info_line
+        } else {
+            &self.theme.info_line_unfocused
+        });
+        buf.set_style(line_area, style);
+        let position = match state.index {
+            Some(index) => format!("{}/{}", index + 1, state.entries.len()),
+            None => state.entries.len().to_string(),
+        };
+        buf.set_stringn(
+            line_area.x,
+            line_area.y,
+            format!(" Locations: {} [{position}]", state.title),
+            line_area.width.into(),
+            style,
+        );
+    }
+}
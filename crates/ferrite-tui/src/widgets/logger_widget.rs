@@ -44,13 +44,18 @@ impl StatefulWidget for LoggerWidget<'_> {
             {
                 Some(msg) => {
                     let string = format!("{:>5} {} {}", msg.level, msg.target, msg.fields.message);
+                    let y = area.top() + area.height - y - 2; // TODO fix this - 2
                     buf.set_stringn(
                         area.x,
-                        area.top() + area.height - y - 2, // TODO fix this - 2
+                        y,
                         string,
                         area.width.into(),
                         convert_style(&self.theme.text),
                     );
+                    buf.set_style(
+                        tui::layout::Rect::new(area.x, y, area.width.min(5), 1),
+                        convert_style(self.theme.diagnostic(&msg.level)),
+                    );
                 }
                 None => break,
             }
@@ -71,10 +76,10 @@ impl StatefulWidget for LoggerWidget<'_> {
 
         buf.set_style(line_area, style);
         #[cfg(not(feature = "talloc"))]
-        let line = format!(" Frame time: {:?}", self.render_time,);
+        let mut line = format!(" Frame time: {:?}", self.render_time,);
 
         #[cfg(feature = "talloc")]
-        let line = format!(
+        let mut line = format!(
             " Frame time: {:?} Heap memory usage: {} Heap allocations: {}, Frame allocations: {}",
             self.render_time,
             ferrite_core::byte_size::format_byte_size(
@@ -84,6 +89,10 @@ impl StatefulWidget for LoggerWidget<'_> {
             ferrite_talloc::Talloc::phase_allocations()
         );
 
+        if state.dropped > 0 {
+            line.push_str(&format!(" Dropped messages: {}", state.dropped));
+        }
+
         buf.set_stringn(
             line_area.x,
             line_area.y,
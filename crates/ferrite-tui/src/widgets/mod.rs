@@ -1,11 +1,16 @@
 pub mod background_widget;
+pub mod breadcrumbs;
 pub mod centered_text_widget;
 pub mod chord_widget;
 pub mod completer_widget;
+pub mod completion_widget;
 pub mod editor_widget;
 pub mod file_explorer_widget;
+pub mod git_status_widget;
 pub mod info_line;
+pub mod location_list_widget;
 pub mod logger_widget;
+pub mod notifications_widget;
 pub mod one_line_input_widget;
 pub mod palette_widget;
 pub mod picker_widget;
@@ -0,0 +1,233 @@
+use ferrite_core::{
+    engine::format_elapsed,
+    job_manager::{JobId, JobStatus},
+    notifications::{Notification, Notifications},
+    theme::EditorTheme,
+    tr,
+};
+use ferrite_utility::wrap::wrap_text;
+use tui::{
+    layout::{Margin, Rect},
+    widgets::{Block, Borders, Clear, StatefulWidget, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::glue::convert_style;
+
+/// Max width of a toast including its border
+const TOAST_WIDTH: u16 = 60;
+/// Max lines of a message shown in a toast
+const TOAST_LINES: usize = 4;
+
+/// A cancel button of a running job
+#[derive(Debug, Clone, Copy)]
+pub struct CancelItem {
+    pub area: Rect,
+    pub job: JobId,
+}
+
+pub struct NotificationsWidget<'a> {
+    theme: &'a EditorTheme,
+    has_focus: bool,
+    /// Running jobs listed above the notifications
+    jobs: &'a [JobStatus],
+    /// Receives the areas of the rendered cancel buttons
+    pub cancel_items: Option<&'a mut Vec<CancelItem>>,
+}
+
+impl<'a> NotificationsWidget<'a> {
+    pub fn new(theme: &'a EditorTheme, has_focus: bool, jobs: &'a [JobStatus]) -> Self {
+        Self {
+            theme,
+            has_focus,
+            jobs,
+            cancel_items: None,
+        }
+    }
+}
+
+impl StatefulWidget for NotificationsWidget<'_> {
+    type State = Notifications;
+
+    fn render(
+        self,
+        area: tui::layout::Rect,
+        buf: &mut tui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        if area.area() == 0 {
+            return;
+        }
+
+        Clear.render(area, buf);
+        buf.set_style(area, convert_style(&self.theme.background));
+
+        let list_area = Rect {
+            height: area.height - 1,
+            ..area
+        };
+        let mut y = list_area.y;
+        let cancel = tr!("job.cancel");
+        let mut cancel_items = self.cancel_items;
+        for job in self.jobs {
+            if y >= list_area.bottom() {
+                break;
+            }
+            let mut line = format!(" {:>12} {}", "running", job.summary());
+            if let Some(progress) = job
+                .progress
+                .as_ref()
+                .filter(|progress| !progress.message.is_empty())
+            {
+                line = format!("{line}: {}", progress.message);
+            }
+            buf.set_stringn(
+                list_area.x,
+                y,
+                line,
+                list_area.width.into(),
+                convert_style(&self.theme.text),
+            );
+            buf.set_style(
+                Rect::new(list_area.x + 6, y, 7, 1).intersection(list_area),
+                convert_style(self.theme.diagnostic("hint")),
+            );
+            if job.info.cancellable && !job.is_cancelled() {
+                let width = cancel.width() as u16;
+                let cancel_area =
+                    Rect::new(list_area.right().saturating_sub(width + 1), y, width, 1)
+                        .intersection(list_area);
+                buf.set_stringn(
+                    cancel_area.x,
+                    y,
+                    &cancel,
+                    cancel_area.width.into(),
+                    convert_style(&self.theme.info_line),
+                );
+                if let Some(items) = &mut cancel_items {
+                    items.push(CancelItem {
+                        area: cancel_area,
+                        job: job.id,
+                    });
+                }
+            }
+            y += 1;
+        }
+
+        'outer: for notification in state.notifications.iter().skip(state.scroll) {
+            let level = notification.severity.level();
+            let prefix = format!(
+                " {:>4} {level:>7} ",
+                format_elapsed(notification.time.elapsed().unwrap_or_default())
+            );
+            for (i, line) in notification.message.lines().enumerate() {
+                if y >= list_area.bottom() {
+                    break 'outer;
+                }
+                let line = if i == 0 {
+                    format!("{prefix}{line}")
+                } else {
+                    format!("{:width$}{line}", "", width = prefix.width())
+                };
+                buf.set_stringn(
+                    list_area.x,
+                    y,
+                    line,
+                    list_area.width.into(),
+                    convert_style(&self.theme.text),
+                );
+                if i == 0 {
+                    buf.set_style(
+                        Rect::new(list_area.x + 6, y, 7, 1).intersection(list_area),
+                        convert_style(self.theme.diagnostic(level)),
+                    );
+                }
+                y += 1;
+            }
+        }
+
+        let line_area = Rect {
+            y: area.y + area.height - 1,
+            height: 1,
+            ..area
+        };
+        let style = convert_style(if self.has_focus {
+            &self.theme.info_line
+        } else {
+            &self.theme.info_line_unfocused
+        });
+        buf.set_style(line_area, style);
+        buf.set_stringn(
+            line_area.x,
+            line_area.y,
+            format!(" Notifications: {}", state.notifications.len()),
+            line_area.width.into(),
+            style,
+        );
+    }
+}
+
+/// The newest notification shown in the top right corner of `area`
+pub struct ToastWidget<'a> {
+    theme: &'a EditorTheme,
+    notification: &'a Notification,
+}
+
+impl<'a> ToastWidget<'a> {
+    pub fn new(theme: &'a EditorTheme, notification: &'a Notification) -> Self {
+        Self {
+            theme,
+            notification,
+        }
+    }
+}
+
+impl Widget for ToastWidget<'_> {
+    fn render(self, area: Rect, buf: &mut tui::buffer::Buffer) {
+        let max_width = TOAST_WIDTH.min(area.width / 2);
+        if max_width < 4 {
+            return;
+        }
+        let mut lines = wrap_text(&self.notification.message, max_width as usize - 4);
+        let hidden = lines.len().saturating_sub(TOAST_LINES);
+        lines.truncate(TOAST_LINES);
+        let more = (hidden > 0).then(|| format!("+{hidden} more"));
+        let width = lines
+            .iter()
+            .map(|line| line.width())
+            .chain(more.iter().map(|more| more.width()))
+            .max()
+            .unwrap_or(0) as u16
+            + 4;
+        let height = lines.len() as u16 + more.is_some() as u16 + 2;
+        let toast_area = Rect {
+            x: area.right().saturating_sub(width + 1),
+            y: area.y + 1,
+            width,
+            height,
+        }
+        .intersection(area);
+
+        Clear.render(toast_area, buf);
+        Block::default()
+            .borders(Borders::all())
+            .border_style(convert_style(
+                self.theme.diagnostic(self.notification.severity.level()),
+            ))
+            .style(convert_style(&self.theme.background))
+            .render(toast_area, buf);
+        let inner_area = toast_area.inner(Margin::new(2, 1));
+        for (i, line) in lines.iter().copied().chain(more.as_deref()).enumerate() {
+            if i >= inner_area.height.into() {
+                break;
+            }
+            buf.set_stringn(
+                inner_area.x,
+                inner_area.y + i as u16,
+                line,
+                inner_area.width.into(),
+                convert_style(&self.theme.text),
+            );
+        }
+    }
+}
@@ -1,20 +1,14 @@
-use ferrite_core::{
-    buffer::Buffer,
-    config::editor::{CursorType, Editor},
-    theme::EditorTheme,
-};
-use tui::{
-    layout::Rect,
-    style::{self, Style},
-    widgets::StatefulWidget,
-};
+use ferrite_core::{buffer::Buffer, config::editor::Editor, theme::EditorTheme};
+use ferrite_utility::graphemes::RopeGraphemes;
+use tui::{layout::Rect, widgets::StatefulWidget};
 
-use crate::glue::convert_style;
+use crate::glue::{convert_style, cursor_style};
 
 pub struct OneLineInputWidget<'a> {
     theme: &'a EditorTheme,
     config: &'a Editor,
     focused: bool,
+    masked: bool,
 }
 
 impl<'a> OneLineInputWidget<'a> {
@@ -23,8 +17,15 @@ impl<'a> OneLineInputWidget<'a> {
             theme,
             config,
             focused,
+            masked: false,
         }
     }
+
+    /// Renders every character of the input as `*`
+    pub fn masked(mut self, masked: bool) -> Self {
+        self.masked = masked;
+        self
+    }
 }
 
 impl StatefulWidget for OneLineInputWidget<'_> {
@@ -45,10 +46,15 @@ impl StatefulWidget for OneLineInputWidget<'_> {
             area.width.into(),
             convert_style(&self.theme.text),
         );
+        let text = if self.masked {
+            "*".repeat(RopeGraphemes::new(view.lines[0].text).count())
+        } else {
+            view.lines[0].text.to_string()
+        };
         buf.set_stringn(
             area.x,
             area.y,
-            view.lines[0].text.to_string(),
+            text,
             area.width.into(),
             convert_style(&self.theme.text),
         );
@@ -74,20 +80,10 @@ impl StatefulWidget for OneLineInputWidget<'_> {
         };
 
         if cursor_area.intersects(area) && self.focused {
-            match self.config.gui.cursor_type {
-                CursorType::Block => {
-                    buf.set_style(
-                        cursor_area,
-                        convert_style(&self.theme.text).add_modifier(style::Modifier::REVERSED),
-                    );
-                }
-                CursorType::Line => {
-                    buf.set_style(
-                        cursor_area,
-                        Style::default().add_modifier(style::Modifier::SLOW_BLINK),
-                    );
-                }
-            }
+            buf.set_style(
+                cursor_area,
+                cursor_style(self.theme, self.config.gui.cursor_type, true),
+            );
         }
     }
 }
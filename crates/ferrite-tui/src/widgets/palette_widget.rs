@@ -1,13 +1,18 @@
 use ferrite_core::{
+    buffer::Buffer,
     config::editor::Editor,
+    layout::overlay::overlay_area,
     palette::{CommandPalette, PaletteState},
     theme::EditorTheme,
 };
-use tui::{layout::Rect, widgets::StatefulWidget};
+use tui::{
+    layout::{Margin, Rect},
+    widgets::{Block, Borders, Clear, StatefulWidget, Widget},
+};
 use unicode_width::UnicodeWidthStr;
 
 use super::{completer_widget::CompleterWidget, one_line_input_widget::OneLineInputWidget};
-use crate::glue::convert_style;
+use crate::glue::{convert_border, convert_style, ferrite_to_tui_rect, tui_to_ferrite_rect};
 
 pub struct CmdPaletteWidget<'a> {
     theme: &'a EditorTheme,
@@ -32,6 +37,36 @@ impl<'a> CmdPaletteWidget<'a> {
     }
 }
 
+impl CmdPaletteWidget<'_> {
+    fn render_input(
+        &self,
+        area: Rect,
+        buf: &mut tui::buffer::Buffer,
+        prompt: &str,
+        buffer: &mut Buffer,
+        masked: bool,
+    ) {
+        let prompt_width = prompt.width() as u16 + 1;
+        buf.set_stringn(
+            area.x,
+            area.y,
+            format!(" {}", prompt),
+            area.width.into(),
+            convert_style(&self.theme.text),
+        );
+        let input_area = Rect {
+            x: area.x + prompt_width,
+            y: area.y,
+            width: area.width.saturating_sub(prompt_width),
+            height: 1,
+        };
+
+        OneLineInputWidget::new(self.theme, self.config, self.focused)
+            .masked(masked)
+            .render(input_area, buf, buffer);
+    }
+}
+
 impl StatefulWidget for CmdPaletteWidget<'_> {
     type State = CommandPalette;
 
@@ -47,27 +82,50 @@ impl StatefulWidget for CmdPaletteWidget<'_> {
                 prompt,
                 completer,
                 mode,
+                masked,
                 ..
             } => {
-                let prompt_width = prompt.width() as u16 + 1;
-                buf.set_stringn(
-                    area.x,
-                    area.y,
-                    format!(" {}", prompt),
-                    area.width.into(),
-                    convert_style(&self.theme.text),
-                );
-                let input_area = Rect {
-                    x: area.x + prompt_width,
-                    y: area.y,
-                    width: area.width.saturating_sub(prompt_width),
-                    height: 1,
-                };
+                let show_completer = self.focused && (mode == "command" || mode == "shell");
+                let layout = &self.config.overlay.palette;
+                if layout.floating && self.focused {
+                    let total_area = tui_to_ferrite_rect(self.total_area);
+                    let width = overlay_area(layout, total_area, None).width as u16;
+                    let completer_height = if show_completer {
+                        CompleterWidget::height(completer, width.saturating_sub(2))
+                    } else {
+                        0
+                    };
+                    let area = ferrite_to_tui_rect(overlay_area(
+                        layout,
+                        total_area,
+                        Some(completer_height as usize + 3),
+                    ));
+                    Clear.render(area, buf);
+                    Block::default()
+                        .borders(Borders::all())
+                        .border_style(convert_style(&self.theme.border))
+                        .border_type(convert_border(layout.border))
+                        .style(convert_style(&self.theme.background))
+                        .render(area, buf);
+                    let inner_area = area.inner(Margin::new(1, 1));
+                    if inner_area.height == 0 {
+                        return;
+                    }
+                    self.render_input(inner_area, buf, prompt, buffer, *masked);
+                    if show_completer {
+                        let completer_area = Rect {
+                            y: inner_area.y + 1,
+                            height: inner_area.height - 1,
+                            ..inner_area
+                        };
+                        CompleterWidget::new(self.theme).render(completer_area, buf, completer);
+                    }
+                    return;
+                }
 
-                OneLineInputWidget::new(self.theme, self.config, self.focused)
-                    .render(input_area, buf, buffer);
+                self.render_input(area, buf, prompt, buffer, *masked);
 
-                if self.focused && (mode == "command" || mode == "shell") {
+                if show_completer {
                     let completer_area = {
                         let mut completer_area = self.total_area;
                         completer_area.height = completer_area.height.saturating_sub(1);
@@ -77,44 +135,15 @@ impl StatefulWidget for CmdPaletteWidget<'_> {
                     CompleterWidget::new(self.theme).render(completer_area, buf, completer);
                 }
             }
-            PaletteState::Message(msg) => {
-                for (i, line) in msg.lines().enumerate() {
-                    if i >= area.height.into() {
-                        break;
-                    }
-                    buf.set_stringn(
-                        area.x + 1,
-                        area.y + i as u16,
-                        line,
-                        (area.width as usize).saturating_sub(1),
-                        convert_style(&self.theme.text),
-                    );
-                }
-            }
-            PaletteState::Error(msg) => {
-                for (i, line) in msg.lines().enumerate() {
-                    if i >= area.height.into() {
-                        break;
-                    }
-                    buf.set_stringn(
-                        area.x + 1,
-                        area.y + i as u16,
-                        line,
-                        (area.width as usize).saturating_sub(1),
-                        convert_style(&self.theme.error_text),
-                    );
-                }
-            }
             PaletteState::Nothing => (),
-            PaletteState::Prompt {
-                selected,
-                prompt,
-                alt1_char,
-                alt2_char,
-                ..
-            } => {
-                let msg = CommandPalette::get_prompt(*selected, prompt, *alt1_char, *alt2_char);
-                for (i, line) in msg.lines().enumerate() {
+            PaletteState::Message(_) | PaletteState::Error(_) | PaletteState::Prompt { .. } => {
+                let style = if matches!(state.state(), PaletteState::Error(_)) {
+                    &self.theme.error_text
+                } else {
+                    &self.theme.text
+                };
+                let width = (area.width as usize).saturating_sub(1);
+                for (i, line) in state.message_lines(width).iter().enumerate() {
                     if i >= area.height.into() {
                         break;
                     }
@@ -122,8 +151,8 @@ impl StatefulWidget for CmdPaletteWidget<'_> {
                         area.x + 1,
                         area.y + i as u16,
                         line,
-                        (area.width as usize).saturating_sub(1),
-                        convert_style(&self.theme.text),
+                        width,
+                        convert_style(style),
                     );
                 }
             }
@@ -18,7 +18,7 @@ use super::{
     centered_text_widget::CenteredTextWidget, editor_widget::EditorWidget,
     one_line_input_widget::OneLineInputWidget,
 };
-use crate::glue::convert_style;
+use crate::glue::{convert_border, convert_style};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TextAlign {
@@ -69,7 +69,7 @@ where
             .title(self.title)
             .borders(Borders::all())
             .border_style(convert_style(&self.theme.border))
-            .border_type(BorderType::Plain)
+            .border_type(convert_border(self.config.overlay.picker.border))
             .style(convert_style(&self.theme.background));
         main_block.render(area, buf);
         let inner_area = area.inner(Margin {
@@ -252,7 +252,7 @@ where
                             width: result_area.width,
                             height: 1,
                         },
-                        convert_style(&self.theme.selection),
+                        convert_style(&self.theme.picker_selection),
                     );
                 }
             }
@@ -277,6 +277,7 @@ where
                         EditorWidget::new(self.theme, self.config, view_id, false, None, None);
                     preview.line_nr = false;
                     preview.info_line = false;
+                    preview.breadcrumbs = false;
                     preview.render(preview_area, buf, buffer);
                 }
                 Some(Preview::SharedBuffer(buffer)) => {
@@ -286,6 +287,7 @@ where
                         EditorWidget::new(self.theme, self.config, view_id, false, None, None);
                     preview.line_nr = false;
                     preview.info_line = false;
+                    preview.breadcrumbs = false;
                     preview.render(preview_area, buf, &mut *guard);
                 }
                 Some(Preview::TooLarge) => {
@@ -300,6 +302,10 @@ where
                     let text = CenteredTextWidget::new(self.theme, "Error loading preview");
                     text.render(preview_area, buf);
                 }
+                Some(Preview::Loading) => {
+                    let text = CenteredTextWidget::new(self.theme, "Loading...");
+                    text.render(preview_area, buf);
+                }
                 _ => (),
             }
         }
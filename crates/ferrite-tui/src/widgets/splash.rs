@@ -1,4 +1,4 @@
-use ferrite_core::theme::EditorTheme;
+use ferrite_core::{theme::EditorTheme, tr};
 use tui::widgets::Widget;
 use unicode_width::UnicodeWidthStr;
 
@@ -14,32 +14,57 @@ impl<'a> SplashWidget<'a> {
     }
 }
 
-const SPLASH: &str = r"
-╭────────────────────────────────────╮
-│     ______               _ __      │
-│    / ____/__  __________(_) /____  │
-│   / /_  / _ \/ ___/ ___/ / __/ _ \ │
-│  / __/ /  __/ /  / /  / / /_/  __/ │
-│ /_/    \___/_/  /_/  /_/\__/\___/  │
-│                                    │
-│      Command palette CTRL + P      │
-│       Browse files CTRL + O        │
-│           Quit CTRL + Q            │
-╰────────────────────────────────────╯
-";
+const LOGO: &[&str] = &[
+    "     ______               _ __      ",
+    "    / ____/__  __________(_) /____  ",
+    "   / /_  / _ \\/ ___/ ___/ / __/ _ \\ ",
+    "  / __/ /  __/ /  / /  / / /_/  __/ ",
+    " /_/    \\___/_/  /_/  /_/\\__/\\___/  ",
+    "",
+];
+
+/// Builds the splash box with the hints translated to the current locale
+fn splash_lines() -> Vec<String> {
+    let hints = [
+        format!("{} CTRL + P", tr!("splash.command_palette")),
+        format!("{} CTRL + O", tr!("splash.browse_files")),
+        format!("{} > recent-files", tr!("splash.recent_files")),
+        format!("{} > recent-projects", tr!("splash.recent_projects")),
+        format!("{} CTRL + Q", tr!("splash.quit")),
+    ];
+    let inner_width = LOGO
+        .iter()
+        .map(|line| line.width())
+        .chain(hints.iter().map(|hint| hint.width() + 2))
+        .max()
+        .unwrap_or_default();
+
+    let center = |line: &str| {
+        let padding = inner_width - line.width();
+        let left = padding.div_ceil(2);
+        format!("│{}{line}{}│", " ".repeat(left), " ".repeat(padding - left))
+    };
+
+    let mut lines = vec![format!("╭{}╮", "─".repeat(inner_width))];
+    lines.extend(LOGO.iter().map(|line| center(line)));
+    lines.extend(hints.iter().map(|hint| center(hint)));
+    lines.push(format!("╰{}╯", "─".repeat(inner_width)));
+    lines
+}
 
 impl Widget for SplashWidget<'_> {
     fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
-        let lines = SPLASH.lines().count();
-        let width = SPLASH
-            .lines()
+        let splash = splash_lines();
+        let lines = splash.len();
+        let width = splash
+            .iter()
             .map(|line| line.width())
             .max()
             .unwrap_or_default();
         let left = (area.width as usize).saturating_sub(width) / 2;
         let top = (area.height as usize).saturating_sub(lines) / 2;
         if area.width as usize >= width {
-            for (i, line) in SPLASH.lines().enumerate() {
+            for (i, line) in splash.iter().enumerate() {
                 buf.set_string(
                     area.left() + left as u16,
                     area.top() + top as u16 + i as u16,
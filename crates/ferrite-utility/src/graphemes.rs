@@ -27,6 +27,22 @@ pub fn tab_width_at(visual_x: usize, tab_width: u16) -> usize {
     tab_width as usize - (visual_x % tab_width as usize)
 }
 
+/// Returns true if `ch` is part of a word, the chars in between words stop word motions
+pub fn is_word_char(ch: char) -> bool {
+    use unicode_general_category::GeneralCategory;
+    matches!(
+        unicode_general_category::get_general_category(ch),
+        GeneralCategory::ConnectorPunctuation
+            | GeneralCategory::UppercaseLetter
+            | GeneralCategory::TitlecaseLetter
+            | GeneralCategory::LowercaseLetter
+            | GeneralCategory::NonspacingMark
+            | GeneralCategory::DecimalNumber
+            | GeneralCategory::LetterNumber
+            | GeneralCategory::OtherLetter
+    )
+}
+
 #[must_use]
 pub fn grapheme_width(g: &str, current_col: usize) -> usize {
     if g.as_bytes()[0] <= 127 {
@@ -604,20 +620,7 @@ impl RopeGraphemeExt for RopeSlice<'_> {
     }
 
     fn is_word_char(&self) -> bool {
-        self.chars().all(|ch| {
-            use unicode_general_category::GeneralCategory;
-            matches!(
-                unicode_general_category::get_general_category(ch),
-                GeneralCategory::ConnectorPunctuation
-                    | GeneralCategory::UppercaseLetter
-                    | GeneralCategory::TitlecaseLetter
-                    | GeneralCategory::LowercaseLetter
-                    | GeneralCategory::NonspacingMark
-                    | GeneralCategory::DecimalNumber
-                    | GeneralCategory::LetterNumber
-                    | GeneralCategory::OtherLetter
-            )
-        })
+        self.chars().all(is_word_char)
     }
 
     fn end_of_line_byte(&self, line_idx: usize) -> usize {
@@ -4,3 +4,4 @@ pub mod line_ending;
 pub mod point;
 pub mod trim;
 pub mod vec1;
+pub mod wrap;
@@ -0,0 +1,43 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::graphemes::grapheme_width;
+
+fn str_width(text: &str) -> usize {
+    let mut width = 0;
+    for grapheme in text.graphemes(true) {
+        width += grapheme_width(grapheme, width);
+    }
+    width
+}
+
+/// Splits `text` into lines no wider than `width` columns, breaking after whitespace when possible.
+/// Words wider than `width` are broken between graphemes.
+pub fn wrap_text(text: &str, width: usize) -> Vec<&str> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let mut start = 0;
+        let mut col = 0;
+        // Byte after the last whitespace of the current line where it can be broken
+        let mut last_break = None;
+        for (idx, grapheme) in line.grapheme_indices(true) {
+            let columns = grapheme_width(grapheme, col);
+            while col + columns > width && idx > start {
+                let end = match last_break {
+                    Some(end) if end > start => end,
+                    _ => idx,
+                };
+                lines.push(line[start..end].trim_end());
+                start = end;
+                col = str_width(&line[start..idx]);
+                last_break = None;
+            }
+            col += columns;
+            if grapheme.chars().all(char::is_whitespace) {
+                last_break = Some(idx + grapheme.len());
+            }
+        }
+        lines.push(line[start..].trim_end());
+    }
+    lines
+}
@@ -0,0 +1,13 @@
+[
+  (compound_statement)
+  (field_declaration_list)
+  (enumerator_list)
+  (initializer_list)
+  (argument_list)
+  (parameter_list)
+] @indent
+
+[
+  "}"
+  ")"
+] @outdent
@@ -0,0 +1,11 @@
+(function_definition
+  declarator: (function_declarator
+    declarator: (identifier) @name)) @item.function
+
+(struct_specifier
+  name: (type_identifier) @name
+  body: (_)) @item.struct
+
+(enum_specifier
+  name: (type_identifier) @name
+  body: (_)) @item.enum
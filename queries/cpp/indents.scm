@@ -0,0 +1,17 @@
+[
+  (compound_statement)
+  (field_declaration_list)
+  (declaration_list)
+  (enumerator_list)
+  (initializer_list)
+  (argument_list)
+  (parameter_list)
+  (template_argument_list)
+  (template_parameter_list)
+] @indent
+
+[
+  "}"
+  ")"
+  ">"
+] @outdent
@@ -0,0 +1,9 @@
+[
+  (block)
+  (arguments)
+] @indent
+
+[
+  "}"
+  ")"
+] @outdent
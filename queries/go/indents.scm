@@ -0,0 +1,20 @@
+[
+  (block)
+  (field_declaration_list)
+  (interface_type)
+  (literal_value)
+  (argument_list)
+  (parameter_list)
+  (import_spec_list)
+  (const_declaration)
+  (var_declaration)
+  (type_declaration)
+  (expression_switch_statement)
+  (type_switch_statement)
+  (select_statement)
+] @indent
+
+[
+  "}"
+  ")"
+] @outdent
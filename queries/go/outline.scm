@@ -0,0 +1,8 @@
+(function_declaration
+  name: (identifier) @name) @item.function
+
+(method_declaration
+  name: (field_identifier) @name) @item.method
+
+(type_spec
+  name: (type_identifier) @name) @item.type
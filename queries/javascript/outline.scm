@@ -0,0 +1,8 @@
+(class_declaration
+  name: (identifier) @name) @item.class
+
+(function_declaration
+  name: (identifier) @name) @item.function
+
+(method_definition
+  name: (property_identifier) @name) @item.method
@@ -0,0 +1,19 @@
+[
+  (function_declaration)
+  (function_definition)
+  (if_statement)
+  (for_statement)
+  (while_statement)
+  (repeat_statement)
+  (do_statement)
+  (table_constructor)
+  (arguments)
+  (parameters)
+] @indent
+
+[
+  "end"
+  "until"
+  "}"
+  ")"
+] @outdent
@@ -0,0 +1,26 @@
+; From nvim-treesitter/nvim-treesitter
+
+(code_span) @markup.raw.inline
+
+[
+  (emphasis_delimiter)
+  (code_span_delimiter)
+] @punctuation.bracket
+
+(emphasis) @markup.italic
+
+(strong_emphasis) @markup.bold
+
+[
+  (link_destination)
+  (uri_autolink)
+] @markup.link.url
+
+[
+  (link_text)
+  (image_description)
+] @markup.link.text
+
+(link_label) @markup.link.label
+
+(backslash_escape) @string.escape
@@ -0,0 +1,30 @@
+[
+  (function_definition)
+  (class_definition)
+  (if_statement)
+  (elif_clause)
+  (else_clause)
+  (for_statement)
+  (while_statement)
+  (with_statement)
+  (try_statement)
+  (except_clause)
+  (finally_clause)
+  (match_statement)
+  (case_clause)
+  (dictionary)
+  (list)
+  (set)
+  (tuple)
+  (argument_list)
+  (parameters)
+  (parenthesized_expression)
+  (list_comprehension)
+  (dictionary_comprehension)
+] @indent
+
+[
+  "}"
+  "]"
+  ")"
+] @outdent
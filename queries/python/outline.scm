@@ -0,0 +1,5 @@
+(class_definition
+  name: (identifier) @name) @item.class
+
+(function_definition
+  name: (identifier) @name) @item.function
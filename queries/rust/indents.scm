@@ -0,0 +1,29 @@
+[
+  (block)
+  (declaration_list)
+  (field_declaration_list)
+  (ordered_field_declaration_list)
+  (enum_variant_list)
+  (field_initializer_list)
+  (match_block)
+  (use_list)
+  (arguments)
+  (parameters)
+  (closure_parameters)
+  (type_arguments)
+  (type_parameters)
+  (array_expression)
+  (tuple_expression)
+  (tuple_type)
+  (tuple_pattern)
+  (struct_pattern)
+  (token_tree)
+  (where_clause)
+] @indent
+
+[
+  "}"
+  "]"
+  ")"
+  ">"
+] @outdent
@@ -0,0 +1,26 @@
+(mod_item
+  name: (identifier) @name) @item.module
+
+(impl_item
+  type: (_) @name) @item.impl
+
+(trait_item
+  name: (type_identifier) @name) @item.trait
+
+(struct_item
+  name: (type_identifier) @name) @item.struct
+
+(enum_item
+  name: (type_identifier) @name) @item.enum
+
+(union_item
+  name: (type_identifier) @name) @item.union
+
+(function_item
+  name: (identifier) @name) @item.function
+
+(function_signature_item
+  name: (identifier) @name) @item.function
+
+(macro_definition
+  name: (identifier) @name) @item.macro
@@ -0,0 +1,25 @@
+[
+  (statement_block)
+  (class_body)
+  (interface_body)
+  (enum_body)
+  (object)
+  (object_type)
+  (object_pattern)
+  (array)
+  (array_pattern)
+  (arguments)
+  (formal_parameters)
+  (type_arguments)
+  (type_parameters)
+  (switch_body)
+  (template_string)
+  (parenthesized_expression)
+] @indent
+
+[
+  "}"
+  "]"
+  ")"
+  ">"
+] @outdent
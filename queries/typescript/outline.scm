@@ -0,0 +1,11 @@
+(class_declaration
+  name: (type_identifier) @name) @item.class
+
+(interface_declaration
+  name: (type_identifier) @name) @item.interface
+
+(function_declaration
+  name: (identifier) @name) @item.function
+
+(method_definition
+  name: (property_identifier) @name) @item.method
@@ -1,14 +1,18 @@
 use std::{
     fs::{self, OpenOptions},
+    path::PathBuf,
     process::ExitCode,
     sync::{mpsc, Mutex},
 };
 
 use anyhow::Result;
-use ferrite_cli::Ui;
+use ferrite_cli::{Command, Ui};
 use ferrite_core::{
+    batch,
     config::{editor::Editor, languages::Languages},
-    logger::{LogMessage, LoggerSink},
+    ipc,
+    logger::{self, LogMessage, LoggerSink},
+    replay,
 };
 use tracing::Level;
 use tracing_subscriber::{filter, fmt, layer::Layer, prelude::*, Registry};
@@ -57,6 +61,51 @@ fn run_gui(args: &ferrite_cli::Args, rx: mpsc::Receiver<LogMessage>) -> Result<(
     Ok(())
 }
 
+/// Sends the files to the running instance, returns false if there is none
+fn open_remote(args: &ferrite_cli::Args) -> Result<bool> {
+    let cwd = std::env::current_dir()?;
+    let requests: Vec<_> = args
+        .file_args()
+        .into_iter()
+        .map(|file_arg| ipc::Request::Open {
+            path: cwd.join(file_arg.path),
+            line: file_arg.line,
+            column: file_arg.column,
+            read_only: args.read_only,
+            wait: args.wait,
+        })
+        .collect();
+    ipc::send(&requests)
+}
+
+fn run_exec(
+    args: &ferrite_cli::Args,
+    files: &[PathBuf],
+    commands: &[String],
+    rx: mpsc::Receiver<LogMessage>,
+) -> Result<ExitCode> {
+    let mut failed = false;
+    for outcome in batch::run(args, files, commands, rx)? {
+        let source = match outcome.command {
+            Some(command) => format!("{}: {command}", outcome.path.display()),
+            None => outcome.path.display().to_string(),
+        };
+        match outcome.result {
+            Ok(Some(msg)) => println!("{source}: {msg}"),
+            Ok(None) => (),
+            Err(err) => {
+                eprintln!("{source}: {err}");
+                failed = true;
+            }
+        }
+    }
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
 fn main() -> Result<ExitCode> {
     let Some(dirs) = directories::ProjectDirs::from("", "", "ferrite") else {
         eprintln!("Unable to get project directory");
@@ -102,6 +151,10 @@ fn main() -> Result<ExitCode> {
         }
     }
 
+    if args.remote && open_remote(&args)? {
+        return Ok(ExitCode::SUCCESS);
+    }
+
     let _puffin_server = if args.profile {
         let server_addr = format!("127.0.0.1:{}", puffin_http::DEFAULT_PORT);
         let puffin_server = puffin_http::Server::new(&server_addr).unwrap();
@@ -141,7 +194,7 @@ fn main() -> Result<ExitCode> {
         _ => Level::INFO,
     };
 
-    let (tx, rx) = mpsc::channel();
+    let (tx, rx) = logger::channel();
     let logger = LoggerSink::new(tx);
 
     let subscriber = Registry::default()
@@ -165,6 +218,28 @@ fn main() -> Result<ExitCode> {
 
     ferrite_core::clipboard::init(args.local_clipboard);
 
+    if let Some(Command::Exec {
+        commands,
+        script,
+        files,
+    }) = &args.command
+    {
+        let mut commands = commands.clone();
+        if let Some(script) = script {
+            commands.extend(batch::parse_script(&fs::read_to_string(script)?));
+        }
+        return run_exec(&args, files, &commands, rx);
+    }
+
+    if let (Some(recording), true) = (&args.replay, args.headless) {
+        let count = replay::run_headless(&args, recording, rx)?;
+        eprintln!(
+            "Replayed {count} inputs from `{}`",
+            recording.to_string_lossy()
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
     #[cfg(not(any(feature = "tui", feature = "gui")))]
     compile_error!("You must enable either tui or gui");
 
@@ -193,13 +268,13 @@ fn main() -> Result<ExitCode> {
         }
         _ => {
             #[cfg(feature = "gui")]
-            if std::env::var("WAYLAND_DISPLAY").is_ok() {
+            if std::env::var("WAYLAND_DISPLAY").is_ok() && !args.pipe {
                 run_gui(&args, rx)?;
                 return Ok(ExitCode::SUCCESS);
             }
 
             #[cfg(feature = "tui")]
-            if std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+            if args.pipe || std::io::IsTerminal::is_terminal(&std::io::stdout()) {
                 ferrite_term::run(&args, rx)?;
                 return Ok(ExitCode::SUCCESS);
             } else {